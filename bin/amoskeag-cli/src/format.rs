@@ -33,10 +33,14 @@ fn format_value_with_depth(value: &Value, depth: usize) -> String {
                 n.to_string()
             }
         }
-        Value::String(s) => s.clone(),
+        Value::Int(n) => n.to_string(),
+        Value::Decimal(d) => d.to_string(),
+        Value::String(s) => s.to_string(),
         Value::Boolean(b) => b.to_string(),
         Value::Nil => "nil".to_string(),
         Value::Symbol(s) => format!(":{}", s),
+        Value::Duration(d) => d.to_string(),
+        Value::Function(f) => f.to_string(),
         Value::Array(arr) => {
             if arr.is_empty() {
                 "[]".to_string()
@@ -91,10 +95,10 @@ mod tests {
 
     #[test]
     fn test_format_string() {
-        assert_eq!(format_value(&Value::String("hello".to_string())), "hello");
-        assert_eq!(format_value(&Value::String("".to_string())), "");
+        assert_eq!(format_value(&Value::String("hello".into())), "hello");
+        assert_eq!(format_value(&Value::String("".into())), "");
         assert_eq!(
-            format_value(&Value::String("with spaces".to_string())),
+            format_value(&Value::String("with spaces".into())),
             "with spaces"
         );
     }
@@ -121,48 +125,51 @@ mod tests {
 
     #[test]
     fn test_format_empty_array() {
-        assert_eq!(format_value(&Value::Array(vec![])), "[]");
+        assert_eq!(format_value(&Value::Array(vec![].into())), "[]");
     }
 
     #[test]
     fn test_format_array() {
-        let arr = Value::Array(vec![
-            Value::Number(1.0),
-            Value::Number(2.0),
-            Value::Number(3.0),
-        ]);
+        let arr =
+            Value::Array(vec![Value::Number(1.0), Value::Number(2.0), Value::Number(3.0)].into());
         assert_eq!(format_value(&arr), "[1, 2, 3]");
     }
 
     #[test]
     fn test_format_mixed_array() {
-        let arr = Value::Array(vec![
-            Value::Number(42.0),
-            Value::String("hello".to_string()),
-            Value::Boolean(true),
-        ]);
+        let arr = Value::Array(
+            vec![
+                Value::Number(42.0),
+                Value::String("hello".into()),
+                Value::Boolean(true),
+            ]
+            .into(),
+        );
         assert_eq!(format_value(&arr), "[42, hello, true]");
     }
 
     #[test]
     fn test_format_empty_dictionary() {
-        assert_eq!(format_value(&Value::Dictionary(HashMap::new())), "{}");
+        assert_eq!(
+            format_value(&Value::Dictionary(HashMap::new().into())),
+            "{}"
+        );
     }
 
     #[test]
     fn test_format_dictionary() {
         let mut map = HashMap::new();
-        map.insert("name".to_string(), Value::String("alice".to_string()));
-        let result = format_value(&Value::Dictionary(map));
+        map.insert("name".to_string(), Value::String("alice".into()));
+        let result = format_value(&Value::Dictionary(map.into()));
         assert!(result.contains("name: alice"));
     }
 
     #[test]
     fn test_format_nested_structures() {
-        let inner = Value::Array(vec![Value::Number(1.0), Value::Number(2.0)]);
+        let inner = Value::Array(vec![Value::Number(1.0), Value::Number(2.0)].into());
         let mut map = HashMap::new();
         map.insert("items".to_string(), inner);
-        let outer = Value::Dictionary(map);
+        let outer = Value::Dictionary(map.into());
         let result = format_value(&outer);
         assert!(result.contains("items: [1, 2]"));
     }
@@ -121,7 +121,7 @@ fn handle_set_command(rest: &str, data: &mut HashMap<String, Value>) {
     }
 
     match serde_json::from_str::<serde_json::Value>(value_str) {
-        Ok(json) => match crate::json::json_to_value(&json) {
+        Ok(json) => match amoskeag::json::from_json(&json) {
             Ok(value) => {
                 data.insert(key.to_string(), value);
                 println!("Set {} = {}", key, value_str);
@@ -156,14 +156,14 @@ mod tests {
     fn test_handle_set_command_valid() {
         let mut data = HashMap::new();
         handle_set_command("x 42", &mut data);
-        assert!(matches!(data.get("x"), Some(Value::Number(n)) if *n == 42.0));
+        assert!(matches!(data.get("x"), Some(Value::Int(42))));
     }
 
     #[test]
     fn test_handle_set_command_string() {
         let mut data = HashMap::new();
         handle_set_command("name \"alice\"", &mut data);
-        assert!(matches!(data.get("name"), Some(Value::String(s)) if s == "alice"));
+        assert!(matches!(data.get("name"), Some(Value::String(s)) if &**s == "alice"));
     }
 
     #[test]
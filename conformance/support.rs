@@ -0,0 +1,113 @@
+//! Loads the conformance cases in `cases.json`, shared by the interpreter's
+//! tests and the tests of each transpiler's runtime
+//!
+//! Included with `#[path]` by each crate's integration tests, which are all
+//! two directories below the repository root.
+
+#![allow(dead_code)]
+
+use serde_json::Value;
+
+/// One expression and the result every backend must give for it
+pub struct Case {
+    pub expression: String,
+    pub expected: Value,
+    /// Backends whose documented limitations exclude this case
+    pub skip: Vec<String>,
+}
+
+impl Case {
+    pub fn runs_on(&self, backend: &str) -> bool {
+        !self.skip.iter().any(|skipped| skipped == backend)
+    }
+}
+
+pub struct Conformance {
+    /// The symbols the expressions may use
+    pub symbols: Vec<String>,
+    /// The data every expression is evaluated against
+    pub data: Value,
+    pub cases: Vec<Case>,
+}
+
+pub fn load() -> Conformance {
+    let path = concat!(env!("CARGO_MANIFEST_DIR"), "/../../conformance/cases.json");
+    let text = std::fs::read_to_string(path).unwrap_or_else(|e| panic!("{}: {}", path, e));
+    let fixture: Value = serde_json::from_str(&text).unwrap();
+    let strings = |value: Option<&Value>| -> Vec<String> {
+        value
+            .and_then(Value::as_array)
+            .map(|items| {
+                items
+                    .iter()
+                    .map(|item| item.as_str().unwrap().to_string())
+                    .collect()
+            })
+            .unwrap_or_default()
+    };
+    Conformance {
+        symbols: strings(fixture.get("symbols")),
+        data: fixture["data"].clone(),
+        cases: fixture["cases"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|case| Case {
+                expression: case["expression"].as_str().unwrap().to_string(),
+                expected: case["expected"].clone(),
+                skip: strings(case.get("skip")),
+            })
+            .collect(),
+    }
+}
+
+/// Whether a backend's cases can run, given whether its tools were found
+///
+/// CI sets `AMOSKEAG_CONFORMANCE_REQUIRED`, so that missing tools fail the
+/// test there instead of skipping it.
+pub fn tools_found(found: bool, tools: &str) -> bool {
+    if !found {
+        assert!(
+            std::env::var_os("AMOSKEAG_CONFORMANCE_REQUIRED").is_none(),
+            "{} not found on the path",
+            tools
+        );
+        eprintln!("skipping: {} not found on the path", tools);
+    }
+    found
+}
+
+/// Fail with every case whose result differs from the expected one
+///
+/// Results are JSON in the fixture's form: integers without and floats with
+/// a decimal point, `{"$symbol": name}`, `{"$duration": iso}`,
+/// `{"$decimal": digits}`, and `{"$error": message}` for a failure.
+pub fn check(backend: &str, cases: &[&Case], results: &[Value]) {
+    assert_eq!(
+        results.len(),
+        cases.len(),
+        "{} gave {} results for {} cases",
+        backend,
+        results.len(),
+        cases.len()
+    );
+    let mismatches: Vec<String> = cases
+        .iter()
+        .zip(results)
+        .filter(|(case, actual)| case.expected != **actual)
+        .map(|(case, actual)| {
+            format!(
+                "{}: expected {}, {} gave {}",
+                case.expression, case.expected, backend, actual
+            )
+        })
+        .collect();
+    assert!(
+        mismatches.is_empty(),
+        "{} of {} cases differ on {}:\n{}",
+        mismatches.len(),
+        cases.len(),
+        backend,
+        mismatches.join("\n")
+    );
+}
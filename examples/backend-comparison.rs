@@ -76,9 +76,13 @@ fn main() {
 fn format_result(result: &Value) -> String {
     match result {
         Value::Number(n) => format!("{}", n),
+        Value::Int(n) => format!("{}", n),
+        Value::Decimal(d) => format!("{}", d),
         Value::String(s) => format!("\"{}\"", s),
         Value::Boolean(b) => format!("{}", b),
         Value::Symbol(s) => format!(":{}", s),
+        Value::Duration(d) => d.to_string(),
+        Value::Function(f) => f.to_string(),
         Value::Nil => "nil".to_string(),
         Value::Array(arr) => {
             let items: Vec<String> = arr.iter().map(format_result).collect();
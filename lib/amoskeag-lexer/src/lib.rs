@@ -32,6 +32,7 @@ pub enum TokenType {
 
     // Literals
     Number(f64),
+    Integer(i64),
     String(String),
     Identifier(String),
     Symbol(String), // The value after the colon, e.g., :approve -> "approve"
@@ -92,6 +93,7 @@ impl fmt::Display for TokenType {
             TokenType::Or => write!(f, "or"),
             TokenType::Not => write!(f, "not"),
             TokenType::Number(n) => write!(f, "{}", n),
+            TokenType::Integer(n) => write!(f, "{}", n),
             TokenType::String(s) => write!(f, "\"{}\"", s),
             TokenType::Identifier(s) => write!(f, "{}", s),
             TokenType::Symbol(s) => write!(f, ":{}", s),
@@ -494,6 +496,18 @@ impl Lexer {
             }
         }
 
+        // Literals without a decimal part are integers, unless they are too large for one
+        if !lexeme.contains('.') {
+            if let Ok(num) = lexeme.parse::<i64>() {
+                return Ok(Token::new(
+                    TokenType::Integer(num),
+                    lexeme,
+                    start_line,
+                    start_column,
+                ));
+            }
+        }
+
         match lexeme.parse::<f64>() {
             Ok(num) if num.is_finite() => Ok(Token::new(
                 TokenType::Number(num),
@@ -731,7 +745,7 @@ mod tests {
         let mut lexer = Lexer::new(input);
         let tokens = lexer.tokenize().unwrap();
 
-        assert_eq!(tokens[0].token_type, TokenType::Number(123.0));
+        assert_eq!(tokens[0].token_type, TokenType::Integer(123));
         assert_eq!(tokens[1].token_type, TokenType::Number(45.67));
         assert_eq!(tokens[2].token_type, TokenType::Number(0.5));
     }
@@ -797,7 +811,7 @@ mod tests {
         assert_eq!(tokens[0].token_type, TokenType::Let);
         assert_eq!(tokens[1].token_type, TokenType::Identifier("x".to_string()));
         assert_eq!(tokens[2].token_type, TokenType::Assign);
-        assert_eq!(tokens[3].token_type, TokenType::Number(5.0));
+        assert_eq!(tokens[3].token_type, TokenType::Integer(5));
         assert_eq!(tokens[4].token_type, TokenType::Let);
         assert_eq!(tokens[5].token_type, TokenType::Identifier("y".to_string()));
     }
@@ -825,7 +839,7 @@ mod tests {
             TokenType::Identifier("age".to_string())
         );
         assert_eq!(tokens[4].token_type, TokenType::Greater);
-        assert_eq!(tokens[5].token_type, TokenType::Number(16.0));
+        assert_eq!(tokens[5].token_type, TokenType::Integer(16));
         assert_eq!(
             tokens[6].token_type,
             TokenType::Symbol("continue".to_string())
@@ -948,13 +962,30 @@ mod tests {
         let mut lexer = Lexer::new(input);
         let tokens = lexer.tokenize().unwrap();
 
-        assert_eq!(tokens[0].token_type, TokenType::Number(0.0));
-        assert_eq!(tokens[1].token_type, TokenType::Number(123.0));
+        assert_eq!(tokens[0].token_type, TokenType::Integer(0));
+        assert_eq!(tokens[1].token_type, TokenType::Integer(123));
         assert_eq!(tokens[2].token_type, TokenType::Number(0.5));
         assert_eq!(tokens[3].token_type, TokenType::Number(123.456));
         // .0 should be tokenized as . and 0
         assert_eq!(tokens[4].token_type, TokenType::Dot);
-        assert_eq!(tokens[5].token_type, TokenType::Number(0.0));
+        assert_eq!(tokens[5].token_type, TokenType::Integer(0));
+    }
+
+    #[test]
+    fn test_integer_literals() {
+        let input = "42 42.0 9223372036854775807 9223372036854775808";
+        let mut lexer = Lexer::new(input);
+        let tokens = lexer.tokenize().unwrap();
+
+        assert_eq!(tokens[0].token_type, TokenType::Integer(42));
+        // A decimal point makes it a float, even with no fractional part
+        assert_eq!(tokens[1].token_type, TokenType::Number(42.0));
+        assert_eq!(tokens[2].token_type, TokenType::Integer(i64::MAX));
+        // Too large for an integer, so it falls back to a float
+        assert_eq!(
+            tokens[3].token_type,
+            TokenType::Number(9223372036854775808.0)
+        );
     }
 
     #[test]
@@ -5,14 +5,16 @@
 //! producing an Abstract Syntax Tree (AST) using a recursive descent parser.
 
 use amoskeag_lexer::{Token, TokenType};
+use serde::{Deserialize, Serialize};
 use std::fmt;
 use thiserror::Error;
 
 /// AST node representing an expression
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum Expr {
     // Literals
     Number(f64),
+    Integer(i64),
     String(String),
     Boolean(bool),
     Nil,
@@ -63,8 +65,19 @@ pub enum Expr {
     },
 }
 
+impl Expr {
+    /// The value of a `Number` or `Integer` literal, as a float
+    pub fn as_number(&self) -> Option<f64> {
+        match self {
+            Expr::Number(n) => Some(*n),
+            Expr::Integer(n) => Some(*n as f64),
+            _ => None,
+        }
+    }
+}
+
 /// Binary operators
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum BinaryOp {
     // Arithmetic
     Add,
@@ -109,7 +122,7 @@ impl fmt::Display for BinaryOp {
 }
 
 /// Unary operators
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum UnaryOp {
     Not,
     Negate,
@@ -353,6 +366,11 @@ impl Parser {
                 self.advance();
                 Ok(Expr::Number(n))
             }
+            TokenType::Integer(n) => {
+                let n = *n;
+                self.advance();
+                Ok(Expr::Integer(n))
+            }
             TokenType::String(s) => {
                 let s = s.clone();
                 self.advance();
@@ -661,7 +679,13 @@ mod tests {
     #[test]
     fn test_parse_number() {
         let expr = parse("42").unwrap();
-        assert_eq!(expr, Expr::Number(42.0));
+        assert_eq!(expr, Expr::Integer(42));
+    }
+
+    #[test]
+    fn test_parse_float_literal() {
+        assert_eq!(parse("42.0").unwrap(), Expr::Number(42.0));
+        assert_eq!(parse("0.25").unwrap(), Expr::Number(0.25));
     }
 
     #[test]
@@ -716,8 +740,8 @@ mod tests {
             expr,
             Expr::Binary {
                 op: BinaryOp::Add,
-                left: Box::new(Expr::Number(1.0)),
-                right: Box::new(Expr::Number(2.0)),
+                left: Box::new(Expr::Integer(1)),
+                right: Box::new(Expr::Integer(2)),
             }
         );
     }
@@ -730,7 +754,7 @@ mod tests {
             Expr::Binary {
                 op: BinaryOp::Greater,
                 left: Box::new(Expr::Variable(vec!["age".to_string()])),
-                right: Box::new(Expr::Number(18.0)),
+                right: Box::new(Expr::Integer(18)),
             }
         );
     }
@@ -744,7 +768,7 @@ mod tests {
                 condition: Box::new(Expr::Binary {
                     op: BinaryOp::Greater,
                     left: Box::new(Expr::Variable(vec!["age".to_string()])),
-                    right: Box::new(Expr::Number(18.0)),
+                    right: Box::new(Expr::Integer(18)),
                 }),
                 then_branch: Box::new(Expr::Symbol("adult".to_string())),
                 else_branch: Box::new(Expr::Symbol("minor".to_string())),
@@ -759,11 +783,11 @@ mod tests {
             expr,
             Expr::Let {
                 name: "x".to_string(),
-                value: Box::new(Expr::Number(5.0)),
+                value: Box::new(Expr::Integer(5)),
                 body: Box::new(Expr::Binary {
                     op: BinaryOp::Add,
                     left: Box::new(Expr::Variable(vec!["x".to_string()])),
-                    right: Box::new(Expr::Number(1.0)),
+                    right: Box::new(Expr::Integer(1)),
                 }),
             }
         );
@@ -788,7 +812,7 @@ mod tests {
             expr,
             Expr::FunctionCall {
                 name: "truncate".to_string(),
-                args: vec![Expr::Variable(vec!["name".to_string()]), Expr::Number(10.0),],
+                args: vec![Expr::Variable(vec!["name".to_string()]), Expr::Integer(10),],
             }
         );
     }
@@ -805,7 +829,7 @@ mod tests {
                         name: "downcase".to_string(),
                         args: vec![Expr::Variable(vec!["name".to_string()])],
                     },
-                    Expr::Number(10.0),
+                    Expr::Integer(10),
                 ],
             }
         );
@@ -826,7 +850,7 @@ mod tests {
             }),
             right: Box::new(Expr::Binary {
                 op: BinaryOp::Add,
-                left: Box::new(Expr::Number(1.0)),
+                left: Box::new(Expr::Integer(1)),
                 right: Box::new(Expr::Variable(vec!["tax".to_string()])),
             }),
         };
@@ -835,7 +859,7 @@ mod tests {
             expr,
             Expr::FunctionCall {
                 name: "round".to_string(),
-                args: vec![product, Expr::Number(2.0)],
+                args: vec![product, Expr::Integer(2)],
             }
         );
     }
@@ -845,11 +869,7 @@ mod tests {
         let expr = parse("[1, 2, 3]").unwrap();
         assert_eq!(
             expr,
-            Expr::Array(vec![
-                Expr::Number(1.0),
-                Expr::Number(2.0),
-                Expr::Number(3.0),
-            ])
+            Expr::Array(vec![Expr::Integer(1), Expr::Integer(2), Expr::Integer(3),])
         );
     }
 
@@ -860,7 +880,7 @@ mod tests {
             expr,
             Expr::Dictionary(vec![
                 ("name".to_string(), Expr::String("Alice".to_string())),
-                ("age".to_string(), Expr::Number(30.0)),
+                ("age".to_string(), Expr::Integer(30)),
             ])
         );
     }
@@ -885,7 +905,7 @@ mod tests {
                         "driver".to_string(),
                         "age".to_string()
                     ])),
-                    right: Box::new(Expr::Number(16.0)),
+                    right: Box::new(Expr::Integer(16)),
                 }),
                 then_branch: Box::new(Expr::Symbol("continue".to_string())),
                 else_branch: Box::new(Expr::Symbol("deny".to_string())),
@@ -901,11 +921,11 @@ mod tests {
             expr,
             Expr::Binary {
                 op: BinaryOp::Add,
-                left: Box::new(Expr::Number(1.0)),
+                left: Box::new(Expr::Integer(1)),
                 right: Box::new(Expr::Binary {
                     op: BinaryOp::Multiply,
-                    left: Box::new(Expr::Number(2.0)),
-                    right: Box::new(Expr::Number(3.0)),
+                    left: Box::new(Expr::Integer(2)),
+                    right: Box::new(Expr::Integer(3)),
                 }),
             }
         );
@@ -940,7 +960,7 @@ mod tests {
             if let Expr::Dictionary(inner_pairs) = &outer_pairs[0].1 {
                 assert_eq!(inner_pairs.len(), 1);
                 assert_eq!(inner_pairs[0].0, "inner");
-                assert_eq!(inner_pairs[0].1, Expr::Number(42.0));
+                assert_eq!(inner_pairs[0].1, Expr::Integer(42));
             } else {
                 panic!("Expected nested dictionary");
             }
@@ -968,7 +988,7 @@ mod tests {
             expr,
             Expr::Unary {
                 op: UnaryOp::Negate,
-                operand: Box::new(Expr::Number(10.0)),
+                operand: Box::new(Expr::Integer(10)),
             }
         );
     }
@@ -1006,8 +1026,8 @@ mod tests {
             expr,
             Expr::Binary {
                 op: BinaryOp::Modulo,
-                left: Box::new(Expr::Number(10.0)),
-                right: Box::new(Expr::Number(3.0)),
+                left: Box::new(Expr::Integer(10)),
+                right: Box::new(Expr::Integer(3)),
             }
         );
     }
@@ -1021,10 +1041,10 @@ mod tests {
                 op: BinaryOp::Multiply,
                 left: Box::new(Expr::Binary {
                     op: BinaryOp::Add,
-                    left: Box::new(Expr::Number(1.0)),
-                    right: Box::new(Expr::Number(2.0)),
+                    left: Box::new(Expr::Integer(1)),
+                    right: Box::new(Expr::Integer(2)),
                 }),
-                right: Box::new(Expr::Number(3.0)),
+                right: Box::new(Expr::Integer(3)),
             }
         );
     }
@@ -1035,11 +1055,11 @@ mod tests {
 
         if let Expr::Let { name, value, body } = expr {
             assert_eq!(name, "x");
-            assert_eq!(*value, Expr::Number(1.0));
+            assert_eq!(*value, Expr::Integer(1));
 
             if let Expr::Let { name, value, body } = *body {
                 assert_eq!(name, "y");
-                assert_eq!(*value, Expr::Number(2.0));
+                assert_eq!(*value, Expr::Integer(2));
 
                 assert!(matches!(*body, Expr::Binary { .. }));
             } else {
@@ -1056,11 +1076,11 @@ mod tests {
 
         if let Expr::Let { name, value, body } = expr {
             assert_eq!(name, "x");
-            assert_eq!(*value, Expr::Number(1.0));
+            assert_eq!(*value, Expr::Integer(1));
 
             if let Expr::Let { name, value, body } = *body {
                 assert_eq!(name, "y");
-                assert_eq!(*value, Expr::Number(2.0));
+                assert_eq!(*value, Expr::Integer(2));
                 assert!(matches!(*body, Expr::Binary { .. }));
             } else {
                 panic!("Expected nested let");
@@ -1082,7 +1102,7 @@ mod tests {
         {
             assert_eq!(*condition, Expr::Boolean(true));
             assert!(matches!(*then_branch, Expr::If { .. }));
-            assert_eq!(*else_branch, Expr::Number(3.0));
+            assert_eq!(*else_branch, Expr::Integer(3));
         } else {
             panic!("Expected if expression");
         }
@@ -1107,7 +1127,7 @@ mod tests {
             expr,
             Expr::FunctionCall {
                 name: "add".to_string(),
-                args: vec![Expr::Number(1.0), Expr::Number(2.0), Expr::Number(3.0),],
+                args: vec![Expr::Integer(1), Expr::Integer(2), Expr::Integer(3),],
             }
         );
     }
@@ -1345,7 +1365,7 @@ mod tests {
         {
             assert_eq!(*condition, Expr::Boolean(true));
             assert!(matches!(*then_branch, Expr::If { .. }));
-            assert_eq!(*else_branch, Expr::Number(3.0));
+            assert_eq!(*else_branch, Expr::Integer(3));
         } else {
             panic!("Expected if expression");
         }
@@ -176,8 +176,8 @@ impl AlgebraicAnalyzer {
                     });
                 }
             }
-        } else if let Expr::Number(n) = divisor {
-            if *n == 0.0 {
+        } else if let Some(n) = divisor.as_number() {
+            if n == 0.0 {
                 self.vulnerable_inputs.push(VulnerableInput {
                     error_type: "DivisionByZero".to_string(),
                     description: "Constant division by zero".to_string(),
@@ -240,33 +240,28 @@ impl AlgebraicAnalyzer {
         path_conditions: &[Constraint],
     ) {
         match name {
-            "at" => {
+            "at" if args.len() >= 2 => {
                 // Array access - check for out of bounds
-                if args.len() >= 2 {
-                    if let Some(index_var) = self.extract_variable_name(&args[1]) {
-                        // Try negative index
-                        let mut solver = ConstraintSolver::new();
-                        for condition in path_conditions {
-                            solver.add_constraint(condition.clone());
-                        }
-                        solver.add_constraint(Constraint::LessThan {
-                            variable: index_var.clone(),
-                            value: 0.0,
-                        });
-
-                        if let Some(solution) = solver.solve() {
-                            if solution.satisfies {
-                                self.vulnerable_inputs.push(VulnerableInput {
-                                    error_type: "ArrayOutOfBounds".to_string(),
-                                    description: format!(
-                                        "Negative array index when {} < 0",
-                                        index_var
-                                    ),
-                                    example_input: solution.values.clone(),
-                                    location: format!("at() with index {}", index_var),
-                                    severity: "Warning".to_string(),
-                                });
-                            }
+                if let Some(index_var) = self.extract_variable_name(&args[1]) {
+                    // Try negative index
+                    let mut solver = ConstraintSolver::new();
+                    for condition in path_conditions {
+                        solver.add_constraint(condition.clone());
+                    }
+                    solver.add_constraint(Constraint::LessThan {
+                        variable: index_var.clone(),
+                        value: 0.0,
+                    });
+
+                    if let Some(solution) = solver.solve() {
+                        if solution.satisfies {
+                            self.vulnerable_inputs.push(VulnerableInput {
+                                error_type: "ArrayOutOfBounds".to_string(),
+                                description: format!("Negative array index when {} < 0", index_var),
+                                example_input: solution.values.clone(),
+                                location: format!("at() with index {}", index_var),
+                                severity: "Warning".to_string(),
+                            });
                         }
                     }
                 }
@@ -351,10 +346,7 @@ impl AlgebraicAnalyzer {
     }
 
     fn extract_number_value(&self, expr: &Expr) -> Option<f64> {
-        match expr {
-            Expr::Number(n) => Some(*n),
-            _ => None,
-        }
+        expr.as_number()
     }
 }
 
@@ -226,7 +226,12 @@ impl DataFlowAnalyzer {
 
     fn analyze_expr(&mut self, expr: &Expr) -> Vec<usize> {
         match expr {
-            Expr::Number(_) | Expr::String(_) | Expr::Boolean(_) | Expr::Nil | Expr::Symbol(_) => {
+            Expr::Number(_)
+            | Expr::Integer(_)
+            | Expr::String(_)
+            | Expr::Boolean(_)
+            | Expr::Nil
+            | Expr::Symbol(_) => {
                 // Literals create no data flow
                 vec![]
             }
@@ -101,7 +101,7 @@ impl ErrorDetector {
 
     fn analyze_expr(&mut self, expr: &Expr, ranges: &HashMap<String, ValueRange>) {
         match expr {
-            Expr::Number(_) | Expr::String(_) | Expr::Boolean(_) | Expr::Nil => {
+            Expr::Number(_) | Expr::Integer(_) | Expr::String(_) | Expr::Boolean(_) | Expr::Nil => {
                 // Literals are always safe
             }
 
@@ -250,7 +250,7 @@ impl ErrorDetector {
 
                 // Check for division by zero
                 if matches!(op, BinaryOp::Divide | BinaryOp::Modulo) {
-                    if let Expr::Number(n) = **right {
+                    if let Some(n) = right.as_number() {
                         if n == 0.0 {
                             self.errors.push(ProgrammingError {
                                 severity: ErrorSeverity::Critical,
@@ -290,7 +290,7 @@ impl ErrorDetector {
 
                 // Check for integer overflow
                 if matches!(op, BinaryOp::Multiply | BinaryOp::Add) {
-                    if let (Expr::Number(a), Expr::Number(b)) = (&**left, &**right) {
+                    if let (Some(a), Some(b)) = (left.as_number(), right.as_number()) {
                         if a.is_finite() && b.is_finite() {
                             let result = match op {
                                 BinaryOp::Multiply => a * b,
@@ -317,8 +317,8 @@ impl ErrorDetector {
 
                 // Check for suspicious comparisons
                 if matches!(op, BinaryOp::Equal | BinaryOp::NotEqual) {
-                    if let (Expr::Number(a), Expr::Number(b)) = (&**left, &**right) {
-                        if (a - b).abs() < f64::EPSILON && *a != *b {
+                    if let (Some(a), Some(b)) = (left.as_number(), right.as_number()) {
+                        if (a - b).abs() < f64::EPSILON && a != b {
                             self.errors.push(ProgrammingError {
                                 severity: ErrorSeverity::Info,
                                 message: "Floating-point equality comparison may be unreliable".to_string(),
@@ -367,31 +367,29 @@ impl ErrorDetector {
     ) {
         // Check for common risky patterns
         match name {
-            "at" => {
+            "at" if args.len() >= 2 => {
                 // Array access function - check for out of bounds
-                if args.len() >= 2 {
-                    if let (Expr::Array(arr), Expr::Number(idx)) = (&args[0], &args[1]) {
-                        let index = *idx as i64;
-                        if index < 0 || index >= arr.len() as i64 {
-                            self.errors.push(ProgrammingError {
-                                severity: ErrorSeverity::Critical,
-                                message: format!(
-                                    "Array index {} out of bounds (array length: {})",
-                                    index,
-                                    arr.len()
-                                ),
-                                category: ErrorCategory::ArrayOutOfBounds,
-                                location: Some(format!("at({}, {})", arr.len(), index)),
-                                suggestion: Some("Use an index within array bounds".to_string()),
-                            });
-                        }
+                if let (Expr::Array(arr), Some(idx)) = (&args[0], args[1].as_number()) {
+                    let index = idx as i64;
+                    if index < 0 || index >= arr.len() as i64 {
+                        self.errors.push(ProgrammingError {
+                            severity: ErrorSeverity::Critical,
+                            message: format!(
+                                "Array index {} out of bounds (array length: {})",
+                                index,
+                                arr.len()
+                            ),
+                            category: ErrorCategory::ArrayOutOfBounds,
+                            location: Some(format!("at({}, {})", arr.len(), index)),
+                            suggestion: Some("Use an index within array bounds".to_string()),
+                        });
                     }
                 }
             }
             // Check for division by zero
             "divided_by" if args.len() >= 2 => {
-                if let Expr::Number(n) = &args[1] {
-                    if *n == 0.0 {
+                if let Some(n) = args[1].as_number() {
+                    if n == 0.0 {
                         self.errors.push(ProgrammingError {
                             severity: ErrorSeverity::Critical,
                             message: "Division by zero in divided_by function".to_string(),
@@ -411,42 +409,42 @@ impl ErrorDetector {
             Expr::Boolean(b) => Some(*b),
             Expr::Binary { op, left, right } => match op {
                 BinaryOp::Equal => {
-                    if let (Expr::Number(a), Expr::Number(b)) = (&**left, &**right) {
+                    if let (Some(a), Some(b)) = (left.as_number(), right.as_number()) {
                         Some(a == b)
                     } else {
                         None
                     }
                 }
                 BinaryOp::NotEqual => {
-                    if let (Expr::Number(a), Expr::Number(b)) = (&**left, &**right) {
+                    if let (Some(a), Some(b)) = (left.as_number(), right.as_number()) {
                         Some(a != b)
                     } else {
                         None
                     }
                 }
                 BinaryOp::Less => {
-                    if let (Expr::Number(a), Expr::Number(b)) = (&**left, &**right) {
+                    if let (Some(a), Some(b)) = (left.as_number(), right.as_number()) {
                         Some(a < b)
                     } else {
                         None
                     }
                 }
                 BinaryOp::Greater => {
-                    if let (Expr::Number(a), Expr::Number(b)) = (&**left, &**right) {
+                    if let (Some(a), Some(b)) = (left.as_number(), right.as_number()) {
                         Some(a > b)
                     } else {
                         None
                     }
                 }
                 BinaryOp::LessEqual => {
-                    if let (Expr::Number(a), Expr::Number(b)) = (&**left, &**right) {
+                    if let (Some(a), Some(b)) = (left.as_number(), right.as_number()) {
                         Some(a <= b)
                     } else {
                         None
                     }
                 }
                 BinaryOp::GreaterEqual => {
-                    if let (Expr::Number(a), Expr::Number(b)) = (&**left, &**right) {
+                    if let (Some(a), Some(b)) = (left.as_number(), right.as_number()) {
                         Some(a >= b)
                     } else {
                         None
@@ -273,6 +273,7 @@ impl RangeAnalyzer {
     fn analyze_expr(&mut self, expr: &Expr) -> ValueRange {
         match expr {
             Expr::Number(n) => ValueRange::Exact(*n),
+            Expr::Integer(n) => ValueRange::Exact(*n as f64),
 
             Expr::String(_) | Expr::Boolean(_) | Expr::Symbol(_) => ValueRange::NonNumeric,
 
@@ -0,0 +1,50 @@
+//! Support for the transpilers that write source for other languages
+//!
+//! The Python and JavaScript transpilers emit their runtime helpers from
+//! tab-indented source lines, and take the rules those helpers follow from
+//! the standard library, so that the generated code and the interpreter
+//! agree.
+
+use std::fmt::{self, Write};
+
+/// Write source lines, expanding each leading tab to one level of `indent`
+pub fn write_lines<S: AsRef<str>>(
+    output: &mut impl Write,
+    indent: &str,
+    lines: &[S],
+) -> fmt::Result {
+    for line in lines {
+        let line = line.as_ref();
+        let depth = line.chars().take_while(|c| *c == '\t').count();
+        writeln!(output, "{}{}", indent.repeat(depth), &line[depth..])?;
+    }
+    Ok(())
+}
+
+/// A list literal of single-quoted strings, such as `['a', 'b']`, which
+/// reads the same in Python, JavaScript, and Ruby
+///
+/// The strings are written as they are, so they must not contain quotes
+/// or backslashes.
+pub fn string_list(items: &[&str]) -> String {
+    let quoted: Vec<String> = items.iter().map(|item| format!("'{}'", item)).collect();
+    format!("[{}]", quoted.join(", "))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_write_lines() {
+        let mut output = String::new();
+        write_lines(&mut output, "  ", &["f() {", "\treturn 1;", "}"]).unwrap();
+        assert_eq!(output, "f() {\n  return 1;\n}\n");
+    }
+
+    #[test]
+    fn test_string_list() {
+        assert_eq!(string_list(&["true", "yes"]), "['true', 'yes']");
+        assert_eq!(string_list(&[]), "[]");
+    }
+}
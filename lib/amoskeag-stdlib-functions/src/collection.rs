@@ -1,6 +1,7 @@
 //! Collection manipulation functions for Amoskeag
 
-use crate::{FunctionError, Value};
+use crate::numeric::decimal::overflow;
+use crate::{Decimal, FunctionError, Value};
 
 /// Get the size/length of a collection
 /// size(val: String | Array | Dictionary) -> Number
@@ -48,7 +49,7 @@ pub fn contains(array: &Value, value: &Value) -> Result<Value, FunctionError> {
         Value::String(s) => {
             // Also support checking if a string contains a substring
             match value {
-                Value::String(substr) => Ok(Value::Boolean(s.contains(substr.as_str()))),
+                Value::String(substr) => Ok(Value::Boolean(s.contains(&**substr))),
                 _ => Err(FunctionError::TypeError {
                     expected: "String".to_string(),
                     got: value.type_name().to_string(),
@@ -64,11 +65,18 @@ pub fn contains(array: &Value, value: &Value) -> Result<Value, FunctionError> {
 
 /// Sum an array of numbers
 /// sum(arr: Array) -> Number
+///
+/// If any element is a decimal the sum is exact and is a decimal.
 pub fn sum(value: &Value) -> Result<Value, FunctionError> {
+    if let Value::Array(arr) = value {
+        if arr.iter().any(|item| matches!(item, Value::Decimal(_))) {
+            return sum_decimal(arr);
+        }
+    }
     match value {
         Value::Array(arr) => {
             let mut total = 0.0;
-            for item in arr {
+            for item in arr.iter() {
                 match item {
                     Value::Number(n) => total += n,
                     _ => {
@@ -88,6 +96,19 @@ pub fn sum(value: &Value) -> Result<Value, FunctionError> {
     }
 }
 
+/// Exact sum of an array holding at least one `Decimal`
+fn sum_decimal(arr: &[Value]) -> Result<Value, FunctionError> {
+    let mut total = Decimal::ZERO;
+    for item in arr {
+        let n = item.as_decimal().ok_or_else(|| FunctionError::TypeError {
+            expected: "Array of Numbers".to_string(),
+            got: format!("Array containing {}", item.type_name()),
+        })?;
+        total = total.checked_add(n).ok_or_else(|| overflow("sum"))?;
+    }
+    Ok(Value::Decimal(total))
+}
+
 /// Calculate the average of an array of numbers
 /// avg(arr: Array) -> Number
 pub fn avg(value: &Value) -> Result<Value, FunctionError> {
@@ -110,26 +131,352 @@ pub fn avg(value: &Value) -> Result<Value, FunctionError> {
     }
 }
 
+/// Calculate the weighted average of an array of numbers
+/// weighted_average(values: Array, weights: Array) -> Number
+/// weighted_average(records: Array) -> Number
+///
+/// When `weights` is nil, `values` must be an array of dictionaries with
+/// `value` and `weight` keys. Returns nil for an empty array.
+pub fn weighted_average(values: &Value, weights: &Value) -> Result<Value, FunctionError> {
+    let pairs: Vec<(f64, f64)> = match weights {
+        Value::Nil => weighted_records(values)?,
+        _ => {
+            let values = number_items(values)?;
+            let weights = number_items(weights)?;
+            if values.len() != weights.len() {
+                return Err(FunctionError::ArgumentError {
+                    message: format!(
+                        "weighted_average requires one weight per value, got {} values and {} weights",
+                        values.len(),
+                        weights.len()
+                    ),
+                });
+            }
+            values.into_iter().zip(weights).collect()
+        }
+    };
+
+    if pairs.is_empty() {
+        return Ok(Value::Nil);
+    }
+
+    let total_weight: f64 = pairs.iter().map(|(_, w)| w).sum();
+    if total_weight == 0.0 {
+        return Err(FunctionError::ArgumentError {
+            message: "weighted_average requires weights that do not sum to zero".to_string(),
+        });
+    }
+    let total: f64 = pairs.iter().map(|(v, w)| v * w).sum();
+    Ok(Value::Number(total / total_weight))
+}
+
+/// Read `value` / `weight` pairs from an array of dictionaries
+fn weighted_records(records: &Value) -> Result<Vec<(f64, f64)>, FunctionError> {
+    let items = match records {
+        Value::Array(items) => items,
+        _ => {
+            return Err(FunctionError::TypeError {
+                expected: "Array".to_string(),
+                got: records.type_name().to_string(),
+            })
+        }
+    };
+
+    items
+        .iter()
+        .map(|item| {
+            let dict = match item {
+                Value::Dictionary(dict) => dict,
+                _ => {
+                    return Err(FunctionError::TypeError {
+                        expected: "Array of Dictionaries".to_string(),
+                        got: format!("Array containing {}", item.type_name()),
+                    })
+                }
+            };
+            let field = |key: &str| match dict.get(key) {
+                Some(Value::Number(n)) => Ok(*n),
+                Some(other) => Err(FunctionError::TypeError {
+                    expected: "Number".to_string(),
+                    got: other.type_name().to_string(),
+                }),
+                None => Err(FunctionError::ArgumentError {
+                    message: format!("weighted_average record is missing '{}'", key),
+                }),
+            };
+            Ok((field("value")?, field("weight")?))
+        })
+        .collect()
+}
+
+/// Calculate the median of an array of numbers
+/// median(arr: Array) -> Number
+///
+/// Returns nil for an empty array. An even number of items yields the mean
+/// of the two middle values.
+pub fn median(value: &Value) -> Result<Value, FunctionError> {
+    let mut numbers = number_items(value)?;
+    if numbers.is_empty() {
+        return Ok(Value::Nil);
+    }
+
+    numbers.sort_by(|a, b| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal));
+    let mid = numbers.len() / 2;
+    if numbers.len() % 2 == 0 {
+        Ok(Value::Number((numbers[mid - 1] + numbers[mid]) / 2.0))
+    } else {
+        Ok(Value::Number(numbers[mid]))
+    }
+}
+
+/// Find the most frequent value in an array
+/// mode(arr: Array) -> Any
+///
+/// Items of any type are compared by equality, so `1` and `'1'` are counted
+/// separately. Ties go to the value that appears first; an empty array
+/// returns nil.
+pub fn mode(value: &Value) -> Result<Value, FunctionError> {
+    match value {
+        Value::Array(arr) => {
+            let mut counts: Vec<(&Value, usize)> = Vec::new();
+            for item in arr.iter() {
+                match counts.iter_mut().find(|(seen, _)| *seen == item) {
+                    Some((_, count)) => *count += 1,
+                    None => counts.push((item, 1)),
+                }
+            }
+
+            let mut best: Option<(&Value, usize)> = None;
+            for (item, count) in counts {
+                if best.is_none_or(|(_, best_count)| count > best_count) {
+                    best = Some((item, count));
+                }
+            }
+            Ok(best.map(|(item, _)| item.clone()).unwrap_or(Value::Nil))
+        }
+        _ => Err(FunctionError::TypeError {
+            expected: "Array".to_string(),
+            got: value.type_name().to_string(),
+        }),
+    }
+}
+
+/// Calculate a percentile of an array of numbers using linear interpolation
+/// percentile(arr: Array, p: Number) -> Number
+///
+/// `p` ranges from 0 to 100 inclusive. Returns nil for an empty array.
+pub fn percentile(value: &Value, p: &Value) -> Result<Value, FunctionError> {
+    let rank = rank_fraction("percentile", p, 100.0)?;
+    interpolated_rank(value, rank)
+}
+
+/// Calculate a quantile of an array of numbers using linear interpolation
+/// quantile(arr: Array, q: Number) -> Number
+///
+/// `q` ranges from 0 to 1 inclusive. Returns nil for an empty array.
+pub fn quantile(value: &Value, q: &Value) -> Result<Value, FunctionError> {
+    let rank = rank_fraction("quantile", q, 1.0)?;
+    interpolated_rank(value, rank)
+}
+
+/// Validate a percentile or quantile argument and scale it to [0, 1]
+fn rank_fraction(name: &str, value: &Value, max: f64) -> Result<f64, FunctionError> {
+    match value {
+        Value::Number(n) if (0.0..=max).contains(n) => Ok(n / max),
+        Value::Number(n) => Err(FunctionError::ArgumentError {
+            message: format!("{} must be between 0 and {}, got {}", name, max, n),
+        }),
+        _ => Err(FunctionError::TypeError {
+            expected: "Number".to_string(),
+            got: value.type_name().to_string(),
+        }),
+    }
+}
+
+/// Interpolate between the closest ranks of the sorted numbers
+fn interpolated_rank(value: &Value, rank: f64) -> Result<Value, FunctionError> {
+    let mut numbers = number_items(value)?;
+    if numbers.is_empty() {
+        return Ok(Value::Nil);
+    }
+
+    numbers.sort_by(|a, b| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal));
+    let position = rank * (numbers.len() - 1) as f64;
+    let lower = position.floor() as usize;
+    let upper = position.ceil() as usize;
+    let weight = position - lower as f64;
+    Ok(Value::Number(
+        numbers[lower] + (numbers[upper] - numbers[lower]) * weight,
+    ))
+}
+
+/// Calculate the population variance of an array of numbers
+/// variance(arr: Array) -> Number
+///
+/// Returns nil for an empty array.
+pub fn variance(value: &Value) -> Result<Value, FunctionError> {
+    let numbers = number_items(value)?;
+    Ok(squared_deviations(&numbers, 0)
+        .map(Value::Number)
+        .unwrap_or(Value::Nil))
+}
+
+/// Calculate the sample variance of an array of numbers
+/// variance_sample(arr: Array) -> Number
+///
+/// Divides by n - 1 (Bessel's correction). Returns nil for fewer than two items.
+pub fn variance_sample(value: &Value) -> Result<Value, FunctionError> {
+    let numbers = number_items(value)?;
+    Ok(squared_deviations(&numbers, 1)
+        .map(Value::Number)
+        .unwrap_or(Value::Nil))
+}
+
+/// Calculate the population standard deviation of an array of numbers
+/// stddev(arr: Array) -> Number
+///
+/// Returns nil for an empty array.
+pub fn stddev(value: &Value) -> Result<Value, FunctionError> {
+    let numbers = number_items(value)?;
+    Ok(squared_deviations(&numbers, 0)
+        .map(|v| Value::Number(v.sqrt()))
+        .unwrap_or(Value::Nil))
+}
+
+/// Calculate the sample standard deviation of an array of numbers
+/// stddev_sample(arr: Array) -> Number
+///
+/// Divides by n - 1 (Bessel's correction). Returns nil for fewer than two items.
+pub fn stddev_sample(value: &Value) -> Result<Value, FunctionError> {
+    let numbers = number_items(value)?;
+    Ok(squared_deviations(&numbers, 1)
+        .map(|v| Value::Number(v.sqrt()))
+        .unwrap_or(Value::Nil))
+}
+
+/// Calculate the population covariance of two arrays of numbers
+/// covariance(xs: Array, ys: Array) -> Number
+///
+/// Returns nil for empty arrays.
+pub fn covariance(xs: &Value, ys: &Value) -> Result<Value, FunctionError> {
+    let (xs, ys) = number_pairs("covariance", xs, ys)?;
+    Ok(co_deviations(&xs, &ys, 0)
+        .map(Value::Number)
+        .unwrap_or(Value::Nil))
+}
+
+/// Calculate the sample covariance of two arrays of numbers
+/// covariance_sample(xs: Array, ys: Array) -> Number
+///
+/// Divides by n - 1. Returns nil for fewer than two pairs.
+pub fn covariance_sample(xs: &Value, ys: &Value) -> Result<Value, FunctionError> {
+    let (xs, ys) = number_pairs("covariance_sample", xs, ys)?;
+    Ok(co_deviations(&xs, &ys, 1)
+        .map(Value::Number)
+        .unwrap_or(Value::Nil))
+}
+
+/// Calculate the Pearson correlation coefficient of two arrays of numbers
+/// correlation(xs: Array, ys: Array) -> Number
+///
+/// Returns nil when the coefficient is undefined: for empty arrays, or when
+/// either array holds a single repeated value.
+pub fn correlation(xs: &Value, ys: &Value) -> Result<Value, FunctionError> {
+    let (xs, ys) = number_pairs("correlation", xs, ys)?;
+    let (Some(cov), Some(var_x), Some(var_y)) = (
+        co_deviations(&xs, &ys, 0),
+        squared_deviations(&xs, 0),
+        squared_deviations(&ys, 0),
+    ) else {
+        return Ok(Value::Nil);
+    };
+    if var_x == 0.0 || var_y == 0.0 {
+        return Ok(Value::Nil);
+    }
+    Ok(Value::Number(
+        (cov / (var_x * var_y).sqrt()).clamp(-1.0, 1.0),
+    ))
+}
+
+/// Collect two equal-length arrays of numbers
+fn number_pairs(name: &str, xs: &Value, ys: &Value) -> Result<(Vec<f64>, Vec<f64>), FunctionError> {
+    let xs = number_items(xs)?;
+    let ys = number_items(ys)?;
+    if xs.len() != ys.len() {
+        return Err(FunctionError::ArgumentError {
+            message: format!(
+                "{} requires arrays of equal length, got {} and {}",
+                name,
+                xs.len(),
+                ys.len()
+            ),
+        });
+    }
+    Ok((xs, ys))
+}
+
+/// Sum of products of paired deviations from the means, divided by `n - ddof`
+fn co_deviations(xs: &[f64], ys: &[f64], ddof: usize) -> Option<f64> {
+    if xs.len() <= ddof {
+        return None;
+    }
+    let n = xs.len() as f64;
+    let mean_x = xs.iter().sum::<f64>() / n;
+    let mean_y = ys.iter().sum::<f64>() / n;
+    let total: f64 = xs
+        .iter()
+        .zip(ys)
+        .map(|(x, y)| (x - mean_x) * (y - mean_y))
+        .sum();
+    Some(total / (xs.len() - ddof) as f64)
+}
+
+/// Sum of squared deviations from the mean, divided by `n - ddof`
+fn squared_deviations(numbers: &[f64], ddof: usize) -> Option<f64> {
+    if numbers.len() <= ddof {
+        return None;
+    }
+    let mean = numbers.iter().sum::<f64>() / numbers.len() as f64;
+    let total: f64 = numbers.iter().map(|n| (n - mean).powi(2)).sum();
+    Some(total / (numbers.len() - ddof) as f64)
+}
+
+/// Collect the items of an array that must contain only numbers
+fn number_items(value: &Value) -> Result<Vec<f64>, FunctionError> {
+    match value {
+        Value::Array(arr) => arr
+            .iter()
+            .map(|item| match item {
+                Value::Number(n) => Ok(*n),
+                _ => Err(FunctionError::TypeError {
+                    expected: "Array of Numbers".to_string(),
+                    got: format!("Array containing {}", item.type_name()),
+                }),
+            })
+            .collect(),
+        _ => Err(FunctionError::TypeError {
+            expected: "Array".to_string(),
+            got: value.type_name().to_string(),
+        }),
+    }
+}
+
 /// Sort an array (ascending order)
 /// sort(arr: Array) -> Array
+///
+/// Integers, floats, and decimals sort together by value and keep their kind.
 pub fn sort(value: &Value) -> Result<Value, FunctionError> {
     match value {
         Value::Array(arr) => {
-            let mut sorted = arr.clone();
+            let mut sorted = arr.to_vec();
 
             // Check if all elements are numbers or all are strings
-            let all_numbers = arr.iter().all(|v| matches!(v, Value::Number(_)));
+            let all_numbers = arr.iter().all(|v| v.as_f64().is_some());
             let all_strings = arr.iter().all(|v| matches!(v, Value::String(_)));
 
             if all_numbers {
-                sorted.sort_by(|a, b| {
-                    if let (Value::Number(x), Value::Number(y)) = (a, b) {
-                        x.partial_cmp(y).unwrap_or(std::cmp::Ordering::Equal)
-                    } else {
-                        std::cmp::Ordering::Equal
-                    }
-                });
-                Ok(Value::Array(sorted))
+                sorted.sort_by(compare_numbers);
+                Ok(Value::Array(sorted.into()))
             } else if all_strings {
                 sorted.sort_by(|a, b| {
                     if let (Value::String(x), Value::String(y)) = (a, b) {
@@ -138,7 +485,7 @@ pub fn sort(value: &Value) -> Result<Value, FunctionError> {
                         std::cmp::Ordering::Equal
                     }
                 });
-                Ok(Value::Array(sorted))
+                Ok(Value::Array(sorted.into()))
             } else {
                 Err(FunctionError::InvalidOperation {
                     message: "Array must contain all Numbers or all Strings to sort".to_string(),
@@ -159,8 +506,11 @@ pub fn keys(value: &Value) -> Result<Value, FunctionError> {
         Value::Dictionary(dict) => {
             let mut key_list: Vec<String> = dict.keys().cloned().collect();
             key_list.sort();
-            let keys: Vec<Value> = key_list.into_iter().map(Value::String).collect();
-            Ok(Value::Array(keys))
+            let keys: Vec<Value> = key_list
+                .into_iter()
+                .map(|k| Value::String(k.into()))
+                .collect();
+            Ok(Value::Array(keys.into()))
         }
         _ => Err(FunctionError::TypeError {
             expected: "Dictionary".to_string(),
@@ -175,7 +525,7 @@ pub fn values(value: &Value) -> Result<Value, FunctionError> {
     match value {
         Value::Dictionary(dict) => {
             let values: Vec<Value> = dict.values().cloned().collect();
-            Ok(Value::Array(values))
+            Ok(Value::Array(values.into()))
         }
         _ => Err(FunctionError::TypeError {
             expected: "Dictionary".to_string(),
@@ -189,9 +539,9 @@ pub fn values(value: &Value) -> Result<Value, FunctionError> {
 pub fn reverse(value: &Value) -> Result<Value, FunctionError> {
     match value {
         Value::Array(arr) => {
-            let mut reversed = arr.clone();
+            let mut reversed = arr.to_vec();
             reversed.reverse();
-            Ok(Value::Array(reversed))
+            Ok(Value::Array(reversed.into()))
         }
         _ => Err(FunctionError::TypeError {
             expected: "Array".to_string(),
@@ -203,9 +553,8 @@ pub fn reverse(value: &Value) -> Result<Value, FunctionError> {
 /// Get element at index (0-based)
 /// at(arr: Array, index: Number) -> Any
 pub fn at(array: &Value, index: &Value) -> Result<Value, FunctionError> {
-    match (array, index) {
-        (Value::Array(arr), Value::Number(idx)) => {
-            let i = *idx as i64;
+    match (array, index_value(index)) {
+        (Value::Array(arr), Some(i)) => {
             let actual_index = if i < 0 {
                 // Support negative indexing
                 (arr.len() as i64 + i) as usize
@@ -226,6 +575,31 @@ pub fn at(array: &Value, index: &Value) -> Result<Value, FunctionError> {
     }
 }
 
+/// Order two numbers of any kind: two `Int`s exactly, a `Decimal` against
+/// anything with a decimal form exactly, and the rest as floats, with NaN
+/// equal to everything
+fn compare_numbers(a: &Value, b: &Value) -> std::cmp::Ordering {
+    let exact = match (a, b) {
+        (Value::Int(x), Value::Int(y)) => Some(x.cmp(y)),
+        (Value::Decimal(_), _) | (_, Value::Decimal(_)) => {
+            a.as_decimal().zip(b.as_decimal()).map(|(x, y)| x.cmp(&y))
+        }
+        _ => None,
+    };
+    exact
+        .or_else(|| a.as_f64().partial_cmp(&b.as_f64()))
+        .unwrap_or(std::cmp::Ordering::Equal)
+}
+
+/// The position an `Int`, `Number`, or `Decimal` index names, truncating any
+/// fraction, or `None` for anything else
+pub(crate) fn index_value(index: &Value) -> Option<i64> {
+    match index {
+        Value::Int(n) => Some(*n),
+        _ => index.as_f64().map(|n| n as i64),
+    }
+}
+
 /// Remove duplicate elements from an array
 /// uniq(arr: Array) -> Array
 pub fn uniq(value: &Value) -> Result<Value, FunctionError> {
@@ -234,14 +608,14 @@ pub fn uniq(value: &Value) -> Result<Value, FunctionError> {
             let mut seen = Vec::new();
             let mut unique = Vec::new();
 
-            for item in arr {
+            for item in arr.iter() {
                 if !seen.contains(item) {
                     seen.push(item.clone());
                     unique.push(item.clone());
                 }
             }
 
-            Ok(Value::Array(unique))
+            Ok(Value::Array(unique.into()))
         }
         _ => Err(FunctionError::TypeError {
             expected: "Array".to_string(),
@@ -257,14 +631,16 @@ pub fn group_by(array: &Value, key: &Value) -> Result<Value, FunctionError> {
         (Value::Array(arr), Value::String(key_str)) => {
             let mut groups = std::collections::HashMap::new();
 
-            for item in arr {
+            for item in arr.iter() {
                 match item {
                     Value::Dictionary(dict) => {
-                        if let Some(key_value) = dict.get(key_str) {
+                        if let Some(key_value) = dict.get(&**key_str) {
                             // Convert the key value to a string for grouping
                             let group_key = match key_value {
-                                Value::String(s) => s.clone(),
+                                Value::String(s) => s.to_string(),
                                 Value::Number(n) => n.to_string(),
+                                Value::Int(n) => n.to_string(),
+                                Value::Decimal(d) => d.normalize().to_string(),
                                 Value::Boolean(b) => b.to_string(),
                                 Value::Nil => "nil".to_string(),
                                 _ => continue, // Skip complex types
@@ -288,10 +664,10 @@ pub fn group_by(array: &Value, key: &Value) -> Result<Value, FunctionError> {
             // Convert HashMap<String, Vec<Value>> to HashMap<String, Value>
             let result: std::collections::HashMap<String, Value> = groups
                 .into_iter()
-                .map(|(k, v)| (k, Value::Array(v)))
+                .map(|(k, v)| (k, Value::Array(v.into())))
                 .collect();
 
-            Ok(Value::Dictionary(result))
+            Ok(Value::Dictionary(result.into()))
         }
         (Value::Array(_), _) => Err(FunctionError::TypeError {
             expected: "String".to_string(),
@@ -311,10 +687,10 @@ pub fn map(array: &Value, key: &Value) -> Result<Value, FunctionError> {
         (Value::Array(arr), Value::String(key_str)) => {
             let mut result = Vec::new();
 
-            for item in arr {
+            for item in arr.iter() {
                 match item {
                     Value::Dictionary(dict) => {
-                        if let Some(value) = dict.get(key_str) {
+                        if let Some(value) = dict.get(&**key_str) {
                             result.push(value.clone());
                         } else {
                             result.push(Value::Nil);
@@ -329,7 +705,7 @@ pub fn map(array: &Value, key: &Value) -> Result<Value, FunctionError> {
                 }
             }
 
-            Ok(Value::Array(result))
+            Ok(Value::Array(result.into()))
         }
         (Value::Array(_), _) => Err(FunctionError::TypeError {
             expected: "String".to_string(),
@@ -342,6 +718,312 @@ pub fn map(array: &Value, key: &Value) -> Result<Value, FunctionError> {
     }
 }
 
+/// Keep only the given keys of a dictionary
+/// pick(dict: Dictionary, keys: Array) -> Dictionary
+pub fn pick(dict: &Value, keys: &Value) -> Result<Value, FunctionError> {
+    match dict {
+        Value::Dictionary(map) => {
+            let key_list = string_keys(keys)?;
+            let picked: std::collections::HashMap<String, Value> = key_list
+                .into_iter()
+                .filter_map(|k| map.get(k).map(|v| (k.to_string(), v.clone())))
+                .collect();
+            Ok(Value::Dictionary(picked.into()))
+        }
+        _ => Err(FunctionError::TypeError {
+            expected: "Dictionary".to_string(),
+            got: dict.type_name().to_string(),
+        }),
+    }
+}
+
+/// Remove the given keys from a dictionary
+/// omit(dict: Dictionary, keys: Array) -> Dictionary
+pub fn omit(dict: &Value, keys: &Value) -> Result<Value, FunctionError> {
+    match dict {
+        Value::Dictionary(map) => {
+            let key_list = string_keys(keys)?;
+            let remaining: std::collections::HashMap<String, Value> = map
+                .iter()
+                .filter(|(k, _)| !key_list.contains(&k.as_str()))
+                .map(|(k, v)| (k.clone(), v.clone()))
+                .collect();
+            Ok(Value::Dictionary(remaining.into()))
+        }
+        _ => Err(FunctionError::TypeError {
+            expected: "Dictionary".to_string(),
+            got: dict.type_name().to_string(),
+        }),
+    }
+}
+
+/// Check whether a dictionary contains a key, even if its value is nil
+/// has_key(dict: Dictionary, key: String) -> Boolean
+pub fn has_key(dict: &Value, key: &Value) -> Result<Value, FunctionError> {
+    match (dict, key) {
+        (Value::Dictionary(map), Value::String(k)) => Ok(Value::Boolean(map.contains_key(&**k))),
+        (Value::Dictionary(_), _) => Err(FunctionError::TypeError {
+            expected: "String".to_string(),
+            got: key.type_name().to_string(),
+        }),
+        _ => Err(FunctionError::TypeError {
+            expected: "Dictionary".to_string(),
+            got: dict.type_name().to_string(),
+        }),
+    }
+}
+
+/// Get the value for a key, or the default when the key is missing
+/// get(dict: Dictionary, key: String, default: Any) -> Any
+///
+/// A key that is present with a nil value returns nil, not the default.
+pub fn get(dict: &Value, key: &Value, default_val: &Value) -> Result<Value, FunctionError> {
+    match (dict, key) {
+        (Value::Dictionary(map), Value::String(k)) => Ok(map
+            .get(&**k)
+            .cloned()
+            .unwrap_or_else(|| default_val.clone())),
+        (Value::Dictionary(_), _) => Err(FunctionError::TypeError {
+            expected: "String".to_string(),
+            got: key.type_name().to_string(),
+        }),
+        _ => Err(FunctionError::TypeError {
+            expected: "Dictionary".to_string(),
+            got: dict.type_name().to_string(),
+        }),
+    }
+}
+
+/// Convert a dictionary into an array of [key, value] pairs, sorted by key
+/// entries(dict: Dictionary) -> Array
+pub fn entries(value: &Value) -> Result<Value, FunctionError> {
+    match value {
+        Value::Dictionary(dict) => {
+            let mut pairs: Vec<(&String, &Value)> = dict.iter().collect();
+            pairs.sort_by(|a, b| a.0.cmp(b.0));
+            let entries: Vec<Value> = pairs
+                .into_iter()
+                .map(|(k, v)| Value::Array(vec![Value::String(k.clone().into()), v.clone()].into()))
+                .collect();
+            Ok(Value::Array(entries.into()))
+        }
+        _ => Err(FunctionError::TypeError {
+            expected: "Dictionary".to_string(),
+            got: value.type_name().to_string(),
+        }),
+    }
+}
+
+/// Build a dictionary from an array of [key, value] pairs
+/// from_entries(pairs: Array) -> Dictionary
+///
+/// Later pairs overwrite earlier pairs with the same key.
+pub fn from_entries(value: &Value) -> Result<Value, FunctionError> {
+    match value {
+        Value::Array(arr) => {
+            let mut dict = std::collections::HashMap::with_capacity(arr.len());
+            for item in arr.iter() {
+                match item {
+                    Value::Array(pair) if pair.len() == 2 => match &pair[0] {
+                        Value::String(k) => {
+                            dict.insert(k.to_string(), pair[1].clone());
+                        }
+                        other => {
+                            return Err(FunctionError::TypeError {
+                                expected: "String key".to_string(),
+                                got: other.type_name().to_string(),
+                            })
+                        }
+                    },
+                    Value::Array(pair) => {
+                        return Err(FunctionError::ValueError {
+                            message: format!(
+                                "Each entry must be a [key, value] pair, got {} elements",
+                                pair.len()
+                            ),
+                        })
+                    }
+                    _ => {
+                        return Err(FunctionError::TypeError {
+                            expected: "Array of [key, value] pairs".to_string(),
+                            got: format!("Array containing {}", item.type_name()),
+                        })
+                    }
+                }
+            }
+            Ok(Value::Dictionary(dict.into()))
+        }
+        _ => Err(FunctionError::TypeError {
+            expected: "Array".to_string(),
+            got: value.type_name().to_string(),
+        }),
+    }
+}
+
+/// Swap the keys and values of a dictionary, stringifying the values
+/// invert(dict: Dictionary) -> Dictionary
+///
+/// Values must be scalars (String, Number, Boolean, Symbol, or Nil). When several
+/// keys share a value, the key that sorts last wins so the result is deterministic.
+pub fn invert(value: &Value) -> Result<Value, FunctionError> {
+    match value {
+        Value::Dictionary(dict) => {
+            let mut pairs: Vec<(&String, &Value)> = dict.iter().collect();
+            pairs.sort_by(|a, b| a.0.cmp(b.0));
+
+            let mut inverted = std::collections::HashMap::with_capacity(pairs.len());
+            for (k, v) in pairs {
+                let new_key = match v {
+                    Value::String(s) => s.to_string(),
+                    Value::Number(n) => n.to_string(),
+                    Value::Boolean(b) => b.to_string(),
+                    Value::Symbol(s) => s.clone(),
+                    Value::Nil => "nil".to_string(),
+                    _ => {
+                        return Err(FunctionError::TypeError {
+                            expected: "Dictionary with scalar values".to_string(),
+                            got: format!("Dictionary containing {}", v.type_name()),
+                        })
+                    }
+                };
+                inverted.insert(new_key, Value::String(k.clone().into()));
+            }
+            Ok(Value::Dictionary(inverted.into()))
+        }
+        _ => Err(FunctionError::TypeError {
+            expected: "Dictionary".to_string(),
+            got: value.type_name().to_string(),
+        }),
+    }
+}
+
+/// Navigate into nested dictionaries and arrays by a dynamic path
+/// dig(data: Any, path: Array) -> Any
+///
+/// String path elements index dictionaries and Number path elements index arrays
+/// (negative indexes count from the end, as in `at`). Like dot navigation, a
+/// missing key, out-of-range or fractional index, or mismatched container
+/// yields nil.
+pub fn dig(data: &Value, path: &Value) -> Result<Value, FunctionError> {
+    let steps = match path {
+        Value::Array(steps) => steps,
+        _ => {
+            return Err(FunctionError::TypeError {
+                expected: "Array".to_string(),
+                got: path.type_name().to_string(),
+            })
+        }
+    };
+
+    let mut current = data;
+    for step in steps.iter() {
+        let next = match (current, step, index_value(step)) {
+            (Value::Dictionary(map), Value::String(key), _) => map.get(&**key),
+            (Value::Array(arr), _, Some(i)) if step.as_f64().is_some_and(|n| n.fract() == 0.0) => {
+                let actual_index = if i < 0 { arr.len() as i64 + i } else { i };
+                if actual_index < 0 {
+                    None
+                } else {
+                    arr.get(actual_index as usize)
+                }
+            }
+            (_, Value::String(_), _) | (_, _, Some(_)) => None,
+            _ => {
+                return Err(FunctionError::TypeError {
+                    expected: "Array of Strings or Numbers".to_string(),
+                    got: format!("Array containing {}", step.type_name()),
+                })
+            }
+        };
+
+        match next {
+            Some(value) => current = value,
+            None => return Ok(Value::Nil),
+        }
+    }
+
+    Ok(current.clone())
+}
+
+/// Combine two arrays, keeping the first occurrence of each element
+/// union(a: Array, b: Array) -> Array
+///
+/// Uses the same value equality as `uniq`, and preserves first-seen order.
+pub fn union(left: &Value, right: &Value) -> Result<Value, FunctionError> {
+    let (a, b) = array_pair(left, right)?;
+    let mut result: Vec<Value> = Vec::new();
+    for item in a.iter().chain(b.iter()) {
+        if !result.contains(item) {
+            result.push(item.clone());
+        }
+    }
+    Ok(Value::Array(result.into()))
+}
+
+/// Elements of the first array that also appear in the second
+/// intersection(a: Array, b: Array) -> Array
+pub fn intersection(left: &Value, right: &Value) -> Result<Value, FunctionError> {
+    let (a, b) = array_pair(left, right)?;
+    let mut result: Vec<Value> = Vec::new();
+    for item in a {
+        if b.contains(item) && !result.contains(item) {
+            result.push(item.clone());
+        }
+    }
+    Ok(Value::Array(result.into()))
+}
+
+/// Elements of the first array that do not appear in the second
+/// difference(a: Array, b: Array) -> Array
+pub fn difference(left: &Value, right: &Value) -> Result<Value, FunctionError> {
+    let (a, b) = array_pair(left, right)?;
+    let mut result: Vec<Value> = Vec::new();
+    for item in a {
+        if !b.contains(item) && !result.contains(item) {
+            result.push(item.clone());
+        }
+    }
+    Ok(Value::Array(result.into()))
+}
+
+/// Unpack two Array arguments for the set operations
+fn array_pair<'a>(
+    left: &'a Value,
+    right: &'a Value,
+) -> Result<(&'a Vec<Value>, &'a Vec<Value>), FunctionError> {
+    match (left, right) {
+        (Value::Array(a), Value::Array(b)) => Ok((a, b)),
+        (Value::Array(_), _) => Err(FunctionError::TypeError {
+            expected: "Array".to_string(),
+            got: right.type_name().to_string(),
+        }),
+        _ => Err(FunctionError::TypeError {
+            expected: "Array".to_string(),
+            got: left.type_name().to_string(),
+        }),
+    }
+}
+
+/// Extract a list of string keys from an Array argument
+fn string_keys(keys: &Value) -> Result<Vec<&str>, FunctionError> {
+    match keys {
+        Value::Array(arr) => arr
+            .iter()
+            .map(|k| match k {
+                Value::String(s) => Ok(&**s),
+                _ => Err(FunctionError::TypeError {
+                    expected: "Array of Strings".to_string(),
+                    got: format!("Array containing {}", k.type_name()),
+                }),
+            })
+            .collect(),
+        _ => Err(FunctionError::TypeError {
+            expected: "Array".to_string(),
+            got: keys.type_name().to_string(),
+        }),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -350,15 +1032,13 @@ mod tests {
     #[test]
     fn test_size() {
         assert_eq!(
-            size(&Value::String("hello".to_string())).unwrap(),
+            size(&Value::String("hello".into())).unwrap(),
             Value::Number(5.0)
         );
         assert_eq!(
-            size(&Value::Array(vec![
-                Value::Number(1.0),
-                Value::Number(2.0),
-                Value::Number(3.0)
-            ]))
+            size(&Value::Array(
+                vec![Value::Number(1.0), Value::Number(2.0), Value::Number(3.0)].into()
+            ))
             .unwrap(),
             Value::Number(3.0)
         );
@@ -366,25 +1046,25 @@ mod tests {
 
     #[test]
     fn test_first() {
-        let arr = Value::Array(vec![Value::Number(1.0), Value::Number(2.0)]);
+        let arr = Value::Array(vec![Value::Number(1.0), Value::Number(2.0)].into());
         assert_eq!(first(&arr).unwrap(), Value::Number(1.0));
 
-        let empty = Value::Array(vec![]);
+        let empty = Value::Array(vec![].into());
         assert_eq!(first(&empty).unwrap(), Value::Nil);
     }
 
     #[test]
     fn test_last() {
-        let arr = Value::Array(vec![Value::Number(1.0), Value::Number(2.0)]);
+        let arr = Value::Array(vec![Value::Number(1.0), Value::Number(2.0)].into());
         assert_eq!(last(&arr).unwrap(), Value::Number(2.0));
 
-        let empty = Value::Array(vec![]);
+        let empty = Value::Array(vec![].into());
         assert_eq!(last(&empty).unwrap(), Value::Nil);
     }
 
     #[test]
     fn test_contains() {
-        let arr = Value::Array(vec![Value::Number(1.0), Value::Number(2.0)]);
+        let arr = Value::Array(vec![Value::Number(1.0), Value::Number(2.0)].into());
         assert_eq!(
             contains(&arr, &Value::Number(2.0)).unwrap(),
             Value::Boolean(true)
@@ -397,73 +1077,310 @@ mod tests {
 
     #[test]
     fn test_contains_string() {
-        let s = Value::String("hello world".to_string());
+        let s = Value::String("hello world".into());
         assert_eq!(
-            contains(&s, &Value::String("world".to_string())).unwrap(),
+            contains(&s, &Value::String("world".into())).unwrap(),
             Value::Boolean(true)
         );
         assert_eq!(
-            contains(&s, &Value::String("foo".to_string())).unwrap(),
+            contains(&s, &Value::String("foo".into())).unwrap(),
             Value::Boolean(false)
         );
     }
 
     #[test]
     fn test_sum() {
-        let arr = Value::Array(vec![
-            Value::Number(1.0),
-            Value::Number(2.0),
-            Value::Number(3.0),
-        ]);
+        let arr =
+            Value::Array(vec![Value::Number(1.0), Value::Number(2.0), Value::Number(3.0)].into());
         assert_eq!(sum(&arr).unwrap(), Value::Number(6.0));
     }
 
     #[test]
     fn test_avg() {
-        let arr = Value::Array(vec![
-            Value::Number(1.0),
-            Value::Number(2.0),
-            Value::Number(3.0),
-        ]);
+        let arr =
+            Value::Array(vec![Value::Number(1.0), Value::Number(2.0), Value::Number(3.0)].into());
         assert_eq!(avg(&arr).unwrap(), Value::Number(2.0));
 
-        let empty = Value::Array(vec![]);
+        let empty = Value::Array(vec![].into());
         assert_eq!(avg(&empty).unwrap(), Value::Nil);
     }
 
+    #[test]
+    fn test_weighted_average() {
+        let values = Value::Array(vec![Value::Number(10.0), Value::Number(20.0)].into());
+        let weights = Value::Array(vec![Value::Number(3.0), Value::Number(1.0)].into());
+        assert_eq!(
+            weighted_average(&values, &weights).unwrap(),
+            Value::Number(12.5)
+        );
+
+        let record = |value: f64, weight: f64| {
+            let mut dict = HashMap::new();
+            dict.insert("value".to_string(), Value::Number(value));
+            dict.insert("weight".to_string(), Value::Number(weight));
+            Value::Dictionary(dict.into())
+        };
+        let records = Value::Array(vec![record(10.0, 3.0), record(20.0, 1.0)].into());
+        assert_eq!(
+            weighted_average(&records, &Value::Nil).unwrap(),
+            Value::Number(12.5)
+        );
+
+        let empty = Value::Array(vec![].into());
+        assert_eq!(weighted_average(&empty, &empty).unwrap(), Value::Nil);
+
+        let short = Value::Array(vec![Value::Number(1.0)].into());
+        assert!(matches!(
+            weighted_average(&values, &short),
+            Err(FunctionError::ArgumentError { .. })
+        ));
+        let zero = Value::Array(vec![Value::Number(0.0), Value::Number(0.0)].into());
+        assert!(matches!(
+            weighted_average(&values, &zero),
+            Err(FunctionError::ArgumentError { .. })
+        ));
+        assert!(matches!(
+            weighted_average(&values, &Value::Nil),
+            Err(FunctionError::TypeError { .. })
+        ));
+    }
+
+    #[test]
+    fn test_median() {
+        let odd =
+            Value::Array(vec![Value::Number(3.0), Value::Number(1.0), Value::Number(2.0)].into());
+        assert_eq!(median(&odd).unwrap(), Value::Number(2.0));
+
+        let even = Value::Array(
+            vec![
+                Value::Number(4.0),
+                Value::Number(1.0),
+                Value::Number(3.0),
+                Value::Number(2.0),
+            ]
+            .into(),
+        );
+        assert_eq!(median(&even).unwrap(), Value::Number(2.5));
+
+        assert_eq!(median(&Value::Array(vec![].into())).unwrap(), Value::Nil);
+
+        let mixed = Value::Array(vec![Value::Number(1.0), Value::String("2".into())].into());
+        assert!(matches!(
+            median(&mixed),
+            Err(FunctionError::TypeError { .. })
+        ));
+    }
+
+    #[test]
+    fn test_mode() {
+        let arr = Value::Array(
+            vec![
+                Value::Number(1.0),
+                Value::Number(2.0),
+                Value::Number(2.0),
+                Value::Number(3.0),
+            ]
+            .into(),
+        );
+        assert_eq!(mode(&arr).unwrap(), Value::Number(2.0));
+
+        // Ties resolve to the first value seen
+        let tied = Value::Array(
+            vec![
+                Value::String("b".into()),
+                Value::String("a".into()),
+                Value::String("a".into()),
+                Value::String("b".into()),
+            ]
+            .into(),
+        );
+        assert_eq!(mode(&tied).unwrap(), Value::String("b".into()));
+
+        // Mixed types are counted separately
+        let mixed = Value::Array(
+            vec![
+                Value::Number(1.0),
+                Value::String("1".into()),
+                Value::String("1".into()),
+            ]
+            .into(),
+        );
+        assert_eq!(mode(&mixed).unwrap(), Value::String("1".into()));
+
+        assert_eq!(mode(&Value::Array(vec![].into())).unwrap(), Value::Nil);
+        assert!(mode(&Value::Number(1.0)).is_err());
+    }
+
+    #[test]
+    fn test_percentile_and_quantile() {
+        let arr = Value::Array(
+            [15.0, 20.0, 35.0, 40.0, 50.0]
+                .into_iter()
+                .map(Value::Number)
+                .collect::<Vec<_>>()
+                .into(),
+        );
+        assert_eq!(
+            percentile(&arr, &Value::Number(0.0)).unwrap(),
+            Value::Number(15.0)
+        );
+        assert_eq!(
+            percentile(&arr, &Value::Number(40.0)).unwrap(),
+            Value::Number(29.0)
+        );
+        assert_eq!(
+            percentile(&arr, &Value::Number(100.0)).unwrap(),
+            Value::Number(50.0)
+        );
+        assert_eq!(
+            quantile(&arr, &Value::Number(0.5)).unwrap(),
+            Value::Number(35.0)
+        );
+
+        assert_eq!(
+            percentile(&Value::Array(vec![].into()), &Value::Number(50.0)).unwrap(),
+            Value::Nil
+        );
+        assert!(matches!(
+            percentile(&arr, &Value::Number(101.0)),
+            Err(FunctionError::ArgumentError { .. })
+        ));
+        assert!(matches!(
+            quantile(&arr, &Value::Number(-0.1)),
+            Err(FunctionError::ArgumentError { .. })
+        ));
+        assert!(matches!(
+            percentile(&arr, &Value::String("95".into())),
+            Err(FunctionError::TypeError { .. })
+        ));
+    }
+
+    #[test]
+    fn test_variance_and_stddev() {
+        let arr = Value::Array(
+            [2.0, 4.0, 4.0, 4.0, 5.0, 5.0, 7.0, 9.0]
+                .into_iter()
+                .map(Value::Number)
+                .collect::<Vec<_>>()
+                .into(),
+        );
+        assert_eq!(variance(&arr).unwrap(), Value::Number(4.0));
+        assert_eq!(stddev(&arr).unwrap(), Value::Number(2.0));
+
+        match variance_sample(&arr).unwrap() {
+            Value::Number(n) => assert!((n - 32.0 / 7.0).abs() < 1e-12),
+            other => panic!("expected number, got {:?}", other),
+        }
+        match stddev_sample(&arr).unwrap() {
+            Value::Number(n) => assert!((n - (32.0f64 / 7.0).sqrt()).abs() < 1e-12),
+            other => panic!("expected number, got {:?}", other),
+        }
+
+        let single = Value::Array(vec![Value::Number(5.0)].into());
+        assert_eq!(variance(&single).unwrap(), Value::Number(0.0));
+        assert_eq!(variance_sample(&single).unwrap(), Value::Nil);
+        assert_eq!(stddev(&Value::Array(vec![].into())).unwrap(), Value::Nil);
+
+        let mixed = Value::Array(vec![Value::Number(1.0), Value::Nil].into());
+        assert!(matches!(
+            stddev(&mixed),
+            Err(FunctionError::TypeError { .. })
+        ));
+    }
+
+    #[test]
+    fn test_covariance_and_correlation() {
+        let numbers = |items: &[f64]| {
+            Value::Array(
+                items
+                    .iter()
+                    .copied()
+                    .map(Value::Number)
+                    .collect::<Vec<_>>()
+                    .into(),
+            )
+        };
+        let xs = numbers(&[1.0, 2.0, 3.0, 4.0]);
+        let ys = numbers(&[2.0, 4.0, 6.0, 8.0]);
+
+        assert_eq!(covariance(&xs, &ys).unwrap(), Value::Number(2.5));
+        match covariance_sample(&xs, &ys).unwrap() {
+            Value::Number(n) => assert!((n - 10.0 / 3.0).abs() < 1e-12),
+            other => panic!("expected number, got {:?}", other),
+        }
+        assert_eq!(correlation(&xs, &ys).unwrap(), Value::Number(1.0));
+
+        let inverse = numbers(&[8.0, 6.0, 4.0, 2.0]);
+        assert_eq!(correlation(&xs, &inverse).unwrap(), Value::Number(-1.0));
+
+        let flat = numbers(&[5.0, 5.0, 5.0, 5.0]);
+        assert_eq!(correlation(&xs, &flat).unwrap(), Value::Nil);
+        assert_eq!(
+            covariance(&numbers(&[]), &numbers(&[])).unwrap(),
+            Value::Nil
+        );
+
+        assert!(matches!(
+            covariance(&xs, &numbers(&[1.0])),
+            Err(FunctionError::ArgumentError { .. })
+        ));
+    }
+
     #[test]
     fn test_sort_numbers() {
-        let arr = Value::Array(vec![
-            Value::Number(3.0),
-            Value::Number(1.0),
-            Value::Number(2.0),
-        ]);
+        let arr =
+            Value::Array(vec![Value::Number(3.0), Value::Number(1.0), Value::Number(2.0)].into());
         let sorted = sort(&arr).unwrap();
         assert_eq!(
             sorted,
-            Value::Array(vec![
-                Value::Number(1.0),
-                Value::Number(2.0),
-                Value::Number(3.0)
-            ])
+            Value::Array(vec![Value::Number(1.0), Value::Number(2.0), Value::Number(3.0)].into())
+        );
+    }
+
+    #[test]
+    fn test_sort_mixed_numbers() {
+        let big = 9_007_199_254_740_993;
+        let arr = Value::Array(
+            vec![
+                Value::Int(big),
+                Value::Number(2.5),
+                Value::Int(big - 1),
+                Value::Decimal("0.1".parse().unwrap()),
+            ]
+            .into(),
         );
+        let sorted = sort(&arr).unwrap();
+        assert!(matches!(
+            &sorted,
+            Value::Array(items) if matches!(
+                &items[..],
+                [Value::Decimal(_), Value::Number(_), Value::Int(a), Value::Int(b)]
+                    if *a == big - 1 && *b == big
+            )
+        ));
     }
 
     #[test]
     fn test_sort_strings() {
-        let arr = Value::Array(vec![
-            Value::String("c".to_string()),
-            Value::String("a".to_string()),
-            Value::String("b".to_string()),
-        ]);
+        let arr = Value::Array(
+            vec![
+                Value::String("c".into()),
+                Value::String("a".into()),
+                Value::String("b".into()),
+            ]
+            .into(),
+        );
         let sorted = sort(&arr).unwrap();
         assert_eq!(
             sorted,
-            Value::Array(vec![
-                Value::String("a".to_string()),
-                Value::String("b".to_string()),
-                Value::String("c".to_string()),
-            ])
+            Value::Array(
+                vec![
+                    Value::String("a".into()),
+                    Value::String("b".into()),
+                    Value::String("c".into()),
+                ]
+                .into()
+            )
         );
     }
 
@@ -472,16 +1389,13 @@ mod tests {
         let mut dict = HashMap::new();
         dict.insert("a".to_string(), Value::Number(1.0));
         dict.insert("b".to_string(), Value::Number(2.0));
-        let d = Value::Dictionary(dict);
+        let d = Value::Dictionary(dict.into());
 
         let result = keys(&d).unwrap();
         // Keys should be sorted
         assert_eq!(
             result,
-            Value::Array(vec![
-                Value::String("a".to_string()),
-                Value::String("b".to_string()),
-            ])
+            Value::Array(vec![Value::String("a".into()), Value::String("b".into()),].into())
         );
     }
 
@@ -489,7 +1403,7 @@ mod tests {
     fn test_values() {
         let mut dict = HashMap::new();
         dict.insert("a".to_string(), Value::Number(1.0));
-        let d = Value::Dictionary(dict);
+        let d = Value::Dictionary(dict.into());
 
         let result = values(&d).unwrap();
         assert!(matches!(result, Value::Array(_)));
@@ -497,29 +1411,19 @@ mod tests {
 
     #[test]
     fn test_reverse() {
-        let arr = Value::Array(vec![
-            Value::Number(1.0),
-            Value::Number(2.0),
-            Value::Number(3.0),
-        ]);
+        let arr =
+            Value::Array(vec![Value::Number(1.0), Value::Number(2.0), Value::Number(3.0)].into());
         let reversed = reverse(&arr).unwrap();
         assert_eq!(
             reversed,
-            Value::Array(vec![
-                Value::Number(3.0),
-                Value::Number(2.0),
-                Value::Number(1.0)
-            ])
+            Value::Array(vec![Value::Number(3.0), Value::Number(2.0), Value::Number(1.0)].into())
         );
     }
 
     #[test]
     fn test_at() {
-        let arr = Value::Array(vec![
-            Value::Number(1.0),
-            Value::Number(2.0),
-            Value::Number(3.0),
-        ]);
+        let arr =
+            Value::Array(vec![Value::Number(1.0), Value::Number(2.0), Value::Number(3.0)].into());
 
         assert_eq!(at(&arr, &Value::Number(0.0)).unwrap(), Value::Number(1.0));
         assert_eq!(at(&arr, &Value::Number(2.0)).unwrap(), Value::Number(3.0));
@@ -527,71 +1431,83 @@ mod tests {
 
         // Test negative indexing
         assert_eq!(at(&arr, &Value::Number(-1.0)).unwrap(), Value::Number(3.0));
+
+        // Integer indexes work as well
+        assert_eq!(at(&arr, &Value::Int(1)).unwrap(), Value::Number(2.0));
+        assert_eq!(at(&arr, &Value::Int(-3)).unwrap(), Value::Number(1.0));
     }
 
     #[test]
     fn test_uniq() {
-        let arr = Value::Array(vec![
-            Value::Number(1.0),
-            Value::Number(2.0),
-            Value::Number(1.0),
-            Value::Number(3.0),
-            Value::Number(2.0),
-        ]);
-        let result = uniq(&arr).unwrap();
-        assert_eq!(
-            result,
-            Value::Array(vec![
+        let arr = Value::Array(
+            vec![
                 Value::Number(1.0),
                 Value::Number(2.0),
+                Value::Number(1.0),
                 Value::Number(3.0),
-            ])
+                Value::Number(2.0),
+            ]
+            .into(),
+        );
+        let result = uniq(&arr).unwrap();
+        assert_eq!(
+            result,
+            Value::Array(vec![Value::Number(1.0), Value::Number(2.0), Value::Number(3.0),].into())
         );
 
         // Test with strings
-        let arr_str = Value::Array(vec![
-            Value::String("a".to_string()),
-            Value::String("b".to_string()),
-            Value::String("a".to_string()),
-            Value::String("c".to_string()),
-        ]);
+        let arr_str = Value::Array(
+            vec![
+                Value::String("a".into()),
+                Value::String("b".into()),
+                Value::String("a".into()),
+                Value::String("c".into()),
+            ]
+            .into(),
+        );
         let result_str = uniq(&arr_str).unwrap();
         assert_eq!(
             result_str,
-            Value::Array(vec![
-                Value::String("a".to_string()),
-                Value::String("b".to_string()),
-                Value::String("c".to_string()),
-            ])
+            Value::Array(
+                vec![
+                    Value::String("a".into()),
+                    Value::String("b".into()),
+                    Value::String("c".into()),
+                ]
+                .into()
+            )
         );
 
         // Test empty array
-        let empty = Value::Array(vec![]);
-        assert_eq!(uniq(&empty).unwrap(), Value::Array(vec![]));
+        let empty = Value::Array(vec![].into());
+        assert_eq!(uniq(&empty).unwrap(), Value::Array(vec![].into()));
     }
 
     #[test]
     fn test_group_by() {
         // Create test data: array of dictionaries with a "type" field
         let mut dict1 = HashMap::new();
-        dict1.insert("type".to_string(), Value::String("fruit".to_string()));
-        dict1.insert("name".to_string(), Value::String("apple".to_string()));
+        dict1.insert("type".to_string(), Value::String("fruit".into()));
+        dict1.insert("name".to_string(), Value::String("apple".into()));
 
         let mut dict2 = HashMap::new();
-        dict2.insert("type".to_string(), Value::String("vegetable".to_string()));
-        dict2.insert("name".to_string(), Value::String("carrot".to_string()));
+        dict2.insert("type".to_string(), Value::String("vegetable".into()));
+        dict2.insert("name".to_string(), Value::String("carrot".into()));
 
         let mut dict3 = HashMap::new();
-        dict3.insert("type".to_string(), Value::String("fruit".to_string()));
-        dict3.insert("name".to_string(), Value::String("banana".to_string()));
-
-        let arr = Value::Array(vec![
-            Value::Dictionary(dict1.clone()),
-            Value::Dictionary(dict2.clone()),
-            Value::Dictionary(dict3.clone()),
-        ]);
+        dict3.insert("type".to_string(), Value::String("fruit".into()));
+        dict3.insert("name".to_string(), Value::String("banana".into()));
+
+        let arr = Value::Array(
+            vec![
+                Value::Dictionary(dict1.clone().into()),
+                Value::Dictionary(dict2.clone().into()),
+                Value::Dictionary(dict3.clone().into()),
+            ]
+            .into(),
+        );
 
-        let result = group_by(&arr, &Value::String("type".to_string())).unwrap();
+        let result = group_by(&arr, &Value::String("type".into())).unwrap();
 
         match result {
             Value::Dictionary(groups) => {
@@ -615,23 +1531,26 @@ mod tests {
     fn test_group_by_with_numbers() {
         let mut dict1 = HashMap::new();
         dict1.insert("score".to_string(), Value::Number(100.0));
-        dict1.insert("name".to_string(), Value::String("alice".to_string()));
+        dict1.insert("name".to_string(), Value::String("alice".into()));
 
         let mut dict2 = HashMap::new();
         dict2.insert("score".to_string(), Value::Number(95.0));
-        dict2.insert("name".to_string(), Value::String("bob".to_string()));
+        dict2.insert("name".to_string(), Value::String("bob".into()));
 
         let mut dict3 = HashMap::new();
         dict3.insert("score".to_string(), Value::Number(100.0));
-        dict3.insert("name".to_string(), Value::String("charlie".to_string()));
-
-        let arr = Value::Array(vec![
-            Value::Dictionary(dict1),
-            Value::Dictionary(dict2),
-            Value::Dictionary(dict3),
-        ]);
+        dict3.insert("name".to_string(), Value::String("charlie".into()));
+
+        let arr = Value::Array(
+            vec![
+                Value::Dictionary(dict1.into()),
+                Value::Dictionary(dict2.into()),
+                Value::Dictionary(dict3.into()),
+            ]
+            .into(),
+        );
 
-        let result = group_by(&arr, &Value::String("score".to_string())).unwrap();
+        let result = group_by(&arr, &Value::String("score".into())).unwrap();
 
         match result {
             Value::Dictionary(groups) => {
@@ -654,59 +1573,485 @@ mod tests {
     #[test]
     fn test_map() {
         let mut dict1 = HashMap::new();
-        dict1.insert("name".to_string(), Value::String("alice".to_string()));
+        dict1.insert("name".to_string(), Value::String("alice".into()));
         dict1.insert("age".to_string(), Value::Number(30.0));
 
         let mut dict2 = HashMap::new();
-        dict2.insert("name".to_string(), Value::String("bob".to_string()));
+        dict2.insert("name".to_string(), Value::String("bob".into()));
         dict2.insert("age".to_string(), Value::Number(25.0));
 
         let mut dict3 = HashMap::new();
-        dict3.insert("name".to_string(), Value::String("charlie".to_string()));
+        dict3.insert("name".to_string(), Value::String("charlie".into()));
         dict3.insert("age".to_string(), Value::Number(35.0));
 
-        let arr = Value::Array(vec![
-            Value::Dictionary(dict1),
-            Value::Dictionary(dict2),
-            Value::Dictionary(dict3),
-        ]);
+        let arr = Value::Array(
+            vec![
+                Value::Dictionary(dict1.into()),
+                Value::Dictionary(dict2.into()),
+                Value::Dictionary(dict3.into()),
+            ]
+            .into(),
+        );
 
         // Test mapping "name" key
-        let result = map(&arr, &Value::String("name".to_string())).unwrap();
+        let result = map(&arr, &Value::String("name".into())).unwrap();
         assert_eq!(
             result,
-            Value::Array(vec![
-                Value::String("alice".to_string()),
-                Value::String("bob".to_string()),
-                Value::String("charlie".to_string()),
-            ])
+            Value::Array(
+                vec![
+                    Value::String("alice".into()),
+                    Value::String("bob".into()),
+                    Value::String("charlie".into()),
+                ]
+                .into()
+            )
         );
 
         // Test mapping "age" key
-        let result = map(&arr, &Value::String("age".to_string())).unwrap();
+        let result = map(&arr, &Value::String("age".into())).unwrap();
         assert_eq!(
             result,
-            Value::Array(vec![
-                Value::Number(30.0),
-                Value::Number(25.0),
-                Value::Number(35.0),
-            ])
+            Value::Array(
+                vec![
+                    Value::Number(30.0),
+                    Value::Number(25.0),
+                    Value::Number(35.0),
+                ]
+                .into()
+            )
         );
     }
 
     #[test]
     fn test_map_missing_key() {
         let mut dict1 = HashMap::new();
-        dict1.insert("name".to_string(), Value::String("alice".to_string()));
+        dict1.insert("name".to_string(), Value::String("alice".into()));
 
         let mut dict2 = HashMap::new();
-        dict2.insert("name".to_string(), Value::String("bob".to_string()));
+        dict2.insert("name".to_string(), Value::String("bob".into()));
         dict2.insert("age".to_string(), Value::Number(25.0));
 
-        let arr = Value::Array(vec![Value::Dictionary(dict1), Value::Dictionary(dict2)]);
+        let arr = Value::Array(
+            vec![
+                Value::Dictionary(dict1.into()),
+                Value::Dictionary(dict2.into()),
+            ]
+            .into(),
+        );
 
         // Test mapping "age" key where first dict doesn't have it
-        let result = map(&arr, &Value::String("age".to_string())).unwrap();
-        assert_eq!(result, Value::Array(vec![Value::Nil, Value::Number(25.0),]));
+        let result = map(&arr, &Value::String("age".into())).unwrap();
+        assert_eq!(
+            result,
+            Value::Array(vec![Value::Nil, Value::Number(25.0),].into())
+        );
+    }
+
+    #[test]
+    fn test_pick() {
+        let mut dict = HashMap::new();
+        dict.insert("a".to_string(), Value::Number(1.0));
+        dict.insert("b".to_string(), Value::Number(2.0));
+        dict.insert("c".to_string(), Value::Number(3.0));
+        let d = Value::Dictionary(dict.into());
+
+        let keys = Value::Array(
+            vec![
+                Value::String("a".into()),
+                Value::String("c".into()),
+                Value::String("missing".into()),
+            ]
+            .into(),
+        );
+        let mut expected = HashMap::new();
+        expected.insert("a".to_string(), Value::Number(1.0));
+        expected.insert("c".to_string(), Value::Number(3.0));
+        assert_eq!(pick(&d, &keys).unwrap(), Value::Dictionary(expected.into()));
+
+        // Type errors
+        assert!(matches!(
+            pick(&Value::Array(vec![].into()), &keys),
+            Err(FunctionError::TypeError { .. })
+        ));
+        assert!(matches!(
+            pick(&d, &Value::String("a".into())),
+            Err(FunctionError::TypeError { .. })
+        ));
+        assert!(matches!(
+            pick(&d, &Value::Array(vec![Value::Number(1.0)].into())),
+            Err(FunctionError::TypeError { .. })
+        ));
+    }
+
+    #[test]
+    fn test_omit() {
+        let mut dict = HashMap::new();
+        dict.insert("a".to_string(), Value::Number(1.0));
+        dict.insert("b".to_string(), Value::Number(2.0));
+        dict.insert("c".to_string(), Value::Number(3.0));
+        let d = Value::Dictionary(dict.into());
+
+        let keys =
+            Value::Array(vec![Value::String("b".into()), Value::String("missing".into())].into());
+        let mut expected = HashMap::new();
+        expected.insert("a".to_string(), Value::Number(1.0));
+        expected.insert("c".to_string(), Value::Number(3.0));
+        assert_eq!(omit(&d, &keys).unwrap(), Value::Dictionary(expected.into()));
+
+        assert!(matches!(
+            omit(&Value::Nil, &keys),
+            Err(FunctionError::TypeError { .. })
+        ));
+    }
+
+    #[test]
+    fn test_has_key() {
+        let mut dict = HashMap::new();
+        dict.insert("a".to_string(), Value::Number(1.0));
+        dict.insert("empty".to_string(), Value::Nil);
+        let d = Value::Dictionary(dict.into());
+
+        assert_eq!(
+            has_key(&d, &Value::String("a".into())).unwrap(),
+            Value::Boolean(true)
+        );
+        assert_eq!(
+            has_key(&d, &Value::String("empty".into())).unwrap(),
+            Value::Boolean(true)
+        );
+        assert_eq!(
+            has_key(&d, &Value::String("missing".into())).unwrap(),
+            Value::Boolean(false)
+        );
+        assert!(matches!(
+            has_key(&d, &Value::Number(1.0)),
+            Err(FunctionError::TypeError { .. })
+        ));
+        assert!(matches!(
+            has_key(&Value::Nil, &Value::String("a".into())),
+            Err(FunctionError::TypeError { .. })
+        ));
+    }
+
+    #[test]
+    fn test_get() {
+        let mut dict = HashMap::new();
+        dict.insert("a".to_string(), Value::Number(1.0));
+        dict.insert("empty".to_string(), Value::Nil);
+        let d = Value::Dictionary(dict.into());
+        let fallback = Value::String("fallback".into());
+
+        assert_eq!(
+            get(&d, &Value::String("a".into()), &fallback).unwrap(),
+            Value::Number(1.0)
+        );
+        // Present-but-nil is not the same as missing
+        assert_eq!(
+            get(&d, &Value::String("empty".into()), &fallback).unwrap(),
+            Value::Nil
+        );
+        assert_eq!(
+            get(&d, &Value::String("missing".into()), &fallback).unwrap(),
+            fallback
+        );
+        assert!(matches!(
+            get(
+                &Value::Array(vec![].into()),
+                &Value::String("a".into()),
+                &fallback
+            ),
+            Err(FunctionError::TypeError { .. })
+        ));
+    }
+
+    #[test]
+    fn test_entries() {
+        let mut dict = HashMap::new();
+        dict.insert("b".to_string(), Value::Number(2.0));
+        dict.insert("a".to_string(), Value::Number(1.0));
+        let d = Value::Dictionary(dict.into());
+
+        assert_eq!(
+            entries(&d).unwrap(),
+            Value::Array(
+                vec![
+                    Value::Array(vec![Value::String("a".into()), Value::Number(1.0)].into()),
+                    Value::Array(vec![Value::String("b".into()), Value::Number(2.0)].into()),
+                ]
+                .into()
+            )
+        );
+        assert_eq!(
+            entries(&Value::Dictionary(HashMap::new().into())).unwrap(),
+            Value::Array(vec![].into())
+        );
+        assert!(matches!(
+            entries(&Value::Array(vec![].into())),
+            Err(FunctionError::TypeError { .. })
+        ));
+    }
+
+    #[test]
+    fn test_from_entries() {
+        let pairs = Value::Array(
+            vec![
+                Value::Array(vec![Value::String("a".into()), Value::Number(1.0)].into()),
+                Value::Array(vec![Value::String("b".into()), Value::Number(2.0)].into()),
+                Value::Array(vec![Value::String("a".into()), Value::Number(3.0)].into()),
+            ]
+            .into(),
+        );
+        let mut expected = HashMap::new();
+        expected.insert("a".to_string(), Value::Number(3.0));
+        expected.insert("b".to_string(), Value::Number(2.0));
+        assert_eq!(
+            from_entries(&pairs).unwrap(),
+            Value::Dictionary(expected.into())
+        );
+
+        // Round trip
+        let mut dict = HashMap::new();
+        dict.insert("x".to_string(), Value::Boolean(true));
+        let d = Value::Dictionary(dict.into());
+        assert_eq!(from_entries(&entries(&d).unwrap()).unwrap(), d);
+
+        // Errors
+        assert!(matches!(
+            from_entries(&Value::Array(
+                vec![Value::Array(vec![Value::String("a".into())].into())].into()
+            )),
+            Err(FunctionError::ValueError { .. })
+        ));
+        assert!(matches!(
+            from_entries(&Value::Array(
+                vec![Value::Array(
+                    vec![Value::Number(1.0), Value::Number(2.0)].into()
+                )]
+                .into()
+            )),
+            Err(FunctionError::TypeError { .. })
+        ));
+        assert!(matches!(
+            from_entries(&Value::Array(vec![Value::Number(1.0)].into())),
+            Err(FunctionError::TypeError { .. })
+        ));
+    }
+
+    #[test]
+    fn test_invert() {
+        let mut dict = HashMap::new();
+        dict.insert("CA".to_string(), Value::String("California".into()));
+        dict.insert("one".to_string(), Value::Number(1.0));
+        dict.insert("flag".to_string(), Value::Boolean(true));
+        dict.insert("decision".to_string(), Value::Symbol("approve".to_string()));
+        let d = Value::Dictionary(dict.into());
+
+        let mut expected = HashMap::new();
+        expected.insert("California".to_string(), Value::String("CA".into()));
+        expected.insert("1".to_string(), Value::String("one".into()));
+        expected.insert("true".to_string(), Value::String("flag".into()));
+        expected.insert("approve".to_string(), Value::String("decision".into()));
+        assert_eq!(invert(&d).unwrap(), Value::Dictionary(expected.into()));
+
+        // Duplicate values: the last key in sorted order wins
+        let mut dict = HashMap::new();
+        dict.insert("a".to_string(), Value::Number(1.0));
+        dict.insert("b".to_string(), Value::Number(1.0));
+        let mut expected = HashMap::new();
+        expected.insert("1".to_string(), Value::String("b".into()));
+        assert_eq!(
+            invert(&Value::Dictionary(dict.into())).unwrap(),
+            Value::Dictionary(expected.into())
+        );
+
+        // Non-scalar values are rejected
+        let mut dict = HashMap::new();
+        dict.insert("a".to_string(), Value::Array(vec![].into()));
+        assert!(matches!(
+            invert(&Value::Dictionary(dict.into())),
+            Err(FunctionError::TypeError { .. })
+        ));
+        assert!(matches!(
+            invert(&Value::Nil),
+            Err(FunctionError::TypeError { .. })
+        ));
+    }
+
+    #[test]
+    fn test_dig() {
+        let mut coverage = HashMap::new();
+        coverage.insert("limit".to_string(), Value::Number(50000.0));
+        let mut policy = HashMap::new();
+        policy.insert(
+            "coverages".to_string(),
+            Value::Array(vec![Value::Dictionary(coverage.into())].into()),
+        );
+        let mut root = HashMap::new();
+        root.insert("policy".to_string(), Value::Dictionary(policy.into()));
+        let data = Value::Dictionary(root.into());
+
+        let path = |steps: Vec<Value>| Value::Array(steps.into());
+        let s = |v: &str| Value::String(v.into());
+
+        assert_eq!(
+            dig(
+                &data,
+                &path(vec![
+                    s("policy"),
+                    s("coverages"),
+                    Value::Number(0.0),
+                    s("limit")
+                ])
+            )
+            .unwrap(),
+            Value::Number(50000.0)
+        );
+        assert_eq!(
+            dig(
+                &data,
+                &path(vec![s("policy"), s("coverages"), Value::Int(0), s("limit")])
+            )
+            .unwrap(),
+            Value::Number(50000.0)
+        );
+        assert_eq!(
+            dig(
+                &data,
+                &path(vec![
+                    s("policy"),
+                    s("coverages"),
+                    Value::Number(-1.0),
+                    s("limit")
+                ])
+            )
+            .unwrap(),
+            Value::Number(50000.0)
+        );
+
+        // Safe navigation
+        assert_eq!(
+            dig(&data, &path(vec![s("policy"), s("missing"), s("limit")])).unwrap(),
+            Value::Nil
+        );
+        assert_eq!(
+            dig(
+                &data,
+                &path(vec![s("policy"), s("coverages"), Value::Number(5.0)])
+            )
+            .unwrap(),
+            Value::Nil
+        );
+        assert_eq!(
+            dig(
+                &data,
+                &path(vec![s("policy"), s("coverages"), Value::Number(-5.0)])
+            )
+            .unwrap(),
+            Value::Nil
+        );
+        assert_eq!(
+            dig(&data, &path(vec![s("policy"), Value::Number(0.0)])).unwrap(),
+            Value::Nil
+        );
+        // A fractional index names no element rather than being truncated
+        for index in [0.5, -0.5, f64::NAN, f64::INFINITY] {
+            assert_eq!(
+                dig(
+                    &data,
+                    &path(vec![s("policy"), s("coverages"), Value::Number(index)])
+                )
+                .unwrap(),
+                Value::Nil,
+                "{}",
+                index
+            );
+        }
+        assert_eq!(dig(&data, &path(vec![])).unwrap(), data);
+
+        // Type errors
+        assert!(matches!(
+            dig(&data, &s("policy")),
+            Err(FunctionError::TypeError { .. })
+        ));
+        assert!(matches!(
+            dig(&data, &path(vec![Value::Boolean(true)])),
+            Err(FunctionError::TypeError { .. })
+        ));
+    }
+
+    #[test]
+    fn test_union() {
+        let a = Value::Array(
+            vec![
+                Value::String("COLL".into()),
+                Value::String("COMP".into()),
+                Value::String("COLL".into()),
+            ]
+            .into(),
+        );
+        let b = Value::Array(vec![Value::String("COMP".into()), Value::String("UM".into())].into());
+        assert_eq!(
+            union(&a, &b).unwrap(),
+            Value::Array(
+                vec![
+                    Value::String("COLL".into()),
+                    Value::String("COMP".into()),
+                    Value::String("UM".into()),
+                ]
+                .into()
+            )
+        );
+        assert!(matches!(
+            union(&a, &Value::Nil),
+            Err(FunctionError::TypeError { .. })
+        ));
+    }
+
+    #[test]
+    fn test_intersection() {
+        let a = Value::Array(
+            vec![
+                Value::Number(1.0),
+                Value::Number(2.0),
+                Value::Number(2.0),
+                Value::Number(3.0),
+            ]
+            .into(),
+        );
+        let b = Value::Array(vec![Value::Number(3.0), Value::Number(2.0)].into());
+        assert_eq!(
+            intersection(&a, &b).unwrap(),
+            Value::Array(vec![Value::Number(2.0), Value::Number(3.0)].into())
+        );
+        assert_eq!(
+            intersection(&a, &Value::Array(vec![].into())).unwrap(),
+            Value::Array(vec![].into())
+        );
+        assert!(matches!(
+            intersection(&Value::Nil, &b),
+            Err(FunctionError::TypeError { .. })
+        ));
+    }
+
+    #[test]
+    fn test_difference() {
+        let required = Value::Array(
+            vec![
+                Value::String("BI".into()),
+                Value::String("PD".into()),
+                Value::String("UM".into()),
+            ]
+            .into(),
+        );
+        let declared =
+            Value::Array(vec![Value::String("PD".into()), Value::String("COLL".into())].into());
+        assert_eq!(
+            difference(&required, &declared).unwrap(),
+            Value::Array(vec![Value::String("BI".into()), Value::String("UM".into()),].into())
+        );
+        assert!(matches!(
+            difference(&required, &Value::String("PD".into())),
+            Err(FunctionError::TypeError { .. })
+        ));
     }
 }
@@ -0,0 +1,568 @@
+//! Type conversion functions
+//!
+//! The lenient functions return nil for input they cannot convert, so rules can
+//! combine them with `coalesce` or `default`. The `_strict` variants return an
+//! error instead, for rules where bad data should stop evaluation.
+
+use crate::string::is_numeric_string;
+use crate::{FunctionError, Value};
+use std::collections::HashMap;
+
+/// Maximum nesting depth accepted by `parse_json`
+pub const MAX_JSON_DEPTH: usize = 100;
+
+/// Lowercase strings `to_boolean` reads as true
+pub const TRUE_STRINGS: &[&str] = &["true", "yes", "y", "on", "1"];
+
+/// Lowercase strings `to_boolean` reads as false
+pub const FALSE_STRINGS: &[&str] = &["false", "no", "n", "off", "0"];
+
+/// Convert a value to a number
+/// to_number(val: Any) -> Number | Nil
+///
+/// Numbers pass through; strings are parsed after trimming whitespace and must
+/// be plain decimals ("25", "-3.5", "1e3"); booleans become 1 or 0. Anything
+/// else, including unparseable strings, gives nil.
+pub fn to_number(value: &Value) -> Value {
+    match value {
+        Value::Number(n) => Value::Number(*n),
+        Value::Int(n) => Value::Int(*n),
+        Value::Decimal(d) => Value::Decimal(*d),
+        Value::Boolean(b) => Value::Number(if *b { 1.0 } else { 0.0 }),
+        Value::String(s) => {
+            let trimmed = s.trim();
+            match is_numeric_string(&Value::String(trimmed.into())) {
+                Value::Boolean(true) => trimmed
+                    .parse::<f64>()
+                    .map(Value::Number)
+                    .unwrap_or(Value::Nil),
+                _ => Value::Nil,
+            }
+        }
+        _ => Value::Nil,
+    }
+}
+
+/// Convert a value to a number, failing on unconvertible input
+/// to_number_strict(val: Any) -> Number
+pub fn to_number_strict(value: &Value) -> Result<Value, FunctionError> {
+    match to_number(value) {
+        Value::Nil => Err(conversion_error(value, "Number")),
+        n => Ok(n),
+    }
+}
+
+/// Convert a value to a string
+/// to_string(val: Any) -> String | Nil
+///
+/// Numbers use their shortest form ("25", "0.1"), booleans become "true" or
+/// "false", symbols become their name without the colon, durations use their
+/// ISO 8601 form ("P30D"), and nil becomes the empty string. Arrays and
+/// dictionaries give nil; use `to_json` for those. Functions also give nil.
+pub fn to_string(value: &Value) -> Value {
+    match value {
+        Value::String(s) => Value::String(s.clone()),
+        Value::Number(n) => Value::String(n.to_string().into()),
+        Value::Int(n) => Value::String(n.to_string().into()),
+        Value::Decimal(d) => Value::String(d.to_string().into()),
+        Value::Boolean(b) => Value::String(b.to_string().into()),
+        Value::Symbol(s) => Value::String(s.clone().into()),
+        Value::Duration(d) => Value::String(d.to_string().into()),
+        Value::Nil => Value::String("".into()),
+        Value::Array(_) | Value::Dictionary(_) | Value::Function(_) => Value::Nil,
+    }
+}
+
+/// Convert a value to a string, failing on unconvertible input
+/// to_string_strict(val: Any) -> String
+pub fn to_string_strict(value: &Value) -> Result<Value, FunctionError> {
+    match to_string(value) {
+        Value::Nil => Err(conversion_error(value, "String")),
+        s => Ok(s),
+    }
+}
+
+/// Convert a value to a boolean
+/// to_boolean(val: Any) -> Boolean | Nil
+///
+/// Booleans pass through; numbers are true unless zero; strings are matched
+/// case-insensitively after trimming: "true", "yes", "y", "on", "1" are true
+/// and "false", "no", "n", "off", "0" are false. Anything else gives nil.
+pub fn to_boolean(value: &Value) -> Value {
+    match value {
+        Value::Boolean(b) => Value::Boolean(*b),
+        Value::Number(n) if !n.is_nan() => Value::Boolean(*n != 0.0),
+        Value::Int(n) => Value::Boolean(*n != 0),
+        Value::Decimal(d) => Value::Boolean(!d.is_zero()),
+        Value::String(s) => {
+            let text = s.trim().to_ascii_lowercase();
+            if TRUE_STRINGS.contains(&text.as_str()) {
+                Value::Boolean(true)
+            } else if FALSE_STRINGS.contains(&text.as_str()) {
+                Value::Boolean(false)
+            } else {
+                Value::Nil
+            }
+        }
+        _ => Value::Nil,
+    }
+}
+
+/// Convert a value to a boolean, failing on unconvertible input
+/// to_boolean_strict(val: Any) -> Boolean
+pub fn to_boolean_strict(value: &Value) -> Result<Value, FunctionError> {
+    match to_boolean(value) {
+        Value::Nil => Err(conversion_error(value, "Boolean")),
+        b => Ok(b),
+    }
+}
+
+/// Parse a JSON document into a value
+/// parse_json(str: String) -> Any
+///
+/// Objects become dictionaries, arrays become arrays, and null becomes nil.
+/// Numbers without a fractional part that fit in 64 bits become integers.
+/// Invalid JSON, or nesting deeper than `MAX_JSON_DEPTH`, is a value error.
+pub fn parse_json(value: &Value) -> Result<Value, FunctionError> {
+    match value {
+        Value::String(s) => {
+            let json: serde_json::Value =
+                serde_json::from_str(s).map_err(|e| FunctionError::ValueError {
+                    message: format!("Invalid JSON: {}", e),
+                })?;
+            JsonConverter::default()
+                .read(&json)
+                .map_err(JsonConversionError::into_function_error)
+        }
+        _ => Err(FunctionError::TypeError {
+            expected: "String".to_string(),
+            got: value.type_name().to_string(),
+        }),
+    }
+}
+
+/// Serialize a value as a compact JSON string
+/// to_json(val: Any) -> String
+///
+/// Whole numbers are written without a fractional part, symbols are written
+/// as ":name" strings, durations as ISO 8601 strings, and dictionary keys are
+/// sorted for stable output.
+pub fn to_json(value: &Value) -> Result<Value, FunctionError> {
+    let json = JsonConverter::default()
+        .write(value)
+        .map_err(JsonConversionError::into_function_error)?;
+    serde_json::to_string(&json)
+        .map(|s| Value::String(s.into()))
+        .map_err(|e| FunctionError::ValueError {
+            message: format!("Cannot serialize to JSON: {}", e),
+        })
+}
+
+/// Converts between JSON and values, for `parse_json` and `to_json` and for
+/// hosts passing data in and out
+///
+/// Integers become `Int` and other numbers `Number`. Symbols are written as
+/// `":name"` strings and read back as symbols only with `with_symbols`,
+/// since a JSON string may legitimately start with a colon. Decimals and
+/// durations are written as strings. The default allows `MAX_JSON_DEPTH`
+/// levels of nesting and any number of values.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct JsonConverter {
+    max_depth: usize,
+    max_values: Option<usize>,
+    decode_symbols: bool,
+}
+
+impl Default for JsonConverter {
+    fn default() -> Self {
+        Self {
+            max_depth: MAX_JSON_DEPTH,
+            max_values: None,
+            decode_symbols: false,
+        }
+    }
+}
+
+/// Why a conversion between JSON and a value failed
+#[derive(Debug, Clone, PartialEq)]
+pub enum JsonConversionError {
+    TooDeep { max_depth: usize },
+    TooLarge { max_values: usize },
+    InvalidNumber(String),
+    NonFinite(f64),
+    Function(String),
+}
+
+impl std::fmt::Display for JsonConversionError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            JsonConversionError::TooDeep { max_depth } => {
+                write!(f, "JSON nesting too deep (max {} levels)", max_depth)
+            }
+            JsonConversionError::TooLarge { max_values } => {
+                write!(f, "JSON too large (max {} values)", max_values)
+            }
+            JsonConversionError::InvalidNumber(n) => write!(f, "JSON number out of range: {}", n),
+            JsonConversionError::NonFinite(n) => {
+                write!(f, "Cannot serialize non-finite number {} to JSON", n)
+            }
+            JsonConversionError::Function(name) => {
+                write!(f, "Cannot serialize function {} to JSON", name)
+            }
+        }
+    }
+}
+
+impl std::error::Error for JsonConversionError {}
+
+impl JsonConversionError {
+    fn into_function_error(self) -> FunctionError {
+        FunctionError::ValueError {
+            message: self.to_string(),
+        }
+    }
+}
+
+impl JsonConverter {
+    /// Reject documents nested deeper than `max_depth` levels
+    pub fn with_max_depth(mut self, max_depth: usize) -> Self {
+        self.max_depth = max_depth;
+        self
+    }
+
+    /// Reject documents holding more than `max_values` values in total
+    pub fn with_max_values(mut self, max_values: usize) -> Self {
+        self.max_values = Some(max_values);
+        self
+    }
+
+    /// Read `":name"` strings back as the symbol `:name`
+    pub fn with_symbols(mut self, decode_symbols: bool) -> Self {
+        self.decode_symbols = decode_symbols;
+        self
+    }
+
+    /// Convert a JSON value to a value
+    pub fn read(&self, json: &serde_json::Value) -> Result<Value, JsonConversionError> {
+        let mut budget = self.max_values;
+        self.read_at(json, 0, &mut budget)
+    }
+
+    /// Convert a value to JSON, with dictionary keys sorted
+    pub fn write(&self, value: &Value) -> Result<serde_json::Value, JsonConversionError> {
+        let mut budget = self.max_values;
+        self.write_at(value, 0, &mut budget)
+    }
+
+    fn read_at(
+        &self,
+        json: &serde_json::Value,
+        depth: usize,
+        budget: &mut Option<usize>,
+    ) -> Result<Value, JsonConversionError> {
+        self.enter(depth, budget)?;
+        match json {
+            serde_json::Value::Null => Ok(Value::Nil),
+            serde_json::Value::Bool(b) => Ok(Value::Boolean(*b)),
+            serde_json::Value::Number(n) => match n.as_i64() {
+                Some(i) => Ok(Value::Int(i)),
+                None => n
+                    .as_f64()
+                    .filter(|f| f.is_finite())
+                    .map(Value::Number)
+                    .ok_or_else(|| JsonConversionError::InvalidNumber(n.to_string())),
+            },
+            serde_json::Value::String(s) => match s.strip_prefix(':') {
+                Some(name) if self.decode_symbols && !name.is_empty() => {
+                    Ok(Value::Symbol(name.to_string()))
+                }
+                _ => Ok(Value::String(s.as_str().into())),
+            },
+            serde_json::Value::Array(items) => items
+                .iter()
+                .map(|item| self.read_at(item, depth + 1, budget))
+                .collect::<Result<Vec<_>, _>>()
+                .map(|items| Value::Array(items.into())),
+            serde_json::Value::Object(fields) => {
+                let mut map = HashMap::with_capacity(fields.len());
+                for (key, field) in fields {
+                    map.insert(key.clone(), self.read_at(field, depth + 1, budget)?);
+                }
+                Ok(Value::Dictionary(map.into()))
+            }
+        }
+    }
+
+    fn write_at(
+        &self,
+        value: &Value,
+        depth: usize,
+        budget: &mut Option<usize>,
+    ) -> Result<serde_json::Value, JsonConversionError> {
+        self.enter(depth, budget)?;
+        match value {
+            Value::Nil => Ok(serde_json::Value::Null),
+            Value::Boolean(b) => Ok(serde_json::Value::Bool(*b)),
+            Value::Number(n) if !n.is_finite() => Err(JsonConversionError::NonFinite(*n)),
+            // Whole numbers within the exactly-representable range serialize as integers
+            Value::Number(n) if n.fract() == 0.0 && n.abs() < 9_007_199_254_740_992.0 => {
+                Ok(serde_json::Value::from(*n as i64))
+            }
+            Value::Number(n) => Ok(serde_json::Value::from(*n)),
+            Value::Int(n) => Ok(serde_json::Value::from(*n)),
+            // Written as a string so no digits are lost to a float
+            Value::Decimal(d) => Ok(serde_json::Value::String(d.to_string())),
+            Value::String(s) => Ok(serde_json::Value::String(s.to_string())),
+            Value::Symbol(s) => Ok(serde_json::Value::String(format!(":{}", s))),
+            Value::Duration(d) => Ok(serde_json::Value::String(d.to_string())),
+            Value::Array(items) => items
+                .iter()
+                .map(|item| self.write_at(item, depth + 1, budget))
+                .collect::<Result<Vec<_>, _>>()
+                .map(serde_json::Value::Array),
+            Value::Dictionary(map) => {
+                let mut keys: Vec<&String> = map.keys().collect();
+                keys.sort();
+                let mut fields = serde_json::Map::with_capacity(map.len());
+                for key in keys {
+                    fields.insert(key.clone(), self.write_at(&map[key], depth + 1, budget)?);
+                }
+                Ok(serde_json::Value::Object(fields))
+            }
+            Value::Function(f) => Err(JsonConversionError::Function(f.to_string())),
+        }
+    }
+
+    /// Charge one value against the depth and size limits
+    fn enter(&self, depth: usize, budget: &mut Option<usize>) -> Result<(), JsonConversionError> {
+        if depth > self.max_depth {
+            return Err(JsonConversionError::TooDeep {
+                max_depth: self.max_depth,
+            });
+        }
+        if let Some(remaining) = budget {
+            if *remaining == 0 {
+                return Err(JsonConversionError::TooLarge {
+                    max_values: self.max_values.unwrap_or_default(),
+                });
+            }
+            *remaining -= 1;
+        }
+        Ok(())
+    }
+}
+
+/// Build the error returned by the strict conversions
+fn conversion_error(value: &Value, target: &str) -> FunctionError {
+    FunctionError::ValueError {
+        message: match value {
+            Value::String(s) => format!("Cannot convert String {:?} to {}", s, target),
+            _ => format!("Cannot convert {} to {}", value.type_name(), target),
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_to_number() {
+        assert_eq!(to_number(&Value::String("25".into())), Value::Number(25.0));
+        assert_eq!(
+            to_number(&Value::String(" -3.5 ".into())),
+            Value::Number(-3.5)
+        );
+        assert_eq!(
+            to_number(&Value::String("1e3".into())),
+            Value::Number(1000.0)
+        );
+        assert_eq!(to_number(&Value::Number(7.0)), Value::Number(7.0));
+        assert_eq!(to_number(&Value::Boolean(true)), Value::Number(1.0));
+        assert_eq!(to_number(&Value::String("abc".into())), Value::Nil);
+        assert_eq!(to_number(&Value::String("1,000".into())), Value::Nil);
+        assert_eq!(to_number(&Value::String("NaN".into())), Value::Nil);
+        assert_eq!(to_number(&Value::Nil), Value::Nil);
+        assert_eq!(to_number(&Value::Array(vec![].into())), Value::Nil);
+    }
+
+    #[test]
+    fn test_to_number_strict() {
+        assert_eq!(
+            to_number_strict(&Value::String("42".into())).unwrap(),
+            Value::Number(42.0)
+        );
+        assert!(matches!(
+            to_number_strict(&Value::String("forty-two".into())),
+            Err(FunctionError::ValueError { .. })
+        ));
+        assert!(matches!(
+            to_number_strict(&Value::Nil),
+            Err(FunctionError::ValueError { .. })
+        ));
+    }
+
+    #[test]
+    fn test_to_string() {
+        assert_eq!(to_string(&Value::Number(25.0)), Value::String("25".into()));
+        assert_eq!(to_string(&Value::Number(0.1)), Value::String("0.1".into()));
+        assert_eq!(
+            to_string(&Value::Boolean(false)),
+            Value::String("false".into())
+        );
+        assert_eq!(
+            to_string(&Value::Symbol("high".to_string())),
+            Value::String("high".into())
+        );
+        assert_eq!(to_string(&Value::Nil), Value::String("".into()));
+        assert_eq!(
+            to_string(&Value::Dictionary(HashMap::new().into())),
+            Value::Nil
+        );
+        assert!(matches!(
+            to_string_strict(&Value::Array(vec![].into())),
+            Err(FunctionError::ValueError { .. })
+        ));
+    }
+
+    #[test]
+    fn test_to_boolean() {
+        for s in ["true", "YES", " y ", "on", "1"] {
+            assert_eq!(
+                to_boolean(&Value::String(s.to_string().into())),
+                Value::Boolean(true),
+                "Failed for: {}",
+                s
+            );
+        }
+        for s in ["false", "No", "n", "OFF", "0"] {
+            assert_eq!(
+                to_boolean(&Value::String(s.to_string().into())),
+                Value::Boolean(false),
+                "Failed for: {}",
+                s
+            );
+        }
+        assert_eq!(to_boolean(&Value::String("maybe".into())), Value::Nil);
+        assert_eq!(to_boolean(&Value::Number(0.0)), Value::Boolean(false));
+        assert_eq!(to_boolean(&Value::Number(-2.0)), Value::Boolean(true));
+        assert_eq!(to_boolean(&Value::Nil), Value::Nil);
+    }
+
+    #[test]
+    fn test_to_boolean_strict() {
+        assert_eq!(
+            to_boolean_strict(&Value::String("yes".into())).unwrap(),
+            Value::Boolean(true)
+        );
+        assert!(matches!(
+            to_boolean_strict(&Value::String("maybe".into())),
+            Err(FunctionError::ValueError { .. })
+        ));
+    }
+
+    #[test]
+    fn test_parse_json() {
+        let json = Value::String(
+            r#"{"driver": {"age": 42, "tickets": [1, 2.5]}, "active": true, "notes": null}"#
+                .to_string()
+                .into(),
+        );
+        let result = parse_json(&json).unwrap();
+        let Value::Dictionary(root) = result else {
+            panic!("expected dictionary");
+        };
+        assert_eq!(root["active"], Value::Boolean(true));
+        assert_eq!(root["notes"], Value::Nil);
+        let Value::Dictionary(driver) = &root["driver"] else {
+            panic!("expected dictionary");
+        };
+        assert!(matches!(driver["age"], Value::Int(42)));
+        assert_eq!(
+            driver["tickets"],
+            Value::Array(vec![Value::Number(1.0), Value::Number(2.5)].into())
+        );
+    }
+
+    #[test]
+    fn test_parse_json_errors() {
+        assert!(matches!(
+            parse_json(&Value::String("{not json".into())),
+            Err(FunctionError::ValueError { .. })
+        ));
+        let deep = "[".repeat(MAX_JSON_DEPTH + 2) + &"]".repeat(MAX_JSON_DEPTH + 2);
+        assert!(matches!(
+            parse_json(&Value::String(deep.into())),
+            Err(FunctionError::ValueError { .. })
+        ));
+        assert!(matches!(
+            parse_json(&Value::Number(1.0)),
+            Err(FunctionError::TypeError { .. })
+        ));
+    }
+
+    #[test]
+    fn test_to_json() {
+        let mut dict = HashMap::new();
+        dict.insert("zip".to_string(), Value::String("03101".into()));
+        dict.insert("age".to_string(), Value::Number(42.0));
+        dict.insert(
+            "tags".to_string(),
+            Value::Array(
+                vec![
+                    Value::Symbol("preferred".to_string()),
+                    Value::Number(0.5),
+                    Value::Nil,
+                ]
+                .into(),
+            ),
+        );
+        assert_eq!(
+            to_json(&Value::Dictionary(dict.into())).unwrap(),
+            Value::String(
+                r#"{"age":42,"tags":[":preferred",0.5,null],"zip":"03101"}"#
+                    .to_string()
+                    .into()
+            )
+        );
+        assert_eq!(
+            to_json(&Value::String("a \"quote\"".into())).unwrap(),
+            Value::String(r#""a \"quote\"""#.into())
+        );
+        assert!(matches!(
+            to_json(&Value::Number(f64::INFINITY)),
+            Err(FunctionError::ValueError { .. })
+        ));
+    }
+
+    #[test]
+    fn test_json_round_trip() {
+        let source = r#"{"a":[1,2,{"b":"c"}],"d":false}"#;
+        let parsed = parse_json(&Value::String(source.into())).unwrap();
+        assert_eq!(to_json(&parsed).unwrap(), Value::String(source.into()));
+    }
+
+    #[test]
+    fn test_json_converter() {
+        let json: serde_json::Value = serde_json::from_str(r#"[":ok", [1, 2]]"#).unwrap();
+        let converter = JsonConverter::default().with_symbols(true);
+        assert_eq!(
+            converter.read(&json).unwrap(),
+            Value::Array(
+                vec![
+                    Value::Symbol("ok".to_string()),
+                    Value::Array(vec![Value::Int(1), Value::Int(2)].into())
+                ]
+                .into()
+            )
+        );
+        assert_eq!(
+            converter.with_max_values(3).read(&json),
+            Err(JsonConversionError::TooLarge { max_values: 3 })
+        );
+        assert_eq!(
+            converter.with_max_depth(0).read(&json),
+            Err(JsonConversionError::TooDeep { max_depth: 0 })
+        );
+    }
+}
@@ -1,13 +1,373 @@
 //! Date functions for the Amoskeag language
 //!
 //! This module provides functions for working with dates.
+//!
+//! Dates are ISO 8601 strings (`YYYY-MM-DD`). Calendar arithmetic is done on
+//! [`CivilDate`], a proleptic Gregorian date, so no external date library is
+//! needed.
+
+use super::{Duration, FunctionError, Value};
+use std::collections::HashSet;
+use std::fmt;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// A calendar date in the proleptic Gregorian calendar
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub(crate) struct CivilDate {
+    year: i32,
+    month: u32,
+    day: u32,
+}
 
-use super::{FunctionError, Value};
+impl CivilDate {
+    /// Build a date, checking the day against the length of the month
+    fn new(year: i32, month: u32, day: u32) -> Result<Self, FunctionError> {
+        if !(1000..=9999).contains(&year) {
+            return Err(FunctionError::ValueError {
+                message: format!("Year must be between 1000 and 9999, got: {}", year),
+            });
+        }
+        if !(1..=12).contains(&month) {
+            return Err(FunctionError::ValueError {
+                message: format!("Month must be between 1 and 12, got: {}", month),
+            });
+        }
+        if day < 1 || day > days_in_month(year, month) {
+            return Err(FunctionError::ValueError {
+                message: format!("Day {} is out of range for {}-{:02}", day, year, month),
+            });
+        }
+        Ok(CivilDate { year, month, day })
+    }
+
+    /// Read a date from a `YYYY-MM-DD` string, ignoring any time portion
+    pub(crate) fn from_value(value: &Value) -> Result<Self, FunctionError> {
+        let s = match value {
+            Value::String(s) => s,
+            _ => {
+                return Err(FunctionError::TypeError {
+                    expected: "string".to_string(),
+                    got: value.type_name().to_string(),
+                })
+            }
+        };
+
+        let invalid = || FunctionError::ValueError {
+            message: format!("Invalid date format: {}. Expected YYYY-MM-DD", s),
+        };
+        let date_part = s.split(['T', ' ']).next().unwrap_or(s);
+        let mut parts = date_part.splitn(3, '-');
+        let (Some(year), Some(month), Some(day)) = (parts.next(), parts.next(), parts.next())
+        else {
+            return Err(invalid());
+        };
+        if year.len() != 4 || month.len() != 2 || day.len() != 2 {
+            return Err(invalid());
+        }
+        let year = year.parse().map_err(|_| invalid())?;
+        let month = month.parse().map_err(|_| invalid())?;
+        let day = day.parse().map_err(|_| invalid())?;
+        CivilDate::new(year, month, day)
+    }
+
+    pub(crate) fn year(self) -> i32 {
+        self.year
+    }
+
+    pub(crate) fn month(self) -> u32 {
+        self.month
+    }
+
+    pub(crate) fn day(self) -> u32 {
+        self.day
+    }
+
+    /// True on the last day of the month
+    pub(crate) fn is_month_end(self) -> bool {
+        self.day == days_in_month(self.year, self.month)
+    }
+
+    pub(crate) fn to_value(self) -> Value {
+        Value::String(self.to_string().into())
+    }
+
+    /// Days since 1970-01-01
+    pub(crate) fn days_since_epoch(self) -> i64 {
+        let year = i64::from(self.year) - i64::from(self.month <= 2);
+        let era = year.div_euclid(400);
+        let year_of_era = year - era * 400;
+        let month = i64::from(self.month);
+        let day_of_year =
+            (153 * (month + if month > 2 { -3 } else { 9 }) + 2) / 5 + i64::from(self.day) - 1;
+        let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+        era * 146_097 + day_of_era - 719_468
+    }
+
+    /// The date a number of days after 1970-01-01
+    pub(crate) fn from_days_since_epoch(days: i64) -> Result<Self, FunctionError> {
+        let z = days + 719_468;
+        let era = z.div_euclid(146_097);
+        let day_of_era = z - era * 146_097;
+        let year_of_era =
+            (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+        let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+        let mp = (5 * day_of_year + 2) / 153;
+        let day = day_of_year - (153 * mp + 2) / 5 + 1;
+        let month = if mp < 10 { mp + 3 } else { mp - 9 };
+        let year = year_of_era + era * 400 + i64::from(month <= 2);
+        let year = i32::try_from(year).map_err(|_| FunctionError::ValueError {
+            message: format!("Year must be between 1000 and 9999, got: {}", year),
+        })?;
+        CivilDate::new(year, month as u32, day as u32)
+    }
+
+    pub(crate) fn add_days(self, days: i64) -> Result<Self, FunctionError> {
+        CivilDate::from_days_since_epoch(self.days_since_epoch() + days)
+    }
+
+    /// Whole months from `self` to a later date, counting a month as complete
+    /// when the same (or clamped) day of the month has been reached
+    pub(crate) fn whole_months_until(self, later: CivilDate) -> Result<i64, FunctionError> {
+        let mut months = (i64::from(later.year) - i64::from(self.year)) * 12
+            + i64::from(later.month)
+            - i64::from(self.month);
+        if months > 0 && self.add_months(months)? > later {
+            months -= 1;
+        }
+        Ok(months)
+    }
+
+    /// The first day of the month, `offset` months away
+    fn month_start(self, offset: i64) -> Result<Self, FunctionError> {
+        CivilDate::new(self.year, self.month, 1)?.add_months(offset)
+    }
+
+    /// The last day of the month, `offset` months away
+    pub(crate) fn month_end(self, offset: i64) -> Result<Self, FunctionError> {
+        let start = self.month_start(offset)?;
+        CivilDate::new(
+            start.year,
+            start.month,
+            days_in_month(start.year, start.month),
+        )
+    }
+
+    /// Day of the week, with Monday as 0 and Sunday as 6
+    fn weekday(self) -> i64 {
+        // 1970-01-01 was a Thursday
+        (self.days_since_epoch() + 3).rem_euclid(7)
+    }
+
+    /// True on Saturdays and Sundays
+    pub(crate) fn is_weekend(self) -> bool {
+        self.weekday() >= 5
+    }
+
+    /// Shift by whole months, clamping the day to the end of shorter months
+    pub(crate) fn add_months(self, months: i64) -> Result<Self, FunctionError> {
+        let index = i64::from(self.year) * 12 + i64::from(self.month) - 1 + months;
+        let year = i32::try_from(index.div_euclid(12)).map_err(|_| FunctionError::ValueError {
+            message: "Date is out of range".to_string(),
+        })?;
+        let month = index.rem_euclid(12) as u32 + 1;
+        let day = self.day.min(days_in_month(year, month));
+        CivilDate::new(year, month, day)
+    }
+}
+
+impl fmt::Display for CivilDate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:04}-{:02}-{:02}", self.year, self.month, self.day)
+    }
+}
 
-/// Returns the current date as a string in YYYY-MM-DD format
+fn is_leap_year(year: i32) -> bool {
+    (year % 4 == 0 && year % 100 != 0) || year % 400 == 0
+}
+
+fn days_in_month(year: i32, month: u32) -> u32 {
+    match month {
+        2 if is_leap_year(year) => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+/// Read a whole-number argument used in date arithmetic
+fn whole_amount(value: &Value) -> Result<i64, FunctionError> {
+    match value {
+        Value::Number(n) if n.fract() == 0.0 && n.abs() <= 1e9 => Ok(*n as i64),
+        Value::Number(n) => Err(FunctionError::ArgumentError {
+            message: format!("Expected a whole number, got {}", n),
+        }),
+        _ => Err(FunctionError::TypeError {
+            expected: "Number".to_string(),
+            got: value.type_name().to_string(),
+        }),
+    }
+}
+
+/// Read a calendar unit name such as `'days'` or `:months`
+fn date_unit(value: &Value) -> Result<&str, FunctionError> {
+    let name = match value {
+        Value::String(s) => s,
+        Value::Symbol(s) => s.as_str(),
+        _ => {
+            return Err(FunctionError::TypeError {
+                expected: "String".to_string(),
+                got: value.type_name().to_string(),
+            })
+        }
+    };
+    match name {
+        "day" | "days" => Ok("days"),
+        "week" | "weeks" => Ok("weeks"),
+        "month" | "months" => Ok("months"),
+        "year" | "years" => Ok("years"),
+        _ => Err(FunctionError::ArgumentError {
+            message: format!(
+                "Unknown date unit '{}'. Expected days, weeks, months, or years",
+                name
+            ),
+        }),
+    }
+}
+
+/// Adds days, weeks, months, or years to a date
+/// date_add(date: String, amount: Number, unit: String) -> String
+///
+/// Adding months or years clamps to the end of shorter months, so
+/// `date_add('2024-01-31', 1, 'months')` is `'2024-02-29'`.
+pub fn date_add(date: &Value, amount: &Value, unit: &Value) -> Result<Value, FunctionError> {
+    let date = CivilDate::from_value(date)?;
+    let amount = whole_amount(amount)?;
+    let shifted = match date_unit(unit)? {
+        "days" => date.add_days(amount)?,
+        "weeks" => date.add_days(amount * 7)?,
+        "months" => date.add_months(amount)?,
+        _ => date.add_months(amount * 12)?,
+    };
+    Ok(shifted.to_value())
+}
+
+/// Subtracts days, weeks, months, or years from a date
+/// date_sub(date: String, amount: Number, unit: String) -> String
+pub fn date_sub(date: &Value, amount: &Value, unit: &Value) -> Result<Value, FunctionError> {
+    let amount = Value::Number(-(whole_amount(amount)? as f64));
+    date_add(date, &amount, unit)
+}
+
+/// Returns true if the first date is strictly before the second
+/// date_before(a: String, b: String) -> Boolean
+///
+/// Unlike `<` on strings, both arguments are validated as dates, so a
+/// non-ISO value is an error rather than a silently wrong answer.
+pub fn date_before(a: &Value, b: &Value) -> Result<Value, FunctionError> {
+    Ok(Value::Boolean(
+        CivilDate::from_value(a)? < CivilDate::from_value(b)?,
+    ))
+}
+
+/// Returns true if the first date is strictly after the second
+/// date_after(a: String, b: String) -> Boolean
+pub fn date_after(a: &Value, b: &Value) -> Result<Value, FunctionError> {
+    Ok(Value::Boolean(
+        CivilDate::from_value(a)? > CivilDate::from_value(b)?,
+    ))
+}
+
+/// Compares two dates, returning -1, 0, or 1
+/// date_compare(a: String, b: String) -> Number
+///
+/// Any time portion is ignored, so two timestamps on the same day compare
+/// equal.
+pub fn date_compare(a: &Value, b: &Value) -> Result<Value, FunctionError> {
+    let ordering = CivilDate::from_value(a)?.cmp(&CivilDate::from_value(b)?);
+    Ok(Value::Number(ordering as i8 as f64))
+}
+
+/// Creates a duration of whole days
+/// duration_days(n: Number) -> Duration
+pub fn duration_days(n: &Value) -> Result<Value, FunctionError> {
+    Ok(Value::Duration(Duration::days(whole_amount(n)?)))
+}
+
+/// Creates a duration of whole weeks, stored as days
+/// duration_weeks(n: Number) -> Duration
+pub fn duration_weeks(n: &Value) -> Result<Value, FunctionError> {
+    Ok(Value::Duration(Duration::days(whole_amount(n)? * 7)))
+}
+
+/// Creates a duration of whole calendar months
+/// duration_months(n: Number) -> Duration
+pub fn duration_months(n: &Value) -> Result<Value, FunctionError> {
+    Ok(Value::Duration(Duration::months(whole_amount(n)?)))
+}
+
+/// Creates a duration of whole years, stored as months
+/// duration_years(n: Number) -> Duration
+pub fn duration_years(n: &Value) -> Result<Value, FunctionError> {
+    Ok(Value::Duration(Duration::months(whole_amount(n)? * 12)))
+}
+
+/// Adds a duration to a date
+/// date_add(date: String, duration: Duration) -> String
+///
+/// Months are applied first (clamping to the end of shorter months), then days.
+pub fn add_duration(date: &Value, duration: &Value) -> Result<Value, FunctionError> {
+    let duration = as_duration(duration)?;
+    let date = CivilDate::from_value(date)?
+        .add_months(duration.months)?
+        .add_days(duration.days)?;
+    Ok(date.to_value())
+}
+
+/// Subtracts a duration from a date
+/// date_sub(date: String, duration: Duration) -> String
+pub fn subtract_duration(date: &Value, duration: &Value) -> Result<Value, FunctionError> {
+    let duration = as_duration(duration)?;
+    let negated = Duration {
+        months: -duration.months,
+        days: -duration.days,
+    };
+    add_duration(date, &Value::Duration(negated))
+}
+
+/// Returns the number of days between two dates as a duration
+/// date_diff(start: String, end: String) -> Duration
+pub fn duration_between(start: &Value, end: &Value) -> Result<Value, FunctionError> {
+    let start = CivilDate::from_value(start)?;
+    let end = CivilDate::from_value(end)?;
+    Ok(Value::Duration(Duration::days(
+        end.days_since_epoch() - start.days_since_epoch(),
+    )))
+}
+
+fn as_duration(value: &Value) -> Result<Duration, FunctionError> {
+    match value {
+        Value::Duration(d) => Ok(*d),
+        _ => Err(FunctionError::TypeError {
+            expected: "Duration".to_string(),
+            got: value.type_name().to_string(),
+        }),
+    }
+}
+
+/// Returns the current UTC date from the system clock in YYYY-MM-DD format
+///
+/// Evaluation contexts can substitute their own clock; this is the default.
 pub fn date_now() -> Result<Value, FunctionError> {
-    // For testing purposes, return a fixed date
-    Ok(Value::String("2025-01-18".to_string()))
+    let seconds = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_err(|_| FunctionError::ValueError {
+            message: "System clock is set before 1970-01-01".to_string(),
+        })?
+        .as_secs();
+    let days = i64::try_from(seconds / 86_400).map_err(|_| FunctionError::ValueError {
+        message: "System clock is out of range".to_string(),
+    })?;
+    Ok(CivilDate::from_days_since_epoch(days)?.to_value())
 }
 
 /// Formats a date string according to the given format
@@ -33,8 +393,8 @@ pub fn date_format(date: &Value, format: &Value) -> Result<Value, FunctionError>
     };
 
     // Simple formatting: if format is "YYYY-MM-DD HH:mm:ss", append time
-    match format_str.as_str() {
-        "YYYY-MM-DD HH:mm:ss" => Ok(Value::String(format!("{} 14:30:00", date_str))),
+    match &**format_str {
+        "YYYY-MM-DD HH:mm:ss" => Ok(Value::String(format!("{} 14:30:00", date_str).into())),
         _ => Ok(Value::String(date_str.clone())),
     }
 }
@@ -66,7 +426,7 @@ pub fn date_trunc(datetime: &Value) -> Result<Value, FunctionError> {
         && date_part.chars().nth(4) == Some('-')
         && date_part.chars().nth(7) == Some('-')
     {
-        Ok(Value::String(date_part[..10].to_string()))
+        Ok(Value::String(date_part[..10].into()))
     } else {
         Err(FunctionError::ValueError {
             message: format!("Invalid datetime format: {}", datetime_str),
@@ -135,3 +495,568 @@ pub fn date_parse(date_str: &Value) -> Result<Value, FunctionError> {
     // Return the validated date string
     Ok(Value::String(s.clone()))
 }
+
+/// Returns the difference between two dates in the given unit
+/// date_diff(start: String, end: String, unit: String) -> Number
+///
+/// Weeks, months, and years count only complete periods. The result is
+/// negative when `end` is before `start`.
+pub fn date_diff(start: &Value, end: &Value, unit: &Value) -> Result<Value, FunctionError> {
+    let start = CivilDate::from_value(start)?;
+    let end = CivilDate::from_value(end)?;
+    let unit = date_unit(unit)?;
+    let (earlier, later, sign) = if start <= end {
+        (start, end, 1)
+    } else {
+        (end, start, -1)
+    };
+
+    let amount = match unit {
+        "days" => later.days_since_epoch() - earlier.days_since_epoch(),
+        "weeks" => (later.days_since_epoch() - earlier.days_since_epoch()) / 7,
+        "months" => earlier.whole_months_until(later)?,
+        _ => earlier.whole_months_until(later)? / 12,
+    };
+    Ok(Value::Int(sign * amount))
+}
+
+/// Returns true if the date falls on a Saturday or Sunday
+/// is_weekend(date: String) -> Boolean
+pub fn is_weekend(date: &Value) -> Result<Value, FunctionError> {
+    Ok(Value::Boolean(CivilDate::from_value(date)?.is_weekend()))
+}
+
+/// Counts the business days between two dates
+/// business_days_between(start: String, end: String, holidays?: Array) -> Number
+///
+/// Counts weekdays after `start` up to and including `end`, skipping any
+/// dates in `holidays`, so `business_days_between(d, add_business_days(d, n))`
+/// is `n`. The result is negative when `end` is before `start`.
+pub fn business_days_between(
+    start: &Value,
+    end: &Value,
+    holidays: &Value,
+) -> Result<Value, FunctionError> {
+    let start = CivilDate::from_value(start)?;
+    let end = CivilDate::from_value(end)?;
+    let holidays = holiday_days(holidays)?;
+    let (earlier, later, sign) = if start <= end {
+        (start.days_since_epoch(), end.days_since_epoch(), 1)
+    } else {
+        (end.days_since_epoch(), start.days_since_epoch(), -1)
+    };
+
+    let mut count = 0;
+    for day in earlier + 1..=later {
+        if is_business_day(CivilDate::from_days_since_epoch(day)?, &holidays) {
+            count += 1;
+        }
+    }
+    Ok(Value::Number(f64::from(sign * count)))
+}
+
+/// Moves a date forward (or backward) by a number of business days
+/// add_business_days(date: String, days: Number, holidays?: Array) -> String
+pub fn add_business_days(
+    date: &Value,
+    days: &Value,
+    holidays: &Value,
+) -> Result<Value, FunctionError> {
+    let mut current = CivilDate::from_value(date)?;
+    let days = whole_amount(days)?;
+    let holidays = holiday_days(holidays)?;
+    let step = days.signum();
+
+    let mut remaining = days.abs();
+    while remaining > 0 {
+        current = current.add_days(step)?;
+        if is_business_day(current, &holidays) {
+            remaining -= 1;
+        }
+    }
+    Ok(current.to_value())
+}
+
+fn is_business_day(date: CivilDate, holidays: &HashSet<i64>) -> bool {
+    !date.is_weekend() && !holidays.contains(&date.days_since_epoch())
+}
+
+/// Read an optional holiday list (nil or an array of dates)
+fn holiday_days(holidays: &Value) -> Result<HashSet<i64>, FunctionError> {
+    match holidays {
+        Value::Nil => Ok(HashSet::new()),
+        Value::Array(items) => items
+            .iter()
+            .map(|item| CivilDate::from_value(item).map(CivilDate::days_since_epoch))
+            .collect(),
+        _ => Err(FunctionError::TypeError {
+            expected: "Array".to_string(),
+            got: holidays.type_name().to_string(),
+        }),
+    }
+}
+
+/// Returns the first day of the date's month
+/// beginning_of_month(date: String) -> String
+pub fn beginning_of_month(date: &Value) -> Result<Value, FunctionError> {
+    Ok(CivilDate::from_value(date)?.month_start(0)?.to_value())
+}
+
+/// Returns the last day of the date's month
+/// end_of_month(date: String) -> String
+pub fn end_of_month(date: &Value) -> Result<Value, FunctionError> {
+    Ok(CivilDate::from_value(date)?.month_end(0)?.to_value())
+}
+
+/// Returns the first day of the date's calendar quarter
+/// beginning_of_quarter(date: String) -> String
+pub fn beginning_of_quarter(date: &Value) -> Result<Value, FunctionError> {
+    let date = CivilDate::from_value(date)?;
+    let offset = i64::from((date.month - 1) % 3);
+    Ok(date.month_start(-offset)?.to_value())
+}
+
+/// Returns the last day of the date's calendar quarter
+/// end_of_quarter(date: String) -> String
+pub fn end_of_quarter(date: &Value) -> Result<Value, FunctionError> {
+    let date = CivilDate::from_value(date)?;
+    let offset = i64::from(2 - (date.month - 1) % 3);
+    Ok(date.month_end(offset)?.to_value())
+}
+
+/// Returns a person's age in whole years on a given date
+/// age(birthdate: String, as_of: String) -> Number
+///
+/// A birthday counts once its month and day have been reached, so someone
+/// born on February 29 turns a year older on March 1 in common years.
+pub fn age(birthdate: &Value, as_of: &Value) -> Result<Value, FunctionError> {
+    let birth = CivilDate::from_value(birthdate)?;
+    let as_of = CivilDate::from_value(as_of)?;
+    if as_of < birth {
+        return Err(FunctionError::ArgumentError {
+            message: format!(
+                "age requires as_of on or after the birthdate, got {} before {}",
+                as_of, birth
+            ),
+        });
+    }
+
+    let birthday_pending = (as_of.month, as_of.day) < (birth.month, birth.day);
+    let years = as_of.year - birth.year - i32::from(birthday_pending);
+    Ok(Value::Number(f64::from(years)))
+}
+
+/// Returns the ISO 8601 week number (1 to 53) of a date
+/// iso_week(date: String) -> Number
+///
+/// Weeks start on Monday and belong to the year containing their Thursday,
+/// so early January can fall in week 52 or 53 of the previous year, and
+/// late December in week 1 of the next: 2024-12-30 is in week 1 of 2025.
+/// Pair it with `iso_year` to name the week.
+pub fn iso_week(date: &Value) -> Result<Value, FunctionError> {
+    let (_, week) = iso_week_date(date)?;
+    Ok(Value::Number(week as f64))
+}
+
+/// Returns the ISO 8601 week-numbering year of a date
+/// iso_year(date: String) -> Number
+///
+/// The year the date's `iso_week` belongs to, which differs from the
+/// calendar year near New Year: 2024-12-30 is in 2025, and 2021-01-03 in 2020.
+pub fn iso_year(date: &Value) -> Result<Value, FunctionError> {
+    let (year, _) = iso_week_date(date)?;
+    Ok(Value::Number(f64::from(year)))
+}
+
+/// The ISO 8601 year and week number of a date
+fn iso_week_date(date: &Value) -> Result<(i32, i64), FunctionError> {
+    let date = CivilDate::from_value(date)?;
+    let thursday = date.days_since_epoch() + 3 - date.weekday();
+    let thursday_year = CivilDate::from_days_since_epoch(thursday)?.year;
+    let year_start = CivilDate::new(thursday_year, 1, 1)?.days_since_epoch();
+    Ok((thursday_year, (thursday - year_start) / 7 + 1))
+}
+
+/// Returns the calendar quarter (1 to 4) of a date
+/// quarter(date: String) -> Number
+pub fn quarter(date: &Value) -> Result<Value, FunctionError> {
+    let date = CivilDate::from_value(date)?;
+    Ok(Value::Number(f64::from((date.month - 1) / 3 + 1)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(s: &str) -> Value {
+        Value::String(s.to_string().into())
+    }
+
+    #[test]
+    fn test_civil_date_round_trip() {
+        for s in [
+            "1970-01-01",
+            "2000-02-29",
+            "2024-12-31",
+            "1600-03-01",
+            "9999-12-31",
+        ] {
+            let parsed = CivilDate::from_value(&date(s)).unwrap();
+            let days = parsed.days_since_epoch();
+            assert_eq!(CivilDate::from_days_since_epoch(days).unwrap(), parsed);
+            assert_eq!(parsed.to_value(), date(s));
+        }
+        assert_eq!(
+            CivilDate::from_value(&date("1970-01-02"))
+                .unwrap()
+                .days_since_epoch(),
+            1
+        );
+    }
+
+    #[test]
+    fn test_civil_date_rejects_invalid_dates() {
+        for s in [
+            "2023-02-29",
+            "2024-13-01",
+            "2024-4-1",
+            "20240101",
+            "not a date",
+        ] {
+            assert!(
+                CivilDate::from_value(&date(s)).is_err(),
+                "expected {} to be rejected",
+                s
+            );
+        }
+        assert!(CivilDate::from_value(&Value::Number(1.0)).is_err());
+        assert!(CivilDate::from_value(&date("2024-03-15T10:00:00Z")).is_ok());
+    }
+
+    #[test]
+    fn test_date_add() {
+        let cases = [
+            ("2024-01-30", 5.0, "days", "2024-02-04"),
+            ("2024-01-01", -1.0, "day", "2023-12-31"),
+            ("2024-01-01", 2.0, "weeks", "2024-01-15"),
+            ("2024-01-31", 1.0, "months", "2024-02-29"),
+            ("2023-01-31", 1.0, "months", "2023-02-28"),
+            ("2024-11-30", 3.0, "months", "2025-02-28"),
+            ("2024-02-29", 1.0, "years", "2025-02-28"),
+            ("2024-02-29", 4.0, "years", "2028-02-29"),
+        ];
+        for (start, amount, unit, expected) in cases {
+            assert_eq!(
+                date_add(&date(start), &Value::Number(amount), &date(unit)).unwrap(),
+                date(expected),
+                "{} + {} {}",
+                start,
+                amount,
+                unit
+            );
+        }
+
+        assert_eq!(
+            date_add(
+                &date("2024-01-31"),
+                &Value::Number(1.0),
+                &Value::Symbol("month".to_string())
+            )
+            .unwrap(),
+            date("2024-02-29")
+        );
+        assert!(matches!(
+            date_add(&date("2024-01-01"), &Value::Number(1.5), &date("days")),
+            Err(FunctionError::ArgumentError { .. })
+        ));
+        assert!(matches!(
+            date_add(
+                &date("2024-01-01"),
+                &Value::Number(1.0),
+                &date("fortnights")
+            ),
+            Err(FunctionError::ArgumentError { .. })
+        ));
+    }
+
+    #[test]
+    fn test_date_sub() {
+        assert_eq!(
+            date_sub(&date("2024-03-31"), &Value::Number(1.0), &date("months")).unwrap(),
+            date("2024-02-29")
+        );
+        assert_eq!(
+            date_sub(&date("2024-03-01"), &Value::Number(1.0), &date("days")).unwrap(),
+            date("2024-02-29")
+        );
+    }
+
+    #[test]
+    fn test_date_diff() {
+        let cases = [
+            ("2024-01-01", "2024-03-01", "days", 60),
+            ("2024-03-01", "2024-01-01", "days", -60),
+            ("2024-01-01", "2024-01-14", "weeks", 1),
+            ("2024-01-15", "2024-03-14", "months", 1),
+            ("2024-01-15", "2024-03-15", "months", 2),
+            ("2024-01-31", "2024-02-29", "months", 1),
+            ("2024-03-15", "2024-01-15", "months", -2),
+            ("2020-02-29", "2024-02-28", "years", 3),
+            ("2020-02-29", "2024-02-29", "years", 4),
+            ("2024-06-01", "2024-06-01", "years", 0),
+        ];
+        for (start, end, unit, expected) in cases {
+            assert!(
+                matches!(
+                    date_diff(&date(start), &date(end), &date(unit)),
+                    Ok(Value::Int(n)) if n == expected
+                ),
+                "{} to {} in {}",
+                start,
+                end,
+                unit
+            );
+        }
+
+        assert!(matches!(
+            date_diff(&date("2024-01-01"), &Value::Nil, &date("days")),
+            Err(FunctionError::TypeError { .. })
+        ));
+    }
+
+    #[test]
+    fn test_is_weekend() {
+        // 2024-06-01 was a Saturday
+        assert_eq!(
+            is_weekend(&date("2024-06-01")).unwrap(),
+            Value::Boolean(true)
+        );
+        assert_eq!(
+            is_weekend(&date("2024-06-02")).unwrap(),
+            Value::Boolean(true)
+        );
+        assert_eq!(
+            is_weekend(&date("2024-06-03")).unwrap(),
+            Value::Boolean(false)
+        );
+        assert_eq!(
+            is_weekend(&date("2024-06-07")).unwrap(),
+            Value::Boolean(false)
+        );
+    }
+
+    #[test]
+    fn test_business_days() {
+        // Friday to the following Friday
+        assert_eq!(
+            business_days_between(&date("2024-06-07"), &date("2024-06-14"), &Value::Nil).unwrap(),
+            Value::Number(5.0)
+        );
+        assert_eq!(
+            business_days_between(&date("2024-06-14"), &date("2024-06-07"), &Value::Nil).unwrap(),
+            Value::Number(-5.0)
+        );
+
+        let holidays = Value::Array(vec![date("2024-06-10")].into());
+        assert_eq!(
+            business_days_between(&date("2024-06-07"), &date("2024-06-14"), &holidays).unwrap(),
+            Value::Number(4.0)
+        );
+
+        assert_eq!(
+            add_business_days(&date("2024-06-07"), &Value::Number(1.0), &Value::Nil).unwrap(),
+            date("2024-06-10")
+        );
+        assert_eq!(
+            add_business_days(&date("2024-06-07"), &Value::Number(1.0), &holidays).unwrap(),
+            date("2024-06-11")
+        );
+        assert_eq!(
+            add_business_days(&date("2024-06-10"), &Value::Number(-1.0), &Value::Nil).unwrap(),
+            date("2024-06-07")
+        );
+        assert_eq!(
+            add_business_days(&date("2024-06-08"), &Value::Number(0.0), &Value::Nil).unwrap(),
+            date("2024-06-08")
+        );
+
+        assert!(matches!(
+            add_business_days(
+                &date("2024-06-07"),
+                &Value::Number(1.0),
+                &date("2024-06-10")
+            ),
+            Err(FunctionError::TypeError { .. })
+        ));
+    }
+
+    #[test]
+    fn test_period_boundaries() {
+        type Boundary = fn(&Value) -> Result<Value, FunctionError>;
+        let cases: [(Boundary, &str, &str); 8] = [
+            (beginning_of_month, "2024-02-17", "2024-02-01"),
+            (end_of_month, "2024-02-17", "2024-02-29"),
+            (end_of_month, "2023-02-17", "2023-02-28"),
+            (end_of_month, "2024-12-31", "2024-12-31"),
+            (beginning_of_quarter, "2024-05-20", "2024-04-01"),
+            (end_of_quarter, "2024-05-20", "2024-06-30"),
+            (end_of_quarter, "2024-10-01", "2024-12-31"),
+            (beginning_of_quarter, "2024-03-31", "2024-01-01"),
+        ];
+        for (function, input, expected) in cases {
+            assert_eq!(function(&date(input)).unwrap(), date(expected), "{}", input);
+        }
+    }
+
+    #[test]
+    fn test_age() {
+        let cases = [
+            ("1990-06-15", "2024-06-14", 33.0),
+            ("1990-06-15", "2024-06-15", 34.0),
+            ("2000-02-29", "2023-02-28", 22.0),
+            ("2000-02-29", "2023-03-01", 23.0),
+            ("2000-02-29", "2024-02-29", 24.0),
+            ("2024-01-01", "2024-01-01", 0.0),
+        ];
+        for (birth, as_of, expected) in cases {
+            assert_eq!(
+                age(&date(birth), &date(as_of)).unwrap(),
+                Value::Number(expected),
+                "born {} as of {}",
+                birth,
+                as_of
+            );
+        }
+
+        assert!(matches!(
+            age(&date("2024-01-02"), &date("2024-01-01")),
+            Err(FunctionError::ArgumentError { .. })
+        ));
+    }
+
+    #[test]
+    fn test_iso_week() {
+        let cases = [
+            ("2024-01-01", 1.0),
+            ("2024-12-30", 1.0),
+            ("2021-01-03", 53.0),
+            ("2023-01-01", 52.0),
+            ("2020-12-31", 53.0),
+            ("2024-06-15", 24.0),
+        ];
+        for (input, expected) in cases {
+            assert_eq!(
+                iso_week(&date(input)).unwrap(),
+                Value::Number(expected),
+                "{}",
+                input
+            );
+        }
+    }
+
+    #[test]
+    fn test_iso_year() {
+        let cases = [
+            ("2024-01-01", 2024.0),
+            ("2024-12-29", 2024.0),
+            ("2024-12-30", 2025.0),
+            ("2021-01-03", 2020.0),
+            ("2021-01-04", 2021.0),
+            ("2020-12-31", 2020.0),
+        ];
+        for (input, expected) in cases {
+            assert_eq!(
+                iso_year(&date(input)).unwrap(),
+                Value::Number(expected),
+                "{}",
+                input
+            );
+        }
+        assert!(iso_year(&Value::Nil).is_err());
+    }
+
+    #[test]
+    fn test_quarter() {
+        assert_eq!(quarter(&date("2024-01-01")).unwrap(), Value::Number(1.0));
+        assert_eq!(quarter(&date("2024-06-30")).unwrap(), Value::Number(2.0));
+        assert_eq!(quarter(&date("2024-07-01")).unwrap(), Value::Number(3.0));
+        assert_eq!(quarter(&date("2024-12-31")).unwrap(), Value::Number(4.0));
+        assert!(quarter(&Value::Nil).is_err());
+    }
+
+    #[test]
+    fn test_durations() {
+        assert_eq!(
+            duration_weeks(&Value::Number(2.0)).unwrap(),
+            Value::Duration(Duration::days(14))
+        );
+        assert_eq!(
+            duration_years(&Value::Number(1.0)).unwrap(),
+            Value::Duration(Duration::months(12))
+        );
+        assert!(duration_days(&Value::Number(1.5)).is_err());
+
+        let month_and_a_day = Value::Duration(Duration { months: 1, days: 1 });
+        assert_eq!(
+            add_duration(&date("2024-01-31"), &month_and_a_day).unwrap(),
+            date("2024-03-01")
+        );
+        assert_eq!(
+            subtract_duration(
+                &date("2024-03-31"),
+                &duration_months(&Value::Number(1.0)).unwrap()
+            )
+            .unwrap(),
+            date("2024-02-29")
+        );
+        assert!(matches!(
+            add_duration(&date("2024-01-31"), &Value::Number(30.0)),
+            Err(FunctionError::TypeError { .. })
+        ));
+
+        assert_eq!(
+            duration_between(&date("2024-01-01"), &date("2024-03-01")).unwrap(),
+            Value::Duration(Duration::days(60))
+        );
+        assert_eq!(
+            duration_between(&date("2024-03-01"), &date("2024-01-01")).unwrap(),
+            Value::Duration(Duration::days(-60))
+        );
+    }
+
+    #[test]
+    fn test_date_comparison() {
+        assert_eq!(
+            date_before(&date("2023-12-31"), &date("2024-01-01")).unwrap(),
+            Value::Boolean(true)
+        );
+        assert_eq!(
+            date_after(&date("2023-12-31"), &date("2024-01-01")).unwrap(),
+            Value::Boolean(false)
+        );
+        assert_eq!(
+            date_compare(&date("2024-01-01T23:00:00Z"), &date("2024-01-01")).unwrap(),
+            Value::Number(0.0)
+        );
+        assert_eq!(
+            date_compare(&date("2024-02-01"), &date("2024-01-15")).unwrap(),
+            Value::Number(1.0)
+        );
+        assert_eq!(
+            date_compare(&date("2024-01-15"), &date("2024-02-01")).unwrap(),
+            Value::Number(-1.0)
+        );
+
+        // Formats that would compare lexically by accident are rejected
+        assert!(matches!(
+            date_before(&date("12/31/2023"), &date("2024-01-01")),
+            Err(FunctionError::ValueError { .. })
+        ));
+    }
+
+    #[test]
+    fn test_date_now_is_a_valid_date() {
+        let today = date_now().unwrap();
+        assert!(CivilDate::from_value(&today).is_ok(), "got {:?}", today);
+    }
+}
@@ -1,18 +1,22 @@
 //! Amoskeag Standard Library - Functions
 //!
 //! This crate implements the standard library functions for the Amoskeag language,
-//! organized by category: string, numeric, collection, logic, date, and financial functions.
+//! organized by category: string, numeric, collection, conversion, logic, date, and
+//! financial functions.
 
 // Re-export the Value type from operators
-pub use amoskeag_stdlib_operators::{OperatorError, Value};
+pub use amoskeag_stdlib_operators::{Decimal, Duration, OperatorError, Value};
 
 // Re-export inventory for function registration
 pub use inventory;
 
+pub mod codegen;
 pub mod collection;
+pub mod conversion;
 pub mod date;
 pub mod logic;
 pub mod numeric;
+pub mod signatures;
 pub mod string;
 
 /// Error types for function operations
@@ -23,6 +27,7 @@ pub enum FunctionError {
     IndexOutOfBounds { index: usize, len: usize },
     InvalidOperation { message: String },
     ValueError { message: String },
+    InvalidPattern { pattern: String, message: String },
 }
 
 impl std::fmt::Display for FunctionError {
@@ -47,6 +52,9 @@ impl std::fmt::Display for FunctionError {
             FunctionError::ValueError { message } => {
                 write!(f, "Value error: {}", message)
             }
+            FunctionError::InvalidPattern { pattern, message } => {
+                write!(f, "Invalid pattern '{}': {}", pattern, message)
+            }
         }
     }
 }
@@ -55,6 +63,7 @@ impl std::error::Error for FunctionError {}
 
 // Re-export all public functions for convenience
 pub use collection::*;
+pub use conversion::*;
 pub use date::*;
 pub use logic::*;
 pub use numeric::*;
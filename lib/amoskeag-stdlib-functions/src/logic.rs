@@ -1,20 +1,22 @@
 //! Logic and conditional functions for Amoskeag
 
+use crate::collection::index_value;
 use crate::{FunctionError, Value};
+use std::cmp::Ordering;
 
 /// Choose an element from an array by 1-based index (Excel-style)
 /// choose(index: Number, arr: Array) -> Any
 pub fn choose(index: &Value, array: &Value) -> Result<Value, FunctionError> {
-    match (index, array) {
-        (Value::Number(idx), Value::Array(arr)) => {
+    match (index_value(index), array) {
+        (Some(idx), Value::Array(arr)) => {
             // 1-based indexing (Excel style)
-            if *idx < 1.0 {
+            if idx < 1 {
                 return Ok(Value::Nil);
             }
-            let i = (*idx as usize) - 1;
+            let i = (idx - 1) as usize;
             Ok(arr.get(i).cloned().unwrap_or(Value::Nil))
         }
-        (Value::Number(_), _) => Err(FunctionError::TypeError {
+        (Some(_), _) => Err(FunctionError::TypeError {
             expected: "Array".to_string(),
             got: array.type_name().to_string(),
         }),
@@ -87,20 +89,84 @@ pub fn is_symbol(value: &Value) -> Value {
     Value::Boolean(matches!(value, Value::Symbol(_)))
 }
 
-/// Coalesce: return the first non-nil value
-/// coalesce(val1: Any, val2: Any) -> Any
-pub fn coalesce(val1: &Value, val2: &Value) -> Value {
-    if matches!(val1, Value::Nil) {
-        val2.clone()
-    } else {
-        val1.clone()
+/// Check whether a value lies within a range
+/// between(value: Number | String, lo: Number | String, hi: Number | String, bounds: String) -> Boolean
+///
+/// `bounds` uses interval notation: "[]" includes both ends (the default),
+/// "()" excludes both, and "[)" or "(]" include only one. Strings compare
+/// lexicographically, which orders `YYYY-MM-DD` dates correctly.
+pub fn between(
+    value: &Value,
+    lo: &Value,
+    hi: &Value,
+    bounds: &Value,
+) -> Result<Value, FunctionError> {
+    let (lower_inclusive, upper_inclusive) = match bounds {
+        Value::String(b) => parse_bounds(b)?,
+        _ => {
+            return Err(FunctionError::TypeError {
+                expected: "String".to_string(),
+                got: bounds.type_name().to_string(),
+            })
+        }
+    };
+
+    let ordering = |other: &Value| match (value, other) {
+        (Value::Number(a), Value::Number(b)) => Ok(a.partial_cmp(b)),
+        (Value::String(a), Value::String(b)) => Ok(Some(a.cmp(b))),
+        (Value::Number(_) | Value::String(_), _) => Err(FunctionError::TypeError {
+            expected: value.type_name().to_string(),
+            got: other.type_name().to_string(),
+        }),
+        _ => Err(FunctionError::TypeError {
+            expected: "Number or String".to_string(),
+            got: value.type_name().to_string(),
+        }),
+    };
+
+    let above_lo = match ordering(lo)? {
+        Some(Ordering::Greater) => true,
+        Some(Ordering::Equal) => lower_inclusive,
+        _ => false,
+    };
+    let below_hi = match ordering(hi)? {
+        Some(Ordering::Less) => true,
+        Some(Ordering::Equal) => upper_inclusive,
+        _ => false,
+    };
+    Ok(Value::Boolean(above_lo && below_hi))
+}
+
+/// Parse a `between` bounds flag into (lower inclusive, upper inclusive)
+pub fn parse_bounds(bounds: &str) -> Result<(bool, bool), FunctionError> {
+    match bounds {
+        "[]" => Ok((true, true)),
+        "[)" => Ok((true, false)),
+        "(]" => Ok((false, true)),
+        "()" => Ok((false, false)),
+        _ => Err(FunctionError::ArgumentError {
+            message: format!(
+                "bounds must be one of \"[]\", \"[)\", \"(]\", or \"()\", got {:?}",
+                bounds
+            ),
+        }),
     }
 }
 
+/// Coalesce: return the first non-nil value, or nil if every value is nil
+/// coalesce(val1: Any, val2: Any, ...) -> Any
+pub fn coalesce(values: &[Value]) -> Value {
+    values
+        .iter()
+        .find(|value| !matches!(value, Value::Nil))
+        .cloned()
+        .unwrap_or(Value::Nil)
+}
+
 /// Default: return value if not nil, else return default
 /// default(val: Any, default_val: Any) -> Any
 pub fn default(value: &Value, default_val: &Value) -> Value {
-    coalesce(value, default_val)
+    coalesce(&[value.clone(), default_val.clone()])
 }
 
 #[cfg(test)]
@@ -109,24 +175,27 @@ mod tests {
 
     #[test]
     fn test_choose() {
-        let arr = Value::Array(vec![
-            Value::String("first".to_string()),
-            Value::String("second".to_string()),
-            Value::String("third".to_string()),
-        ]);
+        let arr = Value::Array(
+            vec![
+                Value::String("first".into()),
+                Value::String("second".into()),
+                Value::String("third".into()),
+            ]
+            .into(),
+        );
 
         // 1-based indexing
         assert_eq!(
             choose(&Value::Number(1.0), &arr).unwrap(),
-            Value::String("first".to_string())
+            Value::String("first".into())
         );
         assert_eq!(
             choose(&Value::Number(2.0), &arr).unwrap(),
-            Value::String("second".to_string())
+            Value::String("second".into())
         );
         assert_eq!(
             choose(&Value::Number(3.0), &arr).unwrap(),
-            Value::String("third".to_string())
+            Value::String("third".into())
         );
 
         // Out of bounds
@@ -134,6 +203,12 @@ mod tests {
 
         // Index 0 should return nil (1-based)
         assert_eq!(choose(&Value::Number(0.0), &arr).unwrap(), Value::Nil);
+
+        assert_eq!(
+            choose(&Value::Int(2), &arr).unwrap(),
+            Value::String("second".into())
+        );
+        assert_eq!(choose(&Value::Number(f64::NAN), &arr).unwrap(), Value::Nil);
     }
 
     #[test]
@@ -141,42 +216,42 @@ mod tests {
         assert_eq!(
             if_then_else(
                 &Value::Boolean(true),
-                &Value::String("yes".to_string()),
-                &Value::String("no".to_string())
+                &Value::String("yes".into()),
+                &Value::String("no".into())
             )
             .unwrap(),
-            Value::String("yes".to_string())
+            Value::String("yes".into())
         );
 
         assert_eq!(
             if_then_else(
                 &Value::Boolean(false),
-                &Value::String("yes".to_string()),
-                &Value::String("no".to_string())
+                &Value::String("yes".into()),
+                &Value::String("no".into())
             )
             .unwrap(),
-            Value::String("no".to_string())
+            Value::String("no".into())
         );
 
         // Truthy/falsy behavior
         assert_eq!(
             if_then_else(
                 &Value::Nil,
-                &Value::String("yes".to_string()),
-                &Value::String("no".to_string())
+                &Value::String("yes".into()),
+                &Value::String("no".into())
             )
             .unwrap(),
-            Value::String("no".to_string())
+            Value::String("no".into())
         );
 
         assert_eq!(
             if_then_else(
                 &Value::Number(42.0),
-                &Value::String("yes".to_string()),
-                &Value::String("no".to_string())
+                &Value::String("yes".into()),
+                &Value::String("no".into())
             )
             .unwrap(),
-            Value::String("yes".to_string())
+            Value::String("yes".into())
         );
     }
 
@@ -190,7 +265,7 @@ mod tests {
     fn test_is_number() {
         assert_eq!(is_number(&Value::Number(42.0)), Value::Boolean(true));
         assert_eq!(
-            is_number(&Value::String("42".to_string())),
+            is_number(&Value::String("42".into())),
             Value::Boolean(false)
         );
     }
@@ -198,7 +273,7 @@ mod tests {
     #[test]
     fn test_is_string() {
         assert_eq!(
-            is_string(&Value::String("hello".to_string())),
+            is_string(&Value::String("hello".into())),
             Value::Boolean(true)
         );
         assert_eq!(is_string(&Value::Number(42.0)), Value::Boolean(false));
@@ -212,37 +287,104 @@ mod tests {
 
     #[test]
     fn test_is_array() {
-        assert_eq!(is_array(&Value::Array(vec![])), Value::Boolean(true));
-        assert_eq!(
-            is_array(&Value::String("[]".to_string())),
-            Value::Boolean(false)
-        );
+        assert_eq!(is_array(&Value::Array(vec![].into())), Value::Boolean(true));
+        assert_eq!(is_array(&Value::String("[]".into())), Value::Boolean(false));
     }
 
     #[test]
     fn test_coalesce() {
         assert_eq!(
-            coalesce(&Value::Nil, &Value::Number(42.0)),
+            coalesce(&[Value::Nil, Value::Number(42.0)]),
             Value::Number(42.0)
         );
         assert_eq!(
-            coalesce(&Value::Number(10.0), &Value::Number(42.0)),
+            coalesce(&[Value::Number(10.0), Value::Number(42.0)]),
             Value::Number(10.0)
         );
+        assert_eq!(
+            coalesce(&[
+                Value::Nil,
+                Value::Nil,
+                Value::Boolean(false),
+                Value::Number(1.0)
+            ]),
+            Value::Boolean(false)
+        );
+        assert_eq!(coalesce(&[Value::Nil, Value::Nil]), Value::Nil);
     }
 
     #[test]
     fn test_default() {
         assert_eq!(
-            default(&Value::Nil, &Value::String("default".to_string())),
-            Value::String("default".to_string())
+            default(&Value::Nil, &Value::String("default".into())),
+            Value::String("default".into())
         );
         assert_eq!(
             default(
-                &Value::String("value".to_string()),
-                &Value::String("default".to_string())
+                &Value::String("value".into()),
+                &Value::String("default".into())
             ),
-            Value::String("value".to_string())
+            Value::String("value".into())
         );
     }
+
+    #[test]
+    fn test_between() {
+        let check = |v: f64, bounds: &str| {
+            between(
+                &Value::Number(v),
+                &Value::Number(18.0),
+                &Value::Number(25.0),
+                &Value::String(bounds.into()),
+            )
+            .unwrap()
+        };
+        assert_eq!(check(18.0, "[]"), Value::Boolean(true));
+        assert_eq!(check(25.0, "[]"), Value::Boolean(true));
+        assert_eq!(check(25.0, "[)"), Value::Boolean(false));
+        assert_eq!(check(18.0, "(]"), Value::Boolean(false));
+        assert_eq!(check(20.0, "()"), Value::Boolean(true));
+        assert_eq!(check(18.0, "()"), Value::Boolean(false));
+        assert_eq!(check(17.9, "[]"), Value::Boolean(false));
+        assert_eq!(check(f64::NAN, "[]"), Value::Boolean(false));
+
+        let date = |s: &str| Value::String(s.into());
+        assert_eq!(
+            between(
+                &date("2024-06-15"),
+                &date("2024-01-01"),
+                &date("2024-12-31"),
+                &date("[]")
+            )
+            .unwrap(),
+            Value::Boolean(true)
+        );
+    }
+
+    #[test]
+    fn test_between_invalid_inputs() {
+        let bounds = Value::String("[]".into());
+        assert!(matches!(
+            between(
+                &Value::Number(1.0),
+                &Value::String("0".into()),
+                &Value::Number(2.0),
+                &bounds
+            ),
+            Err(FunctionError::TypeError { .. })
+        ));
+        assert!(matches!(
+            between(&Value::Nil, &Value::Nil, &Value::Nil, &bounds),
+            Err(FunctionError::TypeError { .. })
+        ));
+        assert!(matches!(
+            between(
+                &Value::Number(1.0),
+                &Value::Number(0.0),
+                &Value::Number(2.0),
+                &Value::String("inclusive".into())
+            ),
+            Err(FunctionError::ArgumentError { .. })
+        ));
+    }
 }
@@ -7,6 +7,7 @@ use crate::{FunctionError, Value};
 pub fn abs(value: &Value) -> Result<Value, FunctionError> {
     match value {
         Value::Number(n) => Ok(Value::Number(n.abs())),
+        Value::Decimal(d) => Ok(Value::Decimal(d.abs())),
         _ => Err(FunctionError::TypeError {
             expected: "Number".to_string(),
             got: value.type_name().to_string(),
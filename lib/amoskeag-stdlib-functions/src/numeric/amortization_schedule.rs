@@ -0,0 +1,165 @@
+//! amortization_schedule function
+
+use super::decimal::{decimal_args, overflow};
+use super::pmt::pmt;
+use crate::{Decimal, FunctionError, Value};
+use rust_decimal::prelude::ToPrimitive;
+use std::collections::HashMap;
+
+/// Largest number of periods a schedule may contain
+pub const MAX_SCHEDULE_PERIODS: f64 = 10_000.0;
+
+/// Build the period-by-period repayment schedule of a fixed-rate loan
+/// amortization_schedule(rate: Number, nper: Number, pv: Number) -> Array
+///
+/// Each entry is a dictionary with `period`, `payment`, `interest`,
+/// `principal`, and `balance`. Payment, interest, and principal use the same
+/// sign convention as pmt, ipmt, and ppmt (negative for a loan received);
+/// balance is the amount still owed after the payment. Payments are made at
+/// the end of each period, and the final period absorbs rounding drift so the
+/// balance ends at exactly 0.
+///
+/// Decimal arguments give a schedule of decimals, computed exactly to 28
+/// significant digits.
+///
+/// Example: amortization_schedule(0.05/12, 12, 1000) = 12 monthly entries
+pub fn amortization_schedule(
+    rate: &Value,
+    nper: &Value,
+    pv: &Value,
+) -> Result<Value, FunctionError> {
+    if let Some([r, n, principal]) = decimal_args([rate, nper, pv]) {
+        return schedule_decimal(r, n, principal);
+    }
+    let (r, n, principal) = match (rate, nper, pv) {
+        (Value::Number(r), Value::Number(n), Value::Number(p)) => (*r, *n, *p),
+        (Value::Number(_), Value::Number(_), _) => {
+            return Err(FunctionError::TypeError {
+                expected: "Number".to_string(),
+                got: pv.type_name().to_string(),
+            })
+        }
+        (Value::Number(_), _, _) => {
+            return Err(FunctionError::TypeError {
+                expected: "Number".to_string(),
+                got: nper.type_name().to_string(),
+            })
+        }
+        _ => {
+            return Err(FunctionError::TypeError {
+                expected: "Number".to_string(),
+                got: rate.type_name().to_string(),
+            })
+        }
+    };
+
+    if n < 1.0 || n.fract() != 0.0 || n > MAX_SCHEDULE_PERIODS {
+        return Err(periods_error());
+    }
+
+    let payment = match pmt(rate, nper, pv, &Value::Number(0.0))? {
+        Value::Number(p) => -p,
+        _ => unreachable!(),
+    };
+
+    let periods = n as usize;
+    let mut balance = principal;
+    let mut schedule = Vec::with_capacity(periods);
+    for period in 1..=periods {
+        let interest = balance * r;
+        let (paid, repaid) = if period == periods {
+            (balance + interest, balance)
+        } else {
+            (payment, payment - interest)
+        };
+        balance = if period == periods {
+            0.0
+        } else {
+            balance - repaid
+        };
+
+        schedule.push(entry(
+            Value::Number(period as f64),
+            Value::Number(-paid),
+            Value::Number(-interest),
+            Value::Number(-repaid),
+            Value::Number(balance),
+        ));
+    }
+
+    Ok(Value::Array(schedule.into()))
+}
+
+fn schedule_decimal(r: Decimal, n: Decimal, principal: Decimal) -> Result<Value, FunctionError> {
+    if n < Decimal::ONE || !n.fract().is_zero() || n > Decimal::from(MAX_SCHEDULE_PERIODS as i64) {
+        return Err(periods_error());
+    }
+
+    let payment = match pmt(
+        &Value::Decimal(r),
+        &Value::Decimal(n),
+        &Value::Decimal(principal),
+        &Value::Decimal(Decimal::ZERO),
+    )? {
+        Value::Decimal(p) => -p,
+        _ => unreachable!(),
+    };
+
+    let periods = n.to_u32().unwrap_or_default();
+    let mut balance = principal;
+    let mut schedule = Vec::with_capacity(periods as usize);
+    for period in 1..=periods {
+        let step = || {
+            let interest = balance.checked_mul(r)?;
+            if period == periods {
+                Some((
+                    balance.checked_add(interest)?,
+                    interest,
+                    balance,
+                    Decimal::ZERO,
+                ))
+            } else {
+                let repaid = payment.checked_sub(interest)?;
+                Some((payment, interest, repaid, balance.checked_sub(repaid)?))
+            }
+        };
+        let (paid, interest, repaid, remaining) =
+            step().ok_or_else(|| overflow("amortization_schedule"))?;
+        balance = remaining;
+
+        schedule.push(entry(
+            Value::Decimal(Decimal::from(period)),
+            Value::Decimal(-paid),
+            Value::Decimal(-interest),
+            Value::Decimal(-repaid),
+            Value::Decimal(balance),
+        ));
+    }
+
+    Ok(Value::Array(schedule.into()))
+}
+
+fn entry(
+    period: Value,
+    payment: Value,
+    interest: Value,
+    principal: Value,
+    balance: Value,
+) -> Value {
+    let mut entry = HashMap::new();
+    entry.insert("period".to_string(), period);
+    entry.insert("payment".to_string(), payment);
+    entry.insert("interest".to_string(), interest);
+    entry.insert("principal".to_string(), principal);
+    entry.insert("balance".to_string(), balance);
+    Value::Dictionary(entry.into())
+}
+
+fn periods_error() -> FunctionError {
+    FunctionError::ArgumentError {
+        message: format!(
+            "nper must be a whole number between 1 and {}",
+            MAX_SCHEDULE_PERIODS
+        ),
+    }
+}
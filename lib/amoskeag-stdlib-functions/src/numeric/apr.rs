@@ -0,0 +1,138 @@
+//! apr, apr_effective, periodic_to_annual, and annual_to_periodic functions
+
+use super::rate::rate;
+use crate::{FunctionError, Value};
+
+/// Calculate the fee-adjusted annual percentage rate of a loan
+/// apr(nper: Number, pmt: Number, pv: Number, fees: Number, npery: Number) -> Number
+///
+/// Solves for the periodic rate at which the payments repay the amount
+/// actually received (pv - fees), then annualizes it nominally:
+/// APR = rate(nper, pmt, pv - fees) * npery
+///
+/// Example: apr(36, -304.22, 10000, 300, 12) ≈ 0.0806 for a 6% loan with $300 in fees
+pub fn apr(
+    nper: &Value,
+    pmt: &Value,
+    pv: &Value,
+    fees: &Value,
+    npery: &Value,
+) -> Result<Value, FunctionError> {
+    let (periodic, periods_per_year) = fee_adjusted_rate(nper, pmt, pv, fees, npery)?;
+    Ok(Value::Number(periodic * periods_per_year))
+}
+
+/// Calculate the fee-adjusted effective annual rate of a loan
+/// apr_effective(nper: Number, pmt: Number, pv: Number, fees: Number, npery: Number) -> Number
+///
+/// Like apr, but compounds the periodic rate over the year:
+/// EAR = (1 + rate(nper, pmt, pv - fees))^npery - 1
+pub fn apr_effective(
+    nper: &Value,
+    pmt: &Value,
+    pv: &Value,
+    fees: &Value,
+    npery: &Value,
+) -> Result<Value, FunctionError> {
+    let (periodic, periods_per_year) = fee_adjusted_rate(nper, pmt, pv, fees, npery)?;
+    Ok(Value::Number((1.0 + periodic).powf(periods_per_year) - 1.0))
+}
+
+/// Convert a periodic rate to the equivalent effective annual rate
+/// periodic_to_annual(rate: Number, npery: Number) -> Number
+///
+/// Formula: annual = (1 + rate)^npery - 1
+///
+/// Example: periodic_to_annual(0.01, 12) = 0.1268 (1% a month)
+pub fn periodic_to_annual(rate: &Value, npery: &Value) -> Result<Value, FunctionError> {
+    let (r, n) = rate_and_periods(rate, npery)?;
+    Ok(Value::Number((1.0 + r).powf(n) - 1.0))
+}
+
+/// Convert an effective annual rate to the equivalent periodic rate
+/// annual_to_periodic(rate: Number, npery: Number) -> Number
+///
+/// Formula: periodic = (1 + rate)^(1 / npery) - 1
+///
+/// Example: annual_to_periodic(0.1268, 12) ≈ 0.01
+pub fn annual_to_periodic(rate: &Value, npery: &Value) -> Result<Value, FunctionError> {
+    let (r, n) = rate_and_periods(rate, npery)?;
+    Ok(Value::Number((1.0 + r).powf(1.0 / n) - 1.0))
+}
+
+fn rate_and_periods(rate: &Value, npery: &Value) -> Result<(f64, f64), FunctionError> {
+    match (rate, npery) {
+        (Value::Number(r), Value::Number(n)) => {
+            if *n < 1.0 {
+                return Err(FunctionError::ArgumentError {
+                    message: "npery must be at least 1".to_string(),
+                });
+            }
+
+            if *r <= -1.0 {
+                return Err(FunctionError::ArgumentError {
+                    message: "rate must be greater than -1".to_string(),
+                });
+            }
+
+            Ok((*r, *n))
+        }
+        (Value::Number(_), _) => Err(FunctionError::TypeError {
+            expected: "Number".to_string(),
+            got: npery.type_name().to_string(),
+        }),
+        _ => Err(FunctionError::TypeError {
+            expected: "Number".to_string(),
+            got: rate.type_name().to_string(),
+        }),
+    }
+}
+
+/// Periodic rate on the amount received after fees, and periods per year
+fn fee_adjusted_rate(
+    nper: &Value,
+    pmt: &Value,
+    pv: &Value,
+    fees: &Value,
+    npery: &Value,
+) -> Result<(f64, f64), FunctionError> {
+    let (principal, fee, periods_per_year) = match (pv, fees, npery) {
+        (Value::Number(p), Value::Number(f), Value::Number(n)) => (*p, *f, *n),
+        (Value::Number(_), Value::Number(_), _) => {
+            return Err(FunctionError::TypeError {
+                expected: "Number".to_string(),
+                got: npery.type_name().to_string(),
+            })
+        }
+        (Value::Number(_), _, _) => {
+            return Err(FunctionError::TypeError {
+                expected: "Number".to_string(),
+                got: fees.type_name().to_string(),
+            })
+        }
+        _ => {
+            return Err(FunctionError::TypeError {
+                expected: "Number".to_string(),
+                got: pv.type_name().to_string(),
+            })
+        }
+    };
+
+    if periods_per_year < 1.0 {
+        return Err(FunctionError::ArgumentError {
+            message: "npery must be at least 1".to_string(),
+        });
+    }
+
+    if fee < 0.0 || fee >= principal.abs() {
+        return Err(FunctionError::ArgumentError {
+            message: "fees must be non-negative and less than pv".to_string(),
+        });
+    }
+
+    let net = principal - fee * principal.signum();
+    match rate(nper, pmt, &Value::Number(net))? {
+        Value::Number(periodic) => Ok((periodic, periods_per_year)),
+        _ => unreachable!(),
+    }
+}
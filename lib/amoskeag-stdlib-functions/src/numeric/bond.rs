@@ -0,0 +1,326 @@
+//! price, yield, duration, and mduration bond functions
+//!
+//! These follow Excel's semantics: coupon dates are counted back from
+//! maturity, and `basis` selects the day count convention:
+//! 0 = US 30/360, 1 = actual/actual, 2 = actual/360, 3 = actual/365,
+//! 4 = European 30/360.
+
+use crate::date::CivilDate;
+use crate::{FunctionError, Value};
+
+/// Calculate the price per 100 face value of a bond paying periodic interest
+/// price(settlement: String, maturity: String, rate: Number, yld: Number,
+///       redemption: Number, frequency: Number, basis: Number) -> Number
+///
+/// Example: price('2008-02-15', '2017-11-15', 0.0575, 0.065, 100, 2, 0) = 94.63
+pub fn price(
+    settlement: &Value,
+    maturity: &Value,
+    rate: &Value,
+    yld: &Value,
+    redemption: &Value,
+    frequency: &Value,
+    basis: &Value,
+) -> Result<Value, FunctionError> {
+    let bond = Bond::new(settlement, maturity, frequency, basis)?;
+    let rate = non_negative("rate", rate)?;
+    let yld = non_negative("yld", yld)?;
+    let redemption = positive("redemption", redemption)?;
+    Ok(Value::Number(bond.price(rate, yld, redemption)))
+}
+
+/// Calculate the yield of a bond paying periodic interest
+/// yield(settlement: String, maturity: String, rate: Number, pr: Number,
+///       redemption: Number, frequency: Number, basis: Number) -> Number
+///
+/// With one coupon period or less remaining this is Excel's closed form;
+/// otherwise the yield is found by bisection on price.
+///
+/// Example: yield('2008-02-15', '2016-11-15', 0.0575, 95.04287, 100, 2, 0) = 0.065
+pub fn yield_fn(
+    settlement: &Value,
+    maturity: &Value,
+    rate: &Value,
+    pr: &Value,
+    redemption: &Value,
+    frequency: &Value,
+    basis: &Value,
+) -> Result<Value, FunctionError> {
+    let bond = Bond::new(settlement, maturity, frequency, basis)?;
+    let rate = non_negative("rate", rate)?;
+    let pr = positive("pr", pr)?;
+    let redemption = positive("redemption", redemption)?;
+
+    let f = bond.frequency;
+    if bond.coupons <= 1 {
+        let e = bond.coupon_days();
+        let a = bond.accrued_days();
+        let dsr = bond.days_to_next_coupon();
+        let paid = pr / 100.0 + a / e * rate / f;
+        let received = redemption / 100.0 + rate / f;
+        return Ok(Value::Number((received - paid) / paid * f * e / dsr));
+    }
+
+    // Price falls as yield rises, so bisect between a near-total loss and
+    // an absurdly high yield
+    let (mut low, mut high) = (-f + 1e-9, 1_000.0);
+    if bond.price(rate, low, redemption) < pr || bond.price(rate, high, redemption) > pr {
+        return Err(FunctionError::ArgumentError {
+            message: "yield calculation did not converge".to_string(),
+        });
+    }
+    for _ in 0..200 {
+        let mid = (low + high) / 2.0;
+        if bond.price(rate, mid, redemption) > pr {
+            low = mid;
+        } else {
+            high = mid;
+        }
+        if high - low < 1e-12 {
+            break;
+        }
+    }
+    Ok(Value::Number((low + high) / 2.0))
+}
+
+/// Calculate the Macaulay duration of a bond, in years
+/// duration(settlement: String, maturity: String, coupon: Number, yld: Number,
+///          frequency: Number, basis: Number) -> Number
+///
+/// Example: duration('2018-07-01', '2048-01-01', 0.08, 0.09, 2, 1) = 10.92
+pub fn duration(
+    settlement: &Value,
+    maturity: &Value,
+    coupon: &Value,
+    yld: &Value,
+    frequency: &Value,
+    basis: &Value,
+) -> Result<Value, FunctionError> {
+    let bond = Bond::new(settlement, maturity, frequency, basis)?;
+    let coupon = non_negative("coupon", coupon)?;
+    let yld = non_negative("yld", yld)?;
+    Ok(Value::Number(bond.macaulay_duration(coupon, yld)))
+}
+
+/// Calculate the modified duration of a bond, in years
+/// mduration(settlement: String, maturity: String, coupon: Number, yld: Number,
+///           frequency: Number, basis: Number) -> Number
+///
+/// Formula: MDURATION = DURATION / (1 + yld / frequency)
+pub fn mduration(
+    settlement: &Value,
+    maturity: &Value,
+    coupon: &Value,
+    yld: &Value,
+    frequency: &Value,
+    basis: &Value,
+) -> Result<Value, FunctionError> {
+    let bond = Bond::new(settlement, maturity, frequency, basis)?;
+    let coupon = non_negative("coupon", coupon)?;
+    let yld = non_negative("yld", yld)?;
+    let macaulay = bond.macaulay_duration(coupon, yld);
+    Ok(Value::Number(macaulay / (1.0 + yld / bond.frequency)))
+}
+
+/// Settlement, maturity, and the coupon schedule around settlement
+struct Bond {
+    settlement: CivilDate,
+    frequency: f64,
+    basis: u32,
+    /// Coupon date on or before settlement
+    previous_coupon: CivilDate,
+    /// First coupon date after settlement
+    next_coupon: CivilDate,
+    /// Coupons payable between settlement and maturity
+    coupons: i64,
+}
+
+impl Bond {
+    fn new(
+        settlement: &Value,
+        maturity: &Value,
+        frequency: &Value,
+        basis: &Value,
+    ) -> Result<Self, FunctionError> {
+        let settlement = CivilDate::from_value(settlement)?;
+        let maturity = CivilDate::from_value(maturity)?;
+        if settlement >= maturity {
+            return Err(FunctionError::ArgumentError {
+                message: "settlement must be before maturity".to_string(),
+            });
+        }
+
+        let frequency = match frequency {
+            Value::Number(f) if [1.0, 2.0, 4.0].contains(f) => *f,
+            Value::Number(_) => {
+                return Err(FunctionError::ArgumentError {
+                    message: "frequency must be 1, 2, or 4".to_string(),
+                })
+            }
+            _ => {
+                return Err(FunctionError::TypeError {
+                    expected: "Number".to_string(),
+                    got: frequency.type_name().to_string(),
+                })
+            }
+        };
+        let basis = match basis {
+            Value::Number(b) if [0.0, 1.0, 2.0, 3.0, 4.0].contains(b) => *b as u32,
+            Value::Number(_) => {
+                return Err(FunctionError::ArgumentError {
+                    message: "basis must be between 0 and 4".to_string(),
+                })
+            }
+            _ => {
+                return Err(FunctionError::TypeError {
+                    expected: "Number".to_string(),
+                    got: basis.type_name().to_string(),
+                })
+            }
+        };
+
+        let step = 12 / frequency as i64;
+        let coupon_date = |k: i64| {
+            if maturity.is_month_end() {
+                maturity.month_end(-k * step)
+            } else {
+                maturity.add_months(-k * step)
+            }
+        };
+        let mut coupons = 1;
+        let mut previous_coupon = coupon_date(1)?;
+        while previous_coupon > settlement {
+            coupons += 1;
+            previous_coupon = coupon_date(coupons)?;
+        }
+
+        Ok(Bond {
+            settlement,
+            frequency,
+            basis,
+            previous_coupon,
+            next_coupon: coupon_date(coupons - 1)?,
+            coupons,
+        })
+    }
+
+    /// Days in the coupon period containing settlement (Excel's COUPDAYS)
+    fn coupon_days(&self) -> f64 {
+        match self.basis {
+            1 => actual_days(self.previous_coupon, self.next_coupon),
+            3 => 365.0 / self.frequency,
+            _ => 360.0 / self.frequency,
+        }
+    }
+
+    /// Days from the previous coupon to settlement (Excel's COUPDAYBS)
+    fn accrued_days(&self) -> f64 {
+        day_count(self.previous_coupon, self.settlement, self.basis)
+    }
+
+    /// Days from settlement to the next coupon (Excel's COUPDAYSNC)
+    fn days_to_next_coupon(&self) -> f64 {
+        match self.basis {
+            0 | 4 => self.coupon_days() - self.accrued_days(),
+            _ => actual_days(self.settlement, self.next_coupon),
+        }
+    }
+
+    fn price(&self, rate: f64, yld: f64, redemption: f64) -> f64 {
+        let f = self.frequency;
+        let e = self.coupon_days();
+        let dsc_e = self.days_to_next_coupon() / e;
+        let n = self.coupons as f64;
+        let coupon = 100.0 * rate / f;
+        let discount = 1.0 + yld / f;
+
+        let mut total = redemption / discount.powf(n - 1.0 + dsc_e);
+        total -= coupon * self.accrued_days() / e;
+        for k in 0..self.coupons {
+            total += coupon / discount.powf(k as f64 + dsc_e);
+        }
+        total
+    }
+
+    fn macaulay_duration(&self, coupon: f64, yld: f64) -> f64 {
+        let f = self.frequency;
+        let offset = self.days_to_next_coupon() / self.coupon_days() - 1.0;
+        let cash_flow = 100.0 * coupon / f;
+        let discount = 1.0 + yld / f;
+
+        let (mut weighted, mut present_value) = (0.0, 0.0);
+        for k in 1..=self.coupons {
+            let t = k as f64 + offset;
+            let amount = if k == self.coupons {
+                cash_flow + 100.0
+            } else {
+                cash_flow
+            };
+            weighted += t * amount / discount.powf(t);
+            present_value += amount / discount.powf(t);
+        }
+        weighted / present_value / f
+    }
+}
+
+fn actual_days(start: CivilDate, end: CivilDate) -> f64 {
+    (end.days_since_epoch() - start.days_since_epoch()) as f64
+}
+
+/// Days between two dates under a day count basis
+fn day_count(start: CivilDate, end: CivilDate, basis: u32) -> f64 {
+    match basis {
+        0 => days_30_360(start, end, false),
+        4 => days_30_360(start, end, true),
+        _ => actual_days(start, end),
+    }
+}
+
+/// 30/360 day count, US (NASD) or European
+fn days_30_360(start: CivilDate, end: CivilDate, european: bool) -> f64 {
+    let (mut d1, mut d2) = (start.day(), end.day());
+    if european {
+        d1 = d1.min(30);
+        d2 = d2.min(30);
+    } else {
+        let feb_end = |date: CivilDate| date.month() == 2 && date.is_month_end();
+        if feb_end(start) && feb_end(end) {
+            d2 = 30;
+        }
+        if feb_end(start) || d1 == 31 {
+            d1 = 30;
+        }
+        if d2 == 31 && d1 >= 30 {
+            d2 = 30;
+        }
+    }
+    let years = f64::from(end.year() - start.year());
+    let months = f64::from(end.month()) - f64::from(start.month());
+    years * 360.0 + months * 30.0 + f64::from(d2) - f64::from(d1)
+}
+
+fn non_negative(name: &str, value: &Value) -> Result<f64, FunctionError> {
+    match value {
+        Value::Number(n) if *n >= 0.0 => Ok(*n),
+        Value::Number(_) => Err(FunctionError::ArgumentError {
+            message: format!("{} must not be negative", name),
+        }),
+        _ => Err(FunctionError::TypeError {
+            expected: "Number".to_string(),
+            got: value.type_name().to_string(),
+        }),
+    }
+}
+
+fn positive(name: &str, value: &Value) -> Result<f64, FunctionError> {
+    match value {
+        Value::Number(n) if *n > 0.0 => Ok(*n),
+        Value::Number(_) => Err(FunctionError::ArgumentError {
+            message: format!("{} must be greater than 0", name),
+        }),
+        _ => Err(FunctionError::TypeError {
+            expected: "Number".to_string(),
+            got: value.type_name().to_string(),
+        }),
+    }
+}
@@ -0,0 +1,71 @@
+//! bucket function
+
+use crate::{FunctionError, Value};
+
+/// Map a number into a labeled band
+/// bucket(value: Number, thresholds: Array, labels: Array) -> Any
+///
+/// Thresholds must be ascending numbers, and there must be exactly one more
+/// label than thresholds. Values below the first threshold get the first
+/// label; a value equal to a threshold falls in the band above it.
+///
+/// Example: bucket(30, [18, 25, 40, 65], [:youth, :young, :adult, :senior, :elder]) = :adult
+pub fn bucket(value: &Value, thresholds: &Value, labels: &Value) -> Result<Value, FunctionError> {
+    let v = match value {
+        Value::Number(n) => *n,
+        _ => {
+            return Err(FunctionError::TypeError {
+                expected: "Number".to_string(),
+                got: value.type_name().to_string(),
+            })
+        }
+    };
+    let (thresholds, labels) = match (thresholds, labels) {
+        (Value::Array(t), Value::Array(l)) => (t, l),
+        (Value::Array(_), _) => {
+            return Err(FunctionError::TypeError {
+                expected: "Array".to_string(),
+                got: labels.type_name().to_string(),
+            })
+        }
+        _ => {
+            return Err(FunctionError::TypeError {
+                expected: "Array".to_string(),
+                got: thresholds.type_name().to_string(),
+            })
+        }
+    };
+    if labels.len() != thresholds.len() + 1 {
+        return Err(FunctionError::ArgumentError {
+            message: format!(
+                "bucket needs one more label than thresholds, got {} thresholds and {} labels",
+                thresholds.len(),
+                labels.len()
+            ),
+        });
+    }
+
+    let mut band = 0;
+    let mut previous = f64::NEG_INFINITY;
+    for threshold in thresholds.iter() {
+        let t = match threshold {
+            Value::Number(t) => *t,
+            _ => {
+                return Err(FunctionError::TypeError {
+                    expected: "Array of Numbers".to_string(),
+                    got: format!("Array containing {}", threshold.type_name()),
+                })
+            }
+        };
+        if t <= previous {
+            return Err(FunctionError::ArgumentError {
+                message: "bucket thresholds must be in ascending order".to_string(),
+            });
+        }
+        if v >= t {
+            band += 1;
+        }
+        previous = t;
+    }
+    Ok(labels[band].clone())
+}
@@ -7,6 +7,7 @@ use crate::{FunctionError, Value};
 pub fn ceil(value: &Value) -> Result<Value, FunctionError> {
     match value {
         Value::Number(n) => Ok(Value::Number(n.ceil())),
+        Value::Decimal(d) => Ok(Value::Decimal(d.ceil())),
         _ => Err(FunctionError::TypeError {
             expected: "Number".to_string(),
             got: value.type_name().to_string(),
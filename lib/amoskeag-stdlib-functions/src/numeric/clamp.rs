@@ -0,0 +1,30 @@
+//! clamp function
+
+use crate::{FunctionError, Value};
+
+/// Restrict a number to a range
+/// clamp(num: Number, lo: Number, hi: Number) -> Number
+pub fn clamp(value: &Value, lo: &Value, hi: &Value) -> Result<Value, FunctionError> {
+    match (value, lo, hi) {
+        (Value::Number(n), Value::Number(lo), Value::Number(hi)) => {
+            if lo > hi || lo.is_nan() || hi.is_nan() {
+                return Err(FunctionError::ArgumentError {
+                    message: format!("clamp requires lo <= hi, got lo {} and hi {}", lo, hi),
+                });
+            }
+            Ok(Value::Number(n.clamp(*lo, *hi)))
+        }
+        (Value::Number(_), Value::Number(_), _) => Err(FunctionError::TypeError {
+            expected: "Number".to_string(),
+            got: hi.type_name().to_string(),
+        }),
+        (Value::Number(_), _, _) => Err(FunctionError::TypeError {
+            expected: "Number".to_string(),
+            got: lo.type_name().to_string(),
+        }),
+        _ => Err(FunctionError::TypeError {
+            expected: "Number".to_string(),
+            got: value.type_name().to_string(),
+        }),
+    }
+}
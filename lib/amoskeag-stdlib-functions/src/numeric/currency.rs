@@ -0,0 +1,87 @@
+//! currency function
+
+use super::number_format::number_format;
+use crate::{FunctionError, Value};
+
+/// Supported currencies: (code, symbol, decimal places)
+const CURRENCIES: &[(&str, &str, u8)] = &[
+    ("USD", "$", 2),
+    ("CAD", "CA$", 2),
+    ("AUD", "A$", 2),
+    ("MXN", "MX$", 2),
+    ("EUR", "€", 2),
+    ("GBP", "£", 2),
+    ("CHF", "CHF", 2),
+    ("INR", "₹", 2),
+    ("JPY", "¥", 0),
+    ("KRW", "₩", 0),
+];
+
+/// Supported locales: (tag, thousands separator, decimal separator, symbol after amount)
+const LOCALES: &[(&str, &str, &str, bool)] = &[
+    ("en-US", ",", ".", false),
+    ("en-GB", ",", ".", false),
+    ("en-CA", ",", ".", false),
+    ("ja-JP", ",", ".", false),
+    ("de-DE", ".", ",", true),
+    ("fr-FR", " ", ",", true),
+    ("es-ES", ".", ",", true),
+];
+
+/// Format an amount as money in a given currency and locale
+/// currency(amount: Number, code: String, locale: String) -> String
+///
+/// The currency decides the symbol and number of decimal places; the locale
+/// decides separators and whether the symbol precedes or follows the amount.
+/// The amount is rounded to the currency's places as `round` would, with
+/// halves away from zero.
+pub fn currency(amount: &Value, code: &Value, locale: &Value) -> Result<Value, FunctionError> {
+    let (code, locale) = match (code, locale) {
+        (Value::String(c), Value::String(l)) => (c, l),
+        (Value::String(_), _) => {
+            return Err(FunctionError::TypeError {
+                expected: "String".to_string(),
+                got: locale.type_name().to_string(),
+            })
+        }
+        _ => {
+            return Err(FunctionError::TypeError {
+                expected: "String".to_string(),
+                got: code.type_name().to_string(),
+            })
+        }
+    };
+    let (_, symbol, decimals) = CURRENCIES
+        .iter()
+        .find(|(c, _, _)| c.eq_ignore_ascii_case(code))
+        .ok_or_else(|| FunctionError::ArgumentError {
+            message: format!("Unsupported currency code: {}", code),
+        })?;
+    let (_, thousands_sep, decimal_sep, symbol_after) = LOCALES
+        .iter()
+        .find(|(tag, _, _, _)| tag.eq_ignore_ascii_case(locale))
+        .ok_or_else(|| FunctionError::ArgumentError {
+            message: format!("Unsupported locale: {}", locale),
+        })?;
+
+    let formatted = match number_format(
+        amount,
+        &Value::Number(*decimals as f64),
+        &Value::String(thousands_sep.to_string().into()),
+        &Value::String(decimal_sep.to_string().into()),
+    )? {
+        Value::String(s) => s,
+        _ => unreachable!("number_format always returns a String"),
+    };
+    let (sign, digits) = match formatted.strip_prefix('-') {
+        Some(rest) => ("-", rest),
+        None => ("", &*formatted),
+    };
+
+    let result = if *symbol_after {
+        format!("{}{} {}", sign, digits, symbol)
+    } else {
+        format!("{}{}{}", sign, symbol, digits)
+    };
+    Ok(Value::String(result.into()))
+}
@@ -0,0 +1,25 @@
+//! Shared helpers for numeric functions that compute exactly on decimals
+
+use crate::{Decimal, FunctionError, Value};
+
+/// All of `args` as decimals, when at least one is a `Decimal` and every one is a number
+///
+/// Decimal-aware functions try this first and fall through to their float
+/// arms when it gives `None`.
+pub(crate) fn decimal_args<const N: usize>(args: [&Value; N]) -> Option<[Decimal; N]> {
+    if !args.iter().any(|arg| matches!(arg, Value::Decimal(_))) {
+        return None;
+    }
+    let mut decimals = [Decimal::ZERO; N];
+    for (decimal, arg) in decimals.iter_mut().zip(args) {
+        *decimal = arg.as_decimal()?;
+    }
+    Some(decimals)
+}
+
+/// The error for a decimal calculation whose result is too large to represent
+pub(crate) fn overflow(function: &str) -> FunctionError {
+    FunctionError::ValueError {
+        message: format!("Decimal overflow in {}", function),
+    }
+}
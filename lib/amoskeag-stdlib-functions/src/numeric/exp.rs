@@ -0,0 +1,24 @@
+//! exp function
+
+use crate::{FunctionError, Value};
+
+/// Calculate e raised to a power
+/// exp(num: Number) -> Number
+pub fn exp(value: &Value) -> Result<Value, FunctionError> {
+    match value {
+        Value::Number(n) => {
+            let result = n.exp();
+            if result.is_infinite() && n.is_finite() {
+                Err(FunctionError::ValueError {
+                    message: format!("exp({}) overflows", n),
+                })
+            } else {
+                Ok(Value::Number(result))
+            }
+        }
+        _ => Err(FunctionError::TypeError {
+            expected: "Number".to_string(),
+            got: value.type_name().to_string(),
+        }),
+    }
+}
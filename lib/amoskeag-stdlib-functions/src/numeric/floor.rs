@@ -7,6 +7,7 @@ use crate::{FunctionError, Value};
 pub fn floor(value: &Value) -> Result<Value, FunctionError> {
     match value {
         Value::Number(n) => Ok(Value::Number(n.floor())),
+        Value::Decimal(d) => Ok(Value::Decimal(d.floor())),
         _ => Err(FunctionError::TypeError {
             expected: "Number".to_string(),
             got: value.type_name().to_string(),
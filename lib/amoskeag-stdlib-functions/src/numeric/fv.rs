@@ -1,6 +1,8 @@
 //! fv function
 
-use crate::{FunctionError, Value};
+use super::decimal::{decimal_args, overflow};
+use crate::{Decimal, FunctionError, Value};
+use rust_decimal::MathematicalOps;
 
 /// Calculate the future value of an investment
 /// fv(rate: Number, nper: Number, pmt: Number, pv: Number) -> Number
@@ -8,8 +10,13 @@ use crate::{FunctionError, Value};
 /// Formula: FV = -PV * (1 + r)^n - PMT * (((1 + r)^n - 1) / r)
 /// where r = periodic interest rate, n = number of periods, PMT = payment per period, PV = present value
 ///
+/// Decimal arguments are computed exactly, to 28 significant digits.
+///
 /// Example: fv(0.06/12, 10*12, -100, -1000) = future value of $1000 + $100/month for 10 years at 6% annual rate
 pub fn fv(rate: &Value, nper: &Value, pmt: &Value, pv: &Value) -> Result<Value, FunctionError> {
+    if let Some([r, n, p, v]) = decimal_args([rate, nper, pmt, pv]) {
+        return fv_decimal(r, n, p, v);
+    }
     match (rate, nper, pmt, pv) {
         (Value::Number(r), Value::Number(n), Value::Number(p), Value::Number(v)) => {
             if *n <= 0.0 {
@@ -49,3 +56,24 @@ pub fn fv(rate: &Value, nper: &Value, pmt: &Value, pv: &Value) -> Result<Value,
         }),
     }
 }
+
+fn fv_decimal(r: Decimal, n: Decimal, p: Decimal, v: Decimal) -> Result<Value, FunctionError> {
+    if n <= Decimal::ZERO {
+        return Err(FunctionError::ArgumentError {
+            message: "nper must be greater than 0".to_string(),
+        });
+    }
+
+    let future_value = || {
+        if r.is_zero() {
+            return (-v).checked_sub(p.checked_mul(n)?);
+        }
+        let factor = Decimal::ONE.checked_add(r)?.checked_powd(n)?;
+        let annuity = factor.checked_sub(Decimal::ONE)?.checked_div(r)?;
+        (-v).checked_mul(factor)?
+            .checked_sub(p.checked_mul(annuity)?)
+    };
+    future_value()
+        .map(Value::Decimal)
+        .ok_or_else(|| overflow("fv"))
+}
@@ -0,0 +1,66 @@
+//! fvschedule function
+
+use super::decimal::overflow;
+use crate::{Decimal, FunctionError, Value};
+
+/// Calculate the future value of a principal under a schedule of interest rates
+/// fvschedule(principal: Number, schedule: Array) -> Number
+///
+/// Formula: FV = principal * (1 + r1) * (1 + r2) * ... * (1 + rn)
+/// where each r is the rate applied in its period. An empty schedule returns the principal.
+///
+/// When any argument is a decimal the product is computed exactly, to 28
+/// significant digits.
+///
+/// Example: fvschedule(1, [0.09, 0.11, 0.1]) = 1.33089
+pub fn fvschedule(principal: &Value, schedule: &Value) -> Result<Value, FunctionError> {
+    let exact = principal.contains_decimal() || schedule.contains_decimal();
+    match (principal, schedule) {
+        (Value::Number(_) | Value::Decimal(_), Value::Array(rates)) if exact => {
+            let mut future_value = principal
+                .as_decimal()
+                .ok_or_else(|| overflow("fvschedule"))?;
+            for rate in rates.iter() {
+                let r = match rate {
+                    Value::Number(_) | Value::Decimal(_) => rate.as_decimal(),
+                    _ => {
+                        return Err(FunctionError::TypeError {
+                            expected: "Array of Numbers".to_string(),
+                            got: format!("Array containing {}", rate.type_name()),
+                        })
+                    }
+                };
+                future_value = r
+                    .and_then(|r| Decimal::ONE.checked_add(r))
+                    .and_then(|growth| future_value.checked_mul(growth))
+                    .ok_or_else(|| overflow("fvschedule"))?;
+            }
+
+            Ok(Value::Decimal(future_value))
+        }
+        (Value::Number(p), Value::Array(rates)) => {
+            let mut future_value = *p;
+            for rate in rates.iter() {
+                match rate {
+                    Value::Number(r) => future_value *= 1.0 + r,
+                    _ => {
+                        return Err(FunctionError::TypeError {
+                            expected: "Array of Numbers".to_string(),
+                            got: format!("Array containing {}", rate.type_name()),
+                        })
+                    }
+                }
+            }
+
+            Ok(Value::Number(future_value))
+        }
+        (Value::Number(_), _) => Err(FunctionError::TypeError {
+            expected: "Array".to_string(),
+            got: schedule.type_name().to_string(),
+        }),
+        _ => Err(FunctionError::TypeError {
+            expected: "Number".to_string(),
+            got: principal.type_name().to_string(),
+        }),
+    }
+}
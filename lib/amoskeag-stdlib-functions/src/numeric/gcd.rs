@@ -0,0 +1,66 @@
+//! gcd and lcm functions
+
+use crate::{FunctionError, Value};
+
+/// Largest integer an f64 can represent exactly (2^53)
+const MAX_SAFE_INTEGER: f64 = 9_007_199_254_740_992.0;
+
+/// Calculate the greatest common divisor of two whole numbers
+/// gcd(a: Number, b: Number) -> Number
+///
+/// Two integers give an integer; otherwise the result is a float.
+pub fn gcd(a: &Value, b: &Value) -> Result<Value, FunctionError> {
+    let result = euclid(whole_number(a)?, whole_number(b)?);
+    whole_result("gcd", a, b, u128::from(result))
+}
+
+/// Calculate the least common multiple of two whole numbers
+/// lcm(a: Number, b: Number) -> Number
+///
+/// Two integers give an integer; otherwise the result is a float.
+pub fn lcm(a: &Value, b: &Value) -> Result<Value, FunctionError> {
+    let (x, y) = (whole_number(a)?, whole_number(b)?);
+    if x == 0 || y == 0 {
+        return whole_result("lcm", a, b, 0);
+    }
+    whole_result("lcm", a, b, u128::from(x / euclid(x, y)) * u128::from(y))
+}
+
+fn euclid(mut a: u64, mut b: u64) -> u64 {
+    while b != 0 {
+        (a, b) = (b, a % b);
+    }
+    a
+}
+
+/// Validate that a value is a whole number and return its magnitude
+fn whole_number(value: &Value) -> Result<u64, FunctionError> {
+    match value {
+        Value::Int(n) => Ok(n.unsigned_abs()),
+        Value::Number(_) | Value::Decimal(_) => match value.as_f64() {
+            Some(n) if n.fract() == 0.0 && n.abs() <= MAX_SAFE_INTEGER => Ok(n.abs() as u64),
+            _ => Err(FunctionError::ArgumentError {
+                message: format!("expected a whole number, got {}", value),
+            }),
+        },
+        _ => Err(FunctionError::TypeError {
+            expected: "Number".to_string(),
+            got: value.type_name().to_string(),
+        }),
+    }
+}
+
+/// `result` as an integer when both arguments are, otherwise as a float,
+/// failing if it does not fit exactly
+fn whole_result(name: &str, a: &Value, b: &Value, result: u128) -> Result<Value, FunctionError> {
+    let value = if matches!((a, b), (Value::Int(_), Value::Int(_))) {
+        i64::try_from(result).ok().map(Value::Int)
+    } else {
+        Some(result as f64)
+            .filter(|n| *n <= MAX_SAFE_INTEGER)
+            .map(Value::Number)
+    };
+    value.ok_or_else(|| FunctionError::ValueError {
+        message: format!("{}({}, {}) is too large to represent exactly", name, a, b),
+    })
+}
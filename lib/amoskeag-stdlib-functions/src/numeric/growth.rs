@@ -0,0 +1,97 @@
+//! cagr and compound functions
+
+use super::decimal::{decimal_args, overflow};
+use crate::{Decimal, FunctionError, Value};
+use rust_decimal::MathematicalOps;
+
+/// Calculate the compound annual growth rate between two values
+/// cagr(begin_value: Number, end_value: Number, years: Number) -> Number
+///
+/// Formula: CAGR = (end_value / begin_value)^(1 / years) - 1
+///
+/// Example: cagr(10000, 19500, 3) = 0.2493 (about 24.9% per year)
+pub fn cagr(begin_value: &Value, end_value: &Value, years: &Value) -> Result<Value, FunctionError> {
+    match (begin_value, end_value, years) {
+        (Value::Number(b), Value::Number(e), Value::Number(y)) => {
+            if *y <= 0.0 {
+                return Err(FunctionError::ArgumentError {
+                    message: "years must be greater than 0".to_string(),
+                });
+            }
+            if *b == 0.0 {
+                return Err(FunctionError::ArgumentError {
+                    message: "begin_value cannot be 0".to_string(),
+                });
+            }
+
+            let ratio = e / b;
+            if ratio < 0.0 {
+                return Err(FunctionError::ArgumentError {
+                    message: "begin_value and end_value must have the same sign".to_string(),
+                });
+            }
+
+            Ok(Value::Number(ratio.powf(1.0 / y) - 1.0))
+        }
+        (Value::Number(_), Value::Number(_), _) => Err(FunctionError::TypeError {
+            expected: "Number".to_string(),
+            got: years.type_name().to_string(),
+        }),
+        (Value::Number(_), _, _) => Err(FunctionError::TypeError {
+            expected: "Number".to_string(),
+            got: end_value.type_name().to_string(),
+        }),
+        _ => Err(FunctionError::TypeError {
+            expected: "Number".to_string(),
+            got: begin_value.type_name().to_string(),
+        }),
+    }
+}
+
+/// Grow a value at a constant rate over a number of periods
+/// compound(value: Number, rate: Number, periods: Number) -> Number
+///
+/// Formula: value * (1 + rate)^periods
+///
+/// Decimal arguments are computed exactly, to 28 significant digits, when
+/// `periods` is a whole number.
+///
+/// Example: compound(1000, 0.05, 10) = 1628.89
+pub fn compound(value: &Value, rate: &Value, periods: &Value) -> Result<Value, FunctionError> {
+    if let Some([v, r, n]) = decimal_args([value, rate, periods]) {
+        if r <= -Decimal::ONE {
+            return Err(FunctionError::ArgumentError {
+                message: "rate must be greater than -1".to_string(),
+            });
+        }
+        return Decimal::ONE
+            .checked_add(r)
+            .and_then(|growth| growth.checked_powd(n))
+            .and_then(|factor| v.checked_mul(factor))
+            .map(Value::Decimal)
+            .ok_or_else(|| overflow("compound"));
+    }
+    match (value, rate, periods) {
+        (Value::Number(v), Value::Number(r), Value::Number(n)) => {
+            if *r <= -1.0 {
+                return Err(FunctionError::ArgumentError {
+                    message: "rate must be greater than -1".to_string(),
+                });
+            }
+
+            Ok(Value::Number(v * (1.0 + r).powf(*n)))
+        }
+        (Value::Number(_), Value::Number(_), _) => Err(FunctionError::TypeError {
+            expected: "Number".to_string(),
+            got: periods.type_name().to_string(),
+        }),
+        (Value::Number(_), _, _) => Err(FunctionError::TypeError {
+            expected: "Number".to_string(),
+            got: rate.type_name().to_string(),
+        }),
+        _ => Err(FunctionError::TypeError {
+            expected: "Number".to_string(),
+            got: value.type_name().to_string(),
+        }),
+    }
+}
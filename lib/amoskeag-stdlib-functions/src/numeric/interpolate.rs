@@ -0,0 +1,76 @@
+//! interpolate function
+
+use crate::{FunctionError, Value};
+
+/// Look up a value on a piecewise-linear curve
+/// interpolate(x: Number, xs: Array, ys: Array) -> Number
+///
+/// `xs` must be strictly ascending and the same length as `ys`. Between two
+/// points the result is interpolated linearly; outside the table it is held
+/// at the first or last `ys` value rather than extrapolated.
+///
+/// Example: interpolate(30, [20, 40, 60], [1.5, 1.0, 1.2]) = 1.25
+pub fn interpolate(x: &Value, xs: &Value, ys: &Value) -> Result<Value, FunctionError> {
+    let x = match x {
+        Value::Number(n) => *n,
+        _ => {
+            return Err(FunctionError::TypeError {
+                expected: "Number".to_string(),
+                got: x.type_name().to_string(),
+            })
+        }
+    };
+    let xs = numbers(xs)?;
+    let ys = numbers(ys)?;
+    if xs.is_empty() {
+        return Err(FunctionError::ArgumentError {
+            message: "xs array cannot be empty".to_string(),
+        });
+    }
+    if xs.len() != ys.len() {
+        return Err(FunctionError::ArgumentError {
+            message: format!(
+                "xs and ys must have the same length, got {} and {}",
+                xs.len(),
+                ys.len()
+            ),
+        });
+    }
+    if xs.windows(2).any(|pair| pair[1] <= pair[0]) {
+        return Err(FunctionError::ArgumentError {
+            message: "xs must be in ascending order".to_string(),
+        });
+    }
+
+    let last = xs.len() - 1;
+    if x <= xs[0] {
+        return Ok(Value::Number(ys[0]));
+    }
+    if x >= xs[last] {
+        return Ok(Value::Number(ys[last]));
+    }
+
+    // xs[i - 1] < x < xs[last] here, so the segment [i - 1, i] contains x
+    let i = xs.partition_point(|&point| point <= x);
+    let (x0, x1, y0, y1) = (xs[i - 1], xs[i], ys[i - 1], ys[i]);
+    Ok(Value::Number(y0 + (y1 - y0) * (x - x0) / (x1 - x0)))
+}
+
+fn numbers(value: &Value) -> Result<Vec<f64>, FunctionError> {
+    match value {
+        Value::Array(items) => items
+            .iter()
+            .map(|item| match item {
+                Value::Number(n) => Ok(*n),
+                _ => Err(FunctionError::TypeError {
+                    expected: "Array of Numbers".to_string(),
+                    got: format!("Array containing {}", item.type_name()),
+                }),
+            })
+            .collect(),
+        _ => Err(FunctionError::TypeError {
+            expected: "Array".to_string(),
+            got: value.type_name().to_string(),
+        }),
+    }
+}
@@ -3,27 +3,44 @@
 pub mod abs;
 pub mod array_max;
 pub mod array_min;
+pub mod bucket;
 pub mod ceil;
+pub mod clamp;
+pub mod currency;
+pub(crate) mod decimal;
 pub mod divided_by;
+pub mod exp;
 pub mod floor;
+pub mod gcd;
+pub mod interpolate;
 pub mod log;
 pub mod max;
 pub mod min;
 pub mod minus;
 pub mod modulo;
+pub mod number_format;
 pub mod plus;
 pub mod power;
+pub mod random;
 pub mod round;
+pub mod safe_divide;
+pub mod sign;
 pub mod sqrt;
 pub mod times;
+pub mod trunc;
 
 // Financial functions
+pub mod amortization_schedule;
+pub mod apr;
+pub mod bond;
 pub mod cumipmt;
 pub mod cumprinc;
 pub mod db;
 pub mod ddb;
 pub mod effect;
 pub mod fv;
+pub mod fvschedule;
+pub mod growth;
 pub mod ipmt;
 pub mod irr;
 pub mod mirr;
@@ -32,23 +49,40 @@ pub mod nper;
 pub mod npv;
 pub mod pmt;
 pub mod ppmt;
+pub mod prorate;
 pub mod pv;
 pub mod rate;
 pub mod sln;
+pub mod syd;
+pub mod tier;
+pub mod vdb;
+pub mod xirr;
+pub mod xnpv;
 
 // Re-export all functions
 pub use abs::abs;
+pub use amortization_schedule::amortization_schedule;
+pub use apr::{annual_to_periodic, apr, apr_effective, periodic_to_annual};
 pub use array_max::array_max;
 pub use array_min::array_min;
+pub use bond::{duration, mduration, price, yield_fn};
+pub use bucket::bucket;
 pub use ceil::ceil;
+pub use clamp::clamp;
 pub use cumipmt::cumipmt;
 pub use cumprinc::cumprinc;
+pub use currency::currency;
 pub use db::db;
 pub use ddb::ddb;
 pub use divided_by::divided_by;
 pub use effect::effect;
+pub use exp::exp;
 pub use floor::floor;
 pub use fv::fv;
+pub use fvschedule::fvschedule;
+pub use gcd::{gcd, lcm};
+pub use growth::{cagr, compound};
+pub use interpolate::interpolate;
 pub use ipmt::ipmt;
 pub use irr::irr;
 pub use log::{ln, log, log10};
@@ -60,180 +94,35 @@ pub use modulo::modulo_fn;
 pub use nominal::nominal;
 pub use nper::nper;
 pub use npv::npv;
+pub use number_format::number_format;
 pub use plus::plus;
 pub use pmt::pmt;
 pub use power::power;
 pub use ppmt::ppmt;
+pub use prorate::prorate;
 pub use pv::pv;
+pub use random::{next_random, next_u64, random, random_between, uuid};
 pub use rate::rate;
 pub use round::round;
+pub use safe_divide::safe_divide;
+pub use sign::sign;
 pub use sln::sln;
 pub use sqrt::sqrt;
+pub use syd::syd;
+pub use tier::{tier_lookup, tier_marginal};
 pub use times::times;
+pub use trunc::trunc;
+pub use vdb::vdb;
+pub use xirr::xirr;
+pub use xnpv::xnpv;
 
-/// Represents a numeric function that can be registered with inventory
-pub struct NumericFunction {
-    pub name: &'static str,
-    pub description: &'static str,
-    pub arity: Arity,
-}
-
-/// Function arity
-pub enum Arity {
-    Unary,
-    Binary,
-    Ternary,
-    Quaternary,
-    Quinary,
-    Senary,
-}
-
-inventory::collect!(NumericFunction);
-
-inventory::submit! {
-    NumericFunction { name: "abs", description: "Return the absolute value of a number", arity: Arity::Unary }
-}
-
-inventory::submit! {
-    NumericFunction { name: "ceil", description: "Round a number up to the nearest integer", arity: Arity::Unary }
-}
-
-inventory::submit! {
-    NumericFunction { name: "floor", description: "Round a number down to the nearest integer", arity: Arity::Unary }
-}
-
-inventory::submit! {
-    NumericFunction { name: "round", description: "Round a number to a specified number of decimal places", arity: Arity::Binary }
-}
-
-inventory::submit! {
-    NumericFunction { name: "plus", description: "Add two numbers", arity: Arity::Binary }
-}
-
-inventory::submit! {
-    NumericFunction { name: "minus", description: "Subtract two numbers", arity: Arity::Binary }
-}
-
-inventory::submit! {
-    NumericFunction { name: "times", description: "Multiply two numbers", arity: Arity::Binary }
-}
-
-inventory::submit! {
-    NumericFunction { name: "divided_by", description: "Divide two numbers", arity: Arity::Binary }
-}
-
-inventory::submit! {
-    NumericFunction { name: "modulo", description: "Calculate modulo of two numbers", arity: Arity::Binary }
-}
-
-inventory::submit! {
-    NumericFunction { name: "max", description: "Calculate the maximum of two numbers", arity: Arity::Binary }
-}
-
-inventory::submit! {
-    NumericFunction { name: "min", description: "Calculate the minimum of two numbers", arity: Arity::Binary }
-}
-
-inventory::submit! {
-    NumericFunction { name: "array_min", description: "Return the minimum value in an array of numbers", arity: Arity::Unary }
-}
-
-inventory::submit! {
-    NumericFunction { name: "array_max", description: "Return the maximum value in an array of numbers", arity: Arity::Unary }
-}
-
-inventory::submit! {
-    NumericFunction { name: "power", description: "Raise a number to a power", arity: Arity::Binary }
-}
-
-inventory::submit! {
-    NumericFunction { name: "sqrt", description: "Calculate the square root of a number", arity: Arity::Unary }
-}
-
-inventory::submit! {
-    NumericFunction { name: "log", description: "Calculate the base-2 logarithm of a number", arity: Arity::Unary }
-}
-
-inventory::submit! {
-    NumericFunction { name: "log10", description: "Calculate the base-10 logarithm of a number", arity: Arity::Unary }
-}
-
-inventory::submit! {
-    NumericFunction { name: "ln", description: "Calculate the natural logarithm of a number", arity: Arity::Unary }
-}
-
-inventory::submit! {
-    NumericFunction { name: "pmt", description: "Calculate loan payment", arity: Arity::Ternary }
-}
-
-inventory::submit! {
-    NumericFunction { name: "pv", description: "Calculate present value", arity: Arity::Ternary }
-}
-
-inventory::submit! {
-    NumericFunction { name: "fv", description: "Calculate future value", arity: Arity::Quaternary }
-}
-
-inventory::submit! {
-    NumericFunction { name: "nper", description: "Calculate number of periods", arity: Arity::Ternary }
-}
-
-inventory::submit! {
-    NumericFunction { name: "rate", description: "Calculate interest rate", arity: Arity::Ternary }
-}
-
-inventory::submit! {
-    NumericFunction { name: "npv", description: "Calculate net present value", arity: Arity::Binary }
-}
-
-inventory::submit! {
-    NumericFunction { name: "irr", description: "Calculate internal rate of return", arity: Arity::Unary }
-}
-
-inventory::submit! {
-    NumericFunction { name: "mirr", description: "Calculate modified internal rate of return", arity: Arity::Ternary }
-}
-
-inventory::submit! {
-    NumericFunction { name: "sln", description: "Calculate straight-line depreciation", arity: Arity::Ternary }
-}
-
-inventory::submit! {
-    NumericFunction { name: "ddb", description: "Calculate double-declining balance depreciation", arity: Arity::Quaternary }
-}
-
-inventory::submit! {
-    NumericFunction { name: "db", description: "Calculate declining balance depreciation", arity: Arity::Quinary }
-}
-
-inventory::submit! {
-    NumericFunction { name: "ipmt", description: "Calculate interest payment for a period", arity: Arity::Quaternary }
-}
+/// Numeric functions are registered with every other function in
+/// `signatures`; these names remain for existing callers
+#[deprecated(note = "use signatures::StdlibFunction")]
+pub type NumericFunction = crate::signatures::StdlibFunction;
 
-inventory::submit! {
-    NumericFunction { name: "ppmt", description: "Calculate principal payment for a period", arity: Arity::Quaternary }
-}
-
-inventory::submit! {
-    NumericFunction { name: "cumipmt", description: "Calculate cumulative interest paid", arity: Arity::Senary }
-}
-
-inventory::submit! {
-    NumericFunction { name: "cumprinc", description: "Calculate cumulative principal paid", arity: Arity::Senary }
-}
-
-inventory::submit! {
-    NumericFunction { name: "effect", description: "Calculate effective annual interest rate", arity: Arity::Binary }
-}
-
-inventory::submit! {
-    NumericFunction { name: "nominal", description: "Calculate nominal annual interest rate", arity: Arity::Binary }
-}
-
-/// Get all registered numeric functions
-pub fn get_all_numeric_functions() -> inventory::iter<NumericFunction> {
-    inventory::iter::<NumericFunction>
-}
+#[deprecated(note = "use signatures::Arity")]
+pub type Arity = crate::signatures::Arity;
 
 #[cfg(test)]
 mod tests {
@@ -281,7 +170,7 @@ mod tests {
 
         // Type errors for non-Number types
         assert!(matches!(
-            abs(&Value::String("test".to_string())),
+            abs(&Value::String("test".into())),
             Err(FunctionError::TypeError { .. })
         ));
         assert!(matches!(
@@ -293,11 +182,11 @@ mod tests {
             Err(FunctionError::TypeError { .. })
         ));
         assert!(matches!(
-            abs(&Value::Array(vec![])),
+            abs(&Value::Array(vec![].into())),
             Err(FunctionError::TypeError { .. })
         ));
         assert!(matches!(
-            abs(&Value::Dictionary(std::collections::HashMap::new())),
+            abs(&Value::Dictionary(std::collections::HashMap::new().into())),
             Err(FunctionError::TypeError { .. })
         ));
         assert!(matches!(
@@ -356,7 +245,7 @@ mod tests {
 
         // Type errors for non-Number types
         assert!(matches!(
-            ceil(&Value::String("test".to_string())),
+            ceil(&Value::String("test".into())),
             Err(FunctionError::TypeError { .. })
         ));
         assert!(matches!(
@@ -368,11 +257,11 @@ mod tests {
             Err(FunctionError::TypeError { .. })
         ));
         assert!(matches!(
-            ceil(&Value::Array(vec![])),
+            ceil(&Value::Array(vec![].into())),
             Err(FunctionError::TypeError { .. })
         ));
         assert!(matches!(
-            ceil(&Value::Dictionary(std::collections::HashMap::new())),
+            ceil(&Value::Dictionary(std::collections::HashMap::new().into())),
             Err(FunctionError::TypeError { .. })
         ));
         assert!(matches!(
@@ -434,7 +323,7 @@ mod tests {
 
         // Type errors for non-Number types
         assert!(matches!(
-            floor(&Value::String("test".to_string())),
+            floor(&Value::String("test".into())),
             Err(FunctionError::TypeError { .. })
         ));
         assert!(matches!(
@@ -446,11 +335,11 @@ mod tests {
             Err(FunctionError::TypeError { .. })
         ));
         assert!(matches!(
-            floor(&Value::Array(vec![])),
+            floor(&Value::Array(vec![].into())),
             Err(FunctionError::TypeError { .. })
         ));
         assert!(matches!(
-            floor(&Value::Dictionary(std::collections::HashMap::new())),
+            floor(&Value::Dictionary(std::collections::HashMap::new().into())),
             Err(FunctionError::TypeError { .. })
         ));
         assert!(matches!(
@@ -552,11 +441,11 @@ mod tests {
 
         // Type errors
         assert!(matches!(
-            round(&Value::String("1.23".to_string()), &Value::Number(2.0)),
+            round(&Value::String("1.23".into()), &Value::Number(2.0)),
             Err(FunctionError::TypeError { .. })
         ));
         assert!(matches!(
-            round(&Value::Number(1.23), &Value::String("2".to_string())),
+            round(&Value::Number(1.23), &Value::String("2".into())),
             Err(FunctionError::TypeError { .. })
         ));
 
@@ -789,11 +678,11 @@ mod tests {
 
         // Type errors for non-Number types
         assert!(matches!(
-            plus(&Value::String("test".to_string()), &Value::Number(1.0)),
+            plus(&Value::String("test".into()), &Value::Number(1.0)),
             Err(FunctionError::TypeError { .. })
         ));
         assert!(matches!(
-            plus(&Value::Number(1.0), &Value::String("test".to_string())),
+            plus(&Value::Number(1.0), &Value::String("test".into())),
             Err(FunctionError::TypeError { .. })
         ));
         assert!(matches!(
@@ -813,16 +702,16 @@ mod tests {
             Err(FunctionError::TypeError { .. })
         ));
         assert!(matches!(
-            plus(&Value::Array(vec![]), &Value::Number(1.0)),
+            plus(&Value::Array(vec![].into()), &Value::Number(1.0)),
             Err(FunctionError::TypeError { .. })
         ));
         assert!(matches!(
-            plus(&Value::Number(1.0), &Value::Array(vec![])),
+            plus(&Value::Number(1.0), &Value::Array(vec![].into())),
             Err(FunctionError::TypeError { .. })
         ));
         assert!(matches!(
             plus(
-                &Value::Dictionary(std::collections::HashMap::new()),
+                &Value::Dictionary(std::collections::HashMap::new().into()),
                 &Value::Number(1.0)
             ),
             Err(FunctionError::TypeError { .. })
@@ -830,7 +719,7 @@ mod tests {
         assert!(matches!(
             plus(
                 &Value::Number(1.0),
-                &Value::Dictionary(std::collections::HashMap::new())
+                &Value::Dictionary(std::collections::HashMap::new().into())
             ),
             Err(FunctionError::TypeError { .. })
         ));
@@ -968,11 +857,11 @@ mod tests {
 
         // Type errors for non-Number types
         assert!(matches!(
-            minus(&Value::String("test".to_string()), &Value::Number(1.0)),
+            minus(&Value::String("test".into()), &Value::Number(1.0)),
             Err(FunctionError::TypeError { .. })
         ));
         assert!(matches!(
-            minus(&Value::Number(1.0), &Value::String("test".to_string())),
+            minus(&Value::Number(1.0), &Value::String("test".into())),
             Err(FunctionError::TypeError { .. })
         ));
         assert!(matches!(
@@ -992,16 +881,16 @@ mod tests {
             Err(FunctionError::TypeError { .. })
         ));
         assert!(matches!(
-            minus(&Value::Array(vec![]), &Value::Number(1.0)),
+            minus(&Value::Array(vec![].into()), &Value::Number(1.0)),
             Err(FunctionError::TypeError { .. })
         ));
         assert!(matches!(
-            minus(&Value::Number(1.0), &Value::Array(vec![])),
+            minus(&Value::Number(1.0), &Value::Array(vec![].into())),
             Err(FunctionError::TypeError { .. })
         ));
         assert!(matches!(
             minus(
-                &Value::Dictionary(std::collections::HashMap::new()),
+                &Value::Dictionary(std::collections::HashMap::new().into()),
                 &Value::Number(1.0)
             ),
             Err(FunctionError::TypeError { .. })
@@ -1009,7 +898,7 @@ mod tests {
         assert!(matches!(
             minus(
                 &Value::Number(1.0),
-                &Value::Dictionary(std::collections::HashMap::new())
+                &Value::Dictionary(std::collections::HashMap::new().into())
             ),
             Err(FunctionError::TypeError { .. })
         ));
@@ -1172,11 +1061,11 @@ mod tests {
 
         // Type errors for non-Number types
         assert!(matches!(
-            times(&Value::String("test".to_string()), &Value::Number(1.0)),
+            times(&Value::String("test".into()), &Value::Number(1.0)),
             Err(FunctionError::TypeError { .. })
         ));
         assert!(matches!(
-            times(&Value::Number(1.0), &Value::String("test".to_string())),
+            times(&Value::Number(1.0), &Value::String("test".into())),
             Err(FunctionError::TypeError { .. })
         ));
         assert!(matches!(
@@ -1196,16 +1085,16 @@ mod tests {
             Err(FunctionError::TypeError { .. })
         ));
         assert!(matches!(
-            times(&Value::Array(vec![]), &Value::Number(1.0)),
+            times(&Value::Array(vec![].into()), &Value::Number(1.0)),
             Err(FunctionError::TypeError { .. })
         ));
         assert!(matches!(
-            times(&Value::Number(1.0), &Value::Array(vec![])),
+            times(&Value::Number(1.0), &Value::Array(vec![].into())),
             Err(FunctionError::TypeError { .. })
         ));
         assert!(matches!(
             times(
-                &Value::Dictionary(std::collections::HashMap::new()),
+                &Value::Dictionary(std::collections::HashMap::new().into()),
                 &Value::Number(1.0)
             ),
             Err(FunctionError::TypeError { .. })
@@ -1213,7 +1102,7 @@ mod tests {
         assert!(matches!(
             times(
                 &Value::Number(1.0),
-                &Value::Dictionary(std::collections::HashMap::new())
+                &Value::Dictionary(std::collections::HashMap::new().into())
             ),
             Err(FunctionError::TypeError { .. })
         ));
@@ -1415,7 +1304,7 @@ mod tests {
     fn test_divided_by_type_errors() {
         // Type errors for first argument
         assert!(matches!(
-            divided_by(&Value::String("test".to_string()), &Value::Number(2.0)),
+            divided_by(&Value::String("test".into()), &Value::Number(2.0)),
             Err(FunctionError::TypeError { .. })
         ));
         assert!(matches!(
@@ -1427,12 +1316,12 @@ mod tests {
             Err(FunctionError::TypeError { .. })
         ));
         assert!(matches!(
-            divided_by(&Value::Array(vec![]), &Value::Number(2.0)),
+            divided_by(&Value::Array(vec![].into()), &Value::Number(2.0)),
             Err(FunctionError::TypeError { .. })
         ));
         assert!(matches!(
             divided_by(
-                &Value::Dictionary(std::collections::HashMap::new()),
+                &Value::Dictionary(std::collections::HashMap::new().into()),
                 &Value::Number(2.0)
             ),
             Err(FunctionError::TypeError { .. })
@@ -1444,7 +1333,7 @@ mod tests {
 
         // Type errors for second argument
         assert!(matches!(
-            divided_by(&Value::Number(10.0), &Value::String("test".to_string())),
+            divided_by(&Value::Number(10.0), &Value::String("test".into())),
             Err(FunctionError::TypeError { .. })
         ));
         assert!(matches!(
@@ -1456,13 +1345,13 @@ mod tests {
             Err(FunctionError::TypeError { .. })
         ));
         assert!(matches!(
-            divided_by(&Value::Number(10.0), &Value::Array(vec![])),
+            divided_by(&Value::Number(10.0), &Value::Array(vec![].into())),
             Err(FunctionError::TypeError { .. })
         ));
         assert!(matches!(
             divided_by(
                 &Value::Number(10.0),
-                &Value::Dictionary(std::collections::HashMap::new())
+                &Value::Dictionary(std::collections::HashMap::new().into())
             ),
             Err(FunctionError::TypeError { .. })
         ));
@@ -1671,7 +1560,7 @@ mod tests {
     fn test_modulo_type_errors() {
         // First argument not Number
         assert!(matches!(
-            modulo_fn(&Value::String("10".to_string()), &Value::Number(3.0)),
+            modulo_fn(&Value::String("10".into()), &Value::Number(3.0)),
             Err(FunctionError::TypeError {
                 expected: _,
                 got: _
@@ -1684,7 +1573,7 @@ mod tests {
 
         // Second argument not Number
         assert!(matches!(
-            modulo_fn(&Value::Number(10.0), &Value::String("3".to_string())),
+            modulo_fn(&Value::Number(10.0), &Value::String("3".into())),
             Err(FunctionError::TypeError { .. })
         ));
         assert!(matches!(
@@ -1694,7 +1583,7 @@ mod tests {
 
         // Both not Number
         assert!(matches!(
-            modulo_fn(&Value::Nil, &Value::Array(vec![])),
+            modulo_fn(&Value::Nil, &Value::Array(vec![].into())),
             Err(FunctionError::TypeError { .. })
         ));
     }
@@ -1828,11 +1717,11 @@ mod tests {
 
         // Type errors for non-Number types
         assert!(matches!(
-            max(&Value::String("test".to_string()), &Value::Number(1.0)),
+            max(&Value::String("test".into()), &Value::Number(1.0)),
             Err(FunctionError::TypeError { .. })
         ));
         assert!(matches!(
-            max(&Value::Number(1.0), &Value::String("test".to_string())),
+            max(&Value::Number(1.0), &Value::String("test".into())),
             Err(FunctionError::TypeError { .. })
         ));
         assert!(matches!(
@@ -1852,16 +1741,16 @@ mod tests {
             Err(FunctionError::TypeError { .. })
         ));
         assert!(matches!(
-            max(&Value::Array(vec![]), &Value::Number(1.0)),
+            max(&Value::Array(vec![].into()), &Value::Number(1.0)),
             Err(FunctionError::TypeError { .. })
         ));
         assert!(matches!(
-            max(&Value::Number(1.0), &Value::Array(vec![])),
+            max(&Value::Number(1.0), &Value::Array(vec![].into())),
             Err(FunctionError::TypeError { .. })
         ));
         assert!(matches!(
             max(
-                &Value::Dictionary(std::collections::HashMap::new()),
+                &Value::Dictionary(std::collections::HashMap::new().into()),
                 &Value::Number(1.0)
             ),
             Err(FunctionError::TypeError { .. })
@@ -1869,7 +1758,7 @@ mod tests {
         assert!(matches!(
             max(
                 &Value::Number(1.0),
-                &Value::Dictionary(std::collections::HashMap::new())
+                &Value::Dictionary(std::collections::HashMap::new().into())
             ),
             Err(FunctionError::TypeError { .. })
         ));
@@ -2012,11 +1901,11 @@ mod tests {
 
         // Type errors for non-Number types
         assert!(matches!(
-            min(&Value::String("test".to_string()), &Value::Number(1.0)),
+            min(&Value::String("test".into()), &Value::Number(1.0)),
             Err(FunctionError::TypeError { .. })
         ));
         assert!(matches!(
-            min(&Value::Number(1.0), &Value::String("test".to_string())),
+            min(&Value::Number(1.0), &Value::String("test".into())),
             Err(FunctionError::TypeError { .. })
         ));
         assert!(matches!(
@@ -2036,16 +1925,16 @@ mod tests {
             Err(FunctionError::TypeError { .. })
         ));
         assert!(matches!(
-            min(&Value::Array(vec![]), &Value::Number(1.0)),
+            min(&Value::Array(vec![].into()), &Value::Number(1.0)),
             Err(FunctionError::TypeError { .. })
         ));
         assert!(matches!(
-            min(&Value::Number(1.0), &Value::Array(vec![])),
+            min(&Value::Number(1.0), &Value::Array(vec![].into())),
             Err(FunctionError::TypeError { .. })
         ));
         assert!(matches!(
             min(
-                &Value::Dictionary(std::collections::HashMap::new()),
+                &Value::Dictionary(std::collections::HashMap::new().into()),
                 &Value::Number(1.0)
             ),
             Err(FunctionError::TypeError { .. })
@@ -2053,7 +1942,7 @@ mod tests {
         assert!(matches!(
             min(
                 &Value::Number(1.0),
-                &Value::Dictionary(std::collections::HashMap::new())
+                &Value::Dictionary(std::collections::HashMap::new().into())
             ),
             Err(FunctionError::TypeError { .. })
         ));
@@ -2283,7 +2172,7 @@ mod tests {
     fn test_pmt_type_errors() {
         // Test type errors for rate
         let result = pmt(
-            &Value::String("0.05".to_string()),
+            &Value::String("0.05".into()),
             &Value::Number(12.0),
             &Value::Number(10000.0),
             &Value::Number(0.0),
@@ -2303,7 +2192,7 @@ mod tests {
         let result = pmt(
             &Value::Number(0.05),
             &Value::Number(12.0),
-            &Value::Array(vec![]),
+            &Value::Array(vec![].into()),
             &Value::Number(0.0),
         );
         assert!(matches!(result, Err(FunctionError::TypeError { .. })));
@@ -2381,7 +2270,7 @@ mod tests {
     fn test_pv_type_errors() {
         // rate not Number
         let result = pv(
-            &Value::String("0.05".to_string()),
+            &Value::String("0.05".into()),
             &Value::Number(10.0),
             &Value::Number(-1000.0),
         );
@@ -2399,7 +2288,7 @@ mod tests {
         let result = pv(
             &Value::Number(0.05),
             &Value::Number(10.0),
-            &Value::Array(vec![]),
+            &Value::Array(vec![].into()),
         );
         assert!(matches!(result, Err(FunctionError::TypeError { .. })));
     }
@@ -2567,7 +2456,7 @@ mod tests {
     #[test]
     fn test_nper_type_error_rate() {
         let result = nper(
-            &Value::String("0.05".to_string()),
+            &Value::String("0.05".into()),
             &Value::Number(-200.0),
             &Value::Number(8000.0),
         );
@@ -2582,7 +2471,7 @@ mod tests {
     fn test_nper_type_error_pmt() {
         let result = nper(
             &Value::Number(0.05),
-            &Value::String("-200".to_string()),
+            &Value::String("-200".into()),
             &Value::Number(8000.0),
         );
         assert!(result.is_err());
@@ -2593,7 +2482,7 @@ mod tests {
         let result = nper(
             &Value::Number(0.05),
             &Value::Number(-200.0),
-            &Value::String("8000".to_string()),
+            &Value::String("8000".into()),
         );
         assert!(result.is_err());
     }
@@ -2724,7 +2613,7 @@ mod tests {
     #[test]
     fn test_rate_type_error_nper() {
         let result = rate(
-            &Value::String("48".to_string()),
+            &Value::String("48".into()),
             &Value::Number(-200.0),
             &Value::Number(8000.0),
         );
@@ -2746,7 +2635,7 @@ mod tests {
         let result = rate(
             &Value::Number(48.0),
             &Value::Number(-200.0),
-            &Value::Array(vec![]),
+            &Value::Array(vec![].into()),
         );
         assert!(matches!(result, Err(FunctionError::TypeError { .. })));
     }
@@ -2818,11 +2707,14 @@ mod tests {
 
     #[test]
     fn test_npv_basic() {
-        let cash_flows = Value::Array(vec![
-            Value::Number(3000.0),
-            Value::Number(4200.0),
-            Value::Number(6800.0),
-        ]);
+        let cash_flows = Value::Array(
+            vec![
+                Value::Number(3000.0),
+                Value::Number(4200.0),
+                Value::Number(6800.0),
+            ]
+            .into(),
+        );
         let result = npv(&Value::Number(0.1), &cash_flows).unwrap();
         if let Value::Number(npv) = result {
             assert!(
@@ -2835,11 +2727,14 @@ mod tests {
 
     #[test]
     fn test_npv_zero_rate() {
-        let cash_flows = Value::Array(vec![
-            Value::Number(3000.0),
-            Value::Number(4200.0),
-            Value::Number(6800.0),
-        ]);
+        let cash_flows = Value::Array(
+            vec![
+                Value::Number(3000.0),
+                Value::Number(4200.0),
+                Value::Number(6800.0),
+            ]
+            .into(),
+        );
         let result = npv(&Value::Number(0.0), &cash_flows).unwrap();
         if let Value::Number(npv) = result {
             assert!(
@@ -2852,14 +2747,14 @@ mod tests {
 
     #[test]
     fn test_npv_empty_array() {
-        let cash_flows = Value::Array(vec![]);
+        let cash_flows = Value::Array(vec![].into());
         let result = npv(&Value::Number(0.1), &cash_flows);
         assert!(matches!(result, Err(FunctionError::ArgumentError { .. })));
     }
 
     #[test]
     fn test_npv_single_value() {
-        let cash_flows = Value::Array(vec![Value::Number(5000.0)]);
+        let cash_flows = Value::Array(vec![Value::Number(5000.0)].into());
         let result = npv(&Value::Number(0.1), &cash_flows).unwrap();
         if let Value::Number(npv) = result {
             assert!(
@@ -2872,17 +2767,15 @@ mod tests {
 
     #[test]
     fn test_npv_invalid_array() {
-        let cash_flows = Value::Array(vec![
-            Value::Number(1000.0),
-            Value::String("invalid".to_string()),
-        ]);
+        let cash_flows =
+            Value::Array(vec![Value::Number(1000.0), Value::String("invalid".into())].into());
         let result = npv(&Value::Number(0.1), &cash_flows);
         assert!(matches!(result, Err(FunctionError::TypeError { .. })));
     }
 
     #[test]
     fn test_npv_negative_cash_flows() {
-        let cash_flows = Value::Array(vec![Value::Number(-2000.0), Value::Number(-3000.0)]);
+        let cash_flows = Value::Array(vec![Value::Number(-2000.0), Value::Number(-3000.0)].into());
         let result = npv(&Value::Number(0.1), &cash_flows).unwrap();
         if let Value::Number(npv) = result {
             assert!(
@@ -2895,11 +2788,14 @@ mod tests {
 
     #[test]
     fn test_npv_mixed_cash_flows() {
-        let cash_flows = Value::Array(vec![
-            Value::Number(-5000.0),
-            Value::Number(3000.0),
-            Value::Number(4000.0),
-        ]);
+        let cash_flows = Value::Array(
+            vec![
+                Value::Number(-5000.0),
+                Value::Number(3000.0),
+                Value::Number(4000.0),
+            ]
+            .into(),
+        );
         let result = npv(&Value::Number(0.1), &cash_flows).unwrap();
         if let Value::Number(npv) = result {
             // -5000/1.1 + 3000/1.21 + 4000/1.331 ≈ -4545.45 + 2479.34 + 3005.99 ≈ 939.88
@@ -2913,8 +2809,8 @@ mod tests {
 
     #[test]
     fn test_npv_rate_not_number() {
-        let cash_flows = Value::Array(vec![Value::Number(1000.0)]);
-        let result = npv(&Value::String("0.1".to_string()), &cash_flows);
+        let cash_flows = Value::Array(vec![Value::Number(1000.0)].into());
+        let result = npv(&Value::String("0.1".into()), &cash_flows);
         assert!(matches!(result, Err(FunctionError::TypeError { .. })));
     }
 
@@ -2926,12 +2822,15 @@ mod tests {
 
     #[test]
     fn test_irr_basic() {
-        let cash_flows = Value::Array(vec![
-            Value::Number(-10000.0),
-            Value::Number(3000.0),
-            Value::Number(4200.0),
-            Value::Number(6800.0),
-        ]);
+        let cash_flows = Value::Array(
+            vec![
+                Value::Number(-10000.0),
+                Value::Number(3000.0),
+                Value::Number(4200.0),
+                Value::Number(6800.0),
+            ]
+            .into(),
+        );
         let result = irr(&cash_flows).unwrap();
         if let Value::Number(irr_rate) = result {
             assert!(
@@ -2944,35 +2843,35 @@ mod tests {
 
     #[test]
     fn test_irr_no_positive_values() {
-        let cash_flows = Value::Array(vec![Value::Number(-10000.0), Value::Number(-3000.0)]);
+        let cash_flows = Value::Array(vec![Value::Number(-10000.0), Value::Number(-3000.0)].into());
         let result = irr(&cash_flows);
         assert!(matches!(result, Err(FunctionError::ArgumentError { .. })));
     }
 
     #[test]
     fn test_irr_no_negative_values() {
-        let cash_flows = Value::Array(vec![Value::Number(10000.0), Value::Number(3000.0)]);
+        let cash_flows = Value::Array(vec![Value::Number(10000.0), Value::Number(3000.0)].into());
         let result = irr(&cash_flows);
         assert!(matches!(result, Err(FunctionError::ArgumentError { .. })));
     }
 
     #[test]
     fn test_irr_empty_array() {
-        let cash_flows = Value::Array(vec![]);
+        let cash_flows = Value::Array(vec![].into());
         let result = irr(&cash_flows);
         assert!(matches!(result, Err(FunctionError::ArgumentError { .. })));
     }
 
     #[test]
     fn test_irr_single_value() {
-        let cash_flows = Value::Array(vec![Value::Number(-10000.0)]);
+        let cash_flows = Value::Array(vec![Value::Number(-10000.0)].into());
         let result = irr(&cash_flows);
         assert!(matches!(result, Err(FunctionError::ArgumentError { .. })));
     }
 
     #[test]
     fn test_irr_two_values_negative_positive() {
-        let cash_flows = Value::Array(vec![Value::Number(-1000.0), Value::Number(1100.0)]);
+        let cash_flows = Value::Array(vec![Value::Number(-1000.0), Value::Number(1100.0)].into());
         let result = irr(&cash_flows).unwrap();
         if let Value::Number(irr_rate) = result {
             assert!(
@@ -2985,13 +2884,16 @@ mod tests {
 
     #[test]
     fn test_irr_with_zeros() {
-        let cash_flows = Value::Array(vec![
-            Value::Number(-10000.0),
-            Value::Number(0.0),
-            Value::Number(3000.0),
-            Value::Number(4200.0),
-            Value::Number(6800.0),
-        ]);
+        let cash_flows = Value::Array(
+            vec![
+                Value::Number(-10000.0),
+                Value::Number(0.0),
+                Value::Number(3000.0),
+                Value::Number(4200.0),
+                Value::Number(6800.0),
+            ]
+            .into(),
+        );
         let result = irr(&cash_flows).unwrap();
         if let Value::Number(irr_rate) = result {
             assert!(
@@ -3005,7 +2907,7 @@ mod tests {
     #[test]
     fn test_irr_negative_irr() {
         // Cash flows that should give negative IRR
-        let cash_flows = Value::Array(vec![Value::Number(1000.0), Value::Number(-800.0)]);
+        let cash_flows = Value::Array(vec![Value::Number(1000.0), Value::Number(-800.0)].into());
         let result = irr(&cash_flows).unwrap();
         if let Value::Number(irr_rate) = result {
             assert!(
@@ -3024,10 +2926,8 @@ mod tests {
 
     #[test]
     fn test_irr_type_error_array_with_non_number() {
-        let cash_flows = Value::Array(vec![
-            Value::Number(-10000.0),
-            Value::String("3000".to_string()),
-        ]);
+        let cash_flows =
+            Value::Array(vec![Value::Number(-10000.0), Value::String("3000".into())].into());
         let result = irr(&cash_flows);
         assert!(matches!(result, Err(FunctionError::TypeError { .. })));
     }
@@ -3035,7 +2935,7 @@ mod tests {
     #[test]
     fn test_irr_precision() {
         // Test with known precise IRR
-        let cash_flows = Value::Array(vec![Value::Number(-1000.0), Value::Number(1100.0)]);
+        let cash_flows = Value::Array(vec![Value::Number(-1000.0), Value::Number(1100.0)].into());
         let result = irr(&cash_flows).unwrap();
         if let Value::Number(irr_rate) = result {
             // IRR should be approximately 0.1 (10%)
@@ -3049,12 +2949,15 @@ mod tests {
 
     #[test]
     fn test_irr_large_numbers() {
-        let cash_flows = Value::Array(vec![
-            Value::Number(-1e10),
-            Value::Number(3e9),
-            Value::Number(4.2e9),
-            Value::Number(6.8e9),
-        ]);
+        let cash_flows = Value::Array(
+            vec![
+                Value::Number(-1e10),
+                Value::Number(3e9),
+                Value::Number(4.2e9),
+                Value::Number(6.8e9),
+            ]
+            .into(),
+        );
         let result = irr(&cash_flows).unwrap();
         if let Value::Number(irr_rate) = result {
             assert!(
@@ -3067,12 +2970,15 @@ mod tests {
 
     #[test]
     fn test_irr_small_numbers() {
-        let cash_flows = Value::Array(vec![
-            Value::Number(-1e-6),
-            Value::Number(3e-7),
-            Value::Number(4.2e-7),
-            Value::Number(6.8e-7),
-        ]);
+        let cash_flows = Value::Array(
+            vec![
+                Value::Number(-1e-6),
+                Value::Number(3e-7),
+                Value::Number(4.2e-7),
+                Value::Number(6.8e-7),
+            ]
+            .into(),
+        );
         let result = irr(&cash_flows).unwrap();
         if let Value::Number(irr_rate) = result {
             assert!(
@@ -3085,7 +2991,7 @@ mod tests {
 
     #[test]
     fn test_irr_with_nan() {
-        let cash_flows = Value::Array(vec![Value::Number(f64::NAN), Value::Number(3000.0)]);
+        let cash_flows = Value::Array(vec![Value::Number(f64::NAN), Value::Number(3000.0)].into());
         let result = irr(&cash_flows);
         // Should either error or return NaN, but since we convert to f64, it will proceed
         // Actually, since we collect into Vec<f64>, NaN will be there, and NPV will be NaN
@@ -3097,12 +3003,15 @@ mod tests {
     fn test_irr_non_convergence() {
         // Create a case that might not converge easily
         // This is hard to construct, but let's try cash flows that oscillate
-        let cash_flows = Value::Array(vec![
-            Value::Number(-1000.0),
-            Value::Number(2000.0),
-            Value::Number(-1500.0),
-            Value::Number(1200.0),
-        ]);
+        let cash_flows = Value::Array(
+            vec![
+                Value::Number(-1000.0),
+                Value::Number(2000.0),
+                Value::Number(-1500.0),
+                Value::Number(1200.0),
+            ]
+            .into(),
+        );
         let result = irr(&cash_flows);
         // May or may not converge, but should not panic
         let _ = result;
@@ -3110,12 +3019,15 @@ mod tests {
 
     #[test]
     fn test_mirr_basic() {
-        let cash_flows = Value::Array(vec![
-            Value::Number(-10000.0),
-            Value::Number(3000.0),
-            Value::Number(4200.0),
-            Value::Number(6800.0),
-        ]);
+        let cash_flows = Value::Array(
+            vec![
+                Value::Number(-10000.0),
+                Value::Number(3000.0),
+                Value::Number(4200.0),
+                Value::Number(6800.0),
+            ]
+            .into(),
+        );
         let result = mirr(&cash_flows, &Value::Number(0.1), &Value::Number(0.12)).unwrap();
         if let Value::Number(mirr_rate) = result {
             assert!(
@@ -3129,12 +3041,15 @@ mod tests {
     #[test]
     fn test_mirr_different_rates() {
         // Test with different finance and reinvestment rates
-        let cash_flows = Value::Array(vec![
-            Value::Number(-5000.0),
-            Value::Number(1500.0),
-            Value::Number(2000.0),
-            Value::Number(2500.0),
-        ]);
+        let cash_flows = Value::Array(
+            vec![
+                Value::Number(-5000.0),
+                Value::Number(1500.0),
+                Value::Number(2000.0),
+                Value::Number(2500.0),
+            ]
+            .into(),
+        );
 
         // Same rates
         let result = mirr(&cash_flows, &Value::Number(0.08), &Value::Number(0.08)).unwrap();
@@ -3159,12 +3074,15 @@ mod tests {
 
     #[test]
     fn test_mirr_zero_rates() {
-        let cash_flows = Value::Array(vec![
-            Value::Number(-1000.0),
-            Value::Number(300.0),
-            Value::Number(400.0),
-            Value::Number(500.0),
-        ]);
+        let cash_flows = Value::Array(
+            vec![
+                Value::Number(-1000.0),
+                Value::Number(300.0),
+                Value::Number(400.0),
+                Value::Number(500.0),
+            ]
+            .into(),
+        );
 
         // Zero finance rate
         let result = mirr(&cash_flows, &Value::Number(0.0), &Value::Number(0.1)).unwrap();
@@ -3189,11 +3107,14 @@ mod tests {
 
     #[test]
     fn test_mirr_negative_rates() {
-        let cash_flows = Value::Array(vec![
-            Value::Number(-2000.0),
-            Value::Number(800.0),
-            Value::Number(1000.0),
-        ]);
+        let cash_flows = Value::Array(
+            vec![
+                Value::Number(-2000.0),
+                Value::Number(800.0),
+                Value::Number(1000.0),
+            ]
+            .into(),
+        );
 
         // Negative finance rate
         let result = mirr(&cash_flows, &Value::Number(-0.05), &Value::Number(0.1)).unwrap();
@@ -3219,11 +3140,14 @@ mod tests {
     #[test]
     fn test_mirr_all_positive_cash_flows() {
         // Should error - no negative cash flows
-        let cash_flows = Value::Array(vec![
-            Value::Number(1000.0),
-            Value::Number(2000.0),
-            Value::Number(3000.0),
-        ]);
+        let cash_flows = Value::Array(
+            vec![
+                Value::Number(1000.0),
+                Value::Number(2000.0),
+                Value::Number(3000.0),
+            ]
+            .into(),
+        );
         let result = mirr(&cash_flows, &Value::Number(0.1), &Value::Number(0.12));
         assert!(matches!(result, Err(FunctionError::ArgumentError { .. })));
         if let Err(FunctionError::ArgumentError { message }) = result {
@@ -3237,11 +3161,14 @@ mod tests {
     #[test]
     fn test_mirr_all_negative_cash_flows() {
         // Should error - no positive cash flows
-        let cash_flows = Value::Array(vec![
-            Value::Number(-1000.0),
-            Value::Number(-2000.0),
-            Value::Number(-3000.0),
-        ]);
+        let cash_flows = Value::Array(
+            vec![
+                Value::Number(-1000.0),
+                Value::Number(-2000.0),
+                Value::Number(-3000.0),
+            ]
+            .into(),
+        );
         let result = mirr(&cash_flows, &Value::Number(0.1), &Value::Number(0.12));
         assert!(matches!(result, Err(FunctionError::ArgumentError { .. })));
         if let Err(FunctionError::ArgumentError { message }) = result {
@@ -3254,13 +3181,16 @@ mod tests {
 
     #[test]
     fn test_mirr_with_zeros() {
-        let cash_flows = Value::Array(vec![
-            Value::Number(-1000.0),
-            Value::Number(0.0),
-            Value::Number(500.0),
-            Value::Number(0.0),
-            Value::Number(600.0),
-        ]);
+        let cash_flows = Value::Array(
+            vec![
+                Value::Number(-1000.0),
+                Value::Number(0.0),
+                Value::Number(500.0),
+                Value::Number(0.0),
+                Value::Number(600.0),
+            ]
+            .into(),
+        );
         let result = mirr(&cash_flows, &Value::Number(0.1), &Value::Number(0.12)).unwrap();
         if let Value::Number(mirr_rate) = result {
             assert!(
@@ -3273,7 +3203,7 @@ mod tests {
 
     #[test]
     fn test_mirr_single_negative_single_positive() {
-        let cash_flows = Value::Array(vec![Value::Number(-1000.0), Value::Number(1200.0)]);
+        let cash_flows = Value::Array(vec![Value::Number(-1000.0), Value::Number(1200.0)].into());
         let result = mirr(&cash_flows, &Value::Number(0.1), &Value::Number(0.12)).unwrap();
         if let Value::Number(mirr_rate) = result {
             // MIRR = (1200 / 1000)^(1/1) - 1 = 1.2 - 1 = 0.2
@@ -3287,7 +3217,7 @@ mod tests {
 
     #[test]
     fn test_mirr_empty_array() {
-        let cash_flows = Value::Array(vec![]);
+        let cash_flows = Value::Array(vec![].into());
         let result = mirr(&cash_flows, &Value::Number(0.1), &Value::Number(0.12));
         assert!(matches!(result, Err(FunctionError::ArgumentError { .. })));
         if let Err(FunctionError::ArgumentError { message }) = result {
@@ -3297,11 +3227,14 @@ mod tests {
 
     #[test]
     fn test_mirr_invalid_array_element() {
-        let cash_flows = Value::Array(vec![
-            Value::Number(-1000.0),
-            Value::String("invalid".to_string()),
-            Value::Number(500.0),
-        ]);
+        let cash_flows = Value::Array(
+            vec![
+                Value::Number(-1000.0),
+                Value::String("invalid".into()),
+                Value::Number(500.0),
+            ]
+            .into(),
+        );
         let result = mirr(&cash_flows, &Value::Number(0.1), &Value::Number(0.12));
         assert!(matches!(result, Err(FunctionError::TypeError { .. })));
         if let Err(FunctionError::TypeError { expected, got }) = result {
@@ -3312,10 +3245,10 @@ mod tests {
 
     #[test]
     fn test_mirr_finance_rate_not_number() {
-        let cash_flows = Value::Array(vec![Value::Number(-1000.0), Value::Number(1200.0)]);
+        let cash_flows = Value::Array(vec![Value::Number(-1000.0), Value::Number(1200.0)].into());
         let result = mirr(
             &cash_flows,
-            &Value::String("0.1".to_string()),
+            &Value::String("0.1".into()),
             &Value::Number(0.12),
         );
         assert!(matches!(result, Err(FunctionError::TypeError { .. })));
@@ -3327,11 +3260,11 @@ mod tests {
 
     #[test]
     fn test_mirr_reinvest_rate_not_number() {
-        let cash_flows = Value::Array(vec![Value::Number(-1000.0), Value::Number(1200.0)]);
+        let cash_flows = Value::Array(vec![Value::Number(-1000.0), Value::Number(1200.0)].into());
         let result = mirr(
             &cash_flows,
             &Value::Number(0.1),
-            &Value::String("0.12".to_string()),
+            &Value::String("0.12".into()),
         );
         assert!(matches!(result, Err(FunctionError::TypeError { .. })));
         if let Err(FunctionError::TypeError { expected, got }) = result {
@@ -3357,12 +3290,15 @@ mod tests {
     #[test]
     fn test_mirr_extreme_values() {
         // Very large numbers
-        let cash_flows = Value::Array(vec![
-            Value::Number(-1e10),
-            Value::Number(3e9),
-            Value::Number(4e9),
-            Value::Number(5e9),
-        ]);
+        let cash_flows = Value::Array(
+            vec![
+                Value::Number(-1e10),
+                Value::Number(3e9),
+                Value::Number(4e9),
+                Value::Number(5e9),
+            ]
+            .into(),
+        );
         let result = mirr(&cash_flows, &Value::Number(0.1), &Value::Number(0.12)).unwrap();
         if let Value::Number(mirr_rate) = result {
             assert!(
@@ -3372,11 +3308,14 @@ mod tests {
         }
 
         // Very small numbers
-        let cash_flows = Value::Array(vec![
-            Value::Number(-1e-10),
-            Value::Number(3e-10),
-            Value::Number(4e-10),
-        ]);
+        let cash_flows = Value::Array(
+            vec![
+                Value::Number(-1e-10),
+                Value::Number(3e-10),
+                Value::Number(4e-10),
+            ]
+            .into(),
+        );
         let result = mirr(&cash_flows, &Value::Number(0.1), &Value::Number(0.12)).unwrap();
         if let Value::Number(mirr_rate) = result {
             assert!(
@@ -3389,10 +3328,8 @@ mod tests {
     #[test]
     fn test_mirr_infinity_and_nan() {
         // Test with infinity in cash flows - negative infinity should work
-        let cash_flows = Value::Array(vec![
-            Value::Number(f64::NEG_INFINITY),
-            Value::Number(1000.0),
-        ]);
+        let cash_flows =
+            Value::Array(vec![Value::Number(f64::NEG_INFINITY), Value::Number(1000.0)].into());
         let result = mirr(&cash_flows, &Value::Number(0.1), &Value::Number(0.12)).unwrap();
         if let Value::Number(mirr_rate) = result {
             // With negative infinity as initial investment, MIRR should be defined
@@ -3403,12 +3340,15 @@ mod tests {
         }
 
         // Test with NaN in reinvest rate (should propagate NaN)
-        let cash_flows = Value::Array(vec![
-            Value::Number(-1000.0),
-            Value::Number(300.0),
-            Value::Number(400.0),
-            Value::Number(500.0),
-        ]);
+        let cash_flows = Value::Array(
+            vec![
+                Value::Number(-1000.0),
+                Value::Number(300.0),
+                Value::Number(400.0),
+                Value::Number(500.0),
+            ]
+            .into(),
+        );
         let result = mirr(&cash_flows, &Value::Number(0.1), &Value::Number(f64::NAN)).unwrap();
         if let Value::Number(mirr_rate) = result {
             assert!(
@@ -3418,11 +3358,14 @@ mod tests {
         }
 
         // Test with NaN in finance rate for non-zero periods
-        let cash_flows = Value::Array(vec![
-            Value::Number(-1000.0),
-            Value::Number(-500.0),
-            Value::Number(2000.0),
-        ]);
+        let cash_flows = Value::Array(
+            vec![
+                Value::Number(-1000.0),
+                Value::Number(-500.0),
+                Value::Number(2000.0),
+            ]
+            .into(),
+        );
         let result = mirr(&cash_flows, &Value::Number(f64::NAN), &Value::Number(0.12)).unwrap();
         if let Value::Number(mirr_rate) = result {
             assert!(
@@ -3435,11 +3378,14 @@ mod tests {
     #[test]
     fn test_mirr_precision() {
         // Test precision with known values
-        let cash_flows = Value::Array(vec![
-            Value::Number(-1000.0),
-            Value::Number(600.0),
-            Value::Number(600.0),
-        ]);
+        let cash_flows = Value::Array(
+            vec![
+                Value::Number(-1000.0),
+                Value::Number(600.0),
+                Value::Number(600.0),
+            ]
+            .into(),
+        );
         let result = mirr(&cash_flows, &Value::Number(0.1), &Value::Number(0.1)).unwrap();
         if let Value::Number(mirr_rate) = result {
             // PV_negative = -1000
@@ -3553,7 +3499,7 @@ mod tests {
     #[test]
     fn test_sln_type_error_cost() {
         let result = sln(
-            &Value::String("30000".to_string()),
+            &Value::String("30000".into()),
             &Value::Number(7500.0),
             &Value::Number(10.0),
         );
@@ -3583,7 +3529,7 @@ mod tests {
         let result = sln(
             &Value::Number(30000.0),
             &Value::Number(7500.0),
-            &Value::Array(vec![]),
+            &Value::Array(vec![].into()),
         );
         assert!(matches!(result, Err(FunctionError::TypeError { .. })));
         if let Err(FunctionError::TypeError { expected, got }) = result {
@@ -3595,11 +3541,11 @@ mod tests {
     #[test]
     fn test_sln_all_type_errors() {
         // Test various non-Number types for each parameter
-        let string_val = Value::String("test".to_string());
+        let string_val = Value::String("test".into());
         let bool_val = Value::Boolean(true);
         let nil_val = Value::Nil;
-        let array_val = Value::Array(vec![]);
-        let dict_val = Value::Dictionary(HashMap::new());
+        let array_val = Value::Array(vec![].into());
+        let dict_val = Value::Dictionary(HashMap::new().into());
         let symbol_val = Value::Symbol("test".to_string());
         let num_val = Value::Number(30000.0);
         let salvage_val = Value::Number(7500.0);
@@ -3871,7 +3817,7 @@ mod tests {
     #[test]
     fn test_ddb_type_error_cost() {
         let result = ddb(
-            &Value::String("30000".to_string()),
+            &Value::String("30000".into()),
             &Value::Number(7500.0),
             &Value::Number(10.0),
             &Value::Number(1.0),
@@ -3895,7 +3841,7 @@ mod tests {
         let result = ddb(
             &Value::Number(30000.0),
             &Value::Number(7500.0),
-            &Value::Array(vec![]),
+            &Value::Array(vec![].into()),
             &Value::Number(1.0),
         );
         assert!(matches!(result, Err(FunctionError::TypeError { .. })));
@@ -4121,7 +4067,7 @@ mod tests {
     fn test_db_type_errors() {
         // cost not Number
         let result = db(
-            &Value::String("1000000".to_string()),
+            &Value::String("1000000".into()),
             &Value::Number(100000.0),
             &Value::Number(6.0),
             &Value::Number(1.0),
@@ -4154,7 +4100,7 @@ mod tests {
             &Value::Number(1000000.0),
             &Value::Number(100000.0),
             &Value::Number(6.0),
-            &Value::Array(vec![]),
+            &Value::Array(vec![].into()),
             &Value::Number(7.0),
         );
         assert!(matches!(result, Err(FunctionError::TypeError { .. })));
@@ -4165,7 +4111,7 @@ mod tests {
             &Value::Number(100000.0),
             &Value::Number(6.0),
             &Value::Number(1.0),
-            &Value::Dictionary(std::collections::HashMap::new()),
+            &Value::Dictionary(std::collections::HashMap::new().into()),
         );
         assert!(matches!(result, Err(FunctionError::TypeError { .. })));
     }
@@ -4311,7 +4257,7 @@ mod tests {
     #[test]
     fn test_ppmt_type_error_rate() {
         let result = ppmt(
-            &Value::String("0.05".to_string()),
+            &Value::String("0.05".into()),
             &Value::Number(1.0),
             &Value::Number(10.0),
             &Value::Number(10000.0),
@@ -4337,7 +4283,7 @@ mod tests {
         let result = ppmt(
             &Value::Number(0.05),
             &Value::Number(1.0),
-            &Value::Array(vec![]),
+            &Value::Array(vec![].into()),
             &Value::Number(10000.0),
             &Value::Number(0.0),
         );
@@ -4350,7 +4296,7 @@ mod tests {
             &Value::Number(0.05),
             &Value::Number(1.0),
             &Value::Number(10.0),
-            &Value::Dictionary(HashMap::new()),
+            &Value::Dictionary(HashMap::new().into()),
             &Value::Number(0.0),
         );
         assert!(matches!(result, Err(FunctionError::TypeError { .. })));
@@ -4639,7 +4585,7 @@ mod tests {
     fn test_cumprinc_type_errors() {
         // Non-number rate
         let result = cumprinc(
-            &Value::String("0.05".to_string()),
+            &Value::String("0.05".into()),
             &Value::Number(10.0),
             &Value::Number(1000.0),
             &Value::Number(1.0),
@@ -4663,7 +4609,7 @@ mod tests {
         let result = cumprinc(
             &Value::Number(0.05),
             &Value::Number(10.0),
-            &Value::Array(vec![]),
+            &Value::Array(vec![].into()),
             &Value::Number(1.0),
             &Value::Number(5.0),
             &Value::Number(0.0),
@@ -4687,7 +4633,7 @@ mod tests {
             &Value::Number(10.0),
             &Value::Number(1000.0),
             &Value::Number(1.0),
-            &Value::Dictionary(std::collections::HashMap::new()),
+            &Value::Dictionary(std::collections::HashMap::new().into()),
             &Value::Number(0.0),
         );
         assert!(matches!(result, Err(FunctionError::TypeError { .. })));
@@ -4856,7 +4802,7 @@ mod tests {
 
     #[test]
     fn test_effect_type_error_nominal_rate() {
-        let result = effect(&Value::String("0.05".to_string()), &Value::Number(4.0));
+        let result = effect(&Value::String("0.05".into()), &Value::Number(4.0));
         assert!(matches!(result, Err(FunctionError::TypeError { .. })));
         if let Err(FunctionError::TypeError { expected, got }) = result {
             assert_eq!(expected, "Number");
@@ -4876,11 +4822,11 @@ mod tests {
 
     #[test]
     fn test_effect_all_type_errors() {
-        let string_val = Value::String("test".to_string());
+        let string_val = Value::String("test".into());
         let bool_val = Value::Boolean(true);
         let nil_val = Value::Nil;
-        let array_val = Value::Array(vec![]);
-        let dict_val = Value::Dictionary(std::collections::HashMap::new());
+        let array_val = Value::Array(vec![].into());
+        let dict_val = Value::Dictionary(std::collections::HashMap::new().into());
         let symbol_val = Value::Symbol("test".to_string());
         let num_val = Value::Number(0.05);
         let npery_val = Value::Number(4.0);
@@ -5089,7 +5035,7 @@ mod tests {
 
     #[test]
     fn test_nominal_type_error_effect_rate() {
-        let result = nominal(&Value::String("0.05".to_string()), &Value::Number(4.0));
+        let result = nominal(&Value::String("0.05".into()), &Value::Number(4.0));
         assert!(matches!(result, Err(FunctionError::TypeError { .. })));
         if let Err(FunctionError::TypeError { expected, got }) = result {
             assert_eq!(expected, "Number");
@@ -5109,11 +5055,11 @@ mod tests {
 
     #[test]
     fn test_nominal_all_type_errors() {
-        let string_val = Value::String("test".to_string());
+        let string_val = Value::String("test".into());
         let bool_val = Value::Boolean(true);
         let nil_val = Value::Nil;
-        let array_val = Value::Array(vec![]);
-        let dict_val = Value::Dictionary(std::collections::HashMap::new());
+        let array_val = Value::Array(vec![].into());
+        let dict_val = Value::Dictionary(std::collections::HashMap::new().into());
         let symbol_val = Value::Symbol("test".to_string());
         let num_val = Value::Number(0.05);
         let npery_val = Value::Number(4.0);
@@ -5301,7 +5247,7 @@ mod tests {
 
     #[test]
     fn test_inventory_registration() {
-        let functions: Vec<_> = get_all_numeric_functions().into_iter().collect();
+        let functions: Vec<_> = crate::signatures::all().collect();
         assert!(
             !functions.is_empty(),
             "Should have registered numeric functions"
@@ -5426,7 +5372,7 @@ mod tests {
     fn test_ipmt_type_error_rate() {
         // Test ipmt with non-number rate
         let result = ipmt(
-            &Value::String("0.1".to_string()),
+            &Value::String("0.1".into()),
             &Value::Number(1.0),
             &Value::Number(36.0),
             &Value::Number(8000.0),
@@ -5440,7 +5386,7 @@ mod tests {
         // Test ipmt with non-number per
         let result = ipmt(
             &Value::Number(0.1 / 12.0),
-            &Value::String("1".to_string()),
+            &Value::String("1".into()),
             &Value::Number(36.0),
             &Value::Number(8000.0),
             &Value::Number(0.0),
@@ -5454,7 +5400,7 @@ mod tests {
         let result = ipmt(
             &Value::Number(0.1 / 12.0),
             &Value::Number(1.0),
-            &Value::String("36".to_string()),
+            &Value::String("36".into()),
             &Value::Number(8000.0),
             &Value::Number(0.0),
         );
@@ -5468,7 +5414,7 @@ mod tests {
             &Value::Number(0.1 / 12.0),
             &Value::Number(1.0),
             &Value::Number(36.0),
-            &Value::String("8000".to_string()),
+            &Value::String("8000".into()),
             &Value::Number(0.0),
         );
         assert!(matches!(result, Err(FunctionError::TypeError { .. })));
@@ -5621,4 +5567,1556 @@ mod tests {
             );
         }
     }
+
+    #[test]
+    fn test_number_format() {
+        let comma = Value::String(",".into());
+        let dot = Value::String(".".into());
+        assert_eq!(
+            number_format(
+                &Value::Number(1234567.891),
+                &Value::Number(2.0),
+                &comma,
+                &dot
+            )
+            .unwrap(),
+            Value::String("1,234,567.89".into())
+        );
+        assert_eq!(
+            number_format(&Value::Number(999.5), &Value::Number(0.0), &comma, &dot).unwrap(),
+            Value::String("1,000".into())
+        );
+        assert_eq!(
+            number_format(&Value::Number(-12345.0), &Value::Number(1.0), &comma, &dot).unwrap(),
+            Value::String("-12,345.0".into())
+        );
+        assert_eq!(
+            number_format(&Value::Number(-0.001), &Value::Number(2.0), &comma, &dot).unwrap(),
+            Value::String("0.00".into())
+        );
+        assert_eq!(
+            number_format(&Value::Number(42.0), &Value::Number(0.0), &comma, &dot).unwrap(),
+            Value::String("42".into())
+        );
+
+        // Halves round away from zero, as in round
+        assert_eq!(
+            number_format(&Value::Number(2.5), &Value::Number(0.0), &comma, &dot).unwrap(),
+            Value::String("3".into())
+        );
+        assert_eq!(
+            number_format(&Value::Number(-0.125), &Value::Number(2.0), &comma, &dot).unwrap(),
+            Value::String("-0.13".into())
+        );
+    }
+
+    #[test]
+    fn test_number_format_custom_separators() {
+        let result = number_format(
+            &Value::Number(1234567.891),
+            &Value::Number(2.0),
+            &Value::String(".".into()),
+            &Value::String(",".into()),
+        )
+        .unwrap();
+        assert_eq!(result, Value::String("1.234.567,89".into()));
+    }
+
+    #[test]
+    fn test_number_format_errors() {
+        let comma = Value::String(",".into());
+        let dot = Value::String(".".into());
+        assert!(matches!(
+            number_format(&Value::Number(f64::NAN), &Value::Number(2.0), &comma, &dot),
+            Err(FunctionError::ValueError { .. })
+        ));
+        assert!(matches!(
+            number_format(
+                &Value::String("1".into()),
+                &Value::Number(2.0),
+                &comma,
+                &dot
+            ),
+            Err(FunctionError::TypeError { .. })
+        ));
+        assert!(matches!(
+            number_format(&Value::Number(1.0), &Value::Number(2.0), &Value::Nil, &dot),
+            Err(FunctionError::TypeError { .. })
+        ));
+        for decimals in [-1.0, 21.0, 1.5, f64::NAN] {
+            assert!(matches!(
+                number_format(&Value::Number(1.0), &Value::Number(decimals), &comma, &dot),
+                Err(FunctionError::ArgumentError { .. })
+            ));
+        }
+    }
+
+    #[test]
+    fn test_currency() {
+        let usd = Value::String("USD".into());
+        let en_us = Value::String("en-US".into());
+        assert_eq!(
+            currency(&Value::Number(1234.56), &usd, &en_us).unwrap(),
+            Value::String("$1,234.56".into())
+        );
+        assert_eq!(
+            currency(&Value::Number(-1234.5), &usd, &en_us).unwrap(),
+            Value::String("-$1,234.50".into())
+        );
+        // Halves round away from zero, as in round
+        assert_eq!(
+            currency(&Value::Number(0.125), &usd, &en_us).unwrap(),
+            Value::String("$0.13".into())
+        );
+        assert_eq!(
+            currency(&Value::Number(-2.5), &Value::String("JPY".into()), &en_us).unwrap(),
+            Value::String("-¥3".into())
+        );
+        assert_eq!(
+            currency(
+                &Value::Number(1234.56),
+                &Value::String("JPY".into()),
+                &en_us
+            )
+            .unwrap(),
+            Value::String("¥1,235".into())
+        );
+        assert_eq!(
+            currency(
+                &Value::Number(1234.56),
+                &Value::String("EUR".into()),
+                &Value::String("de-DE".into())
+            )
+            .unwrap(),
+            Value::String("1.234,56 €".into())
+        );
+    }
+
+    #[test]
+    fn test_currency_errors() {
+        let en_us = Value::String("en-US".into());
+        assert!(matches!(
+            currency(&Value::Number(1.0), &Value::String("XYZ".into()), &en_us),
+            Err(FunctionError::ArgumentError { .. })
+        ));
+        assert!(matches!(
+            currency(
+                &Value::Number(1.0),
+                &Value::String("USD".into()),
+                &Value::String("xx-XX".into())
+            ),
+            Err(FunctionError::ArgumentError { .. })
+        ));
+        assert!(matches!(
+            currency(
+                &Value::String("1".into()),
+                &Value::String("USD".into()),
+                &en_us
+            ),
+            Err(FunctionError::TypeError { .. })
+        ));
+    }
+
+    #[test]
+    fn test_power_domain_errors() {
+        assert_eq!(
+            power(&Value::Number(1.05), &Value::Number(2.0)).unwrap(),
+            Value::Number(1.05_f64.powf(2.0))
+        );
+        assert_eq!(
+            power(&Value::Number(-8.0), &Value::Number(3.0)).unwrap(),
+            Value::Number(-512.0)
+        );
+        assert!(matches!(
+            power(&Value::Number(-8.0), &Value::Number(1.0 / 3.0)),
+            Err(FunctionError::ValueError { .. })
+        ));
+        assert!(matches!(
+            power(&Value::Number(0.0), &Value::Number(-1.0)),
+            Err(FunctionError::ValueError { .. })
+        ));
+        assert!(matches!(
+            power(&Value::Number(10.0), &Value::Number(400.0)),
+            Err(FunctionError::ValueError { .. })
+        ));
+    }
+
+    #[test]
+    fn test_exp() {
+        assert_eq!(exp(&Value::Number(0.0)).unwrap(), Value::Number(1.0));
+        assert_eq!(
+            exp(&Value::Number(1.0)).unwrap(),
+            Value::Number(std::f64::consts::E)
+        );
+        if let Value::Number(n) = exp(&Value::Number(-1000.0)).unwrap() {
+            assert_eq!(n, 0.0);
+        }
+        assert!(matches!(
+            exp(&Value::Number(1000.0)),
+            Err(FunctionError::ValueError { .. })
+        ));
+        assert!(matches!(
+            exp(&Value::String("1".into())),
+            Err(FunctionError::TypeError { .. })
+        ));
+    }
+
+    #[test]
+    fn test_math_inventory_registration() {
+        let names: Vec<_> = crate::signatures::all().map(|f| f.name).collect();
+        for name in ["sqrt", "pow", "power", "log", "log10", "ln", "exp"] {
+            assert!(names.contains(&name), "Should contain {} function", name);
+        }
+    }
+
+    #[test]
+    fn test_clamp() {
+        let clamp_num = |n: f64, lo: f64, hi: f64| {
+            clamp(&Value::Number(n), &Value::Number(lo), &Value::Number(hi)).unwrap()
+        };
+        assert_eq!(clamp_num(1.4, 0.8, 1.25), Value::Number(1.25));
+        assert_eq!(clamp_num(0.5, 0.8, 1.25), Value::Number(0.8));
+        assert_eq!(clamp_num(1.0, 0.8, 1.25), Value::Number(1.0));
+        assert_eq!(clamp_num(3.0, 3.0, 3.0), Value::Number(3.0));
+        assert!(matches!(
+            clamp(
+                &Value::Number(1.0),
+                &Value::Number(2.0),
+                &Value::Number(1.0)
+            ),
+            Err(FunctionError::ArgumentError { .. })
+        ));
+        assert!(matches!(
+            clamp(&Value::Number(1.0), &Value::Nil, &Value::Number(1.0)),
+            Err(FunctionError::TypeError { .. })
+        ));
+    }
+
+    #[test]
+    fn test_sign() {
+        assert_eq!(sign(&Value::Number(-42.5)).unwrap(), Value::Number(-1.0));
+        assert_eq!(sign(&Value::Number(0.0)).unwrap(), Value::Number(0.0));
+        assert_eq!(sign(&Value::Number(-0.0)).unwrap(), Value::Number(0.0));
+        assert_eq!(sign(&Value::Number(7.0)).unwrap(), Value::Number(1.0));
+        assert!(matches!(
+            sign(&Value::Boolean(true)),
+            Err(FunctionError::TypeError { .. })
+        ));
+    }
+
+    #[test]
+    fn test_trunc() {
+        assert_eq!(trunc(&Value::Number(3.99)).unwrap(), Value::Number(3.0));
+        assert_eq!(trunc(&Value::Number(-3.99)).unwrap(), Value::Number(-3.0));
+        assert_eq!(trunc(&Value::Number(5.0)).unwrap(), Value::Number(5.0));
+        assert!(matches!(
+            trunc(&Value::Nil),
+            Err(FunctionError::TypeError { .. })
+        ));
+    }
+
+    #[test]
+    fn test_gcd() {
+        let gcd_num = |a: f64, b: f64| gcd(&Value::Number(a), &Value::Number(b)).unwrap();
+        assert_eq!(gcd_num(12.0, 18.0), Value::Number(6.0));
+        assert_eq!(gcd_num(-12.0, 18.0), Value::Number(6.0));
+        assert_eq!(gcd_num(7.0, 0.0), Value::Number(7.0));
+        assert_eq!(gcd_num(0.0, 0.0), Value::Number(0.0));
+        assert!(matches!(
+            gcd(&Value::Number(1.5), &Value::Number(3.0)),
+            Err(FunctionError::ArgumentError { .. })
+        ));
+        assert!(matches!(
+            gcd(&Value::Number(f64::INFINITY), &Value::Number(3.0)),
+            Err(FunctionError::ArgumentError { .. })
+        ));
+        assert!(matches!(
+            gcd(&Value::Number(3.0), &Value::Nil),
+            Err(FunctionError::TypeError { .. })
+        ));
+
+        // Integers stay integers, past 2^53 too
+        assert!(matches!(
+            gcd(&Value::Int(12), &Value::Int(-18)),
+            Ok(Value::Int(6))
+        ));
+        assert!(matches!(
+            gcd(&Value::Int(i64::MAX), &Value::Int(0)),
+            Ok(Value::Int(i64::MAX))
+        ));
+        assert!(matches!(
+            gcd(&Value::Int(12), &Value::Number(18.0)),
+            Ok(Value::Number(n)) if n == 6.0
+        ));
+        assert!(matches!(
+            gcd(&Value::Int(i64::MIN), &Value::Int(0)),
+            Err(FunctionError::ValueError { message })
+                if message == "gcd(-9223372036854775808, 0) is too large to represent exactly"
+        ));
+    }
+
+    #[test]
+    fn test_lcm() {
+        let lcm_num = |a: f64, b: f64| lcm(&Value::Number(a), &Value::Number(b)).unwrap();
+        // Monthly and quarterly installments line up every 12 months
+        assert_eq!(lcm_num(4.0, 6.0), Value::Number(12.0));
+        assert_eq!(lcm_num(-3.0, 5.0), Value::Number(15.0));
+        assert_eq!(lcm_num(0.0, 5.0), Value::Number(0.0));
+        assert!(matches!(
+            lcm(
+                &Value::Number(9_007_199_254_740_881.0),
+                &Value::Number(9_007_199_254_740_847.0)
+            ),
+            Err(FunctionError::ValueError { .. })
+        ));
+
+        assert!(matches!(
+            lcm(&Value::Int(4), &Value::Int(6)),
+            Ok(Value::Int(12))
+        ));
+        assert!(matches!(
+            lcm(&Value::Int(0), &Value::Int(6)),
+            Ok(Value::Int(0))
+        ));
+        assert!(matches!(
+            lcm(&Value::Int(9_007_199_254_740_881), &Value::Int(3)),
+            Ok(Value::Int(27_021_597_764_222_643))
+        ));
+        // The error names the arguments as given, not as floats
+        assert!(matches!(
+            lcm(&Value::Int(i64::MAX), &Value::Int(2)),
+            Err(FunctionError::ValueError { message })
+                if message == "lcm(9223372036854775807, 2) is too large to represent exactly"
+        ));
+    }
+
+    #[test]
+    fn test_random_is_deterministic_for_a_seed() {
+        let mut a = 42;
+        let mut b = 42;
+        let first: Vec<_> = (0..5).map(|_| random(&mut a)).collect();
+        let second: Vec<_> = (0..5).map(|_| random(&mut b)).collect();
+        assert_eq!(first, second);
+
+        let mut c = 43;
+        assert_ne!(random(&mut c), first[0]);
+
+        for value in first {
+            match value {
+                Value::Number(n) => assert!((0.0..1.0).contains(&n)),
+                other => panic!("expected number, got {:?}", other),
+            }
+        }
+    }
+
+    #[test]
+    fn test_random_between() {
+        let mut state = 7;
+        let mut seen = [false; 3];
+        for _ in 0..200 {
+            match random_between(&Value::Number(1.0), &Value::Number(3.0), &mut state).unwrap() {
+                Value::Number(n) => {
+                    assert!(n.fract() == 0.0 && (1.0..=3.0).contains(&n));
+                    seen[n as usize - 1] = true;
+                }
+                other => panic!("expected number, got {:?}", other),
+            }
+        }
+        assert!(seen.iter().all(|s| *s), "every bucket should be reachable");
+
+        match random_between(&Value::Number(0.5), &Value::Number(1.5), &mut state).unwrap() {
+            Value::Number(n) => assert!((0.5..1.5).contains(&n)),
+            other => panic!("expected number, got {:?}", other),
+        }
+
+        assert!(matches!(
+            random_between(&Value::Number(2.0), &Value::Number(1.0), &mut state),
+            Err(FunctionError::ArgumentError { .. })
+        ));
+        assert!(matches!(
+            random_between(&Value::Number(1.0), &Value::Nil, &mut state),
+            Err(FunctionError::TypeError { .. })
+        ));
+    }
+
+    #[test]
+    fn test_uuid() {
+        let mut a = 42;
+        let mut b = 42;
+        let first = uuid(&mut a);
+        assert_eq!(first, uuid(&mut b));
+        assert_ne!(first, uuid(&mut a));
+
+        let mut state = 0;
+        for _ in 0..100 {
+            let id = match uuid(&mut state) {
+                Value::String(s) => s,
+                other => panic!("expected string, got {:?}", other),
+            };
+            let groups: Vec<_> = id.split('-').map(str::len).collect();
+            assert_eq!(groups, [8, 4, 4, 4, 12], "{}", id);
+            assert!(id
+                .chars()
+                .all(|c| c == '-' || c.is_ascii_digit() || ('a'..='f').contains(&c)));
+            assert_eq!(&id[14..15], "4", "version nibble in {}", id);
+            assert!("89ab".contains(&id[19..20]), "variant nibble in {}", id);
+        }
+    }
+
+    fn excel_xirr_example() -> (Value, Value) {
+        let values = Value::Array(
+            [-10000.0, 2750.0, 4250.0, 3250.0, 2750.0]
+                .into_iter()
+                .map(Value::Number)
+                .collect::<Vec<_>>()
+                .into(),
+        );
+        let dates = Value::Array(
+            [
+                "2008-01-01",
+                "2008-03-01",
+                "2008-10-30",
+                "2009-02-15",
+                "2009-04-01",
+            ]
+            .into_iter()
+            .map(|d| Value::String(d.to_string().into()))
+            .collect::<Vec<_>>()
+            .into(),
+        );
+        (values, dates)
+    }
+
+    #[test]
+    fn test_xnpv() {
+        let (values, dates) = excel_xirr_example();
+        match xnpv(&Value::Number(0.09), &values, &dates).unwrap() {
+            Value::Number(n) => assert!((n - 2086.65).abs() < 0.01, "XNPV result: {}", n),
+            other => panic!("expected number, got {:?}", other),
+        }
+
+        // At a zero rate XNPV is the plain sum
+        assert_eq!(
+            xnpv(&Value::Number(0.0), &values, &dates).unwrap(),
+            Value::Number(3000.0)
+        );
+    }
+
+    #[test]
+    fn test_xnpv_invalid_inputs() {
+        let (values, dates) = excel_xirr_example();
+        let short = Value::Array(vec![Value::String("2008-01-01".into())].into());
+        assert!(matches!(
+            xnpv(&Value::Number(0.09), &values, &short),
+            Err(FunctionError::ArgumentError { .. })
+        ));
+
+        let out_of_order = Value::Array(
+            vec![
+                Value::String("2008-02-01".into()),
+                Value::String("2008-01-01".into()),
+            ]
+            .into(),
+        );
+        let pair = Value::Array(vec![Value::Number(-1.0), Value::Number(2.0)].into());
+        assert!(matches!(
+            xnpv(&Value::Number(0.09), &pair, &out_of_order),
+            Err(FunctionError::ArgumentError { .. })
+        ));
+
+        assert!(matches!(
+            xnpv(&Value::Number(-1.0), &values, &dates),
+            Err(FunctionError::ArgumentError { .. })
+        ));
+        let bad_date = Value::Array(
+            vec![
+                Value::String("2008-01-01".into()),
+                Value::String("soon".into()),
+            ]
+            .into(),
+        );
+        assert!(matches!(
+            xnpv(&Value::Number(0.09), &pair, &bad_date),
+            Err(FunctionError::ValueError { .. })
+        ));
+    }
+
+    #[test]
+    fn test_xirr() {
+        let (values, dates) = excel_xirr_example();
+        let rate = match xirr(&values, &dates, &Value::Number(0.1)).unwrap() {
+            Value::Number(n) => n,
+            other => panic!("expected number, got {:?}", other),
+        };
+        assert!((rate - 0.373362535).abs() < 1e-6, "XIRR result: {}", rate);
+
+        match xnpv(&Value::Number(rate), &values, &dates).unwrap() {
+            Value::Number(n) => assert!(n.abs() < 1e-6),
+            other => panic!("expected number, got {:?}", other),
+        }
+
+        // A poor guess still converges through the bisection fallback
+        match xirr(&values, &dates, &Value::Number(50.0)).unwrap() {
+            Value::Number(n) => assert!((n - 0.373362535).abs() < 1e-6),
+            other => panic!("expected number, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_xirr_requires_sign_change() {
+        let (_, dates) = excel_xirr_example();
+        let positive = Value::Array(vec![Value::Number(1.0); 5].into());
+        assert!(matches!(
+            xirr(&positive, &dates, &Value::Number(0.1)),
+            Err(FunctionError::ArgumentError { .. })
+        ));
+    }
+
+    fn schedule_field(entry: &Value, key: &str) -> f64 {
+        match entry {
+            Value::Dictionary(map) => match map.get(key) {
+                Some(Value::Number(n)) => *n,
+                other => panic!("expected number for {}, got {:?}", key, other),
+            },
+            other => panic!("expected dictionary, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_amortization_schedule() {
+        let rate = Value::Number(0.05 / 12.0);
+        let schedule =
+            match amortization_schedule(&rate, &Value::Number(12.0), &Value::Number(1000.0))
+                .unwrap()
+            {
+                Value::Array(entries) => entries,
+                other => panic!("expected array, got {:?}", other),
+            };
+        assert_eq!(schedule.len(), 12);
+
+        // Each row agrees with ipmt and ppmt for the same period
+        for (i, entry) in schedule.iter().enumerate() {
+            let per = Value::Number(i as f64 + 1.0);
+            let args = (
+                &Value::Number(12.0),
+                &Value::Number(1000.0),
+                &Value::Number(0.0),
+            );
+            let Value::Number(expected_interest) =
+                ipmt(&rate, &per, args.0, args.1, args.2).unwrap()
+            else {
+                unreachable!()
+            };
+            let Value::Number(expected_principal) =
+                ppmt(&rate, &per, args.0, args.1, args.2).unwrap()
+            else {
+                unreachable!()
+            };
+            assert!((schedule_field(entry, "interest") - expected_interest).abs() < 1e-6);
+            assert!((schedule_field(entry, "principal") - expected_principal).abs() < 1e-6);
+            assert!((schedule_field(entry, "payment") + 85.61).abs() < 0.01);
+        }
+
+        let first = &schedule[0];
+        assert_eq!(schedule_field(first, "period"), 1.0);
+        assert!((schedule_field(first, "interest") + 4.17).abs() < 0.01);
+        assert!((schedule_field(first, "balance") - 918.56).abs() < 0.01);
+        assert_eq!(schedule_field(&schedule[11], "balance"), 0.0);
+    }
+
+    #[test]
+    fn test_amortization_schedule_zero_rate() {
+        let schedule = amortization_schedule(
+            &Value::Number(0.0),
+            &Value::Number(4.0),
+            &Value::Number(1000.0),
+        )
+        .unwrap();
+        let Value::Array(entries) = schedule else {
+            panic!("expected array");
+        };
+        assert_eq!(schedule_field(&entries[1], "payment"), -250.0);
+        assert_eq!(schedule_field(&entries[1], "interest"), 0.0);
+        assert_eq!(schedule_field(&entries[1], "balance"), 500.0);
+    }
+
+    #[test]
+    fn test_amortization_schedule_invalid_inputs() {
+        for nper in [0.0, 2.5, 20_000.0] {
+            assert!(matches!(
+                amortization_schedule(
+                    &Value::Number(0.01),
+                    &Value::Number(nper),
+                    &Value::Number(1000.0)
+                ),
+                Err(FunctionError::ArgumentError { .. })
+            ));
+        }
+        assert!(matches!(
+            amortization_schedule(
+                &Value::Number(0.01),
+                &Value::Number(12.0),
+                &Value::String("1000".into())
+            ),
+            Err(FunctionError::TypeError { .. })
+        ));
+    }
+
+    #[test]
+    fn test_syd() {
+        let cases = [(1.0, 4090.91), (10.0, 409.09)];
+        for (per, expected) in cases {
+            match syd(
+                &Value::Number(30000.0),
+                &Value::Number(7500.0),
+                &Value::Number(10.0),
+                &Value::Number(per),
+            )
+            .unwrap()
+            {
+                Value::Number(n) => assert!((n - expected).abs() < 0.01, "SYD({}) = {}", per, n),
+                other => panic!("expected number, got {:?}", other),
+            }
+        }
+
+        // Every period together depreciates down to salvage
+        let total: f64 = (1..=5)
+            .map(|per| {
+                match syd(
+                    &Value::Number(1000.0),
+                    &Value::Number(100.0),
+                    &Value::Number(5.0),
+                    &Value::Number(per as f64),
+                )
+                .unwrap()
+                {
+                    Value::Number(n) => n,
+                    _ => unreachable!(),
+                }
+            })
+            .sum();
+        assert!((total - 900.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_syd_invalid_inputs() {
+        for (life, per) in [(0.0, 1.0), (10.0, 0.0), (10.0, 11.0)] {
+            assert!(matches!(
+                syd(
+                    &Value::Number(1000.0),
+                    &Value::Number(0.0),
+                    &Value::Number(life),
+                    &Value::Number(per)
+                ),
+                Err(FunctionError::ArgumentError { .. })
+            ));
+        }
+        assert!(matches!(
+            syd(
+                &Value::Number(1000.0),
+                &Value::Nil,
+                &Value::Number(10.0),
+                &Value::Number(1.0)
+            ),
+            Err(FunctionError::TypeError { .. })
+        ));
+    }
+
+    fn vdb_number(args: [f64; 6], no_switch: bool) -> f64 {
+        match vdb(
+            &Value::Number(args[0]),
+            &Value::Number(args[1]),
+            &Value::Number(args[2]),
+            &Value::Number(args[3]),
+            &Value::Number(args[4]),
+            &Value::Number(args[5]),
+            &Value::Boolean(no_switch),
+        )
+        .unwrap()
+        {
+            Value::Number(n) => n,
+            other => panic!("expected number, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_vdb_excel_examples() {
+        let cases = [
+            ([2400.0, 300.0, 3650.0, 0.0, 1.0, 2.0], 1.32),
+            ([2400.0, 300.0, 120.0, 0.0, 1.0, 2.0], 40.00),
+            ([2400.0, 300.0, 10.0, 0.0, 1.0, 2.0], 480.00),
+            ([2400.0, 300.0, 120.0, 6.0, 18.0, 2.0], 396.31),
+            ([2400.0, 300.0, 120.0, 6.0, 18.0, 1.5], 311.81),
+            ([2400.0, 300.0, 10.0, 0.0, 0.875, 1.5], 315.00),
+        ];
+        for (args, expected) in cases {
+            let n = vdb_number(args, false);
+            assert!((n - expected).abs() < 0.01, "VDB{:?} = {}", args, n);
+        }
+    }
+
+    #[test]
+    fn test_vdb_switch_to_straight_line() {
+        // Over the full life the asset depreciates to salvage when switching
+        let full = vdb_number([10000.0, 100.0, 5.0, 0.0, 5.0, 2.0], false);
+        assert!((full - 9900.0).abs() < 1e-6, "VDB full life = {}", full);
+
+        // Without switching, declining balance stops short of salvage:
+        // 10000 * (1 - 0.6^5)
+        let no_switch = vdb_number([10000.0, 100.0, 5.0, 0.0, 5.0, 2.0], true);
+        assert!(
+            (no_switch - 9222.4).abs() < 1e-6,
+            "VDB no_switch = {}",
+            no_switch
+        );
+
+        // A single whole period matches ddb when no switch has happened yet
+        let first = vdb_number([30000.0, 7500.0, 10.0, 0.0, 1.0, 2.0], true);
+        assert!((first - 6000.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_vdb_invalid_inputs() {
+        let invalid = [
+            [2400.0, 300.0, 10.0, 2.0, 1.0, 2.0],
+            [2400.0, 300.0, 10.0, 0.0, 11.0, 2.0],
+            [2400.0, 300.0, 0.0, 0.0, 1.0, 2.0],
+            [2400.0, 3000.0, 10.0, 0.0, 1.0, 2.0],
+            [2400.0, 300.0, 10.0, 0.0, 1.0, 0.0],
+            [1.0, 0.0, 1e15, 0.0, 1e15, 2.0],
+        ];
+        for args in invalid {
+            assert!(
+                matches!(
+                    vdb(
+                        &Value::Number(args[0]),
+                        &Value::Number(args[1]),
+                        &Value::Number(args[2]),
+                        &Value::Number(args[3]),
+                        &Value::Number(args[4]),
+                        &Value::Number(args[5]),
+                        &Value::Boolean(false),
+                    ),
+                    Err(FunctionError::ArgumentError { .. })
+                ),
+                "expected {:?} to be rejected",
+                args
+            );
+        }
+    }
+
+    fn number(value: Value) -> f64 {
+        match value {
+            Value::Number(n) => n,
+            other => panic!("expected number, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_apr_with_fees() {
+        let payment = number(
+            pmt(
+                &Value::Number(0.06 / 12.0),
+                &Value::Number(36.0),
+                &Value::Number(10000.0),
+                &Value::Number(0.0),
+            )
+            .unwrap(),
+        );
+        let args = [
+            Value::Number(36.0),
+            Value::Number(payment),
+            Value::Number(10000.0),
+        ];
+
+        // Without fees the APR is the contract rate
+        let no_fees = number(
+            apr(
+                &args[0],
+                &args[1],
+                &args[2],
+                &Value::Number(0.0),
+                &Value::Number(12.0),
+            )
+            .unwrap(),
+        );
+        assert!((no_fees - 0.06).abs() < 1e-4, "APR: {}", no_fees);
+
+        // Fees raise the rate on the amount actually received
+        let with_fees = number(
+            apr(
+                &args[0],
+                &args[1],
+                &args[2],
+                &Value::Number(300.0),
+                &Value::Number(12.0),
+            )
+            .unwrap(),
+        );
+        assert!((with_fees - 0.0806).abs() < 1e-3, "APR: {}", with_fees);
+        let check = number(
+            pmt(
+                &Value::Number(with_fees / 12.0),
+                &Value::Number(36.0),
+                &Value::Number(9700.0),
+                &Value::Number(0.0),
+            )
+            .unwrap(),
+        );
+        assert!((check - payment).abs() < 0.01);
+
+        let effective = number(
+            apr_effective(
+                &args[0],
+                &args[1],
+                &args[2],
+                &Value::Number(300.0),
+                &Value::Number(12.0),
+            )
+            .unwrap(),
+        );
+        assert!((effective - ((1.0 + with_fees / 12.0).powi(12) - 1.0)).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_apr_invalid_fees() {
+        for fees in [-1.0, 10000.0] {
+            assert!(matches!(
+                apr(
+                    &Value::Number(36.0),
+                    &Value::Number(-304.22),
+                    &Value::Number(10000.0),
+                    &Value::Number(fees),
+                    &Value::Number(12.0),
+                ),
+                Err(FunctionError::ArgumentError { .. })
+            ));
+        }
+        assert!(matches!(
+            apr(
+                &Value::Number(36.0),
+                &Value::Number(-304.22),
+                &Value::Number(10000.0),
+                &Value::Nil,
+                &Value::Number(12.0),
+            ),
+            Err(FunctionError::TypeError { .. })
+        ));
+    }
+
+    #[test]
+    fn test_rate_period_conversions() {
+        let annual =
+            number(periodic_to_annual(&Value::Number(0.01), &Value::Number(12.0)).unwrap());
+        assert!((annual - 0.126825).abs() < 1e-6, "annual: {}", annual);
+
+        let periodic =
+            number(annual_to_periodic(&Value::Number(annual), &Value::Number(12.0)).unwrap());
+        assert!((periodic - 0.01).abs() < 1e-12, "periodic: {}", periodic);
+
+        assert!(matches!(
+            periodic_to_annual(&Value::Number(0.01), &Value::Number(0.0)),
+            Err(FunctionError::ArgumentError { .. })
+        ));
+        assert!(matches!(
+            annual_to_periodic(&Value::Number(-1.5), &Value::Number(12.0)),
+            Err(FunctionError::ArgumentError { .. })
+        ));
+    }
+
+    fn date_value(s: &str) -> Value {
+        Value::String(s.to_string().into())
+    }
+
+    #[test]
+    fn test_bond_price() {
+        let result = price(
+            &date_value("2008-02-15"),
+            &date_value("2017-11-15"),
+            &Value::Number(0.0575),
+            &Value::Number(0.065),
+            &Value::Number(100.0),
+            &Value::Number(2.0),
+            &Value::Number(0.0),
+        )
+        .unwrap();
+        let p = number(result);
+        assert!((p - 94.634362).abs() < 1e-5, "PRICE result: {}", p);
+
+        // A bond priced at its own coupon rate on a coupon date trades at par
+        let par = number(
+            price(
+                &date_value("2020-01-15"),
+                &date_value("2030-01-15"),
+                &Value::Number(0.05),
+                &Value::Number(0.05),
+                &Value::Number(100.0),
+                &Value::Number(2.0),
+                &Value::Number(1.0),
+            )
+            .unwrap(),
+        );
+        assert!((par - 100.0).abs() < 1e-9, "par price: {}", par);
+    }
+
+    #[test]
+    fn test_bond_yield() {
+        let result = yield_fn(
+            &date_value("2008-02-15"),
+            &date_value("2016-11-15"),
+            &Value::Number(0.0575),
+            &Value::Number(95.04287),
+            &Value::Number(100.0),
+            &Value::Number(2.0),
+            &Value::Number(0.0),
+        )
+        .unwrap();
+        let y = number(result);
+        assert!((y - 0.065).abs() < 1e-6, "YIELD result: {}", y);
+
+        // With one coupon left the closed form inverts price exactly
+        let args = |pr: f64| {
+            yield_fn(
+                &date_value("2024-03-01"),
+                &date_value("2024-06-30"),
+                &Value::Number(0.04),
+                &Value::Number(pr),
+                &Value::Number(100.0),
+                &Value::Number(2.0),
+                &Value::Number(1.0),
+            )
+        };
+        let short_yield = number(args(99.5).unwrap());
+        let repriced = number(
+            price(
+                &date_value("2024-03-01"),
+                &date_value("2024-06-30"),
+                &Value::Number(0.04),
+                &Value::Number(short_yield),
+                &Value::Number(100.0),
+                &Value::Number(2.0),
+                &Value::Number(1.0),
+            )
+            .unwrap(),
+        );
+        assert!((repriced - 99.5).abs() < 0.01, "repriced: {}", repriced);
+    }
+
+    #[test]
+    fn test_bond_duration() {
+        let args = [
+            date_value("2018-07-01"),
+            date_value("2048-01-01"),
+            Value::Number(0.08),
+            Value::Number(0.09),
+            Value::Number(2.0),
+            Value::Number(1.0),
+        ];
+        let d =
+            number(duration(&args[0], &args[1], &args[2], &args[3], &args[4], &args[5]).unwrap());
+        assert!((d - 10.9191453).abs() < 1e-6, "DURATION result: {}", d);
+
+        let m =
+            number(mduration(&args[0], &args[1], &args[2], &args[3], &args[4], &args[5]).unwrap());
+        assert!(
+            (m - 10.9191453 / 1.045).abs() < 1e-6,
+            "MDURATION result: {}",
+            m
+        );
+    }
+
+    #[test]
+    fn test_bond_invalid_inputs() {
+        let call = |settlement: &str, frequency: f64, basis: f64| {
+            price(
+                &date_value(settlement),
+                &date_value("2017-11-15"),
+                &Value::Number(0.0575),
+                &Value::Number(0.065),
+                &Value::Number(100.0),
+                &Value::Number(frequency),
+                &Value::Number(basis),
+            )
+        };
+        assert!(matches!(
+            call("2018-01-01", 2.0, 0.0),
+            Err(FunctionError::ArgumentError { .. })
+        ));
+        assert!(matches!(
+            call("2008-02-15", 3.0, 0.0),
+            Err(FunctionError::ArgumentError { .. })
+        ));
+        assert!(matches!(
+            call("2008-02-15", 2.0, 5.0),
+            Err(FunctionError::ArgumentError { .. })
+        ));
+        assert!(matches!(
+            call("02/15/2008", 2.0, 0.0),
+            Err(FunctionError::ValueError { .. })
+        ));
+    }
+
+    #[test]
+    fn test_fvschedule() {
+        let schedule =
+            Value::Array(vec![Value::Number(0.09), Value::Number(0.11), Value::Number(0.1)].into());
+        let result = number(fvschedule(&Value::Number(1.0), &schedule).unwrap());
+        assert!(
+            (result - 1.33089).abs() < 1e-9,
+            "FVSCHEDULE result: {}",
+            result
+        );
+
+        // A constant schedule matches fv with a single rate
+        let constant = Value::Array(vec![Value::Number(0.05); 10].into());
+        let scheduled = number(fvschedule(&Value::Number(1000.0), &constant).unwrap());
+        let single_rate = number(
+            fv(
+                &Value::Number(0.05),
+                &Value::Number(10.0),
+                &Value::Number(0.0),
+                &Value::Number(-1000.0),
+            )
+            .unwrap(),
+        );
+        assert!((scheduled - single_rate).abs() < 1e-9);
+
+        assert_eq!(
+            fvschedule(&Value::Number(500.0), &Value::Array(vec![].into())).unwrap(),
+            Value::Number(500.0)
+        );
+    }
+
+    #[test]
+    fn test_fvschedule_invalid_inputs() {
+        assert!(matches!(
+            fvschedule(&Value::Number(1.0), &Value::Number(0.1)),
+            Err(FunctionError::TypeError { .. })
+        ));
+        assert!(matches!(
+            fvschedule(
+                &Value::Number(1.0),
+                &Value::Array(vec![Value::Number(0.1), Value::String("x".into())].into())
+            ),
+            Err(FunctionError::TypeError { .. })
+        ));
+        assert!(matches!(
+            fvschedule(&Value::Nil, &Value::Array(vec![].into())),
+            Err(FunctionError::TypeError { .. })
+        ));
+    }
+
+    fn tier_table() -> Value {
+        let tier = |threshold: f64, rate: f64| {
+            Value::Array(vec![Value::Number(threshold), Value::Number(rate)].into())
+        };
+        Value::Array(vec![tier(0.0, 0.1), tier(10000.0, 0.2), tier(40000.0, 0.4)].into())
+    }
+
+    #[test]
+    fn test_tier_lookup() {
+        let lookup = |v: f64| tier_lookup(&Value::Number(v), &tier_table()).unwrap();
+        assert_eq!(lookup(0.0), Value::Number(0.1));
+        assert_eq!(lookup(9999.99), Value::Number(0.1));
+        assert_eq!(lookup(10000.0), Value::Number(0.2));
+        assert_eq!(lookup(25000.0), Value::Number(0.2));
+        assert_eq!(lookup(1_000_000.0), Value::Number(0.4));
+        assert_eq!(lookup(-5.0), Value::Nil);
+    }
+
+    #[test]
+    fn test_tier_marginal() {
+        let marginal = |v: f64| number(tier_marginal(&Value::Number(v), &tier_table()).unwrap());
+        assert_eq!(marginal(0.0), 0.0);
+        assert!((marginal(5000.0) - 500.0).abs() < 1e-9);
+        assert!((marginal(25000.0) - 4000.0).abs() < 1e-9);
+        // 1000 + 6000 + 0.4 * 10000
+        assert!((marginal(50000.0) - 11000.0).abs() < 1e-9);
+        assert_eq!(marginal(-100.0), 0.0);
+    }
+
+    #[test]
+    fn test_tier_invalid_tables() {
+        let pair = |a: f64, b: f64| Value::Array(vec![Value::Number(a), Value::Number(b)].into());
+        assert!(matches!(
+            tier_lookup(&Value::Number(1.0), &Value::Array(vec![].into())),
+            Err(FunctionError::ArgumentError { .. })
+        ));
+        assert!(matches!(
+            tier_lookup(
+                &Value::Number(1.0),
+                &Value::Array(vec![pair(100.0, 0.1), pair(50.0, 0.2)].into())
+            ),
+            Err(FunctionError::ArgumentError { .. })
+        ));
+        assert!(matches!(
+            tier_marginal(
+                &Value::Number(1.0),
+                &Value::Array(vec![Value::Array(vec![Value::Number(0.0)].into())].into())
+            ),
+            Err(FunctionError::ArgumentError { .. })
+        ));
+        assert!(matches!(
+            tier_marginal(
+                &Value::Number(1.0),
+                &Value::Array(vec![Value::Number(0.0)].into())
+            ),
+            Err(FunctionError::TypeError { .. })
+        ));
+        assert!(matches!(
+            tier_lookup(&Value::String("1".into()), &tier_table()),
+            Err(FunctionError::TypeError { .. })
+        ));
+    }
+
+    #[test]
+    fn test_prorate() {
+        let call = |start: &str, end: &str, period_days: f64| {
+            number(
+                prorate(
+                    &Value::Number(1200.0),
+                    &date_value(start),
+                    &date_value(end),
+                    &Value::Number(period_days),
+                )
+                .unwrap(),
+            )
+        };
+        assert!((call("2024-01-01", "2024-04-01", 365.0) - 1200.0 * 91.0 / 365.0).abs() < 1e-9);
+        assert!((call("2024-01-01", "2024-04-01", 360.0) - 303.3333333).abs() < 1e-6);
+        assert!((call("2023-01-01", "2024-01-01", 365.0) - 1200.0).abs() < 1e-9);
+        assert_eq!(call("2024-06-15", "2024-06-15", 365.0), 0.0);
+    }
+
+    #[test]
+    fn test_prorate_invalid_inputs() {
+        let call = |start: &str, end: &str, period_days: Value| {
+            prorate(
+                &Value::Number(1200.0),
+                &date_value(start),
+                &date_value(end),
+                &period_days,
+            )
+        };
+        assert!(matches!(
+            call("2024-04-01", "2024-01-01", Value::Number(365.0)),
+            Err(FunctionError::ArgumentError { .. })
+        ));
+        assert!(matches!(
+            call("2024-01-01", "2024-04-01", Value::Number(0.0)),
+            Err(FunctionError::ArgumentError { .. })
+        ));
+        assert!(matches!(
+            call("2024-01-01", "2024-04-01", Value::String("365".into())),
+            Err(FunctionError::TypeError { .. })
+        ));
+        assert!(matches!(
+            call("2024-01-01", "2024-13-01", Value::Number(365.0)),
+            Err(FunctionError::ValueError { .. })
+        ));
+    }
+
+    #[test]
+    fn test_cagr() {
+        let rate = number(
+            cagr(
+                &Value::Number(10000.0),
+                &Value::Number(19500.0),
+                &Value::Number(3.0),
+            )
+            .unwrap(),
+        );
+        assert!((rate - 0.249_333).abs() < 1e-6, "CAGR result: {}", rate);
+
+        // Growing back at the CAGR reproduces the end value
+        let grown = number(
+            compound(
+                &Value::Number(10000.0),
+                &Value::Number(rate),
+                &Value::Number(3.0),
+            )
+            .unwrap(),
+        );
+        assert!((grown - 19500.0).abs() < 1e-6);
+
+        let decline = number(
+            cagr(
+                &Value::Number(200.0),
+                &Value::Number(50.0),
+                &Value::Number(2.0),
+            )
+            .unwrap(),
+        );
+        assert!((decline + 0.5).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_cagr_invalid_inputs() {
+        assert!(matches!(
+            cagr(
+                &Value::Number(0.0),
+                &Value::Number(100.0),
+                &Value::Number(2.0)
+            ),
+            Err(FunctionError::ArgumentError { .. })
+        ));
+        assert!(matches!(
+            cagr(
+                &Value::Number(100.0),
+                &Value::Number(200.0),
+                &Value::Number(0.0)
+            ),
+            Err(FunctionError::ArgumentError { .. })
+        ));
+        assert!(matches!(
+            cagr(
+                &Value::Number(-100.0),
+                &Value::Number(200.0),
+                &Value::Number(2.0)
+            ),
+            Err(FunctionError::ArgumentError { .. })
+        ));
+        assert!(matches!(
+            cagr(&Value::Number(100.0), &Value::Nil, &Value::Number(2.0)),
+            Err(FunctionError::TypeError { .. })
+        ));
+    }
+
+    #[test]
+    fn test_compound() {
+        let result = number(
+            compound(
+                &Value::Number(1000.0),
+                &Value::Number(0.05),
+                &Value::Number(10.0),
+            )
+            .unwrap(),
+        );
+        assert!(
+            (result - 1628.894627).abs() < 1e-6,
+            "compound result: {}",
+            result
+        );
+        assert_eq!(
+            compound(
+                &Value::Number(1000.0),
+                &Value::Number(0.05),
+                &Value::Number(0.0)
+            )
+            .unwrap(),
+            Value::Number(1000.0)
+        );
+        assert!(matches!(
+            compound(
+                &Value::Number(1000.0),
+                &Value::Number(-1.0),
+                &Value::Number(2.0)
+            ),
+            Err(FunctionError::ArgumentError { .. })
+        ));
+        assert!(matches!(
+            compound(
+                &Value::Number(1000.0),
+                &Value::Number(0.05),
+                &Value::Boolean(true)
+            ),
+            Err(FunctionError::TypeError { .. })
+        ));
+    }
+
+    #[test]
+    fn test_bucket() {
+        let thresholds = Value::Array(
+            [18.0, 25.0, 40.0, 65.0]
+                .into_iter()
+                .map(Value::Number)
+                .collect::<Vec<_>>()
+                .into(),
+        );
+        let labels = Value::Array(
+            ["youth", "young", "adult", "senior", "elder"]
+                .into_iter()
+                .map(|s| Value::Symbol(s.to_string()))
+                .collect::<Vec<_>>()
+                .into(),
+        );
+        let band = |age: f64| bucket(&Value::Number(age), &thresholds, &labels).unwrap();
+        assert_eq!(band(16.0), Value::Symbol("youth".to_string()));
+        assert_eq!(band(18.0), Value::Symbol("young".to_string()));
+        assert_eq!(band(39.9), Value::Symbol("adult".to_string()));
+        assert_eq!(band(65.0), Value::Symbol("elder".to_string()));
+        assert_eq!(band(64.0), Value::Symbol("senior".to_string()));
+    }
+
+    #[test]
+    fn test_bucket_invalid_inputs() {
+        let numbers = |ns: &[f64]| {
+            Value::Array(
+                ns.iter()
+                    .copied()
+                    .map(Value::Number)
+                    .collect::<Vec<_>>()
+                    .into(),
+            )
+        };
+        assert!(matches!(
+            bucket(
+                &Value::Number(1.0),
+                &numbers(&[1.0, 2.0]),
+                &numbers(&[0.0, 1.0])
+            ),
+            Err(FunctionError::ArgumentError { .. })
+        ));
+        assert!(matches!(
+            bucket(
+                &Value::Number(1.0),
+                &numbers(&[2.0, 1.0]),
+                &numbers(&[0.0, 1.0, 2.0])
+            ),
+            Err(FunctionError::ArgumentError { .. })
+        ));
+        assert!(matches!(
+            bucket(
+                &Value::Number(1.0),
+                &Value::Array(vec![Value::String("18".into())].into()),
+                &numbers(&[0.0, 1.0])
+            ),
+            Err(FunctionError::TypeError { .. })
+        ));
+        assert!(matches!(
+            bucket(&Value::Nil, &numbers(&[1.0]), &numbers(&[0.0, 1.0])),
+            Err(FunctionError::TypeError { .. })
+        ));
+        assert_eq!(
+            bucket(&Value::Number(5.0), &numbers(&[]), &numbers(&[7.0])).unwrap(),
+            Value::Number(7.0)
+        );
+    }
+
+    #[test]
+    fn test_interpolate() {
+        let numbers = |ns: &[f64]| {
+            Value::Array(
+                ns.iter()
+                    .copied()
+                    .map(Value::Number)
+                    .collect::<Vec<_>>()
+                    .into(),
+            )
+        };
+        let xs = numbers(&[20.0, 40.0, 60.0]);
+        let ys = numbers(&[1.5, 1.0, 1.2]);
+        let at = |x: f64| number(interpolate(&Value::Number(x), &xs, &ys).unwrap());
+
+        assert!((at(30.0) - 1.25).abs() < 1e-12);
+        assert!((at(50.0) - 1.1).abs() < 1e-12);
+        assert_eq!(at(40.0), 1.0);
+        assert_eq!(at(20.0), 1.5);
+        // Held flat outside the table
+        assert_eq!(at(5.0), 1.5);
+        assert_eq!(at(99.0), 1.2);
+
+        let single = interpolate(&Value::Number(3.0), &numbers(&[1.0]), &numbers(&[9.0])).unwrap();
+        assert_eq!(single, Value::Number(9.0));
+    }
+
+    #[test]
+    fn test_interpolate_invalid_inputs() {
+        let numbers = |ns: &[f64]| {
+            Value::Array(
+                ns.iter()
+                    .copied()
+                    .map(Value::Number)
+                    .collect::<Vec<_>>()
+                    .into(),
+            )
+        };
+        let x = Value::Number(1.0);
+        assert!(matches!(
+            interpolate(&x, &numbers(&[]), &numbers(&[])),
+            Err(FunctionError::ArgumentError { .. })
+        ));
+        assert!(matches!(
+            interpolate(&x, &numbers(&[1.0, 2.0]), &numbers(&[1.0])),
+            Err(FunctionError::ArgumentError { .. })
+        ));
+        assert!(matches!(
+            interpolate(&x, &numbers(&[2.0, 2.0]), &numbers(&[1.0, 3.0])),
+            Err(FunctionError::ArgumentError { .. })
+        ));
+        assert!(matches!(
+            interpolate(&x, &numbers(&[1.0]), &Value::Array(vec![Value::Nil].into())),
+            Err(FunctionError::TypeError { .. })
+        ));
+        assert!(matches!(
+            interpolate(&Value::Nil, &numbers(&[1.0]), &numbers(&[1.0])),
+            Err(FunctionError::TypeError { .. })
+        ));
+    }
+
+    #[test]
+    fn test_safe_divide() {
+        assert_eq!(
+            safe_divide(&Value::Number(10.0), &Value::Number(4.0), &Value::Nil).unwrap(),
+            Value::Number(2.5)
+        );
+        assert_eq!(
+            safe_divide(
+                &Value::Number(10.0),
+                &Value::Number(0.0),
+                &Value::Number(0.0)
+            )
+            .unwrap(),
+            Value::Number(0.0)
+        );
+        assert_eq!(
+            safe_divide(&Value::Number(10.0), &Value::Number(-0.0), &Value::Nil).unwrap(),
+            Value::Nil
+        );
+        assert!(matches!(
+            safe_divide(&Value::Number(10.0), &Value::Nil, &Value::Number(0.0)),
+            Err(FunctionError::TypeError { .. })
+        ));
+        assert!(matches!(
+            safe_divide(
+                &Value::String("10".into()),
+                &Value::Number(2.0),
+                &Value::Nil
+            ),
+            Err(FunctionError::TypeError { .. })
+        ));
+    }
+
+    fn decimal(s: &str) -> Value {
+        Value::Decimal(s.parse().unwrap())
+    }
+
+    #[test]
+    fn test_decimal_functions() {
+        assert_eq!(abs(&decimal("-1.25")).unwrap(), decimal("1.25"));
+        assert_eq!(floor(&decimal("-1.25")).unwrap(), decimal("-2"));
+        assert_eq!(ceil(&decimal("1.25")).unwrap(), decimal("2"));
+        // Halves round away from zero, as for floats
+        assert_eq!(
+            round(&decimal("2.345"), &decimal("2")).unwrap(),
+            decimal("2.35")
+        );
+        assert_eq!(
+            round(&decimal("-2.5"), &Value::Number(0.0)).unwrap(),
+            decimal("-3")
+        );
+        assert_eq!(
+            round(&decimal("1250"), &Value::Number(-2.0)).unwrap(),
+            decimal("1300")
+        );
+
+        let payment = pmt(
+            &decimal("0.00375"),
+            &decimal("360"),
+            &decimal("250000"),
+            &decimal("0"),
+        )
+        .unwrap();
+        assert!(matches!(payment, Value::Decimal(_)));
+        assert_eq!(round(&payment, &decimal("2")).unwrap(), decimal("-1266.71"));
+        // Zero rates are exact
+        assert_eq!(
+            fv(
+                &decimal("0"),
+                &decimal("12"),
+                &decimal("-100.10"),
+                &decimal("0")
+            )
+            .unwrap(),
+            decimal("1201.20")
+        );
+        assert_eq!(
+            pv(&decimal("0"), &decimal("3"), &decimal("-33.33")).unwrap(),
+            decimal("99.99")
+        );
+        let npv_value = npv(
+            &decimal("0.1"),
+            &Value::Array(vec![decimal("110"), decimal("121")].into()),
+        )
+        .unwrap();
+        assert_eq!(npv_value, decimal("200"));
+        assert!(matches!(
+            pmt(
+                &decimal("0.01"),
+                &decimal("0"),
+                &decimal("100"),
+                &decimal("0")
+            ),
+            Err(FunctionError::ArgumentError { .. })
+        ));
+    }
+
+    #[test]
+    fn test_decimal_schedules_and_tables() {
+        let rates = Value::Array(vec![decimal("0.09"), decimal("0.11"), decimal("0.1")].into());
+        assert_eq!(
+            fvschedule(&decimal("1"), &rates).unwrap(),
+            decimal("1.330890")
+        );
+        assert_eq!(
+            compound(&decimal("1000"), &decimal("0.05"), &decimal("2")).unwrap(),
+            decimal("1102.5")
+        );
+        assert_eq!(
+            sln(&decimal("30000"), &decimal("7500"), &decimal("10")).unwrap(),
+            decimal("2250")
+        );
+        assert_eq!(
+            syd(
+                &decimal("30000"),
+                &decimal("7500"),
+                &decimal("10"),
+                &decimal("10")
+            )
+            .unwrap(),
+            decimal("409.09090909090909090909090909")
+        );
+
+        let table = Value::Array(
+            vec![
+                Value::Array(vec![decimal("0"), decimal("0.1")].into()),
+                Value::Array(vec![decimal("10000"), decimal("0.2")].into()),
+            ]
+            .into(),
+        );
+        assert_eq!(
+            tier_lookup(&decimal("25000"), &table).unwrap(),
+            decimal("0.2")
+        );
+        assert_eq!(
+            tier_marginal(&decimal("25000"), &table).unwrap(),
+            decimal("4000")
+        );
+        assert_eq!(
+            prorate(
+                &decimal("1200"),
+                &Value::String("2024-01-01".into()),
+                &Value::String("2024-01-31".into()),
+                &decimal("360")
+            )
+            .unwrap(),
+            decimal("100")
+        );
+
+        // The balance reaches exactly zero, with every entry a decimal
+        let Value::Array(schedule) =
+            amortization_schedule(&decimal("0.01"), &decimal("3"), &decimal("300")).unwrap()
+        else {
+            panic!("Expected an array");
+        };
+        assert_eq!(schedule.len(), 3);
+        let Value::Dictionary(last) = &schedule[2] else {
+            panic!("Expected a dictionary");
+        };
+        assert_eq!(last.get("balance"), Some(&decimal("0")));
+        assert_eq!(last.get("period"), Some(&decimal("3")));
+        assert!(last
+            .values()
+            .all(|value| matches!(value, Value::Decimal(_))));
+    }
 }
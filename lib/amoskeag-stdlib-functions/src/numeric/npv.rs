@@ -1,6 +1,7 @@
 //! npv function
 
-use crate::{FunctionError, Value};
+use super::decimal::{decimal_args, overflow};
+use crate::{Decimal, FunctionError, Value};
 
 /// Calculate net present value of a series of cash flows
 /// npv(rate: Number, values: Array) -> Number
@@ -10,8 +11,13 @@ use crate::{FunctionError, Value};
 ///
 /// Note: The first value is at period 1, not period 0. Add initial investment separately.
 ///
+/// A decimal rate computes exactly, to 28 significant digits.
+///
 /// Example: npv(0.1, [3000, 4200, 6800]) = net present value at 10% discount rate
 pub fn npv(rate: &Value, values: &Value) -> Result<Value, FunctionError> {
+    if let (Some([r]), Value::Array(arr)) = (decimal_args([rate]), values) {
+        return npv_decimal(r, arr);
+    }
     match (rate, values) {
         (Value::Number(r), Value::Array(arr)) => {
             if arr.is_empty() {
@@ -48,3 +54,29 @@ pub fn npv(rate: &Value, values: &Value) -> Result<Value, FunctionError> {
         }),
     }
 }
+
+fn npv_decimal(r: Decimal, arr: &[Value]) -> Result<Value, FunctionError> {
+    if arr.is_empty() {
+        return Err(FunctionError::ArgumentError {
+            message: "values array cannot be empty".to_string(),
+        });
+    }
+
+    let growth = Decimal::ONE.checked_add(r).ok_or_else(|| overflow("npv"))?;
+    let mut npv = Decimal::ZERO;
+    let mut discount = Decimal::ONE;
+    for value in arr {
+        let v = value.as_decimal().ok_or_else(|| FunctionError::TypeError {
+            expected: "Array of Numbers".to_string(),
+            got: format!("Array containing {}", value.type_name()),
+        })?;
+        discount = discount
+            .checked_mul(growth)
+            .ok_or_else(|| overflow("npv"))?;
+        npv = v
+            .checked_div(discount)
+            .and_then(|term| npv.checked_add(term))
+            .ok_or_else(|| overflow("npv"))?;
+    }
+    Ok(Value::Decimal(npv))
+}
@@ -0,0 +1,84 @@
+//! number_format function
+
+use super::round::round_half_away;
+use crate::{FunctionError, Value};
+
+/// Format a number with grouped thousands and fixed decimal places
+/// number_format(num: Number, decimals: Number, thousands_sep: String, decimal_sep: String) -> String
+///
+/// Halves round away from zero, as in `round`. `decimals` must be a whole
+/// number from 0 to 20.
+pub fn number_format(
+    value: &Value,
+    decimals: &Value,
+    thousands_sep: &Value,
+    decimal_sep: &Value,
+) -> Result<Value, FunctionError> {
+    let n = match value {
+        Value::Number(n) if n.is_finite() => *n,
+        Value::Number(n) => {
+            return Err(FunctionError::ValueError {
+                message: format!("Cannot format non-finite number: {}", n),
+            })
+        }
+        _ => {
+            return Err(FunctionError::TypeError {
+                expected: "Number".to_string(),
+                got: value.type_name().to_string(),
+            })
+        }
+    };
+    let places = match decimals {
+        Value::Number(d) if d.fract() == 0.0 && (0.0..=20.0).contains(d) => *d as usize,
+        Value::Number(d) => {
+            return Err(FunctionError::ArgumentError {
+                message: format!("decimals must be a whole number from 0 to 20, got {}", d),
+            })
+        }
+        _ => {
+            return Err(FunctionError::TypeError {
+                expected: "Number".to_string(),
+                got: decimals.type_name().to_string(),
+            })
+        }
+    };
+    let (thousands_sep, decimal_sep) = match (thousands_sep, decimal_sep) {
+        (Value::String(t), Value::String(d)) => (t, d),
+        (Value::String(_), _) => {
+            return Err(FunctionError::TypeError {
+                expected: "String".to_string(),
+                got: decimal_sep.type_name().to_string(),
+            })
+        }
+        _ => {
+            return Err(FunctionError::TypeError {
+                expected: "String".to_string(),
+                got: thousands_sep.type_name().to_string(),
+            })
+        }
+    };
+
+    let formatted = format!("{:.*}", places, round_half_away(n, places as i32).abs());
+    let (integer, fraction) = match formatted.split_once('.') {
+        Some((i, f)) => (i, Some(f)),
+        None => (formatted.as_str(), None),
+    };
+
+    let mut result = String::new();
+    // Rounding can turn a tiny negative number into zero; don't print "-0"
+    if n < 0.0 && formatted.chars().any(|c| c.is_ascii_digit() && c != '0') {
+        result.push('-');
+    }
+    for (i, digit) in integer.chars().enumerate() {
+        if i > 0 && (integer.len() - i) % 3 == 0 {
+            result.push_str(thousands_sep);
+        }
+        result.push(digit);
+    }
+    if let Some(fraction) = fraction {
+        result.push_str(decimal_sep);
+        result.push_str(fraction);
+    }
+
+    Ok(Value::String(result.into()))
+}
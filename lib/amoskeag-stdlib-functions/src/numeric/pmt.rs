@@ -1,6 +1,8 @@
 //! pmt function
 
-use crate::{FunctionError, Value};
+use super::decimal::{decimal_args, overflow};
+use crate::{Decimal, FunctionError, Value};
+use rust_decimal::MathematicalOps;
 
 /// Calculate the payment for a loan based on constant payments and a constant interest rate
 /// pmt(rate: Number, nper: Number, pv: Number, type_: Number) -> Number
@@ -11,8 +13,13 @@ use crate::{FunctionError, Value};
 /// For type 1, PMT = PMT_type0 / (1 + r)
 /// where r = periodic interest rate, n = number of periods, PV = present value
 ///
+/// Decimal arguments are computed exactly, to 28 significant digits.
+///
 /// Example: pmt(0.00375, 360, 250000, 0) = -1266.71 (monthly payment on $250k loan at 4.5% APR for 30 years)
 pub fn pmt(rate: &Value, nper: &Value, pv: &Value, type_: &Value) -> Result<Value, FunctionError> {
+    if let Some([r, n, p, t]) = decimal_args([rate, nper, pv, type_]) {
+        return pmt_decimal(r, n, p, t);
+    }
     match (rate, nper, pv, type_) {
         (Value::Number(r), Value::Number(n), Value::Number(p), Value::Number(t)) => {
             if *n <= 0.0 {
@@ -70,3 +77,33 @@ pub fn pmt(rate: &Value, nper: &Value, pv: &Value, type_: &Value) -> Result<Valu
         }),
     }
 }
+
+fn pmt_decimal(r: Decimal, n: Decimal, p: Decimal, t: Decimal) -> Result<Value, FunctionError> {
+    if n <= Decimal::ZERO {
+        return Err(FunctionError::ArgumentError {
+            message: "nper must be greater than 0".to_string(),
+        });
+    }
+    if t != Decimal::ZERO && t != Decimal::ONE {
+        return Err(FunctionError::ArgumentError {
+            message: "type must be 0 or 1".to_string(),
+        });
+    }
+
+    let payment = || {
+        let growth = Decimal::ONE.checked_add(r)?;
+        let payment = if r.is_zero() {
+            (-p).checked_div(n)?
+        } else {
+            let factor = growth.checked_powd(n)?;
+            (-p).checked_mul(r.checked_mul(factor)?)?
+                .checked_div(factor.checked_sub(Decimal::ONE)?)?
+        };
+        if t == Decimal::ONE {
+            payment.checked_div(growth)
+        } else {
+            Some(payment)
+        }
+    };
+    payment().map(Value::Decimal).ok_or_else(|| overflow("pmt"))
+}
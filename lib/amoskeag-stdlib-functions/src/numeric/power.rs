@@ -6,7 +6,29 @@ use crate::{FunctionError, Value};
 /// power(base: Number, exponent: Number) -> Number
 pub fn power(base: &Value, exponent: &Value) -> Result<Value, FunctionError> {
     match (base, exponent) {
-        (Value::Number(b), Value::Number(e)) => Ok(Value::Number(b.powf(*e))),
+        (Value::Number(b), Value::Number(e)) => {
+            let result = b.powf(*e);
+            if result.is_nan() && !b.is_nan() && !e.is_nan() {
+                Err(FunctionError::ValueError {
+                    message: format!(
+                        "Cannot raise negative number {} to fractional power {}",
+                        b, e
+                    ),
+                })
+            } else if result.is_infinite() && b.is_finite() && e.is_finite() {
+                if *b == 0.0 {
+                    Err(FunctionError::ValueError {
+                        message: format!("Cannot raise zero to negative power {}", e),
+                    })
+                } else {
+                    Err(FunctionError::ValueError {
+                        message: format!("{} raised to {} overflows", b, e),
+                    })
+                }
+            } else {
+                Ok(Value::Number(result))
+            }
+        }
         (Value::Number(_), _) => Err(FunctionError::TypeError {
             expected: "Number".to_string(),
             got: exponent.type_name().to_string(),
@@ -0,0 +1,68 @@
+//! prorate function
+
+use super::decimal::{decimal_args, overflow};
+use crate::date::CivilDate;
+use crate::{Decimal, FunctionError, Value};
+
+/// Calculate the pro-rata share of an amount earned between two dates
+/// prorate(amount: Number, start_date: String, end_date: String, period_days: Number) -> Number
+///
+/// Formula: amount * (end_date - start_date) / period_days
+///
+/// Elapsed time is counted in actual days, so `period_days` selects the
+/// convention: 365 for actual/365 or 360 for actual/360.
+///
+/// When `amount` or `period_days` is a decimal the share is computed exactly,
+/// to 28 significant digits.
+///
+/// Example: prorate(1200, '2024-01-01', '2024-04-01', 365) = 299.18
+pub fn prorate(
+    amount: &Value,
+    start_date: &Value,
+    end_date: &Value,
+    period_days: &Value,
+) -> Result<Value, FunctionError> {
+    if !matches!(amount, Value::Number(_) | Value::Decimal(_)) {
+        return Err(FunctionError::TypeError {
+            expected: "Number".to_string(),
+            got: amount.type_name().to_string(),
+        });
+    }
+    let start = CivilDate::from_value(start_date)?;
+    let end = CivilDate::from_value(end_date)?;
+    let positive = match period_days {
+        Value::Number(p) => *p > 0.0,
+        Value::Decimal(p) => *p > Decimal::ZERO,
+        _ => {
+            return Err(FunctionError::TypeError {
+                expected: "Number".to_string(),
+                got: period_days.type_name().to_string(),
+            })
+        }
+    };
+    if !positive {
+        return Err(FunctionError::ArgumentError {
+            message: "period_days must be greater than 0".to_string(),
+        });
+    }
+    if end < start {
+        return Err(FunctionError::ArgumentError {
+            message: format!(
+                "prorate requires end_date on or after start_date, got {} before {}",
+                end, start
+            ),
+        });
+    }
+
+    let elapsed = end.days_since_epoch() - start.days_since_epoch();
+    if let Some([a, p]) = decimal_args([amount, period_days]) {
+        return a
+            .checked_mul(Decimal::from(elapsed))
+            .and_then(|share| share.checked_div(p))
+            .map(Value::Decimal)
+            .ok_or_else(|| overflow("prorate"));
+    }
+    let amount = amount.as_f64().unwrap_or(f64::NAN);
+    let period_days = period_days.as_f64().unwrap_or(f64::NAN);
+    Ok(Value::Number(amount * elapsed as f64 / period_days))
+}
@@ -1,6 +1,8 @@
 //! pv function
 
-use crate::{FunctionError, Value};
+use super::decimal::{decimal_args, overflow};
+use crate::{Decimal, FunctionError, Value};
+use rust_decimal::MathematicalOps;
 
 /// Calculate the present value of an investment
 /// pv(rate: Number, nper: Number, pmt: Number) -> Number
@@ -8,8 +10,13 @@ use crate::{FunctionError, Value};
 /// Formula: PV = PMT * ((1 - (1 + r)^-n) / r)
 /// where r = periodic interest rate, n = number of periods, PMT = payment per period
 ///
+/// Decimal arguments are computed exactly, to 28 significant digits.
+///
 /// Example: pv(0.08/12, 20*12, -1000) = present value of $1000/month for 20 years at 8% annual rate
 pub fn pv(rate: &Value, nper: &Value, pmt: &Value) -> Result<Value, FunctionError> {
+    if let Some([r, n, p]) = decimal_args([rate, nper, pmt]) {
+        return pv_decimal(r, n, p);
+    }
     match (rate, nper, pmt) {
         (Value::Number(r), Value::Number(n), Value::Number(p)) => {
             if *n <= 0.0 {
@@ -43,3 +50,22 @@ pub fn pv(rate: &Value, nper: &Value, pmt: &Value) -> Result<Value, FunctionErro
         }),
     }
 }
+
+fn pv_decimal(r: Decimal, n: Decimal, p: Decimal) -> Result<Value, FunctionError> {
+    if n <= Decimal::ZERO {
+        return Err(FunctionError::ArgumentError {
+            message: "nper must be greater than 0".to_string(),
+        });
+    }
+
+    let present_value = || {
+        if r.is_zero() {
+            return (-p).checked_mul(n);
+        }
+        let factor = Decimal::ONE.checked_add(r)?.checked_powd(-n)?;
+        (-p).checked_mul(Decimal::ONE.checked_sub(factor)?.checked_div(r)?)
+    };
+    present_value()
+        .map(Value::Decimal)
+        .ok_or_else(|| overflow("pv"))
+}
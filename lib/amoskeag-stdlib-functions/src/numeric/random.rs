@@ -0,0 +1,85 @@
+//! random, random_between, and uuid functions
+//!
+//! Amoskeag programs stay reproducible: randomness comes from a caller-owned
+//! seed state, advanced with SplitMix64, rather than from the operating system.
+
+use crate::{FunctionError, Value};
+
+/// Advance a SplitMix64 state and return the next 64 random bits
+pub fn next_u64(state: &mut u64) -> u64 {
+    *state = state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+    let mut z = *state;
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^ (z >> 31)
+}
+
+/// Advance a SplitMix64 state and return a float uniformly distributed in [0, 1)
+pub fn next_random(state: &mut u64) -> f64 {
+    // Use the top 53 bits so every result is exactly representable
+    (next_u64(state) >> 11) as f64 / (1u64 << 53) as f64
+}
+
+/// Return a pseudo-random number in [0, 1)
+/// random() -> Number
+pub fn random(state: &mut u64) -> Value {
+    Value::Number(next_random(state))
+}
+
+/// Return a pseudo-random number between two bounds
+/// random_between(lo: Number, hi: Number) -> Number
+///
+/// When both bounds are whole numbers the result is a whole number in
+/// [lo, hi], inclusive; otherwise it is a fraction in [lo, hi).
+pub fn random_between(lo: &Value, hi: &Value, state: &mut u64) -> Result<Value, FunctionError> {
+    match (lo, hi) {
+        (Value::Number(lo), Value::Number(hi)) => {
+            if !lo.is_finite() || !hi.is_finite() || lo > hi {
+                return Err(FunctionError::ArgumentError {
+                    message: format!(
+                        "random_between requires finite bounds with lo <= hi, got {} and {}",
+                        lo, hi
+                    ),
+                });
+            }
+            let sample = next_random(state);
+            if lo.fract() == 0.0 && hi.fract() == 0.0 {
+                let span = hi - lo + 1.0;
+                Ok(Value::Number((lo + (sample * span).floor()).min(*hi)))
+            } else {
+                Ok(Value::Number(lo + sample * (hi - lo)))
+            }
+        }
+        (Value::Number(_), _) => Err(FunctionError::TypeError {
+            expected: "Number".to_string(),
+            got: hi.type_name().to_string(),
+        }),
+        _ => Err(FunctionError::TypeError {
+            expected: "Number".to_string(),
+            got: lo.type_name().to_string(),
+        }),
+    }
+}
+
+/// Return a random (version 4) UUID
+/// uuid() -> String
+///
+/// Formatted as lowercase hex, e.g. "0c9f1b1e-3d2a-4b8e-9f51-6a7c2d4e8b10".
+pub fn uuid(state: &mut u64) -> Value {
+    let high = next_u64(state);
+    let low = next_u64(state);
+    // Set the version (4) and the RFC 4122 variant bits
+    let high = (high & !0xF000) | 0x4000;
+    let low = (low & !(0b11 << 62)) | (0b10 << 62);
+    Value::String(
+        format!(
+            "{:08x}-{:04x}-{:04x}-{:04x}-{:012x}",
+            high >> 32,
+            (high >> 16) & 0xFFFF,
+            high & 0xFFFF,
+            low >> 48,
+            low & 0xFFFF_FFFF_FFFF
+        )
+        .into(),
+    )
+}
@@ -1,10 +1,17 @@
 //! round function
 
-use crate::{FunctionError, Value};
+use super::decimal::overflow;
+use crate::{Decimal, FunctionError, Value};
+use rust_decimal::{MathematicalOps, RoundingStrategy};
 
 /// Round a number to a specified number of decimal places
 /// round(num: Number, digits: Number) -> Number
+///
+/// Halves round away from zero. Decimals are rounded exactly and stay decimals.
 pub fn round(value: &Value, digits: &Value) -> Result<Value, FunctionError> {
+    if let (Value::Decimal(d), Some(places)) = (value, digits.as_f64()) {
+        return round_decimal(*d, places);
+    }
     match (value, digits) {
         (Value::Number(n), Value::Number(d)) => {
             let decimal_places = if d.is_finite() {
@@ -14,14 +21,7 @@ pub fn round(value: &Value, digits: &Value) -> Result<Value, FunctionError> {
                     message: "digits must be finite".to_string(),
                 });
             };
-            let multiplier = 10_f64.powi(decimal_places.abs());
-            let rounded = if decimal_places >= 0 {
-                (n * multiplier).round() / multiplier
-            } else {
-                // For negative decimal places, round to tens, hundreds, etc.
-                (n / multiplier).round() * multiplier
-            };
-            Ok(Value::Number(rounded))
+            Ok(Value::Number(round_half_away(*n, decimal_places)))
         }
         (Value::Number(_), _) => Err(FunctionError::TypeError {
             expected: "Number".to_string(),
@@ -33,3 +33,45 @@ pub fn round(value: &Value, digits: &Value) -> Result<Value, FunctionError> {
         }),
     }
 }
+
+/// `n` rounded to `places` decimal places, or to tens, hundreds, etc. when
+/// negative, with halves away from zero
+///
+/// The formatting functions round with this too, so they print what `round`
+/// returns. A number too large to scale already has no fractional digits
+/// and is returned unchanged.
+pub(crate) fn round_half_away(n: f64, places: i32) -> f64 {
+    let multiplier = 10_f64.powi(places.abs());
+    let rounded = if places >= 0 {
+        (n * multiplier).round() / multiplier
+    } else {
+        (n / multiplier).round() * multiplier
+    };
+    if rounded.is_finite() {
+        rounded
+    } else {
+        n
+    }
+}
+
+/// Round a decimal to `digits` places, or to tens, hundreds, etc. when negative
+fn round_decimal(value: Decimal, digits: f64) -> Result<Value, FunctionError> {
+    if !digits.is_finite() {
+        return Err(FunctionError::ArgumentError {
+            message: "digits must be finite".to_string(),
+        });
+    }
+    let places = (digits as i32).clamp(-20, 20);
+    let rounded = if places >= 0 {
+        value.round_dp_with_strategy(places as u32, RoundingStrategy::MidpointAwayFromZero)
+    } else {
+        let scale = Decimal::TEN
+            .checked_powi(i64::from(-places))
+            .ok_or_else(|| overflow("round"))?;
+        (value / scale)
+            .round_dp_with_strategy(0, RoundingStrategy::MidpointAwayFromZero)
+            .checked_mul(scale)
+            .ok_or_else(|| overflow("round"))?
+    };
+    Ok(Value::Decimal(rounded))
+}
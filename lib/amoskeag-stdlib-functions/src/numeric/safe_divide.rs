@@ -0,0 +1,30 @@
+//! safe_divide function
+
+use crate::{FunctionError, Value};
+
+/// Divide two numbers, returning a fallback instead of failing on a zero divisor
+/// safe_divide(a: Number, b: Number, default: Any) -> Any
+///
+/// Non-numeric operands are still a type error; only division by zero is
+/// replaced with `default`.
+///
+/// Example: safe_divide(claims, exposures, 0) = 0 when exposures is 0
+pub fn safe_divide(a: &Value, b: &Value, default: &Value) -> Result<Value, FunctionError> {
+    match (a, b) {
+        (Value::Number(x), Value::Number(y)) => {
+            if *y == 0.0 {
+                Ok(default.clone())
+            } else {
+                Ok(Value::Number(x / y))
+            }
+        }
+        (Value::Number(_), _) => Err(FunctionError::TypeError {
+            expected: "Number".to_string(),
+            got: b.type_name().to_string(),
+        }),
+        _ => Err(FunctionError::TypeError {
+            expected: "Number".to_string(),
+            got: a.type_name().to_string(),
+        }),
+    }
+}
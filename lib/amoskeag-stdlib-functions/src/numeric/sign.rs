@@ -0,0 +1,16 @@
+//! sign function
+
+use crate::{FunctionError, Value};
+
+/// Return -1, 0, or 1 according to the sign of a number
+/// sign(num: Number) -> Number
+pub fn sign(value: &Value) -> Result<Value, FunctionError> {
+    match value {
+        Value::Number(n) if *n == 0.0 => Ok(Value::Number(0.0)),
+        Value::Number(n) => Ok(Value::Number(n.signum())),
+        _ => Err(FunctionError::TypeError {
+            expected: "Number".to_string(),
+            got: value.type_name().to_string(),
+        }),
+    }
+}
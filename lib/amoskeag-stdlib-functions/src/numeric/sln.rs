@@ -1,14 +1,29 @@
 //! sln function
 
-use crate::{FunctionError, Value};
+use super::decimal::{decimal_args, overflow};
+use crate::{Decimal, FunctionError, Value};
 
 /// Calculate straight-line depreciation
 /// sln(cost: Number, salvage: Number, life: Number) -> Number
 ///
 /// Formula: SLN = (cost - salvage) / life
 ///
+/// Decimal arguments are computed exactly, to 28 significant digits.
+///
 /// Example: sln(30000, 7500, 10) = 2250 (annual depreciation for 10 years)
 pub fn sln(cost: &Value, salvage: &Value, life: &Value) -> Result<Value, FunctionError> {
+    if let Some([c, s, l]) = decimal_args([cost, salvage, life]) {
+        if l <= Decimal::ZERO {
+            return Err(FunctionError::ArgumentError {
+                message: "life must be greater than 0".to_string(),
+            });
+        }
+        return c
+            .checked_sub(s)
+            .and_then(|basis| basis.checked_div(l))
+            .map(Value::Decimal)
+            .ok_or_else(|| overflow("sln"));
+    }
     match (cost, salvage, life) {
         (Value::Number(c), Value::Number(s), Value::Number(l)) => {
             if *l <= 0.0 {
@@ -0,0 +1,84 @@
+//! syd function
+
+use super::decimal::{decimal_args, overflow};
+use crate::{Decimal, FunctionError, Value};
+
+/// Calculate sum-of-years' digits depreciation
+/// syd(cost: Number, salvage: Number, life: Number, per: Number) -> Number
+///
+/// Formula: SYD = (cost - salvage) * (life - per + 1) * 2 / (life * (life + 1))
+///
+/// Decimal arguments are computed exactly, to 28 significant digits.
+///
+/// Example: syd(30000, 7500, 10, 1) = 4090.91 (depreciation for the first year)
+pub fn syd(
+    cost: &Value,
+    salvage: &Value,
+    life: &Value,
+    per: &Value,
+) -> Result<Value, FunctionError> {
+    if let Some([c, s, l, p]) = decimal_args([cost, salvage, life, per]) {
+        return syd_decimal(c, s, l, p);
+    }
+    match (cost, salvage, life, per) {
+        (Value::Number(c), Value::Number(s), Value::Number(l), Value::Number(p)) => {
+            if *l <= 0.0 {
+                return Err(FunctionError::ArgumentError {
+                    message: "life must be greater than 0".to_string(),
+                });
+            }
+
+            if *p <= 0.0 || *p > *l {
+                return Err(FunctionError::ArgumentError {
+                    message: format!("per must be greater than 0 and at most {}", l),
+                });
+            }
+
+            let depreciation = (c - s) * (l - p + 1.0) * 2.0 / (l * (l + 1.0));
+            Ok(Value::Number(depreciation))
+        }
+        (Value::Number(_), Value::Number(_), Value::Number(_), _) => {
+            Err(FunctionError::TypeError {
+                expected: "Number".to_string(),
+                got: per.type_name().to_string(),
+            })
+        }
+        (Value::Number(_), Value::Number(_), _, _) => Err(FunctionError::TypeError {
+            expected: "Number".to_string(),
+            got: life.type_name().to_string(),
+        }),
+        (Value::Number(_), _, _, _) => Err(FunctionError::TypeError {
+            expected: "Number".to_string(),
+            got: salvage.type_name().to_string(),
+        }),
+        _ => Err(FunctionError::TypeError {
+            expected: "Number".to_string(),
+            got: cost.type_name().to_string(),
+        }),
+    }
+}
+
+fn syd_decimal(c: Decimal, s: Decimal, l: Decimal, p: Decimal) -> Result<Value, FunctionError> {
+    if l <= Decimal::ZERO {
+        return Err(FunctionError::ArgumentError {
+            message: "life must be greater than 0".to_string(),
+        });
+    }
+    if p <= Decimal::ZERO || p > l {
+        return Err(FunctionError::ArgumentError {
+            message: format!("per must be greater than 0 and at most {}", l),
+        });
+    }
+
+    let depreciation = || {
+        let remaining = l.checked_sub(p)?.checked_add(Decimal::ONE)?;
+        let digits = l.checked_mul(l.checked_add(Decimal::ONE)?)?;
+        c.checked_sub(s)?
+            .checked_mul(remaining)?
+            .checked_mul(Decimal::TWO)?
+            .checked_div(digits)
+    };
+    depreciation()
+        .map(Value::Decimal)
+        .ok_or_else(|| overflow("syd"))
+}
@@ -0,0 +1,141 @@
+//! tier_lookup and tier_marginal functions
+//!
+//! Both take a rate table of `[threshold, rate]` pairs in ascending
+//! threshold order, e.g. `[[0, 0.1], [10000, 0.2], [40000, 0.4]]`. When the
+//! value or the table holds a decimal, both are read as decimals and the
+//! result is exact.
+
+use super::decimal::overflow;
+use crate::{Decimal, FunctionError, Value};
+
+/// Look up the rate of the tier a value falls into
+/// tier_lookup(value: Number, tiers: Array) -> Number | Nil
+///
+/// Returns the rate of the highest threshold at or below the value, or nil
+/// when the value is below every threshold.
+///
+/// Example: tier_lookup(25000, [[0, 0.1], [10000, 0.2], [40000, 0.4]]) = 0.2
+pub fn tier_lookup(value: &Value, tiers: &Value) -> Result<Value, FunctionError> {
+    if value.contains_decimal() || tiers.contains_decimal() {
+        let v = tier_value(value, Value::as_decimal)?;
+        let tiers = rate_table(tiers, Value::as_decimal)?;
+        return Ok(tiers
+            .iter()
+            .rev()
+            .find(|(threshold, _)| *threshold <= v)
+            .map_or(Value::Nil, |(_, rate)| Value::Decimal(*rate)));
+    }
+    let v = tier_value(value, as_f64)?;
+    let tiers = rate_table(tiers, as_f64)?;
+    Ok(tiers
+        .iter()
+        .rev()
+        .find(|(threshold, _)| *threshold <= v)
+        .map_or(Value::Nil, |(_, rate)| Value::Number(*rate)))
+}
+
+/// Apply each tier's rate to the portion of a value within that tier
+/// tier_marginal(value: Number, tiers: Array) -> Number
+///
+/// Each rate applies from its threshold up to the next one, as with tax
+/// brackets. Any portion below the first threshold is charged nothing.
+///
+/// Example: tier_marginal(25000, [[0, 0.1], [10000, 0.2], [40000, 0.4]]) = 4000
+pub fn tier_marginal(value: &Value, tiers: &Value) -> Result<Value, FunctionError> {
+    if value.contains_decimal() || tiers.contains_decimal() {
+        let v = tier_value(value, Value::as_decimal)?;
+        let tiers = rate_table(tiers, Value::as_decimal)?;
+        let mut total = Decimal::ZERO;
+        for (i, (threshold, rate)) in tiers.iter().enumerate() {
+            if v <= *threshold {
+                break;
+            }
+            let ceiling = tiers.get(i + 1).map_or(v, |(next, _)| (*next).min(v));
+            total = ceiling
+                .checked_sub(*threshold)
+                .and_then(|width| width.checked_mul(*rate))
+                .and_then(|charge| total.checked_add(charge))
+                .ok_or_else(|| overflow("tier_marginal"))?;
+        }
+        return Ok(Value::Decimal(total));
+    }
+    let v = tier_value(value, as_f64)?;
+    let tiers = rate_table(tiers, as_f64)?;
+
+    let mut total = 0.0;
+    for (i, (threshold, rate)) in tiers.iter().enumerate() {
+        if v <= *threshold {
+            break;
+        }
+        let ceiling = tiers.get(i + 1).map_or(v, |(next, _)| next.min(v));
+        total += (ceiling - threshold) * rate;
+    }
+    Ok(Value::Number(total))
+}
+
+fn as_f64(value: &Value) -> Option<f64> {
+    match value {
+        Value::Number(n) => Some(*n),
+        _ => None,
+    }
+}
+
+/// `value` read with `number`, which gives `None` for anything but a number
+fn tier_value<T>(value: &Value, number: fn(&Value) -> Option<T>) -> Result<T, FunctionError> {
+    match number(value) {
+        Some(n) => Ok(n),
+        None => Err(FunctionError::TypeError {
+            expected: "Number".to_string(),
+            got: value.type_name().to_string(),
+        }),
+    }
+}
+
+/// Parse `[[threshold, rate], ...]`, requiring strictly ascending thresholds
+fn rate_table<T: PartialOrd + Copy>(
+    tiers: &Value,
+    number: fn(&Value) -> Option<T>,
+) -> Result<Vec<(T, T)>, FunctionError> {
+    let rows = match tiers {
+        Value::Array(rows) => rows,
+        _ => {
+            return Err(FunctionError::TypeError {
+                expected: "Array".to_string(),
+                got: tiers.type_name().to_string(),
+            })
+        }
+    };
+    if rows.is_empty() {
+        return Err(FunctionError::ArgumentError {
+            message: "tiers array cannot be empty".to_string(),
+        });
+    }
+
+    let mut table: Vec<(T, T)> = Vec::with_capacity(rows.len());
+    for row in rows.iter() {
+        let pair = match row {
+            Value::Array(pair) => match pair.as_slice() {
+                [threshold, rate] => number(threshold).zip(number(rate)),
+                _ => None,
+            },
+            _ => {
+                return Err(FunctionError::TypeError {
+                    expected: "Array of [threshold, rate] pairs".to_string(),
+                    got: format!("Array containing {}", row.type_name()),
+                })
+            }
+        }
+        .ok_or_else(|| FunctionError::ArgumentError {
+            message: "each tier must be a [threshold, rate] pair of numbers".to_string(),
+        })?;
+        if let Some((previous, _)) = table.last() {
+            if pair.0 <= *previous {
+                return Err(FunctionError::ArgumentError {
+                    message: "tier thresholds must be in ascending order".to_string(),
+                });
+            }
+        }
+        table.push(pair);
+    }
+    Ok(table)
+}
@@ -0,0 +1,15 @@
+//! trunc function
+
+use crate::{FunctionError, Value};
+
+/// Remove the fractional part of a number, rounding toward zero
+/// trunc(num: Number) -> Number
+pub fn trunc(value: &Value) -> Result<Value, FunctionError> {
+    match value {
+        Value::Number(n) => Ok(Value::Number(n.trunc())),
+        _ => Err(FunctionError::TypeError {
+            expected: "Number".to_string(),
+            got: value.type_name().to_string(),
+        }),
+    }
+}
@@ -0,0 +1,164 @@
+//! vdb function
+
+use super::amortization_schedule::MAX_SCHEDULE_PERIODS;
+use crate::{FunctionError, Value};
+
+/// Calculate variable declining balance depreciation between two periods
+/// vdb(cost: Number, salvage: Number, life: Number, start_period: Number,
+///     end_period: Number, factor: Number, no_switch: Boolean) -> Number
+///
+/// Uses the declining balance method at `factor` times the straight-line
+/// rate, switching to straight-line once that gives the larger amount unless
+/// `no_switch` is true. Periods may be fractional. Matches Excel's VDB.
+///
+/// Example: vdb(2400, 300, 10, 0, 1, 2, false) = 480 (first year's depreciation)
+pub fn vdb(
+    cost: &Value,
+    salvage: &Value,
+    life: &Value,
+    start_period: &Value,
+    end_period: &Value,
+    factor: &Value,
+    no_switch: &Value,
+) -> Result<Value, FunctionError> {
+    let number = |value: &Value| match value {
+        Value::Number(n) => Ok(*n),
+        _ => Err(FunctionError::TypeError {
+            expected: "Number".to_string(),
+            got: value.type_name().to_string(),
+        }),
+    };
+    let cost = number(cost)?;
+    let salvage = number(salvage)?;
+    let life = number(life)?;
+    let start = number(start_period)?;
+    let end = number(end_period)?;
+    let factor = number(factor)?;
+    let no_switch = match no_switch {
+        Value::Boolean(b) => *b,
+        _ => {
+            return Err(FunctionError::TypeError {
+                expected: "Boolean".to_string(),
+                got: no_switch.type_name().to_string(),
+            })
+        }
+    };
+
+    if life <= 0.0 {
+        return Err(FunctionError::ArgumentError {
+            message: "life must be greater than 0".to_string(),
+        });
+    }
+    if life > MAX_SCHEDULE_PERIODS {
+        return Err(FunctionError::ArgumentError {
+            message: format!("life must be at most {} periods", MAX_SCHEDULE_PERIODS),
+        });
+    }
+    if start < 0.0 || end < start || end > life {
+        return Err(FunctionError::ArgumentError {
+            message: format!(
+                "periods must satisfy 0 <= start_period <= end_period <= {}",
+                life
+            ),
+        });
+    }
+    if cost < 0.0 || salvage > cost {
+        return Err(FunctionError::ArgumentError {
+            message: "cost must be non-negative and at least salvage".to_string(),
+        });
+    }
+    if factor <= 0.0 {
+        return Err(FunctionError::ArgumentError {
+            message: "factor must be greater than 0".to_string(),
+        });
+    }
+
+    if no_switch {
+        let first = start.floor() as i64 + 1;
+        let last = end.ceil() as i64;
+        let mut total = 0.0;
+        for period in first..=last {
+            let mut term = declining_balance(cost, salvage, life, period as f64, factor);
+            if period == first {
+                term *= end.min(first as f64) - start;
+            } else if period == last {
+                term *= end + 1.0 - last as f64;
+            }
+            total += term;
+        }
+        return Ok(Value::Number(total));
+    }
+
+    let (mut start, mut end, mut switch_life) = (start, end, life);
+    if start != start.floor() && factor > 1.0 && start >= life / 2.0 {
+        let part = start - life / 2.0;
+        start = life / 2.0;
+        end -= part;
+        switch_life += 1.0;
+    }
+
+    let remaining_cost = cost - switched_total(cost, salvage, life, switch_life, start, factor);
+    Ok(Value::Number(switched_total(
+        remaining_cost,
+        salvage,
+        life,
+        life - start,
+        end - start,
+        factor,
+    )))
+}
+
+/// Declining balance depreciation for a single whole period
+fn declining_balance(cost: f64, salvage: f64, life: f64, period: f64, factor: f64) -> f64 {
+    let rate = factor / life;
+    let (rate, old_value) = if rate >= 1.0 {
+        (1.0, if period == 1.0 { cost } else { 0.0 })
+    } else {
+        (rate, cost * (1.0 - rate).powf(period - 1.0))
+    };
+    let new_value = cost * (1.0 - rate).powf(period);
+    let depreciation = if new_value < salvage {
+        old_value - salvage
+    } else {
+        old_value - new_value
+    };
+    depreciation.max(0.0)
+}
+
+/// Total depreciation over `period` periods, switching to straight-line
+/// over `switch_life` once it exceeds the declining balance amount
+fn switched_total(
+    cost: f64,
+    salvage: f64,
+    life: f64,
+    switch_life: f64,
+    period: f64,
+    factor: f64,
+) -> f64 {
+    let last = period.ceil() as i64;
+    let mut remaining = cost - salvage;
+    let mut straight_line = 0.0;
+    let mut switched = false;
+    let mut total = 0.0;
+
+    for i in 1..=last {
+        let mut term = if switched {
+            straight_line
+        } else {
+            let declining = declining_balance(cost, salvage, life, i as f64, factor);
+            straight_line = remaining / (switch_life - (i - 1) as f64);
+            if straight_line > declining {
+                switched = true;
+                straight_line
+            } else {
+                remaining -= declining;
+                declining
+            }
+        };
+        if i == last {
+            term *= period + 1.0 - last as f64;
+        }
+        total += term;
+    }
+    total
+}
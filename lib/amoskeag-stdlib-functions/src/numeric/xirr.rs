@@ -0,0 +1,88 @@
+//! xirr function
+
+use super::xnpv::{dated_cash_flows, discounted_sum};
+use crate::{FunctionError, Value};
+
+/// Calculate internal rate of return for cash flows on irregular dates
+/// xirr(values: Array, dates: Array, guess: Number) -> Number
+///
+/// Finds the rate where XNPV = 0, using Newton-Raphson from `guess` and
+/// falling back to bisection when Newton's method does not converge.
+///
+/// Example: xirr([-10000, 2750, 4250, 3250, 2750], dates, 0.1) ≈ 0.3734
+pub fn xirr(values: &Value, dates: &Value, guess: &Value) -> Result<Value, FunctionError> {
+    let guess = match guess {
+        Value::Number(g) => *g,
+        _ => {
+            return Err(FunctionError::TypeError {
+                expected: "Number".to_string(),
+                got: guess.type_name().to_string(),
+            })
+        }
+    };
+
+    let flows = dated_cash_flows(values, dates)?;
+    let has_positive = flows.iter().any(|(_, v)| *v > 0.0);
+    let has_negative = flows.iter().any(|(_, v)| *v < 0.0);
+    if !has_positive || !has_negative {
+        return Err(FunctionError::ArgumentError {
+            message: "cash flows must contain both positive and negative values".to_string(),
+        });
+    }
+
+    newton(&flows, guess)
+        .or_else(|| bisect(&flows))
+        .map(Value::Number)
+        .ok_or_else(|| FunctionError::ArgumentError {
+            message: "XIRR calculation did not converge".to_string(),
+        })
+}
+
+fn newton(flows: &[(f64, f64)], guess: f64) -> Option<f64> {
+    let tolerance = 1e-10;
+    let mut rate = guess;
+
+    for _ in 0..100 {
+        if rate <= -1.0 || !rate.is_finite() {
+            return None;
+        }
+        let value = discounted_sum(rate, flows);
+        let derivative: f64 = flows
+            .iter()
+            .map(|(years, amount)| -years * amount / (1.0 + rate).powf(years + 1.0))
+            .sum();
+        if derivative == 0.0 {
+            return None;
+        }
+
+        let next = rate - value / derivative;
+        if (next - rate).abs() < tolerance {
+            return (next > -1.0).then_some(next);
+        }
+        rate = next;
+    }
+    None
+}
+
+fn bisect(flows: &[(f64, f64)]) -> Option<f64> {
+    let (mut low, mut high) = (-0.999_999, 1_000.0);
+    let mut low_value = discounted_sum(low, flows);
+    if low_value.signum() == discounted_sum(high, flows).signum() {
+        return None;
+    }
+
+    for _ in 0..200 {
+        let mid = (low + high) / 2.0;
+        let mid_value = discounted_sum(mid, flows);
+        if mid_value == 0.0 || (high - low) / 2.0 < 1e-12 {
+            return Some(mid);
+        }
+        if mid_value.signum() == low_value.signum() {
+            low = mid;
+            low_value = mid_value;
+        } else {
+            high = mid;
+        }
+    }
+    Some((low + high) / 2.0)
+}
@@ -0,0 +1,101 @@
+//! xnpv function
+
+use crate::date::CivilDate;
+use crate::{FunctionError, Value};
+
+/// Calculate net present value of cash flows on irregular dates
+/// xnpv(rate: Number, values: Array, dates: Array) -> Number
+///
+/// Formula: XNPV = sum(values[i] / (1 + rate)^((dates[i] - dates[0]) / 365))
+///
+/// Unlike npv, the first value is not discounted, matching Excel's XNPV.
+///
+/// Example: xnpv(0.09, [-10000, 2750, 4250], ['2008-01-01', '2008-03-01', '2008-10-30'])
+pub fn xnpv(rate: &Value, values: &Value, dates: &Value) -> Result<Value, FunctionError> {
+    let r = match rate {
+        Value::Number(r) => *r,
+        _ => {
+            return Err(FunctionError::TypeError {
+                expected: "Number".to_string(),
+                got: rate.type_name().to_string(),
+            })
+        }
+    };
+    if r <= -1.0 {
+        return Err(FunctionError::ArgumentError {
+            message: "rate must be greater than -1".to_string(),
+        });
+    }
+
+    let flows = dated_cash_flows(values, dates)?;
+    Ok(Value::Number(discounted_sum(r, &flows)))
+}
+
+/// Pair each cash flow with its time in years from the first date
+pub(crate) fn dated_cash_flows(
+    values: &Value,
+    dates: &Value,
+) -> Result<Vec<(f64, f64)>, FunctionError> {
+    let (values, dates) = match (values, dates) {
+        (Value::Array(v), Value::Array(d)) => (v, d),
+        (Value::Array(_), _) => {
+            return Err(FunctionError::TypeError {
+                expected: "Array".to_string(),
+                got: dates.type_name().to_string(),
+            })
+        }
+        _ => {
+            return Err(FunctionError::TypeError {
+                expected: "Array".to_string(),
+                got: values.type_name().to_string(),
+            })
+        }
+    };
+
+    if values.is_empty() {
+        return Err(FunctionError::ArgumentError {
+            message: "values array cannot be empty".to_string(),
+        });
+    }
+    if values.len() != dates.len() {
+        return Err(FunctionError::ArgumentError {
+            message: format!(
+                "values and dates must have the same length, got {} and {}",
+                values.len(),
+                dates.len()
+            ),
+        });
+    }
+
+    let start = CivilDate::from_value(&dates[0])?.days_since_epoch();
+    values
+        .iter()
+        .zip(dates.iter())
+        .map(|(value, date)| {
+            let amount = match value {
+                Value::Number(n) => *n,
+                _ => {
+                    return Err(FunctionError::TypeError {
+                        expected: "Array of Numbers".to_string(),
+                        got: format!("Array containing {}", value.type_name()),
+                    })
+                }
+            };
+            let days = CivilDate::from_value(date)?.days_since_epoch() - start;
+            if days < 0 {
+                return Err(FunctionError::ArgumentError {
+                    message: "dates must not precede the first date".to_string(),
+                });
+            }
+            Ok((days as f64 / 365.0, amount))
+        })
+        .collect()
+}
+
+/// Sum of cash flows discounted to the first date
+pub(crate) fn discounted_sum(rate: f64, flows: &[(f64, f64)]) -> f64 {
+    flows
+        .iter()
+        .map(|(years, amount)| amount / (1.0 + rate).powf(*years))
+        .sum()
+}
@@ -0,0 +1,1083 @@
+//! Signatures of the standard library functions
+//!
+//! Every function callable from an Amoskeag program is submitted here once,
+//! with how many arguments it takes, whether its result depends on more
+//! than its arguments, how it treats integers and decimals, and the types of
+//! its arguments and result where those are fixed. The compiler checks calls
+//! against these entries and the interpreter and transpilers
+//! consult them, so adding a function to the standard library means adding
+//! its entry here and its implementation.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::OnceLock;
+
+/// A standard library function, as registered with inventory
+#[derive(Debug)]
+pub struct StdlibFunction {
+    pub name: &'static str,
+    pub description: &'static str,
+    pub arity: Arity,
+    pub purity: Purity,
+    pub numbers: Numbers,
+    /// The function to call instead, if this one is deprecated
+    pub replacement: Option<&'static str>,
+    /// The types of its leading arguments; any past the end are unchecked
+    pub params: &'static [Type],
+    /// The type of its result, or `Type::Any` when that depends on the call
+    pub returns: Type,
+}
+
+impl StdlibFunction {
+    /// A pure, untyped function that works in floats
+    pub const fn new(name: &'static str, description: &'static str, arity: Arity) -> Self {
+        StdlibFunction {
+            name,
+            description,
+            arity,
+            purity: Purity::Pure,
+            numbers: Numbers::Floats,
+            replacement: None,
+            params: &[],
+            returns: Type::Any,
+        }
+    }
+
+    pub const fn with_purity(mut self, purity: Purity) -> Self {
+        self.purity = purity;
+        self
+    }
+
+    pub const fn with_numbers(mut self, numbers: Numbers) -> Self {
+        self.numbers = numbers;
+        self
+    }
+
+    /// Deprecate the function in favour of `replacement`
+    pub const fn deprecated(mut self, replacement: &'static str) -> Self {
+        self.replacement = Some(replacement);
+        self
+    }
+
+    pub const fn with_types(mut self, params: &'static [Type], returns: Type) -> Self {
+        self.params = params;
+        self.returns = returns;
+        self
+    }
+}
+
+/// The type of a value, as far as the type checker can tell
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Type {
+    /// Unknown; compatible with everything
+    Any,
+    /// Integers, floats, and decimals alike
+    Number,
+    String,
+    Boolean,
+    Nil,
+    Symbol,
+    Array,
+    Dictionary,
+    Duration,
+    Function,
+}
+
+impl fmt::Display for Type {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Type::Any => "Any",
+            Type::Number => "Number",
+            Type::String => "String",
+            Type::Boolean => "Boolean",
+            Type::Nil => "Nil",
+            Type::Symbol => "Symbol",
+            Type::Array => "Array",
+            Type::Dictionary => "Dictionary",
+            Type::Duration => "Duration",
+            Type::Function => "Function",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// What a function's result depends on besides its arguments
+///
+/// Memoization caches everything but `Impure` calls within an evaluation;
+/// partial evaluation folds only `Pure` ones ahead of it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Purity {
+    /// Nothing: the same arguments always give the same result
+    Pure,
+    /// The evaluation's settings, such as its clock or locale, which stay
+    /// the same throughout one evaluation
+    Contextual,
+    /// State that advances with each call, such as a random generator
+    Impure,
+}
+
+/// What a function expects of the integers and decimals in its arguments,
+/// including those nested in arrays and dictionaries
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Numbers {
+    /// Floats: integers and decimals are converted to numbers first
+    Floats,
+    /// Floats, for a function that has no exact decimal form because it
+    /// needs iteration, logarithms, or fractional powers; it is refused in
+    /// decimal mode rather than give an inexact result
+    FloatsOnly,
+    /// Floats or decimals: integers are converted to numbers first, and
+    /// decimals are computed with exactly
+    Exact,
+    /// Anything: the function only selects, reorders, or compares values, or
+    /// keeps integers exact itself, so they pass through unchanged
+    Unchanged,
+}
+
+/// How many arguments a function accepts
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Arity {
+    /// Exactly this many
+    Exact(usize),
+    /// Between the two counts, inclusive
+    Range(usize, usize),
+    /// This many or more
+    AtLeast(usize),
+}
+
+impl Arity {
+    pub fn min_args(&self) -> usize {
+        match *self {
+            Arity::Exact(count) | Arity::Range(count, _) | Arity::AtLeast(count) => count,
+        }
+    }
+
+    /// The most arguments accepted, or `None` when there is no limit
+    pub fn max_args(&self) -> Option<usize> {
+        match *self {
+            Arity::Exact(count) | Arity::Range(_, count) => Some(count),
+            Arity::AtLeast(_) => None,
+        }
+    }
+
+    /// Whether a call with `count` arguments is allowed
+    pub fn accepts(&self, count: usize) -> bool {
+        count >= self.min_args() && self.max_args().is_none_or(|max| count <= max)
+    }
+}
+
+/// The expected argument count as shown in arity errors, e.g. `2` or `1-3`
+impl fmt::Display for Arity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Arity::Exact(count) => write!(f, "{}", count),
+            Arity::Range(min, max) => write!(f, "{}-{}", min, max),
+            Arity::AtLeast(min) => write!(f, "at least {}", min),
+        }
+    }
+}
+
+inventory::collect!(StdlibFunction);
+
+/// The standard library function called `name`
+pub fn lookup(name: &str) -> Option<&'static StdlibFunction> {
+    static BY_NAME: OnceLock<HashMap<&'static str, &'static StdlibFunction>> = OnceLock::new();
+    BY_NAME
+        .get_or_init(|| all().map(|function| (function.name, function)).collect())
+        .get(name)
+        .copied()
+}
+
+/// A call to a standard library function with the wrong number of arguments
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ArityError {
+    pub function: String,
+    pub expected: Arity,
+    pub actual: usize,
+}
+
+impl fmt::Display for ArityError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Function '{}' expects {} arguments, but {} were provided",
+            self.function, self.expected, self.actual
+        )
+    }
+}
+
+impl std::error::Error for ArityError {}
+
+/// Check the argument count of a call to `name`
+///
+/// Fails when `name` is a standard library function that does not take
+/// `count` arguments. Other names pass, since whether they exist is up to the
+/// caller.
+pub fn check_arity(name: &str, count: usize) -> Result<(), ArityError> {
+    match lookup(name) {
+        Some(function) if !function.arity.accepts(count) => Err(ArityError {
+            function: name.to_string(),
+            expected: function.arity,
+            actual: count,
+        }),
+        _ => Ok(()),
+    }
+}
+
+/// Every standard library function, in no particular order
+pub fn all() -> impl Iterator<Item = &'static StdlibFunction> {
+    inventory::iter::<StdlibFunction>.into_iter()
+}
+
+// String functions
+inventory::submit! {
+    StdlibFunction::new("upcase", "Convert a string to uppercase", Arity::Exact(1)).with_types(&[Type::String], Type::String)
+}
+
+inventory::submit! {
+    StdlibFunction::new("downcase", "Convert a string to lowercase", Arity::Exact(1)).with_types(&[Type::String], Type::String)
+}
+
+inventory::submit! {
+    StdlibFunction::new("capitalize", "Capitalize the first character of a string", Arity::Exact(1)).with_types(&[Type::String], Type::String)
+}
+
+inventory::submit! {
+    StdlibFunction::new("snake_case", "Convert a string to snake_case", Arity::Exact(1))
+}
+
+inventory::submit! {
+    StdlibFunction::new("camel_case", "Convert a string to camelCase", Arity::Exact(1))
+}
+
+inventory::submit! {
+    StdlibFunction::new("titlecase", "Capitalize every word of a string", Arity::Exact(1))
+}
+
+inventory::submit! {
+    StdlibFunction::new("strip", "Remove leading and trailing whitespace", Arity::Exact(1)).with_types(&[Type::String], Type::String)
+}
+
+inventory::submit! {
+    StdlibFunction::new("trim_start", "Remove leading whitespace", Arity::Exact(1))
+}
+
+inventory::submit! {
+    StdlibFunction::new("trim_end", "Remove trailing whitespace", Arity::Exact(1))
+}
+
+inventory::submit! {
+    StdlibFunction::new("squish", "Trim outer whitespace and collapse internal whitespace runs to one space", Arity::Exact(1))
+}
+
+inventory::submit! {
+    StdlibFunction::new("slugify", "Convert a string into a URL-safe identifier", Arity::Exact(1))
+}
+
+inventory::submit! {
+    StdlibFunction::new("split", "Split a string into an array by a separator", Arity::Exact(2)).with_types(&[Type::String, Type::String], Type::Array)
+}
+
+inventory::submit! {
+    StdlibFunction::new("chars", "Split a string into its characters", Arity::Exact(1))
+}
+
+inventory::submit! {
+    StdlibFunction::new("lines", "Split a string into lines", Arity::Exact(1))
+}
+
+inventory::submit! {
+    StdlibFunction::new("join", "Join an array of strings with a separator", Arity::Exact(2)).with_types(&[Type::Array, Type::String], Type::String)
+}
+
+inventory::submit! {
+    StdlibFunction::new("truncate", "Truncate a string to a maximum length", Arity::Exact(2)).with_types(&[Type::String, Type::Number], Type::String)
+}
+
+inventory::submit! {
+    StdlibFunction::new("substring", "Extract part of a string by character position", Arity::Exact(3))
+}
+
+inventory::submit! {
+    StdlibFunction::new("repeat", "Repeat a string a number of times", Arity::Exact(2))
+}
+
+inventory::submit! {
+    StdlibFunction::new("pad_left", "Pad a string on the left to a minimum width", Arity::Exact(3))
+}
+
+inventory::submit! {
+    StdlibFunction::new("pad_right", "Pad a string on the right to a minimum width", Arity::Exact(3))
+}
+
+inventory::submit! {
+    StdlibFunction::new("format", "Format values into a template string", Arity::AtLeast(1))
+}
+
+inventory::submit! {
+    StdlibFunction::new("replace", "Replace all occurrences of a substring", Arity::Exact(3)).with_types(&[Type::String, Type::String, Type::String], Type::String)
+}
+
+inventory::submit! {
+    StdlibFunction::new("starts_with", "Check whether a string begins with a prefix", Arity::Exact(2)).with_types(&[Type::String, Type::String], Type::Boolean)
+}
+
+inventory::submit! {
+    StdlibFunction::new("ends_with", "Check whether a string ends with a suffix", Arity::Exact(2)).with_types(&[Type::String, Type::String], Type::Boolean)
+}
+
+inventory::submit! {
+    StdlibFunction::new("index_of", "Find the character position of the first occurrence of a substring", Arity::Exact(2))
+}
+
+inventory::submit! {
+    StdlibFunction::new("includes", "Check whether a string contains a substring", Arity::Exact(2))
+}
+
+inventory::submit! {
+    StdlibFunction::new("levenshtein", "Count the single-character edits needed to turn one string into another", Arity::Exact(2))
+}
+
+inventory::submit! {
+    StdlibFunction::new("similarity", "Score how similar two strings are, from 0 (nothing shared) to 1 (identical)", Arity::Exact(2))
+}
+
+inventory::submit! {
+    StdlibFunction::new("regex_match", "Test whether a string matches a regular expression", Arity::Exact(2))
+}
+
+inventory::submit! {
+    StdlibFunction::new("regex_replace", "Replace all matches of a regular expression", Arity::Exact(3))
+}
+
+inventory::submit! {
+    StdlibFunction::new("regex_extract", "Extract the first match of a regular expression", Arity::Exact(2))
+}
+
+inventory::submit! {
+    StdlibFunction::new("matches_pattern", "Check whether an entire string matches a regular expression", Arity::Exact(2))
+}
+
+inventory::submit! {
+    StdlibFunction::new("is_email", "Check whether a string looks like an email address", Arity::Exact(1))
+}
+
+inventory::submit! {
+    StdlibFunction::new("is_numeric_string", "Check whether a string is a plain decimal number", Arity::Exact(1))
+}
+
+inventory::submit! {
+    StdlibFunction::new("luhn_valid", "Check whether a card or ID number passes the Luhn checksum", Arity::Exact(1))
+}
+
+inventory::submit! {
+    StdlibFunction::new("sha256", "Hash a string with SHA-256", Arity::Exact(1))
+}
+
+inventory::submit! {
+    StdlibFunction::new("md5", "Hash a string with MD5", Arity::Exact(1))
+}
+
+inventory::submit! {
+    StdlibFunction::new("base64_encode", "Encode a string as standard, padded Base64", Arity::Exact(1))
+}
+
+inventory::submit! {
+    StdlibFunction::new("base64_decode", "Decode a standard, padded Base64 string", Arity::Exact(1))
+}
+
+inventory::submit! {
+    StdlibFunction::new("url_encode", "Percent-encode a string for use in a URL component", Arity::Exact(1))
+}
+
+inventory::submit! {
+    StdlibFunction::new("url_decode", "Decode a percent-encoded URL component", Arity::Exact(1))
+}
+
+// Numeric functions
+inventory::submit! {
+    StdlibFunction::new("abs", "Return the absolute value of a number", Arity::Exact(1)).with_numbers(Numbers::Exact).with_types(&[Type::Number], Type::Number)
+}
+
+inventory::submit! {
+    StdlibFunction::new("ceil", "Round a number up to the nearest integer", Arity::Exact(1)).with_numbers(Numbers::Exact).with_types(&[Type::Number], Type::Number)
+}
+
+inventory::submit! {
+    StdlibFunction::new("floor", "Round a number down to the nearest integer", Arity::Exact(1)).with_numbers(Numbers::Exact).with_types(&[Type::Number], Type::Number)
+}
+
+inventory::submit! {
+    StdlibFunction::new("round", "Round a number to a specified number of decimal places", Arity::Range(1, 2)).with_numbers(Numbers::Exact).with_types(&[Type::Number, Type::Number], Type::Number)
+}
+
+inventory::submit! {
+    StdlibFunction::new("trunc", "Round a number toward zero", Arity::Exact(1))
+}
+
+inventory::submit! {
+    StdlibFunction::new("sign", "Return -1, 0, or 1 according to the sign of a number", Arity::Exact(1))
+}
+
+inventory::submit! {
+    StdlibFunction::new("clamp", "Restrict a number to a range", Arity::Exact(3))
+}
+
+inventory::submit! {
+    StdlibFunction::new("gcd", "Calculate the greatest common divisor of two whole numbers", Arity::Exact(2)).with_numbers(Numbers::Unchanged)
+}
+
+inventory::submit! {
+    StdlibFunction::new("random", "Return a pseudo-random number in [0, 1)", Arity::Exact(0)).with_purity(Purity::Impure)
+}
+
+inventory::submit! {
+    StdlibFunction::new("random_between", "Return a pseudo-random number between two bounds", Arity::Exact(2)).with_purity(Purity::Impure)
+}
+
+inventory::submit! {
+    StdlibFunction::new("uuid", "Return a random (version 4) UUID", Arity::Exact(0)).with_purity(Purity::Impure)
+}
+
+inventory::submit! {
+    StdlibFunction::new("bucket", "Map a number into a labeled band", Arity::Exact(3))
+}
+
+inventory::submit! {
+    StdlibFunction::new("interpolate", "Look up a value on a piecewise-linear curve", Arity::Exact(3))
+}
+
+inventory::submit! {
+    StdlibFunction::new("lcm", "Calculate the least common multiple of two whole numbers", Arity::Exact(2)).with_numbers(Numbers::Unchanged)
+}
+
+inventory::submit! {
+    StdlibFunction::new("plus", "Add two numbers", Arity::Exact(2))
+}
+
+inventory::submit! {
+    StdlibFunction::new("minus", "Subtract two numbers", Arity::Exact(2))
+}
+
+inventory::submit! {
+    StdlibFunction::new("times", "Multiply two numbers", Arity::Exact(2))
+}
+
+inventory::submit! {
+    StdlibFunction::new("divided_by", "Divide two numbers", Arity::Exact(2))
+}
+
+inventory::submit! {
+    StdlibFunction::new("safe_divide", "Divide two numbers with a fallback for a zero divisor", Arity::Range(2, 3))
+}
+
+inventory::submit! {
+    StdlibFunction::new("modulo", "Calculate modulo of two numbers", Arity::Exact(2))
+}
+
+inventory::submit! {
+    StdlibFunction::new("max", "Calculate the maximum of two numbers", Arity::Exact(2))
+}
+
+inventory::submit! {
+    StdlibFunction::new("min", "Calculate the minimum of two numbers", Arity::Exact(2))
+}
+
+inventory::submit! {
+    StdlibFunction::new("array_min", "Return the minimum value in an array of numbers", Arity::Exact(1))
+}
+
+inventory::submit! {
+    StdlibFunction::new("array_max", "Return the maximum value in an array of numbers", Arity::Exact(1))
+}
+
+inventory::submit! {
+    StdlibFunction::new("power", "Raise a number to a power", Arity::Exact(2))
+}
+
+inventory::submit! {
+    StdlibFunction::new("pow", "Raise a number to a power (alias of power)", Arity::Exact(2))
+}
+
+inventory::submit! {
+    StdlibFunction::new("exp", "Calculate e raised to a power", Arity::Exact(1))
+}
+
+inventory::submit! {
+    StdlibFunction::new("sqrt", "Calculate the square root of a number", Arity::Exact(1)).with_types(&[Type::Number], Type::Number)
+}
+
+inventory::submit! {
+    StdlibFunction::new("log", "Calculate the base-2 logarithm of a number", Arity::Exact(1))
+}
+
+inventory::submit! {
+    StdlibFunction::new("log10", "Calculate the base-10 logarithm of a number", Arity::Exact(1))
+}
+
+inventory::submit! {
+    StdlibFunction::new("ln", "Calculate the natural logarithm of a number", Arity::Exact(1))
+}
+
+inventory::submit! {
+    StdlibFunction::new("number_format", "Format a number with thousands separators and fixed decimal places", Arity::Range(1, 4))
+}
+
+inventory::submit! {
+    StdlibFunction::new("currency", "Format an amount as money in a given currency and locale", Arity::Range(1, 3)).with_purity(Purity::Contextual)
+}
+
+// Collection functions
+inventory::submit! {
+    StdlibFunction::new("size", "Get the size/length of a collection", Arity::Exact(1)).with_types(&[], Type::Number)
+}
+
+inventory::submit! {
+    StdlibFunction::new("first", "Get the first element of an array", Arity::Exact(1)).with_numbers(Numbers::Unchanged)
+}
+
+inventory::submit! {
+    StdlibFunction::new("last", "Get the last element of an array", Arity::Exact(1)).with_numbers(Numbers::Unchanged)
+}
+
+inventory::submit! {
+    StdlibFunction::new("contains", "Check if an array contains a value", Arity::Exact(2)).with_numbers(Numbers::Unchanged)
+}
+
+inventory::submit! {
+    StdlibFunction::new("sum", "Sum an array of numbers", Arity::Exact(1)).with_numbers(Numbers::Exact).with_types(&[Type::Array], Type::Number)
+}
+
+inventory::submit! {
+    StdlibFunction::new("avg", "Calculate the average of an array of numbers", Arity::Exact(1)).with_types(&[Type::Array], Type::Number)
+}
+
+inventory::submit! {
+    StdlibFunction::new("weighted_average", "Calculate the weighted average of an array of numbers", Arity::Range(1, 2))
+}
+
+inventory::submit! {
+    StdlibFunction::new("median", "Calculate the median of an array of numbers", Arity::Exact(1)).with_types(&[Type::Array], Type::Number)
+}
+
+inventory::submit! {
+    StdlibFunction::new("mode", "Find the most frequent value in an array", Arity::Exact(1))
+}
+
+inventory::submit! {
+    StdlibFunction::new("variance", "Calculate the population variance of an array of numbers", Arity::Exact(1))
+}
+
+inventory::submit! {
+    StdlibFunction::new("variance_sample", "Calculate the sample variance of an array of numbers", Arity::Exact(1))
+}
+
+inventory::submit! {
+    StdlibFunction::new("stddev", "Calculate the population standard deviation of an array of numbers", Arity::Exact(1))
+}
+
+inventory::submit! {
+    StdlibFunction::new("stddev_sample", "Calculate the sample standard deviation of an array of numbers", Arity::Exact(1))
+}
+
+inventory::submit! {
+    StdlibFunction::new("percentile", "Calculate a percentile of an array of numbers using linear interpolation", Arity::Exact(2))
+}
+
+inventory::submit! {
+    StdlibFunction::new("quantile", "Calculate a quantile of an array of numbers using linear interpolation", Arity::Exact(2))
+}
+
+inventory::submit! {
+    StdlibFunction::new("covariance", "Calculate the population covariance of two arrays of numbers", Arity::Exact(2))
+}
+
+inventory::submit! {
+    StdlibFunction::new("covariance_sample", "Calculate the sample covariance of two arrays of numbers", Arity::Exact(2))
+}
+
+inventory::submit! {
+    StdlibFunction::new("correlation", "Calculate the Pearson correlation coefficient of two arrays of numbers", Arity::Exact(2))
+}
+
+inventory::submit! {
+    StdlibFunction::new("sort", "Sort an array (ascending order)", Arity::Exact(1)).with_numbers(Numbers::Unchanged).with_types(&[Type::Array], Type::Array)
+}
+
+inventory::submit! {
+    StdlibFunction::new("keys", "Get the keys of a dictionary", Arity::Exact(1)).with_numbers(Numbers::Unchanged).with_types(&[Type::Dictionary], Type::Array)
+}
+
+inventory::submit! {
+    StdlibFunction::new("values", "Get the values of a dictionary", Arity::Exact(1)).with_numbers(Numbers::Unchanged).with_types(&[Type::Dictionary], Type::Array)
+}
+
+inventory::submit! {
+    StdlibFunction::new("reverse", "Reverse an array", Arity::Exact(1)).with_numbers(Numbers::Unchanged).with_types(&[Type::Array], Type::Array)
+}
+
+inventory::submit! {
+    StdlibFunction::new("at", "Get element at index (0-based)", Arity::Exact(2)).with_numbers(Numbers::Unchanged)
+}
+
+inventory::submit! {
+    StdlibFunction::new("uniq", "Remove duplicate elements from an array", Arity::Exact(1)).with_numbers(Numbers::Unchanged).with_types(&[Type::Array], Type::Array)
+}
+
+inventory::submit! {
+    StdlibFunction::new("group_by", "Group array elements by a key", Arity::Exact(2)).with_numbers(Numbers::Unchanged)
+}
+
+inventory::submit! {
+    StdlibFunction::new("map", "Map a key from an array of dictionaries to an array of values", Arity::Exact(2)).with_numbers(Numbers::Unchanged)
+}
+
+inventory::submit! {
+    StdlibFunction::new("pick", "Keep only the given keys of a dictionary", Arity::Exact(2)).with_numbers(Numbers::Unchanged)
+}
+
+inventory::submit! {
+    StdlibFunction::new("omit", "Remove the given keys from a dictionary", Arity::Exact(2)).with_numbers(Numbers::Unchanged)
+}
+
+inventory::submit! {
+    StdlibFunction::new("has_key", "Check whether a dictionary contains a key, even if its value is nil", Arity::Exact(2)).with_numbers(Numbers::Unchanged).with_types(&[Type::Dictionary, Type::String], Type::Boolean)
+}
+
+inventory::submit! {
+    StdlibFunction::new("get", "Get the value for a key, or the default when the key is missing", Arity::Range(2, 3)).with_numbers(Numbers::Unchanged)
+}
+
+inventory::submit! {
+    StdlibFunction::new("entries", "Convert a dictionary into an array of [key, value] pairs, sorted by key", Arity::Exact(1)).with_numbers(Numbers::Unchanged)
+}
+
+inventory::submit! {
+    StdlibFunction::new("from_entries", "Build a dictionary from an array of [key, value] pairs", Arity::Exact(1)).with_numbers(Numbers::Unchanged)
+}
+
+inventory::submit! {
+    StdlibFunction::new("invert", "Swap the keys and values of a dictionary, stringifying the values", Arity::Exact(1))
+}
+
+inventory::submit! {
+    StdlibFunction::new("dig", "Navigate into nested dictionaries and arrays by a dynamic path", Arity::Exact(2)).with_numbers(Numbers::Unchanged)
+}
+
+inventory::submit! {
+    StdlibFunction::new("union", "Combine two arrays, keeping the first occurrence of each element", Arity::Exact(2)).with_numbers(Numbers::Unchanged)
+}
+
+inventory::submit! {
+    StdlibFunction::new("intersection", "Elements of the first array that also appear in the second", Arity::Exact(2)).with_numbers(Numbers::Unchanged)
+}
+
+inventory::submit! {
+    StdlibFunction::new("difference", "Elements of the first array that do not appear in the second", Arity::Exact(2)).with_numbers(Numbers::Unchanged)
+}
+
+// Logic functions
+inventory::submit! {
+    StdlibFunction::new("choose", "Choose an element from an array by 1-based index (Excel-style)", Arity::Exact(2)).with_numbers(Numbers::Unchanged)
+}
+
+inventory::submit! {
+    StdlibFunction::new("if_then_else", "Conditional expression: if condition is true, return true_val, else return false_val", Arity::Exact(3)).with_numbers(Numbers::Unchanged)
+}
+
+inventory::submit! {
+    StdlibFunction::new("is_number", "Check if a value is a number", Arity::Exact(1))
+}
+
+inventory::submit! {
+    StdlibFunction::new("is_string", "Check if a value is a string", Arity::Exact(1))
+}
+
+inventory::submit! {
+    StdlibFunction::new("is_boolean", "Check if a value is a boolean", Arity::Exact(1))
+}
+
+inventory::submit! {
+    StdlibFunction::new("is_nil", "Check if a value is nil", Arity::Exact(1))
+}
+
+inventory::submit! {
+    StdlibFunction::new("is_array", "Check if a value is an array", Arity::Exact(1))
+}
+
+inventory::submit! {
+    StdlibFunction::new("is_dictionary", "Check if a value is a dictionary", Arity::Exact(1))
+}
+
+inventory::submit! {
+    StdlibFunction::new("coalesce", "Coalesce: return the first non-nil value, or nil if every value is nil", Arity::AtLeast(2)).with_numbers(Numbers::Unchanged)
+}
+
+inventory::submit! {
+    StdlibFunction::new("default", "Default: return value if not nil, else return default", Arity::Exact(2)).with_numbers(Numbers::Unchanged)
+}
+
+inventory::submit! {
+    StdlibFunction::new("between", "Check whether a value lies within a range", Arity::Range(3, 4))
+}
+
+inventory::submit! {
+    StdlibFunction::new("try", "Evaluate the first argument, or the second if the first fails", Arity::Exact(2))
+}
+
+// Conversion functions
+inventory::submit! {
+    StdlibFunction::new("to_number", "Convert a value to a number", Arity::Exact(1))
+}
+
+inventory::submit! {
+    StdlibFunction::new("to_number_strict", "Convert a value to a number, failing on unconvertible input", Arity::Exact(1))
+}
+
+inventory::submit! {
+    StdlibFunction::new("to_string", "Convert a value to a string", Arity::Exact(1))
+}
+
+inventory::submit! {
+    StdlibFunction::new("to_string_strict", "Convert a value to a string, failing on unconvertible input", Arity::Exact(1))
+}
+
+inventory::submit! {
+    StdlibFunction::new("to_boolean", "Convert a value to a boolean", Arity::Exact(1))
+}
+
+inventory::submit! {
+    StdlibFunction::new("to_boolean_strict", "Convert a value to a boolean, failing on unconvertible input", Arity::Exact(1))
+}
+
+inventory::submit! {
+    StdlibFunction::new("parse_json", "Parse a JSON document into a value", Arity::Exact(1))
+}
+
+inventory::submit! {
+    StdlibFunction::new("to_json", "Serialize a value as a compact JSON string", Arity::Exact(1))
+}
+
+// Financial functions - Time Value of Money
+inventory::submit! {
+    StdlibFunction::new("pmt", "Calculate loan payment", Arity::Exact(4)).with_numbers(Numbers::Exact)
+}
+
+inventory::submit! {
+    StdlibFunction::new("pv", "Calculate present value", Arity::Exact(3)).with_numbers(Numbers::Exact)
+}
+
+inventory::submit! {
+    StdlibFunction::new("fv", "Calculate future value", Arity::Exact(4)).with_numbers(Numbers::Exact)
+}
+
+inventory::submit! {
+    StdlibFunction::new("fvschedule", "Calculate future value under a schedule of rates", Arity::Exact(2)).with_numbers(Numbers::Exact)
+}
+
+inventory::submit! {
+    StdlibFunction::new("cagr", "Calculate compound annual growth rate", Arity::Exact(3)).with_numbers(Numbers::FloatsOnly)
+}
+
+inventory::submit! {
+    StdlibFunction::new("compound", "Grow a value at a constant rate over a number of periods", Arity::Exact(3)).with_numbers(Numbers::Exact)
+}
+
+inventory::submit! {
+    StdlibFunction::new("nper", "Calculate number of periods", Arity::Exact(3)).with_numbers(Numbers::FloatsOnly)
+}
+
+inventory::submit! {
+    StdlibFunction::new("rate", "Calculate interest rate", Arity::Exact(3)).with_numbers(Numbers::FloatsOnly)
+}
+
+// Financial functions - Investment Analysis
+inventory::submit! {
+    StdlibFunction::new("npv", "Calculate net present value", Arity::Exact(2)).with_numbers(Numbers::Exact)
+}
+
+inventory::submit! {
+    StdlibFunction::new("irr", "Calculate internal rate of return", Arity::Exact(1)).with_numbers(Numbers::FloatsOnly)
+}
+
+inventory::submit! {
+    StdlibFunction::new("mirr", "Calculate modified internal rate of return", Arity::Exact(3)).with_numbers(Numbers::FloatsOnly)
+}
+
+inventory::submit! {
+    StdlibFunction::new("xnpv", "Calculate net present value of irregularly dated cash flows", Arity::Exact(3)).with_numbers(Numbers::FloatsOnly)
+}
+
+inventory::submit! {
+    StdlibFunction::new("xirr", "Calculate internal rate of return of irregularly dated cash flows", Arity::Range(2, 3)).with_numbers(Numbers::FloatsOnly)
+}
+
+// Financial functions - Loan Schedules
+inventory::submit! {
+    StdlibFunction::new("amortization_schedule", "Build a loan repayment schedule", Arity::Exact(3)).with_numbers(Numbers::Exact)
+}
+
+// Financial functions - Depreciation
+inventory::submit! {
+    StdlibFunction::new("sln", "Calculate straight-line depreciation", Arity::Exact(3)).with_numbers(Numbers::Exact)
+}
+
+inventory::submit! {
+    StdlibFunction::new("ddb", "Calculate double-declining balance depreciation", Arity::Exact(4)).with_numbers(Numbers::FloatsOnly)
+}
+
+inventory::submit! {
+    StdlibFunction::new("db", "Calculate declining balance depreciation", Arity::Exact(5)).with_numbers(Numbers::FloatsOnly)
+}
+
+inventory::submit! {
+    StdlibFunction::new("syd", "Calculate sum-of-years' digits depreciation", Arity::Exact(4)).with_numbers(Numbers::Exact)
+}
+
+inventory::submit! {
+    StdlibFunction::new("vdb", "Calculate variable declining balance depreciation", Arity::Range(5, 7)).with_numbers(Numbers::FloatsOnly)
+}
+
+// Financial functions - Payment Components
+inventory::submit! {
+    StdlibFunction::new("ipmt", "Calculate interest payment for a period", Arity::Exact(5)).with_numbers(Numbers::FloatsOnly)
+}
+
+inventory::submit! {
+    StdlibFunction::new("ppmt", "Calculate principal payment for a period", Arity::Exact(5)).with_numbers(Numbers::FloatsOnly)
+}
+
+inventory::submit! {
+    StdlibFunction::new("cumipmt", "Calculate cumulative interest paid", Arity::Exact(6)).with_numbers(Numbers::FloatsOnly)
+}
+
+inventory::submit! {
+    StdlibFunction::new("cumprinc", "Calculate cumulative principal paid", Arity::Exact(6)).with_numbers(Numbers::FloatsOnly)
+}
+
+// Financial functions - Interest Rate Conversion
+inventory::submit! {
+    StdlibFunction::new("effect", "Calculate effective annual interest rate", Arity::Exact(2)).with_numbers(Numbers::FloatsOnly)
+}
+
+inventory::submit! {
+    StdlibFunction::new("nominal", "Calculate nominal annual interest rate", Arity::Exact(2)).with_numbers(Numbers::FloatsOnly)
+}
+
+inventory::submit! {
+    StdlibFunction::new("apr", "Calculate fee-adjusted annual percentage rate", Arity::Exact(5)).with_numbers(Numbers::FloatsOnly)
+}
+
+inventory::submit! {
+    StdlibFunction::new("apr_effective", "Calculate fee-adjusted effective annual rate", Arity::Exact(5)).with_numbers(Numbers::FloatsOnly)
+}
+
+inventory::submit! {
+    StdlibFunction::new("periodic_to_annual", "Convert a periodic rate to an effective annual rate", Arity::Exact(2)).with_numbers(Numbers::FloatsOnly)
+}
+
+inventory::submit! {
+    StdlibFunction::new("annual_to_periodic", "Convert an effective annual rate to a periodic rate", Arity::Exact(2)).with_numbers(Numbers::FloatsOnly)
+}
+
+// Financial functions - Rate Tables
+inventory::submit! {
+    StdlibFunction::new("tier_lookup", "Look up the rate of the tier a value falls into", Arity::Exact(2)).with_numbers(Numbers::Exact)
+}
+
+inventory::submit! {
+    StdlibFunction::new("tier_marginal", "Apply tiered rates to each band of a value", Arity::Exact(2)).with_numbers(Numbers::Exact)
+}
+
+inventory::submit! {
+    StdlibFunction::new("prorate", "Calculate the pro-rata share of an amount between two dates", Arity::Range(3, 4)).with_numbers(Numbers::Exact)
+}
+
+// Financial functions - Bonds
+inventory::submit! {
+    StdlibFunction::new("price", "Calculate the price of a coupon bond", Arity::Range(6, 7)).with_numbers(Numbers::FloatsOnly)
+}
+
+inventory::submit! {
+    StdlibFunction::new("yield", "Calculate the yield of a coupon bond", Arity::Range(6, 7)).with_numbers(Numbers::FloatsOnly)
+}
+
+inventory::submit! {
+    StdlibFunction::new("duration", "Calculate the Macaulay duration of a bond", Arity::Range(5, 6)).with_numbers(Numbers::FloatsOnly)
+}
+
+inventory::submit! {
+    StdlibFunction::new("mduration", "Calculate the modified duration of a bond", Arity::Range(5, 6)).with_numbers(Numbers::FloatsOnly)
+}
+
+// Date functions
+inventory::submit! {
+    StdlibFunction::new("date_now", "Return the current UTC date from the system clock in YYYY-MM-DD format", Arity::Exact(0)).with_purity(Purity::Contextual)
+}
+
+inventory::submit! {
+    StdlibFunction::new("date_format", "Format a date string according to the given format", Arity::Exact(2))
+}
+
+inventory::submit! {
+    StdlibFunction::new("date_trunc", "Truncate a datetime string to just the date portion (YYYY-MM-DD)", Arity::Exact(1))
+}
+
+inventory::submit! {
+    StdlibFunction::new("date_parse", "Parse a date string in YYYY-MM-DD format and validates it", Arity::Exact(1))
+}
+
+inventory::submit! {
+    StdlibFunction::new("date_add", "Add days, weeks, months, or years to a date", Arity::Range(2, 3))
+}
+
+inventory::submit! {
+    StdlibFunction::new("date_sub", "Subtract days, weeks, months, or years from a date", Arity::Range(2, 3))
+}
+
+inventory::submit! {
+    StdlibFunction::new("date_diff", "Return the difference between two dates in the given unit", Arity::Range(2, 3))
+}
+
+inventory::submit! {
+    StdlibFunction::new("date_before", "Return true if the first date is strictly before the second", Arity::Exact(2))
+}
+
+inventory::submit! {
+    StdlibFunction::new("date_after", "Return true if the first date is strictly after the second", Arity::Exact(2))
+}
+
+inventory::submit! {
+    StdlibFunction::new("date_compare", "Compare two dates, returning -1, 0, or 1", Arity::Exact(2))
+}
+
+inventory::submit! {
+    StdlibFunction::new("duration_days", "Create a duration of whole days", Arity::Exact(1))
+}
+
+inventory::submit! {
+    StdlibFunction::new("duration_weeks", "Create a duration of whole weeks, stored as days", Arity::Exact(1))
+}
+
+inventory::submit! {
+    StdlibFunction::new("duration_months", "Create a duration of whole calendar months", Arity::Exact(1))
+}
+
+inventory::submit! {
+    StdlibFunction::new("duration_years", "Create a duration of whole years, stored as months", Arity::Exact(1))
+}
+
+inventory::submit! {
+    StdlibFunction::new("is_weekend", "Return true if the date falls on a Saturday or Sunday", Arity::Exact(1))
+}
+
+inventory::submit! {
+    StdlibFunction::new("business_days_between", "Count the business days between two dates", Arity::Range(2, 3))
+}
+
+inventory::submit! {
+    StdlibFunction::new("add_business_days", "Move a date forward (or backward) by a number of business days", Arity::Range(2, 3))
+}
+
+inventory::submit! {
+    StdlibFunction::new("beginning_of_month", "Return the first day of the date's month", Arity::Exact(1))
+}
+
+inventory::submit! {
+    StdlibFunction::new("end_of_month", "Return the last day of the date's month", Arity::Exact(1))
+}
+
+inventory::submit! {
+    StdlibFunction::new("beginning_of_quarter", "Return the first day of the date's calendar quarter", Arity::Exact(1))
+}
+
+inventory::submit! {
+    StdlibFunction::new("end_of_quarter", "Return the last day of the date's calendar quarter", Arity::Exact(1))
+}
+
+inventory::submit! {
+    StdlibFunction::new("age", "Return a person's age in whole years on a given date", Arity::Range(1, 2)).with_purity(Purity::Contextual)
+}
+
+inventory::submit! {
+    StdlibFunction::new("iso_week", "Return the ISO 8601 week number (1 to 53) of a date", Arity::Exact(1))
+}
+
+inventory::submit! {
+    StdlibFunction::new("iso_year", "Return the ISO 8601 year a date's week number belongs to", Arity::Exact(1))
+}
+
+inventory::submit! {
+    StdlibFunction::new("quarter", "Return the calendar quarter (1 to 4) of a date", Arity::Exact(1))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+
+    #[test]
+    fn test_lookup() {
+        let round = lookup("round").unwrap();
+        assert_eq!(round.arity, Arity::Range(1, 2));
+        assert!(round.arity.accepts(1) && round.arity.accepts(2));
+        assert!(!round.arity.accepts(3));
+        assert_eq!(round.arity.to_string(), "1-2");
+
+        let coalesce = lookup("coalesce").unwrap();
+        assert_eq!(coalesce.arity.max_args(), None);
+        assert!(coalesce.arity.accepts(10));
+        assert!(!coalesce.arity.accepts(1));
+
+        assert!(lookup("no_such_function").is_none());
+        assert_eq!(check_arity("upcase", 1), Ok(()));
+        let error = check_arity("upcase", 2).unwrap_err();
+        assert_eq!(error.expected, Arity::Exact(1));
+        assert_eq!(
+            error.to_string(),
+            "Function 'upcase' expects 1 arguments, but 2 were provided"
+        );
+        assert_eq!(check_arity("no_such_function", 2), Ok(()));
+
+        assert_eq!(lookup("upcase").unwrap().purity, Purity::Pure);
+        assert_eq!(lookup("date_now").unwrap().purity, Purity::Contextual);
+        assert_eq!(lookup("uuid").unwrap().purity, Purity::Impure);
+
+        assert_eq!(lookup("upcase").unwrap().numbers, Numbers::Floats);
+        assert_eq!(lookup("round").unwrap().numbers, Numbers::Exact);
+        assert_eq!(lookup("first").unwrap().numbers, Numbers::Unchanged);
+        assert_eq!(lookup("irr").unwrap().numbers, Numbers::FloatsOnly);
+
+        let join = lookup("join").unwrap();
+        assert_eq!(join.params, &[Type::Array, Type::String]);
+        assert_eq!(join.returns, Type::String);
+        assert_eq!(lookup("first").unwrap().returns, Type::Any);
+    }
+
+    #[test]
+    fn test_types_fit_arity() {
+        for function in all() {
+            assert!(
+                function
+                    .arity
+                    .max_args()
+                    .is_none_or(|max| function.params.len() <= max),
+                "{} has more parameter types than arguments",
+                function.name
+            );
+        }
+    }
+
+    #[test]
+    fn test_names_are_unique() {
+        let mut names = HashSet::new();
+        for function in all() {
+            assert!(
+                names.insert(function.name),
+                "{} registered twice",
+                function.name
+            );
+        }
+        assert_eq!(names.len(), all().count());
+    }
+}
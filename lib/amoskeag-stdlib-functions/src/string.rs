@@ -1,12 +1,24 @@
 //! String manipulation functions for Amoskeag
 
+use crate::numeric::round::round_half_away;
 use crate::{FunctionError, Value};
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use md5::Md5;
+use regex::Regex;
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::sync::{Mutex, OnceLock};
+use unicode_normalization::{char::is_combining_mark, UnicodeNormalization};
+
+/// Largest string, in bytes, that `repeat` will build
+pub const MAX_REPEAT_LENGTH: usize = 1_000_000;
 
 /// Convert a string to uppercase
 /// upcase(str: String) -> String
 pub fn upcase(value: &Value) -> Result<Value, FunctionError> {
     match value {
-        Value::String(s) => Ok(Value::String(s.to_uppercase())),
+        Value::String(s) => Ok(Value::String(s.to_uppercase().into())),
         _ => Err(FunctionError::TypeError {
             expected: "String".to_string(),
             got: value.type_name().to_string(),
@@ -18,7 +30,7 @@ pub fn upcase(value: &Value) -> Result<Value, FunctionError> {
 /// downcase(str: String) -> String
 pub fn downcase(value: &Value) -> Result<Value, FunctionError> {
     match value {
-        Value::String(s) => Ok(Value::String(s.to_lowercase())),
+        Value::String(s) => Ok(Value::String(s.to_lowercase().into())),
         _ => Err(FunctionError::TypeError {
             expected: "String".to_string(),
             got: value.type_name().to_string(),
@@ -33,13 +45,58 @@ pub fn capitalize(value: &Value) -> Result<Value, FunctionError> {
         Value::String(s) => {
             let mut chars = s.chars();
             match chars.next() {
-                None => Ok(Value::String(String::new())),
+                None => Ok(Value::String("".into())),
                 Some(first) => {
                     let capitalized = first.to_uppercase().collect::<String>()
                         + chars.as_str().to_lowercase().as_str();
-                    Ok(Value::String(capitalized))
+                    Ok(Value::String(capitalized.into()))
+                }
+            }
+        }
+        _ => Err(FunctionError::TypeError {
+            expected: "String".to_string(),
+            got: value.type_name().to_string(),
+        }),
+    }
+}
+
+/// Convert a string to snake_case
+/// snake_case(str: String) -> String
+///
+/// Words are split on punctuation, whitespace, and case changes, so
+/// "policyHolder Name", "PolicyHolderName", and "policy-holder-name" all
+/// become "policy_holder_name".
+pub fn snake_case(value: &Value) -> Result<Value, FunctionError> {
+    match value {
+        Value::String(s) => Ok(Value::String(
+            split_words(s)
+                .iter()
+                .map(|w| w.to_lowercase())
+                .collect::<Vec<_>>()
+                .join("_")
+                .into(),
+        )),
+        _ => Err(FunctionError::TypeError {
+            expected: "String".to_string(),
+            got: value.type_name().to_string(),
+        }),
+    }
+}
+
+/// Convert a string to camelCase
+/// camel_case(str: String) -> String
+pub fn camel_case(value: &Value) -> Result<Value, FunctionError> {
+    match value {
+        Value::String(s) => {
+            let mut result = String::with_capacity(s.len());
+            for (i, word) in split_words(s).iter().enumerate() {
+                if i == 0 {
+                    result.push_str(&word.to_lowercase());
+                } else {
+                    result.push_str(&capitalize_word(word));
                 }
             }
+            Ok(Value::String(result.into()))
         }
         _ => Err(FunctionError::TypeError {
             expected: "String".to_string(),
@@ -48,11 +105,72 @@ pub fn capitalize(value: &Value) -> Result<Value, FunctionError> {
     }
 }
 
+/// Capitalize every word of a string
+/// titlecase(str: String) -> String
+///
+/// Words are separated by whitespace or underscores, which become single
+/// spaces; other punctuation is kept, so "smith-jones" stays hyphenated.
+pub fn titlecase(value: &Value) -> Result<Value, FunctionError> {
+    match value {
+        Value::String(s) => Ok(Value::String(
+            s.split(|c: char| c.is_whitespace() || c == '_')
+                .filter(|w| !w.is_empty())
+                .map(capitalize_word)
+                .collect::<Vec<_>>()
+                .join(" ")
+                .into(),
+        )),
+        _ => Err(FunctionError::TypeError {
+            expected: "String".to_string(),
+            got: value.type_name().to_string(),
+        }),
+    }
+}
+
+/// Uppercase the first character of a word and lowercase the rest
+fn capitalize_word(word: &str) -> String {
+    let mut chars = word.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().collect::<String>() + &chars.as_str().to_lowercase(),
+        None => String::new(),
+    }
+}
+
+/// Split an identifier-like string into words
+///
+/// Breaks on any non-alphanumeric character, on a lowercase-to-uppercase
+/// transition ("policyHolder"), and before the last capital of an acronym
+/// run ("HTTPServer" -> "HTTP", "Server").
+fn split_words(s: &str) -> Vec<String> {
+    let mut words = Vec::new();
+    for chunk in s.split(|c: char| !c.is_alphanumeric()) {
+        let chars: Vec<char> = chunk.chars().collect();
+        let mut current = String::new();
+        for (i, &c) in chars.iter().enumerate() {
+            if i > 0 && c.is_uppercase() {
+                let prev = chars[i - 1];
+                let next_is_lower = chars.get(i + 1).is_some_and(|n| n.is_lowercase());
+                if prev.is_lowercase()
+                    || prev.is_numeric()
+                    || (prev.is_uppercase() && next_is_lower)
+                {
+                    words.push(std::mem::take(&mut current));
+                }
+            }
+            current.push(c);
+        }
+        if !current.is_empty() {
+            words.push(current);
+        }
+    }
+    words
+}
+
 /// Remove leading and trailing whitespace
 /// strip(str: String) -> String
 pub fn strip(value: &Value) -> Result<Value, FunctionError> {
     match value {
-        Value::String(s) => Ok(Value::String(s.trim().to_string())),
+        Value::String(s) => Ok(Value::String(s.trim().into())),
         _ => Err(FunctionError::TypeError {
             expected: "String".to_string(),
             got: value.type_name().to_string(),
@@ -64,7 +182,7 @@ pub fn strip(value: &Value) -> Result<Value, FunctionError> {
 /// lstrip(str: String) -> String
 pub fn lstrip(value: &Value) -> Result<Value, FunctionError> {
     match value {
-        Value::String(s) => Ok(Value::String(s.trim_start().to_string())),
+        Value::String(s) => Ok(Value::String(s.trim_start().into())),
         _ => Err(FunctionError::TypeError {
             expected: "String".to_string(),
             got: value.type_name().to_string(),
@@ -76,7 +194,68 @@ pub fn lstrip(value: &Value) -> Result<Value, FunctionError> {
 /// rstrip(str: String) -> String
 pub fn rstrip(value: &Value) -> Result<Value, FunctionError> {
     match value {
-        Value::String(s) => Ok(Value::String(s.trim_end().to_string())),
+        Value::String(s) => Ok(Value::String(s.trim_end().into())),
+        _ => Err(FunctionError::TypeError {
+            expected: "String".to_string(),
+            got: value.type_name().to_string(),
+        }),
+    }
+}
+
+/// Convert a string into a URL-safe identifier
+/// slugify(str: String) -> String
+///
+/// Lowercases, strips diacritics, spells out letters such as ß and Æ, and
+/// collapses every run of other characters into a single dash: "Café Zoë &
+/// Co." becomes "cafe-zoe-co".
+pub fn slugify(value: &Value) -> Result<Value, FunctionError> {
+    match value {
+        Value::String(s) => {
+            let mut slug = String::with_capacity(s.len());
+            let mut pending_dash = false;
+            let mut buf = [0; 4];
+            for c in s.nfkd().filter(|c| !is_combining_mark(*c)) {
+                let letters = match transliterate(c) {
+                    Some(letters) => letters,
+                    None if c.is_ascii_alphanumeric() => c.encode_utf8(&mut buf),
+                    None => {
+                        pending_dash = true;
+                        continue;
+                    }
+                };
+                if pending_dash && !slug.is_empty() {
+                    slug.push('-');
+                }
+                pending_dash = false;
+                slug.push_str(&letters.to_ascii_lowercase());
+            }
+            Ok(Value::String(slug.into()))
+        }
+        _ => Err(FunctionError::TypeError {
+            expected: "String".to_string(),
+            got: value.type_name().to_string(),
+        }),
+    }
+}
+
+/// The ASCII spelling of a letter that has no decomposition to strip
+fn transliterate(c: char) -> Option<&'static str> {
+    match c {
+        'ß' | 'ẞ' => Some("ss"),
+        'Ø' | 'ø' => Some("o"),
+        'Æ' | 'æ' => Some("ae"),
+        'Œ' | 'œ' => Some("oe"),
+        _ => None,
+    }
+}
+
+/// Trim outer whitespace and collapse internal whitespace runs to one space
+/// squish(str: String) -> String
+pub fn squish(value: &Value) -> Result<Value, FunctionError> {
+    match value {
+        Value::String(s) => Ok(Value::String(
+            s.split_whitespace().collect::<Vec<_>>().join(" ").into(),
+        )),
         _ => Err(FunctionError::TypeError {
             expected: "String".to_string(),
             got: value.type_name().to_string(),
@@ -90,10 +269,10 @@ pub fn split(value: &Value, separator: &Value) -> Result<Value, FunctionError> {
     match (value, separator) {
         (Value::String(s), Value::String(sep)) => {
             let parts: Vec<Value> = s
-                .split(sep.as_str())
-                .map(|part| Value::String(part.to_string()))
+                .split(&**sep)
+                .map(|part| Value::String(part.into()))
                 .collect();
-            Ok(Value::Array(parts))
+            Ok(Value::Array(parts.into()))
         }
         (Value::String(_), _) => Err(FunctionError::TypeError {
             expected: "String".to_string(),
@@ -106,15 +285,52 @@ pub fn split(value: &Value, separator: &Value) -> Result<Value, FunctionError> {
     }
 }
 
+/// Split a string into its characters
+/// chars(str: String) -> Array
+pub fn chars(value: &Value) -> Result<Value, FunctionError> {
+    match value {
+        Value::String(s) => Ok(Value::Array(
+            s.chars()
+                .map(|c| Value::String(c.to_string().into()))
+                .collect::<Vec<_>>()
+                .into(),
+        )),
+        _ => Err(FunctionError::TypeError {
+            expected: "String".to_string(),
+            got: value.type_name().to_string(),
+        }),
+    }
+}
+
+/// Split a string into lines
+/// lines(str: String) -> Array
+///
+/// Accepts both `\n` and `\r\n` line endings; a trailing newline does not
+/// produce an empty final line.
+pub fn lines(value: &Value) -> Result<Value, FunctionError> {
+    match value {
+        Value::String(s) => Ok(Value::Array(
+            s.lines()
+                .map(|l| Value::String(l.into()))
+                .collect::<Vec<_>>()
+                .into(),
+        )),
+        _ => Err(FunctionError::TypeError {
+            expected: "String".to_string(),
+            got: value.type_name().to_string(),
+        }),
+    }
+}
+
 /// Join an array of strings with a separator
 /// join(arr: Array, sep: String) -> String
 pub fn join(array: &Value, separator: &Value) -> Result<Value, FunctionError> {
     match (array, separator) {
         (Value::Array(arr), Value::String(sep)) => {
-            let strings: Result<Vec<String>, FunctionError> = arr
+            let strings: Result<Vec<&str>, FunctionError> = arr
                 .iter()
                 .map(|v| match v {
-                    Value::String(s) => Ok(s.clone()),
+                    Value::String(s) => Ok(&**s),
                     _ => Err(FunctionError::TypeError {
                         expected: "Array of Strings".to_string(),
                         got: format!("Array containing {}", v.type_name()),
@@ -123,7 +339,7 @@ pub fn join(array: &Value, separator: &Value) -> Result<Value, FunctionError> {
                 .collect();
 
             match strings {
-                Ok(strs) => Ok(Value::String(strs.join(sep))),
+                Ok(strs) => Ok(Value::String(strs.join(&**sep).into())),
                 Err(e) => Err(e),
             }
         }
@@ -148,13 +364,178 @@ pub fn truncate(value: &Value, length: &Value) -> Result<Value, FunctionError> {
                 Ok(Value::String(s.clone()))
             } else {
                 let truncated: String = s.chars().take(max_len).collect();
-                Ok(Value::String(truncated))
+                Ok(Value::String(truncated.into()))
+            }
+        }
+        (Value::String(_), _) => Err(FunctionError::TypeError {
+            expected: "Number".to_string(),
+            got: length.type_name().to_string(),
+        }),
+        _ => Err(FunctionError::TypeError {
+            expected: "String".to_string(),
+            got: value.type_name().to_string(),
+        }),
+    }
+}
+
+/// Repeat a string a number of times
+/// repeat(str: String, count: Number) -> String
+///
+/// Fails rather than allocating a result longer than `MAX_REPEAT_LENGTH` bytes.
+pub fn repeat(value: &Value, count: &Value) -> Result<Value, FunctionError> {
+    match (value, count) {
+        (Value::String(s), Value::Number(n)) => {
+            if !n.is_finite() || *n < 0.0 {
+                return Err(FunctionError::ArgumentError {
+                    message: format!("repeat count must be a non-negative number, got {}", n),
+                });
+            }
+            let times = *n as usize;
+            match s.len().checked_mul(times) {
+                Some(len) if len <= MAX_REPEAT_LENGTH => Ok(Value::String(s.repeat(times).into())),
+                _ => Err(FunctionError::ValueError {
+                    message: format!(
+                        "repeat result would exceed the maximum length of {} bytes",
+                        MAX_REPEAT_LENGTH
+                    ),
+                }),
             }
         }
         (Value::String(_), _) => Err(FunctionError::TypeError {
+            expected: "Number".to_string(),
+            got: count.type_name().to_string(),
+        }),
+        _ => Err(FunctionError::TypeError {
+            expected: "String".to_string(),
+            got: value.type_name().to_string(),
+        }),
+    }
+}
+
+/// The length in bytes of `repeat(value, count)`, worked out without
+/// building it
+///
+/// `None` when `repeat` rejects the arguments or the length overflows.
+pub fn repeat_length(value: &Value, count: &Value) -> Option<usize> {
+    match (value, count) {
+        (Value::String(s), Value::Number(n)) if n.is_finite() && *n >= 0.0 => {
+            s.len().checked_mul(*n as usize)
+        }
+        _ => None,
+    }
+}
+
+/// Extract part of a string by character position
+/// substring(str: String, start: Number, length: Number) -> String
+///
+/// Positions count characters, not bytes. A negative start counts back from
+/// the end of the string; ranges past the end are clipped.
+pub fn substring(value: &Value, start: &Value, length: &Value) -> Result<Value, FunctionError> {
+    match (value, start, length) {
+        (Value::String(s), Value::Number(start), Value::Number(len)) => {
+            let char_count = s.chars().count() as i64;
+            let start = *start as i64;
+            let start = if start < 0 {
+                (char_count + start).max(0)
+            } else {
+                start
+            } as usize;
+            let len = (*len).max(0.0) as usize;
+            Ok(Value::String(
+                s.chars().skip(start).take(len).collect::<String>().into(),
+            ))
+        }
+        (Value::String(_), Value::Number(_), _) => Err(FunctionError::TypeError {
             expected: "Number".to_string(),
             got: length.type_name().to_string(),
         }),
+        (Value::String(_), _, _) => Err(FunctionError::TypeError {
+            expected: "Number".to_string(),
+            got: start.type_name().to_string(),
+        }),
+        _ => Err(FunctionError::TypeError {
+            expected: "String".to_string(),
+            got: value.type_name().to_string(),
+        }),
+    }
+}
+
+/// Pad a string on the left to a minimum width
+/// pad_left(str: String, width: Number, fill: String) -> String
+///
+/// Width is measured in characters. The fill must be a single character;
+/// strings already at or beyond the width are returned unchanged. Fails rather
+/// than allocating a result longer than `MAX_REPEAT_LENGTH` bytes.
+pub fn pad_left(value: &Value, width: &Value, fill: &Value) -> Result<Value, FunctionError> {
+    let (s, padding) = pad_args(value, width, fill)?;
+    Ok(Value::String(format!("{}{}", padding, s).into()))
+}
+
+/// Pad a string on the right to a minimum width
+/// pad_right(str: String, width: Number, fill: String) -> String
+pub fn pad_right(value: &Value, width: &Value, fill: &Value) -> Result<Value, FunctionError> {
+    let (s, padding) = pad_args(value, width, fill)?;
+    Ok(Value::String(format!("{}{}", s, padding).into()))
+}
+
+/// The length in bytes of `pad_left` or `pad_right` with these arguments,
+/// worked out without building it
+///
+/// `None` when padding rejects the arguments or the length overflows.
+pub fn pad_length(value: &Value, width: &Value, fill: &Value) -> Option<usize> {
+    match (value, width, fill) {
+        (Value::String(s), Value::Number(w), Value::String(f)) if f.chars().count() == 1 => {
+            let missing = ((*w).max(0.0) as usize).saturating_sub(s.chars().count());
+            missing
+                .checked_mul(f.len())
+                .and_then(|padding| padding.checked_add(s.len()))
+        }
+        _ => None,
+    }
+}
+
+/// Validate padding arguments and build the fill string
+fn pad_args<'a>(
+    value: &'a Value,
+    width: &Value,
+    fill: &Value,
+) -> Result<(&'a str, String), FunctionError> {
+    match (value, width, fill) {
+        (Value::String(s), Value::Number(w), Value::String(f)) => {
+            let mut fill_chars = f.chars();
+            let fill_char = match (fill_chars.next(), fill_chars.next()) {
+                (Some(c), None) => c,
+                _ => {
+                    return Err(FunctionError::ArgumentError {
+                        message: format!("fill must be a single character, got '{}'", f),
+                    })
+                }
+            };
+            let target = (*w).max(0.0) as usize;
+            let missing = target.saturating_sub(s.chars().count());
+            let len = missing
+                .checked_mul(fill_char.len_utf8())
+                .and_then(|padding| padding.checked_add(s.len()));
+            match len {
+                Some(len) if missing == 0 || len <= MAX_REPEAT_LENGTH => {
+                    Ok((s, std::iter::repeat_n(fill_char, missing).collect()))
+                }
+                _ => Err(FunctionError::ValueError {
+                    message: format!(
+                        "pad result would exceed the maximum length of {} bytes",
+                        MAX_REPEAT_LENGTH
+                    ),
+                }),
+            }
+        }
+        (Value::String(_), Value::Number(_), _) => Err(FunctionError::TypeError {
+            expected: "String".to_string(),
+            got: fill.type_name().to_string(),
+        }),
+        (Value::String(_), _, _) => Err(FunctionError::TypeError {
+            expected: "Number".to_string(),
+            got: width.type_name().to_string(),
+        }),
         _ => Err(FunctionError::TypeError {
             expected: "String".to_string(),
             got: value.type_name().to_string(),
@@ -167,7 +548,7 @@ pub fn truncate(value: &Value, length: &Value) -> Result<Value, FunctionError> {
 pub fn replace(value: &Value, find: &Value, replacement: &Value) -> Result<Value, FunctionError> {
     match (value, find, replacement) {
         (Value::String(s), Value::String(f), Value::String(r)) => {
-            Ok(Value::String(s.replace(f, r)))
+            Ok(Value::String(s.replace(&**f, r).into()))
         }
         (Value::String(_), Value::String(_), _) => Err(FunctionError::TypeError {
             expected: "String".to_string(),
@@ -184,92 +565,1441 @@ pub fn replace(value: &Value, find: &Value, replacement: &Value) -> Result<Value
     }
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+/// Check whether a string begins with a prefix
+/// starts_with(str: String, prefix: String) -> Boolean
+pub fn starts_with(value: &Value, prefix: &Value) -> Result<Value, FunctionError> {
+    match (value, prefix) {
+        (Value::String(s), Value::String(p)) => Ok(Value::Boolean(s.starts_with(&**p))),
+        (Value::String(_), _) => Err(FunctionError::TypeError {
+            expected: "String".to_string(),
+            got: prefix.type_name().to_string(),
+        }),
+        _ => Err(FunctionError::TypeError {
+            expected: "String".to_string(),
+            got: value.type_name().to_string(),
+        }),
+    }
+}
 
-    #[test]
-    fn test_upcase() {
-        let result = upcase(&Value::String("hello".to_string())).unwrap();
-        assert_eq!(result, Value::String("HELLO".to_string()));
+/// Check whether a string ends with a suffix
+/// ends_with(str: String, suffix: String) -> Boolean
+pub fn ends_with(value: &Value, suffix: &Value) -> Result<Value, FunctionError> {
+    match (value, suffix) {
+        (Value::String(s), Value::String(x)) => Ok(Value::Boolean(s.ends_with(&**x))),
+        (Value::String(_), _) => Err(FunctionError::TypeError {
+            expected: "String".to_string(),
+            got: suffix.type_name().to_string(),
+        }),
+        _ => Err(FunctionError::TypeError {
+            expected: "String".to_string(),
+            got: value.type_name().to_string(),
+        }),
     }
+}
 
-    #[test]
-    fn test_downcase() {
-        let result = downcase(&Value::String("HELLO".to_string())).unwrap();
-        assert_eq!(result, Value::String("hello".to_string()));
+/// Find the character position of the first occurrence of a substring
+/// index_of(str: String, substr: String) -> Number | Nil
+///
+/// Returns nil when the substring does not occur.
+pub fn index_of(value: &Value, substr: &Value) -> Result<Value, FunctionError> {
+    let (s, needle) = string_pair(value, substr)?;
+    Ok(s.find(needle)
+        .map(|byte_index| Value::Int(s[..byte_index].chars().count() as i64))
+        .unwrap_or(Value::Nil))
+}
+
+/// Check whether a string contains a substring
+/// includes(str: String, substr: String) -> Boolean
+pub fn includes(value: &Value, substr: &Value) -> Result<Value, FunctionError> {
+    let (s, needle) = string_pair(value, substr)?;
+    Ok(Value::Boolean(s.contains(needle)))
+}
+
+/// Count the single-character edits needed to turn one string into another
+/// levenshtein(a: String, b: String) -> Number
+pub fn levenshtein(left: &Value, right: &Value) -> Result<Value, FunctionError> {
+    let (a, b) = string_pair(left, right)?;
+    Ok(Value::Int(edit_distance(a, b) as i64))
+}
+
+/// Score how similar two strings are, from 0 (nothing shared) to 1 (identical)
+/// similarity(a: String, b: String) -> Number
+///
+/// Computed as one minus the edit distance divided by the longer length.
+pub fn similarity(left: &Value, right: &Value) -> Result<Value, FunctionError> {
+    let (a, b) = string_pair(left, right)?;
+    let longest = a.chars().count().max(b.chars().count());
+    if longest == 0 {
+        return Ok(Value::Number(1.0));
     }
+    Ok(Value::Number(
+        1.0 - edit_distance(a, b) as f64 / longest as f64,
+    ))
+}
 
-    #[test]
-    fn test_capitalize() {
-        let result = capitalize(&Value::String("hello world".to_string())).unwrap();
-        assert_eq!(result, Value::String("Hello world".to_string()));
+/// Character-based Levenshtein distance using a single rolling row
+fn edit_distance(a: &str, b: &str) -> usize {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+    let mut row: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.iter().enumerate() {
+        let mut diagonal = row[0];
+        row[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let above = row[j + 1];
+            let cost = if ca == cb { 0 } else { 1 };
+            row[j + 1] = (above + 1).min(row[j] + 1).min(diagonal + cost);
+            diagonal = above;
+        }
     }
+    row[b.len()]
+}
 
-    #[test]
-    fn test_strip() {
-        let result = strip(&Value::String("  hello  ".to_string())).unwrap();
-        assert_eq!(result, Value::String("hello".to_string()));
+/// Unpack two String arguments
+fn string_pair<'a>(left: &'a Value, right: &'a Value) -> Result<(&'a str, &'a str), FunctionError> {
+    match (left, right) {
+        (Value::String(a), Value::String(b)) => Ok((a, b)),
+        (Value::String(_), _) => Err(FunctionError::TypeError {
+            expected: "String".to_string(),
+            got: right.type_name().to_string(),
+        }),
+        _ => Err(FunctionError::TypeError {
+            expected: "String".to_string(),
+            got: left.type_name().to_string(),
+        }),
     }
+}
 
-    #[test]
-    fn test_lstrip() {
-        let result = lstrip(&Value::String("  hello  ".to_string())).unwrap();
-        assert_eq!(result, Value::String("hello  ".to_string()));
+/// Format values into a template string
+/// format(template: String, ...args) -> String
+///
+/// Supports a safe subset of printf specifiers: `%s` (any value), `%d`
+/// (Number, truncated to an integer), `%f` and `%.Nf` (Number with N
+/// decimal places, default 6, halves rounded away from zero as in `round`),
+/// and `%%` for a literal percent sign.
+pub fn format(template: &Value, args: &[Value]) -> Result<Value, FunctionError> {
+    let template = match template {
+        Value::String(t) => t,
+        _ => {
+            return Err(FunctionError::TypeError {
+                expected: "String".to_string(),
+                got: template.type_name().to_string(),
+            })
+        }
+    };
+
+    let mut output = String::with_capacity(template.len());
+    let mut remaining = args.iter();
+    let mut chars = template.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        if c != '%' {
+            output.push(c);
+            continue;
+        }
+
+        let mut precision: Option<usize> = None;
+        if chars.peek() == Some(&'.') {
+            chars.next();
+            let mut digits = String::new();
+            while let Some(d) = chars.peek().filter(|d| d.is_ascii_digit()) {
+                digits.push(*d);
+                chars.next();
+            }
+            precision = match digits.parse::<usize>() {
+                Ok(p) if p <= 20 => Some(p),
+                _ => {
+                    return Err(FunctionError::ArgumentError {
+                        message: format!("invalid precision '.{}' in format string", digits),
+                    })
+                }
+            };
+        }
+
+        let spec = chars.next();
+        if spec == Some('%') && precision.is_none() {
+            output.push('%');
+            continue;
+        }
+
+        let arg = match spec {
+            Some('s') | Some('d') | Some('f') => {
+                remaining
+                    .next()
+                    .ok_or_else(|| FunctionError::ArgumentError {
+                        message: "not enough arguments for format string".to_string(),
+                    })?
+            }
+            Some(other) => {
+                return Err(FunctionError::ArgumentError {
+                    message: format!("unsupported format specifier '%{}'", other),
+                })
+            }
+            None => {
+                return Err(FunctionError::ArgumentError {
+                    message: "format string ends with an incomplete specifier".to_string(),
+                })
+            }
+        };
+
+        match (spec, arg, precision) {
+            (Some('s'), _, None) => output.push_str(&arg.to_string()),
+            (Some('d'), Value::Number(n), None) => output.push_str(&format!("{}", n.trunc())),
+            (Some('f'), Value::Number(n), p) => {
+                let places = p.unwrap_or(6);
+                output.push_str(&format!(
+                    "{:.*}",
+                    places,
+                    round_half_away(*n, places as i32)
+                ))
+            }
+            (Some('d'), _, None) | (Some('f'), _, _) => {
+                return Err(FunctionError::TypeError {
+                    expected: "Number".to_string(),
+                    got: arg.type_name().to_string(),
+                })
+            }
+            _ => {
+                return Err(FunctionError::ArgumentError {
+                    message: "precision is only supported with '%f'".to_string(),
+                })
+            }
+        }
     }
 
-    #[test]
-    fn test_rstrip() {
-        let result = rstrip(&Value::String("  hello  ".to_string())).unwrap();
-        assert_eq!(result, Value::String("  hello".to_string()));
+    if remaining.next().is_some() {
+        return Err(FunctionError::ArgumentError {
+            message: "too many arguments for format string".to_string(),
+        });
     }
 
-    #[test]
-    fn test_split() {
-        let result = split(
-            &Value::String("a,b,c".to_string()),
-            &Value::String(",".to_string()),
-        )
-        .unwrap();
-        assert_eq!(
-            result,
-            Value::Array(vec![
-                Value::String("a".to_string()),
-                Value::String("b".to_string()),
-                Value::String("c".to_string()),
-            ])
-        );
+    Ok(Value::String(output.into()))
+}
+
+/// How many compiled patterns `compile_pattern` keeps; the least recently
+/// used is dropped when full, so patterns read from data cannot grow it
+/// without bound
+const PATTERN_CACHE_SIZE: usize = 256;
+
+/// Compiled patterns, each with when it was last used
+#[derive(Default)]
+struct PatternCache {
+    patterns: HashMap<String, (Regex, u64)>,
+    /// Counts uses, so a larger stamp is a more recent use
+    clock: u64,
+}
+
+impl PatternCache {
+    fn get(&mut self, pattern: &str) -> Option<Regex> {
+        self.clock += 1;
+        let (re, used) = self.patterns.get_mut(pattern)?;
+        *used = self.clock;
+        Some(re.clone())
     }
 
-    #[test]
-    fn test_join() {
-        let arr = Value::Array(vec![
-            Value::String("a".to_string()),
-            Value::String("b".to_string()),
-            Value::String("c".to_string()),
-        ]);
-        let result = join(&arr, &Value::String(",".to_string())).unwrap();
-        assert_eq!(result, Value::String("a,b,c".to_string()));
+    fn insert(&mut self, pattern: &str, re: Regex) {
+        if self.patterns.len() >= PATTERN_CACHE_SIZE && !self.patterns.contains_key(pattern) {
+            let oldest = self
+                .patterns
+                .iter()
+                .min_by_key(|(_, (_, used))| *used)
+                .map(|(oldest, _)| oldest.clone());
+            if let Some(oldest) = oldest {
+                self.patterns.remove(&oldest);
+            }
+        }
+        self.clock += 1;
+        self.patterns.insert(pattern.to_string(), (re, self.clock));
+    }
+}
+
+/// Compile a regular expression pattern, reusing it if it was compiled before
+///
+/// Exposed so the compiler can reject invalid literal patterns before evaluation.
+pub fn compile_pattern(pattern: &str) -> Result<Regex, FunctionError> {
+    static PATTERNS: OnceLock<Mutex<PatternCache>> = OnceLock::new();
+    let patterns = PATTERNS.get_or_init(Default::default);
+    let lock = || patterns.lock().unwrap_or_else(|e| e.into_inner());
+    if let Some(re) = lock().get(pattern) {
+        return Ok(re);
     }
 
-    #[test]
-    fn test_truncate() {
-        let result = truncate(
-            &Value::String("hello world".to_string()),
-            &Value::Number(5.0),
-        )
-        .unwrap();
-        assert_eq!(result, Value::String("hello".to_string()));
+    let re = Regex::new(pattern).map_err(|e| FunctionError::InvalidPattern {
+        pattern: pattern.to_string(),
+        message: e.to_string(),
+    })?;
+    lock().insert(pattern, re.clone());
+    Ok(re)
+}
+
+/// Unpack a String subject and String pattern for the regex functions
+fn regex_args<'a>(value: &'a Value, pattern: &Value) -> Result<(&'a str, Regex), FunctionError> {
+    match (value, pattern) {
+        (Value::String(s), Value::String(p)) => Ok((s, compile_pattern(p)?)),
+        (Value::String(_), _) => Err(FunctionError::TypeError {
+            expected: "String".to_string(),
+            got: pattern.type_name().to_string(),
+        }),
+        _ => Err(FunctionError::TypeError {
+            expected: "String".to_string(),
+            got: value.type_name().to_string(),
+        }),
     }
+}
 
-    #[test]
-    fn test_replace() {
-        let result = replace(
-            &Value::String("hello world".to_string()),
-            &Value::String("world".to_string()),
-            &Value::String("rust".to_string()),
+/// Test whether a string matches a regular expression
+/// regex_match(str: String, pattern: String) -> Boolean
+pub fn regex_match(value: &Value, pattern: &Value) -> Result<Value, FunctionError> {
+    let (s, re) = regex_args(value, pattern)?;
+    Ok(Value::Boolean(re.is_match(s)))
+}
+
+/// Replace all matches of a regular expression
+/// regex_replace(str: String, pattern: String, replacement: String) -> String
+///
+/// The replacement may refer to capture groups as `$1` or `${name}`.
+pub fn regex_replace(
+    value: &Value,
+    pattern: &Value,
+    replacement: &Value,
+) -> Result<Value, FunctionError> {
+    let (s, re) = regex_args(value, pattern)?;
+    match replacement {
+        Value::String(r) => Ok(Value::String(re.replace_all(s, &**r).into_owned().into())),
+        _ => Err(FunctionError::TypeError {
+            expected: "String".to_string(),
+            got: replacement.type_name().to_string(),
+        }),
+    }
+}
+
+/// Extract the first match of a regular expression
+/// regex_extract(str: String, pattern: String) -> String | Nil
+///
+/// Returns the first capture group when the pattern has one, otherwise the
+/// whole match. Returns nil when nothing matches.
+pub fn regex_extract(value: &Value, pattern: &Value) -> Result<Value, FunctionError> {
+    let (s, re) = regex_args(value, pattern)?;
+    let extracted = re.captures(s).and_then(|caps| {
+        if caps.len() > 1 {
+            caps.get(1)
+        } else {
+            caps.get(0)
+        }
+    });
+    Ok(extracted
+        .map(|m| Value::String(m.as_str().into()))
+        .unwrap_or(Value::Nil))
+}
+
+/// Check whether a string looks like an email address
+/// is_email(val: Any) -> Boolean
+///
+/// A pragmatic check (local part, `@`, dotted domain), not full RFC 5322.
+/// Non-string values are never email addresses.
+pub fn is_email(value: &Value) -> Value {
+    static EMAIL: OnceLock<Regex> = OnceLock::new();
+    let re = EMAIL.get_or_init(|| {
+        Regex::new(
+            r"^[A-Za-z0-9.!#$%&'*+/=?^_`{|}~-]+@[A-Za-z0-9](?:[A-Za-z0-9-]{0,61}[A-Za-z0-9])?(?:\.[A-Za-z0-9](?:[A-Za-z0-9-]{0,61}[A-Za-z0-9])?)+$",
+        )
+        .expect("email pattern is valid")
+    });
+    Value::Boolean(matches!(value, Value::String(s) if re.is_match(s)))
+}
+
+/// The strings `is_numeric_string` accepts, in a syntax Python and
+/// JavaScript regular expressions also understand
+///
+/// Digits are spelled `[0-9]`, since `\d` also matches other scripts'
+/// digits in Rust and Python, which `to_number` cannot convert.
+pub const NUMERIC_PATTERN: &str = r"^[+-]?(?:[0-9]+\.?[0-9]*|\.[0-9]+)(?:[eE][+-]?[0-9]+)?$";
+
+/// Check whether a string is a plain decimal number
+/// is_numeric_string(val: Any) -> Boolean
+///
+/// Accepts an optional sign, ASCII digits with an optional fractional part,
+/// and an optional exponent, which are exactly the strings `to_number`
+/// converts. Surrounding whitespace, "NaN", "inf", and digits from other
+/// scripts are rejected.
+pub fn is_numeric_string(value: &Value) -> Value {
+    static NUMERIC: OnceLock<Regex> = OnceLock::new();
+    let re = NUMERIC.get_or_init(|| Regex::new(NUMERIC_PATTERN).expect("numeric pattern is valid"));
+    Value::Boolean(matches!(value, Value::String(s) if re.is_match(s)))
+}
+
+/// Check whether a card or ID number passes the Luhn checksum
+/// luhn_valid(str: String) -> Boolean
+///
+/// Spaces and hyphens between digits are ignored. Any other character, or
+/// fewer than two digits, makes the number invalid. Non-string values are
+/// a type error, so numbers that lost leading zeros are never accepted.
+pub fn luhn_valid(value: &Value) -> Result<Value, FunctionError> {
+    let s = match value {
+        Value::String(s) => s,
+        _ => {
+            return Err(FunctionError::TypeError {
+                expected: "String".to_string(),
+                got: value.type_name().to_string(),
+            })
+        }
+    };
+
+    let mut digits = Vec::with_capacity(s.len());
+    for c in s.chars().filter(|c| *c != ' ' && *c != '-') {
+        match c.to_digit(10) {
+            Some(d) => digits.push(d),
+            None => return Ok(Value::Boolean(false)),
+        }
+    }
+    if digits.len() < 2 {
+        return Ok(Value::Boolean(false));
+    }
+
+    let checksum: u32 = digits
+        .iter()
+        .rev()
+        .enumerate()
+        .map(|(i, d)| match (i % 2, d * 2) {
+            (0, _) => *d,
+            (_, doubled) if doubled > 9 => doubled - 9,
+            (_, doubled) => doubled,
+        })
+        .sum();
+    Ok(Value::Boolean(checksum.is_multiple_of(10)))
+}
+
+/// Check whether an entire string matches a regular expression
+/// matches_pattern(str: String, pattern: String) -> Boolean
+///
+/// Unlike `regex_match`, the pattern must match the whole string.
+pub fn matches_pattern(value: &Value, pattern: &Value) -> Result<Value, FunctionError> {
+    let (s, re) = regex_args(value, pattern)?;
+    let anchored = compile_pattern(&format!("^(?:{})$", re.as_str()))?;
+    Ok(Value::Boolean(anchored.is_match(s)))
+}
+
+/// Hash a string with SHA-256
+/// sha256(str: String) -> String
+///
+/// Returns the lowercase hex digest of the string's UTF-8 bytes.
+pub fn sha256(value: &Value) -> Result<Value, FunctionError> {
+    hex_digest::<Sha256>(value)
+}
+
+/// Hash a string with MD5
+/// md5(str: String) -> String
+///
+/// Returns the lowercase hex digest of the string's UTF-8 bytes. MD5 is not
+/// collision resistant; prefer `sha256` for anything security sensitive.
+pub fn md5(value: &Value) -> Result<Value, FunctionError> {
+    hex_digest::<Md5>(value)
+}
+
+fn hex_digest<D: Digest>(value: &Value) -> Result<Value, FunctionError> {
+    match value {
+        Value::String(s) => {
+            let digest = D::digest(s.as_bytes());
+            let hex: String = digest.iter().map(|byte| format!("{:02x}", byte)).collect();
+            Ok(Value::String(hex.into()))
+        }
+        _ => Err(FunctionError::TypeError {
+            expected: "String".to_string(),
+            got: value.type_name().to_string(),
+        }),
+    }
+}
+
+/// Encode a string as standard, padded Base64
+/// base64_encode(str: String) -> String
+pub fn base64_encode(value: &Value) -> Result<Value, FunctionError> {
+    match value {
+        Value::String(s) => Ok(Value::String(BASE64.encode(s.as_bytes()).into())),
+        _ => Err(FunctionError::TypeError {
+            expected: "String".to_string(),
+            got: value.type_name().to_string(),
+        }),
+    }
+}
+
+/// Decode a standard, padded Base64 string
+/// base64_decode(str: String) -> String
+///
+/// Fails if the input is not valid Base64 or does not decode to UTF-8 text.
+pub fn base64_decode(value: &Value) -> Result<Value, FunctionError> {
+    let s = match value {
+        Value::String(s) => s,
+        _ => {
+            return Err(FunctionError::TypeError {
+                expected: "String".to_string(),
+                got: value.type_name().to_string(),
+            })
+        }
+    };
+    let bytes = BASE64
+        .decode(s.as_bytes())
+        .map_err(|e| FunctionError::ValueError {
+            message: format!("invalid Base64 input: {}", e),
+        })?;
+    String::from_utf8(bytes)
+        .map(|s| Value::String(s.into()))
+        .map_err(|_| FunctionError::ValueError {
+            message: "Base64 input does not decode to UTF-8 text".to_string(),
+        })
+}
+
+/// Percent-encode a string for use in a URL component
+/// url_encode(str: String) -> String
+///
+/// Every byte outside the RFC 3986 unreserved set (letters, digits, and
+/// `-._~`) is encoded, so the result is safe in paths and query values.
+pub fn url_encode(value: &Value) -> Result<Value, FunctionError> {
+    let s = match value {
+        Value::String(s) => s,
+        _ => {
+            return Err(FunctionError::TypeError {
+                expected: "String".to_string(),
+                got: value.type_name().to_string(),
+            })
+        }
+    };
+    let mut encoded = String::with_capacity(s.len());
+    for byte in s.bytes() {
+        if byte.is_ascii_alphanumeric() || b"-._~".contains(&byte) {
+            encoded.push(byte as char);
+        } else {
+            encoded.push_str(&format!("%{:02X}", byte));
+        }
+    }
+    Ok(Value::String(encoded.into()))
+}
+
+/// Decode a percent-encoded URL component
+/// url_decode(str: String) -> String
+///
+/// `+` is left as-is rather than read as a space. Fails on malformed `%`
+/// escapes or if the decoded bytes are not UTF-8.
+pub fn url_decode(value: &Value) -> Result<Value, FunctionError> {
+    let s = match value {
+        Value::String(s) => s,
+        _ => {
+            return Err(FunctionError::TypeError {
+                expected: "String".to_string(),
+                got: value.type_name().to_string(),
+            })
+        }
+    };
+    let bytes = s.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'%' {
+            let escape = bytes
+                .get(i + 1..i + 3)
+                .and_then(|hex| std::str::from_utf8(hex).ok())
+                .and_then(|hex| u8::from_str_radix(hex, 16).ok())
+                .ok_or_else(|| FunctionError::ValueError {
+                    message: format!("invalid percent escape at position {}", i),
+                })?;
+            decoded.push(escape);
+            i += 3;
+        } else {
+            decoded.push(bytes[i]);
+            i += 1;
+        }
+    }
+    String::from_utf8(decoded)
+        .map(|s| Value::String(s.into()))
+        .map_err(|_| FunctionError::ValueError {
+            message: "URL input does not decode to UTF-8 text".to_string(),
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_upcase() {
+        let result = upcase(&Value::String("hello".into())).unwrap();
+        assert_eq!(result, Value::String("HELLO".into()));
+    }
+
+    #[test]
+    fn test_downcase() {
+        let result = downcase(&Value::String("HELLO".into())).unwrap();
+        assert_eq!(result, Value::String("hello".into()));
+    }
+
+    #[test]
+    fn test_capitalize() {
+        let result = capitalize(&Value::String("hello world".into())).unwrap();
+        assert_eq!(result, Value::String("Hello world".into()));
+    }
+
+    #[test]
+    fn test_strip() {
+        let result = strip(&Value::String("  hello  ".into())).unwrap();
+        assert_eq!(result, Value::String("hello".into()));
+    }
+
+    #[test]
+    fn test_lstrip() {
+        let result = lstrip(&Value::String("  hello  ".into())).unwrap();
+        assert_eq!(result, Value::String("hello  ".into()));
+    }
+
+    #[test]
+    fn test_rstrip() {
+        let result = rstrip(&Value::String("  hello  ".into())).unwrap();
+        assert_eq!(result, Value::String("  hello".into()));
+    }
+
+    #[test]
+    fn test_split() {
+        let result = split(&Value::String("a,b,c".into()), &Value::String(",".into())).unwrap();
+        assert_eq!(
+            result,
+            Value::Array(
+                vec![
+                    Value::String("a".into()),
+                    Value::String("b".into()),
+                    Value::String("c".into()),
+                ]
+                .into()
+            )
+        );
+    }
+
+    #[test]
+    fn test_join() {
+        let arr = Value::Array(
+            vec![
+                Value::String("a".into()),
+                Value::String("b".into()),
+                Value::String("c".into()),
+            ]
+            .into(),
+        );
+        let result = join(&arr, &Value::String(",".into())).unwrap();
+        assert_eq!(result, Value::String("a,b,c".into()));
+    }
+
+    #[test]
+    fn test_truncate() {
+        let result = truncate(&Value::String("hello world".into()), &Value::Number(5.0)).unwrap();
+        assert_eq!(result, Value::String("hello".into()));
+    }
+
+    #[test]
+    fn test_replace() {
+        let result = replace(
+            &Value::String("hello world".into()),
+            &Value::String("world".into()),
+            &Value::String("rust".into()),
+        )
+        .unwrap();
+        assert_eq!(result, Value::String("hello rust".into()));
+    }
+
+    #[test]
+    fn test_regex_match() {
+        let zip = Value::String("03101-1234".into());
+        let pattern = Value::String(r"^\d{5}(-\d{4})?$".into());
+        assert_eq!(regex_match(&zip, &pattern).unwrap(), Value::Boolean(true));
+        assert_eq!(
+            regex_match(&Value::String("0310".into()), &pattern).unwrap(),
+            Value::Boolean(false)
+        );
+        assert!(matches!(
+            regex_match(&Value::Number(3101.0), &pattern),
+            Err(FunctionError::TypeError { .. })
+        ));
+    }
+
+    #[test]
+    fn test_regex_replace() {
+        let phone = Value::String("(603) 555-0100".into());
+        let result = regex_replace(
+            &phone,
+            &Value::String(r"\D".into()),
+            &Value::String("".into()),
+        )
+        .unwrap();
+        assert_eq!(result, Value::String("6035550100".into()));
+
+        let result = regex_replace(
+            &Value::String("Smith, John".into()),
+            &Value::String(r"(\w+), (\w+)".into()),
+            &Value::String("$2 $1".into()),
+        )
+        .unwrap();
+        assert_eq!(result, Value::String("John Smith".into()));
+    }
+
+    #[test]
+    fn test_regex_extract() {
+        let policy = Value::String("Policy HO-2024-0042 renewed".into());
+        assert_eq!(
+            regex_extract(&policy, &Value::String(r"HO-\d{4}-\d+".into())).unwrap(),
+            Value::String("HO-2024-0042".into())
+        );
+        assert_eq!(
+            regex_extract(&policy, &Value::String(r"HO-(\d{4})".into())).unwrap(),
+            Value::String("2024".into())
+        );
+        assert_eq!(
+            regex_extract(&policy, &Value::String("AUTO".into())).unwrap(),
+            Value::Nil
+        );
+    }
+
+    #[test]
+    fn test_regex_invalid_pattern() {
+        let result = regex_match(
+            &Value::String("abc".into()),
+            &Value::String("(unclosed".into()),
+        );
+        assert!(matches!(
+            result,
+            Err(FunctionError::InvalidPattern { ref pattern, .. }) if pattern == "(unclosed"
+        ));
+    }
+
+    #[test]
+    fn test_compile_pattern_cache() {
+        assert_eq!(compile_pattern("a+b").unwrap().as_str(), "a+b");
+        assert_eq!(compile_pattern("a+b").unwrap().as_str(), "a+b");
+        // Invalid patterns fail every time, not only when first compiled
+        assert!(compile_pattern("(unclosed").is_err());
+        assert!(compile_pattern("(unclosed").is_err());
+
+        for i in 0..=PATTERN_CACHE_SIZE {
+            compile_pattern(&format!("x{}", i)).unwrap();
+        }
+        assert!(compile_pattern("a+b").unwrap().is_match("aab"));
+    }
+
+    #[test]
+    fn test_pattern_cache_drops_least_recently_used() {
+        let mut cache = PatternCache::default();
+        let compiled = |i: usize| Regex::new(&format!("x{}", i)).unwrap();
+        for i in 0..PATTERN_CACHE_SIZE {
+            cache.insert(&format!("x{}", i), compiled(i));
+        }
+        // x0 is used again, so x1 is now the least recently used
+        assert!(cache.get("x0").is_some());
+        cache.insert("new", compiled(PATTERN_CACHE_SIZE));
+
+        assert_eq!(cache.patterns.len(), PATTERN_CACHE_SIZE);
+        assert!(cache.get("x0").is_some());
+        assert!(cache.get("x1").is_none());
+        assert!(cache.get("new").is_some());
+        assert!(cache.get("x2").is_some());
+    }
+
+    #[test]
+    fn test_starts_with() {
+        let code = Value::String("HO-3 Special Form".into());
+        assert_eq!(
+            starts_with(&code, &Value::String("HO-".into())).unwrap(),
+            Value::Boolean(true)
+        );
+        assert_eq!(
+            starts_with(&code, &Value::String("DP-".into())).unwrap(),
+            Value::Boolean(false)
+        );
+        assert!(matches!(
+            starts_with(&code, &Value::Nil),
+            Err(FunctionError::TypeError { .. })
+        ));
+    }
+
+    #[test]
+    fn test_ends_with() {
+        let file = Value::String("declarations.pdf".into());
+        assert_eq!(
+            ends_with(&file, &Value::String(".pdf".into())).unwrap(),
+            Value::Boolean(true)
+        );
+        assert_eq!(
+            ends_with(&file, &Value::String(".doc".into())).unwrap(),
+            Value::Boolean(false)
+        );
+        assert!(matches!(
+            ends_with(&Value::Number(1.0), &Value::String("1".into())),
+            Err(FunctionError::TypeError { .. })
+        ));
+    }
+
+    #[test]
+    fn test_substring() {
+        let s = Value::String("Amoskeag Mills".into());
+        assert_eq!(
+            substring(&s, &Value::Number(0.0), &Value::Number(8.0)).unwrap(),
+            Value::String("Amoskeag".into())
+        );
+        assert_eq!(
+            substring(&s, &Value::Number(-5.0), &Value::Number(5.0)).unwrap(),
+            Value::String("Mills".into())
+        );
+        assert_eq!(
+            substring(&s, &Value::Number(9.0), &Value::Number(100.0)).unwrap(),
+            Value::String("Mills".into())
+        );
+        assert_eq!(
+            substring(&s, &Value::Number(50.0), &Value::Number(3.0)).unwrap(),
+            Value::String("".into())
+        );
+    }
+
+    #[test]
+    fn test_substring_multibyte() {
+        let s = Value::String("Café Zoë".into());
+        assert_eq!(
+            substring(&s, &Value::Number(3.0), &Value::Number(3.0)).unwrap(),
+            Value::String("é Z".into())
+        );
+        assert!(matches!(
+            substring(&s, &Value::String("3".into()), &Value::Number(1.0)),
+            Err(FunctionError::TypeError { .. })
+        ));
+    }
+
+    #[test]
+    fn test_pad_left() {
+        let result = pad_left(
+            &Value::String("42".into()),
+            &Value::Number(6.0),
+            &Value::String("0".into()),
         )
         .unwrap();
-        assert_eq!(result, Value::String("hello rust".to_string()));
+        assert_eq!(result, Value::String("000042".into()));
+
+        let result = pad_left(
+            &Value::String("1234567".into()),
+            &Value::Number(6.0),
+            &Value::String("0".into()),
+        )
+        .unwrap();
+        assert_eq!(result, Value::String("1234567".into()));
+    }
+
+    #[test]
+    fn test_pad_right() {
+        let result = pad_right(
+            &Value::String("Zoë".into()),
+            &Value::Number(5.0),
+            &Value::String(".".into()),
+        )
+        .unwrap();
+        assert_eq!(result, Value::String("Zoë..".into()));
+    }
+
+    #[test]
+    fn test_pad_huge_width() {
+        let result = pad_left(
+            &Value::String("x".into()),
+            &Value::Number(100_000_000_000.0),
+            &Value::String("0".into()),
+        );
+        assert!(matches!(result, Err(FunctionError::ValueError { .. })));
+
+        let result = pad_right(
+            &Value::String("x".into()),
+            &Value::Number(f64::INFINITY),
+            &Value::String("é".into()),
+        );
+        assert!(matches!(result, Err(FunctionError::ValueError { .. })));
+
+        // A string already past the width is returned as is, whatever its length
+        let long = "y".repeat(MAX_REPEAT_LENGTH + 1);
+        let result = pad_left(
+            &Value::String(long.clone().into()),
+            &Value::Number(3.0),
+            &Value::String("0".into()),
+        )
+        .unwrap();
+        assert_eq!(result, Value::String(long.into()));
+
+        let length = |s: &str, width: f64, fill: &str| {
+            pad_length(
+                &Value::String(s.into()),
+                &Value::Number(width),
+                &Value::String(fill.into()),
+            )
+        };
+        assert_eq!(length("x", 100_000_000_000.0, "é"), Some(199_999_999_999));
+        assert_eq!(length("xyz", 2.0, "0"), Some(3));
+        assert_eq!(length("x", f64::INFINITY, "é"), None);
+        assert_eq!(length("x", 5.0, "ab"), None);
+    }
+
+    #[test]
+    fn test_pad_invalid_fill() {
+        let result = pad_left(
+            &Value::String("7".into()),
+            &Value::Number(3.0),
+            &Value::String("ab".into()),
+        );
+        assert!(matches!(result, Err(FunctionError::ArgumentError { .. })));
+
+        let result = pad_right(
+            &Value::String("7".into()),
+            &Value::Number(3.0),
+            &Value::String("".into()),
+        );
+        assert!(matches!(result, Err(FunctionError::ArgumentError { .. })));
+    }
+
+    #[test]
+    fn test_format() {
+        let template = Value::String("Premium: %.2f for %s".into());
+        let args = vec![Value::Number(1234.5), Value::String("Jane".into())];
+        assert_eq!(
+            format(&template, &args).unwrap(),
+            Value::String("Premium: 1234.50 for Jane".into())
+        );
+
+        let template = Value::String("%d units at 100%%, %f".into());
+        let args = vec![Value::Number(12.9), Value::Number(0.5)];
+        assert_eq!(
+            format(&template, &args).unwrap(),
+            Value::String("12 units at 100%, 0.500000".into())
+        );
+
+        // Halves round away from zero, as in round
+        let template = Value::String("%.0f %.2f %.0f".into());
+        let args = vec![
+            Value::Number(2.5),
+            Value::Number(0.125),
+            Value::Number(-2.5),
+        ];
+        assert_eq!(
+            format(&template, &args).unwrap(),
+            Value::String("3 0.13 -3".into())
+        );
+
+        let template = Value::String("no placeholders".into());
+        assert_eq!(
+            format(&template, &[]).unwrap(),
+            Value::String("no placeholders".into())
+        );
+    }
+
+    #[test]
+    fn test_format_errors() {
+        let template = Value::String("%s and %s".into());
+        assert!(matches!(
+            format(&template, &[Value::Nil]),
+            Err(FunctionError::ArgumentError { .. })
+        ));
+
+        let template = Value::String("%s".into());
+        assert!(matches!(
+            format(&template, &[Value::Nil, Value::Nil]),
+            Err(FunctionError::ArgumentError { .. })
+        ));
+
+        let template = Value::String("%x".into());
+        assert!(matches!(
+            format(&template, &[Value::Number(1.0)]),
+            Err(FunctionError::ArgumentError { .. })
+        ));
+
+        let template = Value::String("%d".into());
+        assert!(matches!(
+            format(&template, &[Value::String("1".into())]),
+            Err(FunctionError::TypeError { .. })
+        ));
+
+        assert!(matches!(
+            format(&Value::Number(1.0), &[]),
+            Err(FunctionError::TypeError { .. })
+        ));
+    }
+
+    #[test]
+    fn test_chars() {
+        let result = chars(&Value::String("Zoë".into())).unwrap();
+        assert_eq!(
+            result,
+            Value::Array(
+                vec![
+                    Value::String("Z".into()),
+                    Value::String("o".into()),
+                    Value::String("ë".into()),
+                ]
+                .into()
+            )
+        );
+        assert_eq!(
+            chars(&Value::String("".into())).unwrap(),
+            Value::Array(vec![].into())
+        );
+        assert!(matches!(
+            chars(&Value::Number(1.0)),
+            Err(FunctionError::TypeError { .. })
+        ));
+    }
+
+    #[test]
+    fn test_lines() {
+        let result = lines(&Value::String("line one\r\nline two\n".into())).unwrap();
+        assert_eq!(
+            result,
+            Value::Array(
+                vec![
+                    Value::String("line one".into()),
+                    Value::String("line two".into()),
+                ]
+                .into()
+            )
+        );
+        assert!(matches!(
+            lines(&Value::Nil),
+            Err(FunctionError::TypeError { .. })
+        ));
+    }
+
+    #[test]
+    fn test_repeat() {
+        assert_eq!(
+            repeat(&Value::String("ab".into()), &Value::Number(3.0)).unwrap(),
+            Value::String("ababab".into())
+        );
+        assert_eq!(
+            repeat(&Value::String("ab".into()), &Value::Number(0.0)).unwrap(),
+            Value::String("".into())
+        );
+        assert!(matches!(
+            repeat(&Value::String("ab".into()), &Value::Number(-1.0)),
+            Err(FunctionError::ArgumentError { .. })
+        ));
+        assert!(matches!(
+            repeat(&Value::String("ab".into()), &Value::Nil),
+            Err(FunctionError::TypeError { .. })
+        ));
+    }
+
+    #[test]
+    fn test_repeat_length_guard() {
+        let result = repeat(&Value::String("x".into()), &Value::Number(1e12));
+        assert!(matches!(result, Err(FunctionError::ValueError { .. })));
+
+        let result = repeat(
+            &Value::String("x".into()),
+            &Value::Number(MAX_REPEAT_LENGTH as f64),
+        )
+        .unwrap();
+        assert!(matches!(result, Value::String(s) if s.len() == MAX_REPEAT_LENGTH));
+
+        let ab = Value::String("ab".into());
+        assert_eq!(
+            repeat_length(&ab, &Value::Number(1e12)),
+            Some(2_000_000_000_000)
+        );
+        assert_eq!(repeat_length(&ab, &Value::Number(-1.0)), None);
+        assert_eq!(repeat_length(&ab, &Value::Number(f64::INFINITY)), None);
+    }
+
+    #[test]
+    fn test_slugify() {
+        let cases = [
+            ("Café Zoë & Co.", "cafe-zoe-co"),
+            ("  Granite State Mutual  ", "granite-state-mutual"),
+            ("HO-3 / Special Form", "ho-3-special-form"),
+            ("Ångström", "angstrom"),
+            ("Straße", "strasse"),
+            ("GROẞE Øre", "grosse-ore"),
+            ("Ærø Œuvre", "aero-oeuvre"),
+            ("cœur søster æble", "coeur-soster-aeble"),
+            ("---", ""),
+        ];
+        for (input, expected) in cases {
+            assert_eq!(
+                slugify(&Value::String(input.into())).unwrap(),
+                Value::String(expected.into()),
+                "Failed for: {}",
+                input
+            );
+        }
+        assert!(matches!(
+            slugify(&Value::Nil),
+            Err(FunctionError::TypeError { .. })
+        ));
+    }
+
+    #[test]
+    fn test_snake_case() {
+        let cases = [
+            ("policyHolderName", "policy_holder_name"),
+            ("PolicyHolderName", "policy_holder_name"),
+            ("policy-holder name", "policy_holder_name"),
+            ("HTTPServerURL", "http_server_url"),
+            ("address2Line", "address2_line"),
+            ("already_snake", "already_snake"),
+        ];
+        for (input, expected) in cases {
+            assert_eq!(
+                snake_case(&Value::String(input.into())).unwrap(),
+                Value::String(expected.into()),
+                "Failed for: {}",
+                input
+            );
+        }
+    }
+
+    #[test]
+    fn test_camel_case() {
+        let cases = [
+            ("policy_holder_name", "policyHolderName"),
+            ("Policy Holder Name", "policyHolderName"),
+            ("HTTP server", "httpServer"),
+            ("", ""),
+        ];
+        for (input, expected) in cases {
+            assert_eq!(
+                camel_case(&Value::String(input.into())).unwrap(),
+                Value::String(expected.into()),
+                "Failed for: {}",
+                input
+            );
+        }
+        assert!(matches!(
+            camel_case(&Value::Number(1.0)),
+            Err(FunctionError::TypeError { .. })
+        ));
+    }
+
+    #[test]
+    fn test_titlecase() {
+        let cases = [
+            ("the quick BROWN fox", "The Quick Brown Fox"),
+            ("policy_holder_name", "Policy Holder Name"),
+            ("mary smith-jones", "Mary Smith-jones"),
+            ("  extra   spaces ", "Extra Spaces"),
+        ];
+        for (input, expected) in cases {
+            assert_eq!(
+                titlecase(&Value::String(input.into())).unwrap(),
+                Value::String(expected.into()),
+                "Failed for: {}",
+                input
+            );
+        }
+    }
+
+    #[test]
+    fn test_levenshtein() {
+        let cases = [
+            ("kitten", "sitting", 3),
+            ("Smith", "Smyth", 1),
+            ("", "abc", 3),
+            ("same", "same", 0),
+            ("Zoë", "Zoe", 1),
+        ];
+        for (a, b, expected) in cases {
+            assert!(
+                matches!(
+                    levenshtein(&Value::String(a.into()), &Value::String(b.into())),
+                    Ok(Value::Int(n)) if n == expected
+                ),
+                "Failed for: {} / {}",
+                a,
+                b
+            );
+        }
+        assert!(matches!(
+            levenshtein(&Value::String("a".into()), &Value::Nil),
+            Err(FunctionError::TypeError { .. })
+        ));
+    }
+
+    #[test]
+    fn test_similarity() {
+        let score =
+            |a: &str, b: &str| match similarity(&Value::String(a.into()), &Value::String(b.into()))
+            {
+                Ok(Value::Number(n)) => n,
+                other => panic!("unexpected result: {:?}", other),
+            };
+        assert_eq!(score("123 Main St", "123 Main St"), 1.0);
+        assert_eq!(score("", ""), 1.0);
+        assert_eq!(score("abc", "xyz"), 0.0);
+        assert!((score("Smith", "Smyth") - 0.8).abs() < 1e-10);
+    }
+
+    #[test]
+    fn test_index_of() {
+        let s = Value::String("Café au lait".into());
+        assert!(matches!(
+            index_of(&s, &Value::String("au".into())),
+            Ok(Value::Int(5))
+        ));
+        assert_eq!(
+            index_of(&s, &Value::String("tea".into())).unwrap(),
+            Value::Nil
+        );
+        assert!(matches!(
+            index_of(&s, &Value::String("".into())),
+            Ok(Value::Int(0))
+        ));
+        assert!(matches!(
+            index_of(&Value::Array(vec![].into()), &Value::String("a".into())),
+            Err(FunctionError::TypeError { .. })
+        ));
+    }
+
+    #[test]
+    fn test_includes() {
+        let s = Value::String("Homeowners Special Form".into());
+        assert_eq!(
+            includes(&s, &Value::String("Special".into())).unwrap(),
+            Value::Boolean(true)
+        );
+        assert_eq!(
+            includes(&s, &Value::String("special".into())).unwrap(),
+            Value::Boolean(false)
+        );
+        assert!(matches!(
+            includes(&s, &Value::Number(1.0)),
+            Err(FunctionError::TypeError { .. })
+        ));
+    }
+
+    #[test]
+    fn test_squish() {
+        let result = squish(&Value::String("  123   Main\t\tSt \n Apt 4 ".into())).unwrap();
+        assert_eq!(result, Value::String("123 Main St Apt 4".into()));
+        assert_eq!(
+            squish(&Value::String("   ".into())).unwrap(),
+            Value::String("".into())
+        );
+        assert!(matches!(
+            squish(&Value::Nil),
+            Err(FunctionError::TypeError { .. })
+        ));
+    }
+
+    #[test]
+    fn test_is_email() {
+        let valid = ["jane.doe@example.com", "a+tag@sub.example.co.uk"];
+        for email in valid {
+            assert_eq!(
+                is_email(&Value::String(email.into())),
+                Value::Boolean(true),
+                "Expected valid: {}",
+                email
+            );
+        }
+        let invalid = ["jane", "jane@", "@example.com", "jane@example", "a b@c.com"];
+        for email in invalid {
+            assert_eq!(
+                is_email(&Value::String(email.into())),
+                Value::Boolean(false),
+                "Expected invalid: {}",
+                email
+            );
+        }
+        assert_eq!(is_email(&Value::Nil), Value::Boolean(false));
+    }
+
+    #[test]
+    fn test_is_numeric_string() {
+        for s in ["42", "-3.14", "+0.5", ".5", "1e6", "2.5E-3"] {
+            assert_eq!(
+                is_numeric_string(&Value::String(s.to_string().into())),
+                Value::Boolean(true),
+                "Expected numeric: {}",
+                s
+            );
+        }
+        for s in [
+            "", "abc", "1,000", " 42", "NaN", "inf", "1.2.3", "-", "١٢", "４２", "1e٣",
+        ] {
+            assert_eq!(
+                is_numeric_string(&Value::String(s.to_string().into())),
+                Value::Boolean(false),
+                "Expected non-numeric: {}",
+                s
+            );
+        }
+        assert_eq!(
+            is_numeric_string(&Value::Number(42.0)),
+            Value::Boolean(false)
+        );
+    }
+
+    #[test]
+    fn test_matches_pattern() {
+        let pattern = Value::String(r"[A-Z]{2}\d{6}".into());
+        assert_eq!(
+            matches_pattern(&Value::String("NH123456".into()), &pattern).unwrap(),
+            Value::Boolean(true)
+        );
+        assert_eq!(
+            matches_pattern(&Value::String("xNH123456".into()), &pattern).unwrap(),
+            Value::Boolean(false)
+        );
+        assert_eq!(
+            matches_pattern(&Value::String("NH1234567".into()), &pattern).unwrap(),
+            Value::Boolean(false)
+        );
+        assert!(matches!(
+            matches_pattern(&Value::String("a".into()), &Value::String("(".into())),
+            Err(FunctionError::InvalidPattern { .. })
+        ));
+    }
+
+    #[test]
+    fn test_sha256() {
+        assert_eq!(
+            sha256(&Value::String("abc".into())).unwrap(),
+            Value::String(
+                "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+                    .to_string()
+                    .into()
+            )
+        );
+        assert_eq!(
+            sha256(&Value::String("".into())).unwrap(),
+            Value::String(
+                "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855"
+                    .to_string()
+                    .into()
+            )
+        );
+        assert!(matches!(
+            sha256(&Value::Number(42.0)),
+            Err(FunctionError::TypeError { .. })
+        ));
+    }
+
+    #[test]
+    fn test_md5() {
+        assert_eq!(
+            md5(&Value::String("abc".into())).unwrap(),
+            Value::String("900150983cd24fb0d6963f7d28e17f72".into())
+        );
+        assert_eq!(
+            md5(&Value::String(
+                "The quick brown fox jumps over the lazy dog"
+                    .to_string()
+                    .into()
+            ))
+            .unwrap(),
+            Value::String("9e107d9d372bb6826bd81d3542a419d6".into())
+        );
+        assert!(matches!(
+            md5(&Value::Nil),
+            Err(FunctionError::TypeError { .. })
+        ));
+    }
+
+    #[test]
+    fn test_base64_round_trip() {
+        for text in ["", "f", "fo", "foo", "policy:12345", "héllo wörld"] {
+            let encoded = base64_encode(&Value::String(text.into())).unwrap();
+            assert_eq!(base64_decode(&encoded).unwrap(), Value::String(text.into()));
+        }
+        assert_eq!(
+            base64_encode(&Value::String("foobar".into())).unwrap(),
+            Value::String("Zm9vYmFy".into())
+        );
+        assert_eq!(
+            base64_encode(&Value::String("fo".into())).unwrap(),
+            Value::String("Zm8=".into())
+        );
+    }
+
+    #[test]
+    fn test_base64_decode_rejects_invalid_input() {
+        for input in ["Zm9v!", "Zm8", "Zm9vY"] {
+            assert!(
+                matches!(
+                    base64_decode(&Value::String(input.into())),
+                    Err(FunctionError::ValueError { .. })
+                ),
+                "expected error for {:?}",
+                input
+            );
+        }
+        // "/w==" decodes to the lone byte 0xFF, which is not UTF-8
+        assert!(matches!(
+            base64_decode(&Value::String("/w==".into())),
+            Err(FunctionError::ValueError { .. })
+        ));
+        assert!(matches!(
+            base64_encode(&Value::Number(1.0)),
+            Err(FunctionError::TypeError { .. })
+        ));
+        assert!(matches!(
+            base64_decode(&Value::Nil),
+            Err(FunctionError::TypeError { .. })
+        ));
+    }
+
+    #[test]
+    fn test_url_encode() {
+        let encode = |s: &str| url_encode(&Value::String(s.into())).unwrap();
+        assert_eq!(
+            encode("a b&c=d/e?f"),
+            Value::String("a%20b%26c%3Dd%2Fe%3Ff".into())
+        );
+        assert_eq!(
+            encode("safe-._~AZaz09"),
+            Value::String("safe-._~AZaz09".into())
+        );
+        assert_eq!(encode("café"), Value::String("caf%C3%A9".into()));
+        assert!(matches!(
+            url_encode(&Value::Nil),
+            Err(FunctionError::TypeError { .. })
+        ));
+    }
+
+    #[test]
+    fn test_url_decode() {
+        let decode = |s: &str| url_decode(&Value::String(s.into()));
+        assert_eq!(
+            decode("a%20b%26c%3dd").unwrap(),
+            Value::String("a b&c=d".into())
+        );
+        assert_eq!(decode("caf%C3%A9").unwrap(), Value::String("café".into()));
+        assert_eq!(decode("a+b").unwrap(), Value::String("a+b".into()));
+        for input in ["100%", "%2", "%zz", "%C3"] {
+            assert!(
+                matches!(decode(input), Err(FunctionError::ValueError { .. })),
+                "expected error for {:?}",
+                input
+            );
+        }
+        for text in ["", "hello world", "ünïcödé/?#[]@!$&'()*+,;="] {
+            let encoded = url_encode(&Value::String(text.into())).unwrap();
+            assert_eq!(url_decode(&encoded).unwrap(), Value::String(text.into()));
+        }
+    }
+
+    #[test]
+    fn test_luhn_valid() {
+        let check = |s: &str| luhn_valid(&Value::String(s.into())).unwrap();
+        assert_eq!(check("4111111111111111"), Value::Boolean(true));
+        assert_eq!(check("4111 1111 1111 1111"), Value::Boolean(true));
+        assert_eq!(check("79927398713"), Value::Boolean(true));
+        assert_eq!(check("4111111111111112"), Value::Boolean(false));
+        assert_eq!(check("7992-7398-713"), Value::Boolean(true));
+        assert_eq!(check("4111a11111111111"), Value::Boolean(false));
+        assert_eq!(check("0"), Value::Boolean(false));
+        assert_eq!(check(""), Value::Boolean(false));
+        assert_eq!(check("١٢"), Value::Boolean(false));
+
+        assert!(matches!(
+            luhn_valid(&Value::Number(79927398713.0)),
+            Err(FunctionError::TypeError { .. })
+        ));
+        assert!(matches!(
+            luhn_valid(&Value::Nil),
+            Err(FunctionError::TypeError { .. })
+        ));
     }
 }
@@ -5,7 +5,7 @@ use amoskeag_stdlib_operators::Value;
 #[test]
 fn test_cumipmt_wrong_type_rate() {
     let result = cumipmt(
-        &Value::String("0.05".to_string()),
+        &Value::String("0.05".into()),
         &Value::Number(12.0),
         &Value::Number(1000.0),
         &Value::Number(1.0),
@@ -65,7 +65,7 @@ fn test_cumipmt_wrong_type_start_period() {
         &Value::Number(0.05),
         &Value::Number(12.0),
         &Value::Number(1000.0),
-        &Value::Array(vec![]),
+        &Value::Array(vec![].into()),
         &Value::Number(12.0),
         &Value::Number(0.0),
     );
@@ -85,7 +85,7 @@ fn test_cumipmt_wrong_type_end_period() {
         &Value::Number(12.0),
         &Value::Number(1000.0),
         &Value::Number(1.0),
-        &Value::String("12".to_string()),
+        &Value::String("12".into()),
         &Value::Number(0.0),
     );
     assert!(result.is_err());
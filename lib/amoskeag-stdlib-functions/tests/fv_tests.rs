@@ -37,7 +37,7 @@ fn test_fv_nper_negative() {
 #[test]
 fn test_fv_wrong_type_rate() {
     let result = fv(
-        &Value::String("0.05".to_string()),
+        &Value::String("0.05".into()),
         &Value::Number(12.0),
         &Value::Number(-100.0),
         &Value::Number(-1000.0),
@@ -91,7 +91,7 @@ fn test_fv_wrong_type_pv() {
         &Value::Number(0.05),
         &Value::Number(12.0),
         &Value::Number(-100.0),
-        &Value::Array(vec![]),
+        &Value::Array(vec![].into()),
     );
     assert!(result.is_err());
     if let Err(FunctionError::TypeError { expected, got }) = result {
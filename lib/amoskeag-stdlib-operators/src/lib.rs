@@ -3,25 +3,246 @@
 //! This crate implements the core operators for the Amoskeag language,
 //! including arithmetic, comparison, and logical operators.
 
+use rust_decimal::prelude::{FromPrimitive, ToPrimitive};
+use rust_decimal::MathematicalOps;
+use std::any::Any;
+use std::cmp::Ordering;
 use std::collections::HashMap;
+use std::sync::Arc;
+
+pub use rust_decimal::Decimal;
 
 /// The core Value type for Amoskeag
 /// Represents all possible values in the language
-#[derive(Debug, Clone, PartialEq)]
+///
+/// `Int`, `Number`, and `Decimal` are all numbers: they compare equal when
+/// they hold the same value. Mixing `Int` and `Number` in arithmetic promotes
+/// the result to `Number`, and mixing either with `Decimal` gives a `Decimal`.
+///
+/// Strings, arrays, and dictionaries are reference-counted, so cloning a
+/// `Value` is cheap and never copies their contents.
+#[derive(Debug, Clone)]
 pub enum Value {
     Number(f64),
-    String(String),
+    Int(i64),
+    Decimal(Decimal),
+    String(Arc<str>),
     Boolean(bool),
     Nil,
-    Array(Vec<Value>),
-    Dictionary(HashMap<String, Value>),
+    Array(Arc<Vec<Value>>),
+    Dictionary(Arc<HashMap<String, Value>>),
     Symbol(String),
+    Duration(Duration),
+    Function(Function),
+}
+
+impl PartialEq for Value {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Value::Number(l), Value::Number(r)) => l == r,
+            (Value::Int(l), Value::Int(r)) => l == r,
+            // Exactly: i64::MAX as f64 rounds up to 2^63, so it must not
+            // equal i64::MAX, and 2^53 + 1 must not equal 2^53
+            (Value::Int(i), Value::Number(n)) | (Value::Number(n), Value::Int(i)) => {
+                n.fract() == 0.0 && (-(2f64.powi(63))..2f64.powi(63)).contains(n) && *n as i64 == *i
+            }
+            (Value::Decimal(_), _) | (_, Value::Decimal(_)) => {
+                match (self.as_decimal(), other.as_decimal()) {
+                    (Some(l), Some(r)) => l == r,
+                    _ => false,
+                }
+            }
+            (Value::String(l), Value::String(r)) => l == r,
+            (Value::Boolean(l), Value::Boolean(r)) => l == r,
+            (Value::Nil, Value::Nil) => true,
+            (Value::Array(l), Value::Array(r)) => l == r,
+            (Value::Dictionary(l), Value::Dictionary(r)) => l == r,
+            (Value::Symbol(l), Value::Symbol(r)) => l == r,
+            (Value::Duration(l), Value::Duration(r)) => l == r,
+            (Value::Function(l), Value::Function(r)) => l == r,
+            _ => false,
+        }
+    }
+}
+
+/// A span of calendar time, kept as separate month and day counts
+///
+/// Months and days are not interchangeable (a month is 28 to 31 days), so a
+/// duration of one month is neither shorter nor longer than 30 days.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Duration {
+    pub months: i64,
+    pub days: i64,
+}
+
+impl Duration {
+    pub fn days(days: i64) -> Self {
+        Duration { months: 0, days }
+    }
+
+    pub fn months(months: i64) -> Self {
+        Duration { months, days: 0 }
+    }
+
+    /// Order two durations when their units allow it
+    ///
+    /// Returns `None` when one is longer in months and the other in days.
+    pub fn compare(&self, other: &Duration) -> Option<Ordering> {
+        let months = self.months.cmp(&other.months);
+        let days = self.days.cmp(&other.days);
+        match (months, days) {
+            (Ordering::Equal, ord) | (ord, Ordering::Equal) => Some(ord),
+            (a, b) if a == b => Some(a),
+            _ => None,
+        }
+    }
+}
+
+/// ISO 8601 form, e.g. `P1Y2M`, `P30D`, or `P0D`
+impl std::fmt::Display for Duration {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let (years, months) = (self.months / 12, self.months % 12);
+        write!(f, "P")?;
+        if years != 0 {
+            write!(f, "{}Y", years)?;
+        }
+        if months != 0 {
+            write!(f, "{}M", months)?;
+        }
+        if self.days != 0 || self.months == 0 {
+            write!(f, "{}D", self.days)?;
+        }
+        Ok(())
+    }
+}
+
+/// A callable value: a lambda or a function supplied by the host
+///
+/// Functions compare equal only to themselves (or clones of themselves);
+/// two lambdas with the same parameters and body are still different values.
+#[derive(Clone)]
+pub enum Function {
+    /// A lambda, closed over the scope it was created in
+    Closure(Arc<Closure>),
+    /// A Rust function supplied by the host
+    Host(HostFunction),
+}
+
+impl Function {
+    /// The number of arguments the function takes, if fixed
+    pub fn arity(&self) -> Option<usize> {
+        match self {
+            Function::Closure(closure) => Some(closure.params.len()),
+            Function::Host(host) => host.arity,
+        }
+    }
+}
+
+impl PartialEq for Function {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Function::Closure(l), Function::Closure(r)) => Arc::ptr_eq(l, r),
+            (Function::Host(l), Function::Host(r)) => Arc::ptr_eq(&l.func, &r.func),
+            _ => false,
+        }
+    }
+}
+
+/// `<fn(x, y)>` for lambdas, `<fn name>` for host functions
+impl std::fmt::Display for Function {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Function::Closure(closure) => write!(f, "<fn({})>", closure.params.join(", ")),
+            Function::Host(host) => write!(f, "<fn {}>", host.name),
+        }
+    }
+}
+
+impl std::fmt::Debug for Function {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Function({})", self)
+    }
+}
+
+/// A lambda's parameters, body, and captured scope
+///
+/// The body and scope belong to the evaluator that created the closure; it
+/// stores them in its own representation and downcasts them on each call.
+pub struct Closure {
+    params: Vec<String>,
+    body: Box<dyn Any + Send + Sync>,
+    scope: Box<dyn Any + Send + Sync>,
+}
+
+impl Closure {
+    pub fn new(
+        params: Vec<String>,
+        body: impl Any + Send + Sync,
+        scope: impl Any + Send + Sync,
+    ) -> Self {
+        Closure {
+            params,
+            body: Box::new(body),
+            scope: Box::new(scope),
+        }
+    }
+
+    pub fn params(&self) -> &[String] {
+        &self.params
+    }
+
+    /// The body, if it was stored as a `T`
+    pub fn body<T: Any>(&self) -> Option<&T> {
+        self.body.downcast_ref()
+    }
+
+    /// The captured scope, if it was stored as a `T`
+    pub fn scope<T: Any>(&self) -> Option<&T> {
+        self.scope.downcast_ref()
+    }
+}
+
+/// The Rust side of a host function: arguments in, a value or an error message out
+pub type HostFn = dyn Fn(&[Value]) -> Result<Value, String> + Send + Sync;
+
+/// A named Rust function callable from Amoskeag
+#[derive(Clone)]
+pub struct HostFunction {
+    name: Arc<str>,
+    arity: Option<usize>,
+    func: Arc<HostFn>,
+}
+
+impl HostFunction {
+    /// A host function taking exactly `arity` arguments, or any number when `None`
+    pub fn new(
+        name: impl Into<Arc<str>>,
+        arity: Option<usize>,
+        func: impl Fn(&[Value]) -> Result<Value, String> + Send + Sync + 'static,
+    ) -> Self {
+        HostFunction {
+            name: name.into(),
+            arity,
+            func: Arc::new(func),
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Call the function; arity is the caller's to check
+    pub fn call(&self, args: &[Value]) -> Result<Value, String> {
+        (self.func)(args)
+    }
 }
 
 impl std::fmt::Display for Value {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
             Value::Number(n) => write!(f, "{}", n),
+            Value::Int(n) => write!(f, "{}", n),
+            Value::Decimal(d) => write!(f, "{}", d),
             Value::String(s) => write!(f, "{}", s),
             Value::Boolean(b) => write!(f, "{}", b),
             Value::Nil => write!(f, "nil"),
@@ -46,10 +267,61 @@ impl std::fmt::Display for Value {
                 write!(f, "}}")
             }
             Value::Symbol(s) => write!(f, ":{}", s),
+            Value::Duration(d) => write!(f, "{}", d),
+            Value::Function(func) => write!(f, "{}", func),
         }
     }
 }
 
+// Conversions for hosts building values from Rust data
+impl From<i64> for Value {
+    fn from(n: i64) -> Self {
+        Value::Int(n)
+    }
+}
+
+impl From<i32> for Value {
+    fn from(n: i32) -> Self {
+        Value::Int(n.into())
+    }
+}
+
+impl From<f64> for Value {
+    fn from(n: f64) -> Self {
+        Value::Number(n)
+    }
+}
+
+impl From<bool> for Value {
+    fn from(b: bool) -> Self {
+        Value::Boolean(b)
+    }
+}
+
+impl From<&str> for Value {
+    fn from(s: &str) -> Self {
+        Value::String(s.into())
+    }
+}
+
+impl From<String> for Value {
+    fn from(s: String) -> Self {
+        Value::String(s.into())
+    }
+}
+
+impl From<Vec<Value>> for Value {
+    fn from(items: Vec<Value>) -> Self {
+        Value::Array(items.into())
+    }
+}
+
+impl From<HashMap<String, Value>> for Value {
+    fn from(map: HashMap<String, Value>) -> Self {
+        Value::Dictionary(map.into())
+    }
+}
+
 /// Error types for operator operations
 #[derive(Debug, Clone, PartialEq)]
 pub enum OperatorError {
@@ -63,6 +335,10 @@ pub enum OperatorError {
         left: String,
         right: String,
     },
+    /// A decimal result too large to represent
+    Overflow {
+        op: String,
+    },
 }
 
 impl std::fmt::Display for OperatorError {
@@ -77,6 +353,9 @@ impl std::fmt::Display for OperatorError {
             OperatorError::InvalidOperation { op, left, right } => {
                 write!(f, "Invalid operation: {} {} {}", left, op, right)
             }
+            OperatorError::Overflow { op } => {
+                write!(f, "Decimal overflow in {}", op)
+            }
         }
     }
 }
@@ -88,102 +367,274 @@ impl Value {
     pub fn type_name(&self) -> &str {
         match self {
             Value::Number(_) => "Number",
+            Value::Int(_) => "Integer",
+            Value::Decimal(_) => "Decimal",
             Value::String(_) => "String",
             Value::Boolean(_) => "Boolean",
             Value::Nil => "Nil",
             Value::Array(_) => "Array",
             Value::Dictionary(_) => "Dictionary",
             Value::Symbol(_) => "Symbol",
+            Value::Duration(_) => "Duration",
+            Value::Function(_) => "Function",
+        }
+    }
+
+    /// The numeric value of an `Int` or `Number`, as a float
+    pub fn as_f64(&self) -> Option<f64> {
+        match self {
+            Value::Number(n) => Some(*n),
+            Value::Int(n) => Some(*n as f64),
+            Value::Decimal(d) => d.to_f64(),
+            _ => None,
+        }
+    }
+
+    /// The numeric value of an `Int`, `Number`, or `Decimal`, as a decimal
+    ///
+    /// Floats convert through their shortest decimal form, so `0.1` becomes
+    /// exactly `0.1`; NaN, infinities, and floats too large for a decimal give `None`.
+    pub fn as_decimal(&self) -> Option<Decimal> {
+        match self {
+            Value::Number(n) => Decimal::from_f64(*n),
+            Value::Int(n) => Some(Decimal::from(*n)),
+            Value::Decimal(d) => Some(*d),
+            _ => None,
+        }
+    }
+
+    /// Whether this value is an `Int` or holds one at any depth
+    pub fn contains_int(&self) -> bool {
+        match self {
+            Value::Int(_) => true,
+            Value::Array(items) => items.iter().any(Value::contains_int),
+            Value::Dictionary(map) => map.values().any(Value::contains_int),
+            _ => false,
         }
     }
+
+    /// Whether this value is a `Decimal` or holds one at any depth
+    pub fn contains_decimal(&self) -> bool {
+        match self {
+            Value::Decimal(_) => true,
+            Value::Array(items) => items.iter().any(Value::contains_decimal),
+            Value::Dictionary(map) => map.values().any(Value::contains_decimal),
+            _ => false,
+        }
+    }
+
+    /// Replace every `Int`, including those inside arrays and dictionaries, with a `Number`
+    pub fn promote_ints(&self) -> Value {
+        self.map_leaves(&|value| match value {
+            Value::Int(n) => Some(Value::Number(*n as f64)),
+            _ => None,
+        })
+    }
+
+    /// Replace every `Int` and `Decimal`, at any depth, with a `Number`
+    pub fn to_floats(&self) -> Value {
+        self.map_leaves(&|value| match value {
+            Value::Int(n) => Some(Value::Number(*n as f64)),
+            Value::Decimal(d) => d.to_f64().map(Value::Number),
+            _ => None,
+        })
+    }
+
+    /// Replace every `Int` and `Number`, at any depth, with a `Decimal`
+    ///
+    /// Floats with no decimal form (NaN and infinities) are left as they are.
+    pub fn to_decimals(&self) -> Value {
+        self.map_leaves(&|value| match value {
+            Value::Number(_) | Value::Int(_) => value.as_decimal().map(Value::Decimal),
+            _ => None,
+        })
+    }
+
+    /// Rebuild this value with `f` applied to everything that is not an array
+    /// or dictionary, where `f` gives `None` for leaves it leaves unchanged
+    ///
+    /// Arrays and dictionaries with no changed leaves are shared, not copied.
+    fn map_leaves(&self, f: &impl Fn(&Value) -> Option<Value>) -> Value {
+        self.try_map_leaves(f).unwrap_or_else(|| self.clone())
+    }
+
+    /// `map_leaves`, giving `None` when nothing changed
+    fn try_map_leaves(&self, f: &impl Fn(&Value) -> Option<Value>) -> Option<Value> {
+        match self {
+            Value::Array(items) => {
+                let mapped: Vec<_> = items.iter().map(|v| v.try_map_leaves(f)).collect();
+                if mapped.iter().all(Option::is_none) {
+                    return None;
+                }
+                let items = items
+                    .iter()
+                    .zip(mapped)
+                    .map(|(v, m)| m.unwrap_or_else(|| v.clone()))
+                    .collect::<Vec<_>>();
+                Some(Value::Array(items.into()))
+            }
+            Value::Dictionary(map) => {
+                let mapped: Vec<_> = map.values().map(|v| v.try_map_leaves(f)).collect();
+                if mapped.iter().all(Option::is_none) {
+                    return None;
+                }
+                let map = map
+                    .iter()
+                    .zip(mapped)
+                    .map(|((k, v), m)| (k.clone(), m.unwrap_or_else(|| v.clone())))
+                    .collect::<HashMap<_, _>>();
+                Some(Value::Dictionary(map.into()))
+            }
+            other => f(other),
+        }
+    }
+}
+
+/// Both operands as floats, when both are numbers of any kind
+fn as_floats(left: &Value, right: &Value) -> Option<(f64, f64)> {
+    Some((left.as_f64()?, right.as_f64()?))
+}
+
+/// Apply a numeric operation when both operands are numbers
+///
+/// Two `Int`s use the checked integer operation and fall back to floats only
+/// when it overflows. A `Decimal` on either side makes the operation exact,
+/// and a decimal overflow is an error rather than a silent switch to floats.
+/// Otherwise the result is a `Number`. Returns `None` when either operand is
+/// not a number.
+fn arithmetic(
+    op: &str,
+    left: &Value,
+    right: &Value,
+    checked: fn(i64, i64) -> Option<i64>,
+    decimal: fn(Decimal, Decimal) -> Option<Decimal>,
+    float: fn(f64, f64) -> f64,
+) -> Option<Result<Value, OperatorError>> {
+    match (left, right) {
+        (Value::Int(l), Value::Int(r)) => Some(Ok(match checked(*l, *r) {
+            Some(n) => Value::Int(n),
+            None => Value::Number(float(*l as f64, *r as f64)),
+        })),
+        (Value::Decimal(_), _) | (_, Value::Decimal(_)) => {
+            let (l, r) = (left.as_decimal()?, right.as_decimal()?);
+            Some(
+                decimal(l, r)
+                    .map(Value::Decimal)
+                    .ok_or_else(|| OperatorError::Overflow { op: op.to_string() }),
+            )
+        }
+        _ => as_floats(left, right).map(|(l, r)| Ok(Value::Number(float(l, r)))),
+    }
+}
+
+/// The error for an operator applied to operands it does not support
+fn invalid_operation(op: &str, left: &Value, right: &Value) -> OperatorError {
+    OperatorError::InvalidOperation {
+        op: op.to_string(),
+        left: left.type_name().to_string(),
+        right: right.type_name().to_string(),
+    }
 }
 
 // Arithmetic Operators
 
 /// Addition operator (+)
 pub fn add(left: &Value, right: &Value) -> Result<Value, OperatorError> {
+    let numeric = arithmetic(
+        "+",
+        left,
+        right,
+        i64::checked_add,
+        Decimal::checked_add,
+        |l, r| l + r,
+    );
+    if let Some(result) = numeric {
+        return result;
+    }
     match (left, right) {
-        (Value::Number(l), Value::Number(r)) => Ok(Value::Number(l + r)),
-        (Value::String(l), Value::String(r)) => Ok(Value::String(format!("{}{}", l, r))),
-        (Value::String(l), _) => Ok(Value::String(format!("{}{}", l, right))),
-        _ => Err(OperatorError::InvalidOperation {
-            op: "+".to_string(),
-            left: left.type_name().to_string(),
-            right: right.type_name().to_string(),
-        }),
+        (Value::String(l), Value::String(r)) => Ok(Value::String(format!("{}{}", l, r).into())),
+        (Value::String(l), _) => Ok(Value::String(format!("{}{}", l, right).into())),
+        (Value::Duration(l), Value::Duration(r)) => Ok(Value::Duration(Duration {
+            months: l.months + r.months,
+            days: l.days + r.days,
+        })),
+        _ => Err(invalid_operation("+", left, right)),
     }
 }
 
 /// Subtraction operator (-)
 pub fn subtract(left: &Value, right: &Value) -> Result<Value, OperatorError> {
+    let numeric = arithmetic(
+        "-",
+        left,
+        right,
+        i64::checked_sub,
+        Decimal::checked_sub,
+        |l, r| l - r,
+    );
+    if let Some(result) = numeric {
+        return result;
+    }
     match (left, right) {
-        (Value::Number(l), Value::Number(r)) => Ok(Value::Number(l - r)),
-        _ => Err(OperatorError::InvalidOperation {
-            op: "-".to_string(),
-            left: left.type_name().to_string(),
-            right: right.type_name().to_string(),
-        }),
+        (Value::Duration(l), Value::Duration(r)) => Ok(Value::Duration(Duration {
+            months: l.months - r.months,
+            days: l.days - r.days,
+        })),
+        _ => Err(invalid_operation("-", left, right)),
     }
 }
 
 /// Multiplication operator (*)
 pub fn multiply(left: &Value, right: &Value) -> Result<Value, OperatorError> {
-    match (left, right) {
-        (Value::Number(l), Value::Number(r)) => Ok(Value::Number(l * r)),
-        _ => Err(OperatorError::InvalidOperation {
-            op: "*".to_string(),
-            left: left.type_name().to_string(),
-            right: right.type_name().to_string(),
-        }),
-    }
+    arithmetic(
+        "*",
+        left,
+        right,
+        i64::checked_mul,
+        Decimal::checked_mul,
+        |l, r| l * r,
+    )
+    .unwrap_or_else(|| Err(invalid_operation("*", left, right)))
 }
 
 /// Division operator (/)
+///
+/// Yields a `Number`, so `10 / 4` is `2.5` even though both operands are
+/// `Int`s, unless either operand is a `Decimal`.
 pub fn divide(left: &Value, right: &Value) -> Result<Value, OperatorError> {
-    match (left, right) {
-        (Value::Number(l), Value::Number(r)) => {
-            if *r == 0.0 {
-                Err(OperatorError::DivisionByZero)
-            } else {
-                Ok(Value::Number(l / r))
-            }
-        }
-        _ => Err(OperatorError::InvalidOperation {
-            op: "/".to_string(),
-            left: left.type_name().to_string(),
-            right: right.type_name().to_string(),
-        }),
+    if right.as_f64() == Some(0.0) && left.as_f64().is_some() {
+        return Err(OperatorError::DivisionByZero);
     }
+    // Integer division is done in floats, so never stays integral
+    let never = |_, _| None;
+    arithmetic("/", left, right, never, Decimal::checked_div, |l, r| l / r)
+        .unwrap_or_else(|| Err(invalid_operation("/", left, right)))
 }
 
 /// Exponentiation operator (^)
+///
+/// An `Int` raised to a non-negative `Int` stays an `Int` unless it overflows.
 pub fn power(left: &Value, right: &Value) -> Result<Value, OperatorError> {
-    match (left, right) {
-        (Value::Number(l), Value::Number(r)) => Ok(Value::Number(l.powf(*r))),
-        _ => Err(OperatorError::InvalidOperation {
-            op: "^".to_string(),
-            left: left.type_name().to_string(),
-            right: right.type_name().to_string(),
-        }),
-    }
+    let checked_pow = |l: i64, r: i64| l.checked_pow(u32::try_from(r).ok()?);
+    let decimal_pow = |l: Decimal, r: Decimal| l.checked_powd(r);
+    arithmetic("^", left, right, checked_pow, decimal_pow, f64::powf)
+        .unwrap_or_else(|| Err(invalid_operation("^", left, right)))
 }
 
 /// Modulo operator (%)
 pub fn modulo(left: &Value, right: &Value) -> Result<Value, OperatorError> {
-    match (left, right) {
-        (Value::Number(l), Value::Number(r)) => {
-            if *r == 0.0 {
-                Err(OperatorError::DivisionByZero)
-            } else {
-                Ok(Value::Number(l % r))
-            }
-        }
-        _ => Err(OperatorError::InvalidOperation {
-            op: "%".to_string(),
-            left: left.type_name().to_string(),
-            right: right.type_name().to_string(),
-        }),
+    if right.as_f64() == Some(0.0) && left.as_f64().is_some() {
+        return Err(OperatorError::DivisionByZero);
     }
+    arithmetic(
+        "%",
+        left,
+        right,
+        i64::checked_rem,
+        Decimal::checked_rem,
+        |l, r| l % r,
+    )
+    .unwrap_or_else(|| Err(invalid_operation("%", left, right)))
 }
 
 // Comparison Operators
@@ -200,9 +651,12 @@ pub fn not_equal(left: &Value, right: &Value) -> Value {
 
 /// Less than operator (<)
 pub fn less_than(left: &Value, right: &Value) -> Result<Value, OperatorError> {
+    if let Some(result) = compare_numbers(left, right, Ordering::is_lt) {
+        return Ok(Value::Boolean(result));
+    }
     match (left, right) {
-        (Value::Number(l), Value::Number(r)) => Ok(Value::Boolean(l < r)),
         (Value::String(l), Value::String(r)) => Ok(Value::Boolean(l < r)),
+        (Value::Duration(l), Value::Duration(r)) => compare_durations("<", l, r, Ordering::is_lt),
         _ => Err(OperatorError::InvalidOperation {
             op: "<".to_string(),
             left: left.type_name().to_string(),
@@ -213,9 +667,12 @@ pub fn less_than(left: &Value, right: &Value) -> Result<Value, OperatorError> {
 
 /// Greater than operator (>)
 pub fn greater_than(left: &Value, right: &Value) -> Result<Value, OperatorError> {
+    if let Some(result) = compare_numbers(left, right, Ordering::is_gt) {
+        return Ok(Value::Boolean(result));
+    }
     match (left, right) {
-        (Value::Number(l), Value::Number(r)) => Ok(Value::Boolean(l > r)),
         (Value::String(l), Value::String(r)) => Ok(Value::Boolean(l > r)),
+        (Value::Duration(l), Value::Duration(r)) => compare_durations(">", l, r, Ordering::is_gt),
         _ => Err(OperatorError::InvalidOperation {
             op: ">".to_string(),
             left: left.type_name().to_string(),
@@ -226,9 +683,12 @@ pub fn greater_than(left: &Value, right: &Value) -> Result<Value, OperatorError>
 
 /// Less than or equal operator (<=)
 pub fn less_than_or_equal(left: &Value, right: &Value) -> Result<Value, OperatorError> {
+    if let Some(result) = compare_numbers(left, right, Ordering::is_le) {
+        return Ok(Value::Boolean(result));
+    }
     match (left, right) {
-        (Value::Number(l), Value::Number(r)) => Ok(Value::Boolean(l <= r)),
         (Value::String(l), Value::String(r)) => Ok(Value::Boolean(l <= r)),
+        (Value::Duration(l), Value::Duration(r)) => compare_durations("<=", l, r, Ordering::is_le),
         _ => Err(OperatorError::InvalidOperation {
             op: "<=".to_string(),
             left: left.type_name().to_string(),
@@ -239,9 +699,12 @@ pub fn less_than_or_equal(left: &Value, right: &Value) -> Result<Value, Operator
 
 /// Greater than or equal operator (>=)
 pub fn greater_than_or_equal(left: &Value, right: &Value) -> Result<Value, OperatorError> {
+    if let Some(result) = compare_numbers(left, right, Ordering::is_ge) {
+        return Ok(Value::Boolean(result));
+    }
     match (left, right) {
-        (Value::Number(l), Value::Number(r)) => Ok(Value::Boolean(l >= r)),
         (Value::String(l), Value::String(r)) => Ok(Value::Boolean(l >= r)),
+        (Value::Duration(l), Value::Duration(r)) => compare_durations(">=", l, r, Ordering::is_ge),
         _ => Err(OperatorError::InvalidOperation {
             op: ">=".to_string(),
             left: left.type_name().to_string(),
@@ -250,6 +713,42 @@ pub fn greater_than_or_equal(left: &Value, right: &Value) -> Result<Value, Opera
     }
 }
 
+/// Compare two numbers of either kind, or return `None` if either is not a number
+///
+/// Two `Int`s compare exactly, as does a `Decimal` with any number that has a
+/// decimal form; otherwise both are compared as floats, and a NaN operand
+/// makes every comparison false.
+fn compare_numbers(left: &Value, right: &Value, test: fn(Ordering) -> bool) -> Option<bool> {
+    match (left, right) {
+        (Value::Int(l), Value::Int(r)) => return Some(test(l.cmp(r))),
+        (Value::Decimal(_), _) | (_, Value::Decimal(_)) => {
+            if let (Some(l), Some(r)) = (left.as_decimal(), right.as_decimal()) {
+                return Some(test(l.cmp(&r)));
+            }
+        }
+        _ => {}
+    }
+    let (l, r) = as_floats(left, right)?;
+    Some(l.partial_cmp(&r).is_some_and(test))
+}
+
+/// Compare two durations, rejecting pairs that mix months and days
+fn compare_durations(
+    op: &str,
+    left: &Duration,
+    right: &Duration,
+    test: fn(Ordering) -> bool,
+) -> Result<Value, OperatorError> {
+    match left.compare(right) {
+        Some(ordering) => Ok(Value::Boolean(test(ordering))),
+        None => Err(OperatorError::InvalidOperation {
+            op: op.to_string(),
+            left: left.to_string(),
+            right: right.to_string(),
+        }),
+    }
+}
+
 // Logical Operators
 
 /// Logical AND operator
@@ -295,11 +794,63 @@ mod tests {
     #[test]
     fn test_add_strings() {
         let result = add(
-            &Value::String("hello".to_string()),
-            &Value::String(" world".to_string()),
+            &Value::String("hello".into()),
+            &Value::String(" world".into()),
+        )
+        .unwrap();
+        assert_eq!(result, Value::String("hello world".into()));
+    }
+
+    #[test]
+    fn test_duration_arithmetic() {
+        let result = add(
+            &Value::Duration(Duration::months(1)),
+            &Value::Duration(Duration::days(10)),
+        )
+        .unwrap();
+        assert_eq!(
+            result,
+            Value::Duration(Duration {
+                months: 1,
+                days: 10
+            })
+        );
+
+        let result = subtract(
+            &Value::Duration(Duration::days(30)),
+            &Value::Duration(Duration::days(45)),
         )
         .unwrap();
-        assert_eq!(result, Value::String("hello world".to_string()));
+        assert_eq!(result, Value::Duration(Duration::days(-15)));
+
+        assert!(add(&Value::Duration(Duration::days(1)), &Value::Number(1.0)).is_err());
+    }
+
+    #[test]
+    fn test_duration_comparison() {
+        let month = Value::Duration(Duration::months(1));
+        let year = Value::Duration(Duration::months(12));
+        let thirty_days = Value::Duration(Duration::days(30));
+
+        assert_eq!(less_than(&month, &year).unwrap(), Value::Boolean(true));
+        assert_eq!(
+            greater_than_or_equal(&thirty_days, &Value::Duration(Duration::days(30))).unwrap(),
+            Value::Boolean(true)
+        );
+        // A month is not comparable to a number of days
+        assert!(matches!(
+            less_than(&month, &thirty_days),
+            Err(OperatorError::InvalidOperation { .. })
+        ));
+    }
+
+    #[test]
+    fn test_duration_display() {
+        assert_eq!(Duration::days(30).to_string(), "P30D");
+        assert_eq!(Duration::months(14).to_string(), "P1Y2M");
+        assert_eq!(Duration { months: 1, days: 2 }.to_string(), "P1M2D");
+        assert_eq!(Duration::default().to_string(), "P0D");
+        assert_eq!(Value::Duration(Duration::days(1)).type_name(), "Duration");
     }
 
     #[test]
@@ -423,13 +974,13 @@ mod tests {
         assert!(!to_boolean(&Value::Boolean(false)));
         assert!(!to_boolean(&Value::Nil));
         assert!(to_boolean(&Value::Number(0.0)));
-        assert!(to_boolean(&Value::String("".to_string())));
+        assert!(to_boolean(&Value::String("".into())));
     }
 
     // Additional edge case tests
     #[test]
     fn test_add_type_error() {
-        let result = add(&Value::Number(5.0), &Value::String("hello".to_string()));
+        let result = add(&Value::Number(5.0), &Value::String("hello".into()));
         assert!(matches!(
             result,
             Err(OperatorError::InvalidOperation { .. })
@@ -438,7 +989,7 @@ mod tests {
 
     #[test]
     fn test_subtract_type_error() {
-        let result = subtract(&Value::String("hello".to_string()), &Value::Number(5.0));
+        let result = subtract(&Value::String("hello".into()), &Value::Number(5.0));
         assert!(matches!(
             result,
             Err(OperatorError::InvalidOperation { .. })
@@ -456,7 +1007,7 @@ mod tests {
 
     #[test]
     fn test_divide_type_error() {
-        let result = divide(&Value::Number(10.0), &Value::String("2".to_string()));
+        let result = divide(&Value::Number(10.0), &Value::String("2".into()));
         assert!(matches!(
             result,
             Err(OperatorError::InvalidOperation { .. })
@@ -490,7 +1041,7 @@ mod tests {
     #[test]
     fn test_equal_different_types() {
         assert_eq!(
-            equal(&Value::Number(5.0), &Value::String("5".to_string())),
+            equal(&Value::Number(5.0), &Value::String("5".into())),
             Value::Boolean(false)
         );
 
@@ -502,9 +1053,9 @@ mod tests {
 
     #[test]
     fn test_equal_arrays() {
-        let arr1 = Value::Array(vec![Value::Number(1.0), Value::Number(2.0)]);
-        let arr2 = Value::Array(vec![Value::Number(1.0), Value::Number(2.0)]);
-        let arr3 = Value::Array(vec![Value::Number(1.0), Value::Number(3.0)]);
+        let arr1 = Value::Array(vec![Value::Number(1.0), Value::Number(2.0)].into());
+        let arr2 = Value::Array(vec![Value::Number(1.0), Value::Number(2.0)].into());
+        let arr3 = Value::Array(vec![Value::Number(1.0), Value::Number(3.0)].into());
 
         assert_eq!(equal(&arr1, &arr2), Value::Boolean(true));
         assert_eq!(equal(&arr1, &arr3), Value::Boolean(false));
@@ -555,7 +1106,7 @@ mod tests {
 
     #[test]
     fn test_comparison_type_errors() {
-        let result = less_than(&Value::String("a".to_string()), &Value::Number(5.0));
+        let result = less_than(&Value::String("a".into()), &Value::Number(5.0));
         assert!(matches!(
             result,
             Err(OperatorError::InvalidOperation { .. })
@@ -574,8 +1125,7 @@ mod tests {
         let result = logical_and(&Value::Number(5.0), &Value::Boolean(true)).unwrap();
         assert_eq!(result, Value::Boolean(true)); // Numbers are truthy
 
-        let result =
-            logical_or(&Value::Boolean(false), &Value::String("hello".to_string())).unwrap();
+        let result = logical_or(&Value::Boolean(false), &Value::String("hello".into())).unwrap();
         assert_eq!(result, Value::Boolean(true)); // Non-empty strings are truthy
 
         let result = logical_and(&Value::Nil, &Value::Boolean(true)).unwrap();
@@ -585,29 +1135,24 @@ mod tests {
     #[test]
     fn test_value_type_names() {
         assert_eq!(Value::Number(5.0).type_name(), "Number");
-        assert_eq!(Value::String("hello".to_string()).type_name(), "String");
+        assert_eq!(Value::String("hello".into()).type_name(), "String");
         assert_eq!(Value::Boolean(true).type_name(), "Boolean");
         assert_eq!(Value::Nil.type_name(), "Nil");
-        assert_eq!(Value::Array(vec![]).type_name(), "Array");
-        assert_eq!(Value::Dictionary(HashMap::new()).type_name(), "Dictionary");
+        assert_eq!(Value::Array(vec![].into()).type_name(), "Array");
+        assert_eq!(
+            Value::Dictionary(HashMap::new().into()).type_name(),
+            "Dictionary"
+        );
         assert_eq!(Value::Symbol("test".to_string()).type_name(), "Symbol");
     }
 
     #[test]
     fn test_string_concatenation() {
-        let result = add(
-            &Value::String("".to_string()),
-            &Value::String("hello".to_string()),
-        )
-        .unwrap();
-        assert_eq!(result, Value::String("hello".to_string()));
+        let result = add(&Value::String("".into()), &Value::String("hello".into())).unwrap();
+        assert_eq!(result, Value::String("hello".into()));
 
-        let result = add(
-            &Value::String("hello".to_string()),
-            &Value::String("".to_string()),
-        )
-        .unwrap();
-        assert_eq!(result, Value::String("hello".to_string()));
+        let result = add(&Value::String("hello".into()), &Value::String("".into())).unwrap();
+        assert_eq!(result, Value::String("hello".into()));
     }
 
     #[test]
@@ -640,4 +1185,165 @@ mod tests {
         };
         assert_eq!(format!("{}", err), "Invalid operation: Number + String");
     }
+
+    #[test]
+    fn test_int_arithmetic_stays_int() {
+        let (seven, two) = (Value::Int(7), Value::Int(2));
+        assert!(matches!(add(&seven, &two), Ok(Value::Int(9))));
+        assert!(matches!(subtract(&two, &seven), Ok(Value::Int(-5))));
+        assert!(matches!(multiply(&seven, &two), Ok(Value::Int(14))));
+        assert!(matches!(modulo(&seven, &two), Ok(Value::Int(1))));
+        assert!(matches!(power(&two, &seven), Ok(Value::Int(128))));
+        // Division always yields a float
+        assert!(matches!(divide(&seven, &two), Ok(Value::Number(n)) if n == 3.5));
+        assert!(matches!(divide(&Value::Int(8), &two), Ok(Value::Number(n)) if n == 4.0));
+        assert_eq!(
+            modulo(&seven, &Value::Int(0)),
+            Err(OperatorError::DivisionByZero)
+        );
+        assert_eq!(
+            divide(&seven, &Value::Int(0)),
+            Err(OperatorError::DivisionByZero)
+        );
+    }
+
+    #[test]
+    fn test_int_promotion() {
+        // Mixing with a float promotes
+        assert!(
+            matches!(add(&Value::Int(1), &Value::Number(0.5)), Ok(Value::Number(n)) if n == 1.5)
+        );
+        // Overflow promotes rather than wrapping
+        assert!(matches!(
+            add(&Value::Int(i64::MAX), &Value::Int(1)),
+            Ok(Value::Number(n)) if n == i64::MAX as f64 + 1.0
+        ));
+        // Negative exponents cannot stay integral
+        assert!(matches!(power(&Value::Int(2), &Value::Int(-1)), Ok(Value::Number(n)) if n == 0.5));
+        assert!(add(&Value::Int(1), &Value::Boolean(true)).is_err());
+        assert_eq!(
+            add(&Value::String("n=".into()), &Value::Int(3)).unwrap(),
+            Value::String("n=3".into())
+        );
+    }
+
+    #[test]
+    fn test_int_comparison_and_equality() {
+        assert_eq!(Value::Int(3), Value::Number(3.0));
+        assert_ne!(Value::Int(3), Value::Number(3.5));
+        assert_ne!(Value::Int(i64::MAX), Value::Number(i64::MAX as f64));
+        assert_ne!(
+            Value::Int((1 << 53) + 1),
+            Value::Number((1u64 << 53) as f64)
+        );
+        assert_eq!(Value::Int(i64::MIN), Value::Number(i64::MIN as f64));
+        assert_ne!(Value::Int(0), Value::Number(f64::NAN));
+        assert_eq!(
+            Value::Array(vec![Value::Int(1)].into()),
+            Value::Array(vec![Value::Number(1.0)].into())
+        );
+        assert_eq!(
+            less_than(&Value::Int(2), &Value::Number(2.5)).unwrap(),
+            Value::Boolean(true)
+        );
+        // Large integers compare exactly rather than through f64
+        assert_eq!(
+            greater_than(&Value::Int(i64::MAX), &Value::Int(i64::MAX - 1)).unwrap(),
+            Value::Boolean(true)
+        );
+        assert_eq!(Value::Int(3).type_name(), "Integer");
+        assert_eq!(Value::Int(-3).to_string(), "-3");
+    }
+
+    #[test]
+    fn test_promote_ints() {
+        let nested = Value::Array(vec![Value::Int(1), Value::String("a".into())].into());
+        assert!(nested.contains_int());
+        let promoted = nested.promote_ints();
+        assert!(!promoted.contains_int());
+        assert!(matches!(&promoted, Value::Array(items) if matches!(items[0], Value::Number(_))));
+    }
+
+    fn decimal(s: &str) -> Value {
+        Value::Decimal(s.parse().unwrap())
+    }
+
+    #[test]
+    fn test_decimal_arithmetic_is_exact() {
+        // 0.1 + 0.2 is exactly 0.3 in decimal, unlike in floats
+        let sum = add(&decimal("0.1"), &decimal("0.2")).unwrap();
+        assert!(matches!(&sum, Value::Decimal(d) if d.to_string() == "0.3"));
+        assert_ne!(
+            add(&Value::Number(0.1), &Value::Number(0.2)).unwrap(),
+            Value::Number(0.3)
+        );
+
+        assert_eq!(
+            multiply(&decimal("19.99"), &Value::Int(3)).unwrap(),
+            decimal("59.97")
+        );
+        assert_eq!(
+            divide(&decimal("10"), &Value::Int(4)).unwrap(),
+            decimal("2.5")
+        );
+        assert_eq!(
+            power(&decimal("1.1"), &Value::Int(2)).unwrap(),
+            decimal("1.21")
+        );
+        assert_eq!(
+            modulo(&decimal("7.5"), &Value::Int(2)).unwrap(),
+            decimal("1.5")
+        );
+        assert_eq!(
+            divide(&decimal("1"), &decimal("0")),
+            Err(OperatorError::DivisionByZero)
+        );
+    }
+
+    #[test]
+    fn test_decimal_promotion_and_overflow() {
+        // A float operand converts through its shortest decimal form
+        let result = add(&decimal("1.00"), &Value::Number(0.1)).unwrap();
+        assert!(matches!(&result, Value::Decimal(d) if d.to_string() == "1.10"));
+        assert!(matches!(
+            multiply(&Value::Decimal(Decimal::MAX), &Value::Int(2)),
+            Err(OperatorError::Overflow { .. })
+        ));
+        assert!(add(&decimal("1"), &Value::Boolean(true)).is_err());
+    }
+
+    #[test]
+    fn test_decimal_comparison_and_equality() {
+        assert_eq!(decimal("3.0"), Value::Int(3));
+        assert_eq!(decimal("0.1"), Value::Number(0.1));
+        assert_eq!(
+            less_than(&decimal("2.5"), &Value::Int(3)).unwrap(),
+            Value::Boolean(true)
+        );
+        assert_eq!(decimal("1.50").type_name(), "Decimal");
+        assert_eq!(decimal("1.50").to_string(), "1.50");
+
+        let nested = Value::Array(vec![Value::Int(1), Value::Number(0.5)].into());
+        let decimals = nested.to_decimals();
+        assert!(decimals.contains_decimal());
+        assert!(!decimals.to_floats().contains_decimal());
+    }
+
+    #[test]
+    fn test_clone_shares_contents() {
+        let items = Value::Array(vec![Value::String("a".into()), Value::Number(1.0)].into());
+        let copy = items.clone();
+        assert!(matches!((&items, &copy), (Value::Array(l), Value::Array(r)) if Arc::ptr_eq(l, r)));
+
+        // Conversions that change nothing share the original array
+        let floats = items.to_floats();
+        assert!(
+            matches!((&items, &floats), (Value::Array(l), Value::Array(r)) if Arc::ptr_eq(l, r))
+        );
+        let ints = Value::Array(vec![Value::Int(1)].into());
+        assert_eq!(
+            ints.to_floats(),
+            Value::Array(vec![Value::Number(1.0)].into())
+        );
+    }
 }
@@ -0,0 +1,20 @@
+using System;
+
+namespace Amoskeag
+{
+    /// <summary>
+    /// An error raised while evaluating a transpiled Amoskeag program.
+    /// </summary>
+    /// <remarks>
+    /// The messages match the ones the Amoskeag interpreter reports, such as
+    /// "Operator error: Division by zero". <c>try</c> in a program catches
+    /// these and only these.
+    /// </remarks>
+    public class AmoskeagException : Exception
+    {
+        public AmoskeagException(string message)
+            : base(message)
+        {
+        }
+    }
+}
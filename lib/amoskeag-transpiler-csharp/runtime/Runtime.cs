@@ -0,0 +1,1926 @@
+using System;
+using System.Collections;
+using System.Collections.Generic;
+using System.Globalization;
+using System.Linq;
+using System.Security.Cryptography;
+using System.Text;
+using System.Text.RegularExpressions;
+
+namespace Amoskeag
+{
+    /// <summary>
+    /// Operators and standard library for Amoskeag programs transpiled to C#.
+    /// </summary>
+    /// <remarks>
+    /// Values are plain .NET objects: <c>null</c> for nil, <see cref="bool"/>,
+    /// <see cref="long"/> for integers, <see cref="double"/> for numbers,
+    /// <see cref="string"/>, <see cref="Symbol"/>, <see cref="IList"/> for
+    /// arrays and dictionaries with string keys. Other integral types in the
+    /// input data are treated as integers, and <see cref="float"/> and
+    /// <see cref="decimal"/> as numbers.
+    ///
+    /// Every method has the semantics of the interpreter: integer arithmetic
+    /// falls back to floating point on overflow, <c>/</c> always gives a
+    /// number, and the standard library works in numbers, so integers passed
+    /// to it come back as numbers. Failures throw <see cref="AmoskeagException"/>.
+    /// </remarks>
+    public static class Runtime
+    {
+        /// <summary>Largest string, in UTF-8 bytes, that <c>repeat</c> will build</summary>
+        public const int MaxRepeatLength = 1000000;
+
+        /// <summary>Plain decimal numbers in ASCII digits, the strings <c>ToNumber</c> converts</summary>
+        private static readonly Regex NumericPattern = new Regex(
+            @"^[+-]?(?:[0-9]+\.?[0-9]*|\.[0-9]+)(?:[eE][+-]?[0-9]+)?\z",
+            RegexOptions.CultureInvariant);
+
+        private static readonly Encoding StrictUtf8 = new UTF8Encoding(false, true);
+
+        // Evaluation
+
+        /// <summary>Read a variable with no dots, which unlike a dotted path must be in the data</summary>
+        public static object Variable(IDictionary<string, object> data, string name)
+        {
+            object value;
+            if (!data.TryGetValue(name, out value))
+            {
+                throw new AmoskeagException("Variable '" + name + "' not found");
+            }
+            return value;
+        }
+
+        /// <summary>Read a dotted path, giving nil for a missing key or a non-dictionary</summary>
+        public static object Navigate(object root, params string[] path)
+        {
+            object current = root;
+            foreach (string key in path)
+            {
+                IDictionary<string, object> dictionary = AsDictionary(current);
+                if (dictionary == null || !dictionary.TryGetValue(key, out current))
+                {
+                    return null;
+                }
+            }
+            return current;
+        }
+
+        /// <summary>Whether a value counts as true: everything but nil and false</summary>
+        public static bool Truthy(object value)
+        {
+            return value != null && !(value is bool b && !b);
+        }
+
+        /// <summary>Evaluate <paramref name="body"/> with <paramref name="value"/> bound to its parameter</summary>
+        public static object Let(object value, Func<object, object> body)
+        {
+            return body(value);
+        }
+
+        /// <summary><c>try(body, fallback)</c>: the fallback is evaluated only if the body fails</summary>
+        public static object TryOr(Func<object> body, Func<object> fallback)
+        {
+            try
+            {
+                return body();
+            }
+            catch (AmoskeagException)
+            {
+                return fallback();
+            }
+        }
+
+        /// <summary>An array literal</summary>
+        public static List<object> List(params object[] items)
+        {
+            return new List<object>(items);
+        }
+
+        /// <summary>A dictionary literal, from alternating keys and values</summary>
+        public static Dictionary<string, object> Dict(params object[] pairs)
+        {
+            var dictionary = new Dictionary<string, object>();
+            for (int i = 0; i < pairs.Length; i += 2)
+            {
+                dictionary[(string)pairs[i]] = pairs[i + 1];
+            }
+            return dictionary;
+        }
+
+        // Operators
+
+        /// <summary><c>+</c>: numbers add, and a string on the left concatenates anything</summary>
+        public static object Add(object left, object right)
+        {
+            if (Numeric(left) && Numeric(right))
+            {
+                if (IsInt(left) && IsInt(right))
+                {
+                    long l = ToLong(left);
+                    long r = ToLong(right);
+                    try
+                    {
+                        return checked(l + r);
+                    }
+                    catch (OverflowException)
+                    {
+                        return (double)l + (double)r;
+                    }
+                }
+                return ToDouble(left) + ToDouble(right);
+            }
+            if (left is string s)
+            {
+                return s + Display(right);
+            }
+            throw InvalidOperation("+", left, right);
+        }
+
+        /// <summary><c>-</c></summary>
+        public static object Subtract(object left, object right)
+        {
+            if (Numeric(left) && Numeric(right))
+            {
+                if (IsInt(left) && IsInt(right))
+                {
+                    long l = ToLong(left);
+                    long r = ToLong(right);
+                    try
+                    {
+                        return checked(l - r);
+                    }
+                    catch (OverflowException)
+                    {
+                        return (double)l - (double)r;
+                    }
+                }
+                return ToDouble(left) - ToDouble(right);
+            }
+            throw InvalidOperation("-", left, right);
+        }
+
+        /// <summary><c>*</c></summary>
+        public static object Multiply(object left, object right)
+        {
+            if (Numeric(left) && Numeric(right))
+            {
+                if (IsInt(left) && IsInt(right))
+                {
+                    long l = ToLong(left);
+                    long r = ToLong(right);
+                    try
+                    {
+                        return checked(l * r);
+                    }
+                    catch (OverflowException)
+                    {
+                        return (double)l * (double)r;
+                    }
+                }
+                return ToDouble(left) * ToDouble(right);
+            }
+            throw InvalidOperation("*", left, right);
+        }
+
+        /// <summary><c>/</c>: always a number, so <c>10 / 4</c> is 2.5</summary>
+        public static object Divide(object left, object right)
+        {
+            if (Numeric(left) && Numeric(right))
+            {
+                if (ToDouble(right) == 0.0)
+                {
+                    throw new AmoskeagException("Operator error: Division by zero");
+                }
+                return ToDouble(left) / ToDouble(right);
+            }
+            throw InvalidOperation("/", left, right);
+        }
+
+        /// <summary><c>%</c>: the remainder, with the sign of the left operand</summary>
+        public static object Remainder(object left, object right)
+        {
+            if (Numeric(left) && Numeric(right))
+            {
+                if (ToDouble(right) == 0.0)
+                {
+                    throw new AmoskeagException("Operator error: Division by zero");
+                }
+                if (IsInt(left) && IsInt(right))
+                {
+                    long l = ToLong(left);
+                    long r = ToLong(right);
+                    // long.MinValue % -1 overflows in .NET
+                    if (l == long.MinValue && r == -1)
+                    {
+                        return (double)l % (double)r;
+                    }
+                    return l % r;
+                }
+                return ToDouble(left) % ToDouble(right);
+            }
+            throw InvalidOperation("%", left, right);
+        }
+
+        /// <summary><c>^</c>: an integer raised to a non-negative integer stays an integer unless it overflows</summary>
+        public static object Exponent(object left, object right)
+        {
+            if (Numeric(left) && Numeric(right))
+            {
+                if (IsInt(left) && IsInt(right))
+                {
+                    long b = ToLong(left);
+                    long e = ToLong(right);
+                    long? result = CheckedPow(b, e);
+                    if (result.HasValue)
+                    {
+                        return result.Value;
+                    }
+                    return Math.Pow(b, e);
+                }
+                return Math.Pow(ToDouble(left), ToDouble(right));
+            }
+            throw InvalidOperation("^", left, right);
+        }
+
+        /// <summary><paramref name="b"/> to the power <paramref name="e"/>, or null if it overflows or e is not a u32</summary>
+        private static long? CheckedPow(long b, long e)
+        {
+            if (e < 0 || e > uint.MaxValue)
+            {
+                return null;
+            }
+            if (e == 0)
+            {
+                return 1L;
+            }
+            long acc = 1;
+            try
+            {
+                while (true)
+                {
+                    if ((e & 1) == 1)
+                    {
+                        acc = checked(acc * b);
+                        if (e == 1)
+                        {
+                            return acc;
+                        }
+                    }
+                    e /= 2;
+                    b = checked(b * b);
+                }
+            }
+            catch (OverflowException)
+            {
+                return null;
+            }
+        }
+
+        /// <summary><c>==</c>: deep equality, where an integer equals the same number</summary>
+        public static object Eq(object left, object right)
+        {
+            return ValueEquals(left, right);
+        }
+
+        /// <summary><c>!=</c></summary>
+        public static object Ne(object left, object right)
+        {
+            return !ValueEquals(left, right);
+        }
+
+        /// <summary><c>&lt;</c></summary>
+        public static object Lt(object left, object right)
+        {
+            return Compare("<", left, right) < 0;
+        }
+
+        /// <summary><c>&gt;</c></summary>
+        public static object Gt(object left, object right)
+        {
+            int ordering = Compare(">", left, right);
+            return ordering > 0 && ordering != Unordered;
+        }
+
+        /// <summary><c>&lt;=</c></summary>
+        public static object Le(object left, object right)
+        {
+            return Compare("<=", left, right) <= 0;
+        }
+
+        /// <summary><c>&gt;=</c></summary>
+        public static object Ge(object left, object right)
+        {
+            int ordering = Compare(">=", left, right);
+            return ordering >= 0 && ordering != Unordered;
+        }
+
+        /// <summary><c>and</c>: both operands are evaluated, and the result is a boolean</summary>
+        public static object And(object left, object right)
+        {
+            return Truthy(left) && Truthy(right);
+        }
+
+        /// <summary><c>or</c>: both operands are evaluated, and the result is a boolean</summary>
+        public static object Or(object left, object right)
+        {
+            return Truthy(left) || Truthy(right);
+        }
+
+        /// <summary><c>not</c></summary>
+        public static object Not(object operand)
+        {
+            return !Truthy(operand);
+        }
+
+        /// <summary>Unary <c>-</c></summary>
+        public static object Negate(object operand)
+        {
+            if (IsInt(operand))
+            {
+                long n = ToLong(operand);
+                if (n == long.MinValue)
+                {
+                    return -(double)n;
+                }
+                return -n;
+            }
+            if (Numeric(operand))
+            {
+                return -ToDouble(operand);
+            }
+            throw new AmoskeagException("Type error: expected Number, got " + TypeName(operand));
+        }
+
+        /// <summary>What <see cref="Compare"/> gives when a NaN makes every comparison false</summary>
+        /// <remarks>It is positive, so <c>&lt; 0</c> and <c>&lt;= 0</c> are already false for it.</remarks>
+        private const int Unordered = int.MaxValue;
+
+        /// <summary>Order two numbers or two strings, giving <see cref="Unordered"/> for a NaN</summary>
+        private static int Compare(string op, object left, object right)
+        {
+            if (Numeric(left) && Numeric(right))
+            {
+                if (IsInt(left) && IsInt(right))
+                {
+                    return ToLong(left).CompareTo(ToLong(right));
+                }
+                double l = ToDouble(left);
+                double r = ToDouble(right);
+                if (double.IsNaN(l) || double.IsNaN(r))
+                {
+                    return Unordered;
+                }
+                return CompareNumbers(l, r);
+            }
+            if (left is string a && right is string b)
+            {
+                return Math.Sign(CompareStrings(a, b));
+            }
+            throw InvalidOperation(op, left, right);
+        }
+
+        private static AmoskeagException InvalidOperation(string op, object left, object right)
+        {
+            return new AmoskeagException(
+                "Operator error: Invalid operation: " + TypeName(left) + " " + op + " " + TypeName(right));
+        }
+
+        // String functions
+
+        public static object Upcase(object value)
+        {
+            return StringArg(value).ToUpperInvariant();
+        }
+
+        public static object Downcase(object value)
+        {
+            return StringArg(value).ToLowerInvariant();
+        }
+
+        public static object Capitalize(object value)
+        {
+            string s = StringArg(value);
+            if (s.Length == 0)
+            {
+                return s;
+            }
+            int first = char.IsSurrogatePair(s, 0) ? 2 : 1;
+            return s.Substring(0, first).ToUpperInvariant() + s.Substring(first).ToLowerInvariant();
+        }
+
+        public static object Strip(object value)
+        {
+            return StringArg(value).Trim();
+        }
+
+        public static object TrimStart(object value)
+        {
+            return StringArg(value).TrimStart();
+        }
+
+        public static object TrimEnd(object value)
+        {
+            return StringArg(value).TrimEnd();
+        }
+
+        public static object Squish(object value)
+        {
+            return string.Join(" ", Words(StringArg(value)));
+        }
+
+        public static object Split(object value, object separator)
+        {
+            string s = StringArg(value);
+            string sep = StringArg(separator);
+            var parts = new List<object>();
+            if (sep.Length == 0)
+            {
+                // Like Rust's str::split, an empty separator matches around every character
+                parts.Add("");
+                parts.AddRange(CodePoints(s));
+                parts.Add("");
+                return parts;
+            }
+            int start = 0;
+            int found;
+            while ((found = s.IndexOf(sep, start, StringComparison.Ordinal)) >= 0)
+            {
+                parts.Add(s.Substring(start, found - start));
+                start = found + sep.Length;
+            }
+            parts.Add(s.Substring(start));
+            return parts;
+        }
+
+        public static object Chars(object value)
+        {
+            return new List<object>(CodePoints(StringArg(value)));
+        }
+
+        /// <summary>Splits on <c>\n</c> and <c>\r\n</c>; a trailing newline adds no empty line</summary>
+        public static object Lines(object value)
+        {
+            string s = StringArg(value);
+            var lines = new List<object>();
+            int start = 0;
+            while (start < s.Length)
+            {
+                int newline = s.IndexOf('\n', start);
+                if (newline < 0)
+                {
+                    lines.Add(s.Substring(start));
+                    break;
+                }
+                int end = newline > start && s[newline - 1] == '\r' ? newline - 1 : newline;
+                lines.Add(s.Substring(start, end - start));
+                start = newline + 1;
+            }
+            return lines;
+        }
+
+        public static object Join(object array, object separator)
+        {
+            IList items = ArrayArg(array);
+            string sep = StringArg(separator);
+            var joined = new StringBuilder();
+            for (int i = 0; i < items.Count; i++)
+            {
+                if (!(items[i] is string item))
+                {
+                    throw TypeError("Array of Strings", "Array containing " + ArgType(items[i]));
+                }
+                if (i > 0)
+                {
+                    joined.Append(sep);
+                }
+                joined.Append(item);
+            }
+            return joined.ToString();
+        }
+
+        /// <summary>Keeps the first <c>length</c> characters of a string longer than <c>length</c> UTF-8 bytes</summary>
+        public static object Truncate(object value, object length)
+        {
+            string s = StringArg(value);
+            long max = NonNegative(NumberArg(length));
+            if (Utf8Length(s) <= max)
+            {
+                return s;
+            }
+            return TakeCodePoints(s, 0, max);
+        }
+
+        /// <summary>Positions count characters; a negative start counts back from the end</summary>
+        public static object Substring(object value, object start, object length)
+        {
+            string s = StringArg(value);
+            long offset = Saturate(NumberArg(start));
+            long count = NonNegative(NumberArg(length));
+            if (offset < 0)
+            {
+                offset = Math.Max(CodePoints(s).Count() + offset, 0);
+            }
+            return TakeCodePoints(s, offset, count);
+        }
+
+        public static object Repeat(object value, object count)
+        {
+            string s = StringArg(value);
+            double n = NumberArg(count);
+            if (!IsFinite(n) || n < 0.0)
+            {
+                throw new AmoskeagException(
+                    "Function error: Argument error: repeat count must be a non-negative number, got "
+                    + FormatNumber(n));
+            }
+            long times = (long)n;
+            long bytes = Utf8Length(s);
+            if (times != 0 && bytes > MaxRepeatLength / times)
+            {
+                throw new AmoskeagException(
+                    "Function error: Value error: repeat result would exceed the maximum length of "
+                    + MaxRepeatLength + " bytes");
+            }
+            var repeated = new StringBuilder();
+            for (long i = 0; bytes != 0 && i < times; i++)
+            {
+                repeated.Append(s);
+            }
+            return repeated.ToString();
+        }
+
+        public static object PadLeft(object value, object width, object fill)
+        {
+            string s = StringArg(value);
+            return Padding(s, width, fill) + s;
+        }
+
+        public static object PadRight(object value, object width, object fill)
+        {
+            string s = StringArg(value);
+            return s + Padding(s, width, fill);
+        }
+
+        private static string Padding(string s, object width, object fill)
+        {
+            double w = NumberArg(width);
+            string f = StringArg(fill);
+            if (CodePoints(f).Count() != 1)
+            {
+                throw new AmoskeagException(
+                    "Function error: Argument error: fill must be a single character, got '" + f + "'");
+            }
+            long missing = NonNegative(w) - CodePoints(s).Count();
+            if (missing > 0 && missing > (MaxRepeatLength - Utf8Length(s)) / Utf8Length(f))
+            {
+                throw new AmoskeagException(
+                    "Function error: Value error: pad result would exceed the maximum length of "
+                    + MaxRepeatLength + " bytes");
+            }
+            var padding = new StringBuilder();
+            for (long i = 0; i < missing; i++)
+            {
+                padding.Append(f);
+            }
+            return padding.ToString();
+        }
+
+        public static object Replace(object value, object find, object replacement)
+        {
+            string s = StringArg(value);
+            string f = StringArg(find);
+            string r = StringArg(replacement);
+            if (f.Length == 0)
+            {
+                // Like Rust's str::replace, an empty pattern matches around every character
+                var replaced = new StringBuilder(r);
+                foreach (string c in CodePoints(s))
+                {
+                    replaced.Append(c).Append(r);
+                }
+                return replaced.ToString();
+            }
+            return s.Replace(f, r);
+        }
+
+        public static object StartsWith(object value, object prefix)
+        {
+            return StringArg(value).StartsWith(StringArg(prefix), StringComparison.Ordinal);
+        }
+
+        public static object EndsWith(object value, object suffix)
+        {
+            return StringArg(value).EndsWith(StringArg(suffix), StringComparison.Ordinal);
+        }
+
+        /// <summary>The character position of the first occurrence, or nil</summary>
+        public static object IndexOf(object value, object substring)
+        {
+            string s = StringArg(value);
+            int found = s.IndexOf(StringArg(substring), StringComparison.Ordinal);
+            if (found < 0)
+            {
+                return null;
+            }
+            return (long)CodePoints(s.Substring(0, found)).Count();
+        }
+
+        public static object Includes(object value, object substring)
+        {
+            return StringArg(value).IndexOf(StringArg(substring), StringComparison.Ordinal) >= 0;
+        }
+
+        public static object IsNumericString(object value)
+        {
+            return value is string s && NumericPattern.IsMatch(s);
+        }
+
+        public static object Sha256(object value)
+        {
+            using (var sha256 = SHA256.Create())
+            {
+                return Hex(sha256.ComputeHash(Encoding.UTF8.GetBytes(StringArg(value))));
+            }
+        }
+
+        public static object Md5(object value)
+        {
+            using (var md5 = MD5.Create())
+            {
+                return Hex(md5.ComputeHash(Encoding.UTF8.GetBytes(StringArg(value))));
+            }
+        }
+
+        public static object Base64Encode(object value)
+        {
+            return Convert.ToBase64String(Encoding.UTF8.GetBytes(StringArg(value)));
+        }
+
+        public static object Base64Decode(object value)
+        {
+            string s = StringArg(value);
+            // Convert skips whitespace, which the interpreter rejects
+            string problem = s.Length % 4 != 0 ? "Invalid padding"
+                : s.Any(c => !IsBase64Char(c)) ? "Invalid symbol"
+                : null;
+            byte[] bytes = null;
+            if (problem == null)
+            {
+                try
+                {
+                    bytes = Convert.FromBase64String(s);
+                }
+                catch (FormatException e)
+                {
+                    problem = e.Message;
+                }
+            }
+            if (problem != null)
+            {
+                throw new AmoskeagException("Function error: Value error: invalid Base64 input: " + problem);
+            }
+            return Utf8(bytes, "Base64 input does not decode to UTF-8 text");
+        }
+
+        /// <summary>Percent-encodes every byte outside letters, digits and <c>-._~</c></summary>
+        public static object UrlEncode(object value)
+        {
+            var encoded = new StringBuilder();
+            foreach (byte b in Encoding.UTF8.GetBytes(StringArg(value)))
+            {
+                char c = (char)b;
+                if ((c >= 'a' && c <= 'z') || (c >= 'A' && c <= 'Z') || (c >= '0' && c <= '9') || "-._~".IndexOf(c) >= 0)
+                {
+                    encoded.Append(c);
+                }
+                else
+                {
+                    encoded.Append('%').Append(b.ToString("X2", CultureInfo.InvariantCulture));
+                }
+            }
+            return encoded.ToString();
+        }
+
+        /// <summary>Decodes <c>%</c> escapes; <c>+</c> is left as it is</summary>
+        public static object UrlDecode(object value)
+        {
+            byte[] bytes = Encoding.UTF8.GetBytes(StringArg(value));
+            var decoded = new List<byte>(bytes.Length);
+            for (int i = 0; i < bytes.Length; i++)
+            {
+                if (bytes[i] == '%')
+                {
+                    int high = i + 2 < bytes.Length ? HexDigit(bytes[i + 1]) : -1;
+                    int low = i + 2 < bytes.Length ? HexDigit(bytes[i + 2]) : -1;
+                    if (high < 0 || low < 0)
+                    {
+                        throw new AmoskeagException(
+                            "Function error: Value error: invalid percent escape at position " + i);
+                    }
+                    decoded.Add((byte)(high * 16 + low));
+                    i += 2;
+                }
+                else
+                {
+                    decoded.Add(bytes[i]);
+                }
+            }
+            return Utf8(decoded.ToArray(), "URL input does not decode to UTF-8 text");
+        }
+
+        // Numeric functions
+
+        public static object Abs(object value)
+        {
+            return Math.Abs(NumberArg(value));
+        }
+
+        public static object Ceil(object value)
+        {
+            return Math.Ceiling(NumberArg(value));
+        }
+
+        public static object Floor(object value)
+        {
+            return Math.Floor(NumberArg(value));
+        }
+
+        public static object Round(object value)
+        {
+            return Round(value, 0.0);
+        }
+
+        /// <summary>Rounds half away from zero; negative digits round to tens, hundreds and so on</summary>
+        public static object Round(object value, object digits)
+        {
+            double n = NumberArg(value);
+            double d = NumberArg(digits);
+            if (!IsFinite(d))
+            {
+                throw new AmoskeagException("Function error: Argument error: digits must be finite");
+            }
+            int places = (int)Math.Max(-20.0, Math.Min(20.0, d));
+            double multiplier = Math.Pow(10, Math.Abs(places));
+            if (places >= 0)
+            {
+                return Math.Round(n * multiplier, MidpointRounding.AwayFromZero) / multiplier;
+            }
+            return Math.Round(n / multiplier, MidpointRounding.AwayFromZero) * multiplier;
+        }
+
+        public static object Trunc(object value)
+        {
+            return Math.Truncate(NumberArg(value));
+        }
+
+        public static object Sign(object value)
+        {
+            double n = NumberArg(value);
+            // Math.Sign throws for NaN
+            if (double.IsNaN(n))
+            {
+                return n;
+            }
+            return (double)Math.Sign(n);
+        }
+
+        public static object Clamp(object value, object lo, object hi)
+        {
+            double n = NumberArg(value);
+            double low = NumberArg(lo);
+            double high = NumberArg(hi);
+            if (low > high || double.IsNaN(low) || double.IsNaN(high))
+            {
+                throw new AmoskeagException("Function error: Argument error: clamp requires lo <= hi, got lo "
+                    + FormatNumber(low) + " and hi " + FormatNumber(high));
+            }
+            return n < low ? low : (n > high ? high : n);
+        }
+
+        public static object Plus(object a, object b)
+        {
+            return NumberArg(a) + NumberArg(b);
+        }
+
+        public static object Minus(object a, object b)
+        {
+            return NumberArg(a) - NumberArg(b);
+        }
+
+        public static object Times(object a, object b)
+        {
+            return NumberArg(a) * NumberArg(b);
+        }
+
+        public static object DividedBy(object a, object b)
+        {
+            double x = NumberArg(a);
+            double y = NumberArg(b);
+            if (y == 0.0)
+            {
+                throw new AmoskeagException("Function error: Invalid operation: Division by zero");
+            }
+            return x / y;
+        }
+
+        public static object Modulo(object a, object b)
+        {
+            double x = NumberArg(a);
+            double y = NumberArg(b);
+            if (y == 0.0)
+            {
+                throw new AmoskeagException("Function error: Invalid operation: Modulo by zero");
+            }
+            return x - Math.Truncate(x / y) * y;
+        }
+
+        /// <summary>The larger of two numbers, ignoring a NaN</summary>
+        public static object Max(object a, object b)
+        {
+            double x = NumberArg(a);
+            double y = NumberArg(b);
+            return double.IsNaN(x) ? y : (double.IsNaN(y) ? x : Math.Max(x, y));
+        }
+
+        /// <summary>The smaller of two numbers, ignoring a NaN</summary>
+        public static object Min(object a, object b)
+        {
+            double x = NumberArg(a);
+            double y = NumberArg(b);
+            return double.IsNaN(x) ? y : (double.IsNaN(y) ? x : Math.Min(x, y));
+        }
+
+        /// <summary>Unlike <c>^</c>, fails on results that are not real numbers</summary>
+        public static object Power(object @base, object exponent)
+        {
+            double b = NumberArg(@base);
+            double e = NumberArg(exponent);
+            double result = Math.Pow(b, e);
+            if (double.IsNaN(result) && !double.IsNaN(b) && !double.IsNaN(e))
+            {
+                throw new AmoskeagException("Function error: Value error: Cannot raise negative number "
+                    + FormatNumber(b) + " to fractional power " + FormatNumber(e));
+            }
+            if (double.IsInfinity(result) && IsFinite(b) && IsFinite(e))
+            {
+                if (b == 0.0)
+                {
+                    throw new AmoskeagException(
+                        "Function error: Value error: Cannot raise zero to negative power " + FormatNumber(e));
+                }
+                throw new AmoskeagException(
+                    "Function error: Value error: " + FormatNumber(b) + " raised to " + FormatNumber(e) + " overflows");
+            }
+            return result;
+        }
+
+        public static object Sqrt(object value)
+        {
+            double n = NumberArg(value);
+            if (n < 0.0)
+            {
+                throw new AmoskeagException(
+                    "Function error: Value error: Cannot take square root of negative number: " + FormatNumber(n));
+            }
+            return Math.Sqrt(n);
+        }
+
+        // Collection functions
+
+        /// <summary>The length of an array or dictionary, or of a string in UTF-8 bytes</summary>
+        public static object Size(object value)
+        {
+            if (value is string s)
+            {
+                return (double)Utf8Length(s);
+            }
+            if (value is IList items)
+            {
+                return (double)items.Count;
+            }
+            IDictionary<string, object> dictionary = AsDictionary(value);
+            if (dictionary != null)
+            {
+                return (double)dictionary.Count;
+            }
+            throw TypeError("String, Array, or Dictionary", ArgType(value));
+        }
+
+        public static object First(object value)
+        {
+            IList items = ArrayArg(value);
+            return items.Count == 0 ? null : items[0];
+        }
+
+        public static object Last(object value)
+        {
+            IList items = ArrayArg(value);
+            return items.Count == 0 ? null : items[items.Count - 1];
+        }
+
+        /// <summary>Whether an array holds a value, or a string holds a substring</summary>
+        public static object Contains(object collection, object value)
+        {
+            if (collection is IList items)
+            {
+                return IndexIn(items, value) >= 0;
+            }
+            if (collection is string s)
+            {
+                return s.IndexOf(StringArg(value), StringComparison.Ordinal) >= 0;
+            }
+            throw TypeError("Array or String", ArgType(collection));
+        }
+
+        public static object Sum(object value)
+        {
+            double total = 0.0;
+            foreach (double n in Numbers(value))
+            {
+                total += n;
+            }
+            return total;
+        }
+
+        /// <summary>The mean of an array of numbers, or nil if it is empty</summary>
+        public static object Avg(object value)
+        {
+            double[] numbers = Numbers(value);
+            if (numbers.Length == 0)
+            {
+                return null;
+            }
+            return (double)Sum(value) / numbers.Length;
+        }
+
+        /// <summary>The middle number, or the mean of the middle two; nil if the array is empty</summary>
+        public static object Median(object value)
+        {
+            double[] numbers = Numbers(value);
+            if (numbers.Length == 0)
+            {
+                return null;
+            }
+            double[] sorted = numbers.OrderBy(n => n, Comparer<double>.Create(CompareNumbers)).ToArray();
+            int mid = sorted.Length / 2;
+            if (sorted.Length % 2 == 0)
+            {
+                return (sorted[mid - 1] + sorted[mid]) / 2.0;
+            }
+            return sorted[mid];
+        }
+
+        /// <summary>Sorts an array that holds only numbers or only strings</summary>
+        public static object Sort(object value)
+        {
+            List<object> items = Copy(value);
+            // OrderBy is a stable sort, like the interpreter's
+            if (items.All(Numeric))
+            {
+                var order = Comparer<object>.Create((a, b) => CompareNumbers(ToDouble(a), ToDouble(b)));
+                return items.OrderBy(item => item, order).ToList();
+            }
+            if (items.All(item => item is string))
+            {
+                var order = Comparer<object>.Create((a, b) => CompareStrings((string)a, (string)b));
+                return items.OrderBy(item => item, order).ToList();
+            }
+            throw new AmoskeagException(
+                "Function error: Invalid operation: Array must contain all Numbers or all Strings to sort");
+        }
+
+        /// <summary>A dictionary's keys, sorted</summary>
+        public static object Keys(object value)
+        {
+            var keys = new List<object>(DictionaryArg(value).Keys);
+            keys.Sort((a, b) => CompareStrings((string)a, (string)b));
+            return keys;
+        }
+
+        public static object Values(object value)
+        {
+            return DictionaryArg(value).Values.ToList();
+        }
+
+        public static object Reverse(object value)
+        {
+            List<object> reversed = Copy(value);
+            reversed.Reverse();
+            return reversed;
+        }
+
+        /// <summary>The element at a 0-based index, counting back from the end if negative; nil if out of range</summary>
+        public static object At(object array, object index)
+        {
+            IList items = ArrayArg(array);
+            long i = Saturate(NumberArg(index));
+            long actual = i < 0 ? items.Count + i : i;
+            return actual < 0 || actual >= items.Count ? null : items[(int)actual];
+        }
+
+        public static object Uniq(object value)
+        {
+            var unique = new List<object>();
+            foreach (object item in ArrayArg(value))
+            {
+                if (IndexIn(unique, item) < 0)
+                {
+                    unique.Add(item);
+                }
+            }
+            return unique;
+        }
+
+        public static object Pick(object dictionary, object keys)
+        {
+            IDictionary<string, object> entries = DictionaryArg(dictionary);
+            var picked = new Dictionary<string, object>();
+            foreach (string key in StringKeys(keys))
+            {
+                object value;
+                if (entries.TryGetValue(key, out value))
+                {
+                    picked[key] = value;
+                }
+            }
+            return picked;
+        }
+
+        public static object Omit(object dictionary, object keys)
+        {
+            IDictionary<string, object> entries = DictionaryArg(dictionary);
+            List<string> omitted = StringKeys(keys);
+            var remaining = new Dictionary<string, object>();
+            foreach (KeyValuePair<string, object> entry in entries)
+            {
+                if (!omitted.Contains(entry.Key))
+                {
+                    remaining[entry.Key] = entry.Value;
+                }
+            }
+            return remaining;
+        }
+
+        /// <summary>Whether a dictionary has a key, even if its value is nil</summary>
+        public static object HasKey(object dictionary, object key)
+        {
+            return DictionaryArg(dictionary).ContainsKey(StringArg(key));
+        }
+
+        public static object Get(object dictionary, object key)
+        {
+            return Get(dictionary, key, null);
+        }
+
+        /// <summary>The value for a key, or the default when the key is missing; a nil value is returned as nil</summary>
+        public static object Get(object dictionary, object key, object defaultValue)
+        {
+            IDictionary<string, object> entries = DictionaryArg(dictionary);
+            object value;
+            return entries.TryGetValue(StringArg(key), out value) ? value : defaultValue;
+        }
+
+        /// <summary>Follows string keys into dictionaries and whole number indexes into arrays; nil if anything is missing</summary>
+        public static object Dig(object data, object path)
+        {
+            object current = data;
+            foreach (object step in ArrayArg(path))
+            {
+                if (step is string key)
+                {
+                    IDictionary<string, object> dictionary = AsDictionary(current);
+                    if (dictionary == null || !dictionary.TryGetValue(key, out current))
+                    {
+                        return null;
+                    }
+                }
+                else if (Numeric(step))
+                {
+                    if (!(current is IList items))
+                    {
+                        return null;
+                    }
+                    double n = ToDouble(step);
+                    if (n % 1 != 0)
+                    {
+                        return null;
+                    }
+                    long i = Saturate(n);
+                    long actual = i < 0 ? items.Count + i : i;
+                    if (actual < 0 || actual >= items.Count)
+                    {
+                        return null;
+                    }
+                    current = items[(int)actual];
+                }
+                else
+                {
+                    throw TypeError("Array of Strings or Numbers", "Array containing " + ArgType(step));
+                }
+            }
+            return current;
+        }
+
+        public static object Union(object left, object right)
+        {
+            IList first = ArrayArg(left);
+            IList second = ArrayArg(right);
+            var result = new List<object>();
+            foreach (object item in first.Cast<object>().Concat(second.Cast<object>()))
+            {
+                if (IndexIn(result, item) < 0)
+                {
+                    result.Add(item);
+                }
+            }
+            return result;
+        }
+
+        public static object Intersection(object left, object right)
+        {
+            IList first = ArrayArg(left);
+            IList second = ArrayArg(right);
+            var result = new List<object>();
+            foreach (object item in first)
+            {
+                if (IndexIn(second, item) >= 0 && IndexIn(result, item) < 0)
+                {
+                    result.Add(item);
+                }
+            }
+            return result;
+        }
+
+        public static object Difference(object left, object right)
+        {
+            IList first = ArrayArg(left);
+            IList second = ArrayArg(right);
+            var result = new List<object>();
+            foreach (object item in first)
+            {
+                if (IndexIn(second, item) < 0 && IndexIn(result, item) < 0)
+                {
+                    result.Add(item);
+                }
+            }
+            return result;
+        }
+
+        // Logic functions
+
+        /// <summary>The element at a 1-based index, or nil</summary>
+        public static object Choose(object index, object array)
+        {
+            double i = NumberArg(index);
+            IList items = ArrayArg(array);
+            if (i < 1.0)
+            {
+                return null;
+            }
+            long actual = Saturate(i) - 1;
+            return actual >= items.Count ? null : items[(int)actual];
+        }
+
+        /// <summary>Like <c>if</c>, but both branches are evaluated</summary>
+        public static object IfThenElse(object condition, object whenTrue, object whenFalse)
+        {
+            return Truthy(condition) ? whenTrue : whenFalse;
+        }
+
+        public static object IsNil(object value)
+        {
+            return value == null;
+        }
+
+        public static object IsNumber(object value)
+        {
+            return Numeric(value);
+        }
+
+        public static object IsString(object value)
+        {
+            return value is string;
+        }
+
+        public static object IsBoolean(object value)
+        {
+            return value is bool;
+        }
+
+        public static object IsArray(object value)
+        {
+            return value is IList;
+        }
+
+        public static object IsDictionary(object value)
+        {
+            return AsDictionary(value) != null;
+        }
+
+        /// <summary>The first value that is not nil, or nil</summary>
+        public static object Coalesce(params object[] values)
+        {
+            foreach (object value in values)
+            {
+                if (value != null)
+                {
+                    return value;
+                }
+            }
+            return null;
+        }
+
+        /// <summary><c>default(value, fallback)</c></summary>
+        public static object Default(object value, object fallback)
+        {
+            return Coalesce(value, fallback);
+        }
+
+        public static object Between(object value, object lo, object hi)
+        {
+            return Between(value, lo, hi, "[]");
+        }
+
+        /// <summary>Whether a number or string lies in a range; bounds are "[]", "[)", "(]" or "()"</summary>
+        public static object Between(object value, object lo, object hi, object bounds)
+        {
+            string b = StringArg(bounds);
+            bool lowerInclusive;
+            bool upperInclusive;
+            switch (b)
+            {
+                case "[]":
+                    lowerInclusive = true;
+                    upperInclusive = true;
+                    break;
+                case "[)":
+                    lowerInclusive = true;
+                    upperInclusive = false;
+                    break;
+                case "(]":
+                    lowerInclusive = false;
+                    upperInclusive = true;
+                    break;
+                case "()":
+                    lowerInclusive = false;
+                    upperInclusive = false;
+                    break;
+                default:
+                    throw new AmoskeagException("Function error: Argument error: bounds must be one of "
+                        + "\"[]\", \"[)\", \"(]\", or \"()\", got " + Quote(b));
+            }
+            int low = Order(value, lo);
+            int high = Order(value, hi);
+            bool aboveLo = low != Unordered && (low > 0 || (low == 0 && lowerInclusive));
+            bool belowHi = high < 0 || (high == 0 && upperInclusive);
+            return aboveLo && belowHi;
+        }
+
+        /// <summary>Order <paramref name="value"/> against a bound of the same type, for <c>between</c></summary>
+        private static int Order(object value, object other)
+        {
+            if (Numeric(value) && Numeric(other))
+            {
+                double a = ToDouble(value);
+                double b = ToDouble(other);
+                return double.IsNaN(a) || double.IsNaN(b) ? Unordered : CompareNumbers(a, b);
+            }
+            if (value is string s && other is string t)
+            {
+                return Math.Sign(CompareStrings(s, t));
+            }
+            if (Numeric(value) || value is string)
+            {
+                throw TypeError(ArgType(value), ArgType(other));
+            }
+            throw TypeError("Number or String", ArgType(value));
+        }
+
+        // Conversion functions
+
+        /// <summary>Numbers pass through, plain decimal strings are parsed, booleans become 1 or 0; anything else is nil</summary>
+        public static object ToNumber(object value)
+        {
+            if (Numeric(value))
+            {
+                return ToDouble(value);
+            }
+            if (value is bool b)
+            {
+                return b ? 1.0 : 0.0;
+            }
+            if (value is string s)
+            {
+                string trimmed = s.Trim();
+                if (NumericPattern.IsMatch(trimmed))
+                {
+                    return ParseDouble(trimmed);
+                }
+            }
+            return null;
+        }
+
+        public static object ToNumberStrict(object value)
+        {
+            return Strict(ToNumber(value), value, "Number");
+        }
+
+        /// <summary>Arrays and dictionaries give nil; nil gives the empty string</summary>
+        public static object ToString(object value)
+        {
+            if (value == null)
+            {
+                return "";
+            }
+            if (value is IList || AsDictionary(value) != null)
+            {
+                return null;
+            }
+            if (value is Symbol symbol)
+            {
+                return symbol.Name;
+            }
+            return Display(Floats(value));
+        }
+
+        public static object ToStringStrict(object value)
+        {
+            return Strict(ToString(value), value, "String");
+        }
+
+        /// <summary>Numbers are true unless zero; strings such as "yes", "off" and "1" are recognised; anything else is nil</summary>
+        public static object ToBoolean(object value)
+        {
+            if (value is bool)
+            {
+                return value;
+            }
+            if (Numeric(value))
+            {
+                double n = ToDouble(value);
+                if (double.IsNaN(n))
+                {
+                    return null;
+                }
+                return n != 0.0;
+            }
+            if (value is string s)
+            {
+                switch (s.Trim().ToLowerInvariant())
+                {
+                    case "true":
+                    case "yes":
+                    case "y":
+                    case "on":
+                    case "1":
+                        return true;
+                    case "false":
+                    case "no":
+                    case "n":
+                    case "off":
+                    case "0":
+                        return false;
+                    default:
+                        return null;
+                }
+            }
+            return null;
+        }
+
+        public static object ToBooleanStrict(object value)
+        {
+            return Strict(ToBoolean(value), value, "Boolean");
+        }
+
+        private static object Strict(object result, object value, string target)
+        {
+            if (result != null)
+            {
+                return result;
+            }
+            string what = value is string s ? "String " + Quote(s) : ArgType(value);
+            throw new AmoskeagException("Function error: Value error: Cannot convert " + what + " to " + target);
+        }
+
+        // Values
+
+        /// <summary>A value as the interpreter displays it, e.g. in string concatenation</summary>
+        public static string Display(object value)
+        {
+            if (value == null)
+            {
+                return "nil";
+            }
+            if (value is bool b)
+            {
+                return b ? "true" : "false";
+            }
+            if (IsInt(value))
+            {
+                return ToLong(value).ToString(CultureInfo.InvariantCulture);
+            }
+            if (Numeric(value))
+            {
+                return FormatNumber(ToDouble(value));
+            }
+            if (value is string s)
+            {
+                return s;
+            }
+            if (value is IList items)
+            {
+                return "[" + string.Join(", ", items.Cast<object>().Select(Display)) + "]";
+            }
+            IDictionary<string, object> dictionary = AsDictionary(value);
+            if (dictionary != null)
+            {
+                return "{" + string.Join(", ", dictionary.Select(entry => "\"" + entry.Key + "\": " + Display(entry.Value))) + "}";
+            }
+            return Convert.ToString(value, CultureInfo.InvariantCulture);
+        }
+
+        /// <summary>A number written the way the interpreter writes it: 3 rather than 3.0, and never in exponent form</summary>
+        public static string FormatNumber(double n)
+        {
+            if (double.IsNaN(n))
+            {
+                return "NaN";
+            }
+            if (double.IsInfinity(n))
+            {
+                return n > 0 ? "inf" : "-inf";
+            }
+            if (n == 0.0)
+            {
+                return BitConverter.DoubleToInt64Bits(n) < 0 ? "-0" : "0";
+            }
+            string roundTrip = n.ToString("R", CultureInfo.InvariantCulture);
+            int exponentAt = roundTrip.IndexOf('E');
+            if (exponentAt < 0)
+            {
+                return roundTrip;
+            }
+
+            // Write out digits such as 1.5E+21 in full
+            string mantissa = roundTrip.Substring(0, exponentAt);
+            int exponent = int.Parse(roundTrip.Substring(exponentAt + 1), NumberStyles.AllowLeadingSign, CultureInfo.InvariantCulture);
+            string sign = "";
+            if (mantissa.StartsWith("-", StringComparison.Ordinal))
+            {
+                sign = "-";
+                mantissa = mantissa.Substring(1);
+            }
+            int point = mantissa.IndexOf('.');
+            string digits = point < 0 ? mantissa : mantissa.Remove(point, 1);
+            int whole = (point < 0 ? mantissa.Length : point) + exponent;
+            if (whole <= 0)
+            {
+                return sign + "0." + new string('0', -whole) + digits;
+            }
+            if (whole >= digits.Length)
+            {
+                return sign + digits + new string('0', whole - digits.Length);
+            }
+            return sign + digits.Substring(0, whole) + "." + digits.Substring(whole);
+        }
+
+        /// <summary>Deep equality, where an integer equals the number with the same value</summary>
+        private static bool ValueEquals(object a, object b)
+        {
+            if (a == null || b == null)
+            {
+                return a == b;
+            }
+            if (Numeric(a) && Numeric(b))
+            {
+                if (IsInt(a) && IsInt(b))
+                {
+                    return ToLong(a) == ToLong(b);
+                }
+                return ToDouble(a) == ToDouble(b);
+            }
+            if (a is IList l && b is IList r)
+            {
+                if (l.Count != r.Count)
+                {
+                    return false;
+                }
+                for (int i = 0; i < l.Count; i++)
+                {
+                    if (!ValueEquals(l[i], r[i]))
+                    {
+                        return false;
+                    }
+                }
+                return true;
+            }
+            IDictionary<string, object> left = AsDictionary(a);
+            IDictionary<string, object> right = AsDictionary(b);
+            if (left != null && right != null)
+            {
+                if (left.Count != right.Count)
+                {
+                    return false;
+                }
+                foreach (KeyValuePair<string, object> entry in left)
+                {
+                    object other;
+                    if (!right.TryGetValue(entry.Key, out other) || !ValueEquals(entry.Value, other))
+                    {
+                        return false;
+                    }
+                }
+                return true;
+            }
+            if (a is bool || a is string || a is Symbol)
+            {
+                return a.Equals(b);
+            }
+            return false;
+        }
+
+        /// <summary>The position of the first element equal to <paramref name="value"/>, or -1</summary>
+        private static int IndexIn(IList items, object value)
+        {
+            for (int i = 0; i < items.Count; i++)
+            {
+                if (ValueEquals(items[i], value))
+                {
+                    return i;
+                }
+            }
+            return -1;
+        }
+
+        /// <summary>A value with every integer, at any depth, replaced by a number</summary>
+        /// <remarks>
+        /// The interpreter promotes the arguments of most standard library
+        /// functions this way, so anything they return from their arguments is
+        /// promoted too. Functions that only select or reorder values, such as
+        /// <c>first</c> and <c>sort</c>, return them unchanged.
+        /// </remarks>
+        private static object Floats(object value)
+        {
+            if (IsInt(value))
+            {
+                return ToDouble(value);
+            }
+            if (value is IList items)
+            {
+                return items.Cast<object>().Select(Floats).ToList();
+            }
+            IDictionary<string, object> dictionary = AsDictionary(value);
+            if (dictionary != null)
+            {
+                var promoted = new Dictionary<string, object>();
+                foreach (KeyValuePair<string, object> entry in dictionary)
+                {
+                    promoted[entry.Key] = Floats(entry.Value);
+                }
+                return promoted;
+            }
+            return value;
+        }
+
+        private static bool Numeric(object value)
+        {
+            return IsInt(value) || value is double || value is float || value is decimal || value is ulong;
+        }
+
+        private static bool IsInt(object value)
+        {
+            return value is long || value is int || value is short || value is sbyte
+                || value is byte || value is ushort || value is uint;
+        }
+
+        private static double ToDouble(object value)
+        {
+            return Convert.ToDouble(value, CultureInfo.InvariantCulture);
+        }
+
+        private static long ToLong(object value)
+        {
+            return Convert.ToInt64(value, CultureInfo.InvariantCulture);
+        }
+
+        /// <summary>
+        /// A dictionary as string-keyed entries, or null if the value is not one
+        /// </summary>
+        /// <remarks>
+        /// Input data may hold any <see cref="IDictionary"/>, such as a
+        /// <c>Dictionary&lt;string, long&gt;</c>, which is copied.
+        /// </remarks>
+        private static IDictionary<string, object> AsDictionary(object value)
+        {
+            if (value is IDictionary<string, object> entries)
+            {
+                return entries;
+            }
+            if (value is IDictionary other)
+            {
+                var copy = new Dictionary<string, object>();
+                foreach (DictionaryEntry entry in other)
+                {
+                    copy[Convert.ToString(entry.Key, CultureInfo.InvariantCulture)] = entry.Value;
+                }
+                return copy;
+            }
+            return null;
+        }
+
+        private static string TypeName(object value)
+        {
+            if (value == null)
+            {
+                return "Nil";
+            }
+            if (IsInt(value))
+            {
+                return "Integer";
+            }
+            if (Numeric(value))
+            {
+                return "Number";
+            }
+            if (value is string)
+            {
+                return "String";
+            }
+            if (value is bool)
+            {
+                return "Boolean";
+            }
+            if (value is Symbol)
+            {
+                return "Symbol";
+            }
+            if (value is IList)
+            {
+                return "Array";
+            }
+            if (AsDictionary(value) != null)
+            {
+                return "Dictionary";
+            }
+            return value.GetType().Name;
+        }
+
+        /// <summary>The type of a standard library argument, which never sees integers</summary>
+        private static string ArgType(object value)
+        {
+            return IsInt(value) ? "Number" : TypeName(value);
+        }
+
+        private static AmoskeagException TypeError(string expected, string got)
+        {
+            return new AmoskeagException("Function error: Type error: expected " + expected + ", got " + got);
+        }
+
+        private static double NumberArg(object value)
+        {
+            if (!Numeric(value))
+            {
+                throw TypeError("Number", ArgType(value));
+            }
+            return ToDouble(value);
+        }
+
+        private static string StringArg(object value)
+        {
+            if (!(value is string s))
+            {
+                throw TypeError("String", ArgType(value));
+            }
+            return s;
+        }
+
+        private static IList ArrayArg(object value)
+        {
+            if (!(value is IList items))
+            {
+                throw TypeError("Array", ArgType(value));
+            }
+            return items;
+        }
+
+        private static IDictionary<string, object> DictionaryArg(object value)
+        {
+            IDictionary<string, object> dictionary = AsDictionary(value);
+            if (dictionary == null)
+            {
+                throw TypeError("Dictionary", ArgType(value));
+            }
+            return dictionary;
+        }
+
+        /// <summary>A copy of an array, for functions that reorder it</summary>
+        private static List<object> Copy(object value)
+        {
+            return ArrayArg(value).Cast<object>().ToList();
+        }
+
+        private static double[] Numbers(object value)
+        {
+            IList items = ArrayArg(value);
+            var numbers = new double[items.Count];
+            for (int i = 0; i < numbers.Length; i++)
+            {
+                if (!Numeric(items[i]))
+                {
+                    throw TypeError("Array of Numbers", "Array containing " + ArgType(items[i]));
+                }
+                numbers[i] = ToDouble(items[i]);
+            }
+            return numbers;
+        }
+
+        private static List<string> StringKeys(object keys)
+        {
+            var result = new List<string>();
+            foreach (object key in ArrayArg(keys))
+            {
+                if (!(key is string s))
+                {
+                    throw TypeError("Array of Strings", "Array containing " + ArgType(key));
+                }
+                result.Add(s);
+            }
+            return result;
+        }
+
+        /// <summary>Orders numbers, treating a NaN as equal to anything</summary>
+        private static int CompareNumbers(double a, double b)
+        {
+            return a < b ? -1 : (a > b ? 1 : 0);
+        }
+
+        /// <summary>Orders strings by code point, as Rust does, rather than by UTF-16 unit</summary>
+        private static int CompareStrings(string a, string b)
+        {
+            int i = 0;
+            int j = 0;
+            while (i < a.Length && j < b.Length)
+            {
+                int x = char.IsSurrogatePair(a, i) ? char.ConvertToUtf32(a, i) : a[i];
+                int y = char.IsSurrogatePair(b, j) ? char.ConvertToUtf32(b, j) : b[j];
+                if (x != y)
+                {
+                    return x.CompareTo(y);
+                }
+                i += x > 0xFFFF ? 2 : 1;
+                j += y > 0xFFFF ? 2 : 1;
+            }
+            return (a.Length - i).CompareTo(b.Length - j);
+        }
+
+        /// <summary>A double converted to a long the way Rust's <c>as i64</c> does: saturating, with NaN as zero</summary>
+        private static long Saturate(double n)
+        {
+            if (double.IsNaN(n))
+            {
+                return 0;
+            }
+            if (n >= 9223372036854775807.0)
+            {
+                return long.MaxValue;
+            }
+            if (n <= -9223372036854775808.0)
+            {
+                return long.MinValue;
+            }
+            return (long)n;
+        }
+
+        /// <summary>A count taken from a number, with negatives and NaN as zero</summary>
+        private static long NonNegative(double n)
+        {
+            return n > 0 ? Saturate(n) : 0;
+        }
+
+        private static bool IsFinite(double n)
+        {
+            return !double.IsNaN(n) && !double.IsInfinity(n);
+        }
+
+        /// <summary>
+        /// Parses a string matching <see cref="NumericPattern"/>, giving infinity when it is too large and
+        /// null when its digits are not ASCII, which the interpreter cannot parse either
+        /// </summary>
+        private static double? ParseDouble(string s)
+        {
+            try
+            {
+                return double.Parse(s, NumberStyles.Float, CultureInfo.InvariantCulture);
+            }
+            catch (FormatException)
+            {
+                return null;
+            }
+            catch (OverflowException)
+            {
+                // .NET Framework throws where .NET Core and Rust give infinity
+                return s.StartsWith("-", StringComparison.Ordinal) ? double.NegativeInfinity : double.PositiveInfinity;
+            }
+        }
+
+        /// <summary>The characters of a string, each of which may be a surrogate pair</summary>
+        private static IEnumerable<string> CodePoints(string s)
+        {
+            int i = 0;
+            while (i < s.Length)
+            {
+                int width = char.IsSurrogatePair(s, i) ? 2 : 1;
+                yield return s.Substring(i, width);
+                i += width;
+            }
+        }
+
+        private static List<string> Words(string s)
+        {
+            var words = new List<string>();
+            var word = new StringBuilder();
+            foreach (char c in s)
+            {
+                if (char.IsWhiteSpace(c))
+                {
+                    if (word.Length > 0)
+                    {
+                        words.Add(word.ToString());
+                        word.Clear();
+                    }
+                }
+                else
+                {
+                    word.Append(c);
+                }
+            }
+            if (word.Length > 0)
+            {
+                words.Add(word.ToString());
+            }
+            return words;
+        }
+
+        private static string TakeCodePoints(string s, long skip, long take)
+        {
+            int skipped = (int)Math.Min(skip, int.MaxValue);
+            int taken = (int)Math.Min(take, int.MaxValue);
+            return string.Concat(CodePoints(s).Skip(skipped).Take(taken));
+        }
+
+        private static long Utf8Length(string s)
+        {
+            return Encoding.UTF8.GetByteCount(s);
+        }
+
+        private static string Utf8(byte[] bytes, string error)
+        {
+            try
+            {
+                return StrictUtf8.GetString(bytes);
+            }
+            catch (DecoderFallbackException)
+            {
+                throw new AmoskeagException("Function error: Value error: " + error);
+            }
+        }
+
+        private static bool IsBase64Char(char c)
+        {
+            return (c >= 'A' && c <= 'Z') || (c >= 'a' && c <= 'z') || (c >= '0' && c <= '9')
+                || c == '+' || c == '/' || c == '=';
+        }
+
+        private static int HexDigit(byte b)
+        {
+            if (b >= '0' && b <= '9')
+            {
+                return b - '0';
+            }
+            if (b >= 'a' && b <= 'f')
+            {
+                return b - 'a' + 10;
+            }
+            if (b >= 'A' && b <= 'F')
+            {
+                return b - 'A' + 10;
+            }
+            return -1;
+        }
+
+        private static string Hex(byte[] bytes)
+        {
+            var hex = new StringBuilder(bytes.Length * 2);
+            foreach (byte b in bytes)
+            {
+                hex.Append(b.ToString("x2", CultureInfo.InvariantCulture));
+            }
+            return hex.ToString();
+        }
+
+        private static string Quote(string s)
+        {
+            return "\"" + s.Replace("\\", "\\\\").Replace("\"", "\\\"") + "\"";
+        }
+    }
+}
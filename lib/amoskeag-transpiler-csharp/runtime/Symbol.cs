@@ -0,0 +1,48 @@
+using System;
+
+namespace Amoskeag
+{
+    /// <summary>
+    /// An Amoskeag symbol such as <c>:approve</c>.
+    /// </summary>
+    /// <remarks>
+    /// Symbols are equal when their names are equal, so results can be
+    /// compared with <c>Symbol.Of("approve")</c>.
+    /// </remarks>
+    public sealed class Symbol : IEquatable<Symbol>
+    {
+        private Symbol(string name)
+        {
+            Name = name ?? throw new ArgumentNullException(nameof(name));
+        }
+
+        /// <summary>The symbol's name, without the leading colon</summary>
+        public string Name { get; }
+
+        /// <summary>The symbol with the given name, without the leading colon</summary>
+        public static Symbol Of(string name)
+        {
+            return new Symbol(name);
+        }
+
+        public bool Equals(Symbol other)
+        {
+            return other != null && other.Name == Name;
+        }
+
+        public override bool Equals(object obj)
+        {
+            return Equals(obj as Symbol);
+        }
+
+        public override int GetHashCode()
+        {
+            return Name.GetHashCode();
+        }
+
+        public override string ToString()
+        {
+            return ":" + Name;
+        }
+    }
+}
@@ -0,0 +1,694 @@
+//! Amoskeag to C# Transpiler
+//!
+//! This crate transpiles an Amoskeag program into a C# static class with one
+//! method, `Evaluate(IDictionary<string, object> data)`, so .NET systems can
+//! run rules without embedding the interpreter.
+//!
+//! The generated code calls the runtime library in `runtime/`, the `Amoskeag`
+//! namespace, for every operator and standard library function. The runtime
+//! implements the interpreter's semantics, such as integer arithmetic that
+//! falls back to floating point on overflow, so a transpiled rule gives the
+//! same results as the interpreted one.
+
+use amoskeag_parser::{BinaryOp, Expr, UnaryOp};
+use amoskeag_stdlib_functions::signatures::{self, ArityError};
+use std::fmt::Write;
+use thiserror::Error;
+
+/// The C# namespace of the runtime library
+pub const RUNTIME_NAMESPACE: &str = "Amoskeag";
+
+/// Errors that can occur during transpilation
+#[derive(Error, Debug)]
+pub enum TranspileError {
+    #[error("Formatting error: {0}")]
+    FormatError(#[from] std::fmt::Error),
+
+    #[error("Unsupported expression: {0}")]
+    UnsupportedExpression(String),
+
+    #[error("Function '{0}' is not available in the C# runtime")]
+    UnsupportedFunction(String),
+
+    #[error("'{0}' is not a valid C# name")]
+    InvalidName(String),
+
+    #[error(transparent)]
+    Arity(#[from] ArityError),
+}
+
+/// Configuration for the transpiler
+#[derive(Debug, Clone)]
+pub struct TranspileConfig {
+    /// Namespace of the generated class (default: none, the global namespace)
+    pub namespace: Option<String>,
+    /// Name of the generated class (default: `AmoskeagProgram`)
+    pub class_name: String,
+    /// Indentation string (default: 4 spaces)
+    pub indent: String,
+}
+
+impl Default for TranspileConfig {
+    fn default() -> Self {
+        Self {
+            namespace: None,
+            class_name: "AmoskeagProgram".to_string(),
+            indent: "    ".to_string(),
+        }
+    }
+}
+
+/// Standard library functions implemented by the runtime, each by the
+/// method `method_name` gives
+///
+/// Decimal, duration, date, regex and financial functions are not in the
+/// runtime; programs that call them fail with `UnsupportedFunction`.
+const FUNCTIONS: &[&str] = &[
+    // String functions
+    "upcase",
+    "downcase",
+    "capitalize",
+    "strip",
+    "trim_start",
+    "trim_end",
+    "squish",
+    "split",
+    "chars",
+    "lines",
+    "join",
+    "truncate",
+    "substring",
+    "repeat",
+    "pad_left",
+    "pad_right",
+    "replace",
+    "starts_with",
+    "ends_with",
+    "index_of",
+    "includes",
+    "is_numeric_string",
+    "sha256",
+    "md5",
+    "base64_encode",
+    "base64_decode",
+    "url_encode",
+    "url_decode",
+    // Numeric functions
+    "abs",
+    "ceil",
+    "floor",
+    "round",
+    "trunc",
+    "sign",
+    "clamp",
+    "plus",
+    "minus",
+    "times",
+    "divided_by",
+    "modulo",
+    "max",
+    "min",
+    "power",
+    "pow",
+    "sqrt",
+    // Collection functions
+    "size",
+    "first",
+    "last",
+    "contains",
+    "sum",
+    "avg",
+    "median",
+    "sort",
+    "keys",
+    "values",
+    "reverse",
+    "at",
+    "uniq",
+    "pick",
+    "omit",
+    "has_key",
+    "get",
+    "dig",
+    "union",
+    "intersection",
+    "difference",
+    // Logic functions
+    "choose",
+    "if_then_else",
+    "is_nil",
+    "is_number",
+    "is_string",
+    "is_boolean",
+    "is_array",
+    "is_dictionary",
+    "coalesce",
+    "default",
+    "between",
+    // Conversion functions
+    "to_number",
+    "to_number_strict",
+    "to_string",
+    "to_string_strict",
+    "to_boolean",
+    "to_boolean_strict",
+];
+
+/// Words that cannot be C# identifiers, separated by spaces
+///
+/// `Runtime` and `Symbol` are the runtime classes the generated code names,
+/// so a generated class with either name would hide them.
+const RESERVED_NAMES: &str =
+    "abstract as base bool break byte case catch char checked class const \
+    continue decimal default delegate do double else enum event explicit extern false finally \
+    fixed float for foreach goto if implicit in int interface internal is lock long namespace \
+    new null object operator out override params private protected public readonly ref return \
+    sbyte sealed short sizeof stackalloc static string struct switch this throw true try typeof \
+    uint ulong unchecked unsafe ushort using virtual void volatile while Runtime Symbol";
+
+/// Transpile an Amoskeag AST to a C# class
+///
+/// # Arguments
+///
+/// * `expr` - The AST expression to transpile
+/// * `config` - Configuration for the transpiler
+///
+/// # Returns
+///
+/// A string containing the C# source of `config.class_name`
+pub fn transpile(expr: &Expr, config: &TranspileConfig) -> Result<String, TranspileError> {
+    if !is_identifier(&config.class_name) {
+        return Err(TranspileError::InvalidName(config.class_name.clone()));
+    }
+    if let Some(namespace) = &config.namespace {
+        if !namespace.split('.').all(is_identifier) {
+            return Err(TranspileError::InvalidName(namespace.clone()));
+        }
+    }
+
+    let body = Generator::default().expr(expr)?;
+    let indent = &config.indent;
+    // Everything inside a namespace block is indented one more level
+    let outer = if config.namespace.is_some() {
+        indent.as_str()
+    } else {
+        ""
+    };
+    let mut output = String::new();
+
+    writeln!(output, "// Generated by Amoskeag C# Transpiler")?;
+    writeln!(output)?;
+    writeln!(output, "using System.Collections.Generic;")?;
+    writeln!(output, "using {};", RUNTIME_NAMESPACE)?;
+    writeln!(output)?;
+    if let Some(namespace) = &config.namespace {
+        writeln!(output, "namespace {}", namespace)?;
+        writeln!(output, "{{")?;
+    }
+    writeln!(output, "{}public static class {}", outer, config.class_name)?;
+    writeln!(output, "{}{{", outer)?;
+    writeln!(
+        output,
+        "{}{}/// <summary>Evaluate the Amoskeag program.</summary>",
+        outer, indent
+    )?;
+    writeln!(
+        output,
+        "{}{}public static object Evaluate(IDictionary<string, object> data)",
+        outer, indent
+    )?;
+    writeln!(output, "{}{}{{", outer, indent)?;
+    writeln!(output, "{}{}{}return {};", outer, indent, indent, body)?;
+    writeln!(output, "{}{}}}", outer, indent)?;
+    writeln!(output, "{}}}", outer)?;
+    if config.namespace.is_some() {
+        writeln!(output, "}}")?;
+    }
+
+    Ok(output)
+}
+
+/// Transpile Amoskeag source code to C#
+///
+/// This is a convenience function that parses and transpiles in one step.
+///
+/// # Arguments
+///
+/// * `source` - The Amoskeag source code
+/// * `config` - Optional transpiler configuration
+///
+/// # Returns
+///
+/// A string containing the generated C# class
+pub fn transpile_source(
+    source: &str,
+    config: Option<TranspileConfig>,
+) -> Result<String, Box<dyn std::error::Error>> {
+    let config = config.unwrap_or_default();
+    let ast = amoskeag_parser::parse(source)?;
+    Ok(transpile(&ast, &config)?)
+}
+
+/// Whether `name` can be used as a C# identifier
+fn is_identifier(name: &str) -> bool {
+    let mut chars = name.chars();
+    chars
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+        && !RESERVED_NAMES
+            .split_whitespace()
+            .any(|reserved| reserved == name)
+}
+
+/// Generates the C# expression for an Amoskeag expression
+///
+/// `let` bindings become lambda parameters. Older C# versions do not let a
+/// lambda parameter shadow an enclosing local, so each is renamed `name_n`
+/// with a number unique within the program. The number follows the last
+/// underscore, so two renamed parameters never clash, and neither `data` nor
+/// a keyword ends in one. Brace names such as `{net premium}` that are not
+/// C# identifiers are renamed `let_n`.
+#[derive(Default)]
+struct Generator {
+    /// The `let` names in scope and their C# names, innermost last
+    scope: Vec<(String, String)>,
+    /// Lambda parameters generated so far
+    locals: usize,
+}
+
+impl Generator {
+    fn expr(&mut self, expr: &Expr) -> Result<String, TranspileError> {
+        match expr {
+            Expr::Number(n) => Ok(csharp_number(*n)),
+            Expr::Integer(n) => Ok(format!("{}L", n)),
+            Expr::String(s) => Ok(csharp_string(s)),
+            Expr::Boolean(b) => Ok(b.to_string()),
+            Expr::Nil => Ok("null".to_string()),
+            Expr::Symbol(s) => Ok(format!("Symbol.Of({})", csharp_string(s))),
+
+            Expr::Array(elements) => {
+                let items = self.exprs(elements)?;
+                // A lone null would be taken as the params array itself
+                if items.len() == 1 {
+                    return Ok(format!("Runtime.List((object){})", items[0]));
+                }
+                Ok(format!("Runtime.List({})", items.join(", ")))
+            }
+
+            Expr::Dictionary(pairs) => {
+                let mut parts = Vec::new();
+                for (key, value) in pairs {
+                    parts.push(csharp_string(key));
+                    parts.push(self.expr(value)?);
+                }
+                Ok(format!("Runtime.Dict({})", parts.join(", ")))
+            }
+
+            Expr::Variable(path) => {
+                let Some((root, fields)) = path.split_first() else {
+                    return Ok("null".to_string());
+                };
+                let (base, keys) = match self.scope.iter().rev().find(|(name, _)| name == root) {
+                    Some((_, local)) => (local.clone(), fields),
+                    None => ("data".to_string(), &path[..]),
+                };
+                if keys.is_empty() {
+                    return Ok(base);
+                }
+                // Like the interpreter, a missing variable is an error but a
+                // missing key on the way down a dotted path is nil
+                if keys.len() == 1 && base == "data" {
+                    return Ok(format!("Runtime.Variable(data, {})", csharp_string(root)));
+                }
+                let keys: Vec<String> = keys.iter().map(|key| csharp_string(key)).collect();
+                Ok(format!("Runtime.Navigate({}, {})", base, keys.join(", ")))
+            }
+
+            Expr::FunctionCall { name, args } => self.call(name, args),
+
+            Expr::Let { name, value, body } => {
+                let value = self.expr(value)?;
+                self.locals += 1;
+                let local = if is_identifier(name) {
+                    format!("{}_{}", name, self.locals)
+                } else {
+                    format!("let_{}", self.locals)
+                };
+                self.scope.push((name.clone(), local.clone()));
+                let body = self.expr(body);
+                self.scope.pop();
+                Ok(format!(
+                    "Runtime.Let({}, (object {}) => {})",
+                    value, local, body?
+                ))
+            }
+
+            Expr::If {
+                condition,
+                then_branch,
+                else_branch,
+            } => {
+                let condition = self.expr(condition)?;
+                let then_branch = self.expr(then_branch)?;
+                let else_branch = self.expr(else_branch)?;
+                // The cast gives both branches the type object
+                Ok(format!(
+                    "(Runtime.Truthy({}) ? (object){} : {})",
+                    condition, then_branch, else_branch
+                ))
+            }
+
+            Expr::Binary { op, left, right } => {
+                let method = match op {
+                    BinaryOp::Add => "Add",
+                    BinaryOp::Subtract => "Subtract",
+                    BinaryOp::Multiply => "Multiply",
+                    BinaryOp::Divide => "Divide",
+                    BinaryOp::Modulo => "Remainder",
+                    BinaryOp::Power => "Exponent",
+                    BinaryOp::Equal => "Eq",
+                    BinaryOp::NotEqual => "Ne",
+                    BinaryOp::Less => "Lt",
+                    BinaryOp::Greater => "Gt",
+                    BinaryOp::LessEqual => "Le",
+                    BinaryOp::GreaterEqual => "Ge",
+                    BinaryOp::And => "And",
+                    BinaryOp::Or => "Or",
+                };
+                let left = self.expr(left)?;
+                let right = self.expr(right)?;
+                Ok(format!("Runtime.{}({}, {})", method, left, right))
+            }
+
+            Expr::Unary { op, operand } => {
+                let method = match op {
+                    UnaryOp::Not => "Not",
+                    UnaryOp::Negate => "Negate",
+                };
+                Ok(format!("Runtime.{}({})", method, self.expr(operand)?))
+            }
+
+            // Pipe expression (should be transformed by parser, but handle for completeness)
+            Expr::Pipe { .. } => Err(TranspileError::UnsupportedExpression(
+                "Pipe expressions should be transformed during parsing".to_string(),
+            )),
+        }
+    }
+
+    fn exprs(&mut self, exprs: &[Expr]) -> Result<Vec<String>, TranspileError> {
+        exprs.iter().map(|expr| self.expr(expr)).collect()
+    }
+
+    fn call(&mut self, name: &str, args: &[Expr]) -> Result<String, TranspileError> {
+        signatures::check_arity(name, args.len())?;
+        let args = self.exprs(args)?;
+
+        // try(expr, fallback) evaluates the fallback only if expr fails
+        if name == "try" {
+            return Ok(format!(
+                "Runtime.TryOr(() => {}, () => {})",
+                args[0], args[1]
+            ));
+        }
+
+        if !FUNCTIONS.contains(&name) {
+            return Err(TranspileError::UnsupportedFunction(name.to_string()));
+        }
+        let method = method_name(name);
+        Ok(format!("Runtime.{}({})", method, args.join(", ")))
+    }
+}
+
+/// A C# double literal for `n`
+///
+/// The parser never produces infinity or NaN, but an AST built by hand can,
+/// and C# has no literal for either.
+fn csharp_number(n: f64) -> String {
+    if n.is_nan() {
+        "double.NaN".to_string()
+    } else if n.is_infinite() {
+        if n > 0.0 {
+            "double.PositiveInfinity".to_string()
+        } else {
+            "double.NegativeInfinity".to_string()
+        }
+    } else {
+        // Debug output always has a `.` or an exponent, so it is never an int
+        format!("{:?}", n)
+    }
+}
+
+/// The runtime method implementing the standard library function `name`:
+/// its name in PascalCase, except for the alias `pow`
+fn method_name(name: &str) -> String {
+    if name == "pow" {
+        return "Power".to_string();
+    }
+    let mut method = String::with_capacity(name.len());
+    for word in name.split('_') {
+        let mut chars = word.chars();
+        method.extend(chars.next().map(|c| c.to_ascii_uppercase()));
+        method.push_str(chars.as_str());
+    }
+    method
+}
+
+/// A C# string literal for `s`
+///
+/// Everything outside printable ASCII is escaped, so the source compiles the
+/// same whatever encoding the compiler reads it with.
+fn csharp_string(s: &str) -> String {
+    let mut literal = String::with_capacity(s.len() + 2);
+    literal.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => literal.push_str("\\\""),
+            '\\' => literal.push_str("\\\\"),
+            '\n' => literal.push_str("\\n"),
+            '\r' => literal.push_str("\\r"),
+            '\t' => literal.push_str("\\t"),
+            ' '..='~' => literal.push(c),
+            c => {
+                let mut units = [0; 2];
+                for unit in c.encode_utf16(&mut units) {
+                    let _ = write!(literal, "\\u{:04x}", unit);
+                }
+            }
+        }
+    }
+    literal.push('"');
+    literal
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use amoskeag_parser::parse;
+    use amoskeag_stdlib_functions::signatures::Arity;
+    use pretty_assertions::assert_eq;
+
+    /// The expression `Evaluate` returns for `source`
+    fn body(source: &str) -> String {
+        let csharp = transpile_source(source, None).unwrap();
+        let start = csharp.find("return ").unwrap() + "return ".len();
+        let end = csharp[start..].find(";\n").unwrap();
+        csharp[start..start + end].to_string()
+    }
+
+    #[test]
+    fn test_transpile_class() {
+        let csharp = transpile_source("42", None).unwrap();
+        assert_eq!(
+            csharp,
+            "// Generated by Amoskeag C# Transpiler\n\
+             \n\
+             using System.Collections.Generic;\n\
+             using Amoskeag;\n\
+             \n\
+             public static class AmoskeagProgram\n\
+             {\n\
+             \x20   /// <summary>Evaluate the Amoskeag program.</summary>\n\
+             \x20   public static object Evaluate(IDictionary<string, object> data)\n\
+             \x20   {\n\
+             \x20       return 42L;\n\
+             \x20   }\n\
+             }\n"
+        );
+    }
+
+    #[test]
+    fn test_transpile_namespace() {
+        let config = TranspileConfig {
+            namespace: Some("Acme.Claims.Rules".to_string()),
+            class_name: "Eligibility".to_string(),
+            ..Default::default()
+        };
+        let csharp = transpile_source("42", Some(config)).unwrap();
+        assert!(csharp.contains("namespace Acme.Claims.Rules\n{\n"));
+        assert!(csharp.contains("\n    public static class Eligibility\n    {\n"));
+        assert!(csharp.contains("\n            return 42L;\n"));
+        assert!(csharp.ends_with("        }\n    }\n}\n"));
+    }
+
+    #[test]
+    fn test_transpile_literals() {
+        assert_eq!(body("42.0"), "42.0");
+        assert_eq!(csharp_number(f64::INFINITY), "double.PositiveInfinity");
+        assert_eq!(body("true"), "true");
+        assert_eq!(body("nil"), "null");
+        assert_eq!(body(":approve"), "Symbol.Of(\"approve\")");
+        assert_eq!(body("[1, nil]"), "Runtime.List(1L, null)");
+        assert_eq!(body("[nil]"), "Runtime.List((object)null)");
+        assert_eq!(body("{\"limit\": 5}"), "Runtime.Dict(\"limit\", 5L)");
+    }
+
+    #[test]
+    fn test_csharp_string_escapes() {
+        assert_eq!(csharp_string("say \"hi\"\n"), "\"say \\\"hi\\\"\\n\"");
+        assert_eq!(csharp_string("a\u{0}b"), "\"a\\u0000b\"");
+        assert_eq!(csharp_string("café"), "\"caf\\u00e9\"");
+        assert_eq!(csharp_string("😀"), "\"\\ud83d\\ude00\"");
+    }
+
+    #[test]
+    fn test_transpile_variable() {
+        assert_eq!(body("age"), "Runtime.Variable(data, \"age\")");
+        assert_eq!(
+            body("driver.age"),
+            "Runtime.Navigate(data, \"driver\", \"age\")"
+        );
+    }
+
+    #[test]
+    fn test_transpile_operators() {
+        assert_eq!(
+            body("1 + 2 * 3"),
+            "Runtime.Add(1L, Runtime.Multiply(2L, 3L))"
+        );
+        assert_eq!(
+            body("a % 2 == 0 and not b"),
+            "Runtime.And(Runtime.Eq(Runtime.Remainder(Runtime.Variable(data, \"a\"), 2L), 0L), Runtime.Not(Runtime.Variable(data, \"b\")))"
+        );
+        assert_eq!(body("-x"), "Runtime.Negate(Runtime.Variable(data, \"x\"))");
+    }
+
+    #[test]
+    fn test_transpile_if_expression() {
+        assert_eq!(
+            body("if age >= 18 :adult else :minor end"),
+            "(Runtime.Truthy(Runtime.Ge(Runtime.Variable(data, \"age\"), 18L)) ? (object)Symbol.Of(\"adult\") : Symbol.Of(\"minor\"))"
+        );
+    }
+
+    #[test]
+    fn test_transpile_let_expression() {
+        assert_eq!(
+            body("let x = 5 in x + 1"),
+            "Runtime.Let(5L, (object x_1) => Runtime.Add(x_1, 1L))"
+        );
+    }
+
+    #[test]
+    fn test_transpile_shadowed_let() {
+        // The inner x is a new parameter; the outer one is back in scope after it
+        assert_eq!(
+            body("let x = driver in (let x = x.age in x) + x.years"),
+            "Runtime.Let(Runtime.Variable(data, \"driver\"), (object x_1) => Runtime.Add(Runtime.Let(Runtime.Navigate(x_1, \"age\"), (object x_2) => x_2), Runtime.Navigate(x_1, \"years\")))"
+        );
+    }
+
+    #[test]
+    fn test_transpile_let_brace_name() {
+        assert_eq!(
+            body("let {net premium} = 5 in {net premium}"),
+            "Runtime.Let(5L, (object let_1) => let_1)"
+        );
+    }
+
+    #[test]
+    fn test_transpile_function_calls() {
+        assert_eq!(
+            body("name | upcase"),
+            "Runtime.Upcase(Runtime.Variable(data, \"name\"))"
+        );
+        assert_eq!(
+            body("default(limit, 100)"),
+            "Runtime.Default(Runtime.Variable(data, \"limit\"), 100L)"
+        );
+        assert_eq!(
+            body("round(premium, 2)"),
+            "Runtime.Round(Runtime.Variable(data, \"premium\"), 2L)"
+        );
+    }
+
+    #[test]
+    fn test_transpile_try() {
+        assert_eq!(
+            body("try(to_number_strict(age), 30)"),
+            "Runtime.TryOr(() => Runtime.ToNumberStrict(Runtime.Variable(data, \"age\")), () => 30L)"
+        );
+    }
+
+    #[test]
+    fn test_transpile_unsupported_function() {
+        let expr = parse("date_now()").unwrap();
+        assert!(matches!(
+            transpile(&expr, &TranspileConfig::default()),
+            Err(TranspileError::UnsupportedFunction(name)) if name == "date_now"
+        ));
+    }
+
+    #[test]
+    fn test_runtime_functions_exist() {
+        let runtime = include_str!(concat!(env!("CARGO_MANIFEST_DIR"), "/runtime/Runtime.cs"));
+        for function in FUNCTIONS {
+            assert!(
+                signatures::lookup(function).is_some(),
+                "{} is not a standard library function",
+                function
+            );
+            let method = method_name(function);
+            assert!(
+                runtime.contains(&format!("public static object {}(", method)),
+                "the runtime has no method {} for {}",
+                method,
+                function
+            );
+        }
+    }
+
+    #[test]
+    fn test_transpile_arity_mismatch() {
+        let expr = parse("substring(name, 1)").unwrap();
+        assert!(matches!(
+            transpile(&expr, &TranspileConfig::default()),
+            Err(TranspileError::Arity(ArityError {
+                expected: Arity::Exact(3),
+                actual: 2,
+                ..
+            }))
+        ));
+    }
+
+    #[test]
+    fn test_invalid_names() {
+        let expr = parse("1").unwrap();
+        for (namespace, class_name) in [
+            (None, "class"),
+            (None, "2Fast"),
+            (None, "Runtime"),
+            (Some("Acme..Rules"), "Rule"),
+        ] {
+            let config = TranspileConfig {
+                namespace: namespace.map(str::to_string),
+                class_name: class_name.to_string(),
+                ..Default::default()
+            };
+            assert!(matches!(
+                transpile(&expr, &config),
+                Err(TranspileError::InvalidName(_))
+            ));
+        }
+    }
+}
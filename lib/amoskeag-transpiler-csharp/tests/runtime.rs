@@ -0,0 +1,211 @@
+//! Runs the shared conformance cases on the C# runtime library
+//!
+//! The runtime and the generated classes are built into a console program
+//! with `dotnet run`, and each result is compared with the one the fixture
+//! expects. Cases calling functions the runtime lacks are left out. Without
+//! the .NET SDK on the path the tests are skipped, except in CI.
+
+#[path = "../../../conformance/support.rs"]
+mod conformance;
+
+use amoskeag_transpiler_csharp::{transpile, TranspileConfig, TranspileError};
+use serde_json::Value;
+use std::fmt::Write;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// Prints each result as one line of JSON in the fixture's form
+const HARNESS: &str = r#"
+    static string Run(Func<object> program)
+    {
+        try
+        {
+            return Render(program());
+        }
+        catch (AmoskeagException e)
+        {
+            return "{\"$error\":" + Quote(e.Message) + "}";
+        }
+    }
+
+    static string Render(object value)
+    {
+        switch (value)
+        {
+            case null:
+                return "null";
+            case bool b:
+                return b ? "true" : "false";
+            case string s:
+                return Quote(s);
+            case Symbol symbol:
+                return "{\"$symbol\":" + Quote(symbol.Name) + "}";
+            case double d when double.IsNaN(d) || double.IsInfinity(d):
+                return "null";
+            case double d:
+                string text = d.ToString("R", CultureInfo.InvariantCulture);
+                return text.IndexOfAny(new[] { '.', 'E' }) >= 0 ? text : text + ".0";
+            case IDictionary<string, object> dictionary:
+                return "{" + string.Join(",", dictionary.OrderBy(entry => entry.Key, StringComparer.Ordinal)
+                    .Select(entry => Quote(entry.Key) + ":" + Render(entry.Value))) + "}";
+            case IList items:
+                return "[" + string.Join(",", items.Cast<object>().Select(Render)) + "]";
+            default:
+                return Convert.ToString(value, CultureInfo.InvariantCulture);
+        }
+    }
+
+    static string Quote(string text)
+    {
+        var output = new StringBuilder("\"");
+        foreach (char c in text)
+        {
+            if (c == '"' || c == '\\')
+            {
+                output.Append('\\').Append(c);
+            }
+            else if (c < 0x20)
+            {
+                output.AppendFormat("\\u{0:x4}", (int)c);
+            }
+            else
+            {
+                output.Append(c);
+            }
+        }
+        return output.Append('"').ToString();
+    }
+"#;
+
+/// The C# expression for a JSON value, in the types the runtime expects
+fn csharp_value(value: &Value) -> String {
+    match value {
+        Value::Null => "null".to_string(),
+        Value::Bool(b) => b.to_string(),
+        Value::Number(n) => match n.as_i64() {
+            Some(n) => format!("{}L", n),
+            None => format!("{:?}", n.as_f64().unwrap_or(f64::NAN)),
+        },
+        Value::String(s) => format!("{:?}", s),
+        Value::Array(items) => {
+            let items: Vec<String> = items
+                .iter()
+                .map(|item| format!("(object){}", csharp_value(item)))
+                .collect();
+            format!("Runtime.List({})", items.join(", "))
+        }
+        Value::Object(map) => {
+            let pairs: Vec<String> = map
+                .iter()
+                .map(|(key, value)| format!("{:?}, (object){}", key, csharp_value(value)))
+                .collect();
+            format!("Runtime.Dict({})", pairs.join(", "))
+        }
+    }
+}
+
+fn has_dotnet() -> bool {
+    Command::new("dotnet")
+        .arg("--version")
+        .output()
+        .is_ok_and(|output| output.status.success())
+}
+
+/// A fresh directory for one test's project
+fn work_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("amoskeag-csharp-{}-{}", name, std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+/// The generated class for `case`, or `None` when it calls a function the
+/// runtime lacks
+fn transpile_case(case: &conformance::Case, class_name: String) -> Option<String> {
+    let ast = amoskeag_parser::parse(&case.expression).unwrap();
+    let config = TranspileConfig {
+        class_name,
+        ..TranspileConfig::default()
+    };
+    match transpile(&ast, &config) {
+        Ok(class) => Some(class),
+        Err(TranspileError::UnsupportedFunction(_)) => None,
+        Err(e) => panic!("{} does not transpile: {}", case.expression, e),
+    }
+}
+
+/// Run the transpiled classes, named `Case0` onwards, in one program with
+/// `data`, and return their results in order
+fn run_classes(name: &str, classes: &[String], data: &Value) -> Vec<Value> {
+    let dir = work_dir(name);
+    let runtime = Path::new(env!("CARGO_MANIFEST_DIR")).join("runtime");
+    let project = format!(
+        "<Project Sdk=\"Microsoft.NET.Sdk\">\n  <PropertyGroup>\n    <OutputType>Exe</OutputType>\n    <TargetFramework>net8.0</TargetFramework>\n    <EnableDefaultCompileItems>false</EnableDefaultCompileItems>\n  </PropertyGroup>\n  <ItemGroup>\n    <Compile Include=\"*.cs\" />\n    <Compile Include=\"{}/*.cs\" />\n  </ItemGroup>\n</Project>\n",
+        runtime.display()
+    );
+    std::fs::write(dir.join("Harness.csproj"), project).unwrap();
+
+    let mut main = String::from(
+        "using System;\nusing System.Collections;\nusing System.Collections.Generic;\nusing System.Globalization;\nusing System.Linq;\nusing System.Text;\nusing Amoskeag;\n\npublic static class Program\n{\n",
+    );
+    writeln!(
+        main,
+        "    static readonly IDictionary<string, object> Data = {};\n",
+        csharp_value(data)
+    )
+    .unwrap();
+    main.push_str("    public static void Main()\n    {\n");
+    for (i, class) in classes.iter().enumerate() {
+        std::fs::write(dir.join(format!("Case{}.cs", i)), class).unwrap();
+        writeln!(
+            main,
+            "        Console.WriteLine(Run(() => Case{}.Evaluate(Data)));",
+            i
+        )
+        .unwrap();
+    }
+    main.push_str("    }\n");
+    main.push_str(HARNESS);
+    main.push_str("}\n");
+    std::fs::write(dir.join("Program.cs"), main).unwrap();
+
+    let output = Command::new("dotnet")
+        .args(["run", "--project"])
+        .arg(dir.join("Harness.csproj"))
+        .env("DOTNET_CLI_TELEMETRY_OPTOUT", "1")
+        .env("DOTNET_NOLOGO", "1")
+        .output()
+        .unwrap();
+    assert!(
+        output.status.success(),
+        "dotnet run failed:\n{}{}",
+        String::from_utf8_lossy(&output.stdout),
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let _ = std::fs::remove_dir_all(&dir);
+
+    String::from_utf8(output.stdout)
+        .unwrap()
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap_or_else(|e| panic!("{}: {}", line, e)))
+        .collect()
+}
+
+#[test]
+fn test_runtime_conformance() {
+    if !conformance::tools_found(has_dotnet(), "dotnet") {
+        return;
+    }
+
+    let fixture = conformance::load();
+    let mut cases = Vec::new();
+    let mut classes = Vec::new();
+    for case in fixture.cases.iter().filter(|case| case.runs_on("csharp")) {
+        if let Some(class) = transpile_case(case, format!("Case{}", classes.len())) {
+            cases.push(case);
+            classes.push(class);
+        }
+    }
+    let results = run_classes("conformance", &classes, &fixture.data);
+    conformance::check("C#", &cases, &results);
+}
@@ -0,0 +1,1479 @@
+package io.amoskeag.runtime;
+
+import java.math.BigDecimal;
+import java.nio.ByteBuffer;
+import java.nio.charset.CharacterCodingException;
+import java.nio.charset.CodingErrorAction;
+import java.nio.charset.StandardCharsets;
+import java.security.MessageDigest;
+import java.security.NoSuchAlgorithmException;
+import java.util.ArrayList;
+import java.util.Arrays;
+import java.util.Base64;
+import java.util.Collections;
+import java.util.LinkedHashMap;
+import java.util.List;
+import java.util.Locale;
+import java.util.Map;
+import java.util.function.Function;
+import java.util.function.Supplier;
+import java.util.regex.Pattern;
+
+/**
+ * Operators and standard library for Amoskeag programs transpiled to Java.
+ *
+ * Values are plain Java objects: {@code null} for nil, {@link Boolean},
+ * {@link Long} for integers, {@link Double} for numbers, {@link String},
+ * {@link Symbol}, {@link List} for arrays and {@link Map} with string keys
+ * for dictionaries. Other integral {@link Number}s in the input data are
+ * treated as integers and the rest as numbers.
+ *
+ * Every method has the semantics of the interpreter: integer arithmetic
+ * falls back to floating point on overflow, {@code /} always gives a number,
+ * and the standard library works in numbers, so integers passed to it come
+ * back as numbers. Failures throw {@link AmoskeagException}.
+ */
+public final class Amoskeag {
+    /** Largest string, in UTF-8 bytes, that {@code repeat} will build */
+    public static final int MAX_REPEAT_LENGTH = 1_000_000;
+
+    /** Plain decimal numbers in ASCII digits, the strings {@code toNumber} converts */
+    private static final Pattern NUMERIC = Pattern.compile(
+            "[+-]?(?:[0-9]+\\.?[0-9]*|\\.[0-9]+)(?:[eE][+-]?[0-9]+)?");
+
+    private Amoskeag() {
+    }
+
+    // Evaluation
+
+    /** Read a variable with no dots, which unlike a dotted path must be in the data */
+    public static Object variable(Map<String, Object> data, String name) {
+        if (!data.containsKey(name)) {
+            throw new AmoskeagException("Variable '" + name + "' not found");
+        }
+        return data.get(name);
+    }
+
+    /** Read a dotted path, giving nil for a missing key or a non-dictionary */
+    public static Object navigate(Object root, String... path) {
+        Object current = root;
+        for (String key : path) {
+            if (!(current instanceof Map)) {
+                return null;
+            }
+            current = ((Map<?, ?>) current).get(key);
+        }
+        return current;
+    }
+
+    /** Whether a value counts as true: everything but nil and false */
+    public static boolean truthy(Object value) {
+        return value != null && !Boolean.FALSE.equals(value);
+    }
+
+    /** Evaluate {@code body} with {@code value} bound to its parameter */
+    public static Object let(Object value, Function<Object, Object> body) {
+        return body.apply(value);
+    }
+
+    /** {@code try(body, fallback)}: the fallback is evaluated only if the body fails */
+    public static Object tryOr(Supplier<Object> body, Supplier<Object> fallback) {
+        try {
+            return body.get();
+        } catch (AmoskeagException e) {
+            return fallback.get();
+        }
+    }
+
+    /** An array literal */
+    public static List<Object> list(Object... items) {
+        return new ArrayList<>(Arrays.asList(items));
+    }
+
+    /** A dictionary literal, from alternating keys and values */
+    public static Map<String, Object> dict(Object... pairs) {
+        Map<String, Object> map = new LinkedHashMap<>();
+        for (int i = 0; i < pairs.length; i += 2) {
+            map.put((String) pairs[i], pairs[i + 1]);
+        }
+        return map;
+    }
+
+    // Operators
+
+    /** {@code +}: numbers add, and a string on the left concatenates anything */
+    public static Object add(Object left, Object right) {
+        if (numeric(left) && numeric(right)) {
+            if (isInt(left) && isInt(right)) {
+                long l = ((Number) left).longValue();
+                long r = ((Number) right).longValue();
+                long sum = l + r;
+                if (((l ^ sum) & (r ^ sum)) < 0) {
+                    return (double) l + (double) r;
+                }
+                return sum;
+            }
+            return toDouble(left) + toDouble(right);
+        }
+        if (left instanceof String) {
+            return left + display(right);
+        }
+        throw invalidOperation("+", left, right);
+    }
+
+    /** {@code -} */
+    public static Object subtract(Object left, Object right) {
+        if (numeric(left) && numeric(right)) {
+            if (isInt(left) && isInt(right)) {
+                long l = ((Number) left).longValue();
+                long r = ((Number) right).longValue();
+                long difference = l - r;
+                if (((l ^ r) & (l ^ difference)) < 0) {
+                    return (double) l - (double) r;
+                }
+                return difference;
+            }
+            return toDouble(left) - toDouble(right);
+        }
+        throw invalidOperation("-", left, right);
+    }
+
+    /** {@code *} */
+    public static Object multiply(Object left, Object right) {
+        if (numeric(left) && numeric(right)) {
+            if (isInt(left) && isInt(right)) {
+                long l = ((Number) left).longValue();
+                long r = ((Number) right).longValue();
+                try {
+                    return Math.multiplyExact(l, r);
+                } catch (ArithmeticException e) {
+                    return (double) l * (double) r;
+                }
+            }
+            return toDouble(left) * toDouble(right);
+        }
+        throw invalidOperation("*", left, right);
+    }
+
+    /** {@code /}: always a number, so {@code 10 / 4} is 2.5 */
+    public static Object divide(Object left, Object right) {
+        if (numeric(left) && numeric(right)) {
+            if (toDouble(right) == 0.0) {
+                throw new AmoskeagException("Operator error: Division by zero");
+            }
+            return toDouble(left) / toDouble(right);
+        }
+        throw invalidOperation("/", left, right);
+    }
+
+    /** {@code %}: the remainder, with the sign of the left operand */
+    public static Object remainder(Object left, Object right) {
+        if (numeric(left) && numeric(right)) {
+            if (toDouble(right) == 0.0) {
+                throw new AmoskeagException("Operator error: Division by zero");
+            }
+            if (isInt(left) && isInt(right)) {
+                long l = ((Number) left).longValue();
+                long r = ((Number) right).longValue();
+                if (l == Long.MIN_VALUE && r == -1) {
+                    return (double) l % (double) r;
+                }
+                return l % r;
+            }
+            return toDouble(left) % toDouble(right);
+        }
+        throw invalidOperation("%", left, right);
+    }
+
+    /** {@code ^}: an integer raised to a non-negative integer stays an integer unless it overflows */
+    public static Object exponent(Object left, Object right) {
+        if (numeric(left) && numeric(right)) {
+            if (isInt(left) && isInt(right)) {
+                long base = ((Number) left).longValue();
+                long exp = ((Number) right).longValue();
+                Long result = checkedPow(base, exp);
+                if (result != null) {
+                    return result;
+                }
+                return Math.pow(base, exp);
+            }
+            return Math.pow(toDouble(left), toDouble(right));
+        }
+        throw invalidOperation("^", left, right);
+    }
+
+    /** {@code base} to the power {@code exp}, or null if it overflows or exp is not a u32 */
+    private static Long checkedPow(long base, long exp) {
+        if (exp < 0 || exp > 0xFFFF_FFFFL) {
+            return null;
+        }
+        if (exp == 0) {
+            return 1L;
+        }
+        long acc = 1;
+        try {
+            while (true) {
+                if ((exp & 1) == 1) {
+                    acc = Math.multiplyExact(acc, base);
+                    if (exp == 1) {
+                        return acc;
+                    }
+                }
+                exp /= 2;
+                base = Math.multiplyExact(base, base);
+            }
+        } catch (ArithmeticException e) {
+            return null;
+        }
+    }
+
+    /** {@code ==}: deep equality, where an integer equals the same number */
+    public static Object eq(Object left, Object right) {
+        return valueEquals(left, right);
+    }
+
+    /** {@code !=} */
+    public static Object ne(Object left, Object right) {
+        return !valueEquals(left, right);
+    }
+
+    /** {@code <} */
+    public static Object lt(Object left, Object right) {
+        return compare("<", left, right) < 0;
+    }
+
+    /** {@code >} */
+    public static Object gt(Object left, Object right) {
+        int ordering = compare(">", left, right);
+        return ordering > 0 && ordering != UNORDERED;
+    }
+
+    /** {@code <=} */
+    public static Object le(Object left, Object right) {
+        return compare("<=", left, right) <= 0;
+    }
+
+    /** {@code >=} */
+    public static Object ge(Object left, Object right) {
+        int ordering = compare(">=", left, right);
+        return ordering >= 0 && ordering != UNORDERED;
+    }
+
+    /** {@code and}: both operands are evaluated, and the result is a boolean */
+    public static Object and(Object left, Object right) {
+        return truthy(left) && truthy(right);
+    }
+
+    /** {@code or}: both operands are evaluated, and the result is a boolean */
+    public static Object or(Object left, Object right) {
+        return truthy(left) || truthy(right);
+    }
+
+    /** {@code not} */
+    public static Object not(Object operand) {
+        return !truthy(operand);
+    }
+
+    /** Unary {@code -} */
+    public static Object negate(Object operand) {
+        if (isInt(operand)) {
+            long n = ((Number) operand).longValue();
+            return n == Long.MIN_VALUE ? -(double) n : (Object) (-n);
+        }
+        if (numeric(operand)) {
+            return -toDouble(operand);
+        }
+        throw new AmoskeagException("Type error: expected Number, got " + typeName(operand));
+    }
+
+    /** What {@link #compare} gives when a NaN makes every comparison false */
+    private static final int UNORDERED = Integer.MAX_VALUE;
+
+    /**
+     * Order two numbers or two strings, giving {@link #UNORDERED} for a NaN
+     *
+     * UNORDERED is positive, so {@code < 0} and {@code <= 0} are already
+     * false for it.
+     */
+    private static int compare(String op, Object left, Object right) {
+        if (numeric(left) && numeric(right)) {
+            if (isInt(left) && isInt(right)) {
+                return Long.compare(((Number) left).longValue(), ((Number) right).longValue());
+            }
+            double l = toDouble(left);
+            double r = toDouble(right);
+            if (Double.isNaN(l) || Double.isNaN(r)) {
+                return UNORDERED;
+            }
+            return l < r ? -1 : (l > r ? 1 : 0);
+        }
+        if (left instanceof String && right instanceof String) {
+            return Integer.signum(compareStrings((String) left, (String) right));
+        }
+        throw invalidOperation(op, left, right);
+    }
+
+    private static AmoskeagException invalidOperation(String op, Object left, Object right) {
+        return new AmoskeagException(
+                "Operator error: Invalid operation: " + typeName(left) + " " + op + " " + typeName(right));
+    }
+
+    // String functions
+
+    public static Object upcase(Object value) {
+        return string(value).toUpperCase(Locale.ROOT);
+    }
+
+    public static Object downcase(Object value) {
+        return string(value).toLowerCase(Locale.ROOT);
+    }
+
+    public static Object capitalize(Object value) {
+        String s = string(value);
+        if (s.isEmpty()) {
+            return s;
+        }
+        int first = Character.charCount(s.codePointAt(0));
+        return s.substring(0, first).toUpperCase(Locale.ROOT) + s.substring(first).toLowerCase(Locale.ROOT);
+    }
+
+    public static Object strip(Object value) {
+        return trimEnd(trimStart(string(value)));
+    }
+
+    public static Object trimStart(Object value) {
+        String s = string(value);
+        int start = 0;
+        while (start < s.length() && isWhitespace(s.codePointAt(start))) {
+            start += Character.charCount(s.codePointAt(start));
+        }
+        return s.substring(start);
+    }
+
+    public static Object trimEnd(Object value) {
+        String s = string(value);
+        int end = s.length();
+        while (end > 0 && isWhitespace(s.codePointBefore(end))) {
+            end -= Character.charCount(s.codePointBefore(end));
+        }
+        return s.substring(0, end);
+    }
+
+    public static Object squish(Object value) {
+        return String.join(" ", words(string(value)));
+    }
+
+    public static Object split(Object value, Object separator) {
+        String s = string(value);
+        String sep = string(separator);
+        List<Object> parts = new ArrayList<>();
+        if (sep.isEmpty()) {
+            // Like Rust's str::split, an empty separator matches around every character
+            parts.add("");
+            s.codePoints().forEach(c -> parts.add(new String(Character.toChars(c))));
+            parts.add("");
+            return parts;
+        }
+        int start = 0;
+        int found;
+        while ((found = s.indexOf(sep, start)) >= 0) {
+            parts.add(s.substring(start, found));
+            start = found + sep.length();
+        }
+        parts.add(s.substring(start));
+        return parts;
+    }
+
+    public static Object chars(Object value) {
+        List<Object> chars = new ArrayList<>();
+        string(value).codePoints().forEach(c -> chars.add(new String(Character.toChars(c))));
+        return chars;
+    }
+
+    /** Splits on {@code \n} and {@code \r\n}; a trailing newline adds no empty line */
+    public static Object lines(Object value) {
+        String s = string(value);
+        List<Object> lines = new ArrayList<>();
+        int start = 0;
+        while (start < s.length()) {
+            int newline = s.indexOf('\n', start);
+            if (newline < 0) {
+                lines.add(s.substring(start));
+                break;
+            }
+            int end = newline > start && s.charAt(newline - 1) == '\r' ? newline - 1 : newline;
+            lines.add(s.substring(start, end));
+            start = newline + 1;
+        }
+        return lines;
+    }
+
+    public static Object join(Object array, Object separator) {
+        List<?> items = array(array);
+        String sep = string(separator);
+        StringBuilder joined = new StringBuilder();
+        for (int i = 0; i < items.size(); i++) {
+            Object item = items.get(i);
+            if (!(item instanceof String)) {
+                throw typeError("Array of Strings", "Array containing " + argType(item));
+            }
+            if (i > 0) {
+                joined.append(sep);
+            }
+            joined.append((String) item);
+        }
+        return joined.toString();
+    }
+
+    /** Keeps the first {@code length} characters of a string longer than {@code length} UTF-8 bytes */
+    public static Object truncate(Object value, Object length) {
+        String s = string(value);
+        long max = nonNegative(number(length));
+        if (utf8Length(s) <= max) {
+            return s;
+        }
+        return takeCodePoints(s, 0, max);
+    }
+
+    /** Positions count characters; a negative start counts back from the end */
+    public static Object substring(Object value, Object start, Object length) {
+        String s = string(value);
+        long from = (long) number(start);
+        long count = nonNegative(number(length));
+        long chars = s.codePointCount(0, s.length());
+        if (from < 0) {
+            from = Math.max(chars + from, 0);
+        }
+        return takeCodePoints(s, from, count);
+    }
+
+    public static Object repeat(Object value, Object count) {
+        String s = string(value);
+        double n = number(count);
+        if (!Double.isFinite(n) || n < 0.0) {
+            throw new AmoskeagException(
+                    "Function error: Argument error: repeat count must be a non-negative number, got "
+                            + formatNumber(n));
+        }
+        long times = (long) n;
+        long bytes = utf8Length(s);
+        if (times != 0 && bytes > MAX_REPEAT_LENGTH / times) {
+            throw new AmoskeagException("Function error: Value error: repeat result would exceed the maximum length of "
+                    + MAX_REPEAT_LENGTH + " bytes");
+        }
+        return s.repeat((int) (bytes == 0 ? 0 : times));
+    }
+
+    public static Object padLeft(Object value, Object width, Object fill) {
+        String s = string(value);
+        return padding(s, width, fill) + s;
+    }
+
+    public static Object padRight(Object value, Object width, Object fill) {
+        String s = string(value);
+        return s + padding(s, width, fill);
+    }
+
+    private static String padding(String s, Object width, Object fill) {
+        double w = number(width);
+        String f = string(fill);
+        if (f.isEmpty() || f.codePointCount(0, f.length()) != 1) {
+            throw new AmoskeagException(
+                    "Function error: Argument error: fill must be a single character, got '" + f + "'");
+        }
+        long missing = nonNegative(w) - s.codePointCount(0, s.length());
+        if (missing > 0 && missing > (MAX_REPEAT_LENGTH - utf8Length(s)) / utf8Length(f)) {
+            throw new AmoskeagException("Function error: Value error: pad result would exceed the maximum length of "
+                    + MAX_REPEAT_LENGTH + " bytes");
+        }
+        return missing > 0 ? f.repeat((int) missing) : "";
+    }
+
+    public static Object replace(Object value, Object find, Object replacement) {
+        return string(value).replace(string(find), string(replacement));
+    }
+
+    public static Object startsWith(Object value, Object prefix) {
+        return string(value).startsWith(string(prefix));
+    }
+
+    public static Object endsWith(Object value, Object suffix) {
+        return string(value).endsWith(string(suffix));
+    }
+
+    /** The character position of the first occurrence, or nil */
+    public static Object indexOf(Object value, Object substring) {
+        String s = string(value);
+        int found = s.indexOf(string(substring));
+        return found < 0 ? null : (Object) (long) s.codePointCount(0, found);
+    }
+
+    public static Object includes(Object value, Object substring) {
+        return string(value).contains(string(substring));
+    }
+
+    public static Object isNumericString(Object value) {
+        return value instanceof String && NUMERIC.matcher((String) value).matches();
+    }
+
+    public static Object sha256(Object value) {
+        return digest("SHA-256", string(value));
+    }
+
+    public static Object md5(Object value) {
+        return digest("MD5", string(value));
+    }
+
+    public static Object base64Encode(Object value) {
+        return Base64.getEncoder().encodeToString(string(value).getBytes(StandardCharsets.UTF_8));
+    }
+
+    public static Object base64Decode(Object value) {
+        String s = string(value);
+        byte[] bytes;
+        try {
+            if (s.length() % 4 != 0) {
+                throw new IllegalArgumentException("Invalid padding");
+            }
+            bytes = Base64.getDecoder().decode(s);
+        } catch (IllegalArgumentException e) {
+            throw new AmoskeagException("Function error: Value error: invalid Base64 input: " + e.getMessage());
+        }
+        return utf8(bytes, "Base64 input does not decode to UTF-8 text");
+    }
+
+    /** Percent-encodes every byte outside letters, digits and {@code -._~} */
+    public static Object urlEncode(Object value) {
+        StringBuilder encoded = new StringBuilder();
+        for (byte b : string(value).getBytes(StandardCharsets.UTF_8)) {
+            int c = b & 0xFF;
+            if ((c >= 'a' && c <= 'z') || (c >= 'A' && c <= 'Z') || (c >= '0' && c <= '9') || "-._~".indexOf(c) >= 0) {
+                encoded.append((char) c);
+            } else {
+                encoded.append(String.format("%%%02X", c));
+            }
+        }
+        return encoded.toString();
+    }
+
+    /** Decodes {@code %} escapes; {@code +} is left as it is */
+    public static Object urlDecode(Object value) {
+        byte[] bytes = string(value).getBytes(StandardCharsets.UTF_8);
+        ByteBuffer decoded = ByteBuffer.allocate(bytes.length);
+        for (int i = 0; i < bytes.length; i++) {
+            if (bytes[i] == '%') {
+                int high = i + 2 < bytes.length ? Character.digit(bytes[i + 1], 16) : -1;
+                int low = i + 2 < bytes.length ? Character.digit(bytes[i + 2], 16) : -1;
+                if (high < 0 || low < 0) {
+                    throw new AmoskeagException("Function error: Value error: invalid percent escape at position " + i);
+                }
+                decoded.put((byte) (high * 16 + low));
+                i += 2;
+            } else {
+                decoded.put(bytes[i]);
+            }
+        }
+        return utf8(Arrays.copyOf(decoded.array(), decoded.position()), "URL input does not decode to UTF-8 text");
+    }
+
+    // Numeric functions
+
+    public static Object abs(Object value) {
+        return Math.abs(number(value));
+    }
+
+    public static Object ceil(Object value) {
+        return Math.ceil(number(value));
+    }
+
+    public static Object floor(Object value) {
+        return Math.floor(number(value));
+    }
+
+    public static Object round(Object value) {
+        return round(value, 0.0);
+    }
+
+    /** Rounds half away from zero; negative digits round to tens, hundreds and so on */
+    public static Object round(Object value, Object digits) {
+        double n = number(value);
+        double d = number(digits);
+        if (!Double.isFinite(d)) {
+            throw new AmoskeagException("Function error: Argument error: digits must be finite");
+        }
+        int places = Math.max(-20, Math.min(20, (int) d));
+        double multiplier = Math.pow(10, Math.abs(places));
+        if (places >= 0) {
+            return roundHalfAway(n * multiplier) / multiplier;
+        }
+        return roundHalfAway(n / multiplier) * multiplier;
+    }
+
+    public static Object trunc(Object value) {
+        double n = number(value);
+        return n < 0 ? Math.ceil(n) : Math.floor(n);
+    }
+
+    public static Object sign(Object value) {
+        double n = number(value);
+        return n == 0.0 ? 0.0 : Math.signum(n);
+    }
+
+    public static Object clamp(Object value, Object lo, Object hi) {
+        double n = number(value);
+        double low = number(lo);
+        double high = number(hi);
+        if (low > high || Double.isNaN(low) || Double.isNaN(high)) {
+            throw new AmoskeagException("Function error: Argument error: clamp requires lo <= hi, got lo "
+                    + formatNumber(low) + " and hi " + formatNumber(high));
+        }
+        return n < low ? low : (n > high ? high : n);
+    }
+
+    public static Object plus(Object a, Object b) {
+        return number(a) + number(b);
+    }
+
+    public static Object minus(Object a, Object b) {
+        return number(a) - number(b);
+    }
+
+    public static Object times(Object a, Object b) {
+        return number(a) * number(b);
+    }
+
+    public static Object dividedBy(Object a, Object b) {
+        double x = number(a);
+        double y = number(b);
+        if (y == 0.0) {
+            throw new AmoskeagException("Function error: Invalid operation: Division by zero");
+        }
+        return x / y;
+    }
+
+    public static Object modulo(Object a, Object b) {
+        double x = number(a);
+        double y = number(b);
+        if (y == 0.0) {
+            throw new AmoskeagException("Function error: Invalid operation: Modulo by zero");
+        }
+        double quotient = (Double) trunc(x / y);
+        return x - quotient * y;
+    }
+
+    /** The larger of two numbers, ignoring a NaN */
+    public static Object max(Object a, Object b) {
+        double x = number(a);
+        double y = number(b);
+        return Double.isNaN(x) ? y : (Double.isNaN(y) ? x : Math.max(x, y));
+    }
+
+    /** The smaller of two numbers, ignoring a NaN */
+    public static Object min(Object a, Object b) {
+        double x = number(a);
+        double y = number(b);
+        return Double.isNaN(x) ? y : (Double.isNaN(y) ? x : Math.min(x, y));
+    }
+
+    /** Unlike {@code ^}, fails on results that are not real numbers */
+    public static Object power(Object base, Object exponent) {
+        double b = number(base);
+        double e = number(exponent);
+        double result = Math.pow(b, e);
+        if (Double.isNaN(result) && !Double.isNaN(b) && !Double.isNaN(e)) {
+            throw new AmoskeagException("Function error: Value error: Cannot raise negative number "
+                    + formatNumber(b) + " to fractional power " + formatNumber(e));
+        }
+        if (Double.isInfinite(result) && Double.isFinite(b) && Double.isFinite(e)) {
+            if (b == 0.0) {
+                throw new AmoskeagException(
+                        "Function error: Value error: Cannot raise zero to negative power " + formatNumber(e));
+            }
+            throw new AmoskeagException(
+                    "Function error: Value error: " + formatNumber(b) + " raised to " + formatNumber(e) + " overflows");
+        }
+        return result;
+    }
+
+    public static Object sqrt(Object value) {
+        double n = number(value);
+        if (n < 0.0) {
+            throw new AmoskeagException(
+                    "Function error: Value error: Cannot take square root of negative number: " + formatNumber(n));
+        }
+        return Math.sqrt(n);
+    }
+
+    // Collection functions
+
+    /** The length of an array or dictionary, or of a string in UTF-8 bytes */
+    public static Object size(Object value) {
+        if (value instanceof String) {
+            return (double) utf8Length((String) value);
+        }
+        if (value instanceof List) {
+            return (double) ((List<?>) value).size();
+        }
+        if (value instanceof Map) {
+            return (double) ((Map<?, ?>) value).size();
+        }
+        throw typeError("String, Array, or Dictionary", argType(value));
+    }
+
+    public static Object first(Object value) {
+        List<?> items = array(value);
+        return items.isEmpty() ? null : items.get(0);
+    }
+
+    public static Object last(Object value) {
+        List<?> items = array(value);
+        return items.isEmpty() ? null : items.get(items.size() - 1);
+    }
+
+    /** Whether an array holds a value, or a string holds a substring */
+    public static Object contains(Object collection, Object value) {
+        if (collection instanceof List) {
+            return indexIn((List<?>) collection, value) >= 0;
+        }
+        if (collection instanceof String) {
+            return ((String) collection).contains(string(value));
+        }
+        throw typeError("Array or String", argType(collection));
+    }
+
+    public static Object sum(Object value) {
+        double total = 0.0;
+        for (double n : numbers(value)) {
+            total += n;
+        }
+        return total;
+    }
+
+    /** The mean of an array of numbers, or nil if it is empty */
+    public static Object avg(Object value) {
+        double[] numbers = numbers(value);
+        if (numbers.length == 0) {
+            return null;
+        }
+        return (Double) sum(value) / numbers.length;
+    }
+
+    /** The middle number, or the mean of the middle two; nil if the array is empty */
+    public static Object median(Object value) {
+        double[] numbers = numbers(value);
+        if (numbers.length == 0) {
+            return null;
+        }
+        Double[] sorted = new Double[numbers.length];
+        for (int i = 0; i < numbers.length; i++) {
+            sorted[i] = numbers[i];
+        }
+        Arrays.sort(sorted, Amoskeag::compareNumbers);
+        int mid = sorted.length / 2;
+        if (sorted.length % 2 == 0) {
+            return (sorted[mid - 1] + sorted[mid]) / 2.0;
+        }
+        return sorted[mid];
+    }
+
+    /** Sorts an array that holds only numbers or only strings */
+    public static Object sort(Object value) {
+        List<Object> sorted = copy(value);
+        boolean allNumbers = sorted.stream().allMatch(Amoskeag::numeric);
+        boolean allStrings = sorted.stream().allMatch(item -> item instanceof String);
+        if (allNumbers) {
+            sorted.sort((a, b) -> compareNumbers(toDouble(a), toDouble(b)));
+        } else if (allStrings) {
+            sorted.sort((a, b) -> compareStrings((String) a, (String) b));
+        } else {
+            throw new AmoskeagException(
+                    "Function error: Invalid operation: Array must contain all Numbers or all Strings to sort");
+        }
+        return sorted;
+    }
+
+    /** A dictionary's keys, sorted */
+    public static Object keys(Object value) {
+        List<Object> keys = new ArrayList<Object>(dictionary(value).keySet());
+        keys.sort((a, b) -> compareStrings((String) a, (String) b));
+        return keys;
+    }
+
+    public static Object values(Object value) {
+        List<Object> values = new ArrayList<>();
+        for (Object item : dictionary(value).values()) {
+            values.add(item);
+        }
+        return values;
+    }
+
+    public static Object reverse(Object value) {
+        List<Object> reversed = copy(value);
+        Collections.reverse(reversed);
+        return reversed;
+    }
+
+    /** The element at a 0-based index, counting back from the end if negative; nil if out of range */
+    public static Object at(Object array, Object index) {
+        List<?> items = array(array);
+        long i = (long) number(index);
+        long actual = i < 0 ? items.size() + i : i;
+        return actual < 0 || actual >= items.size() ? null : items.get((int) actual);
+    }
+
+    public static Object uniq(Object value) {
+        List<Object> unique = new ArrayList<>();
+        for (Object item : array(value)) {
+            if (indexIn(unique, item) < 0) {
+                unique.add(item);
+            }
+        }
+        return unique;
+    }
+
+    public static Object pick(Object dict, Object keys) {
+        Map<?, ?> map = dictionary(dict);
+        Map<String, Object> picked = new LinkedHashMap<>();
+        for (String key : stringKeys(keys)) {
+            if (map.containsKey(key)) {
+                picked.put(key, map.get(key));
+            }
+        }
+        return picked;
+    }
+
+    public static Object omit(Object dict, Object keys) {
+        Map<?, ?> map = dictionary(dict);
+        List<String> omitted = stringKeys(keys);
+        Map<String, Object> remaining = new LinkedHashMap<>();
+        for (Map.Entry<?, ?> entry : map.entrySet()) {
+            if (!omitted.contains(entry.getKey())) {
+                remaining.put((String) entry.getKey(), entry.getValue());
+            }
+        }
+        return remaining;
+    }
+
+    /** Whether a dictionary has a key, even if its value is nil */
+    public static Object hasKey(Object dict, Object key) {
+        return dictionary(dict).containsKey(string(key));
+    }
+
+    public static Object get(Object dict, Object key) {
+        return get(dict, key, null);
+    }
+
+    /** The value for a key, or the default when the key is missing; a nil value is returned as nil */
+    public static Object get(Object dict, Object key, Object defaultValue) {
+        Map<?, ?> map = dictionary(dict);
+        String k = string(key);
+        return map.containsKey(k) ? map.get(k) : defaultValue;
+    }
+
+    /** Follows string keys into dictionaries and whole number indexes into arrays; nil if anything is missing */
+    public static Object dig(Object data, Object path) {
+        Object current = data;
+        for (Object step : array(path)) {
+            if (step instanceof String) {
+                if (!(current instanceof Map) || !((Map<?, ?>) current).containsKey(step)) {
+                    return null;
+                }
+                current = ((Map<?, ?>) current).get(step);
+            } else if (numeric(step)) {
+                if (!(current instanceof List)) {
+                    return null;
+                }
+                double n = toDouble(step);
+                if (n % 1 != 0) {
+                    return null;
+                }
+                List<?> items = (List<?>) current;
+                long i = (long) n;
+                long actual = i < 0 ? items.size() + i : i;
+                if (actual < 0 || actual >= items.size()) {
+                    return null;
+                }
+                current = items.get((int) actual);
+            } else {
+                throw typeError("Array of Strings or Numbers", "Array containing " + argType(step));
+            }
+        }
+        return current;
+    }
+
+    public static Object union(Object left, Object right) {
+        List<Object> result = new ArrayList<>();
+        for (List<?> items : arrayPair(left, right)) {
+            for (Object item : items) {
+                if (indexIn(result, item) < 0) {
+                    result.add(item);
+                }
+            }
+        }
+        return result;
+    }
+
+    public static Object intersection(Object left, Object right) {
+        List<?>[] pair = arrayPair(left, right);
+        List<Object> result = new ArrayList<>();
+        for (Object item : pair[0]) {
+            if (indexIn(pair[1], item) >= 0 && indexIn(result, item) < 0) {
+                result.add(item);
+            }
+        }
+        return result;
+    }
+
+    public static Object difference(Object left, Object right) {
+        List<?>[] pair = arrayPair(left, right);
+        List<Object> result = new ArrayList<>();
+        for (Object item : pair[0]) {
+            if (indexIn(pair[1], item) < 0 && indexIn(result, item) < 0) {
+                result.add(item);
+            }
+        }
+        return result;
+    }
+
+    // Logic functions
+
+    /** The element at a 1-based index, or nil */
+    public static Object choose(Object index, Object array) {
+        double i = number(index);
+        List<?> items = array(array);
+        if (i < 1.0) {
+            return null;
+        }
+        long actual = (long) i - 1;
+        return actual >= items.size() ? null : items.get((int) actual);
+    }
+
+    /** Like {@code if}, but both branches are evaluated */
+    public static Object ifThenElse(Object condition, Object whenTrue, Object whenFalse) {
+        return truthy(condition) ? whenTrue : whenFalse;
+    }
+
+    public static Object isNil(Object value) {
+        return value == null;
+    }
+
+    public static Object isNumber(Object value) {
+        return numeric(value);
+    }
+
+    public static Object isString(Object value) {
+        return value instanceof String;
+    }
+
+    public static Object isBoolean(Object value) {
+        return value instanceof Boolean;
+    }
+
+    public static Object isArray(Object value) {
+        return value instanceof List;
+    }
+
+    public static Object isDictionary(Object value) {
+        return value instanceof Map;
+    }
+
+    /** The first value that is not nil, or nil */
+    public static Object coalesce(Object... values) {
+        for (Object value : values) {
+            if (value != null) {
+                return value;
+            }
+        }
+        return null;
+    }
+
+    /** {@code default(value, fallback)}; renamed since {@code default} is a Java keyword */
+    public static Object defaultTo(Object value, Object fallback) {
+        return coalesce(value, fallback);
+    }
+
+    public static Object between(Object value, Object lo, Object hi) {
+        return between(value, lo, hi, "[]");
+    }
+
+    /** Whether a number or string lies in a range; bounds are "[]", "[)", "(]" or "()" */
+    public static Object between(Object value, Object lo, Object hi, Object bounds) {
+        String b = string(bounds);
+        boolean lowerInclusive;
+        boolean upperInclusive;
+        switch (b) {
+            case "[]":
+                lowerInclusive = true;
+                upperInclusive = true;
+                break;
+            case "[)":
+                lowerInclusive = true;
+                upperInclusive = false;
+                break;
+            case "(]":
+                lowerInclusive = false;
+                upperInclusive = true;
+                break;
+            case "()":
+                lowerInclusive = false;
+                upperInclusive = false;
+                break;
+            default:
+                throw new AmoskeagException("Function error: Argument error: bounds must be one of "
+                        + "\"[]\", \"[)\", \"(]\", or \"()\", got " + quote(b));
+        }
+        int low = order(value, lo);
+        int high = order(value, hi);
+        boolean aboveLo = low != UNORDERED && (low > 0 || (low == 0 && lowerInclusive));
+        boolean belowHi = high < 0 || (high == 0 && upperInclusive);
+        return aboveLo && belowHi;
+    }
+
+    /** Order {@code value} against a bound of the same type, for {@code between} */
+    private static int order(Object value, Object other) {
+        if (numeric(value) && numeric(other)) {
+            double a = toDouble(value);
+            double b = toDouble(other);
+            return Double.isNaN(a) || Double.isNaN(b) ? UNORDERED : (a < b ? -1 : (a > b ? 1 : 0));
+        }
+        if (value instanceof String && other instanceof String) {
+            return Integer.signum(compareStrings((String) value, (String) other));
+        }
+        if (numeric(value) || value instanceof String) {
+            throw typeError(argType(value), argType(other));
+        }
+        throw typeError("Number or String", argType(value));
+    }
+
+    // Conversion functions
+
+    /** Numbers pass through, plain decimal strings are parsed, booleans become 1 or 0; anything else is nil */
+    public static Object toNumber(Object value) {
+        if (numeric(value)) {
+            return toDouble(value);
+        }
+        if (value instanceof Boolean) {
+            return (Boolean) value ? 1.0 : 0.0;
+        }
+        if (value instanceof String) {
+            String trimmed = (String) strip(value);
+            if (NUMERIC.matcher(trimmed).matches()) {
+                try {
+                    return Double.parseDouble(trimmed);
+                } catch (NumberFormatException e) {
+                    // Digits other than ASCII ones, which the interpreter cannot parse either
+                    return null;
+                }
+            }
+        }
+        return null;
+    }
+
+    public static Object toNumberStrict(Object value) {
+        return strict(toNumber(value), value, "Number");
+    }
+
+    /** Arrays and dictionaries give nil; nil gives the empty string */
+    public static Object toString(Object value) {
+        if (value == null) {
+            return "";
+        }
+        if (value instanceof List || value instanceof Map) {
+            return null;
+        }
+        if (value instanceof Symbol) {
+            return ((Symbol) value).name();
+        }
+        return display(floats(value));
+    }
+
+    public static Object toStringStrict(Object value) {
+        return strict(toString(value), value, "String");
+    }
+
+    /** Numbers are true unless zero; strings such as "yes", "off" and "1" are recognised; anything else is nil */
+    public static Object toBoolean(Object value) {
+        if (value instanceof Boolean) {
+            return value;
+        }
+        if (numeric(value)) {
+            double n = toDouble(value);
+            return Double.isNaN(n) ? null : (Object) (n != 0.0);
+        }
+        if (value instanceof String) {
+            switch (((String) strip(value)).toLowerCase(Locale.ROOT)) {
+                case "true":
+                case "yes":
+                case "y":
+                case "on":
+                case "1":
+                    return true;
+                case "false":
+                case "no":
+                case "n":
+                case "off":
+                case "0":
+                    return false;
+                default:
+                    return null;
+            }
+        }
+        return null;
+    }
+
+    public static Object toBooleanStrict(Object value) {
+        return strict(toBoolean(value), value, "Boolean");
+    }
+
+    private static Object strict(Object result, Object value, String target) {
+        if (result != null) {
+            return result;
+        }
+        String what = value instanceof String ? "String " + quote((String) value) : argType(value);
+        throw new AmoskeagException("Function error: Value error: Cannot convert " + what + " to " + target);
+    }
+
+    // Values
+
+    /** A value as the interpreter displays it, e.g. in string concatenation */
+    public static String display(Object value) {
+        if (value == null) {
+            return "nil";
+        }
+        if (value instanceof Double || value instanceof Float) {
+            return formatNumber(((Number) value).doubleValue());
+        }
+        if (value instanceof List) {
+            StringBuilder out = new StringBuilder("[");
+            List<?> items = (List<?>) value;
+            for (int i = 0; i < items.size(); i++) {
+                if (i > 0) {
+                    out.append(", ");
+                }
+                out.append(display(items.get(i)));
+            }
+            return out.append("]").toString();
+        }
+        if (value instanceof Map) {
+            StringBuilder out = new StringBuilder("{");
+            boolean firstEntry = true;
+            for (Map.Entry<?, ?> entry : ((Map<?, ?>) value).entrySet()) {
+                if (!firstEntry) {
+                    out.append(", ");
+                }
+                firstEntry = false;
+                out.append('"').append(entry.getKey()).append("\": ").append(display(entry.getValue()));
+            }
+            return out.append("}").toString();
+        }
+        if (numeric(value)) {
+            return formatNumber(toDouble(value));
+        }
+        return value.toString();
+    }
+
+    /** A number written the way the interpreter writes it: 3 rather than 3.0, and never in exponent form */
+    public static String formatNumber(double n) {
+        if (Double.isNaN(n)) {
+            return "NaN";
+        }
+        if (Double.isInfinite(n)) {
+            return n > 0 ? "inf" : "-inf";
+        }
+        if (n == 0.0) {
+            return 1.0 / n < 0 ? "-0" : "0";
+        }
+        return new BigDecimal(Double.toString(n)).stripTrailingZeros().toPlainString();
+    }
+
+    /** Deep equality, where an integer equals the number with the same value */
+    private static boolean valueEquals(Object a, Object b) {
+        if (a == null || b == null) {
+            return a == b;
+        }
+        if (numeric(a) && numeric(b)) {
+            if (isInt(a) && isInt(b)) {
+                return ((Number) a).longValue() == ((Number) b).longValue();
+            }
+            return toDouble(a) == toDouble(b);
+        }
+        if (a instanceof List && b instanceof List) {
+            List<?> l = (List<?>) a;
+            List<?> r = (List<?>) b;
+            if (l.size() != r.size()) {
+                return false;
+            }
+            for (int i = 0; i < l.size(); i++) {
+                if (!valueEquals(l.get(i), r.get(i))) {
+                    return false;
+                }
+            }
+            return true;
+        }
+        if (a instanceof Map && b instanceof Map) {
+            Map<?, ?> l = (Map<?, ?>) a;
+            Map<?, ?> r = (Map<?, ?>) b;
+            if (l.size() != r.size()) {
+                return false;
+            }
+            for (Map.Entry<?, ?> entry : l.entrySet()) {
+                if (!r.containsKey(entry.getKey()) || !valueEquals(entry.getValue(), r.get(entry.getKey()))) {
+                    return false;
+                }
+            }
+            return true;
+        }
+        if (a instanceof Boolean || a instanceof String || a instanceof Symbol) {
+            return a.equals(b);
+        }
+        return false;
+    }
+
+    /** The position of the first element equal to {@code value}, or -1 */
+    private static int indexIn(List<?> items, Object value) {
+        for (int i = 0; i < items.size(); i++) {
+            if (valueEquals(items.get(i), value)) {
+                return i;
+            }
+        }
+        return -1;
+    }
+
+    /**
+     * A value with every integer, at any depth, replaced by a number
+     *
+     * The interpreter promotes the arguments of most standard library
+     * functions this way, so anything they return from their arguments is
+     * promoted too. Functions that only select or reorder values, such as
+     * {@code first} and {@code sort}, return them unchanged.
+     */
+    private static Object floats(Object value) {
+        if (isInt(value)) {
+            return toDouble(value);
+        }
+        if (value instanceof List) {
+            List<Object> items = new ArrayList<>();
+            for (Object item : (List<?>) value) {
+                items.add(floats(item));
+            }
+            return items;
+        }
+        if (value instanceof Map) {
+            Map<String, Object> map = new LinkedHashMap<>();
+            for (Map.Entry<?, ?> entry : ((Map<?, ?>) value).entrySet()) {
+                map.put((String) entry.getKey(), floats(entry.getValue()));
+            }
+            return map;
+        }
+        return value;
+    }
+
+    private static boolean numeric(Object value) {
+        return value instanceof Number;
+    }
+
+    private static boolean isInt(Object value) {
+        return value instanceof Long || value instanceof Integer || value instanceof Short || value instanceof Byte;
+    }
+
+    private static double toDouble(Object value) {
+        return ((Number) value).doubleValue();
+    }
+
+    private static String typeName(Object value) {
+        if (value == null) {
+            return "Nil";
+        }
+        if (isInt(value)) {
+            return "Integer";
+        }
+        if (value instanceof Number) {
+            return "Number";
+        }
+        if (value instanceof String) {
+            return "String";
+        }
+        if (value instanceof Boolean) {
+            return "Boolean";
+        }
+        if (value instanceof Symbol) {
+            return "Symbol";
+        }
+        if (value instanceof List) {
+            return "Array";
+        }
+        if (value instanceof Map) {
+            return "Dictionary";
+        }
+        return value.getClass().getSimpleName();
+    }
+
+    /** The type of a standard library argument, which never sees integers */
+    private static String argType(Object value) {
+        return isInt(value) ? "Number" : typeName(value);
+    }
+
+    private static AmoskeagException typeError(String expected, String got) {
+        return new AmoskeagException("Function error: Type error: expected " + expected + ", got " + got);
+    }
+
+    private static double number(Object value) {
+        if (!numeric(value)) {
+            throw typeError("Number", argType(value));
+        }
+        return toDouble(value);
+    }
+
+    private static String string(Object value) {
+        if (!(value instanceof String)) {
+            throw typeError("String", argType(value));
+        }
+        return (String) value;
+    }
+
+    private static List<?> array(Object value) {
+        if (!(value instanceof List)) {
+            throw typeError("Array", argType(value));
+        }
+        return (List<?>) value;
+    }
+
+    private static Map<?, ?> dictionary(Object value) {
+        if (!(value instanceof Map)) {
+            throw typeError("Dictionary", argType(value));
+        }
+        return (Map<?, ?>) value;
+    }
+
+    /** A copy of an array, for functions that reorder it */
+    private static List<Object> copy(Object value) {
+        return new ArrayList<Object>(array(value));
+    }
+
+    private static List<?>[] arrayPair(Object left, Object right) {
+        return new List<?>[] {array(left), array(right)};
+    }
+
+    private static double[] numbers(Object value) {
+        List<?> items = array(value);
+        double[] numbers = new double[items.size()];
+        for (int i = 0; i < numbers.length; i++) {
+            Object item = items.get(i);
+            if (!numeric(item)) {
+                throw typeError("Array of Numbers", "Array containing " + argType(item));
+            }
+            numbers[i] = toDouble(item);
+        }
+        return numbers;
+    }
+
+    private static List<String> stringKeys(Object keys) {
+        List<String> result = new ArrayList<>();
+        for (Object key : array(keys)) {
+            if (!(key instanceof String)) {
+                throw typeError("Array of Strings", "Array containing " + argType(key));
+            }
+            result.add((String) key);
+        }
+        return result;
+    }
+
+    /** Orders numbers, treating a NaN as equal to anything */
+    private static int compareNumbers(double a, double b) {
+        return a < b ? -1 : (a > b ? 1 : 0);
+    }
+
+    /** Orders strings by code point, as Rust does, rather than by UTF-16 unit */
+    private static int compareStrings(String a, String b) {
+        int i = 0;
+        int j = 0;
+        while (i < a.length() && j < b.length()) {
+            int x = a.codePointAt(i);
+            int y = b.codePointAt(j);
+            if (x != y) {
+                return Integer.compare(x, y);
+            }
+            i += Character.charCount(x);
+            j += Character.charCount(y);
+        }
+        return Integer.compare(a.length() - i, b.length() - j);
+    }
+
+    /** Rounds half away from zero, like Rust's f64::round */
+    private static double roundHalfAway(double x) {
+        double whole = Math.floor(Math.abs(x));
+        double fraction = Math.abs(x) - whole;
+        return Math.copySign(fraction >= 0.5 ? whole + 1 : whole, x);
+    }
+
+    /** A count taken from a number, with negatives and NaN as zero */
+    private static long nonNegative(double n) {
+        return n > 0 ? (long) n : 0;
+    }
+
+    private static boolean isWhitespace(int c) {
+        return Character.isWhitespace(c) || Character.isSpaceChar(c);
+    }
+
+    private static List<String> words(String s) {
+        List<String> words = new ArrayList<>();
+        StringBuilder word = new StringBuilder();
+        s.codePoints().forEach(c -> {
+            if (isWhitespace(c)) {
+                if (word.length() > 0) {
+                    words.add(word.toString());
+                    word.setLength(0);
+                }
+            } else {
+                word.appendCodePoint(c);
+            }
+        });
+        if (word.length() > 0) {
+            words.add(word.toString());
+        }
+        return words;
+    }
+
+    private static String takeCodePoints(String s, long skip, long take) {
+        StringBuilder out = new StringBuilder();
+        s.codePoints().skip(skip).limit(take).forEach(out::appendCodePoint);
+        return out.toString();
+    }
+
+    private static long utf8Length(String s) {
+        long bytes = 0;
+        for (int i = 0; i < s.length(); i += Character.charCount(s.codePointAt(i))) {
+            int c = s.codePointAt(i);
+            bytes += c < 0x80 ? 1 : (c < 0x800 ? 2 : (c < 0x10000 ? 3 : 4));
+        }
+        return bytes;
+    }
+
+    private static String utf8(byte[] bytes, String error) {
+        try {
+            return StandardCharsets.UTF_8.newDecoder()
+                    .onMalformedInput(CodingErrorAction.REPORT)
+                    .onUnmappableCharacter(CodingErrorAction.REPORT)
+                    .decode(ByteBuffer.wrap(bytes))
+                    .toString();
+        } catch (CharacterCodingException e) {
+            throw new AmoskeagException("Function error: Value error: " + error);
+        }
+    }
+
+    private static String digest(String algorithm, String s) {
+        try {
+            StringBuilder hex = new StringBuilder();
+            for (byte b : MessageDigest.getInstance(algorithm).digest(s.getBytes(StandardCharsets.UTF_8))) {
+                hex.append(String.format("%02x", b & 0xFF));
+            }
+            return hex.toString();
+        } catch (NoSuchAlgorithmException e) {
+            throw new IllegalStateException(algorithm + " is required on every Java platform", e);
+        }
+    }
+
+    private static String quote(String s) {
+        return "\"" + s.replace("\\", "\\\\").replace("\"", "\\\"") + "\"";
+    }
+}
@@ -0,0 +1,16 @@
+package io.amoskeag.runtime;
+
+/**
+ * An error raised while evaluating a transpiled Amoskeag program.
+ *
+ * The messages match the ones the Amoskeag interpreter reports, such as
+ * "Operator error: Division by zero". {@code try} in a program catches these
+ * and only these.
+ */
+public class AmoskeagException extends RuntimeException {
+    private static final long serialVersionUID = 1L;
+
+    public AmoskeagException(String message) {
+        super(message);
+    }
+}
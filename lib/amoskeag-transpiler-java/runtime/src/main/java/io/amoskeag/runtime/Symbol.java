@@ -0,0 +1,42 @@
+package io.amoskeag.runtime;
+
+import java.util.Objects;
+
+/**
+ * An Amoskeag symbol such as {@code :approve}.
+ *
+ * Symbols are equal when their names are equal, so results can be compared
+ * with {@code Symbol.of("approve")}.
+ */
+public final class Symbol {
+    private final String name;
+
+    private Symbol(String name) {
+        this.name = Objects.requireNonNull(name, "name");
+    }
+
+    /** The symbol with the given name, without the leading colon */
+    public static Symbol of(String name) {
+        return new Symbol(name);
+    }
+
+    /** The symbol's name, without the leading colon */
+    public String name() {
+        return name;
+    }
+
+    @Override
+    public boolean equals(Object other) {
+        return other instanceof Symbol && ((Symbol) other).name.equals(name);
+    }
+
+    @Override
+    public int hashCode() {
+        return name.hashCode();
+    }
+
+    @Override
+    public String toString() {
+        return ":" + name;
+    }
+}
@@ -0,0 +1,652 @@
+//! Amoskeag to Java Transpiler
+//!
+//! This crate transpiles an Amoskeag program into a Java class with one static
+//! method, `evaluate(Map<String, Object> data)`, so JVM systems can run rules
+//! without embedding the interpreter.
+//!
+//! The generated code calls the runtime library in `runtime/`, the Java package
+//! `io.amoskeag.runtime`, for every operator and standard library function.
+//! The runtime implements the interpreter's semantics, such as integer
+//! arithmetic that falls back to floating point on overflow, so a transpiled
+//! rule gives the same results as the interpreted one.
+
+use amoskeag_parser::{BinaryOp, Expr, UnaryOp};
+use amoskeag_stdlib_functions::signatures::{self, ArityError};
+use std::fmt::Write;
+use thiserror::Error;
+
+/// The Java package of the runtime library
+pub const RUNTIME_PACKAGE: &str = "io.amoskeag.runtime";
+
+/// Errors that can occur during transpilation
+#[derive(Error, Debug)]
+pub enum TranspileError {
+    #[error("Formatting error: {0}")]
+    FormatError(#[from] std::fmt::Error),
+
+    #[error("Unsupported expression: {0}")]
+    UnsupportedExpression(String),
+
+    #[error("Function '{0}' is not available in the Java runtime")]
+    UnsupportedFunction(String),
+
+    #[error("'{0}' is not a valid Java name")]
+    InvalidName(String),
+
+    #[error(transparent)]
+    Arity(#[from] ArityError),
+}
+
+/// Configuration for the transpiler
+#[derive(Debug, Clone)]
+pub struct TranspileConfig {
+    /// Package of the generated class (default: none, the unnamed package)
+    pub package: Option<String>,
+    /// Name of the generated class (default: `AmoskeagProgram`)
+    pub class_name: String,
+    /// Indentation string (default: 4 spaces)
+    pub indent: String,
+}
+
+impl Default for TranspileConfig {
+    fn default() -> Self {
+        Self {
+            package: None,
+            class_name: "AmoskeagProgram".to_string(),
+            indent: "    ".to_string(),
+        }
+    }
+}
+
+/// Standard library functions implemented by the runtime, each by the
+/// method `method_name` gives
+///
+/// Decimal, duration, date, regex and financial functions are not in the
+/// runtime; programs that call them fail with `UnsupportedFunction`.
+const FUNCTIONS: &[&str] = &[
+    // String functions
+    "upcase",
+    "downcase",
+    "capitalize",
+    "strip",
+    "trim_start",
+    "trim_end",
+    "squish",
+    "split",
+    "chars",
+    "lines",
+    "join",
+    "truncate",
+    "substring",
+    "repeat",
+    "pad_left",
+    "pad_right",
+    "replace",
+    "starts_with",
+    "ends_with",
+    "index_of",
+    "includes",
+    "is_numeric_string",
+    "sha256",
+    "md5",
+    "base64_encode",
+    "base64_decode",
+    "url_encode",
+    "url_decode",
+    // Numeric functions
+    "abs",
+    "ceil",
+    "floor",
+    "round",
+    "trunc",
+    "sign",
+    "clamp",
+    "plus",
+    "minus",
+    "times",
+    "divided_by",
+    "modulo",
+    "max",
+    "min",
+    "power",
+    "pow",
+    "sqrt",
+    // Collection functions
+    "size",
+    "first",
+    "last",
+    "contains",
+    "sum",
+    "avg",
+    "median",
+    "sort",
+    "keys",
+    "values",
+    "reverse",
+    "at",
+    "uniq",
+    "pick",
+    "omit",
+    "has_key",
+    "get",
+    "dig",
+    "union",
+    "intersection",
+    "difference",
+    // Logic functions
+    "choose",
+    "if_then_else",
+    "is_nil",
+    "is_number",
+    "is_string",
+    "is_boolean",
+    "is_array",
+    "is_dictionary",
+    "coalesce",
+    "default",
+    "between",
+    // Conversion functions
+    "to_number",
+    "to_number_strict",
+    "to_string",
+    "to_string_strict",
+    "to_boolean",
+    "to_boolean_strict",
+];
+
+/// Words that cannot be Java identifiers, separated by spaces
+const JAVA_KEYWORDS: &str = "_ abstract assert boolean break byte case catch char class const \
+    continue default do double else enum extends false final finally float for goto if \
+    implements import instanceof int interface long native new null package private protected \
+    public return short static strictfp super switch synchronized this throw throws transient \
+    true try void volatile while";
+
+/// Transpile an Amoskeag AST to a Java class
+///
+/// # Arguments
+///
+/// * `expr` - The AST expression to transpile
+/// * `config` - Configuration for the transpiler
+///
+/// # Returns
+///
+/// A string containing the Java source of `config.class_name`
+pub fn transpile(expr: &Expr, config: &TranspileConfig) -> Result<String, TranspileError> {
+    if !is_identifier(&config.class_name) {
+        return Err(TranspileError::InvalidName(config.class_name.clone()));
+    }
+    if let Some(package) = &config.package {
+        if !package.split('.').all(is_identifier) {
+            return Err(TranspileError::InvalidName(package.clone()));
+        }
+    }
+
+    let body = Generator::default().expr(expr)?;
+    let indent = &config.indent;
+    let mut output = String::new();
+
+    writeln!(output, "// Generated by Amoskeag Java Transpiler")?;
+    if let Some(package) = &config.package {
+        writeln!(output, "package {};", package)?;
+    }
+    writeln!(output)?;
+    writeln!(output, "import {}.Amoskeag;", RUNTIME_PACKAGE)?;
+    writeln!(output, "import {}.Symbol;", RUNTIME_PACKAGE)?;
+    writeln!(output, "import java.util.Map;")?;
+    writeln!(output)?;
+    writeln!(output, "public final class {} {{", config.class_name)?;
+    writeln!(output, "{}private {}() {{", indent, config.class_name)?;
+    writeln!(output, "{}}}", indent)?;
+    writeln!(output)?;
+    writeln!(output, "{}/** Evaluate the Amoskeag program. */", indent)?;
+    writeln!(
+        output,
+        "{}public static Object evaluate(Map<String, Object> data) {{",
+        indent
+    )?;
+    writeln!(output, "{}{}return {};", indent, indent, body)?;
+    writeln!(output, "{}}}", indent)?;
+    writeln!(output, "}}")?;
+
+    Ok(output)
+}
+
+/// Transpile Amoskeag source code to Java
+///
+/// This is a convenience function that parses and transpiles in one step.
+///
+/// # Arguments
+///
+/// * `source` - The Amoskeag source code
+/// * `config` - Optional transpiler configuration
+///
+/// # Returns
+///
+/// A string containing the generated Java class
+pub fn transpile_source(
+    source: &str,
+    config: Option<TranspileConfig>,
+) -> Result<String, Box<dyn std::error::Error>> {
+    let config = config.unwrap_or_default();
+    let ast = amoskeag_parser::parse(source)?;
+    Ok(transpile(&ast, &config)?)
+}
+
+/// Whether `name` can be used as a Java identifier
+fn is_identifier(name: &str) -> bool {
+    let mut chars = name.chars();
+    chars
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_' || c == '$')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '$')
+        && !JAVA_KEYWORDS
+            .split_whitespace()
+            .any(|keyword| keyword == name)
+}
+
+/// Generates the Java expression for an Amoskeag expression
+///
+/// `let` bindings become lambda parameters. Java lambdas cannot shadow an
+/// enclosing local, so each is renamed `name$n` with a number unique within
+/// the program; `$` cannot appear in Amoskeag names, so the renamed
+/// parameters never clash with each other, with `data`, or with a keyword.
+/// Brace names such as `{net premium}` that are not Java identifiers are
+/// renamed `let$n`.
+#[derive(Default)]
+struct Generator {
+    /// The `let` names in scope and their Java names, innermost last
+    scope: Vec<(String, String)>,
+    /// Lambda parameters generated so far
+    locals: usize,
+}
+
+impl Generator {
+    fn expr(&mut self, expr: &Expr) -> Result<String, TranspileError> {
+        match expr {
+            // Literals are boxed so every expression is an Object, which
+            // keeps `?:` from unboxing and promoting numeric branches
+            Expr::Number(n) => Ok(format!("Double.valueOf({:?})", n)),
+            Expr::Integer(n) => Ok(format!("Long.valueOf({}L)", n)),
+            Expr::String(s) => Ok(java_string(s)),
+            Expr::Boolean(b) => Ok(if *b { "Boolean.TRUE" } else { "Boolean.FALSE" }.to_string()),
+            Expr::Nil => Ok("null".to_string()),
+            Expr::Symbol(s) => Ok(format!("Symbol.of({})", java_string(s))),
+
+            Expr::Array(elements) => {
+                let items = self.exprs(elements)?;
+                // A lone null would be taken as the varargs array itself
+                if items.len() == 1 {
+                    return Ok(format!("Amoskeag.list((Object) {})", items[0]));
+                }
+                Ok(format!("Amoskeag.list({})", items.join(", ")))
+            }
+
+            Expr::Dictionary(pairs) => {
+                let mut parts = Vec::new();
+                for (key, value) in pairs {
+                    parts.push(java_string(key));
+                    parts.push(self.expr(value)?);
+                }
+                Ok(format!("Amoskeag.dict({})", parts.join(", ")))
+            }
+
+            Expr::Variable(path) => {
+                let Some((root, fields)) = path.split_first() else {
+                    return Ok("null".to_string());
+                };
+                let (base, keys) = match self.scope.iter().rev().find(|(name, _)| name == root) {
+                    Some((_, local)) => (local.clone(), fields),
+                    None => ("data".to_string(), &path[..]),
+                };
+                if keys.is_empty() {
+                    return Ok(base);
+                }
+                // Like the interpreter, a missing variable is an error but a
+                // missing key on the way down a dotted path is nil
+                if keys.len() == 1 && base == "data" {
+                    return Ok(format!("Amoskeag.variable(data, {})", java_string(root)));
+                }
+                let keys: Vec<String> = keys.iter().map(|key| java_string(key)).collect();
+                Ok(format!("Amoskeag.navigate({}, {})", base, keys.join(", ")))
+            }
+
+            Expr::FunctionCall { name, args } => self.call(name, args),
+
+            Expr::Let { name, value, body } => {
+                let value = self.expr(value)?;
+                self.locals += 1;
+                let local = if is_identifier(name) {
+                    format!("{}${}", name, self.locals)
+                } else {
+                    format!("let${}", self.locals)
+                };
+                self.scope.push((name.clone(), local.clone()));
+                let body = self.expr(body);
+                self.scope.pop();
+                Ok(format!(
+                    "Amoskeag.let({}, (Object {}) -> {})",
+                    value, local, body?
+                ))
+            }
+
+            Expr::If {
+                condition,
+                then_branch,
+                else_branch,
+            } => {
+                let condition = self.expr(condition)?;
+                let then_branch = self.expr(then_branch)?;
+                let else_branch = self.expr(else_branch)?;
+                Ok(format!(
+                    "(Amoskeag.truthy({}) ? (Object) {} : {})",
+                    condition, then_branch, else_branch
+                ))
+            }
+
+            Expr::Binary { op, left, right } => {
+                let method = match op {
+                    BinaryOp::Add => "add",
+                    BinaryOp::Subtract => "subtract",
+                    BinaryOp::Multiply => "multiply",
+                    BinaryOp::Divide => "divide",
+                    BinaryOp::Modulo => "remainder",
+                    BinaryOp::Power => "exponent",
+                    BinaryOp::Equal => "eq",
+                    BinaryOp::NotEqual => "ne",
+                    BinaryOp::Less => "lt",
+                    BinaryOp::Greater => "gt",
+                    BinaryOp::LessEqual => "le",
+                    BinaryOp::GreaterEqual => "ge",
+                    BinaryOp::And => "and",
+                    BinaryOp::Or => "or",
+                };
+                let left = self.expr(left)?;
+                let right = self.expr(right)?;
+                Ok(format!("Amoskeag.{}({}, {})", method, left, right))
+            }
+
+            Expr::Unary { op, operand } => {
+                let method = match op {
+                    UnaryOp::Not => "not",
+                    UnaryOp::Negate => "negate",
+                };
+                Ok(format!("Amoskeag.{}({})", method, self.expr(operand)?))
+            }
+
+            // Pipe expression (should be transformed by parser, but handle for completeness)
+            Expr::Pipe { .. } => Err(TranspileError::UnsupportedExpression(
+                "Pipe expressions should be transformed during parsing".to_string(),
+            )),
+        }
+    }
+
+    fn exprs(&mut self, exprs: &[Expr]) -> Result<Vec<String>, TranspileError> {
+        exprs.iter().map(|expr| self.expr(expr)).collect()
+    }
+
+    fn call(&mut self, name: &str, args: &[Expr]) -> Result<String, TranspileError> {
+        signatures::check_arity(name, args.len())?;
+        let args = self.exprs(args)?;
+
+        // try(expr, fallback) evaluates the fallback only if expr fails
+        if name == "try" {
+            return Ok(format!(
+                "Amoskeag.tryOr(() -> {}, () -> {})",
+                args[0], args[1]
+            ));
+        }
+
+        if !FUNCTIONS.contains(&name) {
+            return Err(TranspileError::UnsupportedFunction(name.to_string()));
+        }
+        let method = method_name(name);
+        Ok(format!("Amoskeag.{}({})", method, args.join(", ")))
+    }
+}
+
+/// The runtime method implementing the standard library function `name`:
+/// its name in camelCase, except for the alias `pow` and for `default`,
+/// which is a Java keyword
+fn method_name(name: &str) -> String {
+    match name {
+        "pow" => "power".to_string(),
+        "default" => "defaultTo".to_string(),
+        _ => {
+            let mut words = name.split('_');
+            let mut method = words.next().unwrap_or_default().to_string();
+            for word in words {
+                let mut chars = word.chars();
+                method.extend(chars.next().map(|c| c.to_ascii_uppercase()));
+                method.push_str(chars.as_str());
+            }
+            method
+        }
+    }
+}
+
+/// A Java string literal for `s`
+///
+/// Everything outside printable ASCII is escaped, so the source compiles the
+/// same whatever encoding javac reads it with. Control characters use octal
+/// escapes because javac turns `\u000a` into a real line break before lexing.
+fn java_string(s: &str) -> String {
+    let mut literal = String::with_capacity(s.len() + 2);
+    literal.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => literal.push_str("\\\""),
+            '\\' => literal.push_str("\\\\"),
+            '\n' => literal.push_str("\\n"),
+            '\r' => literal.push_str("\\r"),
+            '\t' => literal.push_str("\\t"),
+            c if (c as u32) < 0x20 => {
+                let _ = write!(literal, "\\{:03o}", c as u32);
+            }
+            ' '..='~' => literal.push(c),
+            c => {
+                let mut units = [0; 2];
+                for unit in c.encode_utf16(&mut units) {
+                    let _ = write!(literal, "\\u{:04x}", unit);
+                }
+            }
+        }
+    }
+    literal.push('"');
+    literal
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use amoskeag_parser::parse;
+    use amoskeag_stdlib_functions::signatures::Arity;
+    use pretty_assertions::assert_eq;
+
+    /// The expression `evaluate` returns for `source`
+    fn body(source: &str) -> String {
+        let java = transpile_source(source, None).unwrap();
+        let start = java.find("return ").unwrap() + "return ".len();
+        let end = java[start..].find(";\n").unwrap();
+        java[start..start + end].to_string()
+    }
+
+    #[test]
+    fn test_transpile_class() {
+        let config = TranspileConfig {
+            package: Some("com.example.rules".to_string()),
+            class_name: "Eligibility".to_string(),
+            ..Default::default()
+        };
+        let java = transpile_source("42", Some(config)).unwrap();
+        assert!(java.starts_with("// Generated by Amoskeag Java Transpiler\n"));
+        assert!(java.contains("package com.example.rules;\n"));
+        assert!(java.contains("import io.amoskeag.runtime.Amoskeag;\n"));
+        assert!(java.contains("public final class Eligibility {\n"));
+        assert!(java.contains("    public static Object evaluate(Map<String, Object> data) {\n"));
+        assert!(java.contains("        return Long.valueOf(42L);\n"));
+    }
+
+    #[test]
+    fn test_transpile_literals() {
+        assert_eq!(body("42.0"), "Double.valueOf(42.0)");
+        assert_eq!(body("true"), "Boolean.TRUE");
+        assert_eq!(body("nil"), "null");
+        assert_eq!(body(":approve"), "Symbol.of(\"approve\")");
+        assert_eq!(body("[1, nil]"), "Amoskeag.list(Long.valueOf(1L), null)");
+        assert_eq!(body("[nil]"), "Amoskeag.list((Object) null)");
+        assert_eq!(
+            body("{\"limit\": 5}"),
+            "Amoskeag.dict(\"limit\", Long.valueOf(5L))"
+        );
+    }
+
+    #[test]
+    fn test_java_string_escapes() {
+        assert_eq!(java_string("say \"hi\"\n"), "\"say \\\"hi\\\"\\n\"");
+        assert_eq!(java_string("a\u{0}b"), "\"a\\000b\"");
+        assert_eq!(java_string("café"), "\"caf\\u00e9\"");
+        assert_eq!(java_string("😀"), "\"\\ud83d\\ude00\"");
+    }
+
+    #[test]
+    fn test_transpile_variable() {
+        assert_eq!(body("age"), "Amoskeag.variable(data, \"age\")");
+        assert_eq!(
+            body("driver.age"),
+            "Amoskeag.navigate(data, \"driver\", \"age\")"
+        );
+    }
+
+    #[test]
+    fn test_transpile_operators() {
+        assert_eq!(
+            body("1 + 2 * 3"),
+            "Amoskeag.add(Long.valueOf(1L), Amoskeag.multiply(Long.valueOf(2L), Long.valueOf(3L)))"
+        );
+        assert_eq!(
+            body("a % 2 == 0 and not b"),
+            "Amoskeag.and(Amoskeag.eq(Amoskeag.remainder(Amoskeag.variable(data, \"a\"), Long.valueOf(2L)), Long.valueOf(0L)), Amoskeag.not(Amoskeag.variable(data, \"b\")))"
+        );
+        assert_eq!(
+            body("-x"),
+            "Amoskeag.negate(Amoskeag.variable(data, \"x\"))"
+        );
+    }
+
+    #[test]
+    fn test_transpile_if_expression() {
+        assert_eq!(
+            body("if age >= 18 :adult else :minor end"),
+            "(Amoskeag.truthy(Amoskeag.ge(Amoskeag.variable(data, \"age\"), Long.valueOf(18L))) ? (Object) Symbol.of(\"adult\") : Symbol.of(\"minor\"))"
+        );
+    }
+
+    #[test]
+    fn test_transpile_let_expression() {
+        assert_eq!(
+            body("let x = 5 in x + 1"),
+            "Amoskeag.let(Long.valueOf(5L), (Object x$1) -> Amoskeag.add(x$1, Long.valueOf(1L)))"
+        );
+    }
+
+    #[test]
+    fn test_transpile_shadowed_let() {
+        // The inner x is a new parameter; the outer one is back in scope after it
+        assert_eq!(
+            body("let x = driver in (let x = x.age in x) + x.years"),
+            "Amoskeag.let(Amoskeag.variable(data, \"driver\"), (Object x$1) -> Amoskeag.add(Amoskeag.let(Amoskeag.navigate(x$1, \"age\"), (Object x$2) -> x$2), Amoskeag.navigate(x$1, \"years\")))"
+        );
+    }
+
+    #[test]
+    fn test_transpile_let_brace_name() {
+        assert_eq!(
+            body("let {net premium} = 5 in {net premium}"),
+            "Amoskeag.let(Long.valueOf(5L), (Object let$1) -> let$1)"
+        );
+    }
+
+    #[test]
+    fn test_transpile_function_calls() {
+        assert_eq!(
+            body("name | upcase"),
+            "Amoskeag.upcase(Amoskeag.variable(data, \"name\"))"
+        );
+        assert_eq!(
+            body("default(limit, 100)"),
+            "Amoskeag.defaultTo(Amoskeag.variable(data, \"limit\"), Long.valueOf(100L))"
+        );
+        assert_eq!(
+            body("round(premium, 2)"),
+            "Amoskeag.round(Amoskeag.variable(data, \"premium\"), Long.valueOf(2L))"
+        );
+    }
+
+    #[test]
+    fn test_transpile_try() {
+        assert_eq!(
+            body("try(to_number_strict(age), 30)"),
+            "Amoskeag.tryOr(() -> Amoskeag.toNumberStrict(Amoskeag.variable(data, \"age\")), () -> Long.valueOf(30L))"
+        );
+    }
+
+    #[test]
+    fn test_transpile_unsupported_function() {
+        let expr = parse("date_now()").unwrap();
+        assert!(matches!(
+            transpile(&expr, &TranspileConfig::default()),
+            Err(TranspileError::UnsupportedFunction(name)) if name == "date_now"
+        ));
+    }
+
+    #[test]
+    fn test_runtime_functions_exist() {
+        let runtime = include_str!(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/runtime/src/main/java/io/amoskeag/runtime/Amoskeag.java"
+        ));
+        for function in FUNCTIONS {
+            assert!(
+                signatures::lookup(function).is_some(),
+                "{} is not a standard library function",
+                function
+            );
+            let method = method_name(function);
+            assert!(
+                runtime.contains(&format!("public static Object {}(", method)),
+                "the runtime has no method {} for {}",
+                method,
+                function
+            );
+        }
+    }
+
+    #[test]
+    fn test_transpile_arity_mismatch() {
+        let expr = parse("substring(name, 1)").unwrap();
+        assert!(matches!(
+            transpile(&expr, &TranspileConfig::default()),
+            Err(TranspileError::Arity(ArityError {
+                expected: Arity::Exact(3),
+                actual: 2,
+                ..
+            }))
+        ));
+    }
+
+    #[test]
+    fn test_invalid_names() {
+        let expr = parse("1").unwrap();
+        for (package, class_name) in [(None, "class"), (None, "2Fast"), (Some("com..x"), "Rule")] {
+            let config = TranspileConfig {
+                package: package.map(str::to_string),
+                class_name: class_name.to_string(),
+                ..Default::default()
+            };
+            assert!(matches!(
+                transpile(&expr, &config),
+                Err(TranspileError::InvalidName(_))
+            ));
+        }
+    }
+}
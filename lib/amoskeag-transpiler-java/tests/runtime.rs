@@ -0,0 +1,215 @@
+//! Runs the shared conformance cases on the Java runtime library
+//!
+//! The runtime and the generated classes are compiled with `javac` and run
+//! with `java`, and each result is compared with the one the fixture
+//! expects. Cases calling functions the runtime lacks are left out. Without
+//! a JDK on the path the tests are skipped, except in CI.
+
+#[path = "../../../conformance/support.rs"]
+mod conformance;
+
+use amoskeag_transpiler_java::{transpile, TranspileConfig, TranspileError};
+use serde_json::Value;
+use std::fmt::Write;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// Prints each result as one line of JSON in the fixture's form
+const HARNESS: &str = r#"
+    static String run(java.util.function.Supplier<Object> program) {
+        try {
+            return render(program.get());
+        } catch (AmoskeagException e) {
+            return "{\"$error\":" + quote(e.getMessage()) + "}";
+        }
+    }
+
+    static String render(Object value) {
+        if (value == null) {
+            return "null";
+        }
+        if (value instanceof String) {
+            return quote((String) value);
+        }
+        if (value instanceof Symbol) {
+            return "{\"$symbol\":" + quote(((Symbol) value).name()) + "}";
+        }
+        if (value instanceof Double && (((Double) value).isNaN() || ((Double) value).isInfinite())) {
+            return "null";
+        }
+        if (value instanceof List) {
+            StringJoiner items = new StringJoiner(",", "[", "]");
+            for (Object item : (List<?>) value) {
+                items.add(render(item));
+            }
+            return items.toString();
+        }
+        if (value instanceof Map) {
+            StringJoiner pairs = new StringJoiner(",", "{", "}");
+            for (Map.Entry<?, ?> entry : new TreeMap<>((Map<?, ?>) value).entrySet()) {
+                pairs.add(quote(entry.getKey().toString()) + ":" + render(entry.getValue()));
+            }
+            return pairs.toString();
+        }
+        return value.toString();
+    }
+
+    static String quote(String text) {
+        StringBuilder out = new StringBuilder("\"");
+        for (char c : text.toCharArray()) {
+            if (c == '"' || c == '\\') {
+                out.append('\\').append(c);
+            } else if (c < 0x20) {
+                out.append(String.format("\\u%04x", (int) c));
+            } else {
+                out.append(c);
+            }
+        }
+        return out.append('"').toString();
+    }
+"#;
+
+/// The Java expression for a JSON value, in the types the runtime expects
+fn java_value(value: &Value) -> String {
+    match value {
+        Value::Null => "null".to_string(),
+        Value::Bool(b) => format!("Boolean.valueOf({})", b),
+        Value::Number(n) => match n.as_i64() {
+            Some(n) => format!("Long.valueOf({}L)", n),
+            None => format!("Double.valueOf({:?})", n.as_f64().unwrap_or(f64::NAN)),
+        },
+        Value::String(s) => format!("{:?}", s),
+        Value::Array(items) => {
+            let items: Vec<String> = items.iter().map(java_value).collect();
+            format!("Amoskeag.list(new Object[] {{{}}})", items.join(", "))
+        }
+        Value::Object(map) => {
+            let pairs: Vec<String> = map
+                .iter()
+                .map(|(key, value)| format!("{:?}, {}", key, java_value(value)))
+                .collect();
+            format!("Amoskeag.dict({})", pairs.join(", "))
+        }
+    }
+}
+
+fn has_jdk() -> bool {
+    ["javac", "java"].iter().all(|tool| {
+        Command::new(tool)
+            .arg("-version")
+            .output()
+            .is_ok_and(|output| output.status.success())
+    })
+}
+
+/// A fresh directory for one test's sources and classes
+fn work_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("amoskeag-java-{}-{}", name, std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+/// The generated class for `case`, or `None` when it calls a function the
+/// runtime lacks
+fn transpile_case(case: &conformance::Case, class_name: String) -> Option<String> {
+    let ast = amoskeag_parser::parse(&case.expression).unwrap();
+    let config = TranspileConfig {
+        class_name,
+        ..TranspileConfig::default()
+    };
+    match transpile(&ast, &config) {
+        Ok(class) => Some(class),
+        Err(TranspileError::UnsupportedFunction(_)) => None,
+        Err(e) => panic!("{} does not transpile: {}", case.expression, e),
+    }
+}
+
+/// Run the transpiled classes, named `Case0` onwards, in one JVM with
+/// `data`, and return their results in order
+fn run_classes(name: &str, classes: &[String], data: &Value) -> Vec<Value> {
+    let dir = work_dir(name);
+    let runtime = Path::new(env!("CARGO_MANIFEST_DIR")).join("runtime/src/main/java");
+    let mut sources: Vec<PathBuf> = std::fs::read_dir(runtime.join("io/amoskeag/runtime"))
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .collect();
+
+    let mut main = String::from(
+        "import io.amoskeag.runtime.*;\nimport java.util.*;\n\npublic final class Main {\n",
+    );
+    writeln!(
+        main,
+        "    static final Map<String, Object> DATA = {};\n",
+        java_value(data)
+    )
+    .unwrap();
+    main.push_str("    public static void main(String[] args) {\n");
+    for (i, class) in classes.iter().enumerate() {
+        let path = dir.join(format!("Case{}.java", i));
+        std::fs::write(&path, class).unwrap();
+        sources.push(path);
+        writeln!(
+            main,
+            "        System.out.println(run(() -> Case{}.evaluate(DATA)));",
+            i
+        )
+        .unwrap();
+    }
+    main.push_str("    }\n");
+    main.push_str(HARNESS);
+    main.push_str("}\n");
+    let path = dir.join("Main.java");
+    std::fs::write(&path, main).unwrap();
+    sources.push(path);
+
+    let classes = dir.join("classes");
+    let javac = Command::new("javac")
+        .arg("-d")
+        .arg(&classes)
+        .args(&sources)
+        .output()
+        .unwrap();
+    assert!(
+        javac.status.success(),
+        "javac failed:\n{}",
+        String::from_utf8_lossy(&javac.stderr)
+    );
+    let java = Command::new("java")
+        .arg("-cp")
+        .arg(&classes)
+        .arg("Main")
+        .output()
+        .unwrap();
+    assert!(
+        java.status.success(),
+        "java failed:\n{}",
+        String::from_utf8_lossy(&java.stderr)
+    );
+    let _ = std::fs::remove_dir_all(&dir);
+
+    String::from_utf8(java.stdout)
+        .unwrap()
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap_or_else(|e| panic!("{}: {}", line, e)))
+        .collect()
+}
+
+#[test]
+fn test_runtime_conformance() {
+    if !conformance::tools_found(has_jdk(), "javac and java") {
+        return;
+    }
+
+    let fixture = conformance::load();
+    let mut cases = Vec::new();
+    let mut classes = Vec::new();
+    for case in fixture.cases.iter().filter(|case| case.runs_on("java")) {
+        if let Some(class) = transpile_case(case, format!("Case{}", classes.len())) {
+            cases.push(case);
+            classes.push(class);
+        }
+    }
+    let results = run_classes("conformance", &classes, &fixture.data);
+    conformance::check("Java", &cases, &results);
+}
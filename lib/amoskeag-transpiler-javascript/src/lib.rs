@@ -5,6 +5,10 @@
 //! while being executable in Node.js or browser environments.
 
 use amoskeag_parser::{BinaryOp, Expr, UnaryOp};
+use amoskeag_stdlib_functions::codegen::{self, string_list};
+use amoskeag_stdlib_functions::conversion::{FALSE_STRINGS, TRUE_STRINGS};
+use amoskeag_stdlib_functions::signatures::{self, ArityError};
+use amoskeag_stdlib_functions::string::NUMERIC_PATTERN;
 use std::fmt::Write;
 use thiserror::Error;
 
@@ -16,6 +20,9 @@ pub enum TranspileError {
 
     #[error("Unsupported expression: {0}")]
     UnsupportedExpression(String),
+
+    #[error(transparent)]
+    Arity(#[from] ArityError),
 }
 
 /// Configuration for the transpiler
@@ -69,6 +76,20 @@ pub fn transpile(expr: &Expr, config: &TranspileConfig) -> Result<String, Transp
 
     writeln!(&mut output).map_err(|e| TranspileError::FormatError(e.to_string()))?;
 
+    // Hashing functions need Node's crypto module
+    if calls_any(expr, HASH_FUNCTIONS) {
+        if config.use_es6_modules {
+            writeln!(&mut output, "import {{ createHash }} from 'node:crypto';")
+        } else {
+            writeln!(
+                &mut output,
+                "const {{ createHash }} = require('node:crypto');"
+            )
+        }
+        .map_err(|e| TranspileError::FormatError(e.to_string()))?;
+        writeln!(&mut output).map_err(|e| TranspileError::FormatError(e.to_string()))?;
+    }
+
     // Generate helper functions if configured
     if config.include_runtime_helpers {
         generate_helpers(&mut output, &config.indent)?;
@@ -155,15 +176,133 @@ fn generate_helpers(output: &mut String, indent: &str) -> Result<(), TranspileEr
     writeln!(output, "}}").map_err(|e| TranspileError::FormatError(e.to_string()))?;
     writeln!(output).map_err(|e| TranspileError::FormatError(e.to_string()))?;
 
+    // Helpers for explicit type conversion, following the standard library
+    writeln!(output, "const _NUMERIC = /{}/;", NUMERIC_PATTERN)
+        .map_err(|e| TranspileError::FormatError(e.to_string()))?;
+    writeln!(
+        output,
+        "const _TRUE_STRINGS = {};",
+        string_list(TRUE_STRINGS)
+    )
+    .map_err(|e| TranspileError::FormatError(e.to_string()))?;
+    writeln!(
+        output,
+        "const _FALSE_STRINGS = {};",
+        string_list(FALSE_STRINGS)
+    )
+    .map_err(|e| TranspileError::FormatError(e.to_string()))?;
+    writeln!(output).map_err(|e| TranspileError::FormatError(e.to_string()))?;
+    write_lines(output, indent, CONVERSION_HELPERS)?;
+
     Ok(())
 }
 
+/// JavaScript source for the conversion helpers, one tab per indent level
+///
+/// Mirrors `to_number`, `to_string`, and `to_boolean` in the standard library,
+/// including the null-on-failure behavior and the strict variants. Symbols
+/// are JavaScript symbols, so they are told apart from strings by type.
+const CONVERSION_HELPERS: &[&str] = &[
+    "function _toNumber(val) {",
+    "\tif (typeof val === 'boolean') {",
+    "\t\treturn val ? 1 : 0;",
+    "\t}",
+    "\tif (typeof val === 'number') {",
+    "\t\treturn val;",
+    "\t}",
+    "\tif (typeof val === 'string') {",
+    "\t\tconst text = val.trim();",
+    "\t\tif (_NUMERIC.test(text)) {",
+    "\t\t\treturn Number(text);",
+    "\t\t}",
+    "\t}",
+    "\treturn null;",
+    "}",
+    "",
+    "function _toString(val) {",
+    "\tif (val === null || val === undefined) {",
+    "\t\treturn '';",
+    "\t}",
+    "\tif (typeof val === 'object') {",
+    "\t\treturn null;",
+    "\t}",
+    "\tif (typeof val === 'symbol') {",
+    "\t\treturn val.description;",
+    "\t}",
+    "\treturn String(val);",
+    "}",
+    "",
+    "function _toBoolean(val) {",
+    "\tif (typeof val === 'boolean') {",
+    "\t\treturn val;",
+    "\t}",
+    "\tif (typeof val === 'number') {",
+    "\t\treturn Number.isNaN(val) ? null : val !== 0;",
+    "\t}",
+    "\tif (typeof val === 'string') {",
+    "\t\tconst text = val.trim().toLowerCase();",
+    "\t\tif (_TRUE_STRINGS.includes(text)) {",
+    "\t\t\treturn true;",
+    "\t\t}",
+    "\t\tif (_FALSE_STRINGS.includes(text)) {",
+    "\t\t\treturn false;",
+    "\t\t}",
+    "\t}",
+    "\treturn null;",
+    "}",
+    "",
+    "function _strict(result, val, target) {",
+    "\tif (result === null) {",
+    "\t\tconst shown = typeof val === 'symbol' ? `:${val.description}` : JSON.stringify(val);",
+    "\t\tthrow new Error(`Cannot convert ${shown} to ${target}`);",
+    "\t}",
+    "\treturn result;",
+    "}",
+    "",
+];
+
+/// Functions whose generated code calls `createHash`
+const HASH_FUNCTIONS: &[&str] = &["sha256", "md5"];
+
+/// Whether the expression calls any of the named functions
+fn calls_any(expr: &Expr, names: &[&str]) -> bool {
+    match expr {
+        Expr::FunctionCall { name, args } => {
+            names.contains(&name.as_str()) || args.iter().any(|a| calls_any(a, names))
+        }
+        Expr::Array(elements) => elements.iter().any(|e| calls_any(e, names)),
+        Expr::Dictionary(pairs) => pairs.iter().any(|(_, v)| calls_any(v, names)),
+        Expr::Let { value, body, .. } => calls_any(value, names) || calls_any(body, names),
+        Expr::If {
+            condition,
+            then_branch,
+            else_branch,
+        } => {
+            calls_any(condition, names)
+                || calls_any(then_branch, names)
+                || calls_any(else_branch, names)
+        }
+        Expr::Binary { left, right, .. } | Expr::Pipe { left, right } => {
+            calls_any(left, names) || calls_any(right, names)
+        }
+        Expr::Unary { operand, .. } => calls_any(operand, names),
+        _ => false,
+    }
+}
+
+/// Write source lines, expanding each leading tab to one level of `indent`
+fn write_lines(output: &mut String, indent: &str, lines: &[&str]) -> Result<(), TranspileError> {
+    codegen::write_lines(output, indent, lines)
+        .map_err(|e| TranspileError::FormatError(e.to_string()))
+}
+
 /// Transpile an expression to JavaScript code
 #[allow(clippy::only_used_in_recursion)]
 fn transpile_expr(expr: &Expr, indent: &str, depth: usize) -> Result<String, TranspileError> {
     match expr {
         // Literals
         Expr::Number(n) => Ok(format!("{}", n)),
+        Expr::Integer(n) => Ok(n.to_string()),
         Expr::String(s) => {
             // Escape special characters for JavaScript strings
             let escaped = s
@@ -176,7 +315,8 @@ fn transpile_expr(expr: &Expr, indent: &str, depth: usize) -> Result<String, Tra
         }
         Expr::Boolean(b) => Ok(if *b { "true" } else { "false" }.to_string()),
         Expr::Nil => Ok("null".to_string()),
-        Expr::Symbol(s) => Ok(format!("\":{}\"", s)),
+        // Symbols from Symbol.for are interned, so equal names compare equal
+        Expr::Symbol(s) => Ok(format!("Symbol.for(\"{}\")", s)),
 
         // Array literal
         Expr::Array(elements) => {
@@ -224,6 +364,7 @@ fn transpile_expr(expr: &Expr, indent: &str, depth: usize) -> Result<String, Tra
 
         // Function call
         Expr::FunctionCall { name, args } => {
+            signatures::check_arity(name, args.len())?;
             let arg_codes: Result<Vec<_>, _> = args
                 .iter()
                 .map(|a| transpile_expr(a, indent, depth))
@@ -345,15 +486,41 @@ fn transpile_expr(expr: &Expr, indent: &str, depth: usize) -> Result<String, Tra
                     "(typeof {} === 'object' && {} !== null && !Array.isArray({}))",
                     arg_codes[0], arg_codes[0], arg_codes[0]
                 )),
-                "coalesce" => Ok(format!(
-                    "({} !== null && {} !== undefined ? {} : {})",
-                    arg_codes[0], arg_codes[0], arg_codes[0], arg_codes[1]
-                )),
+                "coalesce" => Ok(format!("({})", arg_codes.join(" ?? "))),
                 "default" => Ok(format!(
                     "({} !== null && {} !== undefined ? {} : {})",
                     arg_codes[0], arg_codes[0], arg_codes[0], arg_codes[1]
                 )),
 
+                // Error recovery
+                "try" => Ok(format!(
+                    "(() => {{ try {{ return {}; }} catch (_error) {{ return {}; }} }})()",
+                    arg_codes[0], arg_codes[1]
+                )),
+
+                // Hashing functions
+                "sha256" | "md5" => Ok(format!(
+                    "createHash('{}').update({}, 'utf8').digest('hex')",
+                    name, arg_codes[0]
+                )),
+
+                // Conversion functions
+                "to_number" => Ok(format!("_toNumber({})", arg_codes[0])),
+                "to_string" => Ok(format!("_toString({})", arg_codes[0])),
+                "to_boolean" => Ok(format!("_toBoolean({})", arg_codes[0])),
+                "to_number_strict" => Ok(format!(
+                    "_strict(_toNumber({}), {}, 'Number')",
+                    arg_codes[0], arg_codes[0]
+                )),
+                "to_string_strict" => Ok(format!(
+                    "_strict(_toString({}), {}, 'String')",
+                    arg_codes[0], arg_codes[0]
+                )),
+                "to_boolean_strict" => Ok(format!(
+                    "_strict(_toBoolean({}), {}, 'Boolean')",
+                    arg_codes[0], arg_codes[0]
+                )),
+
                 _ => Ok(format!("{}({})", name, arg_codes.join(", "))),
             }
         }
@@ -527,7 +694,7 @@ mod tests {
             ..Default::default()
         };
         let js = transpile(&expr, &config).unwrap();
-        assert!(js.contains("\":approve\""));
+        assert!(js.contains("Symbol.for(\"approve\")"));
     }
 
     #[test]
@@ -604,8 +771,8 @@ mod tests {
         };
         let js = transpile(&expr, &config).unwrap();
         assert!(js.contains("_isTruthy"));
-        assert!(js.contains("\":adult\""));
-        assert!(js.contains("\":minor\""));
+        assert!(js.contains("Symbol.for(\"adult\")"));
+        assert!(js.contains("Symbol.for(\"minor\")"));
     }
 
     #[test]
@@ -649,8 +816,8 @@ mod tests {
         assert!(js.contains("_isTruthy"));
         assert!(js.contains("\"driver\""));
         assert!(js.contains("\"age\""));
-        assert!(js.contains("\":continue\""));
-        assert!(js.contains("\":deny\""));
+        assert!(js.contains("Symbol.for(\"continue\")"));
+        assert!(js.contains("Symbol.for(\"deny\")"));
     }
 
     #[test]
@@ -696,4 +863,57 @@ mod tests {
         assert!(!is_valid_js_identifier("my-var"));
         assert!(!is_valid_js_identifier(""));
     }
+
+    #[test]
+    fn test_transpile_conversion_functions() {
+        let js = transpile_source("to_number(age) + 1", None).unwrap();
+        assert!(js.contains("function _toNumber(val) {"));
+        assert!(js.contains("(_toNumber(data[\"age\"]) + 1)"));
+
+        let js = transpile_source("to_string_strict(name)", None).unwrap();
+        assert!(js.contains("_strict(_toString("));
+        assert!(js.contains("'String')"));
+    }
+
+    #[test]
+    fn test_transpile_hash_functions() {
+        let js = transpile_source("sha256(license_number)", None).unwrap();
+        assert!(js.contains("import { createHash } from 'node:crypto';"));
+        assert!(js.contains(
+            "createHash('sha256').update(data[\"license_number\"], 'utf8').digest('hex')"
+        ));
+
+        let config = TranspileConfig {
+            use_es6_modules: false,
+            ..Default::default()
+        };
+        let js = transpile_source("md5(id)", Some(config)).unwrap();
+        assert!(js.contains("const { createHash } = require('node:crypto');"));
+        assert!(js.contains("createHash('md5')"));
+
+        let js = transpile_source("upcase(name)", None).unwrap();
+        assert!(!js.contains("createHash"));
+    }
+
+    #[test]
+    fn test_transpile_variadic_coalesce() {
+        let js = transpile_source("coalesce(a, b, 0)", None).unwrap();
+        assert!(js.contains("(data[\"a\"] ?? data[\"b\"] ?? 0)"));
+    }
+
+    #[test]
+    fn test_transpile_try() {
+        let js = transpile_source("try(to_number_strict(age), 30)", None).unwrap();
+        assert!(js.contains("(() => { try { return _strict(_toNumber(data[\"age\"]), data[\"age\"], 'Number'); } catch (_error) { return 30; } })()"));
+    }
+
+    #[test]
+    fn test_transpile_arity_mismatch() {
+        let err = transpile_source("upcase(name, 2)", None).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Function 'upcase' expects 1 arguments, but 2 were provided"
+        );
+        assert!(transpile_source("round(rate)", None).is_ok());
+    }
 }
@@ -5,6 +5,10 @@
 //! while being executable in a standard Python environment.
 
 use amoskeag_parser::{BinaryOp, Expr, UnaryOp};
+use amoskeag_stdlib_functions::codegen::{self, string_list};
+use amoskeag_stdlib_functions::conversion::{FALSE_STRINGS, TRUE_STRINGS};
+use amoskeag_stdlib_functions::signatures::{self, ArityError};
+use amoskeag_stdlib_functions::string::NUMERIC_PATTERN;
 use std::fmt::Write;
 use thiserror::Error;
 
@@ -16,6 +20,9 @@ pub enum TranspileError {
 
     #[error("Unsupported expression: {0}")]
     UnsupportedExpression(String),
+
+    #[error(transparent)]
+    Arity(#[from] ArityError),
 }
 
 /// Configuration for the transpiler
@@ -56,7 +63,7 @@ pub fn transpile(expr: &Expr, config: &TranspileConfig) -> Result<String, Transp
     if config.include_runtime_imports {
         writeln!(
             &mut output,
-            "# Generated by Amoskeag Python Transpiler\nfrom typing import Any, Dict, List, Optional, Union\nimport math\n"
+            "# Generated by Amoskeag Python Transpiler\nfrom typing import Any, Dict, List, Optional, Union\nimport hashlib\nimport math\nimport re\n"
         )
         .map_err(|e| TranspileError::FormatError(e.to_string()))?;
     }
@@ -158,19 +165,119 @@ fn generate_helpers(output: &mut String, indent: &str) -> Result<(), TranspileEr
         .map_err(|e| TranspileError::FormatError(e.to_string()))?;
     writeln!(output).map_err(|e| TranspileError::FormatError(e.to_string()))?;
 
+    // Helpers for explicit type conversion, following the standard library
+    writeln!(
+        output,
+        "{}_NUMERIC = re.compile(r'{}', re.ASCII)",
+        indent, NUMERIC_PATTERN
+    )
+    .map_err(|e| TranspileError::FormatError(e.to_string()))?;
+    writeln!(
+        output,
+        "{}_TRUE_STRINGS = {}",
+        indent,
+        string_list(TRUE_STRINGS)
+    )
+    .map_err(|e| TranspileError::FormatError(e.to_string()))?;
+    writeln!(
+        output,
+        "{}_FALSE_STRINGS = {}",
+        indent,
+        string_list(FALSE_STRINGS)
+    )
+    .map_err(|e| TranspileError::FormatError(e.to_string()))?;
+    writeln!(output).map_err(|e| TranspileError::FormatError(e.to_string()))?;
+    write_lines(output, indent, CONVERSION_HELPERS)?;
+
+    // Helper for try(expr, fallback)
+    write_lines(output, indent, TRY_HELPER)?;
+
     Ok(())
 }
 
+/// Python source for `try`, which takes both branches as thunks so the
+/// fallback only runs when the expression raises
+const TRY_HELPER: &[&str] = &[
+    "\tdef _try(body: Any, fallback: Any) -> Any:",
+    "\t\ttry:",
+    "\t\t\treturn body()",
+    "\t\texcept Exception:",
+    "\t\t\treturn fallback()",
+    "",
+];
+
+/// Python source for the symbol type and the conversion helpers, one tab per
+/// indent level
+///
+/// Mirrors `to_number`, `to_string`, and `to_boolean` in the standard library,
+/// including the nil-on-failure behavior and the strict variants. Symbols are
+/// `_Symbol` strings, which still compare equal to `":name"`.
+const CONVERSION_HELPERS: &[&str] = &[
+    "\tclass _Symbol(str):",
+    "\t\t\"\"\"A symbol, spelled with its leading colon.\"\"\"",
+    "",
+    "\tdef _to_number(val: Any) -> Any:",
+    "\t\tif isinstance(val, bool):",
+    "\t\t\treturn 1.0 if val else 0.0",
+    "\t\tif isinstance(val, (int, float)):",
+    "\t\t\treturn val",
+    "\t\tif isinstance(val, str):",
+    "\t\t\ttext = val.strip()",
+    "\t\t\tif _NUMERIC.match(text):",
+    "\t\t\t\treturn float(text)",
+    "\t\treturn None",
+    "",
+    "\tdef _to_string(val: Any) -> Any:",
+    "\t\tif val is None:",
+    "\t\t\treturn ''",
+    "\t\tif isinstance(val, bool):",
+    "\t\t\treturn 'true' if val else 'false'",
+    "\t\tif isinstance(val, float) and val.is_integer():",
+    "\t\t\treturn str(int(val))",
+    "\t\tif isinstance(val, (list, dict)):",
+    "\t\t\treturn None",
+    "\t\tif isinstance(val, _Symbol):",
+    "\t\t\treturn val[1:]",
+    "\t\treturn str(val)",
+    "",
+    "\tdef _to_boolean(val: Any) -> Any:",
+    "\t\tif isinstance(val, bool):",
+    "\t\t\treturn val",
+    "\t\tif isinstance(val, (int, float)):",
+    "\t\t\treturn None if val != val else val != 0",
+    "\t\tif isinstance(val, str):",
+    "\t\t\ttext = val.strip().lower()",
+    "\t\t\tif text in _TRUE_STRINGS:",
+    "\t\t\t\treturn True",
+    "\t\t\tif text in _FALSE_STRINGS:",
+    "\t\t\t\treturn False",
+    "\t\treturn None",
+    "",
+    "\tdef _strict(result: Any, val: Any, target: str) -> Any:",
+    "\t\tif result is None:",
+    "\t\t\traise ValueError(f'Cannot convert {val!r} to {target}')",
+    "\t\treturn result",
+    "",
+];
+
+/// Write source lines, expanding each leading tab to one level of `indent`
+fn write_lines(output: &mut String, indent: &str, lines: &[&str]) -> Result<(), TranspileError> {
+    codegen::write_lines(output, indent, lines)
+        .map_err(|e| TranspileError::FormatError(e.to_string()))
+}
+
 /// Transpile an expression to Python code
 #[allow(clippy::only_used_in_recursion)]
 fn transpile_expr(expr: &Expr, indent: &str, depth: usize) -> Result<String, TranspileError> {
     match expr {
         // Literals
-        Expr::Number(n) => Ok(format!("{}", n)),
+        // Whole floats keep their decimal point so Python treats them as float
+        Expr::Number(n) => Ok(format!("{:?}", n)),
+        Expr::Integer(n) => Ok(n.to_string()),
         Expr::String(s) => Ok(format!("{:?}", s)),
         Expr::Boolean(b) => Ok(if *b { "True" } else { "False" }.to_string()),
         Expr::Nil => Ok("None".to_string()),
-        Expr::Symbol(s) => Ok(format!("\":{}\"", s)),
+        Expr::Symbol(s) => Ok(format!("_Symbol(\":{}\")", s)),
 
         // Array literal
         Expr::Array(elements) => {
@@ -213,6 +320,7 @@ fn transpile_expr(expr: &Expr, indent: &str, depth: usize) -> Result<String, Tra
 
         // Function call
         Expr::FunctionCall { name, args } => {
+            signatures::check_arity(name, args.len())?;
             let arg_codes: Result<Vec<_>, _> = args
                 .iter()
                 .map(|a| transpile_expr(a, indent, depth))
@@ -316,15 +424,46 @@ fn transpile_expr(expr: &Expr, indent: &str, depth: usize) -> Result<String, Tra
                 "is_nil" => Ok(format!("({} is None)", arg_codes[0])),
                 "is_array" => Ok(format!("isinstance({}, list)", arg_codes[0])),
                 "is_dictionary" => Ok(format!("isinstance({}, dict)", arg_codes[0])),
-                "coalesce" => Ok(format!(
-                    "({} if {} is not None else {})",
-                    arg_codes[0], arg_codes[0], arg_codes[1]
-                )),
+                "coalesce" => {
+                    let (last, rest) = arg_codes.split_last().expect("coalesce has arguments");
+                    Ok(rest.iter().rev().fold(last.clone(), |fallback, code| {
+                        format!("({} if {} is not None else {})", code, code, fallback)
+                    }))
+                }
                 "default" => Ok(format!(
                     "({} if {} is not None else {})",
                     arg_codes[0], arg_codes[0], arg_codes[1]
                 )),
 
+                // Error recovery
+                "try" => Ok(format!(
+                    "_try(lambda: {}, lambda: {})",
+                    arg_codes[0], arg_codes[1]
+                )),
+
+                // Hashing functions
+                "sha256" | "md5" => Ok(format!(
+                    "hashlib.{}({}.encode('utf-8')).hexdigest()",
+                    name, arg_codes[0]
+                )),
+
+                // Conversion functions
+                "to_number" => Ok(format!("_to_number({})", arg_codes[0])),
+                "to_string" => Ok(format!("_to_string({})", arg_codes[0])),
+                "to_boolean" => Ok(format!("_to_boolean({})", arg_codes[0])),
+                "to_number_strict" => Ok(format!(
+                    "_strict(_to_number({}), {}, 'Number')",
+                    arg_codes[0], arg_codes[0]
+                )),
+                "to_string_strict" => Ok(format!(
+                    "_strict(_to_string({}), {}, 'String')",
+                    arg_codes[0], arg_codes[0]
+                )),
+                "to_boolean_strict" => Ok(format!(
+                    "_strict(_to_boolean({}), {}, 'Boolean')",
+                    arg_codes[0], arg_codes[0]
+                )),
+
                 _ => Ok(format!("{}({})", name, arg_codes.join(", "))),
             }
         }
@@ -424,6 +563,7 @@ pub fn transpile_source(
 mod tests {
     use super::*;
     use amoskeag_parser::parse;
+    use amoskeag_stdlib_functions::signatures::Arity;
 
     #[test]
     fn test_transpile_number() {
@@ -436,6 +576,17 @@ mod tests {
         assert!(python.contains("return 42"));
     }
 
+    #[test]
+    fn test_transpile_whole_float_keeps_decimal_point() {
+        let expr = parse("42.0 + 1").unwrap();
+        let config = TranspileConfig {
+            include_runtime_imports: false,
+            ..Default::default()
+        };
+        let python = transpile(&expr, &config).unwrap();
+        assert!(python.contains("42.0 + 1"));
+    }
+
     #[test]
     fn test_transpile_string() {
         let source = r#""hello""#;
@@ -457,7 +608,7 @@ mod tests {
             ..Default::default()
         };
         let python = transpile(&expr, &config).unwrap();
-        assert!(python.contains("\":approve\""));
+        assert!(python.contains("_Symbol(\":approve\")"));
     }
 
     #[test]
@@ -507,8 +658,8 @@ mod tests {
         };
         let python = transpile(&expr, &config).unwrap();
         assert!(python.contains("_is_truthy"));
-        assert!(python.contains("\":adult\""));
-        assert!(python.contains("\":minor\""));
+        assert!(python.contains("_Symbol(\":adult\")"));
+        assert!(python.contains("_Symbol(\":minor\")"));
     }
 
     #[test]
@@ -552,8 +703,8 @@ mod tests {
         assert!(python.contains("_is_truthy"));
         assert!(python.contains("\"driver\""));
         assert!(python.contains("\"age\""));
-        assert!(python.contains("\":continue\""));
-        assert!(python.contains("\":deny\""));
+        assert!(python.contains("_Symbol(\":continue\")"));
+        assert!(python.contains("_Symbol(\":deny\")"));
     }
 
     #[test]
@@ -563,4 +714,54 @@ mod tests {
         assert!(python.contains("def evaluate"));
         assert!(python.contains("(2 + (3 * 4))"));
     }
+
+    #[test]
+    fn test_transpile_conversion_functions() {
+        let python = transpile_source("to_number(age) + 1", None).unwrap();
+        assert!(python.contains("def _to_number(val: Any) -> Any:"));
+        assert!(python.contains("(_to_number(data.get(\"age\")) + 1)"));
+
+        let python = transpile_source("flag | to_boolean_strict", None).unwrap();
+        assert!(python
+            .contains("_strict(_to_boolean(data.get(\"flag\")), data.get(\"flag\"), 'Boolean')"));
+    }
+
+    #[test]
+    fn test_transpile_hash_functions() {
+        let python = transpile_source("sha256(license_number)", None).unwrap();
+        assert!(python.contains("import hashlib"));
+        assert!(python
+            .contains("hashlib.sha256(data.get(\"license_number\").encode('utf-8')).hexdigest()"));
+
+        let python = transpile_source("id | md5", None).unwrap();
+        assert!(python.contains("hashlib.md5(data.get(\"id\").encode('utf-8')).hexdigest()"));
+    }
+
+    #[test]
+    fn test_transpile_variadic_coalesce() {
+        let python = transpile_source("coalesce(a, b, 0)", None).unwrap();
+        assert!(python.contains(
+            "(data.get(\"a\") if data.get(\"a\") is not None else (data.get(\"b\") if data.get(\"b\") is not None else 0))"
+        ));
+    }
+
+    #[test]
+    fn test_transpile_try() {
+        let python = transpile_source("try(to_number_strict(age), 30)", None).unwrap();
+        assert!(python.contains("def _try(body: Any, fallback: Any) -> Any:"));
+        assert!(python.contains("_try(lambda: _strict(_to_number(data.get(\"age\")), data.get(\"age\"), 'Number'), lambda: 30)"));
+    }
+
+    #[test]
+    fn test_transpile_arity_mismatch() {
+        let expr = parse("substring(name, 1)").unwrap();
+        assert!(matches!(
+            transpile(&expr, &TranspileConfig::default()),
+            Err(TranspileError::Arity(ArityError {
+                expected: Arity::Exact(3),
+                actual: 2,
+                ..
+            }))
+        ));
+    }
 }
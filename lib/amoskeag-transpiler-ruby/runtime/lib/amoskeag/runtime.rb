@@ -0,0 +1,82 @@
+require "bigdecimal"
+require "date"
+require "securerandom"
+
+module Amoskeag
+  # The standard library for Amoskeag programs transpiled to Ruby
+  #
+  # Every function takes and returns plain Ruby values, with the same
+  # semantics and error messages as the interpreter:
+  #
+  # - Integer, Float, and BigDecimal are all numbers. Functions work in
+  #   floats, so they return Float, except the exact functions (+abs+,
+  #   +ceil+, +floor+, +round+, +sum+, +pmt+, +fv+, +pv+, +npv+), which keep
+  #   BigDecimal arguments exact. +gcd+ and +lcm+ of two Integers, and the
+  #   counts from +index_of+, +levenshtein+, and +date_diff+, are Integer.
+  # - Dictionaries are Hashes. Keys may be Strings or Symbols; returned
+  #   Hashes have String keys.
+  # - Symbols are Ruby Symbols, nil is nil, and durations are
+  #   {Amoskeag::Runtime::Duration}.
+  #
+  # Failures raise {Amoskeag::Runtime::Error}.
+  #
+  # @example
+  #   Amoskeag::Runtime.pad_left("7", 3, "0")       # => "007"
+  #   Amoskeag::Runtime.pmt(0.05 / 12, 360, 200000, 0) # => -1073.64...
+  module Runtime
+    VERSION = "0.1.2"
+
+    # A failed standard library call, e.g. a type error or division by zero
+    #
+    # The message matches the interpreter's, e.g.
+    # "Function error: Type error: expected String, got Number".
+    class Error < StandardError; end
+
+    # A span of calendar time, kept as separate month and day counts
+    Duration = Struct.new(:months, :days) do
+      # The ISO 8601 form, e.g. "P1Y2M" or "P10D"
+      def to_s
+        years = (months.abs / 12) * (months.negative? ? -1 : 1)
+        rest = months - (years * 12)
+        text = +"P"
+        text << "#{years}Y" unless years.zero?
+        text << "#{rest}M" unless rest.zero?
+        text << "#{days}D" if !days.zero? || months.zero?
+        text
+      end
+      alias_method :inspect, :to_s
+    end
+
+    @random_state = 0
+    @uuid_state = nil
+    @locale = "en-US"
+    @clock = -> { Time.now.utc.to_date }
+
+    class << self
+      # The locale +currency+ uses when none is given, e.g. "de-DE"
+      attr_accessor :locale
+
+      # A callable returning today's date, used by +date_now+ and +age+
+      attr_accessor :clock
+
+      # Seed +random+ and +random_between+, which start from seed 0
+      def seed=(seed)
+        @random_state = seed & 0xFFFF_FFFF_FFFF_FFFF
+      end
+
+      # Seed +uuid+, which is seeded from system entropy otherwise
+      def uuid_seed=(seed)
+        @uuid_state = seed & 0xFFFF_FFFF_FFFF_FFFF
+      end
+    end
+  end
+end
+
+require_relative "runtime/values"
+require_relative "runtime/string"
+require_relative "runtime/numeric"
+require_relative "runtime/financial"
+require_relative "runtime/collection"
+require_relative "runtime/logic"
+require_relative "runtime/conversion"
+require_relative "runtime/date"
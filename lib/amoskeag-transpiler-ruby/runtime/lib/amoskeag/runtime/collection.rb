@@ -0,0 +1,435 @@
+module Amoskeag
+  module Runtime
+    module_function
+
+    # Strings count bytes, not characters
+    def size(value)
+      case value
+      when String then value.bytesize.to_f
+      when Array, Hash then value.size.to_f
+      else type_error("String, Array, or Dictionary", value)
+      end
+    end
+
+    def first(value)
+      plain_array(value).first
+    end
+
+    def last(value)
+      plain_array(value).last
+    end
+
+    # An array element, or a substring of a string
+    def contains(array, value)
+      case array
+      when Array then floats(array).any? { |item| same?(item, floats(value)) }
+      when String then array.include?(string(value))
+      else type_error("Array or String", array)
+      end
+    end
+
+    # Exact, and a decimal, when any element is a decimal
+    def sum(value)
+      value = exact(value)
+      return sum_decimal(value) if value.is_a?(Array) && value.any? { |item| item.is_a?(BigDecimal) }
+
+      type_error("Array", value) unless value.is_a?(Array)
+      value.inject(0.0) do |total, item|
+        type_error("Array of Numbers", item, got: "Array containing #{type_name(item)}") unless item.is_a?(Float)
+        total + item
+      end
+    end
+
+    # nil for an empty array
+    def avg(value)
+      items = array(value)
+      return nil if items.empty?
+
+      sum(items) / items.size
+    end
+
+    # Without +weights+, +values+ must be an array of Hashes with "value"
+    # and "weight" keys. nil for an empty array.
+    #
+    # @example
+    #   weighted_average([80, 90], [1, 3]) # => 87.5
+    def weighted_average(values, weights = nil)
+      pairs =
+        if weights.nil?
+          weighted_records(values)
+        else
+          values = numbers(values)
+          weights = numbers(weights)
+          if values.size != weights.size
+            argument_error(
+              "weighted_average requires one weight per value, got #{values.size} values and #{weights.size} weights"
+            )
+          end
+          values.zip(weights)
+        end
+      return nil if pairs.empty?
+
+      total_weight = fsum(pairs.map(&:last))
+      argument_error("weighted_average requires weights that do not sum to zero") if total_weight.zero?
+      fsum(pairs.map { |v, w| v * w }) / total_weight
+    end
+
+    # The mean of the two middle values for an even count; nil for an empty array
+    def median(value)
+      items = sorted_numbers(numbers(value))
+      return nil if items.empty?
+
+      mid = items.size / 2
+      items.size.even? ? (items[mid - 1] + items[mid]) / 2.0 : items[mid]
+    end
+
+    # The most frequent item, of any type; ties go to the one seen first
+    def mode(value)
+      counts = []
+      array(value).each do |item|
+        seen = counts.find { |candidate, _| same?(candidate, item) }
+        if seen
+          seen[1] += 1
+        else
+          counts << [item, 1]
+        end
+      end
+      best = nil
+      counts.each { |entry| best = entry if best.nil? || entry[1] > best[1] }
+      best&.first
+    end
+
+    # +p+ from 0 to 100, interpolating between ranks
+    def percentile(value, p)
+      interpolated_rank(value, rank_fraction("percentile", p, 100.0))
+    end
+
+    # +q+ from 0 to 1, interpolating between ranks
+    def quantile(value, q)
+      interpolated_rank(value, rank_fraction("quantile", q, 1.0))
+    end
+
+    # Population variance; nil for an empty array
+    def variance(value)
+      squared_deviations(numbers(value), 0)
+    end
+
+    # Sample variance; nil for fewer than two items
+    def variance_sample(value)
+      squared_deviations(numbers(value), 1)
+    end
+
+    def stddev(value)
+      squared_deviations(numbers(value), 0)&.then { |v| Math.sqrt(v) }
+    end
+
+    def stddev_sample(value)
+      squared_deviations(numbers(value), 1)&.then { |v| Math.sqrt(v) }
+    end
+
+    def covariance(xs, ys)
+      co_deviations(*number_pairs("covariance", xs, ys), 0)
+    end
+
+    def covariance_sample(xs, ys)
+      co_deviations(*number_pairs("covariance_sample", xs, ys), 1)
+    end
+
+    # Pearson's coefficient; nil when it is undefined, as for empty arrays
+    # or an array holding one repeated value
+    def correlation(xs, ys)
+      xs, ys = number_pairs("correlation", xs, ys)
+      cov = co_deviations(xs, ys, 0)
+      var_x = squared_deviations(xs, 0)
+      var_y = squared_deviations(ys, 0)
+      return nil if cov.nil? || var_x.nil? || var_y.nil? || var_x.zero? || var_y.zero?
+
+      r = cov / Math.sqrt(var_x * var_y)
+      r.nan? ? r : r.clamp(-1.0, 1.0)
+    end
+
+    # Numbers or strings, ascending; any other mix is an error. Integers,
+    # floats, and decimals sort together and keep their kind.
+    def sort(value)
+      items = plain_array(value)
+      return sorted_numbers(items) if items.all? { |item| number?(item) }
+      return items.sort if items.all? { |item| item.is_a?(String) }
+
+      invalid_operation("Array must contain all Numbers or all Strings to sort")
+    end
+
+    # Sorted
+    def keys(value)
+      dictionary(value).keys.sort
+    end
+
+    def values(value)
+      plain_dictionary(value).values
+    end
+
+    def reverse(value)
+      plain_array(value).reverse
+    end
+
+    # Zero-based; a negative index counts back from the end
+    def at(array, index)
+      items = plain_array(array)
+      i = saturate(number(index), -(2**63), (2**63) - 1)
+      i += items.size if i.negative?
+      i.negative? ? nil : items[i]
+    end
+
+    # 1 and 1.0 are the same item, and the first one is kept
+    def uniq(value)
+      distinct(plain_array(value))
+    end
+
+    # Items whose +key+ holds a string, number, boolean, or nil are grouped
+    # under that value as a string; other items are left out
+    def group_by(array, key)
+      items = plain_array(array)
+      key = string(key)
+      items.each_with_object({}) do |item, groups|
+        unless item.is_a?(Hash)
+          type_error("Array of Dictionaries", item, got: "Array containing #{type_name(item)}")
+        end
+        next unless key?(item, key)
+
+        group = scalar_key(lookup(item, key))
+        next if group.nil?
+
+        (groups[group] ||= []) << item
+      end
+    end
+
+    # The value of +key+ in each Hash, nil where it is missing
+    def map(array, key)
+      items = plain_array(array)
+      key = string(key)
+      items.map do |item|
+        unless item.is_a?(Hash)
+          type_error("Array of Dictionaries", item, got: "Array containing #{type_name(item)}")
+        end
+        lookup(item, key)
+      end
+    end
+
+    def pick(dict, keys)
+      map = plain_dictionary(dict)
+      string_keys(keys).each_with_object({}) { |key, picked| picked[key] = map[key] if map.key?(key) }
+    end
+
+    def omit(dict, keys)
+      map = plain_dictionary(dict)
+      excluded = string_keys(keys)
+      map.reject { |key, _| excluded.include?(key) }
+    end
+
+    # True even when the value is nil
+    def has_key(dict, key)
+      map = dictionary(dict)
+      map.key?(string(key))
+    end
+
+    # A key holding nil gives nil, not +default+
+    def get(dict, key, default = nil)
+      map = plain_dictionary(dict)
+      key = string(key)
+      map.key?(key) ? map[key] : default
+    end
+
+    # [key, value] pairs, sorted by key
+    def entries(value)
+      plain_dictionary(value).sort_by(&:first)
+    end
+
+    # Later pairs overwrite earlier ones with the same key
+    def from_entries(value)
+      plain_array(value).each_with_object({}) do |item, dict|
+        unless item.is_a?(Array)
+          type_error("Array of [key, value] pairs", item, got: "Array containing #{type_name(item)}")
+        end
+        value_error("Each entry must be a [key, value] pair, got #{item.size} elements") if item.size != 2
+        type_error("String key", item[0]) unless item[0].is_a?(String)
+        dict[item[0]] = item[1]
+      end
+    end
+
+    # Values become string keys. When several keys share a value, the key
+    # that sorts last wins.
+    def invert(value)
+      dictionary(value).sort_by(&:first).each_with_object({}) do |(key, item), inverted|
+        new_key = item.is_a?(Symbol) ? item.to_s : scalar_key(item)
+        if new_key.nil?
+          type_error("Dictionary with scalar values", item, got: "Dictionary containing #{type_name(item)}")
+        end
+        inverted[new_key] = key
+      end
+    end
+
+    # Strings index Hashes and numbers index arrays (negative from the
+    # end); a missing key, out-of-range or fractional index, or mismatched
+    # container gives nil
+    #
+    # @example
+    #   dig({ "items" => [{ "sku" => "A1" }] }, ["items", 0, "sku"]) # => "A1"
+    def dig(data, path)
+      steps = plain_array(path)
+      current = data
+      steps.each do |step|
+        case step
+        when String
+          return nil unless current.is_a?(Hash)
+
+          key = current.key?(step) ? step : step.to_sym
+          return nil unless current.key?(key)
+
+          current = current[key]
+        when Integer, Float, BigDecimal
+          return nil unless current.is_a?(Array) && (step % 1).zero?
+
+          i = saturate(step.to_f, -(2**63), (2**63) - 1)
+          i += current.size if i.negative?
+          return nil if i.negative? || i >= current.size
+
+          current = current[i]
+        else
+          type_error("Array of Strings or Numbers", step, got: "Array containing #{type_name(step)}")
+        end
+      end
+      current
+    end
+
+    # Items of either array, first occurrences only, in order
+    def union(left, right)
+      a, b = array_pair(left, right)
+      distinct(a + b)
+    end
+
+    def intersection(left, right)
+      a, b = array_pair(left, right)
+      others = floats(b)
+      distinct(a) { |item| others.any? { |other| same?(other, item) } }
+    end
+
+    def difference(left, right)
+      a, b = array_pair(left, right)
+      others = floats(b)
+      distinct(a) { |item| others.none? { |other| same?(other, item) } }
+    end
+
+    class << self
+      private
+
+      def sum_decimal(items)
+        decimal_result("sum") do
+          items.inject(BigDecimal(0)) do |total, item|
+            unless number?(item) && item.finite?
+              type_error("Array of Numbers", item, got: "Array containing #{type_name(item)}")
+            end
+            total + to_decimal(item)
+          end
+        end
+      end
+
+      # "value" / "weight" pairs from an array of Hashes
+      def weighted_records(records)
+        array(records).map do |item|
+          unless item.is_a?(Hash)
+            type_error("Array of Dictionaries", item, got: "Array containing #{type_name(item)}")
+          end
+          %w[value weight].map do |key|
+            argument_error("weighted_average record is missing '#{key}'") unless item.key?(key)
+            item[key].is_a?(Float) ? item[key] : type_error("Number", item[key])
+          end
+        end
+      end
+
+      # Ascending, keeping equal numbers in their original order
+      def sorted_numbers(items)
+        items.each_with_index.sort { |(a, i), (b, j)| (a <=> b).to_i.nonzero? || (i <=> j) }.map(&:first)
+      end
+
+      # A percentile or quantile scaled to [0, 1]
+      def rank_fraction(name, value, max)
+        n = number(value)
+        unless n >= 0 && n <= max
+          argument_error("#{name} must be between 0 and #{format_float(max)}, got #{format_float(n)}")
+        end
+        n / max
+      end
+
+      def interpolated_rank(value, rank)
+        items = sorted_numbers(numbers(value))
+        return nil if items.empty?
+
+        position = rank * (items.size - 1)
+        lower = count(whole(position, &:floor))
+        upper = count(whole(position, &:ceil))
+        items[lower] + ((items[upper] - items[lower]) * (position - lower))
+      end
+
+      def number_pairs(name, xs, ys)
+        xs = numbers(xs)
+        ys = numbers(ys)
+        argument_error("#{name} requires arrays of equal length, got #{xs.size} and #{ys.size}") if xs.size != ys.size
+        [xs, ys]
+      end
+
+      # Sum of products of paired deviations from the means, over n - ddof
+      def co_deviations(xs, ys, ddof)
+        return nil if xs.size <= ddof
+
+        mean_x = fsum(xs) / xs.size
+        mean_y = fsum(ys) / ys.size
+        fsum(xs.zip(ys).map { |x, y| (x - mean_x) * (y - mean_y) }) / (xs.size - ddof)
+      end
+
+      # Sum of squared deviations from the mean, over n - ddof
+      def squared_deviations(items, ddof)
+        return nil if items.size <= ddof
+
+        mean = fsum(items) / items.size
+        fsum(items.map { |n| (n - mean) * (n - mean) }) / (items.size - ddof)
+      end
+
+      # A string, number, boolean, or nil as a Hash key; nil for other values
+      def scalar_key(value)
+        case value
+        when String then value
+        when Integer then value.to_s
+        when Float then format_float(value)
+        when BigDecimal then format_decimal(value)
+        when true, false then value.to_s
+        when nil then "nil"
+        end
+      end
+
+      def array_pair(left, right)
+        type_error("Array", left) unless left.is_a?(Array)
+        type_error("Array", right) unless right.is_a?(Array)
+        [left, right]
+      end
+
+      # The first occurrence of each item, in order, comparing promoted
+      # values; with a block, only the items whose promoted value it accepts
+      def distinct(items)
+        seen = []
+        items.each_with_object([]) do |item, result|
+          promoted = floats(item)
+          next if seen.any? { |other| same?(other, promoted) }
+          next if block_given? && !yield(promoted)
+
+          seen << promoted
+          result << item
+        end
+      end
+
+      def string_keys(keys)
+        array(keys).each do |key|
+          type_error("Array of Strings", key, got: "Array containing #{type_name(key)}") unless key.is_a?(String)
+        end
+      end
+    end
+  end
+end
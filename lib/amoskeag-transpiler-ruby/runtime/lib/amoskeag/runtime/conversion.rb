@@ -0,0 +1,174 @@
+require "json"
+
+module Amoskeag
+  module Runtime
+    # Deeper documents are rejected by +parse_json+
+    MAX_JSON_DEPTH = 100
+
+    # Strings +to_boolean+ reads as true or false, once trimmed and
+    # lowercased; the transpiler's tests check them against the interpreter's
+    TRUE_STRINGS = ['true', 'yes', 'y', 'on', '1'].freeze
+    FALSE_STRINGS = ['false', 'no', 'n', 'off', '0'].freeze
+
+    module_function
+
+    # Strings must be plain decimals ("25", "-3.5", "1e3") once trimmed;
+    # booleans become 1 or 0. Anything else gives nil.
+    def to_number(value)
+      case value
+      when Integer, Float, BigDecimal then value.to_f
+      when true then 1.0
+      when false then 0.0
+      when String
+        text = strip(value)
+        return nil unless is_numeric_string(text)
+
+        Float(text.sub(/\A([+-]?)\./) { "#{$1}0." }.sub(/\.(?=[eE]|\z)/, ".0"), exception: false)
+      end
+    end
+
+    def to_number_strict(value)
+      to_number(value) || conversion_error(value, "Number")
+    end
+
+    # Symbols lose their colon, durations use their ISO 8601 form, and nil
+    # is the empty string. Arrays, dictionaries, and functions give nil.
+    def to_string(value)
+      case value
+      when String then value
+      when Integer, Float, BigDecimal then format_float(value.to_f)
+      when true, false, Symbol, Duration then value.to_s
+      when nil then ""
+      end
+    end
+
+    def to_string_strict(value)
+      to_string(value) || conversion_error(value, "String")
+    end
+
+    # Numbers are true unless zero. Strings, trimmed and in any case, may be
+    # one of TRUE_STRINGS or FALSE_STRINGS. Anything else gives nil.
+    def to_boolean(value)
+      case value
+      when true, false then value
+      when Integer, Float, BigDecimal
+        n = value.to_f
+        n.nan? ? nil : n != 0
+      when String
+        case strip(value).downcase(:ascii)
+        when *TRUE_STRINGS then true
+        when *FALSE_STRINGS then false
+        end
+      end
+    end
+
+    def to_boolean_strict(value)
+      result = to_boolean(value)
+      result.nil? ? conversion_error(value, "Boolean") : result
+    end
+
+    # Objects become Hashes and null becomes nil. Whole numbers that fit in
+    # 64 bits become Integers.
+    def parse_json(value)
+      document =
+        begin
+          JSON.parse(string(value), max_nesting: false)
+        rescue JSON::ParserError => e
+          value_error("Invalid JSON: #{e.message}")
+        end
+      from_json(document, 0)
+    end
+
+    # Compact JSON. Whole numbers have no fractional part, symbols are
+    # ":name" strings, durations are ISO 8601 strings, and keys are sorted.
+    #
+    # @example
+    #   to_json({ "b" => [1, 2.5], "a" => :ok }) # => "{\"a\":\":ok\",\"b\":[1,2.5]}"
+    def to_json(value)
+      json_text(floats(value))
+    end
+
+    class << self
+      private
+
+      def conversion_error(value, target)
+        if value.is_a?(String)
+          value_error("Cannot convert String #{debug_string(value)} to #{target}")
+        else
+          value_error("Cannot convert #{type_name(value)} to #{target}")
+        end
+      end
+
+      def from_json(value, depth)
+        value_error("JSON nesting too deep (max #{MAX_JSON_DEPTH} levels)") if depth > MAX_JSON_DEPTH
+        case value
+        when Integer
+          value.bit_length < 64 ? value : value.to_f
+        when Float
+          value.finite? ? value : value_error("Invalid JSON: number out of range")
+        when Array
+          value.map { |item| from_json(item, depth + 1) }
+        when Hash
+          value.transform_values { |item| from_json(item, depth + 1) }
+        else
+          value
+        end
+      end
+
+      def json_text(value)
+        case value
+        when nil then "null"
+        when true, false then value.to_s
+        when Float then json_number(value)
+        when String then json_string(value)
+        when Symbol then json_string(":#{value}")
+        when Duration then json_string(value.to_s)
+        when Array then "[#{value.map { |item| json_text(item) }.join(",")}]"
+        when Hash
+          fields = value.keys.sort.map { |key| "#{json_string(key)}:#{json_text(value[key])}" }
+          "{#{fields.join(",")}}"
+        else
+          value_error("Cannot serialize function #{show(value)} to JSON")
+        end
+      end
+
+      # Whole numbers below 2**53 as integers, others in their shortest
+      # form, switching to an exponent outside 1e-5 to 1e16
+      def json_number(n)
+        value_error("Cannot serialize non-finite number #{format_float(n)} to JSON") unless n.finite?
+        return n.to_i.to_s if (n % 1).zero? && n.abs < 9_007_199_254_740_992.0
+
+        digits, point = float_digits(n)
+        body =
+          if point >= digits.length && point <= 16
+            "#{digits}#{"0" * (point - digits.length)}.0"
+          elsif point.positive? && point <= 16
+            "#{digits[0, point]}.#{digits[point..]}"
+          elsif point > -5 && point <= 0
+            "0.#{"0" * -point}#{digits}"
+          elsif digits.length == 1
+            "#{digits}e#{point - 1}"
+          else
+            "#{digits[0]}.#{digits[1..]}e#{point - 1}"
+          end
+        n.negative? ? "-#{body}" : body
+      end
+
+      def json_string(text)
+        escaped = text.gsub(/["\\\x00-\x1f]/) do |char|
+          case char
+          when "\"" then "\\\""
+          when "\\" then "\\\\"
+          when "\b" then "\\b"
+          when "\f" then "\\f"
+          when "\n" then "\\n"
+          when "\r" then "\\r"
+          when "\t" then "\\t"
+          else Kernel.format("\\u%04x", char.ord)
+          end
+        end
+        "\"#{escaped}\""
+      end
+    end
+  end
+end
@@ -0,0 +1,267 @@
+require "set"
+
+module Amoskeag
+  module Runtime
+    # Stands in for the +unit+ of +date_add+, +date_sub+, and +date_diff+
+    # when it is left out, since nil is a value the caller could pass
+    NO_UNIT = Object.new.freeze
+    private_constant :NO_UNIT
+
+    module_function
+
+    # Today in UTC as "YYYY-MM-DD", from +Runtime.clock+
+    def date_now
+      @clock.call.strftime("%Y-%m-%d")
+    end
+
+    # Only "YYYY-MM-DD HH:mm:ss" changes the date, appending a fixed time
+    def date_format(date, format)
+      type_error("string", date) unless date.is_a?(String)
+      type_error("string", format) unless format.is_a?(String)
+      format == "YYYY-MM-DD HH:mm:ss" ? "#{date} 14:30:00" : date
+    end
+
+    # The "YYYY-MM-DD" part of a datetime such as "2025-01-18T14:30:00Z"
+    def date_trunc(datetime)
+      type_error("string", datetime) unless datetime.is_a?(String)
+      part = datetime.partition("T").first.partition(" ").first
+      unless part.bytesize >= 10 && part[4] == "-" && part[7] == "-"
+        value_error("Invalid datetime format: #{datetime}")
+      end
+      part.byteslice(0, 10)
+    end
+
+    # +date+ itself, once its year, month, and day are in range
+    def date_parse(date)
+      type_error("string", date) unless date.is_a?(String)
+      parts = date.split("-", -1)
+      value_error("Invalid date format: #{date}. Expected YYYY-MM-DD") if parts.size != 3
+
+      year = date_field(parts[0], "year", /\A[+-]?\d+\z/, -(2**31), (2**31) - 1)
+      month = date_field(parts[1], "month", /\A\+?\d+\z/, 0, (2**32) - 1)
+      day = date_field(parts[2], "day", /\A\+?\d+\z/, 0, (2**32) - 1)
+      value_error("Month must be between 1 and 12, got: #{month}") unless month >= 1 && month <= 12
+      value_error("Day must be between 1 and 31, got: #{day}") unless day >= 1 && day <= 31
+      value_error("Year must be between 1000 and 9999, got: #{year}") unless year >= 1000 && year <= 9999
+      date
+    end
+
+    # With a +unit+ ("days", "weeks", "months", or "years", or a Symbol),
+    # adds that many; without one, adds a Duration. Months and years clamp
+    # to the end of shorter months.
+    #
+    # @example
+    #   date_add("2024-01-31", 1, "months") # => "2024-02-29"
+    def date_add(date, amount, unit = NO_UNIT)
+      return add_duration(date, amount) if unit.equal?(NO_UNIT)
+
+      start = civil_date(date)
+      amount = whole_amount(amount)
+      case date_unit(unit)
+      when "days" then add_days(start, amount)
+      when "weeks" then add_days(start, amount * 7)
+      when "months" then add_months(start, amount)
+      else add_months(start, amount * 12)
+      end.iso8601
+    end
+
+    def date_sub(date, amount, unit = NO_UNIT)
+      return subtract_duration(date, amount) if unit.equal?(NO_UNIT)
+
+      date_add(date, -whole_amount(amount).to_f, unit)
+    end
+
+    # With a +unit+, complete periods from +start+ to +finish+, negative when
+    # +finish+ is earlier; without one, a Duration of days
+    def date_diff(start, finish, unit = NO_UNIT)
+      start = civil_date(start)
+      finish = civil_date(finish)
+      return Duration.new(0, finish.jd - start.jd) if unit.equal?(NO_UNIT)
+
+      unit = date_unit(unit)
+      earlier, later, sign = start <= finish ? [start, finish, 1] : [finish, start, -1]
+      amount =
+        case unit
+        when "days" then later.jd - earlier.jd
+        when "weeks" then (later.jd - earlier.jd) / 7
+        when "months" then whole_months_until(earlier, later)
+        else whole_months_until(earlier, later) / 12
+        end
+      sign * amount
+    end
+
+    def date_before(a, b)
+      civil_date(a) < civil_date(b)
+    end
+
+    def date_after(a, b)
+      civil_date(a) > civil_date(b)
+    end
+
+    # -1, 0, or 1, ignoring any time portion
+    def date_compare(a, b)
+      (civil_date(a) <=> civil_date(b)).to_f
+    end
+
+    def duration_days(n)
+      Duration.new(0, whole_amount(n))
+    end
+
+    def duration_weeks(n)
+      Duration.new(0, whole_amount(n) * 7)
+    end
+
+    def duration_months(n)
+      Duration.new(whole_amount(n), 0)
+    end
+
+    def duration_years(n)
+      Duration.new(whole_amount(n) * 12, 0)
+    end
+
+    def is_weekend(date)
+      civil_date(date).cwday >= 6
+    end
+
+    # Weekdays after +start+ up to and including +finish+, skipping
+    # +holidays+; negative when +finish+ is earlier
+    def business_days_between(start, finish, holidays = nil)
+      start = civil_date(start)
+      finish = civil_date(finish)
+      holidays = holiday_days(holidays)
+      earlier, later, sign = start <= finish ? [start.jd, finish.jd, 1] : [finish.jd, start.jd, -1]
+      days = (earlier + 1..later).count { |jd| business_day?(Date.jd(jd, Date::GREGORIAN), holidays) }
+      (sign * days).to_f
+    end
+
+    # Steps back instead for a negative +days+
+    def add_business_days(date, days, holidays = nil)
+      current = civil_date(date)
+      days = whole_amount(days)
+      holidays = holiday_days(holidays)
+      step = days <=> 0
+      remaining = days.abs
+      while remaining.positive?
+        current = add_days(current, step)
+        remaining -= 1 if business_day?(current, holidays)
+      end
+      current.iso8601
+    end
+
+    def beginning_of_month(date)
+      date = civil_date(date)
+      civil(date.year, date.month, 1).iso8601
+    end
+
+    def end_of_month(date)
+      month_end(civil_date(date), 0).iso8601
+    end
+
+    def beginning_of_quarter(date)
+      date = civil_date(date)
+      add_months(civil(date.year, date.month, 1), -((date.month - 1) % 3)).iso8601
+    end
+
+    def end_of_quarter(date)
+      date = civil_date(date)
+      month_end(date, 2 - ((date.month - 1) % 3)).iso8601
+    end
+
+    # Whole years on +as_of+, today by default. Someone born on February 29
+    # turns a year older on March 1 in common years.
+    def age(birthdate, as_of = date_now)
+      birth = civil_date(birthdate)
+      as_of = civil_date(as_of)
+      if as_of < birth
+        argument_error(
+          "age requires as_of on or after the birthdate, got #{as_of.iso8601} before #{birth.iso8601}"
+        )
+      end
+
+      pending = ([as_of.month, as_of.day] <=> [birth.month, birth.day]).negative?
+      (as_of.year - birth.year - (pending ? 1 : 0)).to_f
+    end
+
+    # Weeks start on Monday and belong to the year holding their Thursday,
+    # so 2024-12-30 is in week 1 of 2025
+    def iso_week(date)
+      civil_date(date).cweek.to_f
+    end
+
+    # The year +iso_week+ counts the date's week in
+    def iso_year(date)
+      civil_date(date).cwyear.to_f
+    end
+
+    def quarter(date)
+      (((civil_date(date).month - 1) / 3) + 1).to_f
+    end
+
+    class << self
+      private
+
+      def date_field(text, name, pattern, min, max)
+        value = text.match?(pattern) ? text.to_i : nil
+        value_error("Invalid #{name}: #{text}") if value.nil? || value < min || value > max
+        value
+      end
+
+      # A whole-number amount for date arithmetic, at most 1e9 in size
+      def whole_amount(value)
+        n = number(value)
+        unless n.finite? && (n % 1).zero? && n.abs <= 1e9
+          argument_error("Expected a whole number, got #{format_float(n)}")
+        end
+        n.to_i
+      end
+
+      # "days", "weeks", "months", or "years", from a String or Symbol
+      # naming one of them, singular or plural
+      def date_unit(value)
+        type_error("String", value) unless value.is_a?(String) || value.is_a?(Symbol)
+        case value.to_s
+        when "day", "days" then "days"
+        when "week", "weeks" then "weeks"
+        when "month", "months" then "months"
+        when "year", "years" then "years"
+        else argument_error("Unknown date unit '#{value}'. Expected days, weeks, months, or years")
+        end
+      end
+
+      def as_duration(value)
+        value.is_a?(Duration) ? value : type_error("Duration", value)
+      end
+
+      # Months first, clamping to the end of shorter months, then days
+      def add_duration(date, duration)
+        duration = as_duration(duration)
+        add_days(add_months(civil_date(date), duration.months), duration.days).iso8601
+      end
+
+      def subtract_duration(date, duration)
+        duration = as_duration(duration)
+        add_duration(date, Duration.new(-duration.months, -duration.days))
+      end
+
+      # Complete months from +earlier+ to +later+, where a month is complete
+      # once the same, or clamped, day is reached
+      def whole_months_until(earlier, later)
+        months = ((later.year - earlier.year) * 12) + later.month - earlier.month
+        months -= 1 if months.positive? && add_months(earlier, months) > later
+        months
+      end
+
+      def holiday_days(holidays)
+        case holidays
+        when nil then Set.new
+        when Array then holidays.map { |day| civil_date(day).jd }.to_set
+        else type_error("Array", holidays)
+        end
+      end
+
+      def business_day?(date, holidays)
+        date.cwday < 6 && !holidays.include?(date.jd)
+      end
+    end
+  end
+end
@@ -0,0 +1,955 @@
+module Amoskeag
+  module Runtime
+    # Largest number of periods +amortization_schedule+ builds
+    MAX_SCHEDULE_PERIODS = 10_000.0
+
+    # Coupon dates of a bond around settlement
+    BondSchedule = Struct.new(:settlement, :frequency, :basis, :previous_coupon, :next_coupon, :coupons)
+    private_constant :BondSchedule
+
+    module_function
+
+    # Payments are negative for a loan received. +type+ 1 pays at the start
+    # of each period rather than the end.
+    #
+    # @example
+    #   pmt(0.00375, 360, 250000, 0) # => -1266.71...
+    def pmt(rate, nper, pv, type)
+      decimals = decimal_args(rate, nper, pv, type)
+      return pmt_decimal(*decimals) if decimals
+
+      r = number(rate)
+      n = number(nper)
+      principal = number(pv)
+      t = number(type)
+      argument_error("nper must be greater than 0") if n <= 0
+      argument_error("type must be 0 or 1") if t != 0 && t != 1
+      payment =
+        if r.zero?
+          -principal / n
+        else
+          factor = powf(1.0 + r, n)
+          -principal * (r * factor) / (factor - 1.0)
+        end
+      t == 1 ? payment / (1.0 + r) : payment
+    end
+
+    def fv(rate, nper, pmt, pv)
+      decimals = decimal_args(rate, nper, pmt, pv)
+      return fv_decimal(*decimals) if decimals
+
+      r = number(rate)
+      n = number(nper)
+      payment = number(pmt)
+      principal = number(pv)
+      argument_error("nper must be greater than 0") if n <= 0
+      return -principal - (payment * n) if r.zero?
+
+      factor = powf(1.0 + r, n)
+      (-principal * factor) - (payment * ((factor - 1.0) / r))
+    end
+
+    def pv(rate, nper, pmt)
+      decimals = decimal_args(rate, nper, pmt)
+      return pv_decimal(*decimals) if decimals
+
+      r = number(rate)
+      n = number(nper)
+      payment = number(pmt)
+      argument_error("nper must be greater than 0") if n <= 0
+      return -payment * n if r.zero?
+
+      -payment * ((1.0 - powf(1.0 + r, -n)) / r)
+    end
+
+    def fvschedule(principal, schedule)
+      return fvschedule_decimal(principal, schedule) if decimal?(principal) || decimal?(schedule)
+
+      value = number(principal)
+      numbers(schedule).inject(value) { |total, r| total * (1.0 + r) }
+    end
+
+    # Compound annual growth rate
+    def cagr(begin_value, end_value, years)
+      b = number(begin_value)
+      e = number(end_value)
+      y = number(years)
+      argument_error("years must be greater than 0") if y <= 0
+      argument_error("begin_value cannot be 0") if b.zero?
+      ratio = e / b
+      argument_error("begin_value and end_value must have the same sign") if ratio.negative?
+      powf(ratio, 1.0 / y) - 1.0
+    end
+
+    def compound(value, rate, periods)
+      decimals = decimal_args(value, rate, periods)
+      return compound_decimal(*decimals) if decimals
+
+      v = number(value)
+      r = number(rate)
+      n = number(periods)
+      argument_error("rate must be greater than -1") if r <= -1
+      v * powf(1.0 + r, n)
+    end
+
+    def nper(rate, pmt, pv)
+      r = number(rate)
+      payment = number(pmt)
+      principal = number(pv)
+      if r.zero?
+        argument_error("payment cannot be zero when rate is zero") if payment.zero?
+        return -principal / payment
+      end
+      denominator = payment - (principal * r)
+      if denominator.zero? || payment / denominator <= 0
+        argument_error("invalid payment or present value for given rate")
+      end
+      -natural_log(payment / denominator) / natural_log(1.0 + r)
+    end
+
+    # Found by Newton's method
+    def rate(nper, pmt, pv)
+      n = number(nper)
+      payment = number(pmt)
+      principal = number(pv)
+      argument_error("nper must be greater than 0") if n <= 0
+      return (-payment / principal) - 1.0 if (n - 1.0).abs < 1e-10
+
+      rate = principal.negative? ? 0.2 : 0.05
+      tolerance = 1e-7
+      2000.times do
+        factor = powf(1.0 + rate, n)
+        p_inv = 1.0 / factor
+        f = principal + (payment * ((1.0 - p_inv) / rate))
+        df = payment * (((-rate * n * p_inv / (1.0 + rate)) - 1.0 + p_inv) / (rate * rate))
+        argument_error("rate calculation did not converge") if df.abs < tolerance
+
+        new_rate = fmax(rate - (f / df), 0.0001)
+        return new_rate if (new_rate - rate).abs < tolerance
+
+        rate = new_rate
+        unless rate >= 0.0001 && rate <= 50.0
+          argument_error("rate calculation did not converge to a reasonable value")
+        end
+      end
+      argument_error("rate calculation exceeded maximum iterations")
+    end
+
+    # The first value is discounted one period, so an initial investment
+    # should be added separately
+    #
+    # @example
+    #   npv(0.1, [3000, 4200, 6800]) # => 11307.28...
+    def npv(rate, values)
+      decimals = decimal_args(rate)
+      return npv_decimal(decimals.first, values) if decimals && values.is_a?(Array)
+
+      r = number(rate)
+      type_error("Array", values) unless values.is_a?(Array)
+      argument_error("values array cannot be empty") if values.empty?
+      numbers(values).each_with_index.inject(0.0) do |total, (v, i)|
+        total + (v / powf(1.0 + r, i + 1.0))
+      end
+    end
+
+    # Found by Newton's method
+    def irr(values)
+      type_error("Array", values) unless values.is_a?(Array)
+      argument_error("values array cannot be empty") if values.empty?
+      flows = numbers(values)
+      unless flows.any?(&:positive?) && flows.any?(&:negative?)
+        argument_error("cash flows must contain both positive and negative values")
+      end
+
+      rate = 0.1
+      tolerance = 1e-6
+      100.times do
+        npv = 0.0
+        dnpv = 0.0
+        flows.each_with_index do |cf, i|
+          factor = powf(1.0 + rate, i.to_f)
+          npv += cf / factor
+          dnpv -= i * cf / ((1.0 + rate) * factor)
+        end
+        argument_error("IRR calculation did not converge") if dnpv.abs < tolerance
+
+        new_rate = rate - (npv / dnpv)
+        return new_rate if (new_rate - rate).abs < tolerance
+
+        rate = new_rate
+        unless rate >= -0.99 && rate <= 100.0
+          argument_error("IRR calculation did not converge to a reasonable value")
+        end
+      end
+      argument_error("IRR calculation exceeded maximum iterations")
+    end
+
+    # Modified internal rate of return: outflows are financed at
+    # +finance_rate+ and inflows reinvested at +reinvest_rate+
+    def mirr(values, finance_rate, reinvest_rate)
+      type_error("Array", values) unless values.is_a?(Array)
+      fr = number(finance_rate)
+      rr = number(reinvest_rate)
+      argument_error("values array cannot be empty") if values.empty?
+      flows = numbers(values)
+      n = flows.size.to_f
+      pv_negative = 0.0
+      flows.each_with_index { |cf, i| pv_negative += cf / powf(1.0 + fr, i.to_f) if cf.negative? }
+      fv_positive = 0.0
+      flows.each_with_index { |cf, i| fv_positive += cf * powf(1.0 + rr, n - 1.0 - i) if cf.positive? }
+      if pv_negative.zero? || fv_positive.zero?
+        argument_error("cash flows must contain both positive and negative values")
+      end
+      powf(fv_positive / -pv_negative, 1.0 / (n - 1.0)) - 1.0
+    end
+
+    # Net present value of cash flows on "YYYY-MM-DD" dates. Unlike +npv+,
+    # the first value is not discounted.
+    def xnpv(rate, values, dates)
+      r = number(rate)
+      argument_error("rate must be greater than -1") if r <= -1
+      discounted_sum(r, dated_cash_flows(values, dates))
+    end
+
+    # Newton's method from +guess+, falling back to bisection when it does
+    # not converge
+    def xirr(values, dates, guess = 0.1)
+      guess = number(guess)
+      flows = dated_cash_flows(values, dates)
+      unless flows.any? { |_, v| v.positive? } && flows.any? { |_, v| v.negative? }
+        argument_error("cash flows must contain both positive and negative values")
+      end
+      xirr_newton(flows, guess) || xirr_bisect(flows) || argument_error("XIRR calculation did not converge")
+    end
+
+    # One Hash per period with "period", "payment", "interest", "principal",
+    # and "balance". The final period absorbs rounding drift so the balance
+    # ends at exactly 0.
+    def amortization_schedule(rate, nper, pv)
+      decimals = decimal_args(rate, nper, pv)
+      return amortization_schedule_decimal(*decimals) if decimals
+
+      r = number(rate)
+      n = number(nper)
+      principal = number(pv)
+      schedule_periods(n)
+      payment = -pmt(r, n, principal, 0.0)
+      periods = n.to_i
+      balance = principal
+      (1..periods).map do |period|
+        interest = balance * r
+        last = period == periods
+        paid, repaid = last ? [balance + interest, balance] : [payment, payment - interest]
+        balance = last ? 0.0 : balance - repaid
+        {
+          "period" => period.to_f,
+          "payment" => -paid,
+          "interest" => -interest,
+          "principal" => -repaid,
+          "balance" => balance
+        }
+      end
+    end
+
+    # Straight-line depreciation
+    def sln(cost, salvage, life)
+      decimals = decimal_args(cost, salvage, life)
+      c, s, l = decimals || [number(cost), number(salvage), number(life)]
+      argument_error("life must be greater than 0") if l <= 0
+      return decimal_result("sln") { (c - s).div(l, DECIMAL_PRECISION) } if decimals
+
+      (c - s) / l
+    end
+
+    # Double-declining balance depreciation, never below salvage
+    def ddb(cost, salvage, life, period)
+      c = number(cost)
+      s = number(salvage)
+      l = number(life)
+      p = number(period)
+      argument_error("life must be greater than 0") if l <= 0
+      argument_error("period must be between 1 and #{format_float(l)}") if p < 1 || p > l
+      whole_period = saturate(p, -(2**31), (2**31) - 1)
+      argument_error("period must be an integer") if whole_period.to_f != p
+
+      rate = 2.0 / l
+      book_value = c
+      depreciation = 0.0
+      whole_period.times do
+        depreciation = book_value * rate
+        depreciation = book_value - s if book_value - depreciation < s
+        book_value -= depreciation
+      end
+      fmax(depreciation, 0.0)
+    end
+
+    # Fixed-declining balance depreciation, with +month+ months in the first year
+    def db(cost, salvage, life, period, month)
+      c = number(cost)
+      s = number(salvage)
+      l = number(life)
+      p = number(period)
+      m = number(month)
+      argument_error("life must be greater than 0") if l <= 0
+      argument_error("period must be between 1 and #{format_float(l + 1.0)}") if p < 1 || p > l + 1.0
+      argument_error("month must be between 1 and 12") if m < 1 || m > 12
+      whole_period = saturate(p, -(2**31), (2**31) - 1)
+      argument_error("period must be an integer") if whole_period.to_f != p
+      return 0.0 if s >= c
+
+      rate = round_half_away((1.0 - powf(s / c, 1.0 / l)) * 1000.0) / 1000.0
+      total = 0.0
+      if p == 1
+        depreciation = c * rate * m / 12.0
+      else
+        total += c * rate * m / 12.0
+        (2...whole_period).each { total += (c - total) * rate }
+        depreciation =
+          if p < l + 1.0
+            (c - total) * rate
+          else
+            (c - total) * rate * (12.0 - m) / 12.0
+          end
+      end
+      book_value = c - total
+      depreciation = fmax(book_value - s, 0.0) if book_value - depreciation < s
+      depreciation
+    end
+
+    # Sum-of-years' digits depreciation
+    def syd(cost, salvage, life, per)
+      decimals = decimal_args(cost, salvage, life, per)
+      c, s, l, p = decimals || [number(cost), number(salvage), number(life), number(per)]
+      argument_error("life must be greater than 0") if l <= 0
+      if p <= 0 || p > l
+        argument_error("per must be greater than 0 and at most #{decimals ? format_decimal(l) : format_float(l)}")
+      end
+      return decimal_result("syd") { ((c - s) * (l - p + 1) * 2).div(l * (l + 1), DECIMAL_PRECISION) } if decimals
+
+      (c - s) * (l - p + 1.0) * 2.0 / (l * (l + 1.0))
+    end
+
+    # Declining balance depreciation between two, possibly fractional,
+    # periods, switching to straight-line when that is larger unless
+    # +no_switch+ is true
+    def vdb(cost, salvage, life, start_period, end_period, factor = 2, no_switch = false)
+      cost = number(cost)
+      salvage = number(salvage)
+      life = number(life)
+      start = number(start_period)
+      finish = number(end_period)
+      factor = number(factor)
+      type_error("Boolean", no_switch) unless no_switch == true || no_switch == false
+      argument_error("life must be greater than 0") if life <= 0
+      argument_error("life must be at most #{format_float(MAX_SCHEDULE_PERIODS)} periods") if life > MAX_SCHEDULE_PERIODS
+      if start.negative? || finish < start || finish > life
+        argument_error("periods must satisfy 0 <= start_period <= end_period <= #{format_float(life)}")
+      end
+      if cost.negative? || salvage > cost
+        argument_error("cost must be non-negative and at least salvage")
+      end
+      argument_error("factor must be greater than 0") if factor <= 0
+
+      if no_switch
+        first = period_index(whole(start, &:floor)) + 1
+        last = period_index(whole(finish, &:ceil))
+        return (first..last).inject(0.0) do |total, period|
+          term = declining_balance(cost, salvage, life, period.to_f, factor)
+          if period == first
+            term *= fmin(finish, first.to_f) - start
+          elsif period == last
+            term *= finish + 1.0 - last
+          end
+          total + term
+        end
+      end
+
+      switch_life = life
+      if start != whole(start, &:floor) && factor > 1 && start >= life / 2.0
+        part = start - (life / 2.0)
+        start = life / 2.0
+        finish -= part
+        switch_life += 1.0
+      end
+      remaining_cost = cost - switched_total(cost, salvage, life, switch_life, start, factor)
+      switched_total(remaining_cost, salvage, life, life - start, finish - start, factor)
+    end
+
+    # The interest part of one period's payment
+    def ipmt(rate, per, nper, pv, type)
+      r = number(rate)
+      p = number(per)
+      n = number(nper)
+      principal = number(pv)
+      t = number(type)
+      argument_error("per must be between 1 and #{format_float(n)}") if p < 1 || p > n
+      argument_error("nper must be greater than 0") if n <= 0
+      argument_error("type must be 0 or 1") if t != 0 && t != 1
+      return 0.0 if r.zero?
+
+      payment = pmt(r, n, principal, t)
+      interest =
+        if p == 1
+          -(principal * r)
+        else
+          factor = powf(1.0 + r, p - 1.0)
+          balance = (principal * factor) + (payment * ((factor - 1.0) / r))
+          -(balance * r)
+        end
+      t == 1 ? interest / (1.0 + r) : interest
+    end
+
+    # The principal part of one period's payment
+    def ppmt(rate, per, nper, pv, type)
+      r = number(rate)
+      p = number(per)
+      n = number(nper)
+      principal = number(pv)
+      t = number(type)
+      argument_error("per must be between 1 and #{format_float(n)}") if p < 1 || p > n
+      argument_error("type must be 0 or 1") if t != 0 && t != 1
+      pmt(r, n, principal, t) - ipmt(r, p, n, principal, t)
+    end
+
+    # Interest paid from +start_period+ to +end_period+, inclusive
+    def cumipmt(rate, nper, pv, start_period, end_period, type)
+      cumulative(rate, nper, pv, start_period, end_period, type) { |*args| ipmt(*args) }
+    end
+
+    # Principal paid from +start_period+ to +end_period+, inclusive
+    def cumprinc(rate, nper, pv, start_period, end_period, type)
+      cumulative(rate, nper, pv, start_period, end_period, type) { |*args| ppmt(*args) }
+    end
+
+    # Effective annual rate of a nominal rate compounded +npery+ times a year
+    def effect(nominal_rate, npery)
+      r = number(nominal_rate)
+      n = number(npery)
+      argument_error("npery must be at least 1") if n < 1
+      argument_error("nominal_rate must be greater than -1") if r <= -1
+      powf(1.0 + (r / n), n) - 1.0
+    end
+
+    # Nominal annual rate of an effective rate compounded +npery+ times a year
+    def nominal(effect_rate, npery)
+      r = number(effect_rate)
+      n = number(npery)
+      argument_error("npery must be at least 1") if n < 1
+      argument_error("effect_rate must be greater than -1") if r <= -1
+      (powf(1.0 + r, 1.0 / n) - 1.0) * n
+    end
+
+    # Annual percentage rate on the amount received after +fees+:
+    # rate(nper, pmt, pv - fees) * npery
+    #
+    # @example
+    #   apr(36, -304.22, 10000, 300, 12) # => 0.0806...
+    def apr(nper, pmt, pv, fees, npery)
+      periodic, periods_per_year = fee_adjusted_rate(nper, pmt, pv, fees, npery)
+      periodic * periods_per_year
+    end
+
+    # Like +apr+, but compounding the periodic rate over the year
+    def apr_effective(nper, pmt, pv, fees, npery)
+      periodic, periods_per_year = fee_adjusted_rate(nper, pmt, pv, fees, npery)
+      powf(1.0 + periodic, periods_per_year) - 1.0
+    end
+
+    def periodic_to_annual(rate, npery)
+      r, n = rate_and_periods(rate, npery)
+      powf(1.0 + r, n) - 1.0
+    end
+
+    def annual_to_periodic(rate, npery)
+      r, n = rate_and_periods(rate, npery)
+      powf(1.0 + r, 1.0 / n) - 1.0
+    end
+
+    # The rate of the highest tier at or below +value+, or nil below every tier
+    #
+    # @example
+    #   tier_lookup(25000, [[0, 0.1], [10000, 0.2], [40000, 0.4]]) # => 0.2
+    def tier_lookup(value, tiers)
+      exact = decimal?(value) || decimal?(tiers)
+      v = tier_value(value, exact)
+      _, rate = rate_table(tiers, exact).reverse_each.find { |threshold, _| threshold <= v }
+      rate
+    end
+
+    # Each tier's rate applied to the portion of +value+ within it, as with
+    # tax brackets
+    #
+    # @example
+    #   tier_marginal(25000, [[0, 0.1], [10000, 0.2], [40000, 0.4]]) # => 4000.0
+    def tier_marginal(value, tiers)
+      exact = decimal?(value) || decimal?(tiers)
+      v = tier_value(value, exact)
+      table = rate_table(tiers, exact)
+      total = exact ? BigDecimal(0) : 0.0
+      table.each_with_index do |(threshold, rate), i|
+        break if v <= threshold
+
+        ceiling = table[i + 1] ? fmin(table[i + 1][0], v) : v
+        total += (ceiling - threshold) * rate
+        total = checked_decimal(total, "tier_marginal") if exact
+      end
+      total
+    end
+
+    # The share of +amount+ earned between two dates, counting actual days
+    # against a +period_days+ year
+    def prorate(amount, start_date, end_date, period_days = 365)
+      decimals = decimal_args(amount, period_days)
+      amount = number(amount)
+      start = civil_date(start_date)
+      finish = civil_date(end_date)
+      period_days = number(period_days)
+      argument_error("period_days must be greater than 0") unless period_days.positive?
+      if finish < start
+        argument_error(
+          "prorate requires end_date on or after start_date, got #{finish.iso8601} before #{start.iso8601}"
+        )
+      end
+      if decimals
+        a, p = decimals
+        return decimal_result("prorate") { (a * (finish.jd - start.jd)).div(p, DECIMAL_PRECISION) }
+      end
+
+      amount * (finish.jd - start.jd) / period_days
+    end
+
+    # Price per 100 face value of a bond paying periodic interest. +basis+
+    # selects the day count: 0 US 30/360, 1 actual/actual, 2 actual/360,
+    # 3 actual/365, 4 European 30/360.
+    #
+    # @example
+    #   price("2008-02-15", "2017-11-15", 0.0575, 0.065, 100, 2, 0) # => 94.63...
+    def price(settlement, maturity, rate, yld, redemption, frequency, basis = 0)
+      bond = bond_schedule(settlement, maturity, frequency, basis)
+      rate = non_negative("rate", rate)
+      yld = non_negative("yld", yld)
+      redemption = positive("redemption", redemption)
+      bond_price(bond, rate, yld, redemption)
+    end
+
+    # Yield of a bond paying periodic interest, found by bisection on price
+    # when more than one coupon remains
+    def yield(settlement, maturity, rate, pr, redemption, frequency, basis = 0)
+      bond = bond_schedule(settlement, maturity, frequency, basis)
+      rate = non_negative("rate", rate)
+      pr = positive("pr", pr)
+      redemption = positive("redemption", redemption)
+      f = bond.frequency
+      if bond.coupons <= 1
+        e = coupon_days(bond)
+        paid = (pr / 100.0) + (accrued_days(bond) / e * rate / f)
+        received = (redemption / 100.0) + (rate / f)
+        return (received - paid) / paid * f * e / days_to_next_coupon(bond)
+      end
+
+      low = -f + 1e-9
+      high = 1_000.0
+      if bond_price(bond, rate, low, redemption) < pr || bond_price(bond, rate, high, redemption) > pr
+        argument_error("yield calculation did not converge")
+      end
+      200.times do
+        mid = (low + high) / 2.0
+        if bond_price(bond, rate, mid, redemption) > pr
+          low = mid
+        else
+          high = mid
+        end
+        break if high - low < 1e-12
+      end
+      (low + high) / 2.0
+    end
+
+    # Macaulay duration of a bond, in years
+    def duration(settlement, maturity, coupon, yld, frequency, basis = 0)
+      bond = bond_schedule(settlement, maturity, frequency, basis)
+      coupon = non_negative("coupon", coupon)
+      yld = non_negative("yld", yld)
+      macaulay_duration(bond, coupon, yld)
+    end
+
+    # Modified duration of a bond, in years
+    def mduration(settlement, maturity, coupon, yld, frequency, basis = 0)
+      bond = bond_schedule(settlement, maturity, frequency, basis)
+      coupon = non_negative("coupon", coupon)
+      yld = non_negative("yld", yld)
+      macaulay_duration(bond, coupon, yld) / (1.0 + (yld / bond.frequency))
+    end
+
+    class << self
+      private
+
+      def pmt_decimal(r, n, principal, t)
+        argument_error("nper must be greater than 0") unless n.positive?
+        argument_error("type must be 0 or 1") unless t.zero? || t == 1
+        decimal_result("pmt") do
+          growth = 1 + r
+          payment =
+            if r.zero?
+              -principal.div(n, DECIMAL_PRECISION)
+            else
+              factor = decimal_power(growth, n)
+              (-principal * (r * factor)).div(factor - 1, DECIMAL_PRECISION)
+            end
+          t == 1 ? payment.div(growth, DECIMAL_PRECISION) : payment
+        end
+      end
+
+      def fvschedule_decimal(principal, schedule)
+        number(principal)
+        type_error("Array", schedule) unless schedule.is_a?(Array)
+        decimal_result("fvschedule") do
+          schedule.inject(to_decimal(principal)) do |total, r|
+            unless number?(r) && r.finite?
+              type_error("Array of Numbers", r, got: "Array containing #{type_name(r)}")
+            end
+            total.mult(1 + to_decimal(r), DECIMAL_PRECISION)
+          end
+        end
+      end
+
+      def compound_decimal(v, r, n)
+        argument_error("rate must be greater than -1") if r <= -1
+        decimal_result("compound") { v * decimal_power(1 + r, n) }
+      end
+
+      def amortization_schedule_decimal(r, n, principal)
+        schedule_periods(n)
+        payment = -pmt_decimal(r, n, principal, BigDecimal(0))
+        periods = n.to_i
+        balance = principal
+        (1..periods).map do |period|
+          interest = decimal_result("amortization_schedule") { balance * r }
+          last = period == periods
+          paid, repaid = last ? [balance + interest, balance] : [payment, payment - interest]
+          balance = last ? BigDecimal(0) : balance - repaid
+          {
+            "period" => BigDecimal(period),
+            "payment" => -checked_decimal(paid, "amortization_schedule"),
+            "interest" => -interest,
+            "principal" => -checked_decimal(repaid, "amortization_schedule"),
+            "balance" => checked_decimal(balance, "amortization_schedule")
+          }
+        end
+      end
+
+      def schedule_periods(n)
+        return unless n < 1 || (n % 1) != 0 || n > MAX_SCHEDULE_PERIODS
+
+        argument_error("nper must be a whole number between 1 and #{format_float(MAX_SCHEDULE_PERIODS)}")
+      end
+
+      def fv_decimal(r, n, payment, principal)
+        argument_error("nper must be greater than 0") unless n.positive?
+        decimal_result("fv") do
+          next -principal - (payment * n) if r.zero?
+
+          factor = decimal_power(1 + r, n)
+          annuity = (factor - 1).div(r, DECIMAL_PRECISION)
+          (-principal * factor) - (payment * annuity)
+        end
+      end
+
+      def pv_decimal(r, n, payment)
+        argument_error("nper must be greater than 0") unless n.positive?
+        decimal_result("pv") do
+          next -payment * n if r.zero?
+
+          factor = decimal_power(1 + r, -n)
+          -payment * (1 - factor).div(r, DECIMAL_PRECISION)
+        end
+      end
+
+      def npv_decimal(r, values)
+        argument_error("values array cannot be empty") if values.empty?
+        decimal_result("npv") do
+          growth = 1 + r
+          discount = BigDecimal(1)
+          values.inject(BigDecimal(0)) do |total, value|
+            unless number?(value) && value.finite?
+              type_error("Array of Numbers", value, got: "Array containing #{type_name(value)}")
+            end
+            discount = discount.mult(growth, DECIMAL_PRECISION)
+            total + to_decimal(value).div(discount, DECIMAL_PRECISION)
+          end
+        end
+      end
+
+      # Each cash flow paired with its time in years from the first date
+      def dated_cash_flows(values, dates)
+        type_error("Array", values) unless values.is_a?(Array)
+        type_error("Array", dates) unless dates.is_a?(Array)
+        argument_error("values array cannot be empty") if values.empty?
+        if values.size != dates.size
+          argument_error("values and dates must have the same length, got #{values.size} and #{dates.size}")
+        end
+        start = civil_date(dates.first).jd
+        values.zip(dates).map do |value, date|
+          type_error("Array of Numbers", value, got: "Array containing #{type_name(value)}") unless number?(value)
+          days = civil_date(date).jd - start
+          argument_error("dates must not precede the first date") if days.negative?
+          [days / 365.0, value.to_f]
+        end
+      end
+
+      # Sum of cash flows discounted to the first date
+      def discounted_sum(rate, flows)
+        fsum(flows.map { |years, amount| amount / powf(1.0 + rate, years) })
+      end
+
+      def xirr_newton(flows, guess)
+        rate = guess
+        100.times do
+          return nil if rate <= -1 || !rate.finite?
+
+          value = discounted_sum(rate, flows)
+          derivative = fsum(flows.map { |years, amount| -years * amount / powf(1.0 + rate, years + 1.0) })
+          return nil if derivative.zero?
+
+          nxt = rate - (value / derivative)
+          return nxt > -1 ? nxt : nil if (nxt - rate).abs < 1e-10
+
+          rate = nxt
+        end
+        nil
+      end
+
+      def xirr_bisect(flows)
+        low = -0.999_999
+        high = 1_000.0
+        low_value = discounted_sum(low, flows)
+        return nil if signum(low_value) == signum(discounted_sum(high, flows))
+
+        200.times do
+          mid = (low + high) / 2.0
+          mid_value = discounted_sum(mid, flows)
+          return mid if mid_value.zero? || (high - low) / 2.0 < 1e-12
+
+          if signum(mid_value) == signum(low_value)
+            low = mid
+            low_value = mid_value
+          else
+            high = mid
+          end
+        end
+        (low + high) / 2.0
+      end
+
+      # A whole period number as Rust's +as i64+ gives it
+      def period_index(n)
+        saturate(n, -(2**63), (2**63) - 1)
+      end
+
+      # Declining balance depreciation for a single whole period
+      def declining_balance(cost, salvage, life, period, factor)
+        rate = factor / life
+        if rate >= 1
+          rate = 1.0
+          old_value = period == 1 ? cost : 0.0
+        else
+          old_value = cost * powf(1.0 - rate, period - 1.0)
+        end
+        new_value = cost * powf(1.0 - rate, period)
+        fmax(new_value < salvage ? old_value - salvage : old_value - new_value, 0.0)
+      end
+
+      # Total depreciation over +period+ periods, switching to straight-line
+      # over +switch_life+ once that exceeds the declining balance amount
+      def switched_total(cost, salvage, life, switch_life, period, factor)
+        last = period_index(whole(period, &:ceil))
+        remaining = cost - salvage
+        straight_line = 0.0
+        switched = false
+        (1..last).inject(0.0) do |total, i|
+          term =
+            if switched
+              straight_line
+            else
+              declining = declining_balance(cost, salvage, life, i.to_f, factor)
+              straight_line = remaining / (switch_life - (i - 1))
+              if straight_line > declining
+                switched = true
+                straight_line
+              else
+                remaining -= declining
+                declining
+              end
+            end
+          term *= period + 1.0 - last if i == last
+          total + term
+        end
+      end
+
+      def cumulative(rate, nper, pv, start_period, end_period, type)
+        args = [rate, nper, pv, start_period, end_period, type]
+        type_error("all arguments must be Numbers", nil, got: "mixed types") unless args.all? { |arg| number?(arg) }
+        r, n, principal, sp, ep, t = args.map(&:to_f)
+        argument_error("start_period must be between 1 and #{format_float(n)}") if sp < 1 || sp > n
+        if ep < sp || ep > n
+          argument_error("end_period must be between #{format_float(sp)} and #{format_float(n)}")
+        end
+        argument_error("type must be 0 or 1") if t != 0 && t != 1
+        first = saturate(sp, -(2**31), (2**31) - 1)
+        last = saturate(ep, -(2**31), (2**31) - 1)
+        (first..last).inject(0.0) { |total, period| total + yield(r, period.to_f, n, principal, t) }
+      end
+
+      def rate_and_periods(rate, npery)
+        r = number(rate)
+        n = number(npery)
+        argument_error("npery must be at least 1") if n < 1
+        argument_error("rate must be greater than -1") if r <= -1
+        [r, n]
+      end
+
+      # Periodic rate on the amount received after fees, and periods per year
+      def fee_adjusted_rate(nper, pmt, pv, fees, npery)
+        principal = number(pv)
+        fee = number(fees)
+        periods_per_year = number(npery)
+        argument_error("npery must be at least 1") if periods_per_year < 1
+        argument_error("fees must be non-negative and less than pv") if fee.negative? || fee >= principal.abs
+        [rate(nper, pmt, principal - (fee * signum(principal))), periods_per_year]
+      end
+
+      # [threshold, rate] pairs, requiring strictly ascending thresholds
+      # Whether +value+ holds a BigDecimal at any depth
+      def decimal?(value)
+        case value
+        when BigDecimal then true
+        when Array then value.any? { |item| decimal?(item) }
+        when Hash then value.each_value.any? { |item| decimal?(item) }
+        else false
+        end
+      end
+
+      def tier_value(value, exact)
+        exact && number?(value) && value.finite? ? to_decimal(value) : number(value)
+      end
+
+      def rate_table(tiers, exact)
+        type_error("Array", tiers) unless tiers.is_a?(Array)
+        argument_error("tiers array cannot be empty") if tiers.empty?
+        tiers.each_with_object([]) do |row, table|
+          unless row.is_a?(Array)
+            type_error("Array of [threshold, rate] pairs", row, got: "Array containing #{type_name(row)}")
+          end
+          unless row.size == 2 && row.all? { |item| number?(item) }
+            argument_error("each tier must be a [threshold, rate] pair of numbers")
+          end
+          pair = row.map { |item| exact && item.finite? ? to_decimal(item) : item.to_f }
+          argument_error("tier thresholds must be in ascending order") if table.last && pair[0] <= table.last[0]
+          table << pair
+        end
+      end
+
+      def bond_schedule(settlement, maturity, frequency, basis)
+        settlement = civil_date(settlement)
+        maturity = civil_date(maturity)
+        argument_error("settlement must be before maturity") if settlement >= maturity
+        frequency = number(frequency)
+        argument_error("frequency must be 1, 2, or 4") unless [1.0, 2.0, 4.0].include?(frequency)
+        basis = number(basis)
+        argument_error("basis must be between 0 and 4") unless [0.0, 1.0, 2.0, 3.0, 4.0].include?(basis)
+
+        step = 12 / frequency.to_i
+        coupon_date = lambda do |k|
+          month_end?(maturity) ? month_end(maturity, -k * step) : add_months(maturity, -k * step)
+        end
+        coupons = 1
+        previous_coupon = coupon_date.call(1)
+        while previous_coupon > settlement
+          coupons += 1
+          previous_coupon = coupon_date.call(coupons)
+        end
+        BondSchedule.new(settlement, frequency, basis.to_i, previous_coupon, coupon_date.call(coupons - 1), coupons)
+      end
+
+      # Days in the coupon period containing settlement (Excel's COUPDAYS)
+      def coupon_days(bond)
+        case bond.basis
+        when 1 then (bond.next_coupon.jd - bond.previous_coupon.jd).to_f
+        when 3 then 365.0 / bond.frequency
+        else 360.0 / bond.frequency
+        end
+      end
+
+      # Days from the previous coupon to settlement (Excel's COUPDAYBS)
+      def accrued_days(bond)
+        case bond.basis
+        when 0 then days_30_360(bond.previous_coupon, bond.settlement, false)
+        when 4 then days_30_360(bond.previous_coupon, bond.settlement, true)
+        else (bond.settlement.jd - bond.previous_coupon.jd).to_f
+        end
+      end
+
+      # Days from settlement to the next coupon (Excel's COUPDAYSNC)
+      def days_to_next_coupon(bond)
+        case bond.basis
+        when 0, 4 then coupon_days(bond) - accrued_days(bond)
+        else (bond.next_coupon.jd - bond.settlement.jd).to_f
+        end
+      end
+
+      def bond_price(bond, rate, yld, redemption)
+        f = bond.frequency
+        e = coupon_days(bond)
+        dsc_e = days_to_next_coupon(bond) / e
+        coupon = 100.0 * rate / f
+        discount = 1.0 + (yld / f)
+        total = redemption / powf(discount, bond.coupons - 1.0 + dsc_e)
+        total -= coupon * accrued_days(bond) / e
+        bond.coupons.times { |k| total += coupon / powf(discount, k + dsc_e) }
+        total
+      end
+
+      def macaulay_duration(bond, coupon, yld)
+        f = bond.frequency
+        offset = (days_to_next_coupon(bond) / coupon_days(bond)) - 1.0
+        cash_flow = 100.0 * coupon / f
+        discount = 1.0 + (yld / f)
+        weighted = 0.0
+        present_value = 0.0
+        (1..bond.coupons).each do |k|
+          t = k + offset
+          amount = k == bond.coupons ? cash_flow + 100.0 : cash_flow
+          weighted += t * amount / powf(discount, t)
+          present_value += amount / powf(discount, t)
+        end
+        weighted / present_value / f
+      end
+
+      # 30/360 day count, US (NASD) or European
+      def days_30_360(start, finish, european)
+        d1 = start.day
+        d2 = finish.day
+        if european
+          d1 = [d1, 30].min
+          d2 = [d2, 30].min
+        else
+          feb_end = ->(date) { date.month == 2 && month_end?(date) }
+          d2 = 30 if feb_end.call(start) && feb_end.call(finish)
+          d1 = 30 if feb_end.call(start) || d1 == 31
+          d2 = 30 if d2 == 31 && d1 >= 30
+        end
+        ((finish.year - start.year) * 360.0) + ((finish.month - start.month) * 30.0) + d2 - d1
+      end
+
+      def non_negative(name, value)
+        n = number(value)
+        argument_error("#{name} must not be negative") unless n >= 0
+        n
+      end
+
+      def positive(name, value)
+        n = number(value)
+        argument_error("#{name} must be greater than 0") unless n.positive?
+        n
+      end
+    end
+  end
+end
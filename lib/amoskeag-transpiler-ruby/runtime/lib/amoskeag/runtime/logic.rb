@@ -0,0 +1,103 @@
+module Amoskeag
+  module Runtime
+    module_function
+
+    # One-based, as in spreadsheets; nil when +index+ is out of range
+    def choose(index, array)
+      i = number(index)
+      items = plain_array(array)
+      return nil unless i >= 1
+
+      items[count(i) - 1]
+    end
+
+    # Only false and nil are false
+    def if_then_else(condition, true_val, false_val)
+      condition.nil? || condition == false ? false_val : true_val
+    end
+
+    def is_nil(value)
+      value.nil?
+    end
+
+    def is_number(value)
+      number?(value)
+    end
+
+    def is_string(value)
+      value.is_a?(String)
+    end
+
+    def is_boolean(value)
+      [true, false].include?(value)
+    end
+
+    def is_array(value)
+      value.is_a?(Array)
+    end
+
+    def is_dictionary(value)
+      value.is_a?(Hash)
+    end
+
+    # +bounds+ is "[]" (the default), "[)", "(]", or "()". Strings compare
+    # byte by byte, which orders "YYYY-MM-DD" dates correctly.
+    #
+    # @example
+    #   between(5, 1, 5, "[)") # => false
+    def between(value, lo, hi, bounds = "[]")
+      lower_inclusive, upper_inclusive = parse_bounds(string(bounds))
+      value = floats(value)
+      above_lo =
+        case ordering(value, floats(lo))
+        when 1 then true
+        when 0 then lower_inclusive
+        else false
+        end
+      below_hi =
+        case ordering(value, floats(hi))
+        when -1 then true
+        when 0 then upper_inclusive
+        else false
+        end
+      above_lo && below_hi
+    end
+
+    # The first value that is not nil
+    def coalesce(*values)
+      values.find { |value| !value.nil? }
+    end
+
+    def default(value, default_val)
+      coalesce(value, default_val)
+    end
+
+    class << self
+      private
+
+      def parse_bounds(bounds)
+        case bounds
+        when "[]" then [true, true]
+        when "[)" then [true, false]
+        when "(]" then [false, true]
+        when "()" then [false, false]
+        else argument_error("bounds must be one of \"[]\", \"[)\", \"(]\", or \"()\", got #{debug_string(bounds)}")
+        end
+      end
+
+      # -1, 0, or 1, or nil when NaN makes numbers unordered
+      def ordering(value, other)
+        case value
+        when Float
+          type_error(type_name(value), other) unless other.is_a?(Float)
+          value <=> other
+        when String
+          type_error(type_name(value), other) unless other.is_a?(String)
+          value <=> other
+        else
+          type_error("Number or String", value)
+        end
+      end
+    end
+  end
+end
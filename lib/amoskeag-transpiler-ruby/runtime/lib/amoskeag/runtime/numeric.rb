@@ -0,0 +1,402 @@
+module Amoskeag
+  module Runtime
+    # Largest integer a float can represent exactly (2^53)
+    MAX_SAFE_INTEGER = 9_007_199_254_740_992.0
+
+    # Supported currencies: code => [symbol, decimal places]
+    CURRENCIES = {
+      "USD" => ["$", 2],
+      "CAD" => ["CA$", 2],
+      "AUD" => ["A$", 2],
+      "MXN" => ["MX$", 2],
+      "EUR" => ["€", 2],
+      "GBP" => ["£", 2],
+      "CHF" => ["CHF", 2],
+      "INR" => ["₹", 2],
+      "JPY" => ["¥", 0],
+      "KRW" => ["₩", 0]
+    }.freeze
+
+    # Supported locales: tag => [thousands separator, decimal separator, symbol after amount]
+    LOCALES = {
+      "en-US" => [",", ".", false],
+      "en-GB" => [",", ".", false],
+      "en-CA" => [",", ".", false],
+      "ja-JP" => [",", ".", false],
+      "de-DE" => [".", ",", true],
+      "fr-FR" => [" ", ",", true],
+      "es-ES" => [".", ",", true]
+    }.freeze
+
+    U64 = 0xFFFF_FFFF_FFFF_FFFF
+
+    module_function
+
+    def abs(value)
+      value = exact(value)
+      return value.abs if value.is_a?(BigDecimal)
+
+      number(value).abs
+    end
+
+    def ceil(value)
+      value = exact(value)
+      return value.ceil(0) if value.is_a?(BigDecimal)
+
+      whole(number(value), &:ceil)
+    end
+
+    def floor(value)
+      value = exact(value)
+      return value.floor(0) if value.is_a?(BigDecimal)
+
+      whole(number(value), &:floor)
+    end
+
+    # Halves round away from zero. Decimals are rounded exactly and stay decimals.
+    def round(value, digits = 0)
+      value = exact(value)
+      if value.is_a?(BigDecimal) && number?(digits)
+        places = number(digits)
+        argument_error("digits must be finite") unless places.finite?
+        return value.round(saturate(places, -(2**31), (2**31) - 1).clamp(-20, 20), BigDecimal::ROUND_HALF_UP)
+      end
+
+      n = number(value)
+      places = number(digits)
+      argument_error("digits must be finite") unless places.finite?
+      places = saturate(places, -(2**31), (2**31) - 1).clamp(-20, 20)
+      multiplier = 10.0**places.abs
+      if places >= 0
+        round_half_away(n * multiplier) / multiplier
+      else
+        round_half_away(n / multiplier) * multiplier
+      end
+    end
+
+    def trunc(value)
+      whole(number(value), &:truncate)
+    end
+
+    def sign(value)
+      n = number(value)
+      return 0.0 if n.zero?
+      return n if n.nan?
+
+      n.positive? ? 1.0 : -1.0
+    end
+
+    def clamp(value, lo, hi)
+      n = number(value)
+      lo = number(lo)
+      hi = number(hi)
+      if lo > hi || lo.nan? || hi.nan?
+        argument_error("clamp requires lo <= hi, got lo #{format_float(lo)} and hi #{format_float(hi)}")
+      end
+      return lo if n < lo
+      return hi if n > hi
+
+      n
+    end
+
+    # Two integers give an integer, anything else a float
+    def gcd(a, b)
+      whole_result("gcd", a, b, euclid(whole_number(a), whole_number(b)))
+    end
+
+    # Two integers give an integer, anything else a float
+    def lcm(a, b)
+      x = whole_number(a)
+      y = whole_number(b)
+      return whole_result("lcm", a, b, 0) if x.zero? || y.zero?
+
+      whole_result("lcm", a, b, x / euclid(x, y) * y)
+    end
+
+    # A number in [0, 1), from the seed set with +Runtime.seed=+
+    def random
+      @random_state, bits = split_mix(@random_state)
+      (bits >> 11).to_f / (2**53)
+    end
+
+    # A whole number in [lo, hi] when both bounds are whole, otherwise a
+    # fraction in [lo, hi)
+    def random_between(lo, hi)
+      lo = number(lo)
+      hi = number(hi)
+      if !lo.finite? || !hi.finite? || lo > hi
+        argument_error(
+          "random_between requires finite bounds with lo <= hi, got #{format_float(lo)} and #{format_float(hi)}"
+        )
+      end
+      sample = random
+      if (lo % 1).zero? && (hi % 1).zero?
+        [lo + (sample * (hi - lo + 1.0)).floor, hi].min.to_f
+      else
+        lo + (sample * (hi - lo))
+      end
+    end
+
+    # A version 4 UUID, e.g. "0c9f1b1e-3d2a-4b8e-9f51-6a7c2d4e8b10"
+    def uuid
+      @uuid_state ||= SecureRandom.random_number(U64 + 1)
+      @uuid_state, high = split_mix(@uuid_state)
+      @uuid_state, low = split_mix(@uuid_state)
+      high = (high & ~0xF000 & U64) | 0x4000
+      low = (low & ~(0b11 << 62) & U64) | (0b10 << 62)
+      Kernel.format(
+        "%08x-%04x-%04x-%04x-%012x",
+        high >> 32, (high >> 16) & 0xFFFF, high & 0xFFFF, low >> 48, low & 0xFFFF_FFFF_FFFF
+      )
+    end
+
+    # A value equal to a threshold falls in the band above it
+    #
+    # @example
+    #   bucket(30, [18, 25, 40, 65], [:youth, :young, :adult, :senior, :elder]) # => :adult
+    def bucket(value, thresholds, labels)
+      v = number(value)
+      type_error("Array", thresholds) unless thresholds.is_a?(Array)
+      type_error("Array", labels) unless labels.is_a?(Array)
+      if labels.size != thresholds.size + 1
+        argument_error(
+          "bucket needs one more label than thresholds, got #{thresholds.size} thresholds and #{labels.size} labels"
+        )
+      end
+      band = 0
+      previous = -Float::INFINITY
+      thresholds.each do |threshold|
+        unless number?(threshold)
+          type_error("Array of Numbers", threshold, got: "Array containing #{type_name(threshold)}")
+        end
+        t = threshold.to_f
+        argument_error("bucket thresholds must be in ascending order") if t <= previous
+        band += 1 if v >= t
+        previous = t
+      end
+      floats(labels[band])
+    end
+
+    # Linear between points, and held at the first or last +ys+ value
+    # outside the table
+    #
+    # @example
+    #   interpolate(30, [20, 40, 60], [1.5, 1.0, 1.2]) # => 1.25
+    def interpolate(x, xs, ys)
+      x = number(x)
+      xs = numbers(xs)
+      ys = numbers(ys)
+      argument_error("xs array cannot be empty") if xs.empty?
+      argument_error("xs and ys must have the same length, got #{xs.size} and #{ys.size}") if xs.size != ys.size
+      argument_error("xs must be in ascending order") if xs.each_cons(2).any? { |a, b| b <= a }
+      return ys.first if x <= xs.first
+      return ys.last if x >= xs.last
+
+      i = xs.index { |point| point > x }
+      x0, x1, y0, y1 = xs[i - 1], xs[i], ys[i - 1], ys[i]
+      y0 + ((y1 - y0) * (x - x0) / (x1 - x0))
+    end
+
+    def plus(a, b)
+      a = number(a)
+      a + number(b)
+    end
+
+    def minus(a, b)
+      a = number(a)
+      a - number(b)
+    end
+
+    def times(a, b)
+      a = number(a)
+      a * number(b)
+    end
+
+    def divided_by(a, b)
+      x = number(a)
+      y = number(b)
+      invalid_operation("Division by zero") if y.zero?
+      x / y
+    end
+
+    # Only division by zero is replaced with +default+; other operands are
+    # still a type error
+    def safe_divide(a, b, default = nil)
+      x = number(a)
+      y = number(b)
+      y.zero? ? floats(default) : x / y
+    end
+
+    # The remainder has the sign of the dividend: x - trunc(x / y) * y
+    def modulo(a, b)
+      x = number(a)
+      y = number(b)
+      invalid_operation("Modulo by zero") if y.zero?
+      x - (whole(x / y, &:truncate) * y)
+    end
+
+    # When one argument is NaN, the other is returned
+    def max(a, b)
+      x = number(a)
+      y = number(b)
+      return y if x.nan?
+      return x if y.nan?
+
+      x >= y ? x : y
+    end
+
+    def min(a, b)
+      x = number(a)
+      y = number(b)
+      return y if x.nan?
+      return x if y.nan?
+
+      x <= y ? x : y
+    end
+
+    def array_min(value)
+      extreme(value, "array_min") { |n, best| n < best }
+    end
+
+    def array_max(value)
+      extreme(value, "array_max") { |n, best| n > best }
+    end
+
+    def power(base, exponent)
+      b = number(base)
+      e = number(exponent)
+      result = powf(b, e)
+      if result.nan? && !b.nan? && !e.nan?
+        value_error("Cannot raise negative number #{format_float(b)} to fractional power #{format_float(e)}")
+      elsif result.infinite? && b.finite? && e.finite?
+        value_error("Cannot raise zero to negative power #{format_float(e)}") if b.zero?
+        value_error("#{format_float(b)} raised to #{format_float(e)} overflows")
+      end
+      result
+    end
+
+    def pow(base, exponent)
+      power(base, exponent)
+    end
+
+    def exp(value)
+      n = number(value)
+      result = Math.exp(n)
+      value_error("exp(#{format_float(n)}) overflows") if result.infinite? && n.finite?
+      result
+    end
+
+    def sqrt(value)
+      n = number(value)
+      value_error("Cannot take square root of negative number: #{format_float(n)}") if n.negative?
+      Math.sqrt(n)
+    end
+
+    # The base-2 logarithm
+    def log(value)
+      Math.log2(positive_for_log(value))
+    end
+
+    def log10(value)
+      Math.log10(positive_for_log(value))
+    end
+
+    def ln(value)
+      Math.log(positive_for_log(value))
+    end
+
+    # Rounding a tiny negative number to zero does not print "-0"
+    #
+    # @example
+    #   number_format(1234567.891, 2) # => "1,234,567.89"
+    def number_format(value, decimals = 0, thousands_sep = ",", decimal_sep = ".")
+      n = number(value)
+      value_error("Cannot format non-finite number: #{format_float(n)}") unless n.finite?
+      places = number(decimals)
+      argument_error("decimals must be finite") unless places.finite?
+      places = saturate(places, -(2**31), (2**31) - 1).clamp(0, 20)
+      thousands_sep = string(thousands_sep)
+      decimal_sep = string(decimal_sep)
+
+      formatted = Kernel.format("%.*f", places, n.abs)
+      integer, fraction = formatted.split(".", 2)
+      result = +""
+      result << "-" if n.negative? && formatted.match?(/[1-9]/)
+      integer.each_char.with_index do |digit, i|
+        result << thousands_sep if i.positive? && ((integer.length - i) % 3).zero?
+        result << digit
+      end
+      result << decimal_sep << fraction if fraction
+      result
+    end
+
+    # The currency decides the symbol and decimal places; the locale decides
+    # the separators and where the symbol goes
+    #
+    # @example
+    #   currency(1234.5, "EUR", "de-DE") # => "1.234,50 €"
+    def currency(amount, code = "USD", locale = Runtime.locale)
+      code = string(code)
+      locale = string(locale)
+      _, (symbol, decimals) = CURRENCIES.find { |known, _| known.casecmp?(code) }
+      argument_error("Unsupported currency code: #{code}") if symbol.nil?
+      _, (thousands_sep, decimal_sep, symbol_after) = LOCALES.find { |tag, _| tag.casecmp?(locale) }
+      argument_error("Unsupported locale: #{locale}") if thousands_sep.nil?
+
+      formatted = number_format(amount, decimals, thousands_sep, decimal_sep)
+      sign = formatted.start_with?("-") ? "-" : ""
+      digits = formatted.delete_prefix("-")
+      symbol_after ? "#{sign}#{digits} #{symbol}" : "#{sign}#{symbol}#{digits}"
+    end
+
+    class << self
+      private
+
+      # Advance a SplitMix64 state, returning the new state and 64 random bits
+      def split_mix(state)
+        state = (state + 0x9E37_79B9_7F4A_7C15) & U64
+        z = state
+        z = ((z ^ (z >> 30)) * 0xBF58_476D_1CE4_E5B9) & U64
+        z = ((z ^ (z >> 27)) * 0x94D0_49BB_1331_11EB) & U64
+        [state, z ^ (z >> 31)]
+      end
+
+      # The magnitude of a whole number no larger than 2^53
+      def whole_number(value)
+        return value.abs if value.is_a?(Integer)
+
+        n = number(value)
+        argument_error("expected a whole number, got #{format_float(n)}") unless n.finite? && (n % 1).zero? && n.abs <= MAX_SAFE_INTEGER
+        n.abs.to_i
+      end
+
+      def whole_result(name, a, b, result)
+        integers = a.is_a?(Integer) && b.is_a?(Integer)
+        if result > (integers ? (2**63) - 1 : MAX_SAFE_INTEGER)
+          value_error("#{name}(#{show(a)}, #{show(b)}) is too large to represent exactly")
+        end
+        integers ? result : result.to_f
+      end
+
+      def euclid(a, b)
+        a, b = b, a % b until b.zero?
+        a
+      end
+
+      def extreme(value, name)
+        items = array(value)
+        argument_error("#{name} requires a non-empty array") if items.empty?
+        items.reduce(nil) do |best, item|
+          type_error("Number", item) unless item.is_a?(Float)
+          best.nil? || yield(item, best) ? item : best
+        end
+      end
+
+      def positive_for_log(value)
+        n = number(value)
+        value_error("Cannot take logarithm of non-positive number: #{format_float(n)}") if n <= 0
+        n
+      end
+    end
+  end
+end
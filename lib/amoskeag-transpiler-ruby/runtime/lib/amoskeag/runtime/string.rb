@@ -0,0 +1,618 @@
+require "digest"
+
+module Amoskeag
+  module Runtime
+    # Largest string, in bytes, that +repeat+ will build
+    MAX_REPEAT_LENGTH = 1_000_000
+
+    EMAIL = /\A[A-Za-z0-9.!\#$%&'*+\/=?^_`{|}~-]+@[A-Za-z0-9](?:[A-Za-z0-9-]{0,61}[A-Za-z0-9])?(?:\.[A-Za-z0-9](?:[A-Za-z0-9-]{0,61}[A-Za-z0-9])?)+\z/
+    NUMERIC = /\A[+-]?(?:[0-9]+\.?[0-9]*|\.[0-9]+)(?:[eE][+-]?[0-9]+)?\z/
+
+    # Letters +slugify+ spells out, having no decomposition to strip
+    TRANSLITERATIONS = {
+      "ß" => "ss", "ẞ" => "ss",
+      "Ø" => "o", "ø" => "o",
+      "Æ" => "ae", "æ" => "ae",
+      "Œ" => "oe", "œ" => "oe"
+    }.freeze
+
+    # Escapes whose meaning differs between the interpreter's regular
+    # expressions, which are Unicode-aware, and Ruby's
+    PATTERN_ESCAPES = {
+      "d" => "\\p{Nd}",
+      "D" => "\\P{Nd}",
+      "w" => "\\p{Word}",
+      "W" => "\\P{Word}",
+      "s" => "\\p{Space}",
+      "S" => "\\P{Space}"
+    }.freeze
+
+    # Word boundaries, which only exist outside character classes
+    BOUNDARY_ESCAPES = {
+      "b" => "(?:(?<=\\p{Word})(?!\\p{Word})|(?<!\\p{Word})(?=\\p{Word}))",
+      "B" => "(?:(?<=\\p{Word})(?=\\p{Word})|(?<!\\p{Word})(?!\\p{Word}))"
+    }.freeze
+
+    @patterns = {}
+
+    module_function
+
+    def upcase(value)
+      string(value).upcase
+    end
+
+    def downcase(value)
+      string(value).downcase
+    end
+
+    def capitalize(value)
+      capitalize_word(string(value))
+    end
+
+    # "policyHolder Name", "PolicyHolderName", and "policy-holder-name" all
+    # become "policy_holder_name"
+    def snake_case(value)
+      split_words(string(value)).map(&:downcase).join("_")
+    end
+
+    def camel_case(value)
+      split_words(string(value)).each_with_index.map do |word, i|
+        i.zero? ? word.downcase : capitalize_word(word)
+      end.join
+    end
+
+    # Words are separated by whitespace or underscores, which become single
+    # spaces; other punctuation is kept
+    def titlecase(value)
+      string(value).split(/[\p{Space}_]/).reject(&:empty?).map { |word| capitalize_word(word) }.join(" ")
+    end
+
+    def strip(value)
+      string(value).sub(/\A\p{Space}+/, "").sub(/\p{Space}+\z/, "")
+    end
+
+    def trim_start(value)
+      string(value).sub(/\A\p{Space}+/, "")
+    end
+
+    def trim_end(value)
+      string(value).sub(/\p{Space}+\z/, "")
+    end
+
+    def squish(value)
+      string(value).split(/\p{Space}+/).reject(&:empty?).join(" ")
+    end
+
+    # "Café Zoë & Co." becomes "cafe-zoe-co"
+    def slugify(value)
+      slug = +""
+      pending_dash = false
+      string(value).unicode_normalize(:nfkd).each_char do |char|
+        next if char.match?(/\p{M}/)
+
+        letters = TRANSLITERATIONS.fetch(char) { char.match?(/[A-Za-z0-9]/) ? char.downcase : nil }
+        if letters
+          slug << "-" if pending_dash && !slug.empty?
+          pending_dash = false
+          slug << letters
+        else
+          pending_dash = true
+        end
+      end
+      slug
+    end
+
+    # Every field is kept, including empty ones at either end
+    def split(value, separator)
+      s = string(value)
+      sep = string(separator)
+      return ["", *s.chars, ""] if sep.empty?
+      return [""] if s.empty?
+
+      s.split(Regexp.new(Regexp.escape(sep)), -1)
+    end
+
+    def chars(value)
+      string(value).chars
+    end
+
+    # Accepts both "\n" and "\r\n" line endings; a trailing newline does not
+    # produce an empty final line
+    def lines(value)
+      string(value).each_line("\n").map do |line|
+        line.end_with?("\n") ? line.delete_suffix("\n").delete_suffix("\r") : line
+      end
+    end
+
+    def join(array, separator)
+      type_error("Array", array) unless array.is_a?(Array)
+      sep = string(separator)
+      array.each do |item|
+        type_error("Array of Strings", item, got: "Array containing #{type_name(item)}") unless item.is_a?(String)
+      end
+      array.join(sep)
+    end
+
+    def truncate(value, length)
+      s = string(value)
+      max = count(number(length))
+      s.bytesize <= max ? s : s[0, max]
+    end
+
+    # Positions count characters. A negative start counts back from the end;
+    # ranges past the end are clipped.
+    def substring(value, start, length)
+      s = string(value)
+      from = saturate(number(start), -(2**63), (2**63) - 1)
+      take = count(number(length))
+      from = [s.length + from, 0].max if from.negative?
+      return "" if from >= s.length
+
+      s[from, [take, s.length].min]
+    end
+
+    def repeat(value, times)
+      s = string(value)
+      n = number(times)
+      if !n.finite? || n.negative?
+        argument_error("repeat count must be a non-negative number, got #{format_float(n)}")
+      end
+      times = n.to_i
+      if s.bytesize * times > MAX_REPEAT_LENGTH
+        value_error("repeat result would exceed the maximum length of #{MAX_REPEAT_LENGTH} bytes")
+      end
+      s * times
+    end
+
+    # Width is measured in characters and the fill must be a single character
+    def pad_left(value, width, fill)
+      s, padding = pad_args(value, width, fill)
+      padding + s
+    end
+
+    def pad_right(value, width, fill)
+      s, padding = pad_args(value, width, fill)
+      s + padding
+    end
+
+    def replace(value, find, replacement)
+      s = string(value)
+      f = string(find)
+      r = string(replacement)
+      s.gsub(f) { r }
+    end
+
+    def starts_with(value, prefix)
+      string(value).start_with?(string(prefix))
+    end
+
+    def ends_with(value, suffix)
+      string(value).end_with?(string(suffix))
+    end
+
+    # The character position of the first occurrence, or nil
+    def index_of(value, substr)
+      s = string(value)
+      index = s.index(string(substr))
+      index
+    end
+
+    def includes(value, substr)
+      string(value).include?(string(substr))
+    end
+
+    def levenshtein(left, right)
+      edit_distance(string(left), string(right))
+    end
+
+    # One minus the edit distance divided by the longer length
+    def similarity(left, right)
+      a = string(left)
+      b = string(right)
+      longest = [a.length, b.length].max
+      return 1.0 if longest.zero?
+
+      1.0 - (edit_distance(a, b).to_f / longest)
+    end
+
+    # Supports +%s+ (any value), +%d+ (a number, truncated), +%f+ and +%.Nf+
+    # (a number with N decimal places, default 6), and +%%+
+    def format(template, *args)
+      chars = string(template).chars
+      remaining = args.map { |arg| floats(arg) }
+      output = +""
+      i = 0
+      while i < chars.size
+        char = chars[i]
+        i += 1
+        if char != "%"
+          output << char
+          next
+        end
+
+        precision = nil
+        if chars[i] == "."
+          i += 1
+          digits = +""
+          while i < chars.size && chars[i].match?(/\A[0-9]\z/)
+            digits << chars[i]
+            i += 1
+          end
+          if digits.empty? || digits.to_i > 20
+            argument_error("invalid precision '.#{digits}' in format string")
+          end
+          precision = digits.to_i
+        end
+
+        spec = chars[i]
+        i += 1
+        if spec == "%" && precision.nil?
+          output << "%"
+          next
+        end
+
+        case spec
+        when "s", "d", "f"
+          argument_error("not enough arguments for format string") if remaining.empty?
+        when nil
+          argument_error("format string ends with an incomplete specifier")
+        else
+          argument_error("unsupported format specifier '%#{spec}'")
+        end
+        arg = remaining.shift
+
+        if spec == "s" && precision.nil?
+          output << show(arg)
+        elsif spec == "f" || precision.nil?
+          type_error("Number", arg) unless arg.is_a?(Float)
+          output << (spec == "d" ? format_float(whole(arg, &:truncate)) : fixed(arg, precision || 6))
+        else
+          argument_error("precision is only supported with '%f'")
+        end
+      end
+
+      argument_error("too many arguments for format string") unless remaining.empty?
+      output
+    end
+
+    def regex_match(value, pattern)
+      s, re = regex_args(value, pattern)
+      re.match?(s)
+    end
+
+    # The replacement may refer to capture groups as +$1+ or +${name}+
+    def regex_replace(value, pattern, replacement)
+      s, re = regex_args(value, pattern)
+      r = string(replacement)
+      s.gsub(re) { expand_replacement(r, Regexp.last_match) }
+    end
+
+    # The first capture group when the pattern has one, otherwise the whole
+    # match; nil when nothing matches
+    def regex_extract(value, pattern)
+      s, re = regex_args(value, pattern)
+      match = re.match(s)
+      return nil if match.nil?
+
+      match.size > 1 ? match[1] : match[0]
+    end
+
+    # Unlike +regex_match+, the pattern must match the whole string
+    def matches_pattern(value, pattern)
+      s, = regex_args(value, pattern)
+      compile_pattern("^(?:#{pattern})$").match?(s)
+    end
+
+    def is_email(value)
+      value.is_a?(String) && EMAIL.match?(value)
+    end
+
+    def is_numeric_string(value)
+      value.is_a?(String) && NUMERIC.match?(value)
+    end
+
+    # Spaces and hyphens are ignored; any other non-digit, or fewer than two
+    # digits, makes the number invalid
+    def luhn_valid(value)
+      digits = []
+      string(value).each_char do |char|
+        next if [" ", "-"].include?(char)
+        return false unless char.match?(/\A[0-9]\z/)
+
+        digits << char.to_i
+      end
+      return false if digits.size < 2
+
+      checksum = digits.reverse.each_with_index.sum do |digit, i|
+        doubled = digit * 2
+        if i.even?
+          digit
+        else
+          doubled > 9 ? doubled - 9 : doubled
+        end
+      end
+      (checksum % 10).zero?
+    end
+
+    def sha256(value)
+      Digest::SHA256.hexdigest(string(value))
+    end
+
+    # MD5 is not collision resistant; prefer +sha256+ for anything security sensitive
+    def md5(value)
+      Digest::MD5.hexdigest(string(value))
+    end
+
+    def base64_encode(value)
+      [string(value)].pack("m0")
+    end
+
+    def base64_decode(value)
+      s = string(value)
+      begin
+        decoded = s.unpack1("m0")
+      rescue ArgumentError => e
+        value_error("invalid Base64 input: #{e.message}")
+      end
+      decoded.force_encoding(Encoding::UTF_8)
+      value_error("Base64 input does not decode to UTF-8 text") unless decoded.valid_encoding?
+      decoded
+    end
+
+    # Every byte outside letters, digits, and "-._~" is encoded
+    def url_encode(value)
+      string(value).bytes.map do |byte|
+        char = byte.chr
+        char.match?(/[A-Za-z0-9\-._~]/) ? char : Kernel.format("%%%02X", byte)
+      end.join
+    end
+
+    # "+" is left as-is rather than read as a space
+    def url_decode(value)
+      bytes = string(value).bytes
+      decoded = []
+      i = 0
+      while i < bytes.size
+        if bytes[i] == "%".ord
+          hex = bytes[i + 1, 2].pack("C*")
+          value_error("invalid percent escape at position #{i}") unless hex.match?(/\A(?:\h\h|\+\h)\z/)
+          decoded << hex.to_i(16)
+          i += 3
+        else
+          decoded << bytes[i]
+          i += 1
+        end
+      end
+      text = decoded.pack("C*").force_encoding(Encoding::UTF_8)
+      value_error("URL input does not decode to UTF-8 text") unless text.valid_encoding?
+      text
+    end
+
+    class << self
+      private
+
+      def capitalize_word(word)
+        return "" if word.empty?
+
+        word[0].upcase + word[1..].downcase
+      end
+
+      # Break on any non-alphanumeric character, on a lowercase-to-uppercase
+      # transition ("policyHolder"), and before the last capital of an
+      # acronym run ("HTTPServer")
+      def split_words(s)
+        s.split(/[^\p{Alpha}\p{N}]/).flat_map do |chunk|
+          chars = chunk.chars
+          words = []
+          current = +""
+          chars.each_with_index do |char, i|
+            if i.positive? && char.match?(/\p{Upper}/)
+              prev = chars[i - 1]
+              next_is_lower = chars[i + 1]&.match?(/\p{Lower}/)
+              if prev.match?(/[\p{Lower}\p{N}]/) || (prev.match?(/\p{Upper}/) && next_is_lower)
+                words << current
+                current = +""
+              end
+            end
+            current << char
+          end
+          words << current unless current.empty?
+          words
+        end
+      end
+
+      def pad_args(value, width, fill)
+        s = string(value)
+        target = count(number(width))
+        f = string(fill)
+        argument_error("fill must be a single character, got '#{f}'") unless f.length == 1
+        missing = [target - s.length, 0].max
+        if missing.positive? && s.bytesize + (missing * f.bytesize) > MAX_REPEAT_LENGTH
+          value_error("pad result would exceed the maximum length of #{MAX_REPEAT_LENGTH} bytes")
+        end
+        [s, f * missing]
+      end
+
+      # Character-based Levenshtein distance using a single rolling row
+      def edit_distance(a, b)
+        b = b.chars
+        row = (0..b.size).to_a
+        a.each_char.with_index do |ca, i|
+          diagonal = row[0]
+          row[0] = i + 1
+          b.each_with_index do |cb, j|
+            above = row[j + 1]
+            cost = ca == cb ? 0 : 1
+            row[j + 1] = [above + 1, row[j] + 1, diagonal + cost].min
+            diagonal = above
+          end
+        end
+        row[b.size]
+      end
+
+      # A number with +precision+ decimal places, as Rust's "{:.N}" writes it
+      def fixed(n, precision)
+        n.finite? ? Kernel.format("%.*f", precision, n) : format_float(n)
+      end
+
+      def regex_args(value, pattern)
+        s = string(value)
+        [s, compile_pattern(string(pattern))]
+      end
+
+      # A regular expression with the interpreter's syntax, compiled to an
+      # equivalent Ruby Regexp and cached
+      def compile_pattern(pattern)
+        @patterns.clear if @patterns.size >= 256
+        @patterns[pattern] ||= begin
+          Regexp.new(translate_pattern(pattern))
+        rescue RegexpError => e
+          raise Error, "Function error: Invalid pattern '#{pattern}': #{e.message}"
+        end
+      end
+
+      # Rewrite a pattern in the interpreter's syntax for Ruby's engine
+      #
+      # +^+ and +$+ match only at the ends of the text unless the +m+ flag is
+      # set for the enclosing group, +s+ is Ruby's +m+, the shorthand classes
+      # are Unicode-aware, and groups may be named with +(?P<name>...)+.
+      def translate_pattern(pattern)
+        chars = pattern.chars
+        out = +""
+        depth = 0
+        multi_line = false
+        scopes = []
+        i = 0
+        while i < chars.size
+          char = chars[i]
+          if char == "\\" && i + 1 < chars.size
+            escape = chars[i + 1]
+            if depth.zero? && BOUNDARY_ESCAPES.key?(escape)
+              out << BOUNDARY_ESCAPES[escape]
+            elsif PATTERN_ESCAPES.key?(escape)
+              out << PATTERN_ESCAPES[escape]
+            elsif %w[p P].include?(escape) && chars[i + 2] && chars[i + 2] != "{"
+              out << "\\#{escape}{#{chars[i + 2]}}"
+              i += 1
+            elsif escape == "x" && chars[i + 2] == "{"
+              out << "\\u"
+            else
+              out << char << escape
+            end
+            i += 2
+            next
+          end
+
+          if depth.positive?
+            if char == "["
+              depth += 1
+            elsif char == "]"
+              depth -= 1
+            end
+            out << char
+            i += 1
+            next
+          end
+
+          case char
+          when "["
+            depth = 1
+            out << char
+            i += 1
+            if chars[i] == "^"
+              out << "^"
+              i += 1
+            end
+            if chars[i] == "]"
+              out << "]"
+              i += 1
+            end
+            next
+          when "^"
+            out << (multi_line ? "^" : "\\A")
+          when "$"
+            out << (multi_line ? "$" : "\\z")
+          when "("
+            flags = pattern[i..].match(/\A\(\?([a-zA-Z-]*)([:)])/)
+            if pattern[i, 4] == "(?P<"
+              scopes << multi_line
+              out << "(?<"
+              i += 4
+              next
+            elsif flags && !flags[1].empty?
+              on, off = flags[1].split("-", 2)
+              scopes << multi_line if flags[2] == ":"
+              multi_line = true if on.include?("m")
+              multi_line = false if off&.include?("m")
+              on = translate_flags(on)
+              off = translate_flags(off.to_s)
+              group = off.empty? ? on : "#{on}-#{off}"
+              if group.empty?
+                out << "(?:" if flags[2] == ":"
+              else
+                out << "(?#{group}#{flags[2]}"
+              end
+              i += flags[0].length
+              next
+            end
+            scopes << multi_line
+            out << char
+          when ")"
+            multi_line = scopes.pop unless scopes.empty?
+            out << char
+          else
+            out << char
+          end
+          i += 1
+        end
+        out
+      end
+
+      # Inline flags: Ruby spells "dot matches newline" as m, and the line
+      # anchor flag is handled by the translation itself
+      def translate_flags(flags)
+        flags.delete("mRu").tr("s", "m")
+      end
+
+      # The replacement text for one match, following the interpreter's
+      # +$name+, +${name}+, and +$$+ syntax; unknown groups are empty
+      def expand_replacement(replacement, match)
+        out = +""
+        i = 0
+        while i < replacement.length
+          char = replacement[i]
+          if char != "$"
+            out << char
+            i += 1
+            next
+          end
+
+          following = replacement[i + 1..]
+          if following.start_with?("$")
+            out << "$"
+            i += 2
+            next
+          end
+
+          reference = following.match(/\A\{([^}]*)\}/) || following.match(/\A([0-9A-Za-z_]+)/)
+          if reference.nil?
+            out << "$"
+            i += 1
+            next
+          end
+          out << capture(match, reference[1]).to_s
+          i += 1 + reference[0].length
+        end
+        out
+      end
+
+      def capture(match, name)
+        if name.match?(/\A\+?[0-9]+\z/)
+          match[name.to_i]
+        elsif match.names.include?(name)
+          match[name]
+        end
+      end
+    end
+  end
+end
@@ -0,0 +1,369 @@
+module Amoskeag
+  module Runtime
+    # Checks, conversions, and error helpers shared by the standard library
+    #
+    # Internal to the runtime: the functions reach these as private methods.
+    module Values
+      private
+
+      # The type of a standard library argument as error messages name it
+      #
+      # Integers and decimals are promoted before most functions see them,
+      # so both are reported as Number.
+      def type_name(value)
+        case value
+        when nil then "Nil"
+        when true, false then "Boolean"
+        when Integer, Float, BigDecimal then "Number"
+        when String then "String"
+        when Symbol then "Symbol"
+        when Array then "Array"
+        when Hash then "Dictionary"
+        when Duration then "Duration"
+        when Proc, Method then "Function"
+        else value.class.name
+        end
+      end
+
+      def type_error(expected, value, got: type_name(value))
+        raise Error, "Function error: Type error: expected #{expected}, got #{got}"
+      end
+
+      def argument_error(message)
+        raise Error, "Function error: Argument error: #{message}"
+      end
+
+      def invalid_operation(message)
+        raise Error, "Function error: Invalid operation: #{message}"
+      end
+
+      def value_error(message)
+        raise Error, "Function error: Value error: #{message}"
+      end
+
+      def number?(value)
+        value.is_a?(Integer) || value.is_a?(Float) || value.is_a?(BigDecimal)
+      end
+
+      def number(value)
+        number?(value) ? value.to_f : type_error("Number", value)
+      end
+
+      def string(value)
+        value.is_a?(String) ? value : type_error("String", value)
+      end
+
+      def array(value)
+        value.is_a?(Array) ? floats(value) : type_error("Array", value)
+      end
+
+      def dictionary(value)
+        value.is_a?(Hash) ? floats(value) : type_error("Dictionary", value)
+      end
+
+      # Like +array+ and +dictionary+, but leaving the values inside as they
+      # are, for the functions that only select or reorder them
+      def plain_array(value)
+        value.is_a?(Array) ? value : type_error("Array", value)
+      end
+
+      def plain_dictionary(value)
+        value.is_a?(Hash) ? value.transform_keys(&:to_s) : type_error("Dictionary", value)
+      end
+
+      # An array whose elements must all be numbers
+      def numbers(value, expected = "Array of Numbers")
+        array(value).map do |item|
+          item.is_a?(Float) ? item : type_error(expected, item, got: "Array containing #{type_name(item)}")
+        end
+      end
+
+      # A value with every number, at any depth, promoted to a Float
+      #
+      # The interpreter promotes the arguments of numeric functions this
+      # way, so values such a function passes through come back promoted
+      # too. Hash keys become Strings.
+      def floats(value)
+        case value
+        when Integer, BigDecimal then value.to_f
+        when Array then value.map { |item| floats(item) }
+        when Hash then value.each_with_object({}) { |(key, item), out| out[key.to_s] = floats(item) }
+        else value
+        end
+      end
+
+      # Like +floats+, but decimals stay exact, for the exact functions
+      def exact(value)
+        case value
+        when Integer then value.to_f
+        when Array then value.map { |item| exact(item) }
+        else value
+        end
+      end
+
+      # Deep equality on promoted values, where NaN equals nothing
+      def same?(left, right)
+        case left
+        when Float
+          right.is_a?(Float) && left == right
+        when Array
+          right.is_a?(Array) && left.size == right.size &&
+            left.zip(right).all? { |l, r| same?(l, r) }
+        when Hash
+          right.is_a?(Hash) && left.size == right.size &&
+            left.all? { |key, item| right.key?(key) && same?(item, right[key]) }
+        else
+          left.instance_of?(right.class) && left == right
+        end
+      end
+
+      # A value as the interpreter displays it, e.g. in +format+ and +to_string+
+      def show(value)
+        case value
+        when nil then "nil"
+        when Float then format_float(value)
+        when BigDecimal then format_decimal(value)
+        when Array then "[#{value.map { |item| show(item) }.join(", ")}]"
+        when Hash then "{#{value.map { |key, item| "\"#{key}\": #{show(item)}" }.join(", ")}}"
+        when Symbol then ":#{value}"
+        when Proc then "<fn(#{value.parameters.map(&:last).join(", ")})>"
+        else value.to_s
+        end
+      end
+
+      # A float written the way the interpreter writes it: 3 rather than 3.0,
+      # and never in exponent form
+      def format_float(n)
+        return "NaN" if n.nan?
+        return n.positive? ? "inf" : "-inf" if n.infinite?
+        return n.to_s == "-0.0" ? "-0" : "0" if n.zero?
+
+        digits, point = float_digits(n)
+        body =
+          if point <= 0
+            "0.#{"0" * -point}#{digits}"
+          elsif point >= digits.length
+            digits + ("0" * (point - digits.length))
+          else
+            "#{digits[0, point]}.#{digits[point..]}"
+          end
+        n.negative? ? "-#{body}" : body
+      end
+
+      def format_decimal(d)
+        text = d.to_s("F")
+        text.end_with?(".0") ? text[0...-2] : text
+      end
+
+      # The shortest digits that identify a finite, non-zero float, and where
+      # the decimal point goes: 0.15 is ["15", 0] and 1500.0 is ["15", 4]
+      def float_digits(n)
+        mantissa, exponent = n.abs.to_s.split("e")
+        whole, fraction = mantissa.split(".")
+        digits = whole + fraction.to_s
+        point = whole.length + exponent.to_i
+        significant = digits.sub(/\A0+/, "")
+        point -= digits.length - significant.length
+        [significant.sub(/0+\z/, ""), point]
+      end
+
+      # A string quoted and escaped the way Rust's debug format writes it
+      def debug_string(text)
+        escaped = text.each_char.map do |char|
+          case char
+          when "\\" then "\\\\"
+          when "\"" then "\\\""
+          when "\n" then "\\n"
+          when "\r" then "\\r"
+          when "\t" then "\\t"
+          when "\0" then "\\0"
+          when /\p{Cc}/ then "\\u{#{char.ord.to_s(16)}}"
+          else char
+          end
+        end
+        "\"#{escaped.join}\""
+      end
+
+      # A float cast to an integer the way Rust's +as+ does: truncated toward
+      # zero, saturated at the bounds, and 0 for NaN
+      def saturate(n, min, max)
+        return 0 if n.nan?
+        return max if n >= max
+        return min if n <= min
+
+        n.to_i
+      end
+
+      # A count as Rust's +n as usize+ gives it
+      def count(n)
+        saturate(n, 0, (2**64) - 1)
+      end
+
+      # A float rounded by the block, keeping the float type and the sign of zero
+      def whole(n)
+        return n unless n.finite?
+
+        rounded = yield(n).to_f
+        return rounded unless rounded.zero?
+
+        n.negative? || (n.zero? && (1.0 / n).negative?) ? -0.0 : 0.0
+      end
+
+      def round_half_away(n)
+        whole(n, &:round)
+      end
+
+      # +base+ raised to +exponent+ with the IEEE result, NaN, where Ruby
+      # would give a Complex for a negative base
+      def powf(base, exponent)
+        if base.negative? && (exponent.nan? || (exponent.finite? && exponent != exponent.round))
+          return Float::NAN if exponent.nan? || base.finite?
+
+          return exponent.positive? ? Float::INFINITY : 0.0
+        end
+        base**exponent
+      end
+
+      # The larger of two floats, or the other one when either is NaN
+      def fmax(a, b)
+        return b if a.nan?
+        return a if b.nan?
+
+        a >= b ? a : b
+      end
+
+      def fmin(a, b)
+        return b if a.nan?
+        return a if b.nan?
+
+        a <= b ? a : b
+      end
+
+      # Floats added left to right, without the compensation Array#sum
+      # applies, starting from -0.0 so a sum of negative zeros keeps its sign
+      def fsum(items)
+        items.inject(-0.0) { |total, n| total + n }
+      end
+
+      # -1.0 or 1.0 by the sign bit, so -0.0 is negative, and NaN for NaN
+      def signum(n)
+        return n if n.nan?
+
+        n.negative? || (n.zero? && (1.0 / n).negative?) ? -1.0 : 1.0
+      end
+
+      # The natural logarithm, NaN rather than an exception below zero
+      def natural_log(n)
+        n.negative? ? Float::NAN : Math.log(n)
+      end
+
+      # A decimal result cut to the 28 digits exact arithmetic keeps, failing
+      # when it is too large to represent
+      def checked_decimal(value, function)
+        value_error("Decimal overflow in #{function}") if !value.finite? || value.abs > DECIMAL_MAX
+        value.round([28 - value.exponent, 28].min, BigDecimal::ROUND_HALF_EVEN)
+      end
+
+      # The decimal the block works out, checked as +checked_decimal+ does
+      #
+      # Arithmetic with no decimal answer, such as dividing by zero, is also
+      # reported as an overflow.
+      def decimal_result(function)
+        checked_decimal(yield, function)
+      rescue ZeroDivisionError, FloatDomainError, Math::DomainError
+        value_error("Decimal overflow in #{function}")
+      end
+
+      def decimal_power(base, exponent)
+        exponent = exponent.to_i if exponent.frac.zero?
+        base.power(exponent, DECIMAL_PRECISION)
+      end
+
+      # Every value of +args+ as a decimal, when one is a BigDecimal and all
+      # are finite numbers
+      def decimal_args(*args)
+        return nil unless args.any? { |arg| arg.is_a?(BigDecimal) }
+        return nil unless args.all? { |arg| number?(arg) && arg.finite? }
+
+        args.map { |arg| to_decimal(arg) }
+      end
+
+      # A number as a decimal, using the shortest digits of a float
+      def to_decimal(value)
+        case value
+        when BigDecimal then value
+        when Float then BigDecimal(value.to_s)
+        else BigDecimal(value)
+        end
+      end
+
+      # The date of a "YYYY-MM-DD" string, ignoring any time portion
+      def civil_date(value)
+        type_error("string", value) unless value.is_a?(String)
+        year, month, day = (value.split(/[T ]/, 2).first || "").split("-", 3)
+        if day.nil? || year.bytesize != 4 || month.bytesize != 2 || day.bytesize != 2 ||
+           !year.match?(/\A[+-]?\d+\z/) || !month.match?(/\A\+?\d+\z/) || !day.match?(/\A\+?\d+\z/)
+          value_error("Invalid date format: #{value}. Expected YYYY-MM-DD")
+        end
+        civil(year.to_i, month.to_i, day.to_i)
+      end
+
+      # A proleptic Gregorian date, checking the day against the length of the month
+      def civil(year, month, day)
+        value_error("Year must be between 1000 and 9999, got: #{year}") unless year >= 1000 && year <= 9999
+        value_error("Month must be between 1 and 12, got: #{month}") unless month >= 1 && month <= 12
+        if day < 1 || day > days_in_month(year, month)
+          value_error("Day #{day} is out of range for #{year}-#{month.to_s.rjust(2, "0")}")
+        end
+        Date.new(year, month, day, Date::GREGORIAN)
+      end
+
+      def days_in_month(year, month)
+        case month
+        when 2 then Date.gregorian_leap?(year) ? 29 : 28
+        when 4, 6, 9, 11 then 30
+        else 31
+        end
+      end
+
+      def month_end?(date)
+        date.day == days_in_month(date.year, date.month)
+      end
+
+      def add_days(date, days)
+        shifted = Date.jd(date.jd + days, Date::GREGORIAN)
+        civil(shifted.year, shifted.month, shifted.day)
+      end
+
+      # Shift by whole months, clamping the day to the end of shorter months
+      def add_months(date, months)
+        year, month = ((date.year * 12) + date.month - 1 + months).divmod(12)
+        value_error("Date is out of range") unless year.bit_length < 32
+        civil(year, month + 1, [date.day, days_in_month(year, month + 1)].min)
+      end
+
+      # The last day of the month, +offset+ months away
+      def month_end(date, offset)
+        start = add_months(civil(date.year, date.month, 1), offset)
+        civil(start.year, start.month, days_in_month(start.year, start.month))
+      end
+
+      # Look up +key+ in a Hash whose keys may be Strings or Symbols
+      def key?(hash, key)
+        hash.key?(key.to_s) || hash.key?(key.to_sym)
+      end
+
+      # The value of +key+ in a Hash whose keys may be Strings or Symbols
+      def lookup(hash, key)
+        hash.key?(key.to_s) ? hash[key.to_s] : hash[key.to_sym]
+      end
+    end
+
+    DECIMAL_MAX = BigDecimal("79228162514264337593543950335")
+
+    # Significant digits kept while working out a decimal result
+    DECIMAL_PRECISION = 40
+
+    extend Values
+  end
+end
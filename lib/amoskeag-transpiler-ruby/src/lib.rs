@@ -2,10 +2,19 @@
 //!
 //! This crate provides functionality to transpile Amoskeag AST expressions
 //! into equivalent Ruby code.
+//!
+//! Standard library calls go to `Amoskeag::Runtime`, a Ruby module shipped as
+//! the `amoskeag-runtime` gem in `runtime/`. It implements every standard
+//! library function with the interpreter's semantics and error messages, so
+//! generated code needs `require "amoskeag/runtime"` and nothing else.
 
 use amoskeag_parser::{BinaryOp, Expr, UnaryOp};
+use amoskeag_stdlib_functions::signatures::{self, ArityError};
 use thiserror::Error;
 
+/// The Ruby module of the runtime library
+pub const RUNTIME_MODULE: &str = "Amoskeag::Runtime";
+
 /// Transpiler errors
 #[derive(Error, Debug)]
 pub enum TranspileError {
@@ -14,6 +23,9 @@ pub enum TranspileError {
 
     #[error("Invalid pipe expression")]
     InvalidPipe,
+
+    #[error(transparent)]
+    Arity(#[from] ArityError),
 }
 
 /// Transpiler for converting Amoskeag expressions to Ruby code
@@ -33,6 +45,7 @@ impl RubyTranspiler {
     fn transpile_expr(&mut self, expr: &Expr) -> Result<String, TranspileError> {
         match expr {
             Expr::Number(n) => Ok(self.transpile_number(*n)),
+            Expr::Integer(n) => Ok(n.to_string()),
             Expr::String(s) => Ok(self.transpile_string(s)),
             Expr::Boolean(b) => Ok(self.transpile_boolean(*b)),
             Expr::Nil => Ok("nil".to_string()),
@@ -49,17 +62,13 @@ impl RubyTranspiler {
             } => self.transpile_if(condition, then_branch, else_branch),
             Expr::Binary { op, left, right } => self.transpile_binary(*op, left, right),
             Expr::Unary { op, operand } => self.transpile_unary(*op, operand),
-            Expr::Pipe { .. } => Err(TranspileError::InvalidPipe),
+            Expr::Pipe { left, right } => self.transpile_pipe(left, right),
         }
     }
 
     fn transpile_number(&self, n: f64) -> String {
-        // Handle integers cleanly
-        if n.fract() == 0.0 && n.is_finite() {
-            format!("{}", n as i64)
-        } else {
-            format!("{}", n)
-        }
+        // Keep the decimal point on whole floats so Ruby treats them as Float
+        format!("{:?}", n)
     }
 
     fn transpile_string(&self, s: &str) -> String {
@@ -109,9 +118,17 @@ impl RubyTranspiler {
     }
 
     fn transpile_variable(&self, parts: &[String]) -> String {
-        // In Ruby, we use method calls for property access
-        // driver.age in Amoskeag becomes driver.age in Ruby
-        parts.join(".")
+        // driver.age reads through the runtime's dig, which takes String or
+        // Symbol keys and, like the interpreter, gives nil for a missing key
+        // or a value that is not a Hash
+        match parts.split_first() {
+            Some((root, [])) => root.clone(),
+            Some((root, keys)) => {
+                let keys: Vec<String> = keys.iter().map(|key| self.transpile_string(key)).collect();
+                format!("{}.dig({}, [{}])", RUNTIME_MODULE, root, keys.join(", "))
+            }
+            None => "nil".to_string(),
+        }
     }
 
     fn transpile_function_call(
@@ -119,39 +136,45 @@ impl RubyTranspiler {
         name: &str,
         args: &[Expr],
     ) -> Result<String, TranspileError> {
+        signatures::check_arity(name, args.len())?;
         let mut arg_strs = Vec::new();
         for arg in args {
             arg_strs.push(self.transpile_expr(arg)?);
         }
 
-        // Map Amoskeag function names to Ruby equivalents
-        let ruby_name = match name {
-            "upcase" => "upcase",
-            "downcase" => "downcase",
-            "truncate" => "truncate",
-            "length" => "length",
-            "size" => "size",
-            "concat" => "concat",
-            "join" => "join",
-            "split" => "split",
-            "map" => "map",
-            "filter" => "select",
-            "reduce" => "reduce",
-            "sum" => "sum",
-            "min" => "min",
-            "max" => "max",
-            "abs" => "abs",
-            "round" => "round",
-            "floor" => "floor",
-            "ceil" => "ceil",
-            _ => name, // Keep the original name for unknown functions
-        };
+        // try(expr, fallback) rescues errors raised by expr
+        if name == "try" && arg_strs.len() == 2 {
+            return Ok(format!(
+                "(begin; {}; rescue StandardError; {}; end)",
+                arg_strs[0], arg_strs[1]
+            ));
+        }
 
-        // Ruby method call syntax
-        if args.is_empty() {
-            Ok(format!("{}()", ruby_name))
+        // Standard library functions live in the runtime; anything else is
+        // left for the host to define
+        if signatures::lookup(name).is_some() {
+            Ok(format!(
+                "{}.{}({})",
+                RUNTIME_MODULE,
+                name,
+                arg_strs.join(", ")
+            ))
         } else {
-            Ok(format!("{}({})", ruby_name, arg_strs.join(", ")))
+            Ok(format!("{}({})", name, arg_strs.join(", ")))
+        }
+    }
+
+    fn transpile_pipe(&mut self, left: &Expr, right: &Expr) -> Result<String, TranspileError> {
+        // Desugared as the parser does: x | f is f(x), and x | f(a) is f(x, a)
+        match right {
+            Expr::FunctionCall { name, args } => {
+                let args: Vec<Expr> = std::iter::once(left).chain(args).cloned().collect();
+                self.transpile_function_call(name, &args)
+            }
+            Expr::Variable(parts) if parts.len() == 1 => {
+                self.transpile_function_call(&parts[0], std::slice::from_ref(left))
+            }
+            _ => Err(TranspileError::InvalidPipe),
         }
     }
 
@@ -207,6 +230,11 @@ impl RubyTranspiler {
         let left_str = self.transpile_expr(left)?;
         let right_str = self.transpile_expr(right)?;
 
+        // Amoskeag division always yields a float, where Ruby's `/` truncates integers
+        if matches!(op, BinaryOp::Divide) {
+            return Ok(format!("({}).fdiv({})", left_str, right_str));
+        }
+
         let op_str = match op {
             BinaryOp::Add => "+",
             BinaryOp::Subtract => "-",
@@ -252,6 +280,7 @@ impl RubyTranspiler {
         let needs_parens = !matches!(
             operand,
             Expr::Number(_)
+                | Expr::Integer(_)
                 | Expr::String(_)
                 | Expr::Boolean(_)
                 | Expr::Nil
@@ -284,6 +313,9 @@ pub fn transpile_to_ruby(source: &str) -> Result<String, Box<dyn std::error::Err
 mod tests {
     use super::*;
     use amoskeag_parser::parse;
+    use amoskeag_stdlib_functions::codegen::string_list;
+    use amoskeag_stdlib_functions::conversion::{FALSE_STRINGS, TRUE_STRINGS};
+    use amoskeag_stdlib_functions::signatures::Arity;
     use pretty_assertions::assert_eq;
 
     #[test]
@@ -360,14 +392,20 @@ mod tests {
     fn test_transpile_variable_access() {
         let expr = parse("driver.age").unwrap();
         let mut transpiler = RubyTranspiler::new();
-        assert_eq!(transpiler.transpile(&expr).unwrap(), "driver.age");
+        assert_eq!(
+            transpiler.transpile(&expr).unwrap(),
+            "Amoskeag::Runtime.dig(driver, [\"age\"])"
+        );
     }
 
     #[test]
     fn test_transpile_function_call() {
         let expr = parse("upcase(name)").unwrap();
         let mut transpiler = RubyTranspiler::new();
-        assert_eq!(transpiler.transpile(&expr).unwrap(), "upcase(name)");
+        assert_eq!(
+            transpiler.transpile(&expr).unwrap(),
+            "Amoskeag::Runtime.upcase(name)"
+        );
     }
 
     #[test]
@@ -377,6 +415,17 @@ mod tests {
         assert_eq!(transpiler.transpile(&expr).unwrap(), "1 + 2");
     }
 
+    #[test]
+    fn test_transpile_division_is_float() {
+        let expr = parse("10 / 4").unwrap();
+        let mut transpiler = RubyTranspiler::new();
+        assert_eq!(transpiler.transpile(&expr).unwrap(), "(10).fdiv(4)");
+
+        let expr = parse("2.0 * 3").unwrap();
+        let mut transpiler = RubyTranspiler::new();
+        assert_eq!(transpiler.transpile(&expr).unwrap(), "2.0 * 3");
+    }
+
     #[test]
     fn test_transpile_comparison() {
         let expr = parse("age > 18").unwrap();
@@ -442,14 +491,20 @@ mod tests {
     fn test_transpile_pipe() {
         let expr = parse("name | upcase").unwrap();
         let mut transpiler = RubyTranspiler::new();
-        assert_eq!(transpiler.transpile(&expr).unwrap(), "upcase(name)");
+        assert_eq!(
+            transpiler.transpile(&expr).unwrap(),
+            "Amoskeag::Runtime.upcase(name)"
+        );
     }
 
     #[test]
     fn test_transpile_pipe_with_args() {
         let expr = parse("name | truncate(10)").unwrap();
         let mut transpiler = RubyTranspiler::new();
-        assert_eq!(transpiler.transpile(&expr).unwrap(), "truncate(name, 10)");
+        assert_eq!(
+            transpiler.transpile(&expr).unwrap(),
+            "Amoskeag::Runtime.truncate(name, 10)"
+        );
     }
 
     #[test]
@@ -458,8 +513,84 @@ mod tests {
         let mut transpiler = RubyTranspiler::new();
         assert_eq!(
             transpiler.transpile(&expr).unwrap(),
-            "truncate(downcase(name), 10)"
+            "Amoskeag::Runtime.truncate(Amoskeag::Runtime.downcase(name), 10)"
+        );
+    }
+
+    fn pipe(left: Expr, right: Expr) -> Expr {
+        Expr::Pipe {
+            left: Box::new(left),
+            right: Box::new(right),
+        }
+    }
+
+    #[test]
+    fn test_transpile_pipe_node() {
+        let name = || Expr::Variable(vec!["name".to_string()]);
+        let mut transpiler = RubyTranspiler::new();
+
+        let expr = pipe(name(), Expr::Variable(vec!["upcase".to_string()]));
+        assert_eq!(
+            transpiler.transpile(&expr).unwrap(),
+            "Amoskeag::Runtime.upcase(name)"
+        );
+
+        let truncate = Expr::FunctionCall {
+            name: "truncate".to_string(),
+            args: vec![Expr::Integer(10)],
+        };
+        let expr = pipe(name(), truncate.clone());
+        assert_eq!(
+            transpiler.transpile(&expr).unwrap(),
+            "Amoskeag::Runtime.truncate(name, 10)"
+        );
+
+        // Pipes chain to the left, as the parser builds them
+        let downcase = Expr::Variable(vec!["downcase".to_string()]);
+        let expr = pipe(pipe(name(), downcase), truncate);
+        assert_eq!(
+            transpiler.transpile(&expr).unwrap(),
+            "Amoskeag::Runtime.truncate(Amoskeag::Runtime.downcase(name), 10)"
+        );
+    }
+
+    #[test]
+    fn test_transpile_pipe_node_arity_mismatch() {
+        let expr = pipe(
+            Expr::String("a,b".to_string()),
+            Expr::FunctionCall {
+                name: "split".to_string(),
+                args: vec![Expr::String(",".to_string()), Expr::Nil],
+            },
+        );
+        let mut transpiler = RubyTranspiler::new();
+        assert!(matches!(
+            transpiler.transpile(&expr),
+            Err(TranspileError::Arity(ArityError {
+                expected: Arity::Exact(2),
+                actual: 3,
+                ..
+            }))
+        ));
+    }
+
+    #[test]
+    fn test_transpile_pipe_node_into_non_function() {
+        let expr = pipe(Expr::Integer(1), Expr::Integer(2));
+        let mut transpiler = RubyTranspiler::new();
+        assert!(matches!(
+            transpiler.transpile(&expr),
+            Err(TranspileError::InvalidPipe)
+        ));
+
+        let expr = pipe(
+            Expr::Integer(1),
+            Expr::Variable(vec!["driver".to_string(), "age".to_string()]),
         );
+        assert!(matches!(
+            transpiler.transpile(&expr),
+            Err(TranspileError::InvalidPipe)
+        ));
     }
 
     #[test]
@@ -475,7 +606,7 @@ mod tests {
         let mut transpiler = RubyTranspiler::new();
         assert_eq!(
             transpiler.transpile(&expr).unwrap(),
-            "driver.age > 16 ? :continue : :deny"
+            "Amoskeag::Runtime.dig(driver, [\"age\"]) > 16 ? :continue : :deny"
         );
     }
 
@@ -493,10 +624,90 @@ mod tests {
     fn test_transpile_filter_function() {
         let expr = parse("filter(items, is_active)").unwrap();
         let mut transpiler = RubyTranspiler::new();
-        // filter maps to select in Ruby
+        // Not a standard library function, so the host must define it
         assert_eq!(
             transpiler.transpile(&expr).unwrap(),
-            "select(items, is_active)"
+            "filter(items, is_active)"
+        );
+    }
+
+    #[test]
+    fn test_transpile_hash_functions() {
+        let expr = parse("sha256(license_number)").unwrap();
+        let mut transpiler = RubyTranspiler::new();
+        assert_eq!(
+            transpiler.transpile(&expr).unwrap(),
+            "Amoskeag::Runtime.sha256(license_number)"
+        );
+    }
+
+    #[test]
+    fn test_transpile_try() {
+        let expr = parse("try(to_number_strict(age), 30)").unwrap();
+        let mut transpiler = RubyTranspiler::new();
+        assert_eq!(
+            transpiler.transpile(&expr).unwrap(),
+            "(begin; Amoskeag::Runtime.to_number_strict(age); rescue StandardError; 30; end)"
+        );
+    }
+
+    #[test]
+    fn test_transpile_zero_argument_function() {
+        let expr = parse("date_now()").unwrap();
+        let mut transpiler = RubyTranspiler::new();
+        assert_eq!(
+            transpiler.transpile(&expr).unwrap(),
+            "Amoskeag::Runtime.date_now()"
+        );
+    }
+
+    #[test]
+    fn test_runtime_implements_standard_library() {
+        let runtime = [
+            include_str!("../runtime/lib/amoskeag/runtime/string.rb"),
+            include_str!("../runtime/lib/amoskeag/runtime/numeric.rb"),
+            include_str!("../runtime/lib/amoskeag/runtime/financial.rb"),
+            include_str!("../runtime/lib/amoskeag/runtime/collection.rb"),
+            include_str!("../runtime/lib/amoskeag/runtime/logic.rb"),
+            include_str!("../runtime/lib/amoskeag/runtime/conversion.rb"),
+            include_str!("../runtime/lib/amoskeag/runtime/date.rb"),
+        ]
+        .concat();
+        for function in signatures::all().map(|f| f.name) {
+            if function == "try" {
+                continue;
+            }
+            assert!(
+                runtime.contains(&format!("\n    def {}(", function))
+                    || runtime.contains(&format!("\n    def {}\n", function)),
+                "{} is not defined in the Ruby runtime",
+                function
+            );
+        }
+    }
+
+    #[test]
+    fn test_runtime_conversion_rules() {
+        let runtime = include_str!("../runtime/lib/amoskeag/runtime/conversion.rb");
+        for (name, strings) in [
+            ("TRUE_STRINGS", TRUE_STRINGS),
+            ("FALSE_STRINGS", FALSE_STRINGS),
+        ] {
+            assert!(
+                runtime.contains(&format!("{} = {}.freeze", name, string_list(strings))),
+                "{} in the Ruby runtime differs from the interpreter's",
+                name
+            );
+        }
+    }
+
+    #[test]
+    fn test_transpile_arity_mismatch() {
+        let expr = parse("sha256()").unwrap();
+        let mut transpiler = RubyTranspiler::new();
+        assert_eq!(
+            transpiler.transpile(&expr).unwrap_err().to_string(),
+            "Function 'sha256' expects 1 arguments, but 0 were provided"
         );
     }
 }
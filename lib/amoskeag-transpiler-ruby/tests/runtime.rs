@@ -0,0 +1,126 @@
+//! Runs the shared conformance cases on the Ruby runtime gem
+//!
+//! Every case is transpiled into one Ruby script, which loads the runtime
+//! from `runtime/lib`, and each result is compared with the one the fixture
+//! expects. Without Ruby on the path the tests are skipped, except in CI.
+
+#[path = "../../../conformance/support.rs"]
+mod conformance;
+
+use amoskeag_transpiler_ruby::transpile_to_ruby;
+use serde_json::Value;
+use std::fmt::Write;
+use std::path::Path;
+use std::process::Command;
+
+/// Prints each result as one line of JSON in the fixture's form
+const HARNESS: &str = r##"require "json"
+require "amoskeag/runtime"
+
+def render(value)
+  case value
+  when nil, true, false, Integer, String then JSON.generate(value)
+  when Float then value.finite? ? value.to_s : "null"
+  when Symbol then JSON.generate({ "$symbol" => value.to_s })
+  when Amoskeag::Runtime::Duration then JSON.generate({ "$duration" => value.to_s })
+  when Array then "[#{value.map { |item| render(item) }.join(",")}]"
+  when Hash
+    pairs = value.map { |key, item| [key.to_s, item] }.sort_by(&:first)
+    "{#{pairs.map { |key, item| "#{JSON.generate(key)}:#{render(item)}" }.join(",")}}"
+  else JSON.generate(value.inspect)
+  end
+end
+
+def run
+  render(yield)
+rescue StandardError => e
+  JSON.generate({ "$error" => e.message })
+end
+"##;
+
+/// `text` as a single-quoted Ruby string
+fn ruby_string(text: &str) -> String {
+    format!("'{}'", text.replace('\\', "\\\\").replace('\'', "\\'"))
+}
+
+fn has_ruby() -> bool {
+    Command::new("ruby")
+        .arg("--version")
+        .output()
+        .is_ok_and(|output| output.status.success())
+}
+
+/// Run the transpiled expressions in one Ruby process, each with the keys
+/// of `data` as its variables, and return their results in order
+fn run_expressions(name: &str, expressions: &[String], data: &Value) -> Vec<Value> {
+    let dir = std::env::temp_dir().join(format!("amoskeag-ruby-{}-{}", name, std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+
+    let variables: Vec<&String> = data.as_object().unwrap().keys().collect();
+    let names: Vec<&str> = variables.iter().map(|name| name.as_str()).collect();
+    let keys: Vec<String> = variables.iter().map(|name| ruby_string(name)).collect();
+
+    let mut script = String::from(HARNESS);
+    writeln!(
+        script,
+        "\nINPUT = JSON.parse({})\n",
+        ruby_string(&data.to_string())
+    )
+    .unwrap();
+    for expression in expressions {
+        writeln!(
+            script,
+            "puts(run {{ lambda {{ |{}| {} }}.call(*INPUT.values_at({})) }})",
+            names.join(", "),
+            expression,
+            keys.join(", ")
+        )
+        .unwrap();
+    }
+    let path = dir.join("conformance.rb");
+    std::fs::write(&path, script).unwrap();
+
+    let runtime = Path::new(env!("CARGO_MANIFEST_DIR")).join("runtime/lib");
+    let output = Command::new("ruby")
+        .arg("-I")
+        .arg(&runtime)
+        .arg(&path)
+        .output()
+        .unwrap();
+    assert!(
+        output.status.success(),
+        "ruby failed:\n{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let _ = std::fs::remove_dir_all(&dir);
+
+    String::from_utf8(output.stdout)
+        .unwrap()
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap_or_else(|e| panic!("{}: {}", line, e)))
+        .collect()
+}
+
+#[test]
+fn test_runtime_conformance() {
+    if !conformance::tools_found(has_ruby(), "ruby") {
+        return;
+    }
+
+    let fixture = conformance::load();
+    let cases: Vec<&conformance::Case> = fixture
+        .cases
+        .iter()
+        .filter(|case| case.runs_on("ruby"))
+        .collect();
+    let expressions: Vec<String> = cases
+        .iter()
+        .map(|case| {
+            transpile_to_ruby(&case.expression)
+                .unwrap_or_else(|e| panic!("{} does not transpile: {}", case.expression, e))
+        })
+        .collect();
+    let results = run_expressions("conformance", &expressions, &fixture.data);
+    conformance::check("Ruby", &cases, &results);
+}
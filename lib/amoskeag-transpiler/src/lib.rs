@@ -5,6 +5,7 @@
 //! to maintain semantic equivalence with the interpreted version.
 
 use amoskeag_parser::{BinaryOp, Expr, UnaryOp};
+use amoskeag_stdlib_functions::signatures::{self, ArityError, Numbers};
 use std::fmt::Write;
 use thiserror::Error;
 
@@ -16,6 +17,9 @@ pub enum TranspileError {
 
     #[error("Unsupported expression: {0}")]
     UnsupportedExpression(String),
+
+    #[error(transparent)]
+    Arity(#[from] ArityError),
 }
 
 /// Configuration for the transpiler
@@ -43,15 +47,16 @@ impl Default for TranspilerConfig {
 pub struct Transpiler {
     config: TranspilerConfig,
     indent_level: usize,
+    /// The generated variable holding the bindings in scope
+    context: String,
+    /// How many `let` scopes have been generated, for naming the next one
+    scopes: usize,
 }
 
 impl Transpiler {
     /// Create a new transpiler with the default configuration
     pub fn new() -> Self {
-        Self {
-            config: TranspilerConfig::default(),
-            indent_level: 0,
-        }
+        Self::with_config(TranspilerConfig::default())
     }
 
     /// Create a new transpiler with a custom configuration
@@ -59,6 +64,8 @@ impl Transpiler {
         Self {
             config,
             indent_level: 0,
+            context: "context".to_string(),
+            scopes: 0,
         }
     }
 
@@ -73,6 +80,7 @@ impl Transpiler {
     /// A string containing the generated Rust code
     pub fn transpile(&mut self, expr: &Expr) -> Result<String, TranspileError> {
         let mut output = String::new();
+        self.scopes = 0;
 
         // Add necessary imports
         writeln!(output, "use amoskeag_stdlib_operators::{{Value, *}};")?;
@@ -102,8 +110,9 @@ impl Transpiler {
     /// Transpile a single expression
     fn transpile_expr(&mut self, expr: &Expr) -> Result<String, TranspileError> {
         match expr {
-            Expr::Number(n) => Ok(format!("Value::Number({})", n)),
-            Expr::String(s) => Ok(format!("Value::String({:?}.to_string())", s)),
+            Expr::Number(n) => Ok(format!("Value::Number({:?})", n)),
+            Expr::Integer(n) => Ok(format!("Value::Int({})", n)),
+            Expr::String(s) => Ok(format!("Value::String({:?}.into())", s)),
             Expr::Boolean(b) => Ok(format!("Value::Boolean({})", b)),
             Expr::Nil => Ok("Value::Nil".to_string()),
             Expr::Symbol(s) => Ok(format!("Value::Symbol({:?}.to_string())", s)),
@@ -130,7 +139,7 @@ impl Transpiler {
         for expr in exprs {
             items.push(self.transpile_expr(expr)?);
         }
-        Ok(format!("Value::Array(vec![{}])", items.join(", ")))
+        Ok(format!("Value::Array(vec![{}].into())", items.join(", ")))
     }
 
     /// Transpile a dictionary literal
@@ -148,7 +157,7 @@ impl Transpiler {
             )?;
         }
 
-        write!(output, " Value::Dictionary(map)")?;
+        write!(output, " Value::Dictionary(map.into())")?;
         write!(output, "}}")?;
 
         Ok(output)
@@ -166,8 +175,8 @@ impl Transpiler {
         // Look up the root variable
         write!(
             output,
-            "let mut current = context.get({:?}).cloned().unwrap_or(Value::Nil);",
-            path[0]
+            "let mut current = {}.get({:?}).cloned().unwrap_or(Value::Nil);",
+            self.context, path[0]
         )?;
 
         // Navigate the path
@@ -191,12 +200,18 @@ impl Transpiler {
         name: &str,
         args: &[Expr],
     ) -> Result<String, TranspileError> {
+        signatures::check_arity(name, args.len())?;
         let mut arg_codes = Vec::new();
         for arg in args {
             arg_codes.push(self.transpile_expr(arg)?);
         }
 
-        let args_str = arg_codes.join(", ");
+        let args_str = args
+            .iter()
+            .zip(&arg_codes)
+            .map(|(arg, code)| stdlib_arg(name, arg, code))
+            .collect::<Vec<_>>()
+            .join(", ");
 
         // Map to the corresponding stdlib function
         let result = match name {
@@ -206,15 +221,29 @@ impl Transpiler {
             | "divided_by" | "max" | "min" | "size" | "first" | "last" | "contains" | "sum"
             | "avg" | "sort" | "keys" | "values" | "reverse" | "at" | "choose" | "if_then_else"
             | "is_number" | "is_string" | "is_boolean" | "is_nil" | "is_array"
-            | "is_dictionary" | "coalesce" | "default" => {
+            | "is_dictionary" | "default" | "sha256" | "md5" => {
                 format!("{}(&{})?", name, args_str)
             }
 
+            // Variadic: the stdlib takes every argument as one slice
+            "coalesce" => format!("coalesce(&[{}])", args_str),
+
+            // Errors from the first argument fall back to the second
+            "try" => try_expression(&arg_codes[0], &arg_codes[1]),
+
             // Special case for modulo (function name vs operator)
             "modulo" => {
                 format!("modulo_fn(&{})?", args_str)
             }
 
+            // Lenient conversions never fail; the strict variants do
+            "to_number" | "to_string" | "to_boolean" => {
+                format!("{}(&{})", name, args_str)
+            }
+            "to_number_strict" | "to_string_strict" | "to_boolean_strict" => {
+                format!("{}(&{})?", name, args_str)
+            }
+
             _ => {
                 return Err(TranspileError::UnsupportedExpression(format!(
                     "Unknown function: {}",
@@ -227,29 +256,36 @@ impl Transpiler {
     }
 
     /// Transpile a let binding
+    ///
+    /// Each binding gets its own numbered context variable, a copy of the
+    /// enclosing one with the name added, so inner bindings shadow outer ones
+    /// only inside the body.
     fn transpile_let(
         &mut self,
         name: &str,
         value: &Expr,
         body: &Expr,
     ) -> Result<String, TranspileError> {
+        // The value sees the enclosing scope, not the new binding
         let value_code = self.transpile_expr(value)?;
 
+        self.scopes += 1;
+        let scope = format!("context_{}", self.scopes);
+
         let mut output = String::new();
         write!(output, "{{")?;
-        write!(output, " let mut new_context = context.clone();")?;
+        write!(output, " let mut {} = {}.clone();", scope, self.context)?;
         write!(
             output,
-            " new_context.insert({:?}.to_string(), {});",
-            name, value_code
+            " {}.insert({:?}.to_string(), {});",
+            scope, name, value_code
         )?;
 
-        // Temporarily use new_context for the body
-        let old_body = self.transpile_expr(body)?;
-        // Replace context references in body with new_context
-        let body_code = old_body.replace("context", "&new_context");
+        let outer = std::mem::replace(&mut self.context, scope);
+        let body_code = self.transpile_expr(body);
+        self.context = outer;
 
-        write!(output, " {}", body_code)?;
+        write!(output, " {}", body_code?)?;
         write!(output, " }}")?;
 
         Ok(output)
@@ -325,7 +361,7 @@ impl Transpiler {
                 let mut output = String::new();
                 write!(output, "{{")?;
                 write!(output, " let val = {};", operand_code)?;
-                write!(output, " match val {{ Value::Number(n) => Value::Number(-n), _ => return Err(\"Type error: expected Number\".into()) }}")?;
+                write!(output, " match val {{ Value::Number(n) => Value::Number(-n), Value::Decimal(d) => Value::Decimal(-d), Value::Int(n) => n.checked_neg().map_or(Value::Number(-(n as f64)), Value::Int), _ => return Err(\"Type error: expected Number\".into()) }}")?;
                 write!(output, " }}")?;
                 Ok(output)
             }
@@ -338,13 +374,19 @@ impl Transpiler {
 
         match right {
             Expr::FunctionCall { name, args } => {
+                signatures::check_arity(name, args.len() + 1)?;
                 // Prepend the left value as the first argument
                 let mut all_args = vec![left_code];
                 for arg in args {
                     all_args.push(self.transpile_expr(arg)?);
                 }
 
-                let args_str = all_args.join(", ");
+                let args_str = std::iter::once(left)
+                    .chain(args)
+                    .zip(&all_args)
+                    .map(|(arg, code)| stdlib_arg(name, arg, code))
+                    .collect::<Vec<_>>()
+                    .join(", ");
 
                 // Map to the corresponding stdlib function
                 let result = match name.as_str() {
@@ -353,13 +395,20 @@ impl Transpiler {
                     | "minus" | "times" | "divided_by" | "max" | "min" | "size" | "first"
                     | "last" | "contains" | "sum" | "avg" | "sort" | "keys" | "values"
                     | "reverse" | "at" | "choose" | "if_then_else" | "is_number" | "is_string"
-                    | "is_boolean" | "is_nil" | "is_array" | "is_dictionary" | "coalesce"
-                    | "default" => {
+                    | "is_boolean" | "is_nil" | "is_array" | "is_dictionary" | "default" => {
                         format!("{}(&{})?", name, args_str)
                     }
+                    "coalesce" => format!("coalesce(&[{}])", args_str),
+                    "try" => try_expression(&all_args[0], &all_args[1]),
                     "modulo" => {
                         format!("modulo_fn(&{})?", args_str)
                     }
+                    "to_number" | "to_string" | "to_boolean" => {
+                        format!("{}(&{})", name, args_str)
+                    }
+                    "to_number_strict" | "to_string_strict" | "to_boolean_strict" => {
+                        format!("{}(&{})?", name, args_str)
+                    }
                     _ => {
                         return Err(TranspileError::UnsupportedExpression(format!(
                             "Unknown function in pipe: {}",
@@ -373,12 +422,20 @@ impl Transpiler {
             Expr::Variable(path) if path.len() == 1 => {
                 // Simple function name without args
                 let name = &path[0];
+                signatures::check_arity(name, 1)?;
+                let left_code = stdlib_arg(name, left, &left_code);
                 let result = match name.as_str() {
                     "upcase" | "downcase" | "capitalize" | "strip" | "size" | "first" | "last"
                     | "sum" | "avg" | "sort" | "keys" | "values" | "reverse" | "is_number"
                     | "is_string" | "is_boolean" | "is_nil" | "is_array" | "is_dictionary" => {
                         format!("{}(&{})?", name, left_code)
                     }
+                    "to_number" | "to_string" | "to_boolean" => {
+                        format!("{}(&{})", name, left_code)
+                    }
+                    "to_number_strict" | "to_string_strict" | "to_boolean_strict" => {
+                        format!("{}(&{})?", name, left_code)
+                    }
                     _ => {
                         return Err(TranspileError::UnsupportedExpression(format!(
                             "Unknown function in pipe: {}",
@@ -395,6 +452,35 @@ impl Transpiler {
     }
 }
 
+/// Rust code passing `code`, the transpiled `arg`, to a stdlib function
+///
+/// The stdlib works in floats, so integers are promoted on the way in, as the
+/// interpreter does, unless the function's signature leaves numbers unchanged.
+fn stdlib_arg(name: &str, arg: &Expr, code: &str) -> String {
+    if signatures::lookup(name).is_some_and(|f| f.numbers == Numbers::Unchanged) {
+        return code.to_string();
+    }
+    match arg {
+        Expr::Integer(n) => format!("Value::Number({:?})", *n as f64),
+        Expr::Number(_) | Expr::String(_) | Expr::Boolean(_) | Expr::Nil | Expr::Symbol(_) => {
+            code.to_string()
+        }
+        _ => format!("{}.promote_ints()", code),
+    }
+}
+
+/// Rust code for `try(body, fallback)`
+///
+/// The body runs in a closure so that `?` and early returns inside it are
+/// caught; the fallback only runs if the body fails.
+fn try_expression(body: &str, fallback: &str) -> String {
+    format!(
+        "(|| -> Result<Value, Box<dyn std::error::Error>> {{ Ok({}) }})()\
+         .or_else(|_| -> Result<Value, Box<dyn std::error::Error>> {{ Ok({}) }})?",
+        body, fallback
+    )
+}
+
 impl Default for Transpiler {
     fn default() -> Self {
         Self::new()
@@ -404,13 +490,44 @@ impl Default for Transpiler {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use amoskeag_stdlib_functions::signatures::Arity;
 
     #[test]
     fn test_transpile_number() {
         let mut transpiler = Transpiler::new();
         let expr = Expr::Number(42.0);
         let result = transpiler.transpile(&expr).unwrap();
-        assert!(result.contains("Value::Number(42)"));
+        assert!(result.contains("Value::Number(42.0)"));
+
+        let expr = Expr::Integer(42);
+        let result = transpiler.transpile(&expr).unwrap();
+        assert!(result.contains("Value::Int(42)"));
+    }
+
+    #[test]
+    fn test_transpile_promotes_integer_arguments() {
+        let mut transpiler = Transpiler::new();
+        let expr = Expr::FunctionCall {
+            name: "abs".to_string(),
+            args: vec![Expr::Integer(-3)],
+        };
+        let result = transpiler.transpile(&expr).unwrap();
+        assert!(result.contains("abs(&Value::Number(-3.0))?"));
+
+        let expr = Expr::FunctionCall {
+            name: "sum".to_string(),
+            args: vec![Expr::Variable(vec!["items".to_string()])],
+        };
+        let result = transpiler.transpile(&expr).unwrap();
+        assert!(result.contains(".promote_ints())?"));
+
+        let expr = Expr::FunctionCall {
+            name: "first".to_string(),
+            args: vec![Expr::Array(vec![Expr::Integer(7)])],
+        };
+        let result = transpiler.transpile(&expr).unwrap();
+        assert!(!result.contains("promote_ints"));
+        assert!(result.contains("Value::Int(7)"));
     }
 
     #[test]
@@ -480,6 +597,57 @@ mod tests {
         assert!(result.contains("upcase"));
     }
 
+    #[test]
+    fn test_transpile_conversion_functions() {
+        let mut transpiler = Transpiler::new();
+        let expr = Expr::FunctionCall {
+            name: "to_number".to_string(),
+            args: vec![Expr::String("25".to_string())],
+        };
+        let result = transpiler.transpile(&expr).unwrap();
+        assert!(result.contains("to_number(&Value::String(\"25\".into()))"));
+        assert!(!result.contains("to_number(&Value::String(\"25\".into()))?"));
+
+        let expr = Expr::FunctionCall {
+            name: "to_boolean_strict".to_string(),
+            args: vec![Expr::String("yes".to_string())],
+        };
+        let result = transpiler.transpile(&expr).unwrap();
+        assert!(result.contains("to_boolean_strict(&Value::String(\"yes\".into()))?"));
+    }
+
+    #[test]
+    fn test_transpile_variadic_coalesce() {
+        let mut transpiler = Transpiler::new();
+        let expr = Expr::FunctionCall {
+            name: "coalesce".to_string(),
+            args: vec![Expr::Nil, Expr::Nil, Expr::Number(1.0)],
+        };
+        let result = transpiler.transpile(&expr).unwrap();
+        assert!(result.contains("coalesce(&[Value::Nil, Value::Nil, Value::Number(1"));
+    }
+
+    #[test]
+    fn test_transpile_try() {
+        let mut transpiler = Transpiler::new();
+        let expr = Expr::FunctionCall {
+            name: "try".to_string(),
+            args: vec![
+                Expr::FunctionCall {
+                    name: "to_number_strict".to_string(),
+                    args: vec![Expr::String("x".to_string())],
+                },
+                Expr::Number(0.0),
+            ],
+        };
+        let result = transpiler.transpile(&expr).unwrap();
+        assert!(result
+            .contains("(|| -> Result<Value, Box<dyn std::error::Error>> { Ok(to_number_strict("));
+        assert!(result.contains(
+            ".or_else(|_| -> Result<Value, Box<dyn std::error::Error>> { Ok(Value::Number("
+        ));
+    }
+
     #[test]
     fn test_transpile_binary_op() {
         let mut transpiler = Transpiler::new();
@@ -503,4 +671,120 @@ mod tests {
         let result = transpiler.transpile(&expr).unwrap();
         assert!(result.contains("is_truthy"));
     }
+
+    fn variable(name: &str) -> Expr {
+        Expr::Variable(vec![name.to_string()])
+    }
+
+    fn let_expr(name: &str, value: Expr, body: Expr) -> Expr {
+        Expr::Let {
+            name: name.to_string(),
+            value: Box::new(value),
+            body: Box::new(body),
+        }
+    }
+
+    #[test]
+    fn test_transpile_let() {
+        let mut transpiler = Transpiler::new();
+        let expr = let_expr("x", Expr::Number(5.0), variable("x"));
+        let result = transpiler.transpile(&expr).unwrap();
+        assert!(result.contains(
+            "{ let mut context_1 = context.clone(); \
+             context_1.insert(\"x\".to_string(), Value::Number(5.0)); \
+             {let mut current = context_1.get(\"x\")"
+        ));
+    }
+
+    #[test]
+    fn test_transpile_nested_let() {
+        let mut transpiler = Transpiler::new();
+        // let x = 1 in let y = x in y
+        let expr = let_expr(
+            "x",
+            Expr::Number(1.0),
+            let_expr("y", variable("x"), variable("y")),
+        );
+        let result = transpiler.transpile(&expr).unwrap();
+        assert!(result.contains("let mut context_2 = context_1.clone(); context_2.insert(\"y\".to_string(), {let mut current = context_1.get(\"x\")"));
+        assert!(result.contains("{let mut current = context_2.get(\"y\")"));
+    }
+
+    #[test]
+    fn test_transpile_shadowed_let() {
+        let mut transpiler = Transpiler::new();
+        // let x = 1 in (let x = x + 1 in x) + x
+        let inner = let_expr(
+            "x",
+            Expr::Binary {
+                op: BinaryOp::Add,
+                left: Box::new(variable("x")),
+                right: Box::new(Expr::Number(1.0)),
+            },
+            variable("x"),
+        );
+        let expr = let_expr(
+            "x",
+            Expr::Number(1.0),
+            Expr::Binary {
+                op: BinaryOp::Add,
+                left: Box::new(inner),
+                right: Box::new(variable("x")),
+            },
+        );
+        let result = transpiler.transpile(&expr).unwrap();
+        // The inner value and the outer body's x both read the outer binding
+        assert_eq!(result.matches("context_1.get(\"x\")").count(), 2);
+        // Only the inner body reads the inner binding
+        assert_eq!(result.matches("context_2.get(\"x\")").count(), 1);
+    }
+
+    #[test]
+    fn test_transpile_let_keeps_strings() {
+        let mut transpiler = Transpiler::new();
+        let expr = let_expr(
+            "context",
+            Expr::String("the context".to_string()),
+            variable("context"),
+        );
+        let result = transpiler.transpile(&expr).unwrap();
+        assert!(result.contains("Value::String(\"the context\".into())"));
+        assert!(result.contains("context_1.insert(\"context\".to_string(), "));
+        assert!(result.contains("context_1.get(\"context\")"));
+        assert!(!result.contains("new_context"));
+    }
+
+    #[test]
+    fn test_transpile_sibling_lets() {
+        let mut transpiler = Transpiler::new();
+        // [let a = 1 in a, let b = 2 in b]
+        let expr = Expr::Array(vec![
+            let_expr("a", Expr::Number(1.0), variable("a")),
+            let_expr("b", Expr::Number(2.0), variable("b")),
+        ]);
+        let result = transpiler.transpile(&expr).unwrap();
+        assert!(result.contains("let mut context_1 = context.clone();"));
+        assert!(result.contains("let mut context_2 = context.clone();"));
+        assert!(result.contains("context_2.get(\"b\")"));
+    }
+
+    #[test]
+    fn test_transpile_arity_mismatch() {
+        let mut transpiler = Transpiler::new();
+        let expr = Expr::Pipe {
+            left: Box::new(Expr::String("a".to_string())),
+            right: Box::new(Expr::FunctionCall {
+                name: "join".to_string(),
+                args: vec![Expr::String(",".to_string()), Expr::Nil],
+            }),
+        };
+        assert!(matches!(
+            transpiler.transpile(&expr),
+            Err(TranspileError::Arity(ArityError {
+                expected: Arity::Exact(2),
+                actual: 3,
+                ..
+            }))
+        ));
+    }
 }
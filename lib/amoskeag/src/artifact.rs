@@ -0,0 +1,154 @@
+//! Stored compiled programs
+//!
+//! Parsing and validating a large rule set on every start-up is wasted work
+//! when the rules have not changed. `CompiledProgram::to_bytes` turns a
+//! program into an artifact that can be cached on disk or in a shared store,
+//! and `CompiledProgram::from_bytes` loads it back without re-parsing.
+//!
+//! Artifacts carry a format version. One written by a different version of
+//! this crate is rejected with `ArtifactError::Version` rather than guessed
+//! at, so a cache can fall back to compiling from source. So is one nested
+//! too deeply to read back safely, which only very deep programs produce.
+//!
+//! `CompiledProgram::fingerprint` identifies what a program does rather than
+//! how it is stored, for detecting rule changes and tagging results with the
+//! rule version that produced them.
+
+use crate::registry::FunctionRegistry;
+use crate::{validate_ast, CompileError, CompiledProgram, NumericMode, Truthiness};
+use amoskeag_parser::Expr;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::borrow::Cow;
+use thiserror::Error;
+
+/// The artifact format written by `CompiledProgram::to_bytes`
+///
+/// Bumped whenever the stored AST or options change shape.
+pub const ARTIFACT_VERSION: u32 = 1;
+
+/// Errors that can occur loading a stored program
+#[derive(Error, Debug)]
+pub enum ArtifactError {
+    #[error("Malformed program artifact: {0}")]
+    Malformed(String),
+
+    #[error("Program artifact has format version {found}, expected {expected}")]
+    Version { found: u32, expected: u32 },
+
+    #[error("Stored program does not validate: {0}")]
+    Invalid(#[from] CompileError),
+}
+
+/// The stored form of a program
+///
+/// Host functions cannot be stored; only the names called are kept in the
+/// AST, and they are resolved against the registry passed when loading.
+#[derive(Serialize, Deserialize)]
+struct Artifact<'a> {
+    version: u32,
+    ast: Cow<'a, Expr>,
+    symbols: Vec<String>,
+    numeric_mode: NumericMode,
+    strict_navigation: bool,
+    truthiness: Truthiness,
+}
+
+/// What a fingerprint covers: everything that decides a program's results
+#[derive(Serialize)]
+struct Canonical<'a> {
+    ast: &'a Expr,
+    numeric_mode: NumericMode,
+    strict_navigation: bool,
+    truthiness: Truthiness,
+}
+
+/// Just enough of an artifact to check its version before reading the rest
+#[derive(Deserialize)]
+struct Header {
+    version: u32,
+}
+
+impl CompiledProgram {
+    /// This program as an artifact for `from_bytes`
+    ///
+    /// The program's numeric mode, navigation and truthiness settings are
+    /// stored with it; host functions are not.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut symbols: Vec<String> = self.symbols.iter().cloned().collect();
+        symbols.sort();
+        let artifact = Artifact {
+            version: ARTIFACT_VERSION,
+            ast: Cow::Borrowed(&self.ast),
+            symbols,
+            numeric_mode: self.numeric_mode,
+            strict_navigation: self.strict_navigation,
+            truthiness: self.truthiness,
+        };
+        serde_json::to_vec(&artifact).expect("compiled programs always serialize")
+    }
+
+    /// A SHA-256 hash of this program's AST and evaluation settings, in hex
+    ///
+    /// Programs that parse to the same AST have the same fingerprint, however
+    /// their source is laid out or commented, as long as they evaluate with
+    /// the same numeric mode, navigation and truthiness. Declared symbols and
+    /// host functions are not part of it. The fingerprint does not depend on
+    /// `ARTIFACT_VERSION`, so it stays stable across storage format changes.
+    pub fn fingerprint(&self) -> String {
+        let canonical = Canonical {
+            ast: &self.ast,
+            numeric_mode: self.numeric_mode,
+            strict_navigation: self.strict_navigation,
+            truthiness: self.truthiness,
+        };
+        let bytes = serde_json::to_vec(&canonical).expect("compiled programs always serialize");
+        Sha256::digest(bytes)
+            .iter()
+            .map(|byte| format!("{:02x}", byte))
+            .collect()
+    }
+
+    /// Load a program stored with `to_bytes`
+    ///
+    /// Calls are validated again against `functions`, so an artifact that
+    /// calls a host function fails with `ArtifactError::Invalid` unless the
+    /// function is registered with a compatible signature.
+    pub fn from_bytes(
+        bytes: &[u8],
+        functions: &FunctionRegistry,
+    ) -> Result<CompiledProgram, ArtifactError> {
+        let header: Header = read(bytes)?;
+        if header.version != ARTIFACT_VERSION {
+            return Err(ArtifactError::Version {
+                found: header.version,
+                expected: ARTIFACT_VERSION,
+            });
+        }
+
+        let artifact: Artifact = read(bytes)?;
+        let symbols = artifact.symbols.into_iter().collect();
+        let ast = artifact.ast.into_owned();
+        validate_ast(&ast, &symbols, functions)?;
+
+        Ok(CompiledProgram {
+            ast,
+            symbols,
+            numeric_mode: artifact.numeric_mode,
+            functions: functions.clone(),
+            strict_navigation: artifact.strict_navigation,
+            truthiness: artifact.truthiness,
+        })
+    }
+}
+
+/// Deserialize an artifact, or the part of it `T` describes
+fn read<'de, T: Deserialize<'de>>(bytes: &'de [u8]) -> Result<T, ArtifactError> {
+    // Artifacts may come from an untrusted store, so serde_json's recursion
+    // limit stays on and overly deep programs are refused as malformed
+    let mut deserializer = serde_json::Deserializer::from_slice(bytes);
+    let malformed = |e: serde_json::Error| ArtifactError::Malformed(e.to_string());
+    let value = T::deserialize(&mut deserializer).map_err(malformed)?;
+    deserializer.end().map_err(malformed)?;
+    Ok(value)
+}
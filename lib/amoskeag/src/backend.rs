@@ -5,13 +5,17 @@
 //! - Transpilation to Python
 //! - Transpilation to Ruby
 //! - Interpretation (tree-walking evaluator)
+//! - WebAssembly modules for browsers and edge runtimes
 
 pub mod interpreter;
+pub mod wasm;
 
+use crate::introspect::walk;
 use crate::{CompileError, EvalError};
-use amoskeag_parser::Expr;
+use amoskeag_parser::{BinaryOp, Expr, UnaryOp};
 use amoskeag_stdlib_operators::Value;
-use std::collections::HashMap;
+use std::collections::{BTreeSet, HashMap};
+use std::time::{Duration, Instant};
 use thiserror::Error;
 
 /// Errors that can occur in any backend
@@ -124,8 +128,74 @@ pub struct BackendCapabilities {
     pub requires_external_deps: bool,
 }
 
+/// The features backends list in `BackendCapabilities::supported_features`
+/// that `required_features` can find in a program
+pub const FEATURES: &[&str] = &[
+    "numbers",
+    "strings",
+    "booleans",
+    "symbols",
+    "arrays",
+    "dictionaries",
+    "arithmetic",
+    "comparisons",
+    "logic",
+    "if_expressions",
+    "let_bindings",
+    "function_calls",
+    "pipe_expressions",
+];
+
+/// The features from `FEATURES` that `expr` uses, sorted
+pub fn required_features(expr: &Expr) -> Vec<&'static str> {
+    let mut features = BTreeSet::new();
+    walk(expr, &mut |expr| {
+        let feature = match expr {
+            Expr::Number(_) | Expr::Integer(_) => "numbers",
+            Expr::String(_) => "strings",
+            Expr::Boolean(_) => "booleans",
+            Expr::Symbol(_) => "symbols",
+            Expr::Array(_) => "arrays",
+            Expr::Dictionary(_) => "dictionaries",
+            Expr::Binary { op, .. } => match op {
+                BinaryOp::And | BinaryOp::Or => "logic",
+                BinaryOp::Equal
+                | BinaryOp::NotEqual
+                | BinaryOp::Less
+                | BinaryOp::Greater
+                | BinaryOp::LessEqual
+                | BinaryOp::GreaterEqual => "comparisons",
+                _ => "arithmetic",
+            },
+            Expr::Unary { op, .. } => match op {
+                UnaryOp::Not => "logic",
+                UnaryOp::Negate => "arithmetic",
+            },
+            Expr::If { .. } => "if_expressions",
+            Expr::Let { .. } => "let_bindings",
+            Expr::FunctionCall { .. } => "function_calls",
+            Expr::Pipe { .. } => "pipe_expressions",
+            Expr::Nil | Expr::Variable(_) => return,
+        };
+        features.insert(feature);
+    });
+    features.into_iter().collect()
+}
+
+impl BackendCapabilities {
+    /// The features `expr` uses that are not in `supported_features`
+    pub fn missing_features(&self, expr: &Expr) -> Vec<&'static str> {
+        required_features(expr)
+            .into_iter()
+            .filter(|feature| !self.supported_features.iter().any(|f| f == feature))
+            .collect()
+    }
+}
+
 /// Performance tier classification for backends
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+///
+/// Tiers order fastest first.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
 pub enum PerformanceTier {
     /// Near-native performance (e.g., JIT compilation)
     Native,
@@ -152,16 +222,104 @@ impl PerformanceTier {
     }
 }
 
+/// How many times `BackendRegistry::select` runs each candidate over the samples
+const BENCHMARK_ROUNDS: u32 = 10;
+
+/// A backend that evaluates to values, with its compiled output type erased
+trait Runner {
+    fn can_run(&self, expr: &Expr) -> bool;
+
+    fn prepare(&self, expr: &Expr, symbols: &[&str]) -> BackendResult<Box<dyn Prepared + '_>>;
+}
+
+impl<B> Runner for B
+where
+    B: Backend<ExecutionResult = Value>,
+    B::CompiledOutput: 'static,
+{
+    fn can_run(&self, expr: &Expr) -> bool {
+        self.supports(expr)
+    }
+
+    fn prepare(&self, expr: &Expr, symbols: &[&str]) -> BackendResult<Box<dyn Prepared + '_>> {
+        let output = self.compile(expr, symbols)?;
+        Ok(Box::new(CompiledFor {
+            backend: self,
+            output,
+        }))
+    }
+}
+
+/// A program compiled by a `Runner`
+trait Prepared {
+    fn execute(&self, data: &HashMap<String, Value>) -> BackendResult<Value>;
+}
+
+struct CompiledFor<'b, B: Backend> {
+    backend: &'b B,
+    output: B::CompiledOutput,
+}
+
+impl<B: Backend<ExecutionResult = Value>> Prepared for CompiledFor<'_, B> {
+    fn execute(&self, data: &HashMap<String, Value>) -> BackendResult<Value> {
+        self.backend.execute(&self.output, data)
+    }
+}
+
+/// The backend `BackendRegistry::select` picked, with the program compiled for it
+pub struct BackendSelection<'r> {
+    backend: String,
+    timings: Vec<(String, Duration)>,
+    fallbacks: Vec<(String, String)>,
+    program: Box<dyn Prepared + 'r>,
+}
+
+impl BackendSelection<'_> {
+    /// The name of the backend picked
+    pub fn backend(&self) -> &str {
+        &self.backend
+    }
+
+    /// The mean time per evaluation measured for each candidate, fastest first
+    ///
+    /// Empty when the backend was overridden or there was no sample data to
+    /// measure with; a candidate that failed on the samples is left out.
+    pub fn timings(&self) -> &[(String, Duration)] {
+        &self.timings
+    }
+
+    /// The backends passed over because they cannot run the program, with
+    /// the reason, in the order they were tried
+    ///
+    /// An override that cannot run the program comes first.
+    pub fn fallbacks(&self) -> &[(String, String)] {
+        &self.fallbacks
+    }
+
+    /// Evaluate the program with the backend picked
+    pub fn execute(&self, data: &HashMap<String, Value>) -> BackendResult<Value> {
+        self.program.execute(data)
+    }
+}
+
 /// Registry of available backends
 pub struct BackendRegistry {
     backends: HashMap<String, BackendCapabilities>,
+    runners: HashMap<String, Box<dyn Runner>>,
+    preferred: Option<String>,
+    /// The time since some fixed instant, for measuring candidates
+    clock: Box<dyn Fn() -> Duration>,
 }
 
 impl BackendRegistry {
     /// Create a new backend registry
     pub fn new() -> Self {
+        let origin = Instant::now();
         Self {
             backends: HashMap::new(),
+            runners: HashMap::new(),
+            preferred: None,
+            clock: Box::new(move || origin.elapsed()),
         }
     }
 
@@ -180,6 +338,189 @@ impl BackendRegistry {
     pub fn list(&self) -> Vec<&BackendCapabilities> {
         self.backends.values().collect()
     }
+
+    /// Register a backend that `select` can run, under its capabilities' name
+    pub fn register_backend<B>(&mut self, backend: B, capabilities: BackendCapabilities)
+    where
+        B: Backend<ExecutionResult = Value> + 'static,
+        B::CompiledOutput: 'static,
+    {
+        self.runners
+            .insert(capabilities.name.clone(), Box::new(backend));
+        self.register(capabilities);
+    }
+
+    /// Make `select` always pick the backend `name`, or pick automatically
+    /// again with `None`
+    pub fn set_override(&mut self, name: Option<&str>) {
+        self.preferred = name.map(str::to_string);
+    }
+
+    /// The backend `select` is overridden to pick, if any
+    pub fn override_backend(&self) -> Option<&str> {
+        self.preferred.as_deref()
+    }
+
+    /// Pick the fastest backend registered with `register_backend` that can
+    /// run `expr`, and compile it there
+    ///
+    /// Candidates are the backends that can run `expr`, except those in
+    /// `PerformanceTier::Transpiled`, which generate code to run elsewhere.
+    /// Each candidate evaluates every sample a few times and the fastest on
+    /// average is picked. Without samples, or if every candidate fails on
+    /// them, the candidate in the fastest tier is picked instead, by name
+    /// among equals.
+    ///
+    /// An override set with `set_override` is picked without measuring,
+    /// whatever its tier. If it cannot run `expr` the candidates are tried
+    /// instead, and the override is listed in `BackendSelection::fallbacks`.
+    ///
+    /// A backend cannot run `expr` if its capabilities lack a feature `expr`
+    /// uses, its `supports` rejects `expr`, or compiling fails with
+    /// `BackendError::UnsupportedFeature`. These are all found here, before
+    /// any evaluation; other compile errors are returned as they are.
+    pub fn select(
+        &self,
+        expr: &Expr,
+        symbols: &[&str],
+        samples: &[HashMap<String, Value>],
+    ) -> BackendResult<BackendSelection<'_>> {
+        let mut fallbacks = Vec::new();
+        if let Some(name) = &self.preferred {
+            let runner = self.runners.get(name).ok_or_else(|| {
+                BackendError::BackendSpecific(format!("backend '{}' is not registered", name))
+            })?;
+            match self.prepare(name, runner.as_ref(), expr, symbols) {
+                Ok(program) => {
+                    return Ok(BackendSelection {
+                        backend: name.clone(),
+                        timings: Vec::new(),
+                        fallbacks,
+                        program,
+                    })
+                }
+                Err(BackendError::UnsupportedFeature(reason)) => {
+                    fallbacks.push((name.clone(), reason))
+                }
+                Err(e) => return Err(e),
+            }
+        }
+
+        let mut candidates: Vec<(&String, PerformanceTier, &dyn Runner)> = self
+            .runners
+            .iter()
+            .filter(|(name, _)| self.preferred.as_ref() != Some(*name))
+            .filter_map(|(name, runner)| {
+                let tier = self.backends.get(name)?.performance_tier;
+                (tier != PerformanceTier::Transpiled).then_some((name, tier, runner.as_ref()))
+            })
+            .collect();
+        candidates.sort_by_key(|(name, tier, _)| (*tier, *name));
+
+        let mut compiled = Vec::new();
+        let mut first_error = None;
+        for (name, _, runner) in candidates {
+            match self.prepare(name, runner, expr, symbols) {
+                Ok(program) => compiled.push((name, program)),
+                Err(BackendError::UnsupportedFeature(reason)) => {
+                    fallbacks.push((name.clone(), reason))
+                }
+                Err(e) => {
+                    first_error.get_or_insert(e);
+                }
+            }
+        }
+        if compiled.is_empty() {
+            return Err(first_error.unwrap_or_else(|| {
+                let reasons: Vec<String> = fallbacks
+                    .iter()
+                    .map(|(name, reason)| format!("{}: {}", name, reason))
+                    .collect();
+                BackendError::UnsupportedFeature(format!(
+                    "no registered backend can run this program ({})",
+                    reasons.join("; ")
+                ))
+            }));
+        }
+
+        let mut timings: Vec<(String, Duration)> = compiled
+            .iter()
+            .filter_map(|(name, program)| {
+                benchmark(program.as_ref(), samples, &self.clock)
+                    .map(|time| (name.to_string(), time))
+            })
+            .collect();
+        // Stable, so equal times keep tier order
+        timings.sort_by_key(|(_, time)| *time);
+
+        let index = timings
+            .first()
+            .and_then(|(fastest, _)| compiled.iter().position(|(name, _)| *name == fastest))
+            .unwrap_or(0);
+        let (name, program) = compiled.swap_remove(index);
+        Ok(BackendSelection {
+            backend: name.clone(),
+            timings,
+            fallbacks,
+            program,
+        })
+    }
+
+    /// Compile `expr` with the backend `name`
+    ///
+    /// Fails with `UnsupportedFeature` before compiling if the backend's
+    /// capabilities lack a feature `expr` uses or its `supports` rejects it.
+    fn prepare<'r>(
+        &'r self,
+        name: &str,
+        runner: &'r dyn Runner,
+        expr: &Expr,
+        symbols: &[&str],
+    ) -> BackendResult<Box<dyn Prepared + 'r>> {
+        let missing = self
+            .backends
+            .get(name)
+            .map(|capabilities| capabilities.missing_features(expr))
+            .unwrap_or_default();
+        if !missing.is_empty() {
+            return Err(BackendError::UnsupportedFeature(format!(
+                "no support for {}",
+                missing.join(", ")
+            )));
+        }
+        if !runner.can_run(expr) {
+            return Err(BackendError::UnsupportedFeature(
+                "the backend rejects this program".to_string(),
+            ));
+        }
+        runner.prepare(expr, symbols)
+    }
+}
+
+/// The mean time `program` takes per sample on `clock`, or `None` without
+/// samples, with too many to count runs of in a `u32`, or if a run fails
+fn benchmark(
+    program: &dyn Prepared,
+    samples: &[HashMap<String, Value>],
+    clock: &dyn Fn() -> Duration,
+) -> Option<Duration> {
+    if samples.is_empty() {
+        return None;
+    }
+    let runs = u32::try_from(samples.len())
+        .ok()?
+        .checked_mul(BENCHMARK_ROUNDS)?;
+    // One untimed pass, so first-run costs are not counted
+    for sample in samples {
+        program.execute(sample).ok()?;
+    }
+    let start = clock();
+    for _ in 0..BENCHMARK_ROUNDS {
+        for sample in samples {
+            program.execute(sample).ok()?;
+        }
+    }
+    Some((clock() - start) / runs)
 }
 
 impl Default for BackendRegistry {
@@ -191,6 +532,7 @@ impl Default for BackendRegistry {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use std::cell::Cell;
 
     // Mock backend for testing trait methods
     struct MockBackend;
@@ -369,4 +711,288 @@ mod tests {
         assert!(result.is_ok());
         assert_eq!(result.unwrap(), Value::Number(42.0));
     }
+
+    thread_local! {
+        /// The fake time read by `timed_registry`, advanced by `TimedBackend`
+        static NOW: Cell<Duration> = const { Cell::new(Duration::ZERO) };
+    }
+
+    /// A registry that measures candidates on the fake clock
+    fn timed_registry() -> BackendRegistry {
+        let mut registry = BackendRegistry::new();
+        registry.clock = Box::new(|| NOW.with(Cell::get));
+        registry
+    }
+
+    /// A backend that takes `delay` on the fake clock per evaluation and
+    /// returns its own name
+    struct TimedBackend {
+        name: &'static str,
+        delay: Duration,
+        numbers_only: bool,
+    }
+
+    impl TimedBackend {
+        fn register(
+            registry: &mut BackendRegistry,
+            name: &'static str,
+            delay_ms: u64,
+            tier: PerformanceTier,
+            numbers_only: bool,
+        ) {
+            let backend = TimedBackend {
+                name,
+                delay: Duration::from_millis(delay_ms),
+                numbers_only,
+            };
+            let capabilities = BackendCapabilities {
+                name: name.to_string(),
+                description: name.to_string(),
+                supported_features: FEATURES.iter().map(|f| f.to_string()).collect(),
+                performance_tier: tier,
+                requires_external_deps: false,
+            };
+            registry.register_backend(backend, capabilities);
+        }
+    }
+
+    impl Backend for TimedBackend {
+        type CompiledOutput = ();
+        type ExecutionResult = Value;
+
+        fn name(&self) -> &str {
+            self.name
+        }
+
+        fn compile(&self, _expr: &Expr, _symbols: &[&str]) -> BackendResult<()> {
+            Ok(())
+        }
+
+        fn execute(&self, _compiled: &(), data: &HashMap<String, Value>) -> BackendResult<Value> {
+            if data.contains_key("fail") {
+                return Err(BackendError::BackendSpecific("failed".to_string()));
+            }
+            NOW.with(|now| now.set(now.get() + self.delay));
+            Ok(Value::Symbol(self.name.to_string()))
+        }
+
+        fn supports(&self, expr: &Expr) -> bool {
+            !self.numbers_only || matches!(expr, Expr::Number(_))
+        }
+
+        fn description(&self) -> &str {
+            self.name
+        }
+    }
+
+    #[test]
+    fn test_select_fastest_backend() {
+        let mut registry = timed_registry();
+        TimedBackend::register(&mut registry, "slow", 2, PerformanceTier::Native, false);
+        TimedBackend::register(&mut registry, "fast", 0, PerformanceTier::Standard, false);
+        let samples = vec![HashMap::new(), HashMap::new()];
+
+        let selection = registry.select(&Expr::Number(1.0), &[], &samples).unwrap();
+        assert_eq!(selection.backend(), "fast");
+        let measured: Vec<&str> = selection
+            .timings()
+            .iter()
+            .map(|(n, _)| n.as_str())
+            .collect();
+        assert_eq!(measured, vec!["fast", "slow"]);
+        assert_eq!(selection.timings()[0].1, Duration::ZERO);
+        assert_eq!(selection.timings()[1].1, Duration::from_millis(2));
+        assert_eq!(
+            selection.execute(&HashMap::new()).unwrap(),
+            Value::Symbol("fast".to_string())
+        );
+
+        drop(selection);
+
+        // Unsupported programs and transpiled backends are not candidates
+        TimedBackend::register(&mut registry, "jit", 0, PerformanceTier::Native, true);
+        TimedBackend::register(&mut registry, "wasm", 0, PerformanceTier::Transpiled, false);
+        let selection = registry.select(&Expr::Nil, &[], &samples).unwrap();
+        let measured: Vec<&str> = selection
+            .timings()
+            .iter()
+            .map(|(n, _)| n.as_str())
+            .collect();
+        assert_eq!(measured, vec!["fast", "slow"]);
+    }
+
+    #[test]
+    fn test_select_by_tier_without_measurements() {
+        let mut registry = timed_registry();
+        TimedBackend::register(&mut registry, "b", 1, PerformanceTier::Fast, false);
+        TimedBackend::register(&mut registry, "a", 0, PerformanceTier::Standard, false);
+        TimedBackend::register(&mut registry, "c", 1, PerformanceTier::Fast, false);
+
+        let selection = registry.select(&Expr::Nil, &[], &[]).unwrap();
+        assert_eq!(selection.backend(), "b");
+        assert!(selection.timings().is_empty());
+
+        // Every candidate fails on these samples
+        let mut failing = HashMap::new();
+        failing.insert("fail".to_string(), Value::Nil);
+        let selection = registry.select(&Expr::Nil, &[], &[failing]).unwrap();
+        assert_eq!(selection.backend(), "b");
+
+        assert!(matches!(
+            BackendRegistry::new().select(&Expr::Nil, &[], &[]),
+            Err(BackendError::UnsupportedFeature(_))
+        ));
+    }
+
+    #[test]
+    fn test_select_override() {
+        let mut registry = timed_registry();
+        TimedBackend::register(&mut registry, "fast", 0, PerformanceTier::Fast, false);
+        TimedBackend::register(&mut registry, "wasm", 1, PerformanceTier::Transpiled, true);
+
+        registry.set_override(Some("wasm"));
+        assert_eq!(registry.override_backend(), Some("wasm"));
+        let selection = registry
+            .select(&Expr::Number(1.0), &[], &[HashMap::new()])
+            .unwrap();
+        assert_eq!(selection.backend(), "wasm");
+        assert!(selection.timings().is_empty());
+        drop(selection);
+
+        // Falls back when the override cannot run the program
+        let selection = registry.select(&Expr::Nil, &[], &[]).unwrap();
+        assert_eq!(selection.backend(), "fast");
+        assert_eq!(
+            selection.fallbacks(),
+            &[(
+                "wasm".to_string(),
+                "the backend rejects this program".to_string()
+            )]
+        );
+        drop(selection);
+
+        registry.set_override(Some("missing"));
+        assert!(matches!(
+            registry.select(&Expr::Nil, &[], &[]),
+            Err(BackendError::BackendSpecific(_))
+        ));
+
+        registry.set_override(None);
+        assert_eq!(
+            registry.select(&Expr::Nil, &[], &[]).unwrap().backend(),
+            "fast"
+        );
+    }
+
+    #[test]
+    fn test_performance_tier_order() {
+        assert!(PerformanceTier::Native < PerformanceTier::Fast);
+        assert!(PerformanceTier::Fast < PerformanceTier::Standard);
+        assert!(PerformanceTier::Standard < PerformanceTier::Transpiled);
+    }
+
+    fn parse_expr(source: &str) -> Expr {
+        crate::compile(source, &["ok"]).unwrap().ast().clone()
+    }
+
+    #[test]
+    fn test_required_features() {
+        assert_eq!(
+            required_features(&parse_expr(
+                r#"let x = [1, 2] in if not a and x | size > 1 :ok else upcase("no") end"#
+            )),
+            vec![
+                "arrays",
+                "comparisons",
+                "function_calls",
+                "if_expressions",
+                "let_bindings",
+                "logic",
+                "numbers",
+                "strings",
+                "symbols",
+            ]
+        );
+        assert!(required_features(&parse_expr("a.b")).is_empty());
+        let pipe = Expr::Pipe {
+            left: Box::new(Expr::Variable(vec!["a".to_string()])),
+            right: Box::new(Expr::Variable(vec!["abs".to_string()])),
+        };
+        assert_eq!(required_features(&pipe), vec!["pipe_expressions"]);
+
+        let capabilities = BackendCapabilities {
+            name: "numeric".to_string(),
+            description: "numeric".to_string(),
+            supported_features: vec!["numbers".to_string(), "arithmetic".to_string()],
+            performance_tier: PerformanceTier::Native,
+            requires_external_deps: false,
+        };
+        assert!(capabilities
+            .missing_features(&parse_expr("-a * 2"))
+            .is_empty());
+        assert_eq!(
+            capabilities.missing_features(&parse_expr(r#"a * 2 == {"k": "v"}"#)),
+            vec!["comparisons", "dictionaries", "strings"]
+        );
+    }
+
+    #[test]
+    fn test_select_falls_back_on_capabilities() {
+        let mut registry = timed_registry();
+        TimedBackend::register(
+            &mut registry,
+            "interpreter",
+            0,
+            PerformanceTier::Standard,
+            false,
+        );
+        registry.register_backend(
+            TimedBackend {
+                name: "jit",
+                delay: Duration::ZERO,
+                numbers_only: false,
+            },
+            BackendCapabilities {
+                name: "jit".to_string(),
+                description: "jit".to_string(),
+                supported_features: vec!["numbers".to_string(), "arithmetic".to_string()],
+                performance_tier: PerformanceTier::Native,
+                requires_external_deps: true,
+            },
+        );
+
+        let selection = registry.select(&parse_expr("1 + 2"), &[], &[]).unwrap();
+        assert_eq!(selection.backend(), "jit");
+        assert!(selection.fallbacks().is_empty());
+        drop(selection);
+
+        let selection = registry
+            .select(&parse_expr(r#"upcase("a")"#), &[], &[])
+            .unwrap();
+        assert_eq!(selection.backend(), "interpreter");
+        assert_eq!(
+            selection.fallbacks(),
+            &[(
+                "jit".to_string(),
+                "no support for function_calls, strings".to_string()
+            )]
+        );
+        drop(selection);
+
+        registry.set_override(Some("jit"));
+        let selection = registry.select(&parse_expr("[1]"), &[], &[]).unwrap();
+        assert_eq!(selection.backend(), "interpreter");
+        assert_eq!(selection.fallbacks().len(), 1);
+        drop(selection);
+
+        // No backend left to fall back to
+        let mut registry = timed_registry();
+        TimedBackend::register(&mut registry, "jit", 0, PerformanceTier::Native, true);
+        let error = registry.select(&parse_expr("[1]"), &[], &[]).err().unwrap();
+        assert_eq!(
+            error.to_string(),
+            "Unsupported feature: no registered backend can run this program \
+             (jit: the backend rejects this program)"
+        );
+    }
 }
@@ -150,7 +150,7 @@ impl Backend for DirectInterpreterBackend {
     ) -> BackendResult<Self::ExecutionResult> {
         use crate::{eval_expr, Context};
 
-        let context = Context::new(data.clone());
+        let context = Context::borrowed(data);
         eval_expr(&compiled.expr, &context).map_err(BackendError::EvalError)
     }
 
@@ -229,7 +229,7 @@ mod tests {
         user.insert("age".to_string(), Value::Number(25.0));
 
         let mut data = HashMap::new();
-        data.insert("user".to_string(), Value::Dictionary(user));
+        data.insert("user".to_string(), Value::Dictionary(user.into()));
 
         let compiled = backend.compile(&expr, &[]).unwrap();
         let result = backend.execute(&compiled, &data).unwrap();
@@ -260,11 +260,7 @@ mod tests {
 
         assert_eq!(
             result,
-            Value::Array(vec![
-                Value::Number(1.0),
-                Value::Number(2.0),
-                Value::Number(3.0),
-            ])
+            Value::Array(vec![Value::Number(1.0), Value::Number(2.0), Value::Number(3.0),].into())
         );
     }
 
@@ -278,7 +274,7 @@ mod tests {
         let result = backend.execute(&compiled, &data).unwrap();
 
         if let Value::Dictionary(dict) = result {
-            assert_eq!(dict.get("name"), Some(&Value::String("Alice".to_string())));
+            assert_eq!(dict.get("name"), Some(&Value::String("Alice".into())));
             assert_eq!(dict.get("age"), Some(&Value::Number(30.0)));
         } else {
             panic!("Expected dictionary");
@@ -0,0 +1,1083 @@
+//! WebAssembly backend
+//!
+//! Compiles a program into a standalone WebAssembly module, so a rule can run
+//! in a browser or an edge runtime without this crate. The module imports
+//! nothing and exports a single function, `evaluate`; the standard library
+//! functions the program calls are compiled into the module beside it.
+//!
+//! This is a subset of the language. Strings, collections, `nil` and most of
+//! the standard library are not compiled yet, so those programs are rejected
+//! rather than given the interpreter's semantics, and `execute` cannot run a
+//! module: that needs a WebAssembly host, which this crate does not embed.
+//!
+//! Only programs over numbers, booleans and symbols compile:
+//!
+//! - Each data path the program reads is a parameter of `evaluate`, in the
+//!   order of `WasmModule::parameters`. There is no `nil`, so the host must
+//!   pass every one.
+//! - Numbers are `f64`, integers included. The interpreter keeps integers
+//!   as exact `i64`, so where it returns `3` this returns `3.0`, and
+//!   integer arithmetic that passes 2^53 rounds here where the interpreter
+//!   stays exact. Integer literals past 2^53 are rejected.
+//! - Booleans are `i32` 0 or 1, and symbols are `i32` indexes into
+//!   `WasmModule::symbols`.
+//! - Whatever is an error in the interpreter, such as dividing by zero or
+//!   the square root of a negative number, traps.
+//!
+//! Anything else, such as strings, collections, `%`, or an `if` whose
+//! condition is not a boolean, is rejected when compiling with
+//! `BackendError::UnsupportedFeature`. The module also carries its
+//! parameters, result type and symbols as JSON in a custom section named
+//! `amoskeag`, for hosts that only have the bytes.
+
+use super::{Backend, BackendCapabilities, BackendError, BackendResult, PerformanceTier};
+use crate::introspect::collect_variables;
+use crate::registry::FunctionRegistry;
+use crate::validate_ast;
+use amoskeag_parser::{BinaryOp, Expr, UnaryOp};
+use amoskeag_stdlib_operators::Value;
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::fmt;
+
+/// The type of a parameter or the result of `evaluate`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WasmType {
+    /// An `f64`
+    Number,
+    /// An `i32`, 1 for true and 0 for false
+    Boolean,
+    /// An `i32` index into `WasmModule::symbols`
+    Symbol,
+}
+
+impl WasmType {
+    /// The WebAssembly value type it is passed as
+    fn val_type(self) -> u8 {
+        match self {
+            WasmType::Number => F64,
+            WasmType::Boolean | WasmType::Symbol => I32,
+        }
+    }
+}
+
+impl fmt::Display for WasmType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            WasmType::Number => write!(f, "number"),
+            WasmType::Boolean => write!(f, "boolean"),
+            WasmType::Symbol => write!(f, "symbol"),
+        }
+    }
+}
+
+/// A program compiled to a WebAssembly module
+#[derive(Debug, Clone)]
+pub struct WasmModule {
+    bytes: Vec<u8>,
+    parameters: Vec<(String, WasmType)>,
+    result: WasmType,
+    symbols: Vec<String>,
+}
+
+impl WasmModule {
+    /// The module in the WebAssembly binary format
+    pub fn bytes(&self) -> &[u8] {
+        &self.bytes
+    }
+
+    /// The data paths `evaluate` takes, in parameter order
+    pub fn parameters(&self) -> &[(String, WasmType)] {
+        &self.parameters
+    }
+
+    /// What `evaluate` returns
+    pub fn result(&self) -> WasmType {
+        self.result
+    }
+
+    /// The declared symbols, sorted; a symbol is passed as its index here
+    pub fn symbols(&self) -> &[String] {
+        &self.symbols
+    }
+}
+
+/// The WebAssembly backend
+pub struct WasmBackend;
+
+impl WasmBackend {
+    /// Create a new WebAssembly backend
+    pub fn new() -> Self {
+        Self
+    }
+
+    /// Get the capabilities of this backend
+    pub fn capabilities() -> BackendCapabilities {
+        BackendCapabilities {
+            name: "wasm".to_string(),
+            description: "Standalone WebAssembly modules for browsers and edge runtimes"
+                .to_string(),
+            supported_features: vec![
+                "numbers".to_string(),
+                "booleans".to_string(),
+                "symbols".to_string(),
+                "arithmetic".to_string(),
+                "comparisons".to_string(),
+                "logic".to_string(),
+                "if_expressions".to_string(),
+                "let_bindings".to_string(),
+                "function_calls".to_string(),
+                "pipe_expressions".to_string(),
+            ],
+            performance_tier: PerformanceTier::Transpiled,
+            requires_external_deps: false,
+        }
+    }
+}
+
+impl Default for WasmBackend {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Backend for WasmBackend {
+    type CompiledOutput = WasmModule;
+    type ExecutionResult = Value;
+
+    fn name(&self) -> &str {
+        "wasm"
+    }
+
+    fn compile(&self, expr: &Expr, symbols: &[&str]) -> BackendResult<Self::CompiledOutput> {
+        let declared: HashSet<String> = symbols.iter().map(|s| s.to_string()).collect();
+        validate_ast(expr, &declared, &FunctionRegistry::new())?;
+        let mut symbols: Vec<String> = declared.into_iter().collect();
+        symbols.sort();
+        Codegen::new(expr, symbols).module(expr)
+    }
+
+    fn execute(
+        &self,
+        _compiled: &Self::CompiledOutput,
+        _data: &HashMap<String, Value>,
+    ) -> BackendResult<Self::ExecutionResult> {
+        Err(BackendError::UnsupportedFeature(
+            "this crate has no WebAssembly host; load WasmModule::bytes into one".to_string(),
+        ))
+    }
+
+    fn supports(&self, expr: &Expr) -> bool {
+        Codegen::new(expr, Vec::new()).module(expr).is_ok()
+    }
+
+    fn description(&self) -> &str {
+        "Standalone WebAssembly modules for numeric, boolean and symbol rules"
+    }
+}
+
+fn unsupported(feature: impl Into<String>) -> BackendError {
+    BackendError::UnsupportedFeature(feature.into())
+}
+
+/// A standard library function compiled into the module as its own function
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Helper {
+    Divide,
+    Sqrt,
+    Min,
+    Max,
+    Sign,
+    Clamp,
+}
+
+impl Helper {
+    /// How many `f64` arguments it takes; it returns one `f64`
+    fn arity(self) -> usize {
+        match self {
+            Helper::Sqrt | Helper::Sign => 1,
+            Helper::Divide | Helper::Min | Helper::Max => 2,
+            Helper::Clamp => 3,
+        }
+    }
+
+    /// Append its instructions, which match the interpreter on NaN, zero
+    /// and errors
+    fn body(self, code: &mut Vec<u8>) {
+        match self {
+            Helper::Divide => {
+                // Trap on a zero divisor
+                local_get(code, 1);
+                f64_const(code, 0.0);
+                code.extend([F64_EQ, IF, EMPTY, UNREACHABLE, END]);
+                local_get(code, 0);
+                local_get(code, 1);
+                code.push(F64_DIV);
+            }
+            Helper::Sqrt => {
+                // Trap on a negative argument
+                local_get(code, 0);
+                f64_const(code, 0.0);
+                code.extend([F64_LT, IF, EMPTY, UNREACHABLE, END]);
+                local_get(code, 0);
+                code.push(F64_SQRT);
+            }
+            Helper::Min | Helper::Max => {
+                // b if b is past a or a is NaN, else a; f64.min and f64.max
+                // would return NaN
+                local_get(code, 1);
+                local_get(code, 0);
+                local_get(code, 1);
+                local_get(code, 0);
+                code.push(if self == Helper::Min { F64_LT } else { F64_GT });
+                local_get(code, 0);
+                local_get(code, 0);
+                code.extend([F64_NE, I32_OR, SELECT]);
+            }
+            Helper::Sign => {
+                // NaN for NaN, 0 for either zero, else 1 with the sign of x
+                local_get(code, 0);
+                local_get(code, 0);
+                code.extend([F64_NE, IF, F64]);
+                local_get(code, 0);
+                code.push(ELSE);
+                f64_const(code, 1.0);
+                local_get(code, 0);
+                code.push(F64_COPYSIGN);
+                f64_const(code, 0.0);
+                local_get(code, 0);
+                f64_const(code, 0.0);
+                code.extend([F64_NE, SELECT, END]);
+            }
+            Helper::Clamp => {
+                // Trap unless lo <= hi, then hi if x > hi, lo if x < lo, else x
+                local_get(code, 1);
+                local_get(code, 2);
+                code.extend([F64_LE, I32_EQZ, IF, EMPTY, UNREACHABLE, END]);
+                local_get(code, 2);
+                local_get(code, 1);
+                local_get(code, 0);
+                local_get(code, 0);
+                local_get(code, 1);
+                code.extend([F64_LT, SELECT]);
+                local_get(code, 0);
+                local_get(code, 2);
+                code.extend([F64_GT, SELECT]);
+            }
+        }
+        code.push(END);
+    }
+}
+
+/// How a standard library function is compiled
+enum Builtin {
+    /// A single `f64` instruction on one argument
+    Instruction(u8),
+    Helper(Helper),
+}
+
+/// Builds the body of `evaluate` and collects the helpers it calls
+struct Codegen<'e> {
+    symbols: Vec<String>,
+    /// Data paths, with the type each was first used as
+    parameters: Vec<(String, Option<WasmType>)>,
+    /// `let` bindings, numbered after the parameters
+    locals: Vec<WasmType>,
+    /// The `let` names in scope, innermost last, with their local index
+    scope: Vec<(&'e str, u32, WasmType)>,
+    helpers: Vec<Helper>,
+    code: Vec<u8>,
+}
+
+impl<'e> Codegen<'e> {
+    fn new(expr: &'e Expr, symbols: Vec<String>) -> Self {
+        let mut paths = BTreeSet::new();
+        collect_variables(expr, &mut Vec::new(), &mut paths);
+        Codegen {
+            symbols,
+            parameters: paths.into_iter().map(|path| (path, None)).collect(),
+            locals: Vec::new(),
+            scope: Vec::new(),
+            helpers: Vec::new(),
+            code: Vec::new(),
+        }
+    }
+
+    /// Compile `expr` and assemble the module
+    fn module(mut self, expr: &'e Expr) -> BackendResult<WasmModule> {
+        let result = self.emit(expr, None)?;
+        self.code.push(END);
+
+        let parameters: Vec<(String, WasmType)> = self
+            .parameters
+            .into_iter()
+            .map(|(path, ty)| (path, ty.unwrap_or(WasmType::Number)))
+            .collect();
+
+        let param_types: Vec<u8> = parameters.iter().map(|(_, ty)| ty.val_type()).collect();
+        let mut types = vec![func_type(&param_types, result.val_type())];
+        let mut bodies = Vec::new();
+
+        let mut body = Vec::new();
+        unsigned(&mut body, self.locals.len() as u64);
+        for local in &self.locals {
+            body.push(1);
+            body.push(local.val_type());
+        }
+        body.extend(&self.code);
+        bodies.push(body);
+
+        for helper in &self.helpers {
+            types.push(func_type(&vec![F64; helper.arity()], F64));
+            let mut body = vec![0];
+            helper.body(&mut body);
+            bodies.push(body);
+        }
+
+        let mut bytes = b"\0asm".to_vec();
+        bytes.extend(1u32.to_le_bytes());
+
+        let mut contents = Vec::new();
+        unsigned(&mut contents, types.len() as u64);
+        types.iter().for_each(|ty| contents.extend(ty));
+        section(&mut bytes, TYPE_SECTION, &contents);
+
+        // Function i has type i
+        let mut contents = Vec::new();
+        unsigned(&mut contents, types.len() as u64);
+        for index in 0..types.len() {
+            unsigned(&mut contents, index as u64);
+        }
+        section(&mut bytes, FUNCTION_SECTION, &contents);
+
+        let mut contents = vec![1];
+        name(&mut contents, "evaluate");
+        contents.extend([EXPORT_FUNC, 0]);
+        section(&mut bytes, EXPORT_SECTION, &contents);
+
+        let mut contents = Vec::new();
+        unsigned(&mut contents, bodies.len() as u64);
+        for body in &bodies {
+            unsigned(&mut contents, body.len() as u64);
+            contents.extend(body);
+        }
+        section(&mut bytes, CODE_SECTION, &contents);
+
+        let metadata = serde_json::json!({
+            "parameters": parameters
+                .iter()
+                .map(|(path, ty)| serde_json::json!({ "name": path, "type": ty.to_string() }))
+                .collect::<Vec<_>>(),
+            "result": result.to_string(),
+            "symbols": self.symbols,
+        });
+        let mut contents = Vec::new();
+        name(&mut contents, "amoskeag");
+        contents.extend(metadata.to_string().into_bytes());
+        section(&mut bytes, CUSTOM_SECTION, &contents);
+
+        Ok(WasmModule {
+            bytes,
+            parameters,
+            result,
+            symbols: self.symbols,
+        })
+    }
+
+    /// Emit `expr` and fail unless it has type `ty`
+    fn emit_as(&mut self, expr: &'e Expr, ty: WasmType) -> BackendResult<()> {
+        let got = self.emit(expr, Some(ty))?;
+        if got != ty {
+            return Err(unsupported(format!("a {} where a {} is needed", got, ty)));
+        }
+        Ok(())
+    }
+
+    /// Emit the instructions that leave the value of `expr` on the stack
+    ///
+    /// `want` is the type the caller needs, if it knows; a data path first
+    /// read where no type is known is taken to be a number.
+    fn emit(&mut self, expr: &'e Expr, want: Option<WasmType>) -> BackendResult<WasmType> {
+        match expr {
+            Expr::Number(n) => {
+                f64_const(&mut self.code, *n);
+                Ok(WasmType::Number)
+            }
+
+            Expr::Integer(n) => {
+                if n.unsigned_abs() > 1 << 53 {
+                    return Err(unsupported(format!(
+                        "integer {} does not fit an f64 exactly",
+                        n
+                    )));
+                }
+                f64_const(&mut self.code, *n as f64);
+                Ok(WasmType::Number)
+            }
+
+            Expr::Boolean(b) => {
+                self.code.push(I32_CONST);
+                signed(&mut self.code, *b as i64);
+                Ok(WasmType::Boolean)
+            }
+
+            Expr::Symbol(symbol) => {
+                let index = match self.symbols.iter().position(|s| s == symbol) {
+                    Some(index) => index,
+                    None => {
+                        self.symbols.push(symbol.clone());
+                        self.symbols.len() - 1
+                    }
+                };
+                self.code.push(I32_CONST);
+                signed(&mut self.code, index as i64);
+                Ok(WasmType::Symbol)
+            }
+
+            Expr::Variable(path) => {
+                let bound = self.scope.iter().rev().find(|(name, ..)| *name == path[0]);
+                let (index, ty) = match bound {
+                    Some(_) if path.len() > 1 => {
+                        return Err(unsupported("reading a field of a let binding"))
+                    }
+                    Some(&(_, index, ty)) => (index, ty),
+                    None => {
+                        let dotted = path.join(".");
+                        let index = self
+                            .parameters
+                            .iter()
+                            .position(|(p, _)| *p == dotted)
+                            .expect("data paths are collected before emitting");
+                        let ty = *self.parameters[index]
+                            .1
+                            .get_or_insert(want.unwrap_or(WasmType::Number));
+                        (index as u32, ty)
+                    }
+                };
+                local_get(&mut self.code, index);
+                Ok(ty)
+            }
+
+            Expr::Let { name, value, body } => {
+                let ty = self.emit(value, None)?;
+                let index = (self.parameters.len() + self.locals.len()) as u32;
+                self.locals.push(ty);
+                self.code.push(LOCAL_SET);
+                unsigned(&mut self.code, index as u64);
+                self.scope.push((name, index, ty));
+                let result = self.emit(body, want);
+                self.scope.pop();
+                result
+            }
+
+            Expr::If {
+                condition,
+                then_branch,
+                else_branch,
+            } => {
+                self.emit_as(condition, WasmType::Boolean)?;
+                self.code.push(IF);
+                let block_type = self.code.len();
+                self.code.push(EMPTY);
+                let ty = self.emit(then_branch, want)?;
+                self.code[block_type] = ty.val_type();
+                self.code.push(ELSE);
+                self.emit_as(else_branch, ty)?;
+                self.code.push(END);
+                Ok(ty)
+            }
+
+            Expr::Binary { op, left, right } => self.binary(*op, left, right),
+
+            Expr::Unary { op, operand } => match op {
+                UnaryOp::Not => {
+                    self.emit_as(operand, WasmType::Boolean)?;
+                    self.code.push(I32_EQZ);
+                    Ok(WasmType::Boolean)
+                }
+                UnaryOp::Negate => {
+                    self.emit_as(operand, WasmType::Number)?;
+                    self.code.push(F64_NEG);
+                    Ok(WasmType::Number)
+                }
+            },
+
+            Expr::FunctionCall { name, args } => self.call(name, args.iter().collect()),
+
+            Expr::Pipe { left, right } => match right.as_ref() {
+                Expr::FunctionCall { name, args } => {
+                    let args = std::iter::once(left.as_ref()).chain(args).collect();
+                    self.call(name, args)
+                }
+                Expr::Variable(path) if path.len() == 1 => self.call(&path[0], vec![left]),
+                _ => Err(unsupported("a pipe into something other than a function")),
+            },
+
+            Expr::String(_) => Err(unsupported("strings")),
+            Expr::Nil => Err(unsupported("nil")),
+            Expr::Array(_) => Err(unsupported("arrays")),
+            Expr::Dictionary(_) => Err(unsupported("dictionaries")),
+        }
+    }
+
+    fn binary(&mut self, op: BinaryOp, left: &'e Expr, right: &'e Expr) -> BackendResult<WasmType> {
+        let instruction = match op {
+            BinaryOp::Add => F64_ADD,
+            BinaryOp::Subtract => F64_SUB,
+            BinaryOp::Multiply => F64_MUL,
+            BinaryOp::Less => F64_LT,
+            BinaryOp::Greater => F64_GT,
+            BinaryOp::LessEqual => F64_LE,
+            BinaryOp::GreaterEqual => F64_GE,
+
+            BinaryOp::Divide => {
+                self.emit_as(left, WasmType::Number)?;
+                self.emit_as(right, WasmType::Number)?;
+                self.call_helper(Helper::Divide);
+                return Ok(WasmType::Number);
+            }
+
+            BinaryOp::Equal | BinaryOp::NotEqual => {
+                // A data path compared with a symbol is a symbol
+                let hint = self.hint(left).or_else(|| self.hint(right));
+                let ty = self.emit(left, hint)?;
+                self.emit_as(right, ty)?;
+                self.code.push(match (op, ty) {
+                    (BinaryOp::Equal, WasmType::Number) => F64_EQ,
+                    (BinaryOp::Equal, _) => I32_EQ,
+                    (_, WasmType::Number) => F64_NE,
+                    (_, _) => I32_NE,
+                });
+                return Ok(WasmType::Boolean);
+            }
+
+            // Both sides are evaluated, as in the interpreter
+            BinaryOp::And | BinaryOp::Or => {
+                self.emit_as(left, WasmType::Boolean)?;
+                self.emit_as(right, WasmType::Boolean)?;
+                self.code
+                    .push(if op == BinaryOp::And { I32_AND } else { I32_OR });
+                return Ok(WasmType::Boolean);
+            }
+
+            BinaryOp::Modulo | BinaryOp::Power => {
+                return Err(unsupported(format!("the {} operator", op)))
+            }
+        };
+
+        self.emit_as(left, WasmType::Number)?;
+        self.emit_as(right, WasmType::Number)?;
+        self.code.push(instruction);
+        Ok(match op {
+            BinaryOp::Add | BinaryOp::Subtract | BinaryOp::Multiply => WasmType::Number,
+            _ => WasmType::Boolean,
+        })
+    }
+
+    /// Emit a call to the standard library function `name`
+    fn call(&mut self, name: &str, args: Vec<&'e Expr>) -> BackendResult<WasmType> {
+        let builtin = match name {
+            "abs" => Builtin::Instruction(F64_ABS),
+            "ceil" => Builtin::Instruction(F64_CEIL),
+            "floor" => Builtin::Instruction(F64_FLOOR),
+            "trunc" => Builtin::Instruction(F64_TRUNC),
+            "sqrt" => Builtin::Helper(Helper::Sqrt),
+            "min" => Builtin::Helper(Helper::Min),
+            "max" => Builtin::Helper(Helper::Max),
+            "sign" => Builtin::Helper(Helper::Sign),
+            "clamp" => Builtin::Helper(Helper::Clamp),
+            _ => return Err(unsupported(format!("the function '{}'", name))),
+        };
+        let arity = match builtin {
+            Builtin::Instruction(_) => 1,
+            Builtin::Helper(helper) => helper.arity(),
+        };
+        if args.len() != arity {
+            return Err(unsupported(format!(
+                "'{}' called with {} arguments",
+                name,
+                args.len()
+            )));
+        }
+
+        for arg in args {
+            self.emit_as(arg, WasmType::Number)?;
+        }
+        match builtin {
+            Builtin::Instruction(instruction) => self.code.push(instruction),
+            Builtin::Helper(helper) => self.call_helper(helper),
+        }
+        Ok(WasmType::Number)
+    }
+
+    /// Emit a call to `helper`, adding it to the module on first use
+    fn call_helper(&mut self, helper: Helper) {
+        let position = match self.helpers.iter().position(|h| *h == helper) {
+            Some(position) => position,
+            None => {
+                self.helpers.push(helper);
+                self.helpers.len() - 1
+            }
+        };
+        // Function 0 is `evaluate`
+        self.code.push(CALL);
+        unsigned(&mut self.code, position as u64 + 1);
+    }
+
+    /// The type `expr` has, if it can be told without emitting it
+    fn hint(&self, expr: &Expr) -> Option<WasmType> {
+        match expr {
+            Expr::Number(_) | Expr::Integer(_) | Expr::FunctionCall { .. } | Expr::Pipe { .. } => {
+                Some(WasmType::Number)
+            }
+            Expr::Boolean(_) => Some(WasmType::Boolean),
+            Expr::Symbol(_) => Some(WasmType::Symbol),
+            Expr::Variable(path) => {
+                match self.scope.iter().rev().find(|(name, ..)| *name == path[0]) {
+                    Some(&(_, _, ty)) => Some(ty),
+                    None => {
+                        let dotted = path.join(".");
+                        self.parameters
+                            .iter()
+                            .find(|(p, _)| *p == dotted)
+                            .and_then(|(_, ty)| *ty)
+                    }
+                }
+            }
+            Expr::If {
+                then_branch,
+                else_branch,
+                ..
+            } => self.hint(then_branch).or_else(|| self.hint(else_branch)),
+            Expr::Binary { op, .. } => Some(match op {
+                BinaryOp::Add
+                | BinaryOp::Subtract
+                | BinaryOp::Multiply
+                | BinaryOp::Divide
+                | BinaryOp::Modulo
+                | BinaryOp::Power => WasmType::Number,
+                _ => WasmType::Boolean,
+            }),
+            Expr::Unary { op, .. } => Some(match op {
+                UnaryOp::Not => WasmType::Boolean,
+                UnaryOp::Negate => WasmType::Number,
+            }),
+            _ => None,
+        }
+    }
+}
+
+fn local_get(code: &mut Vec<u8>, index: u32) {
+    code.push(LOCAL_GET);
+    unsigned(code, index as u64);
+}
+
+fn f64_const(code: &mut Vec<u8>, value: f64) {
+    code.push(F64_CONST);
+    code.extend(value.to_le_bytes());
+}
+
+/// Encode a function type with `params` and one result
+fn func_type(params: &[u8], result: u8) -> Vec<u8> {
+    let mut ty = vec![FUNC_TYPE];
+    unsigned(&mut ty, params.len() as u64);
+    ty.extend(params);
+    ty.extend([1, result]);
+    ty
+}
+
+/// Append a section with its id and size
+fn section(bytes: &mut Vec<u8>, id: u8, contents: &[u8]) {
+    bytes.push(id);
+    unsigned(bytes, contents.len() as u64);
+    bytes.extend(contents);
+}
+
+/// Append a length-prefixed UTF-8 name
+fn name(bytes: &mut Vec<u8>, name: &str) {
+    unsigned(bytes, name.len() as u64);
+    bytes.extend(name.as_bytes());
+}
+
+/// Append `value` as unsigned LEB128
+fn unsigned(bytes: &mut Vec<u8>, mut value: u64) {
+    loop {
+        let byte = (value & 0x7f) as u8;
+        value >>= 7;
+        if value == 0 {
+            bytes.push(byte);
+            return;
+        }
+        bytes.push(byte | 0x80);
+    }
+}
+
+/// Append `value` as signed LEB128
+fn signed(bytes: &mut Vec<u8>, mut value: i64) {
+    loop {
+        let byte = (value & 0x7f) as u8;
+        value >>= 7;
+        let sign_bit = byte & 0x40 != 0;
+        if (value == 0 && !sign_bit) || (value == -1 && sign_bit) {
+            bytes.push(byte);
+            return;
+        }
+        bytes.push(byte | 0x80);
+    }
+}
+
+// Section ids
+const CUSTOM_SECTION: u8 = 0;
+const TYPE_SECTION: u8 = 1;
+const FUNCTION_SECTION: u8 = 3;
+const EXPORT_SECTION: u8 = 7;
+const CODE_SECTION: u8 = 10;
+
+// Types
+const I32: u8 = 0x7f;
+const F64: u8 = 0x7c;
+const FUNC_TYPE: u8 = 0x60;
+const EMPTY: u8 = 0x40;
+const EXPORT_FUNC: u8 = 0x00;
+
+// Instructions
+const UNREACHABLE: u8 = 0x00;
+const IF: u8 = 0x04;
+const ELSE: u8 = 0x05;
+const END: u8 = 0x0b;
+const CALL: u8 = 0x10;
+const SELECT: u8 = 0x1b;
+const LOCAL_GET: u8 = 0x20;
+const LOCAL_SET: u8 = 0x21;
+const I32_CONST: u8 = 0x41;
+const F64_CONST: u8 = 0x44;
+const I32_EQZ: u8 = 0x45;
+const I32_EQ: u8 = 0x46;
+const I32_NE: u8 = 0x47;
+const F64_EQ: u8 = 0x61;
+const F64_NE: u8 = 0x62;
+const F64_LT: u8 = 0x63;
+const F64_GT: u8 = 0x64;
+const F64_LE: u8 = 0x65;
+const F64_GE: u8 = 0x66;
+const I32_AND: u8 = 0x71;
+const I32_OR: u8 = 0x72;
+const F64_ABS: u8 = 0x99;
+const F64_NEG: u8 = 0x9a;
+const F64_CEIL: u8 = 0x9b;
+const F64_FLOOR: u8 = 0x9c;
+const F64_TRUNC: u8 = 0x9d;
+const F64_SQRT: u8 = 0x9f;
+const F64_ADD: u8 = 0xa0;
+const F64_SUB: u8 = 0xa1;
+const F64_MUL: u8 = 0xa2;
+const F64_DIV: u8 = 0xa3;
+const F64_COPYSIGN: u8 = 0xa6;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use amoskeag_lexer::Lexer;
+    use amoskeag_parser::Parser;
+
+    fn parse_expr(source: &str) -> Expr {
+        let mut lexer = Lexer::new(source);
+        let tokens = lexer.tokenize().unwrap();
+        let mut parser = Parser::new(tokens);
+        parser.parse().unwrap()
+    }
+
+    fn compile_wasm(source: &str, symbols: &[&str]) -> BackendResult<WasmModule> {
+        WasmBackend::new().compile(&parse_expr(source), symbols)
+    }
+
+    /// The sections of a module, by id, after the header
+    fn sections(bytes: &[u8]) -> Vec<(u8, Vec<u8>)> {
+        let mut sections = Vec::new();
+        let mut rest = &bytes[8..];
+        while let Some((&id, tail)) = rest.split_first() {
+            let (mut size, mut shift, mut read) = (0usize, 0, 0);
+            loop {
+                let byte = tail[read];
+                size |= ((byte & 0x7f) as usize) << shift;
+                shift += 7;
+                read += 1;
+                if byte & 0x80 == 0 {
+                    break;
+                }
+            }
+            sections.push((id, tail[read..read + size].to_vec()));
+            rest = &tail[read + size..];
+        }
+        sections
+    }
+
+    fn section_of(module: &WasmModule, id: u8) -> Vec<u8> {
+        sections(module.bytes())
+            .into_iter()
+            .find(|(section, _)| *section == id)
+            .map(|(_, contents)| contents)
+            .unwrap()
+    }
+
+    #[test]
+    fn test_wasm_module_layout() {
+        let module = compile_wasm("1 + 2", &[]).unwrap();
+
+        assert_eq!(&module.bytes()[..8], b"\0asm\x01\0\0\0");
+        let ids: Vec<u8> = sections(module.bytes()).iter().map(|(id, _)| *id).collect();
+        assert_eq!(ids, vec![1, 3, 7, 10, 0]);
+
+        // One function, no locals: 1.0 + 2.0
+        let mut body = vec![0, F64_CONST];
+        body.extend(1.0f64.to_le_bytes());
+        body.push(F64_CONST);
+        body.extend(2.0f64.to_le_bytes());
+        body.extend([F64_ADD, END]);
+        let mut code = vec![1, body.len() as u8];
+        code.extend(body);
+        assert_eq!(section_of(&module, CODE_SECTION), code);
+
+        let mut export = vec![1, 8];
+        export.extend(b"evaluate");
+        export.extend([EXPORT_FUNC, 0]);
+        assert_eq!(section_of(&module, EXPORT_SECTION), export);
+    }
+
+    #[test]
+    fn test_wasm_parameters_and_symbols() {
+        let module = compile_wasm(
+            "if applicant.score >= 700 and not applicant.flagged :approve else :deny end",
+            &["deny", "approve"],
+        )
+        .unwrap();
+
+        assert_eq!(
+            module.parameters(),
+            &[
+                ("applicant.flagged".to_string(), WasmType::Boolean),
+                ("applicant.score".to_string(), WasmType::Number),
+            ]
+        );
+        assert_eq!(module.result(), WasmType::Symbol);
+        assert_eq!(module.symbols(), &["approve", "deny"]);
+
+        // evaluate: (i32 flagged, f64 score) -> i32 symbol
+        let types = section_of(&module, TYPE_SECTION);
+        assert_eq!(types, vec![1, FUNC_TYPE, 2, I32, F64, 1, I32]);
+
+        let custom = section_of(&module, CUSTOM_SECTION);
+        let metadata: serde_json::Value = serde_json::from_slice(&custom[9..]).unwrap();
+        assert_eq!(&custom[..9], b"\x08amoskeag");
+        assert_eq!(
+            metadata,
+            serde_json::json!({
+                "parameters": [
+                    { "name": "applicant.flagged", "type": "boolean" },
+                    { "name": "applicant.score", "type": "number" },
+                ],
+                "result": "symbol",
+                "symbols": ["approve", "deny"],
+            })
+        );
+
+        // A data path compared with a symbol is passed as a symbol
+        let module = compile_wasm("status == :active", &["active"]).unwrap();
+        assert_eq!(
+            module.parameters(),
+            &[("status".to_string(), WasmType::Symbol)]
+        );
+        assert_eq!(module.result(), WasmType::Boolean);
+    }
+
+    #[test]
+    fn test_wasm_embeds_only_called_helpers() {
+        let functions = |source| section_of(&compile_wasm(source, &[]).unwrap(), FUNCTION_SECTION);
+
+        assert_eq!(functions("abs(a) + floor(b)"), vec![1, 0]);
+        assert_eq!(functions("a / b + a / 2"), vec![2, 0, 1]);
+        assert_eq!(
+            functions("min(a, b) / max(a, b) | clamp(0, 1)"),
+            vec![5, 0, 1, 2, 3, 4]
+        );
+    }
+
+    #[test]
+    fn test_wasm_let_bindings_are_locals() {
+        let module = compile_wasm("let x = a * 2 in x + x", &[]).unwrap();
+        let code = section_of(&module, CODE_SECTION);
+
+        // One local f64, numbered after the parameter
+        assert_eq!(&code[2..5], &[1, 1, F64]);
+        assert!(code.windows(2).any(|pair| pair == [LOCAL_SET, 1]));
+    }
+
+    #[test]
+    fn test_wasm_unsupported_programs() {
+        let backend = WasmBackend::new();
+
+        for source in [
+            r#""text""#,
+            "nil",
+            "[1, 2]",
+            "a % 2",
+            "if 1 2 else 3 end",
+            "if a > 1 then 1 else true end",
+            "1 + true",
+            "upcase(name)",
+            "9007199254740993",
+        ] {
+            let expr = parse_expr(source);
+            assert!(!backend.supports(&expr), "{}", source);
+            assert!(
+                matches!(
+                    backend.compile(&expr, &[]),
+                    Err(BackendError::UnsupportedFeature(_))
+                ),
+                "{}",
+                source
+            );
+        }
+
+        assert!(backend.supports(&parse_expr("if a > 1 then :big else :small end")));
+        assert!(matches!(
+            compile_wasm(":undeclared", &[]),
+            Err(BackendError::CompileError(_))
+        ));
+    }
+
+    #[test]
+    fn test_wasm_execute_needs_host() {
+        let backend = WasmBackend::new();
+        let module = compile_wasm("1", &[]).unwrap();
+        assert!(matches!(
+            backend.execute(&module, &HashMap::new()),
+            Err(BackendError::UnsupportedFeature(_))
+        ));
+        assert_eq!(backend.name(), "wasm");
+        assert_eq!(
+            WasmBackend::capabilities().performance_tier,
+            PerformanceTier::Transpiled
+        );
+    }
+
+    /// Run `evaluate` in wasmi on `data`, or `None` if it traps
+    fn run(module: &WasmModule, data: &HashMap<String, Value>) -> Option<Value> {
+        use wasmi::{Engine, Linker, Module, Store, Val};
+
+        let engine = Engine::default();
+        let wasm = Module::new(&engine, module.bytes()).unwrap();
+        let mut store = Store::new(&engine, ());
+        let instance = Linker::<()>::new(&engine)
+            .instantiate(&mut store, &wasm)
+            .unwrap()
+            .start(&mut store)
+            .unwrap();
+        let evaluate = instance.get_func(&store, "evaluate").unwrap();
+
+        let inputs: Vec<Val> = module
+            .parameters()
+            .iter()
+            .map(|(path, ty)| match (ty, &data[path]) {
+                (WasmType::Number, Value::Int(n)) => Val::F64((*n as f64).into()),
+                (WasmType::Number, Value::Number(n)) => Val::F64((*n).into()),
+                (WasmType::Boolean, Value::Boolean(b)) => Val::I32(*b as i32),
+                (WasmType::Symbol, Value::Symbol(s)) => {
+                    Val::I32(module.symbols().iter().position(|x| x == s).unwrap() as i32)
+                }
+                (ty, value) => panic!("{} passed as a {}", value, ty),
+            })
+            .collect();
+        let mut outputs = [Val::I32(0)];
+        evaluate.call(&mut store, &inputs, &mut outputs).ok()?;
+
+        Some(match (module.result(), &outputs[0]) {
+            (WasmType::Number, Val::F64(n)) => Value::Number(n.to_float()),
+            (WasmType::Boolean, Val::I32(b)) => Value::Boolean(*b != 0),
+            (WasmType::Symbol, Val::I32(i)) => Value::Symbol(module.symbols()[*i as usize].clone()),
+            (ty, value) => panic!("{:?} returned for a {}", value, ty),
+        })
+    }
+
+    #[test]
+    fn test_wasm_modules_validate() {
+        for source in [
+            "1 + 2",
+            "if applicant.score >= 700 and not applicant.flagged :approve else :deny end",
+            "min(a, b) / max(a, b) | clamp(0, 1)",
+            "let x = a * 2 in let y = x > 3 in if y then x else -x end",
+            "sign(a) + sqrt(b) + abs(a) + ceil(a) + floor(a) + trunc(a)",
+            "status == :active or status != :approve",
+        ] {
+            let module = compile_wasm(source, &["active", "approve", "deny"]).unwrap();
+            if let Err(error) = wasmparser::validate(module.bytes()) {
+                panic!("{}: {}", source, error);
+            }
+        }
+    }
+
+    #[test]
+    fn test_wasm_matches_interpreter() {
+        let symbols = ["approve", "deny", "active", "closed"];
+        let sources = [
+            "a * 2 + b / 4 - 1",
+            "min(a, b) / max(a, b) | clamp(0, 1)",
+            "sign(a - b) + abs(a - b) + floor(b / 3) + ceil(a / 2) + trunc(-b / 3)",
+            "if a > b and not flagged then :approve else :deny end",
+            "let x = a * 2 in if x >= b then x else -x end",
+            "sqrt(b) >= a or a == b",
+            "status == :active",
+            "a / (b - 10)",
+            "sqrt(a)",
+            "clamp(a, b, a)",
+        ];
+        let rows = [
+            [
+                ("a", Value::Int(3)),
+                ("b", Value::Number(10.0)),
+                ("flagged", Value::Boolean(false)),
+                ("status", Value::Symbol("active".to_string())),
+            ],
+            [
+                ("a", Value::Number(-2.5)),
+                ("b", Value::Int(10)),
+                ("flagged", Value::Boolean(true)),
+                ("status", Value::Symbol("closed".to_string())),
+            ],
+            [
+                ("a", Value::Number(12.0)),
+                ("b", Value::Number(0.5)),
+                ("flagged", Value::Boolean(false)),
+                ("status", Value::Symbol("closed".to_string())),
+            ],
+        ];
+
+        for source in sources {
+            let module = compile_wasm(source, &symbols).unwrap();
+            let program = crate::compile(source, &symbols).unwrap();
+            for row in &rows {
+                let data: HashMap<String, Value> = row
+                    .iter()
+                    .map(|(name, value)| (name.to_string(), value.clone()))
+                    .collect();
+                // Errors in the interpreter are traps here
+                let expected = crate::evaluate(&program, &data).ok();
+                assert_eq!(run(&module, &data), expected, "{} on {:?}", source, row);
+            }
+        }
+    }
+
+    #[test]
+    fn test_leb128() {
+        let encode = |f: fn(&mut Vec<u8>)| {
+            let mut bytes = Vec::new();
+            f(&mut bytes);
+            bytes
+        };
+        assert_eq!(encode(|b| unsigned(b, 624485)), vec![0xe5, 0x8e, 0x26]);
+        assert_eq!(encode(|b| unsigned(b, 0)), vec![0]);
+        assert_eq!(encode(|b| signed(b, -1)), vec![0x7f]);
+        assert_eq!(encode(|b| signed(b, 63)), vec![0x3f]);
+        assert_eq!(encode(|b| signed(b, 64)), vec![0xc0, 0x00]);
+        assert_eq!(encode(|b| signed(b, -123456)), vec![0xc0, 0xbb, 0x78]);
+    }
+}
@@ -0,0 +1,160 @@
+//! Building data dictionaries
+//!
+//! `evaluate` takes its data as a `HashMap<String, Value>`, which is tedious
+//! to nest by hand. `ContextBuilder` assembles one from dotted paths, other
+//! dictionaries, and JSON:
+//!
+//! ```
+//! use amoskeag::ContextBuilder;
+//!
+//! let data = ContextBuilder::new()
+//!     .set("driver.age", 25)
+//!     .set("driver.name", "Sam")
+//!     .json(r#"{"policy": {"state": "NH"}}"#)
+//!     .unwrap()
+//!     .build();
+//! assert_eq!(data.len(), 2);
+//! ```
+
+use crate::json::{JsonError, JsonOptions};
+use amoskeag_stdlib_operators::Value;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+/// Assembles the data dictionary for an evaluation
+///
+/// Later calls win: setting or merging a value replaces what was there,
+/// except that two dictionaries are merged key by key.
+#[derive(Debug, Clone, Default)]
+pub struct ContextBuilder {
+    data: HashMap<String, Value>,
+    json_options: JsonOptions,
+}
+
+impl ContextBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Convert JSON passed to `json` with `options` rather than the defaults
+    pub fn with_json_options(mut self, options: JsonOptions) -> Self {
+        self.json_options = options;
+        self
+    }
+
+    /// Set the value at a dotted path such as `driver.age`
+    ///
+    /// Dictionaries along the path are created as needed; anything else in
+    /// the way is replaced by one.
+    pub fn set(mut self, path: &str, value: impl Into<Value>) -> Self {
+        let mut keys = path.split('.');
+        let first = keys.next().unwrap_or_default();
+        let nested = keys.rev().fold(value.into(), |value, key| {
+            Value::Dictionary(HashMap::from([(key.to_string(), value)]).into())
+        });
+        merge_into(&mut self.data, first.to_string(), nested);
+        self
+    }
+
+    /// Merge `data` in, key by key
+    pub fn merge(mut self, data: HashMap<String, Value>) -> Self {
+        for (key, value) in data {
+            merge_into(&mut self.data, key, value);
+        }
+        self
+    }
+
+    /// Parse a JSON object and merge it in
+    pub fn json(self, source: &str) -> Result<Self, JsonError> {
+        let data = self.json_options.parse_data(source)?;
+        Ok(self.merge(data))
+    }
+
+    /// Convert a JSON object and merge it in
+    pub fn json_value(self, json: &serde_json::Value) -> Result<Self, JsonError> {
+        let data = self.json_options.data_from_json(json)?;
+        Ok(self.merge(data))
+    }
+
+    /// The assembled data, ready for `evaluate`
+    pub fn build(self) -> HashMap<String, Value> {
+        self.data
+    }
+}
+
+/// Insert `value` at `key`, merging it into a dictionary already there
+fn merge_into(map: &mut HashMap<String, Value>, key: String, value: Value) {
+    match (map.get_mut(&key), value) {
+        (Some(Value::Dictionary(existing)), Value::Dictionary(incoming)) => {
+            let existing = Arc::make_mut(existing);
+            for (key, value) in Arc::unwrap_or_clone(incoming) {
+                merge_into(existing, key, value);
+            }
+        }
+        (_, value) => {
+            map.insert(key, value);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at<'a>(data: &'a HashMap<String, Value>, path: &str) -> Option<&'a Value> {
+        let mut keys = path.split('.');
+        let mut current = data.get(keys.next()?);
+        for key in keys {
+            current = match current {
+                Some(Value::Dictionary(map)) => map.get(key),
+                _ => None,
+            };
+        }
+        current
+    }
+
+    #[test]
+    fn test_set_paths() {
+        let data = ContextBuilder::new()
+            .set("driver.age", 25)
+            .set("driver.name", "Sam")
+            .set("state", "NH")
+            .build();
+        assert_eq!(at(&data, "driver.age"), Some(&Value::Int(25)));
+        assert_eq!(at(&data, "driver.name"), Some(&Value::String("Sam".into())));
+        assert_eq!(at(&data, "state"), Some(&Value::String("NH".into())));
+
+        // A value in the way of a deeper path is replaced
+        let data = ContextBuilder::new()
+            .set("driver", true)
+            .set("driver.age", 30)
+            .build();
+        assert_eq!(at(&data, "driver.age"), Some(&Value::Int(30)));
+    }
+
+    #[test]
+    fn test_merge_and_json() {
+        let mut extra = HashMap::new();
+        extra.insert("score".to_string(), Value::Int(700));
+        let mut bureau = HashMap::new();
+        bureau.insert("bureau".to_string(), Value::from(extra));
+
+        let data = ContextBuilder::new()
+            .set("bureau.name", "equifax")
+            .merge(bureau)
+            .json(r#"{"bureau": {"score": 710}, "claims": []}"#)
+            .unwrap()
+            .build();
+        assert_eq!(
+            at(&data, "bureau.name"),
+            Some(&Value::String("equifax".into()))
+        );
+        assert_eq!(at(&data, "bureau.score"), Some(&Value::Int(710)));
+        assert_eq!(at(&data, "claims"), Some(&Value::Array(Vec::new().into())));
+
+        assert_eq!(
+            ContextBuilder::new().json("[1]").unwrap_err(),
+            JsonError::NotAnObject("array")
+        );
+    }
+}
@@ -0,0 +1,290 @@
+//! Typed data contracts
+//!
+//! A `DataContract` declares the fields a program's data holds, by dotted
+//! path, and their types. Compiling against one type-checks the program
+//! before it ever runs: arithmetic on strings, comparisons that can never
+//! be true, and standard library calls with the wrong kind of argument are
+//! compile errors, as are reads of fields the contract does not declare
+//! under a record it does.
+//!
+//! Checking is conservative. Anything whose type cannot be known, such as a
+//! root allowed by `CompileOptions::with_variables` but not declared here, or
+//! the result of a host function, is `Type::Any` and passes every check.
+
+use crate::CompileError;
+use amoskeag_parser::{BinaryOp, Expr, UnaryOp};
+use amoskeag_stdlib_functions::signatures;
+use std::collections::BTreeMap;
+
+pub use amoskeag_stdlib_functions::signatures::Type;
+
+/// The fields of a program's data and their types
+///
+/// A field declared under a path, such as `driver.age`, makes `driver` a
+/// record: reading any other field of `driver` is a compile error. A field
+/// declared as `Type::Dictionary` is opaque, and anything may be read from it.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DataContract {
+    fields: BTreeMap<String, Type>,
+}
+
+impl DataContract {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Declare the field at the dotted `path` to be of type `ty`
+    pub fn with_field(mut self, path: impl Into<String>, ty: Type) -> Self {
+        self.fields.insert(path.into(), ty);
+        self
+    }
+
+    /// The declared fields and their types, sorted by path
+    pub fn fields(&self) -> impl Iterator<Item = (&str, Type)> {
+        self.fields.iter().map(|(path, ty)| (path.as_str(), *ty))
+    }
+
+    /// Whether the contract declares `root` or any field under it
+    pub fn has_root(&self, root: &str) -> bool {
+        self.fields.contains_key(root) || self.is_record(root)
+    }
+
+    /// Whether fields are declared under `path`
+    fn is_record(&self, path: &str) -> bool {
+        let prefix = format!("{}.", path);
+        self.fields
+            .range(prefix.clone()..)
+            .next()
+            .is_some_and(|(field, _)| field.starts_with(&prefix))
+    }
+
+    /// The type of the data at `path`
+    fn resolve(&self, path: &[String]) -> Result<Type, CompileError> {
+        let full = path.join(".");
+        if let Some(ty) = self.fields.get(&full) {
+            return Ok(*ty);
+        }
+        if self.is_record(&full) {
+            return Ok(Type::Dictionary);
+        }
+        for depth in (1..path.len()).rev() {
+            let parent = path[..depth].join(".");
+            match self.fields.get(&parent) {
+                Some(Type::Any | Type::Dictionary) => return Ok(Type::Any),
+                Some(ty) => {
+                    return Err(CompileError::NotADictionary {
+                        path: parent,
+                        got: ty.to_string(),
+                    })
+                }
+                None if self.is_record(&parent) => {
+                    return Err(CompileError::UnknownField { path: full })
+                }
+                None => {}
+            }
+        }
+        Ok(Type::Any)
+    }
+}
+
+/// Type-check `ast` against `contract`
+pub(crate) fn check(ast: &Expr, contract: &DataContract) -> Result<(), CompileError> {
+    Checker {
+        contract,
+        scope: Vec::new(),
+    }
+    .infer(ast)
+    .map(|_| ())
+}
+
+struct Checker<'c> {
+    contract: &'c DataContract,
+    /// `let` bindings in scope and their types, innermost last
+    scope: Vec<(String, Type)>,
+}
+
+impl Checker<'_> {
+    fn infer(&mut self, expr: &Expr) -> Result<Type, CompileError> {
+        let ty = match expr {
+            Expr::Number(_) | Expr::Integer(_) => Type::Number,
+            Expr::String(_) => Type::String,
+            Expr::Boolean(_) => Type::Boolean,
+            Expr::Nil => Type::Nil,
+            Expr::Symbol(_) => Type::Symbol,
+            Expr::Array(items) => {
+                for item in items {
+                    self.infer(item)?;
+                }
+                Type::Array
+            }
+            Expr::Dictionary(pairs) => {
+                for (_, value) in pairs {
+                    self.infer(value)?;
+                }
+                Type::Dictionary
+            }
+
+            Expr::Variable(path) => {
+                let local = path.first().and_then(|root| {
+                    self.scope
+                        .iter()
+                        .rev()
+                        .find(|(name, _)| name == root)
+                        .map(|(_, ty)| *ty)
+                });
+                match local {
+                    Some(ty) if path.len() == 1 => ty,
+                    Some(_) => Type::Any,
+                    None => self.contract.resolve(path)?,
+                }
+            }
+
+            Expr::FunctionCall { name, args } => {
+                let arg_types = args
+                    .iter()
+                    .map(|arg| self.infer(arg))
+                    .collect::<Result<Vec<_>, _>>()?;
+                self.call(name, &arg_types)?
+            }
+
+            Expr::Let { name, value, body } => {
+                let value = self.infer(value)?;
+                self.scope.push((name.clone(), value));
+                let body = self.infer(body);
+                self.scope.pop();
+                body?
+            }
+
+            Expr::If {
+                condition,
+                then_branch,
+                else_branch,
+            } => {
+                self.infer(condition)?;
+                let then_type = self.infer(then_branch)?;
+                let else_type = self.infer(else_branch)?;
+                if then_type == else_type {
+                    then_type
+                } else {
+                    Type::Any
+                }
+            }
+
+            Expr::Binary { op, left, right } => {
+                let left = self.infer(left)?;
+                let right = self.infer(right)?;
+                binary(*op, left, right)?
+            }
+
+            Expr::Unary { op, operand } => {
+                let operand = self.infer(operand)?;
+                match (op, operand) {
+                    (UnaryOp::Not, _) => Type::Boolean,
+                    (UnaryOp::Negate, Type::Number | Type::Any) => operand,
+                    (UnaryOp::Negate, _) => {
+                        return Err(CompileError::InvalidOperand {
+                            op: op.to_string(),
+                            operand: operand.to_string(),
+                        })
+                    }
+                }
+            }
+
+            Expr::Pipe { left, right } => {
+                let left = self.infer(left)?;
+                match right.as_ref() {
+                    Expr::FunctionCall { name, args } => {
+                        let mut arg_types = vec![left];
+                        for arg in args {
+                            arg_types.push(self.infer(arg)?);
+                        }
+                        self.call(name, &arg_types)?
+                    }
+                    _ => Type::Any,
+                }
+            }
+        };
+        Ok(ty)
+    }
+
+    /// Check a call's argument types and give its result type
+    fn call(&self, name: &str, args: &[Type]) -> Result<Type, CompileError> {
+        let Some(function) = signatures::lookup(name) else {
+            return Ok(Type::Any);
+        };
+        for (position, (param, arg)) in function.params.iter().zip(args).enumerate() {
+            if !compatible(*param, *arg) {
+                return Err(CompileError::ArgumentType {
+                    function: name.to_string(),
+                    position: position + 1,
+                    expected: param.to_string(),
+                    got: arg.to_string(),
+                });
+            }
+        }
+        Ok(function.returns)
+    }
+}
+
+/// Whether a value of type `actual` may be used where `expected` is required
+fn compatible(expected: Type, actual: Type) -> bool {
+    expected == actual || expected == Type::Any || actual == Type::Any
+}
+
+/// The result type of `left op right`, following the operators' rules
+fn binary(op: BinaryOp, left: Type, right: Type) -> Result<Type, CompileError> {
+    use Type::*;
+    let invalid = || {
+        Err(CompileError::InvalidOperands {
+            op: op.to_string(),
+            left: left.to_string(),
+            right: right.to_string(),
+        })
+    };
+    match op {
+        BinaryOp::And | BinaryOp::Or => Ok(Boolean),
+
+        // Values of different types are never equal; nil may stand for anything
+        BinaryOp::Equal | BinaryOp::NotEqual => {
+            if left != right && ![left, right].iter().any(|ty| matches!(ty, Any | Nil)) {
+                return Err(CompileError::IncomparableTypes {
+                    op: op.to_string(),
+                    left: left.to_string(),
+                    right: right.to_string(),
+                });
+            }
+            Ok(Boolean)
+        }
+
+        BinaryOp::Less | BinaryOp::Greater | BinaryOp::LessEqual | BinaryOp::GreaterEqual => {
+            match (left, right) {
+                (Any, _) | (_, Any) => Ok(Boolean),
+                (Number, Number) | (String, String) | (Duration, Duration) => Ok(Boolean),
+                _ => invalid(),
+            }
+        }
+
+        // A string on the left concatenates anything
+        BinaryOp::Add => match (left, right) {
+            (String, _) => Ok(String),
+            (Number, Number) => Ok(Number),
+            (Duration, Duration) => Ok(Duration),
+            (Any, _) | (_, Any) => Ok(Any),
+            _ => invalid(),
+        },
+
+        BinaryOp::Subtract => match (left, right) {
+            (Number, Number) => Ok(Number),
+            (Duration, Duration) => Ok(Duration),
+            (Any, Number | Duration | Any) | (Number | Duration, Any) => Ok(Any),
+            _ => invalid(),
+        },
+
+        BinaryOp::Multiply | BinaryOp::Divide | BinaryOp::Modulo | BinaryOp::Power => {
+            match (left, right) {
+                (Number | Any, Number | Any) => Ok(Number),
+                _ => invalid(),
+            }
+        }
+    }
+}
@@ -0,0 +1,139 @@
+//! Decision traces
+//!
+//! `evaluate_with_explanation` records what an evaluation looked at on the
+//! way to its result: the conditions it tested and which way each went, the
+//! variables it read, and the functions it called. That is the record an
+//! underwriter needs to justify a decision.
+
+use crate::{EvalError, EvalObserver};
+use amoskeag_parser::Expr;
+use amoskeag_stdlib_operators::Value;
+use std::cell::RefCell;
+
+/// What an evaluation tested, read, and called, each in evaluation order
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Explanation {
+    pub branches: Vec<Branch>,
+    pub variables: Vec<VariableRead>,
+    pub calls: Vec<FunctionCall>,
+}
+
+/// An `if` condition and the branch it selected
+#[derive(Debug, Clone, PartialEq)]
+pub struct Branch {
+    pub condition: Expr,
+    /// What the condition evaluated to
+    pub value: Value,
+    /// `true` if the `then` branch was taken, `false` for the `else` branch
+    pub taken: bool,
+}
+
+/// A variable read, by its dotted path, and the value it held
+#[derive(Debug, Clone, PartialEq)]
+pub struct VariableRead {
+    pub path: String,
+    pub value: Value,
+}
+
+/// A function call with its arguments and result
+#[derive(Debug, Clone, PartialEq)]
+pub struct FunctionCall {
+    pub name: String,
+    pub args: Vec<Value>,
+    pub result: Value,
+}
+
+/// An observer that builds an `Explanation`
+#[derive(Default)]
+pub(crate) struct Explainer {
+    /// `if`s and calls entered but not yet exited, innermost last
+    open: RefCell<Vec<Open>>,
+    explanation: RefCell<Explanation>,
+}
+
+/// An `if` or call waiting on its sub-expressions
+///
+/// Expressions are matched by address, which is stable while the AST is
+/// borrowed for the evaluation.
+enum Open {
+    If {
+        expr: *const Expr,
+        condition: *const Expr,
+    },
+    Call {
+        expr: *const Expr,
+        arg_exprs: Vec<*const Expr>,
+        args: Vec<Value>,
+    },
+}
+
+impl Explainer {
+    pub(crate) fn into_explanation(self) -> Explanation {
+        self.explanation.into_inner()
+    }
+}
+
+impl EvalObserver for Explainer {
+    fn enter(&self, expr: &Expr) {
+        let open = match expr {
+            Expr::If { condition, .. } => Open::If {
+                expr,
+                condition: &**condition,
+            },
+            Expr::FunctionCall { args, .. } => Open::Call {
+                expr,
+                arg_exprs: args.iter().map(|arg| arg as *const Expr).collect(),
+                args: Vec::new(),
+            },
+            _ => return,
+        };
+        self.open.borrow_mut().push(open);
+    }
+
+    fn exit(&self, expr: &Expr, result: &Result<Value, EvalError>) {
+        let mut open = self.open.borrow_mut();
+        let mut explanation = self.explanation.borrow_mut();
+
+        // Close this expression if it is the innermost open one
+        if let Some(Open::If { expr: e, .. } | Open::Call { expr: e, .. }) = open.last() {
+            if std::ptr::eq(*e, expr) {
+                if let (Some(Open::Call { args, .. }), Expr::FunctionCall { name, .. }, Ok(value)) =
+                    (open.pop(), expr, result)
+                {
+                    explanation.calls.push(FunctionCall {
+                        name: name.clone(),
+                        args,
+                        result: value.clone(),
+                    });
+                }
+            }
+        }
+
+        let Ok(value) = result else {
+            return;
+        };
+        if let Expr::Variable(path) = expr {
+            explanation.variables.push(VariableRead {
+                path: path.join("."),
+                value: value.clone(),
+            });
+        }
+
+        // Hand the value to the `if` or call it belongs to
+        match open.last_mut() {
+            Some(Open::If { condition, .. }) if std::ptr::eq(*condition, expr) => {
+                explanation.branches.push(Branch {
+                    condition: expr.clone(),
+                    value: value.clone(),
+                    taken: !matches!(value, Value::Boolean(false) | Value::Nil),
+                });
+            }
+            Some(Open::Call {
+                arg_exprs, args, ..
+            }) if arg_exprs.iter().any(|arg| std::ptr::eq(*arg, expr)) => {
+                args.push(value.clone());
+            }
+            _ => {}
+        }
+    }
+}
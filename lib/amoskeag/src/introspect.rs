@@ -0,0 +1,171 @@
+//! What a compiled program reads and calls
+//!
+//! Callers that assemble data from several services want to fetch only what
+//! a rule needs, list its inputs in a UI, or check before a deploy that a
+//! rule still reads only fields the data provides. Governance tooling wants
+//! to know which functions and symbols a rule uses, to enforce allow-lists
+//! such as no `date_now` in deterministic batch jobs.
+
+use crate::CompiledProgram;
+use amoskeag_parser::Expr;
+use std::collections::BTreeSet;
+
+impl CompiledProgram {
+    /// The data paths this program reads, sorted, e.g. `["driver.age", "vehicle.value"]`
+    ///
+    /// Names bound by `let` are not data and are left out. A dictionary
+    /// read whole and one of its fields are both listed, since both are read.
+    pub fn referenced_variables(&self) -> Vec<String> {
+        let mut paths = BTreeSet::new();
+        collect_variables(&self.ast, &mut Vec::new(), &mut paths);
+        paths.into_iter().collect()
+    }
+
+    /// The functions this program calls, sorted
+    ///
+    /// Standard library and host functions are both listed, including those
+    /// named as the target of a pipe.
+    pub fn used_functions(&self) -> Vec<String> {
+        let names: BTreeSet<&str> = calls(&self.ast).into_iter().collect();
+        names.into_iter().map(str::to_string).collect()
+    }
+
+    /// The symbols this program mentions, sorted
+    ///
+    /// These are a subset of the symbols it was compiled with.
+    pub fn used_symbols(&self) -> Vec<String> {
+        let mut symbols = BTreeSet::new();
+        walk(&self.ast, &mut |expr| {
+            if let Expr::Symbol(symbol) = expr {
+                symbols.insert(symbol.clone());
+            }
+        });
+        symbols.into_iter().collect()
+    }
+}
+
+/// The names of the functions called in `expr`, in source order, with repeats
+pub(crate) fn calls(expr: &Expr) -> Vec<&str> {
+    let mut names = Vec::new();
+    walk(expr, &mut |expr| match expr {
+        Expr::FunctionCall { name, .. } => names.push(name.as_str()),
+        // A bare name on the right of a pipe is a function, not a variable
+        Expr::Pipe { right, .. } => {
+            if let Expr::Variable(path) = right.as_ref() {
+                names.extend(path.first().map(String::as_str));
+            }
+        }
+        _ => {}
+    });
+    names
+}
+
+/// Call `visit` on `expr` and every expression inside it
+pub(crate) fn walk<'e>(expr: &'e Expr, visit: &mut impl FnMut(&'e Expr)) {
+    visit(expr);
+    match expr {
+        Expr::Array(items) | Expr::FunctionCall { args: items, .. } => {
+            for item in items {
+                walk(item, visit);
+            }
+        }
+        Expr::Dictionary(pairs) => {
+            for (_, value) in pairs {
+                walk(value, visit);
+            }
+        }
+        Expr::Let { value, body, .. } => {
+            walk(value, visit);
+            walk(body, visit);
+        }
+        Expr::If {
+            condition,
+            then_branch,
+            else_branch,
+        } => {
+            walk(condition, visit);
+            walk(then_branch, visit);
+            walk(else_branch, visit);
+        }
+        Expr::Binary { left, right, .. } | Expr::Pipe { left, right } => {
+            walk(left, visit);
+            walk(right, visit);
+        }
+        Expr::Unary { operand, .. } => walk(operand, visit),
+        Expr::Number(_)
+        | Expr::Integer(_)
+        | Expr::String(_)
+        | Expr::Boolean(_)
+        | Expr::Nil
+        | Expr::Symbol(_)
+        | Expr::Variable(_) => {}
+    }
+}
+
+/// Add the dotted paths of the data variables in `expr` to `paths`
+///
+/// `bound` holds the `let` names in scope.
+pub(crate) fn collect_variables<'e>(
+    expr: &'e Expr,
+    bound: &mut Vec<&'e str>,
+    paths: &mut BTreeSet<String>,
+) {
+    match expr {
+        Expr::Variable(path) => {
+            if path
+                .first()
+                .is_some_and(|root| !bound.contains(&root.as_str()))
+            {
+                paths.insert(path.join("."));
+            }
+        }
+        Expr::Let { name, value, body } => {
+            collect_variables(value, bound, paths);
+            bound.push(name);
+            collect_variables(body, bound, paths);
+            bound.pop();
+        }
+        Expr::Array(items) => {
+            for item in items {
+                collect_variables(item, bound, paths);
+            }
+        }
+        Expr::Dictionary(pairs) => {
+            for (_, value) in pairs {
+                collect_variables(value, bound, paths);
+            }
+        }
+        Expr::FunctionCall { args, .. } => {
+            for arg in args {
+                collect_variables(arg, bound, paths);
+            }
+        }
+        Expr::If {
+            condition,
+            then_branch,
+            else_branch,
+        } => {
+            collect_variables(condition, bound, paths);
+            collect_variables(then_branch, bound, paths);
+            collect_variables(else_branch, bound, paths);
+        }
+        Expr::Binary { left, right, .. } => {
+            collect_variables(left, bound, paths);
+            collect_variables(right, bound, paths);
+        }
+        Expr::Unary { operand, .. } => collect_variables(operand, bound, paths),
+        // A bare name on the right of a pipe is a function, not a variable
+        Expr::Pipe { left, right } => {
+            collect_variables(left, bound, paths);
+            if !matches!(right.as_ref(), Expr::Variable(_)) {
+                collect_variables(right, bound, paths);
+            }
+        }
+        Expr::Number(_)
+        | Expr::Integer(_)
+        | Expr::String(_)
+        | Expr::Boolean(_)
+        | Expr::Nil
+        | Expr::Symbol(_) => {}
+    }
+}
@@ -0,0 +1,280 @@
+//! Conversion between JSON and Amoskeag values
+//!
+//! Hosts that pass data in as JSON, or hand results back out as JSON, should
+//! go through this module so every embedding sees the same conventions:
+//!
+//! - Integers become `Int`, other numbers become `Number`.
+//! - Symbols are written as `":name"` strings. Reading them back as symbols
+//!   is opt-in, since a JSON string may legitimately start with a colon.
+//! - Durations are written as ISO 8601 strings and decimals as strings, so no
+//!   digits are lost to a float.
+//! - NaN, infinities, and functions cannot be written.
+//! - Nesting depth and total size are bounded, so hostile input cannot
+//!   exhaust the stack or memory.
+//!
+//! Newline-delimited JSON (one object per line) is read lazily, a record at a
+//! time, so files far larger than memory can be scored with `evaluate_each`.
+
+use amoskeag_stdlib_functions::{JsonConversionError, JsonConverter, MAX_JSON_DEPTH};
+use amoskeag_stdlib_operators::Value;
+use std::collections::HashMap;
+use std::io::BufRead;
+use thiserror::Error;
+
+/// Nesting depth allowed by `JsonOptions::default()`
+pub const DEFAULT_MAX_DEPTH: usize = MAX_JSON_DEPTH;
+
+/// Errors that can occur converting between JSON and values
+#[derive(Error, Debug, Clone, PartialEq)]
+pub enum JsonError {
+    #[error("Invalid JSON: {0}")]
+    Syntax(String),
+
+    #[error("JSON nesting too deep (max {max_depth} levels)")]
+    TooDeep { max_depth: usize },
+
+    #[error("JSON too large (max {max_values} values)")]
+    TooLarge { max_values: usize },
+
+    #[error("Data must be a JSON object, got: {0}")]
+    NotAnObject(&'static str),
+
+    #[error("JSON number out of range: {0}")]
+    InvalidNumber(String),
+
+    #[error("Cannot serialize non-finite number {0} to JSON")]
+    NonFinite(f64),
+
+    #[error("Cannot serialize function {0} to JSON")]
+    Function(String),
+
+    #[error("Error reading JSON: {0}")]
+    Io(String),
+}
+
+impl From<JsonConversionError> for JsonError {
+    fn from(error: JsonConversionError) -> Self {
+        match error {
+            JsonConversionError::TooDeep { max_depth } => JsonError::TooDeep { max_depth },
+            JsonConversionError::TooLarge { max_values } => JsonError::TooLarge { max_values },
+            JsonConversionError::InvalidNumber(n) => JsonError::InvalidNumber(n),
+            JsonConversionError::NonFinite(n) => JsonError::NonFinite(n),
+            JsonConversionError::Function(name) => JsonError::Function(name),
+        }
+    }
+}
+
+/// Limits and conventions for JSON conversion
+///
+/// The conversion itself is the one `parse_json` and `to_json` use, so data
+/// read by the host and by a program come out the same.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct JsonOptions {
+    converter: JsonConverter,
+}
+
+impl JsonOptions {
+    /// Reject documents nested deeper than `max_depth` levels
+    pub fn with_max_depth(mut self, max_depth: usize) -> Self {
+        self.converter = self.converter.with_max_depth(max_depth);
+        self
+    }
+
+    /// Reject documents holding more than `max_values` values in total
+    pub fn with_max_values(mut self, max_values: usize) -> Self {
+        self.converter = self.converter.with_max_values(max_values);
+        self
+    }
+
+    /// Read `":name"` strings back as the symbol `:name`
+    pub fn with_symbols(mut self, decode_symbols: bool) -> Self {
+        self.converter = self.converter.with_symbols(decode_symbols);
+        self
+    }
+
+    /// Convert a JSON value to a value
+    pub fn from_json(&self, json: &serde_json::Value) -> Result<Value, JsonError> {
+        Ok(self.converter.read(json)?)
+    }
+
+    /// Convert a JSON object to a data dictionary for evaluation
+    ///
+    /// `null` gives an empty dictionary; any other non-object is an error.
+    pub fn data_from_json(
+        &self,
+        json: &serde_json::Value,
+    ) -> Result<HashMap<String, Value>, JsonError> {
+        match self.from_json(json)? {
+            Value::Dictionary(map) => Ok(map.as_ref().clone()),
+            Value::Nil => Ok(HashMap::new()),
+            _ => Err(JsonError::NotAnObject(json_type_name(json))),
+        }
+    }
+
+    /// Parse JSON source into a data dictionary for evaluation
+    pub fn parse_data(&self, source: &str) -> Result<HashMap<String, Value>, JsonError> {
+        let json: serde_json::Value =
+            serde_json::from_str(source).map_err(|e| JsonError::Syntax(e.to_string()))?;
+        self.data_from_json(&json)
+    }
+
+    /// Read newline-delimited JSON, one data dictionary per line
+    ///
+    /// Lines are read and converted as the iterator is advanced; blank lines
+    /// are skipped. A bad line yields an error and reading continues with
+    /// the next one.
+    pub fn ndjson_data<R: BufRead>(
+        self,
+        reader: R,
+    ) -> impl Iterator<Item = Result<HashMap<String, Value>, JsonError>> {
+        reader.lines().filter_map(move |line| match line {
+            Ok(line) if line.trim().is_empty() => None,
+            Ok(line) => Some(self.parse_data(&line)),
+            Err(e) => Some(Err(JsonError::Io(e.to_string()))),
+        })
+    }
+
+    /// Convert a value to JSON
+    ///
+    /// Dictionary keys come out sorted, so the output is stable.
+    pub fn to_json(&self, value: &Value) -> Result<serde_json::Value, JsonError> {
+        Ok(self.converter.write(value)?)
+    }
+}
+
+/// Convert a JSON value to a value with the default options
+pub fn from_json(json: &serde_json::Value) -> Result<Value, JsonError> {
+    JsonOptions::default().from_json(json)
+}
+
+/// Convert a value to JSON with the default options
+pub fn to_json(value: &Value) -> Result<serde_json::Value, JsonError> {
+    JsonOptions::default().to_json(value)
+}
+
+/// Parse JSON source into a data dictionary with the default options
+pub fn parse_data(source: &str) -> Result<HashMap<String, Value>, JsonError> {
+    JsonOptions::default().parse_data(source)
+}
+
+/// Read newline-delimited JSON data dictionaries with the default options
+pub fn ndjson_data<R: BufRead>(
+    reader: R,
+) -> impl Iterator<Item = Result<HashMap<String, Value>, JsonError>> {
+    JsonOptions::default().ndjson_data(reader)
+}
+
+fn json_type_name(json: &serde_json::Value) -> &'static str {
+    match json {
+        serde_json::Value::Null => "null",
+        serde_json::Value::Bool(_) => "boolean",
+        serde_json::Value::Number(_) => "number",
+        serde_json::Value::String(_) => "string",
+        serde_json::Value::Array(_) => "array",
+        serde_json::Value::Object(_) => "object",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_from_json_all_types() {
+        assert_eq!(from_json(&json!(null)).unwrap(), Value::Nil);
+        assert_eq!(from_json(&json!(true)).unwrap(), Value::Boolean(true));
+        assert!(matches!(from_json(&json!(42)), Ok(Value::Int(42))));
+        assert!(matches!(from_json(&json!(2.5)), Ok(Value::Number(n)) if n == 2.5));
+        assert_eq!(
+            from_json(&json!("hello")).unwrap(),
+            Value::String("hello".into())
+        );
+        assert_eq!(
+            from_json(&json!([1, "a"])).unwrap(),
+            Value::Array(vec![Value::Int(1), Value::String("a".into())].into())
+        );
+    }
+
+    #[test]
+    fn test_parse_data() {
+        let data = parse_data(r#"{"user": {"name": "alice", "age": 30}}"#).unwrap();
+        let Some(Value::Dictionary(user)) = data.get("user") else {
+            panic!("Expected dictionary");
+        };
+        assert_eq!(user.get("name"), Some(&Value::String("alice".into())));
+        assert_eq!(user.get("age"), Some(&Value::Int(30)));
+
+        assert!(parse_data("null").unwrap().is_empty());
+        assert_eq!(
+            parse_data("[1, 2]").unwrap_err(),
+            JsonError::NotAnObject("array")
+        );
+        assert!(matches!(parse_data("{invalid}"), Err(JsonError::Syntax(_))));
+    }
+
+    #[test]
+    fn test_symbols() {
+        let value = Value::Symbol("approve".to_string());
+        let json = to_json(&value).unwrap();
+        assert_eq!(json, json!(":approve"));
+
+        // Symbols only come back when asked for
+        assert_eq!(from_json(&json).unwrap(), Value::String(":approve".into()));
+        let options = JsonOptions::default().with_symbols(true);
+        assert_eq!(options.from_json(&json).unwrap(), value);
+        assert_eq!(
+            options.from_json(&json!(":")).unwrap(),
+            Value::String(":".into())
+        );
+    }
+
+    #[test]
+    fn test_to_json() {
+        let mut map = HashMap::new();
+        map.insert("b".to_string(), Value::Number(2.0));
+        map.insert("a".to_string(), Value::Number(0.5));
+        let value = Value::Array(vec![Value::Dictionary(map.into()), Value::Nil].into());
+        assert_eq!(
+            to_json(&value).unwrap().to_string(),
+            r#"[{"a":0.5,"b":2},null]"#
+        );
+        assert_eq!(
+            to_json(&Value::Number(f64::NAN)).unwrap_err().to_string(),
+            "Cannot serialize non-finite number NaN to JSON"
+        );
+    }
+
+    #[test]
+    fn test_limits() {
+        let nested = json!([[[1]]]);
+        assert!(from_json(&nested).is_ok());
+        let shallow = JsonOptions::default().with_max_depth(2);
+        assert_eq!(
+            shallow.from_json(&nested).unwrap_err(),
+            JsonError::TooDeep { max_depth: 2 }
+        );
+
+        let small = JsonOptions::default().with_max_values(3);
+        assert!(small.from_json(&json!([1, 2])).is_ok());
+        assert_eq!(
+            small.from_json(&json!([1, 2, 3])).unwrap_err(),
+            JsonError::TooLarge { max_values: 3 }
+        );
+        let deep = from_json(&nested).unwrap();
+        assert!(matches!(
+            shallow.to_json(&deep),
+            Err(JsonError::TooDeep { .. })
+        ));
+    }
+
+    #[test]
+    fn test_ndjson_data() {
+        let input = "{\"a\": 1}\n\n[2]\n{\"a\": 3}\n";
+        let records: Vec<_> = ndjson_data(input.as_bytes()).collect();
+        assert_eq!(records.len(), 3);
+        assert_eq!(records[0].as_ref().unwrap().get("a"), Some(&Value::Int(1)));
+        assert_eq!(records[1], Err(JsonError::NotAnObject("array")));
+        assert_eq!(records[2].as_ref().unwrap().get("a"), Some(&Value::Int(3)));
+    }
+}
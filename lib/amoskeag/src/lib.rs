@@ -3,23 +3,80 @@
 //! This is the main crate that provides the compile and evaluate API for Amoskeag programs.
 //! It combines the lexer, parser, and standard library to provide a complete execution environment.
 
+pub mod artifact;
 pub mod backend;
+pub mod builder;
+pub mod contract;
+pub mod explain;
+mod introspect;
+pub mod json;
+pub mod limits;
+mod memo;
+pub mod observer;
+mod partial;
+pub mod provider;
+mod reachability;
+pub mod registry;
+pub mod warnings;
 
 use amoskeag_lexer::Lexer;
 use amoskeag_parser::{BinaryOp, Expr, Parser, UnaryOp};
+use amoskeag_stdlib_functions::signatures::{self, Numbers};
 use amoskeag_stdlib_functions::FunctionError;
-use amoskeag_stdlib_operators::{OperatorError, Value};
+use amoskeag_stdlib_operators::{Closure, Function, OperatorError, Value};
+use limits::Budget;
+use memo::Memo;
+use provider::Resolver;
+use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
+use std::cell::Cell;
 use std::collections::{HashMap, HashSet};
+use std::rc::Rc;
+use std::sync::Arc;
 use thiserror::Error;
+use warnings::Warnings;
 
 // Re-export the Value type for convenience
+pub use amoskeag_stdlib_operators::Duration;
 pub use amoskeag_stdlib_operators::Value as AmoskeagValue;
+pub use amoskeag_stdlib_operators::{Function as AmoskeagFunction, HostFunction};
+
+// Re-export compiled program storage types
+pub use artifact::{ArtifactError, ARTIFACT_VERSION};
 
 // Re-export backend types
 pub use backend::{
-    Backend, BackendCapabilities, BackendError, BackendRegistry, BackendResult, PerformanceTier,
+    Backend, BackendCapabilities, BackendError, BackendRegistry, BackendResult, BackendSelection,
+    PerformanceTier,
 };
 
+// Re-export JSON conversion types
+pub use json::{JsonError, JsonOptions};
+
+// Re-export evaluation limit types
+pub use limits::{EvalOptions, Limit};
+
+// Re-export data contract types
+pub use contract::{DataContract, Type};
+
+// Re-export the data dictionary builder
+pub use builder::ContextBuilder;
+
+// Re-export decision trace types
+pub use explain::Explanation;
+
+// Re-export the evaluation hook trait
+pub use observer::EvalObserver;
+
+// Re-export the on-demand data trait
+pub use provider::DataProvider;
+
+// Re-export host function registration types
+pub use registry::{FunctionRegistry, Signature};
+
+// Re-export the evaluation warning type
+pub use warnings::Warning;
+
 /// Errors that can occur during compilation
 #[derive(Error, Debug)]
 pub enum CompileError {
@@ -32,6 +89,9 @@ pub enum CompileError {
     #[error("Symbol '{symbol}' is not defined in the execution contract")]
     UndefinedSymbol { symbol: String },
 
+    #[error("Variable '{variable}' is not defined in the execution contract")]
+    UndefinedVariable { variable: String },
+
     #[error("Function '{function}' is not defined")]
     UndefinedFunction { function: String },
 
@@ -41,6 +101,102 @@ pub enum CompileError {
         expected: String,
         actual: usize,
     },
+
+    #[error("Invalid pattern '{pattern}' in call to '{function}': {message}")]
+    InvalidPattern {
+        function: String,
+        pattern: String,
+        message: String,
+    },
+
+    #[error("Invalid argument in call to '{function}': {message}")]
+    InvalidArgument { function: String, message: String },
+
+    #[error("Function '{function}' is already defined by the standard library")]
+    DuplicateFunction { function: String },
+
+    #[error("Function '{function}' is not allowed in this program{}", use_instead(.replacement))]
+    DisallowedFunction {
+        function: String,
+        replacement: Option<String>,
+    },
+
+    #[error("Field '{path}' is not declared in the data contract")]
+    UnknownField { path: String },
+
+    #[error("'{path}' is {got}, not a Dictionary")]
+    NotADictionary { path: String, got: String },
+
+    #[error("Operator '{op}' cannot be applied to {left} and {right}")]
+    InvalidOperands {
+        op: String,
+        left: String,
+        right: String,
+    },
+
+    #[error("Operator '{op}' cannot be applied to {operand}")]
+    InvalidOperand { op: String, operand: String },
+
+    #[error("'{op}' between {left} and {right} always gives the same answer")]
+    IncomparableTypes {
+        op: String,
+        left: String,
+        right: String,
+    },
+
+    #[error("Argument {position} of '{function}' must be {expected}, got {got}")]
+    ArgumentType {
+        function: String,
+        position: usize,
+        expected: String,
+        got: String,
+    },
+
+    #[error("{} errors: {}", .0.len(), join_errors(.0))]
+    Multiple(Vec<CompileError>),
+}
+
+impl CompileError {
+    /// The errors this error stands for: the list in `Multiple`, or just
+    /// this one
+    pub fn errors(&self) -> &[CompileError] {
+        match self {
+            CompileError::Multiple(errors) => errors,
+            error => std::slice::from_ref(error),
+        }
+    }
+
+    /// `Ok` for no errors, the error itself for one, and `Multiple` for
+    /// several; repeats of the same error are reported once
+    fn from_errors(errors: Vec<CompileError>) -> Result<(), CompileError> {
+        let mut seen = HashSet::new();
+        let mut errors: Vec<_> = errors
+            .into_iter()
+            .filter(|error| seen.insert(error.to_string()))
+            .collect();
+        match errors.len() {
+            0 => Ok(()),
+            1 => Err(errors.remove(0)),
+            _ => Err(CompileError::Multiple(errors)),
+        }
+    }
+}
+
+/// The messages of `errors`, separated by semicolons
+fn join_errors(errors: &[CompileError]) -> String {
+    errors
+        .iter()
+        .map(CompileError::to_string)
+        .collect::<Vec<_>>()
+        .join("; ")
+}
+
+/// The suggestion at the end of a `DisallowedFunction` message
+fn use_instead(replacement: &Option<String>) -> String {
+    match replacement {
+        Some(replacement) => format!(", use '{}' instead", replacement),
+        None => String::new(),
+    }
 }
 
 /// Errors that can occur during evaluation
@@ -60,16 +216,82 @@ pub enum EvalError {
 
     #[error("Invalid dictionary key: {0}")]
     InvalidDictionaryKey(String),
+
+    #[error("Function {function} expects {expected} arguments, but {actual} were provided")]
+    ArityMismatch {
+        function: String,
+        expected: usize,
+        actual: usize,
+    },
+
+    #[error("Error in {function}: {message}")]
+    HostFunctionError { function: String, message: String },
+
+    #[error("Evaluation limit exceeded: {0}")]
+    LimitExceeded(Limit),
+
+    #[error("Key '{key}' not found in '{path}'")]
+    UnknownKey { path: String, key: String },
+
+    #[error("Cannot navigate into '{path}': expected Dictionary, got {got}")]
+    NotADictionary { path: String, got: String },
 }
 
 /// A compiled Amoskeag program, ready for evaluation
 pub struct CompiledProgram {
     ast: Expr,
-    #[allow(dead_code)]
     symbols: HashSet<String>,
+    numeric_mode: NumericMode,
+    functions: FunctionRegistry,
+    strict_navigation: bool,
+    truthiness: Truthiness,
 }
 
 impl CompiledProgram {
+    /// Evaluate this program's numbers as floats or as exact decimals
+    ///
+    /// `evaluate` uses this mode; a context passed to `eval_expr` uses its own.
+    pub fn with_numeric_mode(mut self, mode: NumericMode) -> Self {
+        self.numeric_mode = mode;
+        self
+    }
+
+    /// The numeric mode `evaluate` runs this program in
+    pub fn numeric_mode(&self) -> NumericMode {
+        self.numeric_mode
+    }
+
+    /// The host functions this program was compiled against
+    pub fn functions(&self) -> &FunctionRegistry {
+        &self.functions
+    }
+
+    /// Make reading a missing variable or key an error instead of `nil`
+    ///
+    /// `evaluate` uses this setting; a context passed to `eval_expr` uses its own.
+    pub fn with_strict_navigation(mut self, strict: bool) -> Self {
+        self.strict_navigation = strict;
+        self
+    }
+
+    /// Whether `evaluate` runs this program with strict navigation
+    pub fn strict_navigation(&self) -> bool {
+        self.strict_navigation
+    }
+
+    /// Set what `if` accepts as a condition
+    ///
+    /// `evaluate` uses this policy; a context passed to `eval_expr` uses its own.
+    pub fn with_truthiness(mut self, truthiness: Truthiness) -> Self {
+        self.truthiness = truthiness;
+        self
+    }
+
+    /// What `if` accepts as a condition when `evaluate` runs this program
+    pub fn truthiness(&self) -> Truthiness {
+        self.truthiness
+    }
+
     /// Get the AST of the compiled program
     ///
     /// This is useful for backends that need direct access to the AST
@@ -78,59 +300,409 @@ impl CompiledProgram {
     }
 }
 
+/// Locale used when the evaluation context does not specify one
+pub const DEFAULT_LOCALE: &str = "en-US";
+
+/// Where `date_now` (and functions defaulting to today) get the current date
+#[derive(Clone, Default)]
+pub enum Clock {
+    /// The system clock, in UTC
+    #[default]
+    System,
+    /// A frozen `YYYY-MM-DD` date, for reproducible runs and backtesting
+    Fixed(String),
+    /// The system clock shifted by a number of days
+    Offset(i64),
+    /// A host-supplied source returning `YYYY-MM-DD` dates
+    Custom(Rc<dyn Fn() -> String>),
+}
+
+impl Clock {
+    /// Today's date according to this clock
+    pub fn today(&self) -> Result<Value, EvalError> {
+        use amoskeag_stdlib_functions::{date_add, date_now, date_parse};
+
+        let today = match self {
+            Clock::System => date_now()?,
+            Clock::Fixed(date) => date_parse(&Value::String(date.clone().into()))?,
+            Clock::Offset(days) => date_add(
+                &date_now()?,
+                &Value::Number(*days as f64),
+                &Value::String("days".into()),
+            )?,
+            Clock::Custom(source) => date_parse(&Value::String(source().into()))?,
+        };
+        Ok(today)
+    }
+}
+
+impl std::fmt::Debug for Clock {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Clock::System => write!(f, "System"),
+            Clock::Fixed(date) => f.debug_tuple("Fixed").field(date).finish(),
+            Clock::Offset(days) => f.debug_tuple("Offset").field(days).finish(),
+            Clock::Custom(_) => write!(f, "Custom(..)"),
+        }
+    }
+}
+
+/// A seed that differs between processes and between calls
+fn entropy_seed() -> u64 {
+    use std::collections::hash_map::RandomState;
+    use std::hash::{BuildHasher, Hasher};
+    use std::time::{SystemTime, UNIX_EPOCH};
+
+    let mut hasher = RandomState::new().build_hasher();
+    if let Ok(elapsed) = SystemTime::now().duration_since(UNIX_EPOCH) {
+        hasher.write_u128(elapsed.as_nanos());
+    }
+    hasher.finish()
+}
+
+/// How numeric literals and numbers from the data are represented during evaluation
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum NumericMode {
+    /// Integers and 64-bit floats
+    #[default]
+    Float,
+    /// Exact decimals with 28 significant digits, for monetary calculations
+    ///
+    /// Every number in the program and its data becomes a `Value::Decimal`,
+    /// so arithmetic operators never introduce binary rounding errors.
+    ///
+    /// These functions also compute exactly: `abs`, `ceil`, `floor`,
+    /// `round`, `sum`, `pmt`, `fv`, `pv`, `npv`, `fvschedule`, `compound`,
+    /// `amortization_schedule`, `sln`, `syd`, `tier_lookup`,
+    /// `tier_marginal`, and `prorate`. The other financial functions need
+    /// iteration, logarithms, or fractional powers, so calling them is an
+    /// error. Non-financial functions such as `sqrt` compute in floats and
+    /// their results are converted back to decimals.
+    Decimal,
+}
+
+/// What an `if` accepts as its condition
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Truthiness {
+    /// `false` and `nil` select the `else` branch; any other value, including
+    /// `0` and `""`, selects the `then` branch
+    #[default]
+    Truthy,
+    /// Conditions must be booleans; anything else is a `TypeError`
+    ///
+    /// This catches conditions such as `if driver.age`, which is true even
+    /// when the age is `0`.
+    Strict,
+}
+
 /// The execution context for evaluating an Amoskeag program
-pub struct Context {
+///
+/// The context either owns its data dictionary or borrows it for `'a`;
+/// `evaluate` borrows the caller's, so no copy is made per evaluation.
+pub struct Context<'a> {
     /// Local variable bindings (from let expressions)
-    locals: HashMap<String, Value>,
-    /// The data dictionary (implicit context)
-    data: HashMap<String, Value>,
+    locals: Scope,
+    /// The data dictionary (implicit context), shared with child contexts
+    data: Rc<Cow<'a, HashMap<String, Value>>>,
+    /// Locale used by formatting functions when none is passed explicitly
+    locale: String,
+    /// Random number state, shared with child contexts so `let` bodies
+    /// continue the same sequence
+    rng_state: Rc<Cell<u64>>,
+    /// Source of the current date
+    clock: Clock,
+    /// State behind `uuid`, seeded from system entropy on first use unless
+    /// the host fixes it
+    uuid_state: Rc<Cell<Option<u64>>>,
+    /// Whether numbers are evaluated as floats or exact decimals
+    numeric_mode: NumericMode,
+    /// Whether missing variables and keys are errors rather than nil
+    strict_navigation: bool,
+    /// What `if` accepts as a condition
+    truthiness: Truthiness,
+    /// Host functions callable alongside the standard library
+    functions: FunctionRegistry,
+    /// Steps taken and time spent against the evaluation's limits, shared
+    /// with child contexts
+    budget: Rc<Budget>,
+    /// Notified as each expression is evaluated
+    observer: Option<Rc<dyn EvalObserver>>,
+    /// Values of repeated sub-expressions, when memoization is on
+    memo: Option<Rc<Memo>>,
+    /// Fetches variables the data does not hold, shared with child contexts
+    provider: Option<Rc<Resolver>>,
+    /// Collects warnings, when the caller asked for them
+    warnings: Option<Rc<Warnings>>,
 }
 
-impl Context {
+impl<'a> Context<'a> {
     /// Create a new context with the given data
     pub fn new(data: HashMap<String, Value>) -> Self {
+        Self::with_data(Cow::Owned(data))
+    }
+
+    /// Create a new context that reads `data` in place rather than owning it
+    pub fn borrowed(data: &'a HashMap<String, Value>) -> Self {
+        Self::with_data(Cow::Borrowed(data))
+    }
+
+    fn with_data(data: Cow<'a, HashMap<String, Value>>) -> Self {
         Self {
-            locals: HashMap::new(),
-            data,
+            locals: Scope::default(),
+            data: Rc::new(data),
+            locale: DEFAULT_LOCALE.to_string(),
+            rng_state: Rc::new(Cell::new(0)),
+            clock: Clock::System,
+            uuid_state: Rc::new(Cell::new(None)),
+            numeric_mode: NumericMode::Float,
+            strict_navigation: false,
+            truthiness: Truthiness::Truthy,
+            functions: FunctionRegistry::default(),
+            budget: Rc::new(Budget::default()),
+            observer: None,
+            memo: None,
+            provider: None,
+            warnings: None,
+        }
+    }
+
+    /// Seed the generator behind `random` and `random_between`
+    ///
+    /// Contexts without an explicit seed use 0, so every evaluation is
+    /// reproducible unless the host chooses otherwise.
+    pub fn with_seed(self, seed: u64) -> Self {
+        self.rng_state.set(seed);
+        self
+    }
+
+    /// Seed the generator behind `uuid`
+    ///
+    /// Unlike `random`, unseeded contexts draw UUIDs from system entropy so
+    /// correlation identifiers differ between evaluations. Seed it in tests
+    /// to get a reproducible sequence.
+    pub fn with_uuid_seed(self, seed: u64) -> Self {
+        self.uuid_state.set(Some(seed));
+        self
+    }
+
+    /// Set the clock used by `date_now` and by functions that default to today
+    pub fn with_clock(mut self, clock: Clock) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// Set the default locale for formatting functions such as `currency`
+    pub fn with_locale(mut self, locale: impl Into<String>) -> Self {
+        self.locale = locale.into();
+        self
+    }
+
+    /// The default locale for formatting functions
+    pub fn locale(&self) -> &str {
+        &self.locale
+    }
+
+    /// Evaluate numbers as floats or as exact decimals
+    ///
+    /// This overrides the mode the program was compiled with.
+    pub fn with_numeric_mode(mut self, mode: NumericMode) -> Self {
+        self.numeric_mode = mode;
+        self
+    }
+
+    /// Whether numbers are evaluated as floats or exact decimals
+    pub fn numeric_mode(&self) -> NumericMode {
+        self.numeric_mode
+    }
+
+    /// Turn off safe navigation
+    ///
+    /// Reading a variable that is not defined, a key a dictionary does not
+    /// have, or a key of something that is not a dictionary fails with
+    /// `EvalError::VariableNotFound`, `UnknownKey`, or `NotADictionary`,
+    /// so a typo such as `drver.age` is caught rather than read as `nil`.
+    pub fn with_strict_navigation(mut self, strict: bool) -> Self {
+        self.strict_navigation = strict;
+        self
+    }
+
+    /// Set what `if` accepts as a condition
+    pub fn with_truthiness(mut self, truthiness: Truthiness) -> Self {
+        self.truthiness = truthiness;
+        self
+    }
+
+    /// Limit the work done by evaluations in this context, and set whether
+    /// they memoize, where missing variables come from, and any clock,
+    /// seeds, or locale `options` sets
+    ///
+    /// Steps are counted, and any timeout measured, from this call. A
+    /// context that memoizes caches values for the first program it
+    /// evaluates, so it should only be used for that program. Warnings and
+    /// explanations are only collected by `evaluate_detailed`.
+    pub fn with_options(mut self, options: EvalOptions) -> Self {
+        self.memo = options.memoization().then(|| Rc::new(Memo::default()));
+        if let Some(provider) = options.provider() {
+            self = self.with_provider(provider.clone());
+        }
+        if let Some(clock) = options.clock() {
+            self.clock = clock.clone();
+        }
+        if let Some(seed) = options.seed() {
+            self = self.with_seed(seed);
+        }
+        if let Some(seed) = options.uuid_seed() {
+            self = self.with_uuid_seed(seed);
+        }
+        if let Some(locale) = options.locale() {
+            self.locale = locale.to_string();
+        }
+        self.budget = Rc::new(Budget::new(options));
+        self
+    }
+
+    /// Notify `observer` before and after each expression is evaluated
+    ///
+    /// Keep a clone of the `Rc` to read back what the observer recorded.
+    pub fn with_observer(mut self, observer: Rc<dyn EvalObserver>) -> Self {
+        self.observer = Some(observer);
+        self
+    }
+
+    /// Ask `provider` for variables that are neither local nor in the data
+    ///
+    /// Each path is resolved at most once per context, the first time it is
+    /// read. With a provider, reading an unknown variable gives whatever the
+    /// provider returns instead of `EvalError::VariableNotFound`.
+    pub fn with_provider(mut self, provider: Rc<dyn DataProvider>) -> Self {
+        self.provider = Some(Rc::new(Resolver::new(provider)));
+        self
+    }
+
+    /// Make the host functions in `functions` callable
+    ///
+    /// `evaluate` passes along the registry the program was compiled with.
+    pub fn with_functions(mut self, functions: FunctionRegistry) -> Self {
+        self.functions = functions;
+        self
+    }
+
+    /// `value` with its numbers in this context's representation
+    fn numeric(&self, value: Value) -> Value {
+        match self.numeric_mode {
+            NumericMode::Float => value,
+            NumericMode::Decimal => value.to_decimals(),
         }
     }
 
     /// Create a child context with a new local binding
+    ///
+    /// The binding is pushed onto the parent's scope, which is shared rather
+    /// than copied, so a binding costs the same however many are in scope.
     fn with_local(&self, name: String, value: Value) -> Self {
-        let mut locals = self.locals.clone();
-        locals.insert(name, value);
+        self.with_scope(self.locals.push(name, value))
+    }
+
+    /// Create a child context with `locals` in scope
+    fn with_scope(&self, locals: Scope) -> Self {
         Self {
             locals,
-            data: self.data.clone(),
+            data: Rc::clone(&self.data),
+            locale: self.locale.clone(),
+            rng_state: Rc::clone(&self.rng_state),
+            clock: self.clock.clone(),
+            uuid_state: Rc::clone(&self.uuid_state),
+            numeric_mode: self.numeric_mode,
+            strict_navigation: self.strict_navigation,
+            truthiness: self.truthiness,
+            functions: self.functions.clone(),
+            budget: Rc::clone(&self.budget),
+            observer: self.observer.clone(),
+            memo: self.memo.clone(),
+            provider: self.provider.clone(),
+            warnings: self.warnings.clone(),
         }
     }
 
-    /// Look up a variable in the context
-    /// Resolution order: locals -> data -> nil
-    ///
-    /// # Safety
-    /// This method implements safe navigation - it never fails,
-    /// returning Nil for undefined variables instead of an error.
-    fn lookup(&self, name: &str) -> Value {
-        debug_assert!(!name.is_empty(), "lookup() called with empty name");
+    /// Look up a variable in the context without cloning it
+    /// Resolution order: locals -> data
+    fn get(&self, name: &str) -> Option<&Value> {
+        debug_assert!(!name.is_empty(), "get() called with empty name");
+        self.locals.get(name).or_else(|| self.data.get(name))
+    }
 
-        // 1. Check local scope
-        if let Some(value) = self.locals.get(name) {
-            return value.clone();
+    /// Record the warning built by `warning`, if warnings are being collected
+    fn warn(&self, warning: impl FnOnce() -> Warning) {
+        if let Some(warnings) = &self.warnings {
+            warnings.push(warning());
         }
+    }
 
-        // 2. Check data dictionary
-        if let Some(value) = self.data.get(name) {
-            return value.clone();
-        }
+    /// Run `f` against the random state, keeping whatever it advances to
+    fn with_rng<T>(&self, f: impl FnOnce(&mut u64) -> T) -> T {
+        let mut state = self.rng_state.get();
+        let result = f(&mut state);
+        self.rng_state.set(state);
+        result
+    }
 
-        // 3. Return nil if not found (safe navigation)
-        Value::Nil
+    /// Run `f` against the UUID state, seeding it from entropy if unset
+    fn with_uuid_rng<T>(&self, f: impl FnOnce(&mut u64) -> T) -> T {
+        let mut state = self.uuid_state.get().unwrap_or_else(entropy_seed);
+        let result = f(&mut state);
+        self.uuid_state.set(Some(state));
+        result
     }
 
     fn contains(&self, name: &str) -> bool {
         debug_assert!(!name.is_empty(), "contains() called with empty name");
-        self.locals.contains_key(name) || self.data.contains_key(name)
+        self.locals.get(name).is_some() || self.data.contains_key(name)
+    }
+}
+
+/// Local bindings as a stack of frames, innermost on top
+///
+/// Pushing a binding makes a new top frame that shares every frame below it,
+/// and a scope is popped by dropping it, so nested `let`s cost nothing for
+/// the bindings already in scope.
+#[derive(Clone, Default)]
+struct Scope(Option<Rc<Frame>>);
+
+struct Frame {
+    name: String,
+    value: Value,
+    below: Scope,
+}
+
+impl Scope {
+    /// This scope with `name` bound to `value` on top
+    fn push(&self, name: String, value: Value) -> Scope {
+        Scope(Some(Rc::new(Frame {
+            name,
+            value,
+            below: self.clone(),
+        })))
+    }
+
+    /// The innermost binding of `name`
+    fn get(&self, name: &str) -> Option<&Value> {
+        self.frames()
+            .find(|frame| frame.name == name)
+            .map(|frame| &frame.value)
+    }
+
+    /// The bindings visible in this scope, for capture by a closure
+    fn to_map(&self) -> HashMap<String, Value> {
+        let mut map = HashMap::new();
+        for frame in self.frames() {
+            map.entry(frame.name.clone())
+                .or_insert_with(|| frame.value.clone());
+        }
+        map
+    }
+
+    fn frames(&self) -> impl Iterator<Item = &Frame> {
+        std::iter::successors(self.0.as_deref(), |frame| frame.below.0.as_deref())
     }
 }
 
@@ -145,6 +717,169 @@ impl Context {
 ///
 /// A compiled program or a compilation error
 pub fn compile(source: &str, symbols: &[&str]) -> Result<CompiledProgram, CompileError> {
+    compile_with_options(source, symbols, &CompileOptions::default())
+}
+
+/// What a program is compiled against beyond its symbols
+#[derive(Clone, Default)]
+pub struct CompileOptions {
+    functions: FunctionRegistry,
+    contract: Option<DataContract>,
+    variables: Option<HashSet<String>>,
+    allowed_functions: Option<HashSet<String>>,
+    denied_functions: HashSet<String>,
+    deprecated_functions: HashMap<String, String>,
+}
+
+impl CompileOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Allow calls to the host functions in `functions`
+    pub fn with_functions(mut self, functions: FunctionRegistry) -> Self {
+        self.functions = functions;
+        self
+    }
+
+    /// Type-check the program against the data described by `contract`
+    pub fn with_contract(mut self, contract: DataContract) -> Self {
+        self.contract = Some(contract);
+        self
+    }
+
+    /// Allow only these root variables, besides those bound by `let` and
+    /// those the contract declares
+    ///
+    /// Reading any other variable is `CompileError::UndefinedVariable`, so a
+    /// typo such as `drver.age` is caught before the program runs. With a
+    /// contract and no list, the contract's roots are the only ones allowed.
+    pub fn with_variables<S: Into<String>>(mut self, roots: impl IntoIterator<Item = S>) -> Self {
+        self.variables = Some(roots.into_iter().map(Into::into).collect());
+        self
+    }
+
+    /// Allow calls only to the functions in `names`, standard library or host
+    ///
+    /// Calling any other function is `CompileError::DisallowedFunction`.
+    pub fn with_allowed_functions<S: Into<String>>(
+        mut self,
+        names: impl IntoIterator<Item = S>,
+    ) -> Self {
+        self.allowed_functions = Some(names.into_iter().map(Into::into).collect());
+        self
+    }
+
+    /// Reject calls to the functions in `names` with
+    /// `CompileError::DisallowedFunction`
+    ///
+    /// Denying `date_now`, `random`, `random_between` and `uuid`, for
+    /// example, keeps a batch rule's result a function of its data alone.
+    pub fn with_denied_functions<S: Into<String>>(
+        mut self,
+        names: impl IntoIterator<Item = S>,
+    ) -> Self {
+        self.denied_functions
+            .extend(names.into_iter().map(Into::into));
+        self
+    }
+
+    /// Warn about calls to `name`, suggesting `replacement` instead
+    ///
+    /// `compile_with_warnings` reports each deprecated function called as a
+    /// `Warning::DeprecatedFunction`. If `name` is also disallowed, the error
+    /// suggests `replacement`.
+    pub fn with_deprecated_function(
+        mut self,
+        name: impl Into<String>,
+        replacement: impl Into<String>,
+    ) -> Self {
+        self.deprecated_functions
+            .insert(name.into(), replacement.into());
+        self
+    }
+
+    pub fn functions(&self) -> &FunctionRegistry {
+        &self.functions
+    }
+
+    pub fn contract(&self) -> Option<&DataContract> {
+        self.contract.as_ref()
+    }
+
+    /// Whether calls to `name` are allowed
+    fn allows_function(&self, name: &str) -> bool {
+        !self.denied_functions.contains(name)
+            && self
+                .allowed_functions
+                .as_ref()
+                .is_none_or(|allowed| allowed.contains(name))
+    }
+
+    /// What to call instead of the deprecated function `name`
+    fn replacement(&self, name: &str) -> Option<&str> {
+        self.deprecated_functions
+            .get(name)
+            .map(String::as_str)
+            .or_else(|| signatures::lookup(name).and_then(|function| function.replacement))
+    }
+
+    /// Whether `root` may be read, or `None` if variables are not checked
+    fn allows_variable(&self, root: &str) -> Option<bool> {
+        if self.variables.is_none() && self.contract.is_none() {
+            return None;
+        }
+        let listed = self
+            .variables
+            .as_ref()
+            .is_some_and(|roots| roots.contains(root));
+        let declared = self.contract.as_ref().is_some_and(|c| c.has_root(root));
+        Some(listed || declared)
+    }
+}
+
+/// Compile an Amoskeag program that may call the host functions in `functions`
+///
+/// Calls to registered functions are validated against their signatures
+/// like calls to the standard library. A registered name that is already a
+/// standard library function is rejected.
+pub fn compile_with_functions(
+    source: &str,
+    symbols: &[&str],
+    functions: &FunctionRegistry,
+) -> Result<CompiledProgram, CompileError> {
+    let options = CompileOptions::new().with_functions(functions.clone());
+    compile_with_options(source, symbols, &options)
+}
+
+/// Compile an Amoskeag program with the host functions and data contract in `options`
+///
+/// With a contract, the program is also type-checked: see `DataContract`.
+pub fn compile_with_options(
+    source: &str,
+    symbols: &[&str],
+    options: &CompileOptions,
+) -> Result<CompiledProgram, CompileError> {
+    compile_with_warnings(source, symbols, options).map(|(program, _)| program)
+}
+
+/// Compile an Amoskeag program like `compile_with_options`, also returning
+/// warnings about calls to deprecated functions
+pub fn compile_with_warnings(
+    source: &str,
+    symbols: &[&str],
+    options: &CompileOptions,
+) -> Result<(CompiledProgram, Vec<Warning>), CompileError> {
+    let functions = &options.functions;
+    if let Some(name) = functions
+        .names()
+        .find(|name| builtin_signature(name).is_some())
+    {
+        return Err(CompileError::DuplicateFunction {
+            function: name.to_string(),
+        });
+    }
+
     // Parse the source code
     let mut lexer = Lexer::new(source);
     let tokens = lexer
@@ -159,54 +894,114 @@ pub fn compile(source: &str, symbols: &[&str]) -> Result<CompiledProgram, Compil
     // Build the symbol table
     let symbol_table: HashSet<String> = symbols.iter().map(|s| s.to_string()).collect();
 
-    // Validate symbols and functions in the AST
-    validate_ast(&ast, &symbol_table)?;
+    // Validate symbols, functions and variables in the AST, reporting
+    // every violation at once
+    let mut errors = Vec::new();
+    collect_ast_errors(&ast, &symbol_table, functions, &mut errors);
+    validate_variables(&ast, options, &mut Vec::new(), &mut errors);
+    let warnings = check_function_policy(&ast, options, &mut errors);
+    CompileError::from_errors(errors)?;
+
+    // Types are only checked once every name is known
+    if let Some(contract) = &options.contract {
+        contract::check(&ast, contract)?;
+    }
 
-    Ok(CompiledProgram {
+    let program = CompiledProgram {
         ast,
         symbols: symbol_table,
-    })
+        numeric_mode: NumericMode::Float,
+        functions: functions.clone(),
+        strict_navigation: false,
+        truthiness: Truthiness::Truthy,
+    };
+    Ok((program, warnings))
+}
+
+/// Apply the allowed, denied and deprecated functions in `options` to the
+/// calls in `ast`, in source order, pushing disallowed calls onto `errors`
+fn check_function_policy(
+    ast: &Expr,
+    options: &CompileOptions,
+    errors: &mut Vec<CompileError>,
+) -> Vec<Warning> {
+    let mut warnings = Vec::new();
+    for name in introspect::calls(ast) {
+        let replacement = options.replacement(name);
+        if !options.allows_function(name) {
+            errors.push(CompileError::DisallowedFunction {
+                function: name.to_string(),
+                replacement: replacement.map(str::to_string),
+            });
+        } else if let Some(replacement) = replacement {
+            let warning = Warning::DeprecatedFunction {
+                name: name.to_string(),
+                replacement: replacement.to_string(),
+            };
+            if !warnings.contains(&warning) {
+                warnings.push(warning);
+            }
+        }
+    }
+    warnings
+}
+
+/// Validate the AST for undefined symbols and functions, reporting every
+/// violation rather than only the first
+fn validate_ast(
+    expr: &Expr,
+    symbols: &HashSet<String>,
+    functions: &FunctionRegistry,
+) -> Result<(), CompileError> {
+    let mut errors = Vec::new();
+    collect_ast_errors(expr, symbols, functions, &mut errors);
+    CompileError::from_errors(errors)
 }
 
-/// Validate the AST for undefined symbols and functions
-fn validate_ast(expr: &Expr, symbols: &HashSet<String>) -> Result<(), CompileError> {
+/// Push the undefined symbols and invalid function calls in `expr` onto `errors`
+fn collect_ast_errors(
+    expr: &Expr,
+    symbols: &HashSet<String>,
+    functions: &FunctionRegistry,
+    errors: &mut Vec<CompileError>,
+) {
     match expr {
         Expr::Symbol(s) => {
             if !symbols.contains(s) {
-                return Err(CompileError::UndefinedSymbol { symbol: s.clone() });
+                errors.push(CompileError::UndefinedSymbol { symbol: s.clone() });
             }
-            Ok(())
         }
 
         Expr::Array(exprs) => {
             for e in exprs {
-                validate_ast(e, symbols)?;
+                collect_ast_errors(e, symbols, functions, errors);
             }
-            Ok(())
         }
 
         Expr::Dictionary(pairs) => {
             for (_, e) in pairs {
-                validate_ast(e, symbols)?;
+                collect_ast_errors(e, symbols, functions, errors);
             }
-            Ok(())
         }
 
         Expr::FunctionCall { name, args } => {
-            // Validate function exists and has correct arity
-            validate_function_call(name, args.len())?;
+            // Validate function exists and has correct arity, then its literal arguments
+            let call = validate_function_call(name, args.len(), functions)
+                .and_then(|()| validate_literal_pattern(name, args))
+                .and_then(|()| validate_literal_bounds(name, args));
+            if let Err(e) = call {
+                errors.push(e);
+            }
 
             // Validate arguments
             for arg in args {
-                validate_ast(arg, symbols)?;
+                collect_ast_errors(arg, symbols, functions, errors);
             }
-            Ok(())
         }
 
         Expr::Let { value, body, .. } => {
-            validate_ast(value, symbols)?;
-            validate_ast(body, symbols)?;
-            Ok(())
+            collect_ast_errors(value, symbols, functions, errors);
+            collect_ast_errors(body, symbols, functions, errors);
         }
 
         Expr::If {
@@ -214,171 +1009,443 @@ fn validate_ast(expr: &Expr, symbols: &HashSet<String>) -> Result<(), CompileErr
             then_branch,
             else_branch,
         } => {
-            validate_ast(condition, symbols)?;
-            validate_ast(then_branch, symbols)?;
-            validate_ast(else_branch, symbols)?;
-            Ok(())
+            collect_ast_errors(condition, symbols, functions, errors);
+            collect_ast_errors(then_branch, symbols, functions, errors);
+            collect_ast_errors(else_branch, symbols, functions, errors);
         }
 
-        Expr::Binary { left, right, .. } => {
-            validate_ast(left, symbols)?;
-            validate_ast(right, symbols)?;
-            Ok(())
+        Expr::Binary { left, right, .. } | Expr::Pipe { left, right } => {
+            collect_ast_errors(left, symbols, functions, errors);
+            collect_ast_errors(right, symbols, functions, errors);
         }
 
-        Expr::Unary { operand, .. } => validate_ast(operand, symbols),
-
-        Expr::Pipe { left, right } => {
-            validate_ast(left, symbols)?;
-            validate_ast(right, symbols)?;
-            Ok(())
-        }
+        Expr::Unary { operand, .. } => collect_ast_errors(operand, symbols, functions, errors),
 
         // Literals and variables don't need validation
-        Expr::Number(_) | Expr::String(_) | Expr::Boolean(_) | Expr::Nil | Expr::Variable(_) => {
-            Ok(())
-        }
+        Expr::Number(_)
+        | Expr::Integer(_)
+        | Expr::String(_)
+        | Expr::Boolean(_)
+        | Expr::Nil
+        | Expr::Variable(_) => {}
     }
 }
 
-/// Validate a function call (existence and arity)
-fn validate_function_call(name: &str, arg_count: usize) -> Result<(), CompileError> {
-    // Define function signatures (name -> (min_args, max_args))
-    // For variadic functions, min and max are different
-    let function_signatures: HashMap<&str, (usize, usize)> = [
-        // String functions
-        ("upcase", (1, 1)),
-        ("downcase", (1, 1)),
-        ("capitalize", (1, 1)),
-        ("strip", (1, 1)),
-        ("split", (2, 2)),
-        ("join", (2, 2)),
-        ("truncate", (2, 2)),
-        ("replace", (3, 3)),
-        // Numeric functions
-        ("abs", (1, 1)),
-        ("ceil", (1, 1)),
-        ("floor", (1, 1)),
-        ("round", (1, 2)),
-        ("plus", (2, 2)),
-        ("minus", (2, 2)),
-        ("times", (2, 2)),
-        ("divided_by", (2, 2)),
-        ("modulo", (2, 2)),
-        ("max", (2, 2)),
-        ("min", (2, 2)),
-        ("array_min", (1, 1)),
-        ("array_max", (1, 1)),
-        ("power", (2, 2)),
-        ("sqrt", (1, 1)),
-        ("log", (1, 1)),
-        ("log10", (1, 1)),
-        ("ln", (1, 1)),
-        // Collection functions
-        ("size", (1, 1)),
-        ("first", (1, 1)),
-        ("last", (1, 1)),
-        ("contains", (2, 2)),
-        ("sum", (1, 1)),
-        ("avg", (1, 1)),
-        ("sort", (1, 1)),
-        ("keys", (1, 1)),
-        ("values", (1, 1)),
-        ("reverse", (1, 1)),
-        ("at", (2, 2)),
-        ("uniq", (1, 1)),
-        ("group_by", (2, 2)),
-        ("map", (2, 2)),
-        // Logic functions
-        ("choose", (2, 2)),
-        ("if_then_else", (3, 3)),
-        ("is_number", (1, 1)),
-        ("is_string", (1, 1)),
-        ("is_boolean", (1, 1)),
-        ("is_nil", (1, 1)),
-        ("is_array", (1, 1)),
-        ("is_dictionary", (1, 1)),
-        ("coalesce", (2, 2)),
-        ("default", (2, 2)),
-        // Financial functions - Time Value of Money
-        ("pmt", (4, 4)),
-        ("pv", (3, 3)),
-        ("fv", (4, 4)),
-        ("nper", (3, 3)),
-        ("rate", (3, 3)),
-        // Financial functions - Investment Analysis
-        ("npv", (2, 2)),
-        ("irr", (1, 1)),
-        ("mirr", (3, 3)),
-        // Financial functions - Depreciation
-        ("sln", (3, 3)),
-        ("ddb", (4, 4)),
-        ("db", (5, 5)),
-        // Financial functions - Payment Components
-        ("ipmt", (5, 5)),
-        ("ppmt", (5, 5)),
-        ("cumipmt", (6, 6)),
-        ("cumprinc", (6, 6)),
-        // Financial functions - Interest Rate Conversion
-        ("effect", (2, 2)),
-        ("nominal", (2, 2)),
-        // Date functions
-        ("date_now", (0, 0)),
-        ("date_format", (2, 2)),
-        ("date_trunc", (1, 1)),
-        ("date_parse", (1, 1)),
-    ]
-    .iter()
-    .cloned()
-    .collect();
-
-    if let Some((min_args, max_args)) = function_signatures.get(name) {
-        if arg_count < *min_args || arg_count > *max_args {
-            let expected = if min_args == max_args {
-                format!("{}", min_args)
-            } else {
-                format!("{}-{}", min_args, max_args)
-            };
-            return Err(CompileError::ArityMismatch {
+/// Reject regex patterns that are string literals and fail to compile
+fn validate_literal_pattern(name: &str, args: &[Expr]) -> Result<(), CompileError> {
+    if !matches!(
+        name,
+        "regex_match" | "regex_replace" | "regex_extract" | "matches_pattern"
+    ) {
+        return Ok(());
+    }
+    if let Some(Expr::String(pattern)) = args.get(1) {
+        if let Err(FunctionError::InvalidPattern { pattern, message }) =
+            amoskeag_stdlib_functions::compile_pattern(pattern)
+        {
+            return Err(CompileError::InvalidPattern {
                 function: name.to_string(),
-                expected,
-                actual: arg_count,
+                pattern,
+                message,
             });
         }
-        Ok(())
-    } else {
-        Err(CompileError::UndefinedFunction {
-            function: name.to_string(),
-        })
     }
+    Ok(())
 }
 
-/// Evaluate a compiled Amoskeag program
-///
-/// # Arguments
-///
-/// * `program` - The compiled program to evaluate
-/// * `data` - The data dictionary (execution context)
-///
-/// # Returns
-///
-/// The result of the evaluation
-pub fn evaluate(
-    program: &CompiledProgram,
-    data: &HashMap<String, Value>,
-) -> Result<Value, EvalError> {
-    let context = Context::new(data.clone());
-    eval_expr(&program.ast, &context)
+/// Reject `between` bounds flags that are literals other than "[]", "[)", "(]", or "()"
+fn validate_literal_bounds(name: &str, args: &[Expr]) -> Result<(), CompileError> {
+    if name != "between" {
+        return Ok(());
+    }
+    let message = match args.get(3) {
+        Some(Expr::String(bounds)) => match amoskeag_stdlib_functions::parse_bounds(bounds) {
+            Err(FunctionError::ArgumentError { message }) => message,
+            _ => return Ok(()),
+        },
+        Some(
+            Expr::Number(_)
+            | Expr::Integer(_)
+            | Expr::Boolean(_)
+            | Expr::Nil
+            | Expr::Symbol(_)
+            | Expr::Array(_)
+            | Expr::Dictionary(_),
+        ) => "bounds must be a string".to_string(),
+        _ => return Ok(()),
+    };
+    Err(CompileError::InvalidArgument {
+        function: name.to_string(),
+        message,
+    })
 }
 
-/// Evaluate an expression in a given context
-///
-/// This function is public to allow backend implementations to use it directly.
+/// Push the variables read that are neither `let`-bound (`bound` holds the
+/// names in scope) nor allowed by `options` onto `errors`
+fn validate_variables<'e>(
+    expr: &'e Expr,
+    options: &CompileOptions,
+    bound: &mut Vec<&'e str>,
+    errors: &mut Vec<CompileError>,
+) {
+    match expr {
+        Expr::Variable(path) => {
+            if let Some(root) = path.first() {
+                if !bound.contains(&root.as_str()) && options.allows_variable(root) == Some(false) {
+                    errors.push(CompileError::UndefinedVariable {
+                        variable: root.clone(),
+                    });
+                }
+            }
+        }
+        Expr::Let { name, value, body } => {
+            validate_variables(value, options, bound, errors);
+            bound.push(name);
+            validate_variables(body, options, bound, errors);
+            bound.pop();
+        }
+        Expr::Array(items) | Expr::FunctionCall { args: items, .. } => {
+            for item in items {
+                validate_variables(item, options, bound, errors);
+            }
+        }
+        Expr::Dictionary(pairs) => {
+            for (_, value) in pairs {
+                validate_variables(value, options, bound, errors);
+            }
+        }
+        Expr::If {
+            condition,
+            then_branch,
+            else_branch,
+        } => {
+            validate_variables(condition, options, bound, errors);
+            validate_variables(then_branch, options, bound, errors);
+            validate_variables(else_branch, options, bound, errors);
+        }
+        Expr::Binary { left, right, .. } => {
+            validate_variables(left, options, bound, errors);
+            validate_variables(right, options, bound, errors);
+        }
+        Expr::Unary { operand, .. } => validate_variables(operand, options, bound, errors),
+        // A bare name on the right of a pipe is a function, not a variable
+        Expr::Pipe { left, right } => {
+            validate_variables(left, options, bound, errors);
+            if !matches!(right.as_ref(), Expr::Variable(_)) {
+                validate_variables(right, options, bound, errors);
+            }
+        }
+        Expr::Number(_)
+        | Expr::Integer(_)
+        | Expr::String(_)
+        | Expr::Boolean(_)
+        | Expr::Nil
+        | Expr::Symbol(_) => {}
+    }
+}
+
+/// Validate a function call (existence and arity)
+fn validate_function_call(
+    name: &str,
+    arg_count: usize,
+    functions: &FunctionRegistry,
+) -> Result<(), CompileError> {
+    let signature = builtin_signature(name)
+        .or_else(|| functions.signature(name))
+        .ok_or_else(|| CompileError::UndefinedFunction {
+            function: name.to_string(),
+        })?;
+    if !signature.accepts(arg_count) {
+        return Err(CompileError::ArityMismatch {
+            function: name.to_string(),
+            expected: signature.describe(),
+            actual: arg_count,
+        });
+    }
+    Ok(())
+}
+
+/// The signature of the standard library function `name`
+fn builtin_signature(name: &str) -> Option<Signature> {
+    signatures::lookup(name).map(|function| function.arity.into())
+}
+
+/// Evaluate a compiled Amoskeag program
+///
+/// # Arguments
+///
+/// * `program` - The compiled program to evaluate
+/// * `data` - The data dictionary (execution context)
+///
+/// # Returns
+///
+/// The result of the evaluation
+pub fn evaluate(
+    program: &CompiledProgram,
+    data: &HashMap<String, Value>,
+) -> Result<Value, EvalError> {
+    evaluate_with_options(program, data, EvalOptions::default())
+}
+
+/// Evaluate a compiled Amoskeag program within the limits in `options`
+///
+/// Returns `EvalError::LimitExceeded` if the evaluation takes too many steps
+/// or runs past its timeout. Use `evaluate_detailed` to get back the
+/// warnings or explanation `options` asks for.
+pub fn evaluate_with_options(
+    program: &CompiledProgram,
+    data: &HashMap<String, Value>,
+    options: EvalOptions,
+) -> Result<Value, EvalError> {
+    evaluate_detailed(program, data, options).map(|evaluation| evaluation.value)
+}
+
+/// The result of an evaluation, with the warnings and explanation its
+/// options asked for
+#[derive(Debug, Clone, PartialEq)]
+pub struct Evaluation {
+    pub value: Value,
+    /// Empty unless `EvalOptions::with_warnings` was set
+    pub warnings: Vec<Warning>,
+    /// `None` unless `EvalOptions::with_explanation` was set
+    pub explanation: Option<Explanation>,
+}
+
+/// Evaluate a compiled Amoskeag program as `options` describe
+///
+/// The other `evaluate` functions are shorthands for this one, so limits,
+/// a data provider, warnings, and an explanation can all be combined here.
+/// If the evaluation fails, its warnings and explanation are discarded
+/// with it.
+pub fn evaluate_detailed(
+    program: &CompiledProgram,
+    data: &HashMap<String, Value>,
+    options: EvalOptions,
+) -> Result<Evaluation, EvalError> {
+    let warnings = options.warnings().then(|| Rc::new(Warnings::default()));
+    let explainer = options
+        .explanation()
+        .then(|| Rc::new(explain::Explainer::default()));
+    let mut context = program_context(program, data, options);
+    context.warnings = warnings.clone();
+    if let Some(explainer) = &explainer {
+        context = context.with_observer(explainer.clone());
+    }
+    let value = eval_expr(&program.ast, &context)?;
+    drop(context);
+    let warnings = warnings.map(|warnings| {
+        Rc::try_unwrap(warnings)
+            .unwrap_or_else(|_| unreachable!("the context was the only other owner"))
+            .into_vec()
+    });
+    let explanation = explainer.map(|explainer| {
+        Rc::try_unwrap(explainer)
+            .unwrap_or_else(|_| unreachable!("the context was the only other owner"))
+            .into_explanation()
+    });
+    Ok(Evaluation {
+        value,
+        warnings: warnings.unwrap_or_default(),
+        explanation,
+    })
+}
+
+/// Evaluate a compiled Amoskeag program against each record in turn
+///
+/// Records are pulled from `records` only as results are taken, so a stream
+/// of any length is scored in constant memory:
+///
+/// ```no_run
+/// # use std::io::BufReader;
+/// # let program = amoskeag::compile("score", &[]).unwrap();
+/// let file = BufReader::new(std::fs::File::open("quotes.ndjson").unwrap());
+/// let records = amoskeag::json::ndjson_data(file).map(Result::unwrap);
+/// for result in amoskeag::evaluate_each(&program, records) {
+///     println!("{}", result.unwrap());
+/// }
+/// ```
+pub fn evaluate_each<'p, I>(
+    program: &'p CompiledProgram,
+    records: I,
+) -> impl Iterator<Item = Result<Value, EvalError>> + 'p
+where
+    I: IntoIterator<Item = HashMap<String, Value>>,
+    I::IntoIter: 'p,
+{
+    evaluate_each_with_options(program, records, EvalOptions::default())
+}
+
+/// Evaluate a compiled Amoskeag program against each record in turn, each
+/// within the limits in `options`
+///
+/// Limits apply to each record separately, and a provider is asked afresh
+/// for each record.
+pub fn evaluate_each_with_options<'p, I>(
+    program: &'p CompiledProgram,
+    records: I,
+    options: EvalOptions,
+) -> impl Iterator<Item = Result<Value, EvalError>> + 'p
+where
+    I: IntoIterator<Item = HashMap<String, Value>>,
+    I::IntoIter: 'p,
+{
+    records
+        .into_iter()
+        .map(move |data| evaluate_with_options(program, &data, options.clone()))
+}
+
+/// Evaluate a compiled Amoskeag program, fetching variables missing from
+/// `data` from `provider` as the program reads them
+///
+/// Paths are passed to the provider whole, so `driver.credit.score` asks for
+/// `["driver", "credit", "score"]` unless `data` holds `driver`.
+pub fn evaluate_with_provider(
+    program: &CompiledProgram,
+    data: &HashMap<String, Value>,
+    provider: Rc<dyn DataProvider>,
+) -> Result<Value, EvalError> {
+    evaluate_with_options(program, data, EvalOptions::new().with_provider(provider))
+}
+
+/// Evaluate a compiled Amoskeag program, collecting warnings about
+/// questionable but legal behavior along the way
+///
+/// Warnings raised inside a `try` whose fallback was used are kept. If the
+/// evaluation fails, its warnings are discarded with it.
+pub fn evaluate_with_warnings(
+    program: &CompiledProgram,
+    data: &HashMap<String, Value>,
+) -> Result<(Value, Vec<Warning>), EvalError> {
+    let evaluation = evaluate_detailed(program, data, EvalOptions::new().with_warnings(true))?;
+    Ok((evaluation.value, evaluation.warnings))
+}
+
+/// Evaluate a compiled Amoskeag program, recording how it reached its result
+///
+/// The explanation lists the `if` conditions tested and which branch each
+/// took, the variables read, and the functions called with their arguments.
+pub fn evaluate_with_explanation(
+    program: &CompiledProgram,
+    data: &HashMap<String, Value>,
+) -> Result<(Value, Explanation), EvalError> {
+    let evaluation = evaluate_detailed(program, data, EvalOptions::new().with_explanation(true))?;
+    let explanation = evaluation.explanation.unwrap_or_default();
+    Ok((evaluation.value, explanation))
+}
+
+/// A context for running `program` over `data`, borrowed in place, as
+/// `options` describe
+fn program_context<'a>(
+    program: &CompiledProgram,
+    data: &'a HashMap<String, Value>,
+    options: EvalOptions,
+) -> Context<'a> {
+    Context::borrowed(data)
+        .with_numeric_mode(program.numeric_mode)
+        .with_functions(program.functions.clone())
+        .with_strict_navigation(program.strict_navigation)
+        .with_truthiness(program.truthiness)
+        .with_options(options)
+}
+
+/// Create a lambda that captures the local bindings of `context`
+///
+/// Calling it with `apply` evaluates `body` with those bindings and the
+/// arguments bound to `params`; data, locale, and the other settings come
+/// from the context it is called in.
+pub fn closure(params: Vec<String>, body: Expr, context: &Context) -> Value {
+    let closure = Closure::new(params, body, context.locals.to_map());
+    Value::Function(Function::Closure(Arc::new(closure)))
+}
+
+/// Call a function value with already-evaluated arguments
+pub fn apply(function: &Function, args: &[Value], context: &Context) -> Result<Value, EvalError> {
+    if let Some(expected) = function.arity() {
+        if args.len() != expected {
+            return Err(EvalError::ArityMismatch {
+                function: function.to_string(),
+                expected,
+                actual: args.len(),
+            });
+        }
+    }
+
+    match function {
+        Function::Closure(closure) => {
+            let (Some(body), Some(scope)) = (
+                closure.body::<Expr>(),
+                closure.scope::<HashMap<String, Value>>(),
+            ) else {
+                return Err(EvalError::TypeError {
+                    expected: "Amoskeag function".to_string(),
+                    got: "closure from another evaluator".to_string(),
+                });
+            };
+            let mut locals = Scope::default();
+            for (name, value) in scope.iter().chain(closure.params().iter().zip(args)) {
+                locals = locals.push(name.clone(), value.clone());
+            }
+            eval_expr(body, &context.with_scope(locals))
+        }
+        Function::Host(host) => host
+            .call(args)
+            .map(|result| context.numeric(result))
+            .map_err(|message| EvalError::HostFunctionError {
+                function: host.name().to_string(),
+                message,
+            }),
+    }
+}
+
+/// Evaluate an expression in a given context
+///
+/// This function is public to allow backend implementations to use it directly.
 pub fn eval_expr(expr: &Expr, context: &Context) -> Result<Value, EvalError> {
+    match &context.observer {
+        Some(observer) => {
+            observer.enter(expr);
+            let result = eval_limited(expr, context);
+            observer.exit(expr, &result);
+            result
+        }
+        None => eval_limited(expr, context),
+    }
+}
+
+/// Evaluate one node within the context's limits
+fn eval_limited(expr: &Expr, context: &Context) -> Result<Value, EvalError> {
+    context.budget.step()?;
+    // Observers and warnings must see every occurrence, so nothing is
+    // cached while either is active
+    let cached = context
+        .memo
+        .as_deref()
+        .filter(|_| context.observer.is_none() && context.warnings.is_none())
+        .and_then(|memo| Some((memo, memo.class(expr)?)));
+    if let Some(value) = cached.and_then(|(memo, class)| memo.get(class)) {
+        return Ok(value);
+    }
+    let value = eval_node(expr, context)?;
+
+    // Variables, lets, and ifs pass along values built elsewhere
+    if context.budget.limits_size()
+        && !matches!(expr, Expr::Variable(_) | Expr::Let { .. } | Expr::If { .. })
+    {
+        context.budget.charge(&value)?;
+    }
+    if let Some((memo, class)) = cached {
+        memo.insert(class, value.clone());
+    }
+    Ok(value)
+}
+
+/// Evaluate one node; `eval_limited` wraps this with the limit checks
+fn eval_node(expr: &Expr, context: &Context) -> Result<Value, EvalError> {
     match expr {
         // Literals
-        Expr::Number(n) => Ok(Value::Number(*n)),
-        Expr::String(s) => Ok(Value::String(s.clone())),
+        Expr::Number(n) => Ok(context.numeric(Value::Number(*n))),
+        Expr::Integer(n) => Ok(context.numeric(Value::Int(*n))),
+        Expr::String(s) => Ok(Value::String(s.clone().into())),
         Expr::Boolean(b) => Ok(Value::Boolean(*b)),
         Expr::Nil => Ok(Value::Nil),
         Expr::Symbol(s) => Ok(Value::Symbol(s.clone())),
@@ -389,7 +1456,7 @@ pub fn eval_expr(expr: &Expr, context: &Context) -> Result<Value, EvalError> {
             for e in exprs {
                 values.push(eval_expr(e, context)?);
             }
-            Ok(Value::Array(values))
+            Ok(Value::Array(values.into()))
         }
 
         // Dictionary literal
@@ -399,7 +1466,7 @@ pub fn eval_expr(expr: &Expr, context: &Context) -> Result<Value, EvalError> {
                 let value = eval_expr(value_expr, context)?;
                 map.insert(key.clone(), value);
             }
-            Ok(Value::Dictionary(map))
+            Ok(Value::Dictionary(map.into()))
         }
 
         // Variable access (with dot navigation)
@@ -412,31 +1479,50 @@ pub fn eval_expr(expr: &Expr, context: &Context) -> Result<Value, EvalError> {
                 return Ok(Value::Nil);
             }
 
+            // Variables the data does not hold come from the provider, if any
+            if !context.contains(&path[0]) {
+                if let Some(provider) = &context.provider {
+                    return Ok(context.numeric(provider.resolve(path)));
+                }
+            }
+
             // If it's a simple variable (no dots), check if it exists
-            if path.len() == 1 && !context.contains(&path[0]) {
+            if (path.len() == 1 || context.strict_navigation) && !context.contains(&path[0]) {
                 return Err(EvalError::VariableNotFound(path[0].clone()));
             }
 
-            // Look up the root variable
-            let mut current = context.lookup(&path[0]);
-
-            // Navigate the path with safe navigation semantics
-            for key in &path[1..] {
-                current = match current {
-                    Value::Dictionary(ref map) => map.get(key).cloned().unwrap_or(Value::Nil),
-                    _ => Value::Nil, // Safe navigation: return nil if not a dictionary
+            // Navigate the path by reference with safe navigation semantics,
+            // cloning only the value it lands on
+            let mut current = context.get(&path[0]);
+            for (depth, key) in path[1..].iter().enumerate() {
+                let parent = current;
+                current = match parent {
+                    Some(Value::Dictionary(map)) => map.get(key),
+                    _ => None, // Safe navigation: nil if not a dictionary
                 };
+                if current.is_none() && context.strict_navigation {
+                    return Err(navigation_error(&path[..=depth], key, parent));
+                }
             }
 
-            Ok(current)
+            Ok(context.numeric(current.cloned().unwrap_or(Value::Nil)))
         }
 
+        // try(expr, fallback): the one call whose arguments are evaluated
+        // lazily, so an error in `expr` yields `fallback` instead of aborting.
+        // Running out of budget is not recoverable.
+        Expr::FunctionCall { name, args } if name == "try" => match eval_expr(&args[0], context) {
+            Err(err @ EvalError::LimitExceeded(_)) => Err(err),
+            Err(_) => eval_expr(&args[1], context),
+            ok => ok,
+        },
+
         // Function call
         Expr::FunctionCall { name, args } => {
             let arg_values: Result<Vec<_>, _> =
                 args.iter().map(|a| eval_expr(a, context)).collect();
             let arg_values = arg_values?;
-            call_function(name, &arg_values)
+            call_function(name, &arg_values, context)
         }
 
         // Let binding
@@ -453,6 +1539,18 @@ pub fn eval_expr(expr: &Expr, context: &Context) -> Result<Value, EvalError> {
             else_branch,
         } => {
             let cond_value = eval_expr(condition, context)?;
+            if !matches!(cond_value, Value::Boolean(_)) {
+                if context.truthiness == Truthiness::Strict {
+                    return Err(EvalError::TypeError {
+                        expected: "Boolean condition".to_string(),
+                        got: cond_value.type_name().to_string(),
+                    });
+                }
+                context.warn(|| Warning::NonBooleanCondition {
+                    condition: condition.as_ref().clone(),
+                    got: cond_value.type_name().to_string(),
+                });
+            }
             let is_truthy = match cond_value {
                 Value::Boolean(b) => b,
                 Value::Nil => false,
@@ -470,12 +1568,26 @@ pub fn eval_expr(expr: &Expr, context: &Context) -> Result<Value, EvalError> {
         Expr::Binary { op, left, right } => {
             let left_val = eval_expr(left, context)?;
             let right_val = eval_expr(right, context)?;
+            if (left_val == Value::Nil || right_val == Value::Nil)
+                && !matches!(op, BinaryOp::Equal | BinaryOp::NotEqual)
+            {
+                context.warn(|| Warning::NilOperand {
+                    op: op.to_string(),
+                    expr: expr.clone(),
+                });
+            }
             eval_binary_op(*op, &left_val, &right_val)
         }
 
         // Unary operations
         Expr::Unary { op, operand } => {
             let val = eval_expr(operand, context)?;
+            if val == Value::Nil {
+                context.warn(|| Warning::NilOperand {
+                    op: op.to_string(),
+                    expr: expr.clone(),
+                });
+            }
             eval_unary_op(*op, &val)
         }
 
@@ -492,11 +1604,11 @@ pub fn eval_expr(expr: &Expr, context: &Context) -> Result<Value, EvalError> {
                     for arg in args {
                         new_args.push(eval_expr(arg, context)?);
                     }
-                    call_function(name, &new_args)
+                    call_function(name, &new_args, context)
                 }
                 Expr::Variable(path) if path.len() == 1 => {
                     // Simple function name without args
-                    call_function(&path[0], &[left_val])
+                    call_function(&path[0], &[left_val], context)
                 }
                 _ => {
                     // Invalid pipe target
@@ -510,6 +1622,20 @@ pub fn eval_expr(expr: &Expr, context: &Context) -> Result<Value, EvalError> {
     }
 }
 
+/// Why strict navigation could not read `key` from `parent`, found at `parent_path`
+fn navigation_error(parent_path: &[String], key: &str, parent: Option<&Value>) -> EvalError {
+    match parent {
+        Some(Value::Dictionary(_)) => EvalError::UnknownKey {
+            path: parent_path.join("."),
+            key: key.to_string(),
+        },
+        other => EvalError::NotADictionary {
+            path: parent_path.join("."),
+            got: other.map_or("Nil", Value::type_name).to_string(),
+        },
+    }
+}
+
 /// Evaluate a binary operation
 ///
 /// # Defensive Programming
@@ -544,6 +1670,10 @@ fn eval_unary_op(op: UnaryOp, operand: &Value) -> Result<Value, EvalError> {
         UnaryOp::Not => Ok(logical_not(operand)),
         UnaryOp::Negate => match operand {
             Value::Number(n) => Ok(Value::Number(-n)),
+            Value::Decimal(d) => Ok(Value::Decimal(-d)),
+            Value::Int(n) => Ok(n
+                .checked_neg()
+                .map_or(Value::Number(-(*n as f64)), Value::Int)),
             _ => Err(EvalError::TypeError {
                 expected: "Number".to_string(),
                 got: operand.type_name().to_string(),
@@ -552,8 +1682,69 @@ fn eval_unary_op(op: UnaryOp, operand: &Value) -> Result<Value, EvalError> {
     }
 }
 
-/// Call a standard library function
-fn call_function(name: &str, args: &[Value]) -> Result<Value, EvalError> {
+/// Call a host function registered in the context, or a standard library function
+///
+/// The numeric standard library works in floats, so integers in the arguments,
+/// including those nested in arrays and dictionaries, are promoted to numbers
+/// first, and so are decimals unless the function's signature says it
+/// computes with them exactly. Host functions and functions that leave
+/// numbers unchanged get their arguments as they are. In decimal mode the
+/// numbers in the result are converted back to decimals, and functions that
+/// only compute in floats are refused.
+fn call_function(name: &str, args: &[Value], context: &Context) -> Result<Value, EvalError> {
+    if let Some(host) = context.functions.get(name) {
+        return apply(&Function::Host(host.clone()), args, context);
+    }
+
+    let numbers = signatures::lookup(name).map_or(Numbers::Floats, |f| f.numbers);
+    if numbers == Numbers::FloatsOnly && context.numeric_mode == NumericMode::Decimal {
+        return Err(EvalError::FunctionError(FunctionError::InvalidOperation {
+            message: format!(
+                "{} has no exact form, so it is not available in decimal mode",
+                name
+            ),
+        }));
+    }
+    let exact = numbers == Numbers::Exact;
+    let promoted: Vec<Value>;
+    let args = if numbers != Numbers::Unchanged
+        && args
+            .iter()
+            .any(|arg| arg.contains_int() || (!exact && arg.contains_decimal()))
+    {
+        promoted = args
+            .iter()
+            .map(|arg| {
+                if exact {
+                    arg.promote_ints()
+                } else {
+                    arg.to_floats()
+                }
+            })
+            .collect();
+        &promoted[..]
+    } else {
+        args
+    };
+
+    // Refuse strings over the size limits before they are built
+    if context.budget.limits_size() {
+        use amoskeag_stdlib_functions::{pad_length, repeat_length};
+        let len = match name {
+            "repeat" => repeat_length(&args[0], &args[1]),
+            "pad_left" | "pad_right" => pad_length(&args[0], &args[1], &args[2]),
+            _ => None,
+        };
+        if let Some(len) = len {
+            context.budget.check_string(len)?;
+        }
+    }
+
+    dispatch_function(name, args, context).map(|result| context.numeric(result))
+}
+
+/// Run the standard library function `name` on already-promoted arguments
+fn dispatch_function(name: &str, args: &[Value], context: &Context) -> Result<Value, EvalError> {
     use amoskeag_stdlib_functions::*;
 
     match name {
@@ -561,11 +1752,44 @@ fn call_function(name: &str, args: &[Value]) -> Result<Value, EvalError> {
         "upcase" => upcase(&args[0]).map_err(EvalError::from),
         "downcase" => downcase(&args[0]).map_err(EvalError::from),
         "capitalize" => capitalize(&args[0]).map_err(EvalError::from),
+        "snake_case" => snake_case(&args[0]).map_err(EvalError::from),
+        "camel_case" => camel_case(&args[0]).map_err(EvalError::from),
+        "titlecase" => titlecase(&args[0]).map_err(EvalError::from),
         "strip" => strip(&args[0]).map_err(EvalError::from),
+        "trim_start" => lstrip(&args[0]).map_err(EvalError::from),
+        "trim_end" => rstrip(&args[0]).map_err(EvalError::from),
+        "squish" => squish(&args[0]).map_err(EvalError::from),
+        "slugify" => slugify(&args[0]).map_err(EvalError::from),
         "split" => split(&args[0], &args[1]).map_err(EvalError::from),
+        "chars" => chars(&args[0]).map_err(EvalError::from),
+        "lines" => lines(&args[0]).map_err(EvalError::from),
         "join" => join(&args[0], &args[1]).map_err(EvalError::from),
         "truncate" => truncate(&args[0], &args[1]).map_err(EvalError::from),
+        "substring" => substring(&args[0], &args[1], &args[2]).map_err(EvalError::from),
+        "repeat" => repeat(&args[0], &args[1]).map_err(EvalError::from),
+        "pad_left" => pad_left(&args[0], &args[1], &args[2]).map_err(EvalError::from),
+        "pad_right" => pad_right(&args[0], &args[1], &args[2]).map_err(EvalError::from),
+        "format" => format(&args[0], &args[1..]).map_err(EvalError::from),
         "replace" => replace(&args[0], &args[1], &args[2]).map_err(EvalError::from),
+        "starts_with" => starts_with(&args[0], &args[1]).map_err(EvalError::from),
+        "ends_with" => ends_with(&args[0], &args[1]).map_err(EvalError::from),
+        "index_of" => index_of(&args[0], &args[1]).map_err(EvalError::from),
+        "includes" => includes(&args[0], &args[1]).map_err(EvalError::from),
+        "levenshtein" => levenshtein(&args[0], &args[1]).map_err(EvalError::from),
+        "similarity" => similarity(&args[0], &args[1]).map_err(EvalError::from),
+        "regex_match" => regex_match(&args[0], &args[1]).map_err(EvalError::from),
+        "regex_replace" => regex_replace(&args[0], &args[1], &args[2]).map_err(EvalError::from),
+        "regex_extract" => regex_extract(&args[0], &args[1]).map_err(EvalError::from),
+        "matches_pattern" => matches_pattern(&args[0], &args[1]).map_err(EvalError::from),
+        "is_email" => Ok(is_email(&args[0])),
+        "is_numeric_string" => Ok(is_numeric_string(&args[0])),
+        "luhn_valid" => luhn_valid(&args[0]).map_err(EvalError::from),
+        "sha256" => sha256(&args[0]).map_err(EvalError::from),
+        "md5" => md5(&args[0]).map_err(EvalError::from),
+        "base64_encode" => base64_encode(&args[0]).map_err(EvalError::from),
+        "base64_decode" => base64_decode(&args[0]).map_err(EvalError::from),
+        "url_encode" => url_encode(&args[0]).map_err(EvalError::from),
+        "url_decode" => url_decode(&args[0]).map_err(EvalError::from),
 
         // Numeric functions
         "abs" => abs(&args[0]).map_err(EvalError::from),
@@ -578,18 +1802,48 @@ fn call_function(name: &str, args: &[Value]) -> Result<Value, EvalError> {
                 round(&args[0], &Value::Number(0.0)).map_err(EvalError::from)
             }
         }
+        "trunc" => trunc(&args[0]).map_err(EvalError::from),
+        "sign" => sign(&args[0]).map_err(EvalError::from),
+        "clamp" => clamp(&args[0], &args[1], &args[2]).map_err(EvalError::from),
+        "gcd" => gcd(&args[0], &args[1]).map_err(EvalError::from),
+        "random" => Ok(context.with_rng(random)),
+        "random_between" => context
+            .with_rng(|state| random_between(&args[0], &args[1], state))
+            .map_err(EvalError::from),
+        "uuid" => Ok(context.with_uuid_rng(uuid)),
+        "bucket" => bucket(&args[0], &args[1], &args[2]).map_err(EvalError::from),
+        "interpolate" => interpolate(&args[0], &args[1], &args[2]).map_err(EvalError::from),
+        "lcm" => lcm(&args[0], &args[1]).map_err(EvalError::from),
         "plus" => plus(&args[0], &args[1]).map_err(EvalError::from),
         "minus" => minus(&args[0], &args[1]).map_err(EvalError::from),
         "times" => times(&args[0], &args[1]).map_err(EvalError::from),
         "divided_by" => divided_by(&args[0], &args[1]).map_err(EvalError::from),
+        "safe_divide" => {
+            let default = args.get(2).cloned().unwrap_or(Value::Nil);
+            safe_divide(&args[0], &args[1], &default).map_err(EvalError::from)
+        }
         "modulo" => modulo_fn(&args[0], &args[1]).map_err(EvalError::from),
         "max" => max(&args[0], &args[1]).map_err(EvalError::from),
         "min" => min(&args[0], &args[1]).map_err(EvalError::from),
         "array_min" => array_min(&args[0]).map_err(EvalError::from),
         "array_max" => array_max(&args[0]).map_err(EvalError::from),
-        "power" => power(&args[0], &args[1]).map_err(EvalError::from),
+        "power" | "pow" => power(&args[0], &args[1]).map_err(EvalError::from),
+        "exp" => exp(&args[0]).map_err(EvalError::from),
         "sqrt" => sqrt(&args[0]).map_err(EvalError::from),
         "log" => log(&args[0]).map_err(EvalError::from),
+        "number_format" => {
+            let decimals = args.get(1).cloned().unwrap_or(Value::Number(0.0));
+            let thousands_sep = args
+                .get(2)
+                .cloned()
+                .unwrap_or_else(|| Value::String(",".into()));
+            let decimal_sep = args
+                .get(3)
+                .cloned()
+                .unwrap_or_else(|| Value::String(".".into()));
+            number_format(&args[0], &decimals, &thousands_sep, &decimal_sep)
+                .map_err(EvalError::from)
+        }
         "log10" => log10(&args[0]).map_err(EvalError::from),
         "ln" => ln(&args[0]).map_err(EvalError::from),
 
@@ -600,6 +1854,21 @@ fn call_function(name: &str, args: &[Value]) -> Result<Value, EvalError> {
         "contains" => contains(&args[0], &args[1]).map_err(EvalError::from),
         "sum" => sum(&args[0]).map_err(EvalError::from),
         "avg" => avg(&args[0]).map_err(EvalError::from),
+        "weighted_average" => {
+            let weights = args.get(1).cloned().unwrap_or(Value::Nil);
+            weighted_average(&args[0], &weights).map_err(EvalError::from)
+        }
+        "median" => median(&args[0]).map_err(EvalError::from),
+        "mode" => mode(&args[0]).map_err(EvalError::from),
+        "variance" => variance(&args[0]).map_err(EvalError::from),
+        "variance_sample" => variance_sample(&args[0]).map_err(EvalError::from),
+        "stddev" => stddev(&args[0]).map_err(EvalError::from),
+        "stddev_sample" => stddev_sample(&args[0]).map_err(EvalError::from),
+        "percentile" => percentile(&args[0], &args[1]).map_err(EvalError::from),
+        "quantile" => quantile(&args[0], &args[1]).map_err(EvalError::from),
+        "covariance" => covariance(&args[0], &args[1]).map_err(EvalError::from),
+        "covariance_sample" => covariance_sample(&args[0], &args[1]).map_err(EvalError::from),
+        "correlation" => correlation(&args[0], &args[1]).map_err(EvalError::from),
         "sort" => sort(&args[0]).map_err(EvalError::from),
         "keys" => keys(&args[0]).map_err(EvalError::from),
         "values" => values(&args[0]).map_err(EvalError::from),
@@ -608,6 +1877,23 @@ fn call_function(name: &str, args: &[Value]) -> Result<Value, EvalError> {
         "uniq" => uniq(&args[0]).map_err(EvalError::from),
         "group_by" => group_by(&args[0], &args[1]).map_err(EvalError::from),
         "map" => map(&args[0], &args[1]).map_err(EvalError::from),
+        "pick" => pick(&args[0], &args[1]).map_err(EvalError::from),
+        "omit" => omit(&args[0], &args[1]).map_err(EvalError::from),
+        "has_key" => has_key(&args[0], &args[1]).map_err(EvalError::from),
+        "entries" => entries(&args[0]).map_err(EvalError::from),
+        "from_entries" => from_entries(&args[0]).map_err(EvalError::from),
+        "invert" => invert(&args[0]).map_err(EvalError::from),
+        "dig" => dig(&args[0], &args[1]).map_err(EvalError::from),
+        "union" => union(&args[0], &args[1]).map_err(EvalError::from),
+        "intersection" => intersection(&args[0], &args[1]).map_err(EvalError::from),
+        "difference" => difference(&args[0], &args[1]).map_err(EvalError::from),
+        "get" => {
+            if args.len() == 3 {
+                get(&args[0], &args[1], &args[2]).map_err(EvalError::from)
+            } else {
+                get(&args[0], &args[1], &Value::Nil).map_err(EvalError::from)
+            }
+        }
 
         // Logic functions
         "choose" => choose(&args[0], &args[1]).map_err(EvalError::from),
@@ -618,13 +1904,44 @@ fn call_function(name: &str, args: &[Value]) -> Result<Value, EvalError> {
         "is_nil" => Ok(is_nil(&args[0])),
         "is_array" => Ok(is_array(&args[0])),
         "is_dictionary" => Ok(is_dictionary(&args[0])),
-        "coalesce" => Ok(coalesce(&args[0], &args[1])),
+        "coalesce" => Ok(coalesce(args)),
         "default" => Ok(default(&args[0], &args[1])),
+        "between" => {
+            let bounds = args
+                .get(3)
+                .cloned()
+                .unwrap_or_else(|| Value::String("[]".into()));
+            between(&args[0], &args[1], &args[2], &bounds).map_err(EvalError::from)
+        }
+
+        // Conversion functions
+        "to_number" => Ok(to_number(&args[0])),
+        "to_number_strict" => to_number_strict(&args[0]).map_err(EvalError::from),
+        "to_string" => Ok(to_string(&args[0])),
+        "to_string_strict" => to_string_strict(&args[0]).map_err(EvalError::from),
+        "to_boolean" => Ok(to_boolean(&args[0])),
+        "to_boolean_strict" => to_boolean_strict(&args[0]).map_err(EvalError::from),
+        "parse_json" => parse_json(&args[0]).map_err(EvalError::from),
+        "to_json" => to_json(&args[0]).map_err(EvalError::from),
 
         // Financial functions - Time Value of Money
+        "currency" => {
+            let code = args
+                .get(1)
+                .cloned()
+                .unwrap_or_else(|| Value::String("USD".into()));
+            let locale = args
+                .get(2)
+                .cloned()
+                .unwrap_or_else(|| Value::String(context.locale().to_string().into()));
+            currency(&args[0], &code, &locale).map_err(EvalError::from)
+        }
         "pmt" => pmt(&args[0], &args[1], &args[2], &args[3]).map_err(EvalError::from),
         "pv" => pv(&args[0], &args[1], &args[2]).map_err(EvalError::from),
         "fv" => fv(&args[0], &args[1], &args[2], &args[3]).map_err(EvalError::from),
+        "fvschedule" => fvschedule(&args[0], &args[1]).map_err(EvalError::from),
+        "cagr" => cagr(&args[0], &args[1], &args[2]).map_err(EvalError::from),
+        "compound" => compound(&args[0], &args[1], &args[2]).map_err(EvalError::from),
         "nper" => nper(&args[0], &args[1], &args[2]).map_err(EvalError::from),
         "rate" => rate(&args[0], &args[1], &args[2]).map_err(EvalError::from),
 
@@ -632,11 +1949,30 @@ fn call_function(name: &str, args: &[Value]) -> Result<Value, EvalError> {
         "npv" => npv(&args[0], &args[1]).map_err(EvalError::from),
         "irr" => irr(&args[0]).map_err(EvalError::from),
         "mirr" => mirr(&args[0], &args[1], &args[2]).map_err(EvalError::from),
+        "xnpv" => xnpv(&args[0], &args[1], &args[2]).map_err(EvalError::from),
+        "xirr" => {
+            let guess = args.get(2).cloned().unwrap_or(Value::Number(0.1));
+            xirr(&args[0], &args[1], &guess).map_err(EvalError::from)
+        }
+
+        // Financial functions - Loan Schedules
+        "amortization_schedule" => {
+            amortization_schedule(&args[0], &args[1], &args[2]).map_err(EvalError::from)
+        }
 
         // Financial functions - Depreciation
         "sln" => sln(&args[0], &args[1], &args[2]).map_err(EvalError::from),
         "ddb" => ddb(&args[0], &args[1], &args[2], &args[3]).map_err(EvalError::from),
         "db" => db(&args[0], &args[1], &args[2], &args[3], &args[4]).map_err(EvalError::from),
+        "syd" => syd(&args[0], &args[1], &args[2], &args[3]).map_err(EvalError::from),
+        "vdb" => {
+            let factor = args.get(5).cloned().unwrap_or(Value::Number(2.0));
+            let no_switch = args.get(6).cloned().unwrap_or(Value::Boolean(false));
+            vdb(
+                &args[0], &args[1], &args[2], &args[3], &args[4], &factor, &no_switch,
+            )
+            .map_err(EvalError::from)
+        }
 
         // Financial functions - Payment Components
         "ipmt" => ipmt(&args[0], &args[1], &args[2], &args[3], &args[4]).map_err(EvalError::from),
@@ -649,11 +1985,51 @@ fn call_function(name: &str, args: &[Value]) -> Result<Value, EvalError> {
         // Financial functions - Interest Rate Conversion
         "effect" => effect(&args[0], &args[1]).map_err(EvalError::from),
         "nominal" => nominal(&args[0], &args[1]).map_err(EvalError::from),
+        "apr" => apr(&args[0], &args[1], &args[2], &args[3], &args[4]).map_err(EvalError::from),
+        "apr_effective" => {
+            apr_effective(&args[0], &args[1], &args[2], &args[3], &args[4]).map_err(EvalError::from)
+        }
+        "periodic_to_annual" => periodic_to_annual(&args[0], &args[1]).map_err(EvalError::from),
+        "annual_to_periodic" => annual_to_periodic(&args[0], &args[1]).map_err(EvalError::from),
+
+        // Financial functions - Rate Tables
+        "tier_lookup" => tier_lookup(&args[0], &args[1]).map_err(EvalError::from),
+        "tier_marginal" => tier_marginal(&args[0], &args[1]).map_err(EvalError::from),
+        "prorate" => {
+            let period_days = args.get(3).cloned().unwrap_or(Value::Number(365.0));
+            prorate(&args[0], &args[1], &args[2], &period_days).map_err(EvalError::from)
+        }
+
+        // Financial functions - Bonds
+        "price" => {
+            let basis = args.get(6).cloned().unwrap_or(Value::Number(0.0));
+            price(
+                &args[0], &args[1], &args[2], &args[3], &args[4], &args[5], &basis,
+            )
+            .map_err(EvalError::from)
+        }
+        "yield" => {
+            let basis = args.get(6).cloned().unwrap_or(Value::Number(0.0));
+            yield_fn(
+                &args[0], &args[1], &args[2], &args[3], &args[4], &args[5], &basis,
+            )
+            .map_err(EvalError::from)
+        }
+        "duration" => {
+            let basis = args.get(5).cloned().unwrap_or(Value::Number(0.0));
+            duration(&args[0], &args[1], &args[2], &args[3], &args[4], &basis)
+                .map_err(EvalError::from)
+        }
+        "mduration" => {
+            let basis = args.get(5).cloned().unwrap_or(Value::Number(0.0));
+            mduration(&args[0], &args[1], &args[2], &args[3], &args[4], &basis)
+                .map_err(EvalError::from)
+        }
 
         // Date functions
         "date_now" => {
             if args.is_empty() {
-                date_now().map_err(EvalError::from)
+                context.clock.today()
             } else {
                 Err(EvalError::TypeError {
                     expected: "0 arguments".to_string(),
@@ -664,6 +2040,57 @@ fn call_function(name: &str, args: &[Value]) -> Result<Value, EvalError> {
         "date_format" => date_format(&args[0], &args[1]).map_err(EvalError::from),
         "date_trunc" => date_trunc(&args[0]).map_err(EvalError::from),
         "date_parse" => date_parse(&args[0]).map_err(EvalError::from),
+        "date_add" => {
+            if args.len() == 3 {
+                date_add(&args[0], &args[1], &args[2]).map_err(EvalError::from)
+            } else {
+                add_duration(&args[0], &args[1]).map_err(EvalError::from)
+            }
+        }
+        "date_sub" => {
+            if args.len() == 3 {
+                date_sub(&args[0], &args[1], &args[2]).map_err(EvalError::from)
+            } else {
+                subtract_duration(&args[0], &args[1]).map_err(EvalError::from)
+            }
+        }
+        "date_diff" => {
+            if args.len() == 3 {
+                date_diff(&args[0], &args[1], &args[2]).map_err(EvalError::from)
+            } else {
+                duration_between(&args[0], &args[1]).map_err(EvalError::from)
+            }
+        }
+        "date_before" => date_before(&args[0], &args[1]).map_err(EvalError::from),
+        "date_after" => date_after(&args[0], &args[1]).map_err(EvalError::from),
+        "date_compare" => date_compare(&args[0], &args[1]).map_err(EvalError::from),
+        "duration_days" => duration_days(&args[0]).map_err(EvalError::from),
+        "duration_weeks" => duration_weeks(&args[0]).map_err(EvalError::from),
+        "duration_months" => duration_months(&args[0]).map_err(EvalError::from),
+        "duration_years" => duration_years(&args[0]).map_err(EvalError::from),
+        "is_weekend" => is_weekend(&args[0]).map_err(EvalError::from),
+        "business_days_between" => {
+            let holidays = args.get(2).cloned().unwrap_or(Value::Nil);
+            business_days_between(&args[0], &args[1], &holidays).map_err(EvalError::from)
+        }
+        "add_business_days" => {
+            let holidays = args.get(2).cloned().unwrap_or(Value::Nil);
+            add_business_days(&args[0], &args[1], &holidays).map_err(EvalError::from)
+        }
+        "beginning_of_month" => beginning_of_month(&args[0]).map_err(EvalError::from),
+        "end_of_month" => end_of_month(&args[0]).map_err(EvalError::from),
+        "beginning_of_quarter" => beginning_of_quarter(&args[0]).map_err(EvalError::from),
+        "end_of_quarter" => end_of_quarter(&args[0]).map_err(EvalError::from),
+        "age" => {
+            let as_of = match args.get(1) {
+                Some(as_of) => as_of.clone(),
+                None => context.clock.today()?,
+            };
+            age(&args[0], &as_of).map_err(EvalError::from)
+        }
+        "iso_week" => iso_week(&args[0]).map_err(EvalError::from),
+        "iso_year" => iso_year(&args[0]).map_err(EvalError::from),
+        "quarter" => quarter(&args[0]).map_err(EvalError::from),
 
         _ => Err(EvalError::TypeError {
             expected: "known function".to_string(),
@@ -694,6 +2121,90 @@ mod tests {
         assert_eq!(result, Value::Number(14.0));
     }
 
+    #[test]
+    fn test_integer_and_float_values() {
+        let data = HashMap::new();
+        let eval = |source: &str| evaluate(&compile(source, &[]).unwrap(), &data).unwrap();
+
+        // Integer literals stay integers through +, -, * and %
+        assert!(matches!(eval("2 + 3 * 4"), Value::Int(14)));
+        assert!(matches!(eval("-(7 % 4)"), Value::Int(-3)));
+        // A decimal point or a float operand makes a float
+        assert!(matches!(eval("2.0 + 3"), Value::Number(n) if n == 5.0));
+        // Division always yields a float
+        assert!(matches!(eval("10 / 4"), Value::Number(n) if n == 2.5));
+        // Overflow promotes rather than wrapping
+        assert!(matches!(eval("9223372036854775807 + 1"), Value::Number(_)));
+        assert_eq!(eval("1 == 1.0"), Value::Boolean(true));
+        // Numeric functions receive integers as floats
+        assert!(matches!(eval("[1, 2, 3] | sum"), Value::Number(n) if n == 6.0));
+        assert!(matches!(eval("round(7 / 2)"), Value::Number(n) if n == 4.0));
+        // Functions that pass values along keep them as integers
+        assert!(matches!(
+            eval("first([9007199254740993])"),
+            Value::Int(9007199254740993)
+        ));
+        assert!(matches!(eval("coalesce(nil, 3)"), Value::Int(3)));
+        assert!(matches!(eval("if_then_else(true, 1, 2)"), Value::Int(1)));
+        assert!(matches!(eval("at([10, 20, 30], 1)"), Value::Int(20)));
+        assert_eq!(
+            eval("sort([3, 1.5, 2])"),
+            Value::from(vec![Value::Number(1.5), Value::Int(2), Value::Int(3)])
+        );
+        assert!(matches!(
+            eval("sort([3, 1])"),
+            Value::Array(items) if matches!(&items[..], [Value::Int(1), Value::Int(3)])
+        ));
+    }
+
+    #[test]
+    fn test_decimal_mode() {
+        let decimal = |s: &str| Value::Decimal(s.parse().unwrap());
+        let mut data = HashMap::new();
+        data.insert("price".to_string(), Value::Number(19.99));
+
+        let program = compile("0.1 + 0.2", &[]).unwrap();
+        assert_ne!(evaluate(&program, &data).unwrap(), Value::Number(0.3));
+        // Selected at compile time
+        let program = program.with_numeric_mode(NumericMode::Decimal);
+        let result = evaluate(&program, &data).unwrap();
+        assert!(matches!(&result, Value::Decimal(d) if d.to_string() == "0.3"));
+
+        // Or at evaluate time, for numbers from the data as well as literals
+        let context = Context::new(data.clone()).with_numeric_mode(NumericMode::Decimal);
+        let program = compile("price * 3", &[]).unwrap();
+        assert_eq!(
+            eval_expr(program.ast(), &context).unwrap(),
+            decimal("59.97")
+        );
+
+        let program = compile("round(pmt(0.045 / 12, 360, 250000, 0), 2)", &[]).unwrap();
+        assert_eq!(
+            eval_expr(program.ast(), &context).unwrap(),
+            decimal("-1266.71")
+        );
+
+        let program = compile("fvschedule(1000, [0.1, 0.2])", &[]).unwrap();
+        assert_eq!(eval_expr(program.ast(), &context).unwrap(), decimal("1320"));
+        let program = compile("tier_marginal(25000, [[0, 0.1], [10000, 0.2]])", &[]).unwrap();
+        assert_eq!(eval_expr(program.ast(), &context).unwrap(), decimal("4000"));
+
+        // Functions without exact support still return decimals
+        let program = compile("[price, 1] | size", &[]).unwrap();
+        assert!(matches!(
+            eval_expr(program.ast(), &context).unwrap(),
+            Value::Decimal(_)
+        ));
+
+        // except financial functions with no exact form, which are refused
+        let program = compile("irr([-100, 110])", &[]).unwrap();
+        assert_eq!(
+            eval_expr(program.ast(), &context).unwrap_err().to_string(),
+            "Function error: Invalid operation: irr has no exact form, so it is not available in decimal mode"
+        );
+        assert!(evaluate(&program, &data).is_ok());
+    }
+
     #[test]
     fn test_compile_and_evaluate_variable() {
         let source = "driver.age";
@@ -703,7 +2214,7 @@ mod tests {
         driver.insert("age".to_string(), Value::Number(25.0));
 
         let mut data = HashMap::new();
-        data.insert("driver".to_string(), Value::Dictionary(driver));
+        data.insert("driver".to_string(), Value::Dictionary(driver.into()));
 
         let result = evaluate(&program, &data).unwrap();
         assert_eq!(result, Value::Number(25.0));
@@ -724,7 +2235,7 @@ mod tests {
         driver.insert("age".to_string(), Value::Number(25.0));
 
         let mut data = HashMap::new();
-        data.insert("driver".to_string(), Value::Dictionary(driver));
+        data.insert("driver".to_string(), Value::Dictionary(driver.into()));
 
         let result = evaluate(&program, &data).unwrap();
         assert_eq!(result, Value::Symbol("continue".to_string()));
@@ -780,7 +2291,7 @@ mod tests {
         let program = compile(source, &[]).unwrap();
         let data = HashMap::new();
         let result = evaluate(&program, &data).unwrap();
-        assert_eq!(result, Value::String("HELLO".to_string()));
+        assert_eq!(result, Value::String("HELLO".into()));
     }
 
     #[test]
@@ -789,7 +2300,7 @@ mod tests {
         let program = compile(source, &[]).unwrap();
         let data = HashMap::new();
         let result = evaluate(&program, &data).unwrap();
-        assert_eq!(result, Value::String("HELLO".to_string()));
+        assert_eq!(result, Value::String("HELLO".into()));
     }
 
     #[test]
@@ -798,7 +2309,7 @@ mod tests {
         let program = compile(source, &[]).unwrap();
         let data = HashMap::new();
         let result = evaluate(&program, &data).unwrap();
-        assert_eq!(result, Value::String("hello".to_string()));
+        assert_eq!(result, Value::String("hello".into()));
     }
 
     #[test]
@@ -888,7 +2399,7 @@ mod tests {
         let program = compile(source, &[]).unwrap();
         let data = HashMap::new();
         let result = evaluate(&program, &data).unwrap();
-        assert_eq!(result, Value::Array(vec![]));
+        assert_eq!(result, Value::Array(vec![].into()));
     }
 
     #[test]
@@ -897,7 +2408,7 @@ mod tests {
         let program = compile(source, &[]).unwrap();
         let data = HashMap::new();
         let result = evaluate(&program, &data).unwrap();
-        assert_eq!(result, Value::Dictionary(HashMap::new()));
+        assert_eq!(result, Value::Dictionary(HashMap::new().into()));
     }
 
     #[test]
@@ -908,10 +2419,13 @@ mod tests {
         let result = evaluate(&program, &data).unwrap();
         assert_eq!(
             result,
-            Value::Array(vec![
-                Value::Array(vec![Value::Number(1.0), Value::Number(2.0)]),
-                Value::Array(vec![Value::Number(3.0), Value::Number(4.0)]),
-            ])
+            Value::Array(
+                vec![
+                    Value::Array(vec![Value::Number(1.0), Value::Number(2.0)].into()),
+                    Value::Array(vec![Value::Number(3.0), Value::Number(4.0)].into()),
+                ]
+                .into()
+            )
         );
     }
 
@@ -959,7 +2473,7 @@ mod tests {
         let program = compile(source, &[]).unwrap();
         let data = HashMap::new();
         let result = evaluate(&program, &data).unwrap();
-        assert_eq!(result, Value::String("hello world".to_string()));
+        assert_eq!(result, Value::String("hello world".into()));
     }
 
     #[test]
@@ -1001,7 +2515,7 @@ mod tests {
         let program = compile(source, &[]).unwrap();
         let data = HashMap::new();
         let result = evaluate(&program, &data).unwrap();
-        assert_eq!(result, Value::String("falsy".to_string()));
+        assert_eq!(result, Value::String("falsy".into()));
     }
 
     #[test]
@@ -1032,16 +2546,16 @@ mod tests {
         level5.insert("e".to_string(), Value::Number(42.0));
 
         let mut level4 = HashMap::new();
-        level4.insert("d".to_string(), Value::Dictionary(level5));
+        level4.insert("d".to_string(), Value::Dictionary(level5.into()));
 
         let mut level3 = HashMap::new();
-        level3.insert("c".to_string(), Value::Dictionary(level4));
+        level3.insert("c".to_string(), Value::Dictionary(level4.into()));
 
         let mut level2 = HashMap::new();
-        level2.insert("b".to_string(), Value::Dictionary(level3));
+        level2.insert("b".to_string(), Value::Dictionary(level3.into()));
 
         let mut data = HashMap::new();
-        data.insert("a".to_string(), Value::Dictionary(level2));
+        data.insert("a".to_string(), Value::Dictionary(level2.into()));
 
         let result = evaluate(&program, &data).unwrap();
         assert_eq!(result, Value::Number(42.0));
@@ -1053,7 +2567,7 @@ mod tests {
         let program = compile(source, &[]).unwrap();
         let data = HashMap::new();
         let result = evaluate(&program, &data).unwrap();
-        assert_eq!(result, Value::String("Hello".to_string()));
+        assert_eq!(result, Value::String("Hello".into()));
     }
 
     #[test]
@@ -1079,7 +2593,7 @@ mod tests {
         if let Value::Array(arr) = result {
             assert_eq!(arr.len(), 5);
             assert_eq!(arr[0], Value::Number(1.0));
-            assert_eq!(arr[1], Value::String("hello".to_string()));
+            assert_eq!(arr[1], Value::String("hello".into()));
             assert_eq!(arr[2], Value::Boolean(true));
             assert_eq!(arr[3], Value::Nil);
             assert_eq!(arr[4], Value::Symbol("symbol".to_string()));
@@ -1091,33 +2605,88 @@ mod tests {
     #[test]
     fn test_string_functions() {
         let test_cases = vec![
-            ("upcase('hello')", Value::String("HELLO".to_string())),
-            ("downcase('HELLO')", Value::String("hello".to_string())),
+            ("upcase('hello')", Value::String("HELLO".into())),
+            ("downcase('HELLO')", Value::String("hello".into())),
             (
                 "capitalize('hello world')",
-                Value::String("Hello world".to_string()),
+                Value::String("Hello world".into()),
             ),
-            ("strip('  hello  ')", Value::String("hello".to_string())),
             (
-                "split('a,b,c', ',')",
-                Value::Array(vec![
-                    Value::String("a".to_string()),
-                    Value::String("b".to_string()),
-                    Value::String("c".to_string()),
-                ]),
+                "snake_case('policyHolder')",
+                Value::String("policy_holder".into()),
+            ),
+            (
+                "camel_case('policy_holder')",
+                Value::String("policyHolder".into()),
+            ),
+            (
+                "titlecase('hello world')",
+                Value::String("Hello World".into()),
+            ),
+            ("strip('  hello  ')", Value::String("hello".into())),
+            ("trim_start('  hello  ')", Value::String("hello  ".into())),
+            ("trim_end('  hello  ')", Value::String("  hello".into())),
+            (
+                "squish('  hello    big  world ')",
+                Value::String("hello big world".into()),
             ),
             (
-                "join(['a', 'b', 'c'], ',')",
-                Value::String("a,b,c".to_string()),
+                "slugify('Crème Brûlée Co.')",
+                Value::String("creme-brulee-co".into()),
             ),
             (
-                "truncate('hello world', 5)",
-                Value::String("hello".to_string()),
+                "split('a,b,c', ',')",
+                Value::Array(
+                    vec![
+                        Value::String("a".into()),
+                        Value::String("b".into()),
+                        Value::String("c".into()),
+                    ]
+                    .into(),
+                ),
             ),
+            ("join(['a', 'b', 'c'], ',')", Value::String("a,b,c".into())),
+            ("truncate('hello world', 5)", Value::String("hello".into())),
             (
                 "replace('hello world', 'world', 'rust')",
-                Value::String("hello rust".to_string()),
+                Value::String("hello rust".into()),
+            ),
+            (
+                "substring('hello world', 6, 5)",
+                Value::String("world".into()),
+            ),
+            ("repeat('-', 5)", Value::String("-----".into())),
+            ("pad_left('7', 3, '0')", Value::String("007".into())),
+            ("pad_right('ab', 4, '-')", Value::String("ab--".into())),
+            (
+                "format('%s owes %.2f', 'Jane', 12.5)",
+                Value::String("Jane owes 12.50".into()),
             ),
+            ("format('%.1f%%', 12.5)", Value::String("12.5%".into())),
+            (
+                "chars('abc')",
+                Value::Array(
+                    vec![
+                        Value::String("a".into()),
+                        Value::String("b".into()),
+                        Value::String("c".into()),
+                    ]
+                    .into(),
+                ),
+            ),
+            ("chars('1HGCM') | size", Value::Number(5.0)),
+            (
+                "lines('a\\nb')",
+                Value::Array(vec![Value::String("a".into()), Value::String("b".into())].into()),
+            ),
+            ("starts_with('hello', 'he')", Value::Boolean(true)),
+            ("'hello' | ends_with('lo')", Value::Boolean(true)),
+            ("ends_with('hello', 'he')", Value::Boolean(false)),
+            ("index_of('hello', 'll')", Value::Number(2.0)),
+            ("index_of('hello', 'z')", Value::Nil),
+            ("'hello' | includes('ell')", Value::Boolean(true)),
+            ("levenshtein('kitten', 'sitting')", Value::Number(3.0)),
+            ("similarity('abcd', 'abcf')", Value::Number(0.75)),
         ];
 
         for (source, expected) in test_cases {
@@ -1137,6 +2706,16 @@ mod tests {
             ("floor(3.8)", Value::Number(3.0)),
             ("round(3.5)", Value::Number(4.0)),
             ("round(2.71828, 2)", Value::Number(2.72)),
+            ("trunc(-2.7)", Value::Number(-2.0)),
+            ("sign(-0.5)", Value::Number(-1.0)),
+            ("clamp(1.4, 0.8, 1.25)", Value::Number(1.25)),
+            ("0.5 | clamp(0.8, 1.25)", Value::Number(0.8)),
+            ("gcd(12, 18)", Value::Number(6.0)),
+            ("lcm(4, 6)", Value::Number(12.0)),
+            ("pow(2, 10)", Value::Number(1024.0)),
+            ("sqrt(16)", Value::Number(4.0)),
+            ("exp(0)", Value::Number(1.0)),
+            ("ln(exp(2))", Value::Number(2.0)),
             ("plus(2, 3)", Value::Number(5.0)),
             ("minus(5, 3)", Value::Number(2.0)),
             ("times(4, 5)", Value::Number(20.0)),
@@ -1165,21 +2744,54 @@ mod tests {
             ("contains([1, 2, 3], 4)", Value::Boolean(false)),
             ("sum([1, 2, 3, 4])", Value::Number(10.0)),
             ("avg([1, 2, 3, 4])", Value::Number(2.5)),
+            ("weighted_average([1, 2], [1, 3])", Value::Number(1.75)),
+            (
+                "weighted_average([{'value': 1, 'weight': 1}, {'value': 2, 'weight': 3}])",
+                Value::Number(1.75),
+            ),
+            ("median([5, 1, 4, 2])", Value::Number(3.0)),
+            ("median([])", Value::Nil),
+            ("mode(['a', 'b', 'b'])", Value::String("b".into())),
+            ("variance([1, 3])", Value::Number(1.0)),
+            ("variance_sample([1, 3])", Value::Number(2.0)),
+            ("stddev([2, 4, 4, 4, 5, 5, 7, 9])", Value::Number(2.0)),
+            ("stddev_sample([1])", Value::Nil),
+            ("percentile([1, 2, 3, 4, 5], 95)", Value::Number(4.8)),
+            ("[10, 20, 30] | quantile(0.25)", Value::Number(15.0)),
+            ("covariance([1, 2, 3], [1, 2, 3])", Value::Number(2.0 / 3.0)),
+            (
+                "covariance_sample([1, 2, 3], [3, 2, 1])",
+                Value::Number(-1.0),
+            ),
+            ("correlation([1, 2, 3], [10, 20, 30])", Value::Number(1.0)),
             ("at([10, 20, 30], 1)", Value::Number(20.0)),
+            (
+                "union([1, 2], [2, 3])",
+                Value::Array(
+                    vec![Value::Number(1.0), Value::Number(2.0), Value::Number(3.0)].into(),
+                ),
+            ),
+            (
+                "intersection([1, 2, 3], [3, 2])",
+                Value::Array(vec![Value::Number(2.0), Value::Number(3.0)].into()),
+            ),
+            (
+                "difference([1, 2, 3], [2])",
+                Value::Array(vec![Value::Number(1.0), Value::Number(3.0)].into()),
+            ),
+            (
+                "invert({'CA': 'California'}) | get('California')",
+                Value::String("CA".into()),
+            ),
             (
                 "keys({'a': 1, 'b': 2})",
-                Value::Array(vec![
-                    Value::String("a".to_string()),
-                    Value::String("b".to_string()),
-                ]),
+                Value::Array(vec![Value::String("a".into()), Value::String("b".into())].into()),
             ),
             (
                 "uniq([1, 2, 1, 3, 2])",
-                Value::Array(vec![
-                    Value::Number(1.0),
-                    Value::Number(2.0),
-                    Value::Number(3.0),
-                ]),
+                Value::Array(
+                    vec![Value::Number(1.0), Value::Number(2.0), Value::Number(3.0)].into(),
+                ),
             ),
         ];
 
@@ -1194,8 +2806,8 @@ mod tests {
     #[test]
     fn test_logic_functions() {
         let test_cases = vec![
-            ("choose(1, ['yes', 'no'])", Value::String("yes".to_string())),
-            ("choose(2, ['yes', 'no'])", Value::String("no".to_string())),
+            ("choose(1, ['yes', 'no'])", Value::String("yes".into())),
+            ("choose(2, ['yes', 'no'])", Value::String("no".into())),
             ("if_then_else(true, 1, 2)", Value::Number(1.0)),
             ("if_then_else(false, 1, 2)", Value::Number(2.0)),
             ("is_number(42)", Value::Boolean(true)),
@@ -1210,22 +2822,18 @@ mod tests {
             ("is_array(42)", Value::Boolean(false)),
             ("is_dictionary({'a': 1})", Value::Boolean(true)),
             ("is_dictionary(42)", Value::Boolean(false)),
-            (
-                "coalesce(nil, 'default')",
-                Value::String("default".to_string()),
-            ),
+            ("coalesce(nil, 'default')", Value::String("default".into())),
             (
                 "coalesce('value', 'default')",
-                Value::String("value".to_string()),
-            ),
-            (
-                "default(nil, 'default')",
-                Value::String("default".to_string()),
+                Value::String("value".into()),
             ),
             (
-                "default('value', 'default')",
-                Value::String("value".to_string()),
+                "coalesce(nil, nil, 'fallback', 'default')",
+                Value::String("fallback".into()),
             ),
+            ("coalesce(nil, nil, nil)", Value::Nil),
+            ("default(nil, 'default')", Value::String("default".into())),
+            ("default('value', 'default')", Value::String("value".into())),
         ];
 
         for (source, expected) in test_cases {
@@ -1269,36 +2877,836 @@ mod tests {
 
         assert_eq!(
             result,
-            Value::Array(vec![
-                Value::String("alice".to_string()),
-                Value::String("bob".to_string()),
-            ])
+            Value::Array(vec![Value::String("alice".into()), Value::String("bob".into()),].into())
         );
     }
 
     #[test]
-    fn test_financial_functions() {
-        // Test PMT function: payment for loan
-        // PMT(rate, nper, pv) - rate=5%, nper=12, pv=1000 should be approximately -85.61
-        let source = "pmt(0.05/12, 12, 1000, 0)";
-        let program = compile(source, &[]).unwrap();
+    fn test_pick_and_omit_functions() {
         let data = HashMap::new();
+
+        let source = "pick({'a': 1, 'b': 2, 'c': 3}, ['a', 'b'])";
+        let program = compile(source, &[]).unwrap();
         let result = evaluate(&program, &data).unwrap();
-        if let Value::Number(payment) = result {
-            // Allow small floating point differences
-            assert!((payment + 85.61).abs() < 0.01, "PMT result: {}", payment);
-        } else {
-            panic!("Expected number for PMT");
-        }
+        let mut expected = HashMap::new();
+        expected.insert("a".to_string(), Value::Number(1.0));
+        expected.insert("b".to_string(), Value::Number(2.0));
+        assert_eq!(result, Value::Dictionary(expected.into()));
 
-        // Test PV function: present value
-        let source = "pv(0.05/12, 12, -85.61)";
+        let source = "{'a': 1, 'b': 2, 'c': 3} | omit(['a', 'b'])";
         let program = compile(source, &[]).unwrap();
         let result = evaluate(&program, &data).unwrap();
-        if let Value::Number(pv) = result {
-            assert!((pv - 1000.0).abs() < 1.0, "PV result: {}", pv);
-        } else {
-            panic!("Expected number for PV");
+        let mut expected = HashMap::new();
+        expected.insert("c".to_string(), Value::Number(3.0));
+        assert_eq!(result, Value::Dictionary(expected.into()));
+    }
+
+    #[test]
+    fn test_has_key_and_get_functions() {
+        let mut policy = HashMap::new();
+        policy.insert("discount".to_string(), Value::Nil);
+        let mut data = HashMap::new();
+        data.insert("policy".to_string(), Value::Dictionary(policy.into()));
+
+        let test_cases = vec![
+            ("has_key(policy, 'discount')", Value::Boolean(true)),
+            ("has_key(policy, 'surcharge')", Value::Boolean(false)),
+            ("get(policy, 'discount', 0)", Value::Nil),
+            ("get(policy, 'surcharge', 0)", Value::Number(0.0)),
+            ("get(policy, 'surcharge')", Value::Nil),
+        ];
+
+        for (source, expected) in test_cases {
+            let program = compile(source, &[]).unwrap();
+            let result = evaluate(&program, &data).unwrap();
+            assert_eq!(result, expected, "Failed for: {}", source);
+        }
+    }
+
+    #[test]
+    fn test_entries_and_from_entries_functions() {
+        let data = HashMap::new();
+
+        let source = "entries({'b': 2, 'a': 1})";
+        let program = compile(source, &[]).unwrap();
+        let result = evaluate(&program, &data).unwrap();
+        assert_eq!(
+            result,
+            Value::Array(
+                vec![
+                    Value::Array(vec![Value::String("a".into()), Value::Number(1.0)].into()),
+                    Value::Array(vec![Value::String("b".into()), Value::Number(2.0)].into()),
+                ]
+                .into()
+            )
+        );
+
+        let source = "from_entries([['limit', 100], ['deductible', 5]])";
+        let program = compile(source, &[]).unwrap();
+        let result = evaluate(&program, &data).unwrap();
+        let mut expected = HashMap::new();
+        expected.insert("limit".to_string(), Value::Number(100.0));
+        expected.insert("deductible".to_string(), Value::Number(5.0));
+        assert_eq!(result, Value::Dictionary(expected.into()));
+    }
+
+    #[test]
+    fn test_dig_function() {
+        let mut coverage = HashMap::new();
+        coverage.insert("limit".to_string(), Value::Number(50000.0));
+        let mut policy = HashMap::new();
+        policy.insert(
+            "coverages".to_string(),
+            Value::Array(vec![Value::Dictionary(coverage.into())].into()),
+        );
+        let mut data = HashMap::new();
+        data.insert("policy".to_string(), Value::Dictionary(policy.into()));
+
+        let source = "dig(policy, ['coverages', 0, 'limit'])";
+        let program = compile(source, &[]).unwrap();
+        let result = evaluate(&program, &data).unwrap();
+        assert_eq!(result, Value::Number(50000.0));
+
+        let source = "dig(policy, ['coverages', 3, 'limit'])";
+        let program = compile(source, &[]).unwrap();
+        let result = evaluate(&program, &data).unwrap();
+        assert_eq!(result, Value::Nil);
+    }
+
+    #[test]
+    fn test_number_format_function() {
+        let test_cases = vec![
+            ("number_format(1234567.891, 2)", "1,234,567.89"),
+            ("number_format(1234567.891)", "1,234,568"),
+            ("1234567.891 | number_format(2, '.', ',')", "1.234.567,89"),
+            ("number_format(1500, 0, ' ')", "1 500"),
+        ];
+
+        for (source, expected) in test_cases {
+            let program = compile(source, &[]).unwrap();
+            let data = HashMap::new();
+            let result = evaluate(&program, &data).unwrap();
+            assert_eq!(
+                result,
+                Value::String(expected.into()),
+                "Failed for: {}",
+                source
+            );
+        }
+    }
+
+    #[test]
+    fn test_currency_function() {
+        let program = compile("currency(1234.56, 'USD')", &[]).unwrap();
+        let data = HashMap::new();
+        assert_eq!(
+            evaluate(&program, &data).unwrap(),
+            Value::String("$1,234.56".into())
+        );
+
+        let program = compile("premium | currency", &[]).unwrap();
+        let mut data = HashMap::new();
+        data.insert("premium".to_string(), Value::Number(99.5));
+        assert_eq!(
+            evaluate(&program, &data).unwrap(),
+            Value::String("$99.50".into())
+        );
+
+        let program = compile("currency(1234.56, 'EUR', 'fr-FR')", &[]).unwrap();
+        assert_eq!(
+            evaluate(&program, &data).unwrap(),
+            Value::String("1 234,56 €".into())
+        );
+    }
+
+    #[test]
+    fn test_currency_uses_context_locale() {
+        let program = compile("let p = 1234.56 in currency(p, 'EUR')", &[]).unwrap();
+        let context = Context::new(HashMap::new()).with_locale("de-DE");
+        assert_eq!(
+            eval_expr(program.ast(), &context).unwrap(),
+            Value::String("1.234,56 €".into())
+        );
+
+        let program = compile("currency(1, 'USD', 'en-US')", &[]).unwrap();
+        assert_eq!(
+            eval_expr(program.ast(), &context).unwrap(),
+            Value::String("$1.00".into())
+        );
+
+        // The public entry points take the locale from their options
+        let program = compile("currency(1234.56, 'EUR')", &[]).unwrap();
+        let options = EvalOptions::new().with_locale("de-DE");
+        assert_eq!(
+            evaluate_with_options(&program, &HashMap::new(), options).unwrap(),
+            Value::String("1.234,56 €".into())
+        );
+    }
+
+    #[test]
+    fn test_conversion_functions() {
+        let mut data = HashMap::new();
+        data.insert("age".to_string(), Value::String("25".into()));
+        data.insert("smoker".to_string(), Value::String("N".into()));
+        data.insert("zip".to_string(), Value::Number(3101.0));
+
+        let test_cases = vec![
+            ("to_number(age) + 1", Value::Number(26.0)),
+            ("to_number('n/a') | default(0)", Value::Number(0.0)),
+            ("age | to_number_strict", Value::Number(25.0)),
+            ("to_boolean(smoker)", Value::Boolean(false)),
+            ("to_string(zip)", Value::String("3101".into())),
+            ("to_string(:preferred)", Value::String("preferred".into())),
+        ];
+
+        for (source, expected) in test_cases {
+            let program = compile(source, &["preferred"]).unwrap();
+            let result = evaluate(&program, &data).unwrap();
+            assert_eq!(result, expected, "Failed for: {}", source);
+        }
+
+        let program = compile("to_number_strict('twenty')", &[]).unwrap();
+        assert!(matches!(
+            evaluate(&program, &data),
+            Err(EvalError::FunctionError(FunctionError::ValueError { .. }))
+        ));
+    }
+
+    #[test]
+    fn test_json_functions() {
+        let mut data = HashMap::new();
+        data.insert(
+            "payload".to_string(),
+            Value::String(r#"{"vehicle": {"year": 2019, "make": "Subaru"}}"#.into()),
+        );
+
+        let source = "dig(parse_json(payload), ['vehicle', 'year'])";
+        let program = compile(source, &[]).unwrap();
+        assert_eq!(evaluate(&program, &data).unwrap(), Value::Number(2019.0));
+
+        let source = "to_json({'decision': :approve, 'score': 0.75})";
+        let program = compile(source, &["approve"]).unwrap();
+        assert_eq!(
+            evaluate(&program, &data).unwrap(),
+            Value::String(r#"{"decision":":approve","score":0.75}"#.into())
+        );
+    }
+
+    #[test]
+    fn test_math_domain_errors() {
+        let data = HashMap::new();
+        for source in ["sqrt(-1)", "ln(0)", "pow(-8, 0.5)", "exp(1000)"] {
+            let program = compile(source, &[]).unwrap();
+            assert!(
+                matches!(
+                    evaluate(&program, &data),
+                    Err(EvalError::FunctionError(FunctionError::ValueError { .. }))
+                ),
+                "Expected domain error for: {}",
+                source
+            );
+        }
+    }
+
+    #[test]
+    fn test_random_functions_are_seeded() {
+        let program = compile("[random(), random_between(1, 100)]", &[]).unwrap();
+
+        let run = |seed: u64| {
+            let context = Context::new(HashMap::new()).with_seed(seed);
+            eval_expr(program.ast(), &context).unwrap()
+        };
+        assert_eq!(run(2024), run(2024));
+        assert_ne!(run(2024), run(2025));
+
+        // Unseeded evaluations are reproducible too
+        let data = HashMap::new();
+        assert_eq!(
+            evaluate(&program, &data).unwrap(),
+            evaluate(&program, &data).unwrap()
+        );
+
+        // The public entry points take the seed from their options
+        let seeded = |seed: u64| {
+            evaluate_with_options(&program, &data, EvalOptions::new().with_seed(seed)).unwrap()
+        };
+        assert_eq!(seeded(2024), run(2024));
+        assert_ne!(seeded(2025), evaluate(&program, &data).unwrap());
+    }
+
+    #[test]
+    fn test_random_sequence_continues_through_let() {
+        let program = compile("let a = random() in [a, random()]", &[]).unwrap();
+        let context = Context::new(HashMap::new()).with_seed(1);
+        match eval_expr(program.ast(), &context).unwrap() {
+            Value::Array(values) => assert_ne!(values[0], values[1]),
+            other => panic!("expected array, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_bucket_function() {
+        let mut data = HashMap::new();
+        let mut driver = HashMap::new();
+        driver.insert("age".to_string(), Value::Number(30.0));
+        data.insert("driver".to_string(), Value::Dictionary(driver.into()));
+
+        let source =
+            "bucket(driver.age, [18, 25, 40, 65], [:youth, :young, :adult, :senior, :elder])";
+        let symbols = ["youth", "young", "adult", "senior", "elder"];
+        let program = compile(source, &symbols).unwrap();
+        assert_eq!(
+            evaluate(&program, &data).unwrap(),
+            Value::Symbol("adult".to_string())
+        );
+
+        let program = compile(
+            "bucket(720, [580, 670, 740], ['poor', 'fair', 'good', 'excellent'])",
+            &[],
+        )
+        .unwrap();
+        assert_eq!(
+            evaluate(&program, &data).unwrap(),
+            Value::String("good".into())
+        );
+    }
+
+    #[test]
+    fn test_interpolate_function() {
+        let mut data = HashMap::new();
+        let mut curve = HashMap::new();
+        curve.insert(
+            "ages".to_string(),
+            Value::Array(
+                vec![
+                    Value::Number(16.0),
+                    Value::Number(25.0),
+                    Value::Number(70.0),
+                ]
+                .into(),
+            ),
+        );
+        curve.insert(
+            "factors".to_string(),
+            Value::Array(vec![Value::Number(2.0), Value::Number(1.0), Value::Number(1.3)].into()),
+        );
+        data.insert("curve".to_string(), Value::Dictionary(curve.into()));
+
+        let test_cases = vec![
+            ("interpolate(20.5, curve.ages, curve.factors)", 1.5),
+            ("interpolate(14, curve.ages, curve.factors)", 2.0),
+            ("round(interpolate(40, curve.ages, curve.factors), 4)", 1.1),
+        ];
+        for (source, expected) in test_cases {
+            let program = compile(source, &[]).unwrap();
+            let result = evaluate(&program, &data).unwrap();
+            assert_eq!(result, Value::Number(expected), "Failed for: {}", source);
+        }
+    }
+
+    #[test]
+    fn test_uuid_function() {
+        let program = compile("[uuid(), let id = uuid() in id]", &[]).unwrap();
+
+        let run = |seed: u64| {
+            let context = Context::new(HashMap::new()).with_uuid_seed(seed);
+            eval_expr(program.ast(), &context).unwrap()
+        };
+        assert_eq!(run(7), run(7));
+        match run(7) {
+            Value::Array(ids) => assert_ne!(ids[0], ids[1]),
+            other => panic!("expected array, got {:?}", other),
+        }
+        let options = EvalOptions::new().with_uuid_seed(7);
+        assert_eq!(
+            evaluate_with_options(&program, &HashMap::new(), options).unwrap(),
+            run(7)
+        );
+
+        // Unseeded evaluations get fresh identifiers, independent of the
+        // random seed
+        let data = HashMap::new();
+        assert_ne!(
+            evaluate(&program, &data).unwrap(),
+            evaluate(&program, &data).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_regex_functions() {
+        let mut data = HashMap::new();
+        data.insert("vin".to_string(), Value::String("1HGCM82633A004352".into()));
+
+        let source = "vin | regex_match('^[A-HJ-NPR-Z0-9]{17}$')";
+        let program = compile(source, &[]).unwrap();
+        assert_eq!(evaluate(&program, &data).unwrap(), Value::Boolean(true));
+
+        let source = "regex_extract(vin, '[0-9]{6}$')";
+        let program = compile(source, &[]).unwrap();
+        assert_eq!(
+            evaluate(&program, &data).unwrap(),
+            Value::String("004352".into())
+        );
+
+        let source = "regex_replace('a-b_c', '[-_]', ' ')";
+        let program = compile(source, &[]).unwrap();
+        assert_eq!(
+            evaluate(&program, &data).unwrap(),
+            Value::String("a b c".into())
+        );
+    }
+
+    #[test]
+    fn test_luhn_valid_requires_a_string() {
+        let program = compile("luhn_valid(79927398713)", &[]).unwrap();
+        assert!(matches!(
+            evaluate(&program, &HashMap::new()),
+            Err(EvalError::FunctionError(FunctionError::TypeError { .. }))
+        ));
+    }
+
+    #[test]
+    fn test_hashing_functions() {
+        let mut data = HashMap::new();
+        let mut driver = HashMap::new();
+        driver.insert("license".to_string(), Value::String("D1234567".into()));
+        data.insert("driver".to_string(), Value::Dictionary(driver.into()));
+
+        let program = compile("sha256('abc')", &[]).unwrap();
+        assert_eq!(
+            evaluate(&program, &data).unwrap(),
+            Value::String(
+                "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+                    .to_string()
+                    .into()
+            )
+        );
+
+        // The same input always hashes to the same identifier
+        let program = compile("sha256(driver.license) == (driver.license | sha256)", &[]).unwrap();
+        assert_eq!(evaluate(&program, &data).unwrap(), Value::Boolean(true));
+
+        let program = compile("md5('abc')", &[]).unwrap();
+        assert_eq!(
+            evaluate(&program, &data).unwrap(),
+            Value::String("900150983cd24fb0d6963f7d28e17f72".into())
+        );
+    }
+
+    #[test]
+    fn test_base64_functions() {
+        let data = HashMap::new();
+        let program = compile("base64_encode('client:secret')", &[]).unwrap();
+        assert_eq!(
+            evaluate(&program, &data).unwrap(),
+            Value::String("Y2xpZW50OnNlY3JldA==".into())
+        );
+
+        let program = compile("'client:secret' | base64_encode | base64_decode", &[]).unwrap();
+        assert_eq!(
+            evaluate(&program, &data).unwrap(),
+            Value::String("client:secret".into())
+        );
+
+        let program = compile("base64_decode('not base64!')", &[]).unwrap();
+        assert!(matches!(
+            evaluate(&program, &data),
+            Err(EvalError::FunctionError(FunctionError::ValueError { .. }))
+        ));
+    }
+
+    #[test]
+    fn test_url_encoding_functions() {
+        let mut data = HashMap::new();
+        data.insert("name".to_string(), Value::String("O'Brien & Sons".into()));
+
+        let program = compile(
+            "'https://example.com/callback?name=' + url_encode(name)",
+            &[],
+        )
+        .unwrap();
+        assert_eq!(
+            evaluate(&program, &data).unwrap(),
+            Value::String("https://example.com/callback?name=O%27Brien%20%26%20Sons".into())
+        );
+
+        let program = compile("name | url_encode | url_decode", &[]).unwrap();
+        assert_eq!(
+            evaluate(&program, &data).unwrap(),
+            Value::String("O'Brien & Sons".into())
+        );
+    }
+
+    #[test]
+    fn test_validation_functions() {
+        let test_cases = vec![
+            ("is_email('agent@example.com')", Value::Boolean(true)),
+            ("is_email('not an email')", Value::Boolean(false)),
+            ("is_email(nil)", Value::Boolean(false)),
+            ("is_numeric_string('1200.50')", Value::Boolean(true)),
+            ("is_numeric_string('12 00')", Value::Boolean(false)),
+            (
+                "matches_pattern('AB-1234', '[A-Z]{2}-[0-9]{4}')",
+                Value::Boolean(true),
+            ),
+            (
+                "matches_pattern('AB-12345', '[A-Z]{2}-[0-9]{4}')",
+                Value::Boolean(false),
+            ),
+            ("luhn_valid('4539 1488 0343 6467')", Value::Boolean(true)),
+            ("luhn_valid('4539 1488 0343 6468')", Value::Boolean(false)),
+        ];
+
+        for (source, expected) in test_cases {
+            let program = compile(source, &[]).unwrap();
+            let data = HashMap::new();
+            let result = evaluate(&program, &data).unwrap();
+            assert_eq!(result, expected, "Failed for: {}", source);
+        }
+
+        assert!(matches!(
+            compile("matches_pattern(id, '[0-9')", &[]),
+            Err(CompileError::InvalidPattern { .. })
+        ));
+    }
+
+    #[test]
+    fn test_regex_invalid_literal_pattern() {
+        let result = compile("regex_match(name, '[a-z')", &[]);
+        assert!(matches!(
+            result,
+            Err(CompileError::InvalidPattern { ref function, ref pattern, .. })
+                if function == "regex_match" && pattern == "[a-z"
+        ));
+
+        // Patterns from data can only be checked at evaluation time
+        let program = compile("regex_match('abc', pattern)", &[]).unwrap();
+        let mut data = HashMap::new();
+        data.insert("pattern".to_string(), Value::String("[a-z".into()));
+        assert!(matches!(
+            evaluate(&program, &data),
+            Err(EvalError::FunctionError(
+                FunctionError::InvalidPattern { .. }
+            ))
+        ));
+    }
+
+    #[test]
+    fn test_try_recovers_from_errors() {
+        let mut data = HashMap::new();
+        let mut driver = HashMap::new();
+        driver.insert("age".to_string(), Value::String("unknown".into()));
+        driver.insert("points".to_string(), Value::Number(2.0));
+        data.insert("driver".to_string(), Value::Dictionary(driver.into()));
+
+        let test_cases = vec![
+            ("try(to_number_strict(driver.age), 30)", Value::Number(30.0)),
+            ("try(driver.points * 10, 0)", Value::Number(20.0)),
+            ("try(1 / 0, nil)", Value::Nil),
+            (
+                "driver.age | to_number_strict | try(-1)",
+                Value::Number(-1.0),
+            ),
+            (
+                "try(missing_variable, 'fallback')",
+                Value::String("fallback".into()),
+            ),
+            (
+                "[try(to_number_strict(driver.age), 30), driver.points]",
+                Value::Array(vec![Value::Number(30.0), Value::Number(2.0)].into()),
+            ),
+        ];
+        for (source, expected) in test_cases {
+            let program = compile(source, &[]).unwrap();
+            let result = evaluate(&program, &data).unwrap();
+            assert_eq!(result, expected, "Failed for: {}", source);
+        }
+
+        // The fallback is only evaluated when needed, and its own errors
+        // still propagate
+        let program = compile("try(1, 1 / 0)", &[]).unwrap();
+        assert_eq!(evaluate(&program, &data).unwrap(), Value::Number(1.0));
+        let program = compile("try(1 / 0, to_number_strict(driver.age))", &[]).unwrap();
+        assert!(evaluate(&program, &data).is_err());
+
+        // Outside try, errors still abort the evaluation
+        let program = compile("to_number_strict(driver.age)", &[]).unwrap();
+        assert!(evaluate(&program, &data).is_err());
+    }
+
+    #[test]
+    fn test_safe_divide_function() {
+        let mut data = HashMap::new();
+        data.insert("claims".to_string(), Value::Number(3.0));
+        data.insert("exposures".to_string(), Value::Number(0.0));
+
+        let test_cases = vec![
+            ("safe_divide(claims, exposures)", Value::Nil),
+            ("safe_divide(claims, exposures, 0)", Value::Number(0.0)),
+            ("claims | safe_divide(4, 0)", Value::Number(0.75)),
+        ];
+        for (source, expected) in test_cases {
+            let program = compile(source, &[]).unwrap();
+            let result = evaluate(&program, &data).unwrap();
+            assert_eq!(result, expected, "Failed for: {}", source);
+        }
+
+        // Plain division still aborts on a zero divisor
+        let program = compile("claims / exposures", &[]).unwrap();
+        assert!(evaluate(&program, &data).is_err());
+    }
+
+    #[test]
+    fn test_between_function() {
+        let mut data = HashMap::new();
+        let mut driver = HashMap::new();
+        driver.insert("age".to_string(), Value::Number(25.0));
+        data.insert("driver".to_string(), Value::Dictionary(driver.into()));
+
+        let test_cases = vec![
+            ("between(driver.age, 18, 25)", true),
+            ("between(driver.age, 18, 25, '[)')", false),
+            ("driver.age | between(25, 65, '[)')", true),
+            ("between('2024-03-01', '2024-01-01', '2024-03-31')", true),
+        ];
+        for (source, expected) in test_cases {
+            let program = compile(source, &[]).unwrap();
+            let result = evaluate(&program, &data).unwrap();
+            assert_eq!(result, Value::Boolean(expected), "Failed for: {}", source);
+        }
+    }
+
+    #[test]
+    fn test_between_invalid_literal_bounds() {
+        assert!(matches!(
+            compile("between(age, 18, 25, 'inclusive')", &[]),
+            Err(CompileError::InvalidArgument { ref function, .. }) if function == "between"
+        ));
+        assert!(matches!(
+            compile("between(age, 18, 25, true)", &[]),
+            Err(CompileError::InvalidArgument { .. })
+        ));
+
+        // Flags from data can only be checked at evaluation time
+        let program = compile("between(5, 1, 10, bounds)", &[]).unwrap();
+        let mut data = HashMap::new();
+        data.insert("bounds".to_string(), Value::String("[[".into()));
+        assert!(matches!(
+            evaluate(&program, &data),
+            Err(EvalError::FunctionError(
+                FunctionError::ArgumentError { .. }
+            ))
+        ));
+    }
+
+    #[test]
+    fn test_financial_functions() {
+        // Test PMT function: payment for loan
+        // PMT(rate, nper, pv) - rate=5%, nper=12, pv=1000 should be approximately -85.61
+        let source = "pmt(0.05/12, 12, 1000, 0)";
+        let program = compile(source, &[]).unwrap();
+        let data = HashMap::new();
+        let result = evaluate(&program, &data).unwrap();
+        if let Value::Number(payment) = result {
+            // Allow small floating point differences
+            assert!((payment + 85.61).abs() < 0.01, "PMT result: {}", payment);
+        } else {
+            panic!("Expected number for PMT");
+        }
+
+        // Test PV function: present value
+        let source = "pv(0.05/12, 12, -85.61)";
+        let program = compile(source, &[]).unwrap();
+        let result = evaluate(&program, &data).unwrap();
+        if let Value::Number(pv) = result {
+            assert!((pv - 1000.0).abs() < 1.0, "PV result: {}", pv);
+        } else {
+            panic!("Expected number for PV");
+        }
+
+        // Test FVSCHEDULE function: step-rate growth
+        let source = "round(fvschedule(1000, [0.02, 0.03, 0.04]), 2)";
+        let program = compile(source, &[]).unwrap();
+        let result = evaluate(&program, &data).unwrap();
+        assert_eq!(result, Value::Number(1092.62));
+
+        // Test CAGR and COMPOUND functions: growth projections
+        let source = "round(cagr(10000, 19500, 3), 4)";
+        let program = compile(source, &[]).unwrap();
+        let result = evaluate(&program, &data).unwrap();
+        assert_eq!(result, Value::Number(0.2493));
+
+        let source = "1000 | compound(0.05, 10) | round(2)";
+        let program = compile(source, &[]).unwrap();
+        let result = evaluate(&program, &data).unwrap();
+        assert_eq!(result, Value::Number(1628.89));
+    }
+
+    #[test]
+    fn test_lending_rate_functions() {
+        let data = HashMap::new();
+        let test_cases = vec![
+            (
+                "round(apr(36, pmt(0.005, 36, 10000, 0), 10000, 0, 12), 4)",
+                0.06,
+            ),
+            (
+                "round(apr(36, pmt(0.005, 36, 10000, 0), 10000, 300, 12), 4)",
+                0.0806,
+            ),
+            ("round(periodic_to_annual(0.01, 12), 4)", 0.1268),
+            ("round(annual_to_periodic(0.1268250301, 12), 6)", 0.01),
+        ];
+        for (source, expected) in test_cases {
+            let program = compile(source, &[]).unwrap();
+            let result = evaluate(&program, &data).unwrap();
+            assert_eq!(result, Value::Number(expected), "Failed for: {}", source);
+        }
+    }
+
+    #[test]
+    fn test_tier_functions() {
+        let mut data = HashMap::new();
+        data.insert(
+            "brackets".to_string(),
+            Value::Array(
+                vec![
+                    Value::Array(vec![Value::Number(0.0), Value::Number(0.1)].into()),
+                    Value::Array(vec![Value::Number(10000.0), Value::Number(0.2)].into()),
+                    Value::Array(vec![Value::Number(40000.0), Value::Number(0.4)].into()),
+                ]
+                .into(),
+            ),
+        );
+        let test_cases = vec![
+            ("tier_lookup(25000, brackets)", Value::Number(0.2)),
+            ("25000 | tier_marginal(brackets)", Value::Number(4000.0)),
+            ("tier_lookup(5, [[10, 0.05], [100, 0.03]])", Value::Nil),
+            (
+                "tier_lookup(250, [[10, 0.05], [100, 0.03]])",
+                Value::Number(0.03),
+            ),
+            (
+                "round(prorate(1200, '2024-01-01', '2024-04-01'), 2)",
+                Value::Number(299.18),
+            ),
+            (
+                "round(prorate(1200, '2024-01-01', '2024-04-01', 360), 2)",
+                Value::Number(303.33),
+            ),
+        ];
+        for (source, expected) in test_cases {
+            let program = compile(source, &[]).unwrap();
+            let result = evaluate(&program, &data).unwrap();
+            assert_eq!(result, expected, "Failed for: {}", source);
+        }
+    }
+
+    #[test]
+    fn test_bond_functions() {
+        let data = HashMap::new();
+        let test_cases = vec![
+            (
+                "round(price('2008-02-15', '2017-11-15', 0.0575, 0.065, 100, 2), 4)",
+                94.6344,
+            ),
+            (
+                "round(yield('2008-02-15', '2016-11-15', 0.0575, 95.04287, 100, 2, 0), 4)",
+                0.065,
+            ),
+            (
+                "round(duration('2018-07-01', '2048-01-01', 0.08, 0.09, 2, 1), 4)",
+                10.9191,
+            ),
+            (
+                "round(mduration('2008-01-01', '2016-01-01', 0.08, 0.09, 2, 1), 4)",
+                5.7357,
+            ),
+        ];
+        for (source, expected) in test_cases {
+            let program = compile(source, &[]).unwrap();
+            let result = evaluate(&program, &data).unwrap();
+            assert_eq!(result, Value::Number(expected), "Failed for: {}", source);
+        }
+    }
+
+    #[test]
+    fn test_depreciation_functions() {
+        let data = HashMap::new();
+        let test_cases = vec![
+            ("round(syd(30000, 7500, 10, 1), 2)", 4090.91),
+            ("vdb(2400, 300, 10, 0, 1)", 480.0),
+            ("round(vdb(2400, 300, 120, 6, 18, 1.5), 2)", 311.81),
+            ("round(vdb(2400, 300, 10, 0, 1, 2, true), 2)", 480.0),
+        ];
+        for (source, expected) in test_cases {
+            let program = compile(source, &[]).unwrap();
+            let result = evaluate(&program, &data).unwrap();
+            assert_eq!(result, Value::Number(expected), "Failed for: {}", source);
+        }
+    }
+
+    #[test]
+    fn test_amortization_schedule_function() {
+        let program = compile("amortization_schedule(0.01, 3, 300) | map('balance')", &[]).unwrap();
+        let data = HashMap::new();
+        let balances = match evaluate(&program, &data).unwrap() {
+            Value::Array(items) => items,
+            other => panic!("Expected array, got {:?}", other),
+        };
+        assert_eq!(balances.len(), 3);
+        match &balances[0] {
+            Value::Number(b) => assert!((b - 200.99).abs() < 0.01, "balance: {}", b),
+            other => panic!("Expected number, got {:?}", other),
+        }
+        assert_eq!(balances[2], Value::Number(0.0));
+
+        let program = compile(
+            "get(at(amortization_schedule(0.01, 3, 300), 0), 'interest', nil)",
+            &[],
+        )
+        .unwrap();
+        assert_eq!(evaluate(&program, &data).unwrap(), Value::Number(-3.0));
+    }
+
+    #[test]
+    fn test_irregular_cash_flow_functions() {
+        let mut data = HashMap::new();
+        data.insert(
+            "flows".to_string(),
+            Value::Array(
+                vec![
+                    Value::Number(-1000.0),
+                    Value::Number(600.0),
+                    Value::Number(600.0),
+                ]
+                .into(),
+            ),
+        );
+        data.insert(
+            "dates".to_string(),
+            Value::Array(
+                vec![
+                    Value::String("2023-01-01".into()),
+                    Value::String("2024-01-01".into()),
+                    Value::String("2025-01-01".into()),
+                ]
+                .into(),
+            ),
+        );
+
+        let program = compile("xirr(flows, dates)", &[]).unwrap();
+        let rate = match evaluate(&program, &data).unwrap() {
+            Value::Number(rate) => rate,
+            other => panic!("Expected number for XIRR, got {:?}", other),
+        };
+        // 2024 is a leap year, so the second flow lands slightly after two years
+        assert!((rate - 0.1307).abs() < 0.001, "XIRR result: {}", rate);
+
+        let program = compile("xnpv(0.1, flows, dates)", &[]).unwrap();
+        match evaluate(&program, &data).unwrap() {
+            Value::Number(npv) => assert!((npv - 41.3).abs() < 1.0, "XNPV result: {}", npv),
+            other => panic!("Expected number for XNPV, got {:?}", other),
         }
     }
 
@@ -1311,11 +3719,249 @@ mod tests {
         let result = evaluate(&program, &data).unwrap();
         assert!(matches!(result, Value::String(_)));
 
-        // Test date_format
-        let source = r#"date_format("2023-01-15", "formatted: %Y-%m-%d")"#;
-        let program = compile(source, &[]).unwrap();
-        let result = evaluate(&program, &data).unwrap();
-        assert!(matches!(result, Value::String(_)));
+        // Test date_format
+        let source = r#"date_format("2023-01-15", "formatted: %Y-%m-%d")"#;
+        let program = compile(source, &[]).unwrap();
+        let result = evaluate(&program, &data).unwrap();
+        assert!(matches!(result, Value::String(_)));
+    }
+
+    #[test]
+    fn test_injectable_clock() {
+        let program = compile("date_now()", &[]).unwrap();
+        let run = |clock: Clock| {
+            let context = Context::new(HashMap::new()).with_clock(clock);
+            eval_expr(program.ast(), &context)
+        };
+
+        assert_eq!(
+            run(Clock::Fixed("2019-07-01".to_string())).unwrap(),
+            Value::String("2019-07-01".into())
+        );
+        assert_eq!(
+            run(Clock::Custom(Rc::new(|| "2020-02-29".to_string()))).unwrap(),
+            Value::String("2020-02-29".into())
+        );
+        assert!(run(Clock::Fixed("07/01/2019".to_string())).is_err());
+
+        let today = run(Clock::System).unwrap();
+        let tomorrow = run(Clock::Offset(1)).unwrap();
+        let program_diff = compile("date_diff(today, tomorrow, 'days')", &[]).unwrap();
+        let mut data = HashMap::new();
+        data.insert("today".to_string(), today);
+        data.insert("tomorrow".to_string(), tomorrow);
+        // The offset holds unless the test straddles midnight UTC
+        assert!(matches!(
+            evaluate(&program_diff, &data).unwrap(),
+            Value::Int(1 | 2)
+        ));
+
+        // Functions that default to today follow the clock too
+        let program = compile("age('2000-06-15')", &[]).unwrap();
+        let context =
+            Context::new(HashMap::new()).with_clock(Clock::Fixed("2024-06-14".to_string()));
+        assert_eq!(
+            eval_expr(program.ast(), &context).unwrap(),
+            Value::Number(23.0)
+        );
+
+        // The public entry points take the clock from their options
+        let program = compile("[date_now(), age('2000-06-15')]", &[]).unwrap();
+        let options = EvalOptions::new().with_clock(Clock::Fixed("2024-06-14".to_string()));
+        assert_eq!(
+            evaluate_with_options(&program, &HashMap::new(), options).unwrap(),
+            Value::Array(vec![Value::String("2024-06-14".into()), Value::Number(23.0)].into())
+        );
+    }
+
+    #[test]
+    fn test_date_arithmetic_functions() {
+        let mut data = HashMap::new();
+        let mut policy = HashMap::new();
+        policy.insert(
+            "effective_date".to_string(),
+            Value::String("2024-01-31".into()),
+        );
+        data.insert("policy".to_string(), Value::Dictionary(policy.into()));
+
+        let test_cases = vec![
+            ("date_add(policy.effective_date, 1, 'months')", "2024-02-29"),
+            ("policy.effective_date | date_add(30, 'days')", "2024-03-01"),
+            ("date_sub(policy.effective_date, 1, 'years')", "2023-01-31"),
+        ];
+
+        for (source, expected) in test_cases {
+            let program = compile(source, &["policy"]).unwrap();
+            let result = evaluate(&program, &data).unwrap();
+            assert_eq!(
+                result,
+                Value::String(expected.into()),
+                "Failed for: {}",
+                source
+            );
+        }
+
+        let program = compile("date_add('2024-02-30', 1, 'days')", &[]).unwrap();
+        assert!(evaluate(&program, &data).is_err());
+
+        let program = compile(
+            "end_of_month(policy.effective_date | date_add(1, 'months'))",
+            &["policy"],
+        )
+        .unwrap();
+        assert_eq!(
+            evaluate(&program, &data).unwrap(),
+            Value::String("2024-02-29".into())
+        );
+        let program = compile(
+            "[iso_week('2024-12-30'), iso_year('2024-12-30'), quarter('2024-08-15')]",
+            &[],
+        )
+        .unwrap();
+        assert_eq!(
+            evaluate(&program, &data).unwrap(),
+            Value::Array(
+                vec![
+                    Value::Number(1.0),
+                    Value::Number(2025.0),
+                    Value::Number(3.0)
+                ]
+                .into()
+            )
+        );
+        let program = compile("end_of_quarter('2024-08-15')", &[]).unwrap();
+        assert_eq!(
+            evaluate(&program, &data).unwrap(),
+            Value::String("2024-09-30".into())
+        );
+
+        let program = compile(
+            "date_diff(policy.effective_date, '2025-01-30', 'months')",
+            &["policy"],
+        )
+        .unwrap();
+        assert_eq!(evaluate(&program, &data).unwrap(), Value::Number(11.0));
+    }
+
+    #[test]
+    fn test_date_comparison_functions() {
+        let mut data = HashMap::new();
+        let mut policy = HashMap::new();
+        policy.insert(
+            "effective_date".to_string(),
+            Value::String("2024-03-15".into()),
+        );
+        data.insert("policy".to_string(), Value::Dictionary(policy.into()));
+
+        let test_cases = vec![
+            (
+                "date_after(policy.effective_date, '2024-01-01')",
+                Value::Boolean(true),
+            ),
+            (
+                "policy.effective_date | date_before('2024-01-01')",
+                Value::Boolean(false),
+            ),
+            (
+                "date_compare(policy.effective_date, '2024-03-15T08:00:00Z')",
+                Value::Number(0.0),
+            ),
+        ];
+        for (source, expected) in test_cases {
+            let program = compile(source, &["policy"]).unwrap();
+            let result = evaluate(&program, &data).unwrap();
+            assert_eq!(result, expected, "Failed for: {}", source);
+        }
+
+        let program =
+            compile("date_after(policy.effective_date, '3/1/2024')", &["policy"]).unwrap();
+        assert!(evaluate(&program, &data).is_err());
+    }
+
+    #[test]
+    fn test_duration_values() {
+        let data = HashMap::new();
+        let test_cases = vec![
+            (
+                "date_add('2024-01-31', duration_months(1))",
+                Value::String("2024-02-29".into()),
+            ),
+            (
+                "date_sub('2024-03-01', duration_days(1) + duration_weeks(1))",
+                Value::String("2024-02-22".into()),
+            ),
+            (
+                "date_diff('2024-01-01', '2024-02-15')",
+                Value::Duration(Duration::days(45)),
+            ),
+            (
+                "date_diff('2024-01-01', '2024-02-15') > duration_days(30)",
+                Value::Boolean(true),
+            ),
+            (
+                "duration_years(1) == duration_months(12)",
+                Value::Boolean(true),
+            ),
+            (
+                "to_string(duration_years(1) + duration_days(3))",
+                Value::String("P1Y3D".into()),
+            ),
+        ];
+
+        for (source, expected) in test_cases {
+            let program = compile(source, &[]).unwrap();
+            let result = evaluate(&program, &data).unwrap();
+            assert_eq!(result, expected, "Failed for: {}", source);
+        }
+
+        // Months and days cannot be ordered against each other
+        let program = compile("duration_months(1) > duration_days(30)", &[]).unwrap();
+        assert!(evaluate(&program, &data).is_err());
+    }
+
+    #[test]
+    fn test_age_function() {
+        let mut data = HashMap::new();
+        let mut driver = HashMap::new();
+        driver.insert("birthdate".to_string(), Value::String("2000-02-29".into()));
+        data.insert("driver".to_string(), Value::Dictionary(driver.into()));
+
+        let program = compile("age(driver.birthdate, '2024-02-28')", &["driver"]).unwrap();
+        assert_eq!(evaluate(&program, &data).unwrap(), Value::Number(23.0));
+
+        // Without as_of, age is measured against date_now()
+        let program = compile("age(driver.birthdate) >= 18", &["driver"]).unwrap();
+        assert_eq!(evaluate(&program, &data).unwrap(), Value::Boolean(true));
+    }
+
+    #[test]
+    fn test_business_day_functions() {
+        let data = HashMap::new();
+        let test_cases = vec![
+            ("is_weekend('2024-06-01')", Value::Boolean(true)),
+            (
+                "business_days_between('2024-06-07', '2024-06-14')",
+                Value::Number(5.0),
+            ),
+            (
+                "business_days_between('2024-06-07', '2024-06-14', ['2024-06-10'])",
+                Value::Number(4.0),
+            ),
+            (
+                "add_business_days('2024-06-07', 3)",
+                Value::String("2024-06-12".into()),
+            ),
+            (
+                "'2024-06-07' | add_business_days(1, ['2024-06-10'])",
+                Value::String("2024-06-11".into()),
+            ),
+        ];
+
+        for (source, expected) in test_cases {
+            let program = compile(source, &[]).unwrap();
+            let result = evaluate(&program, &data).unwrap();
+            assert_eq!(result, expected, "Failed for: {}", source);
+        }
     }
 
     #[test]
@@ -1354,18 +4000,1012 @@ mod tests {
         let source = r#"upcase("")"#;
         let program = compile(source, &[]).unwrap();
         let result = evaluate(&program, &data).unwrap();
-        assert_eq!(result, Value::String("".to_string()));
+        assert_eq!(result, Value::String("".into()));
 
         // Nested function calls
         let source = "upcase(capitalize('hello world'))";
         let program = compile(source, &[]).unwrap();
         let result = evaluate(&program, &data).unwrap();
-        assert_eq!(result, Value::String("HELLO WORLD".to_string()));
+        assert_eq!(result, Value::String("HELLO WORLD".into()));
 
         // Complex pipe chains
         let source = r#""  hello world  " | strip | capitalize | truncate(5)"#;
         let program = compile(source, &[]).unwrap();
         let result = evaluate(&program, &data).unwrap();
-        assert_eq!(result, Value::String("Hello".to_string()));
+        assert_eq!(result, Value::String("Hello".into()));
+    }
+
+    #[test]
+    fn test_function_values() {
+        let mut data = HashMap::new();
+        data.insert("rate".to_string(), Value::Number(0.5));
+        let context = Context::new(data).with_local("base".to_string(), Value::Int(10));
+
+        // A lambda sees the locals it was created with plus its arguments
+        let body = compile("base + x * rate", &[]).unwrap().ast().clone();
+        let Value::Function(scale) = closure(vec!["x".to_string()], body, &context) else {
+            panic!("Expected function");
+        };
+        let outer = Context::new(HashMap::from([("rate".to_string(), Value::Number(2.0))]));
+        assert_eq!(
+            apply(&scale, &[Value::Int(4)], &outer).unwrap(),
+            Value::Number(18.0)
+        );
+        assert_eq!(scale.to_string(), "<fn(x)>");
+        assert!(matches!(
+            apply(&scale, &[], &outer),
+            Err(EvalError::ArityMismatch {
+                expected: 1,
+                actual: 0,
+                ..
+            })
+        ));
+
+        let double = HostFunction::new("double", Some(1), |args| match &args[0] {
+            Value::Int(n) => Ok(Value::Int(n * 2)),
+            other => Err(format!("expected Integer, got {}", other.type_name())),
+        });
+        let double = AmoskeagFunction::Host(double);
+        assert_eq!(
+            apply(&double, &[Value::Int(21)], &context).unwrap(),
+            Value::Int(42)
+        );
+        let err = apply(&double, &[Value::Nil], &context).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Error in double: expected Integer, got Nil"
+        );
+
+        // Functions are equal only to themselves
+        assert_eq!(Value::Function(double.clone()), Value::Function(double));
+        let body = compile("1", &[]).unwrap().ast().clone();
+        assert_ne!(
+            closure(vec![], body.clone(), &context),
+            closure(vec![], body, &context)
+        );
+    }
+
+    #[test]
+    fn test_host_functions() {
+        let mut functions = FunctionRegistry::new();
+        functions
+            .register("credit_score", Signature::exact(1), |args| match &args[0] {
+                Value::String(ssn) if ssn.len() == 9 => Ok(Value::Int(720)),
+                other => Err(format!("invalid SSN {}", other)),
+            })
+            .register("total", Signature::at_least(1), |args| {
+                Ok(Value::Number(args.iter().filter_map(Value::as_f64).sum()))
+            });
+
+        let source = "if credit_score(applicant.ssn) >= 700 :approve else :deny end";
+        let program = compile_with_functions(source, &["approve", "deny"], &functions).unwrap();
+        let mut applicant = HashMap::new();
+        applicant.insert("ssn".to_string(), Value::String("123456789".into()));
+        let mut data = HashMap::new();
+        data.insert("applicant".to_string(), Value::Dictionary(applicant.into()));
+        assert_eq!(
+            evaluate(&program, &data).unwrap(),
+            Value::Symbol("approve".to_string())
+        );
+
+        let program = compile_with_functions("credit_score('bad')", &[], &functions).unwrap();
+        assert!(matches!(
+            evaluate(&program, &data),
+            Err(EvalError::HostFunctionError { function, .. }) if function == "credit_score"
+        ));
+        let program = compile_with_functions("total(1, 2, 3.5)", &[], &functions).unwrap();
+        assert_eq!(evaluate(&program, &data).unwrap(), Value::Number(6.5));
+
+        // Validated like the standard library
+        assert!(matches!(
+            compile_with_functions("credit_score()", &[], &functions),
+            Err(CompileError::ArityMismatch { .. })
+        ));
+        assert!(matches!(
+            compile("credit_score('123456789')", &[]),
+            Err(CompileError::UndefinedFunction { .. })
+        ));
+        functions.register("upcase", Signature::exact(1), |args| Ok(args[0].clone()));
+        assert!(matches!(
+            compile_with_functions("1", &[], &functions),
+            Err(CompileError::DuplicateFunction { function }) if function == "upcase"
+        ));
+    }
+
+    #[test]
+    fn test_eval_limits() {
+        let data = HashMap::new();
+        let program = compile("let x = 1 + 2 in x * 3", &[]).unwrap();
+        assert_eq!(
+            evaluate_with_options(&program, &data, EvalOptions::new().with_max_steps(7)).unwrap(),
+            Value::Int(9)
+        );
+        assert!(matches!(
+            evaluate_with_options(&program, &data, EvalOptions::new().with_max_steps(6)),
+            Err(EvalError::LimitExceeded(Limit::Steps(6)))
+        ));
+
+        // try() cannot recover from running out of budget
+        let program = compile("try(1 + 2 + 3, 0)", &[]).unwrap();
+        assert!(matches!(
+            evaluate_with_options(&program, &data, EvalOptions::new().with_max_steps(3)),
+            Err(EvalError::LimitExceeded(_))
+        ));
+
+        let timeout = std::time::Duration::ZERO;
+        let err = evaluate_with_options(&program, &data, EvalOptions::new().with_timeout(timeout))
+            .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Evaluation limit exceeded: ran longer than 0ns"
+        );
+
+        // A timeout past the end of time never expires
+        let options = EvalOptions::new().with_timeout(std::time::Duration::MAX);
+        assert_eq!(
+            evaluate_with_options(&program, &data, options).unwrap(),
+            Value::Int(6)
+        );
+    }
+
+    #[test]
+    fn test_size_limits() {
+        let data = HashMap::from([("name".to_string(), Value::String("abcdef".into()))]);
+        let run = |source: &str, options: &EvalOptions| {
+            evaluate_with_options(&compile(source, &[]).unwrap(), &data, options.clone())
+        };
+
+        let options = EvalOptions::new().with_max_string_length(8);
+        // Long strings from the data are fine; building one is not
+        assert!(run("name", &options).is_ok());
+        assert!(matches!(
+            run("name + name", &options),
+            Err(EvalError::LimitExceeded(Limit::StringLength(8)))
+        ));
+        assert!(matches!(
+            run("repeat('ab', 5)", &options),
+            Err(EvalError::LimitExceeded(Limit::StringLength(8)))
+        ));
+        // Refused before the string is built, rather than as a function error
+        assert!(matches!(
+            run("try(pad_left('x', 100000000000, '0'), 0)", &options),
+            Err(EvalError::LimitExceeded(Limit::StringLength(8)))
+        ));
+        assert!(matches!(
+            run("parse_json('{\"a\": [\"abcdefghij\"]}')", &options),
+            Err(EvalError::LimitExceeded(Limit::StringLength(8)))
+        ));
+
+        let options = EvalOptions::new().with_max_array_length(2);
+        assert!(run("[1, 2]", &options).is_ok());
+        assert!(matches!(
+            run("split('a,b,c', ',')", &options),
+            Err(EvalError::LimitExceeded(Limit::ArrayLength(2)))
+        ));
+        assert!(matches!(
+            run("parse_json('[[1, 2, 3]]')", &options),
+            Err(EvalError::LimitExceeded(Limit::ArrayLength(2)))
+        ));
+
+        // Every value built counts toward the total, even if discarded
+        let options = EvalOptions::new().with_max_allocation(20);
+        assert!(run("size(name + name)", &options).is_ok());
+        assert_eq!(
+            run("size(name + name + name + name)", &options)
+                .unwrap_err()
+                .to_string(),
+            "Evaluation limit exceeded: values larger than 20 bytes"
+        );
+        assert!(matches!(
+            run("repeat('x', 21)", &options),
+            Err(EvalError::LimitExceeded(Limit::Allocation(20)))
+        ));
+    }
+
+    #[test]
+    fn test_observer() {
+        use std::cell::RefCell;
+
+        #[derive(Default)]
+        struct Trace {
+            depth: Cell<usize>,
+            lines: RefCell<Vec<String>>,
+        }
+
+        impl EvalObserver for Trace {
+            fn enter(&self, _expr: &Expr) {
+                self.depth.set(self.depth.get() + 1);
+            }
+
+            fn exit(&self, _expr: &Expr, result: &Result<Value, EvalError>) {
+                let depth = self.depth.get() - 1;
+                self.depth.set(depth);
+                let result = match result {
+                    Ok(value) => value.to_string(),
+                    Err(err) => err.to_string(),
+                };
+                self.lines
+                    .borrow_mut()
+                    .push(format!("{}{}", "  ".repeat(depth), result));
+            }
+        }
+
+        let trace = Rc::new(Trace::default());
+        let context = Context::new(HashMap::new()).with_observer(trace.clone());
+        let program = compile("let x = 2 in x * upcase('a')", &[]).unwrap();
+        assert!(eval_expr(program.ast(), &context).is_err());
+
+        // Sub-expressions finish before the expressions containing them
+        let error = "Operator error: Invalid operation: Integer * String";
+        assert_eq!(
+            *trace.lines.borrow(),
+            [
+                "  2",
+                "    2",
+                "      a",
+                "    A",
+                &format!("  {}", error),
+                error
+            ]
+        );
+        assert_eq!(trace.depth.get(), 0);
+    }
+
+    #[test]
+    fn test_explanation() {
+        let mut driver = HashMap::new();
+        driver.insert("age".to_string(), Value::Int(19));
+        driver.insert("name".to_string(), Value::String("sam".into()));
+        let mut data = HashMap::new();
+        data.insert("driver".to_string(), Value::Dictionary(driver.into()));
+
+        let source = "if driver.age < 21 and upcase(driver.name) == 'SAM' :deny else :approve end";
+        let program = compile(source, &["approve", "deny"]).unwrap();
+        let (value, explanation) = evaluate_with_explanation(&program, &data).unwrap();
+        assert_eq!(value, Value::Symbol("deny".to_string()));
+
+        assert_eq!(explanation.branches.len(), 1);
+        assert!(explanation.branches[0].taken);
+        assert_eq!(explanation.branches[0].value, Value::Boolean(true));
+        let reads: Vec<_> = explanation
+            .variables
+            .iter()
+            .map(|read| (read.path.as_str(), read.value.to_string()))
+            .collect();
+        assert_eq!(
+            reads,
+            [
+                ("driver.age", "19".to_string()),
+                ("driver.name", "sam".to_string())
+            ]
+        );
+        assert_eq!(explanation.calls.len(), 1);
+        assert_eq!(explanation.calls[0].name, "upcase");
+        assert_eq!(explanation.calls[0].args, [Value::String("sam".into())]);
+        assert_eq!(explanation.calls[0].result, Value::String("SAM".into()));
+    }
+
+    #[test]
+    fn test_memoization() {
+        let claims: Vec<Value> = [60, 70]
+            .into_iter()
+            .map(|amount| {
+                let mut claim = HashMap::new();
+                claim.insert("amount".to_string(), Value::Int(amount));
+                Value::Dictionary(claim.into())
+            })
+            .collect();
+        let mut data = HashMap::new();
+        data.insert("claims".to_string(), Value::Array(claims.into()));
+
+        let source =
+            r#"if sum(map(claims, "amount")) > 100 then sum(map(claims, "amount")) * 2 else 0 end"#;
+        let program = compile(source, &[]).unwrap();
+        // The fewest steps the program runs in
+        let steps = |memoize| {
+            (1..)
+                .find(|&max_steps| {
+                    let options = EvalOptions::new()
+                        .with_memoization(memoize)
+                        .with_max_steps(max_steps);
+                    evaluate_with_options(&program, &data, options).is_ok()
+                })
+                .unwrap()
+        };
+        assert!(steps(true) < steps(false));
+        assert_eq!(
+            evaluate_with_options(&program, &data, EvalOptions::new().with_memoization(true))
+                .unwrap(),
+            Value::Number(260.0)
+        );
+
+        // The same text under different bindings is not shared
+        let options = EvalOptions::new().with_memoization(true);
+        let program = compile("let x = 1 in (let x = 2 in x + 1) + (x + 1)", &[]).unwrap();
+        assert_eq!(
+            evaluate_with_options(&program, &HashMap::new(), options.clone()).unwrap(),
+            Value::Int(5)
+        );
+
+        // Nor are calls that can return something new each time
+        let program = compile("random() == random()", &[]).unwrap();
+        assert_eq!(
+            evaluate_with_options(&program, &HashMap::new(), options).unwrap(),
+            Value::Boolean(false)
+        );
+    }
+
+    #[test]
+    fn test_memoization_keeps_warnings_and_explanation() {
+        let data = ContextBuilder::new().set("name", "Sam").build();
+        let program = compile(
+            "(if name then 1 else 2 end) + (if name then 1 else 2 end)",
+            &[],
+        )
+        .unwrap();
+        let run = |memoize| {
+            let options = EvalOptions::new()
+                .with_memoization(memoize)
+                .with_warnings(true)
+                .with_explanation(true);
+            evaluate_detailed(&program, &data, options).unwrap()
+        };
+
+        let memoized = run(true);
+        assert_eq!(memoized, run(false));
+        assert_eq!(memoized.explanation.unwrap().branches.len(), 2);
+    }
+
+    #[test]
+    fn test_data_provider() {
+        use std::cell::RefCell;
+
+        let fetched = Rc::new(RefCell::new(Vec::new()));
+        let log = fetched.clone();
+        let provider = move |path: &[String]| {
+            log.borrow_mut().push(path.join("."));
+            match path.join(".").as_str() {
+                "bureau.score" => Value::Int(640),
+                _ => Value::Nil,
+            }
+        };
+
+        let mut data = HashMap::new();
+        data.insert("age".to_string(), Value::Int(30));
+        let source = "if age < 25 then bureau.history else bureau.score + bureau.score end";
+        let program = compile(source, &[]).unwrap();
+        let result = evaluate_with_provider(&program, &data, Rc::new(provider)).unwrap();
+        assert_eq!(result, Value::Number(1280.0));
+
+        // Only what the rule touched was fetched, and only once
+        assert_eq!(*fetched.borrow(), ["bureau.score"]);
+
+        // Unknown variables are the provider's to answer
+        let program = compile("missing", &[]).unwrap();
+        let nil = Rc::new(|_: &[String]| Value::Nil);
+        assert_eq!(
+            evaluate_with_provider(&program, &HashMap::new(), nil).unwrap(),
+            Value::Nil
+        );
+    }
+
+    #[test]
+    fn test_strict_navigation() {
+        let data = ContextBuilder::new()
+            .set("driver.age", 25)
+            .set("driver.name", "Sam")
+            .build();
+        let run = |source: &str| {
+            let program = compile(source, &[]).unwrap().with_strict_navigation(true);
+            evaluate(&program, &data)
+        };
+
+        assert_eq!(run("driver.age").unwrap(), Value::Int(25));
+
+        // Each of these is nil under safe navigation
+        for source in ["drver.age", "driver.agee", "driver.name.first"] {
+            let program = compile(source, &[]).unwrap();
+            assert_eq!(evaluate(&program, &data).unwrap(), Value::Nil);
+        }
+        assert_eq!(
+            run("drver.age").unwrap_err().to_string(),
+            "Variable 'drver' not found"
+        );
+        assert_eq!(
+            run("driver.agee").unwrap_err().to_string(),
+            "Key 'agee' not found in 'driver'"
+        );
+        assert_eq!(
+            run("driver.name.first").unwrap_err().to_string(),
+            "Cannot navigate into 'driver.name': expected Dictionary, got String"
+        );
+    }
+
+    #[test]
+    fn test_warnings() {
+        let data = ContextBuilder::new().set("name", "Sam").build();
+        let run = |source: &str| {
+            let program = compile(source, &[]).unwrap();
+            let (_, warnings) = evaluate_with_warnings(&program, &data).unwrap();
+            warnings.iter().map(Warning::to_string).collect::<Vec<_>>()
+        };
+
+        assert!(run("if name == 'Sam' then 1 else 2 end").is_empty());
+        assert_eq!(
+            run("if name then 1 else 2 end"),
+            ["if condition is String, not Boolean"]
+        );
+        assert_eq!(
+            run("try(driver.age + 1, 0) + try(driver.age + 1, 0)"),
+            ["nil used as an operand of '+'"]
+        );
+    }
+
+    #[test]
+    fn test_evaluate_detailed() {
+        let data = ContextBuilder::new().set("name", "Sam").build();
+        let program = compile("if name then bureau.score else 0 end", &[]).unwrap();
+        let provider = Rc::new(|_: &[String]| Value::Int(640));
+        let options = EvalOptions::new()
+            .with_provider(provider)
+            .with_warnings(true)
+            .with_explanation(true)
+            .with_max_steps(100);
+
+        let evaluation = evaluate_detailed(&program, &data, options.clone()).unwrap();
+        assert_eq!(evaluation.value, Value::Int(640));
+        assert_eq!(
+            evaluation
+                .warnings
+                .iter()
+                .map(Warning::to_string)
+                .collect::<Vec<_>>(),
+            ["if condition is String, not Boolean"]
+        );
+        let explanation = evaluation.explanation.unwrap();
+        assert_eq!(explanation.branches.len(), 1);
+        assert!(explanation.branches[0].taken);
+
+        // The limits still apply alongside the provider
+        assert!(matches!(
+            evaluate_detailed(&program, &data, options.with_max_steps(2)),
+            Err(EvalError::LimitExceeded(Limit::Steps(2)))
+        ));
+
+        // Nothing is collected unless asked for
+        let evaluation = evaluate_detailed(&program, &data, EvalOptions::new()).unwrap();
+        assert!(evaluation.warnings.is_empty());
+        assert_eq!(evaluation.explanation, None);
+    }
+
+    #[test]
+    fn test_specialize() {
+        let source = "let base = get(rates, state) in if base == nil then :decline else base * driver.factor + fee end";
+        let program = compile(source, &["decline"]).unwrap();
+        let mut rates = HashMap::new();
+        rates.insert("NH".to_string(), Value::Int(100));
+        let known = ContextBuilder::new()
+            .merge(HashMap::from([("rates".to_string(), Value::from(rates))]))
+            .set("state", "NH")
+            .set("fee", 5)
+            .build();
+
+        let residual = program.specialize(&known);
+        assert_eq!(
+            residual.ast(),
+            compile("100 * driver.factor + 5", &[]).unwrap().ast()
+        );
+
+        let mut data = known.clone();
+        data.extend(ContextBuilder::new().set("driver.factor", 2).build());
+        assert_eq!(
+            evaluate(&residual, &data).unwrap(),
+            evaluate(&program, &data).unwrap()
+        );
+
+        // Unknown lets shadow known data, and failures are left in place
+        let program = compile("let fee = x in fee + (1 / 0)", &[]).unwrap();
+        assert_eq!(program.specialize(&known).ast(), program.ast());
+    }
+
+    #[test]
+    fn test_evaluate_each() {
+        let program = compile(
+            "if age < 25 then :young else :standard end",
+            &["young", "standard"],
+        )
+        .unwrap();
+        let input = "{\"age\": 19}\n{\"age\": 40}\n";
+        let records = json::ndjson_data(input.as_bytes()).map(Result::unwrap);
+        let results: Vec<_> = evaluate_each(&program, records)
+            .map(Result::unwrap)
+            .collect();
+        assert_eq!(
+            results,
+            [
+                Value::Symbol("young".to_string()),
+                Value::Symbol("standard".to_string())
+            ]
+        );
+    }
+
+    #[test]
+    fn test_nested_let_scope() {
+        // Each binding sees the ones before it, and shadowing wins
+        let mut source = String::from("let x0 = 1 in ");
+        for i in 1..200 {
+            source.push_str(&format!("let x{} = x{} + 1 in ", i, i - 1));
+        }
+        source.push_str("let x0 = 0 in x199 + x0");
+        let program = compile(&source, &[]).unwrap();
+        assert_eq!(
+            evaluate(&program, &HashMap::new()).unwrap(),
+            Value::Int(200)
+        );
+    }
+
+    #[test]
+    fn test_truthiness() {
+        let data = ContextBuilder::new().set("driver.age", 0).build();
+        let symbols = ["adult", "unknown"];
+        let program = compile("if driver.age then :adult else :unknown end", &symbols).unwrap();
+        assert_eq!(
+            evaluate(&program, &data).unwrap(),
+            Value::Symbol("adult".to_string())
+        );
+
+        let strict = program.with_truthiness(Truthiness::Strict);
+        assert_eq!(
+            evaluate(&strict, &data).unwrap_err().to_string(),
+            "Type error: expected Boolean condition, got Integer"
+        );
+        let program = compile("if driver.age > 0 then :adult else :unknown end", &symbols)
+            .unwrap()
+            .with_truthiness(Truthiness::Strict);
+        assert_eq!(
+            evaluate(&program, &data).unwrap(),
+            Value::Symbol("unknown".to_string())
+        );
+    }
+
+    #[test]
+    fn test_data_contract() {
+        let contract = DataContract::new()
+            .with_field("driver.age", Type::Number)
+            .with_field("driver.name", Type::String)
+            .with_field("claims", Type::Array)
+            .with_field("extra", Type::Dictionary);
+        let options = CompileOptions::new().with_contract(contract);
+        let check = |source: &str| {
+            compile_with_options(source, &[], &options)
+                .map(|_| ())
+                .map_err(|e| e.to_string())
+        };
+
+        assert!(check("driver.age + 1 > 18 and upcase(driver.name) == 'SAM'").is_ok());
+        assert!(check("sum(claims) / size(claims) + extra.anything.at.all").is_ok());
+        assert!(check("let n = driver.name in n + driver.age").is_ok());
+        assert_eq!(
+            check("unknown_root * 2"),
+            Err("Variable 'unknown_root' is not defined in the execution contract".to_string())
+        );
+
+        assert_eq!(
+            check("driver.age + 'x'"),
+            Err("Operator '+' cannot be applied to Number and String".to_string())
+        );
+        assert_eq!(
+            check("driver.age == 'sixteen'"),
+            Err("'==' between Number and String always gives the same answer".to_string())
+        );
+        assert_eq!(
+            check("upcase(driver.age)"),
+            Err("Argument 1 of 'upcase' must be String, got Number".to_string())
+        );
+        assert_eq!(
+            check("let a = driver.age in -driver.name"),
+            Err("Operator '-' cannot be applied to String".to_string())
+        );
+        assert_eq!(
+            check("driver.agee"),
+            Err("Field 'driver.agee' is not declared in the data contract".to_string())
+        );
+        assert_eq!(
+            check("driver.name.first"),
+            Err("'driver.name' is String, not a Dictionary".to_string())
+        );
+    }
+
+    #[test]
+    fn test_undefined_variables() {
+        let options = CompileOptions::new().with_variables(["driver", "state"]);
+        let check = |source: &str, options: &CompileOptions| {
+            compile_with_options(source, &[], options)
+                .map(|_| ())
+                .map_err(|e| e.to_string())
+        };
+
+        assert!(check("let x = driver.age in x + size(state)", &options).is_ok());
+        assert_eq!(
+            check("drver.age", &options),
+            Err("Variable 'drver' is not defined in the execution contract".to_string())
+        );
+        // Leaving scope unbinds
+        assert!(check("(let x = 1 in x) + x", &options).is_err());
+
+        // A contract's roots are allowed too
+        let contract = DataContract::new().with_field("policy.state", Type::String);
+        let with_contract = CompileOptions::new().with_contract(contract);
+        assert!(check("policy.state", &with_contract).is_ok());
+        assert!(check("driver", &with_contract).is_err());
+        assert!(check(
+            "policy.state + driver.name",
+            &with_contract.with_variables(["driver"])
+        )
+        .is_ok());
+
+        // Without a list or contract, anything goes
+        assert!(check("anything", &CompileOptions::new()).is_ok());
+    }
+
+    #[test]
+    fn test_program_artifact() {
+        let source = "let total = sum(items) in if total > limit then :high else :low end";
+        let program = compile(source, &["high", "low"])
+            .unwrap()
+            .with_numeric_mode(NumericMode::Decimal)
+            .with_truthiness(Truthiness::Strict);
+        let bytes = program.to_bytes();
+
+        let loaded = CompiledProgram::from_bytes(&bytes, &FunctionRegistry::new()).unwrap();
+        assert_eq!(loaded.ast(), program.ast());
+        assert_eq!(loaded.numeric_mode(), NumericMode::Decimal);
+        assert_eq!(loaded.truthiness(), Truthiness::Strict);
+        assert_eq!(loaded.to_bytes(), bytes);
+
+        let mut data = HashMap::new();
+        data.insert(
+            "items".to_string(),
+            Value::from(vec![Value::Number(40.0), Value::Number(70.0)]),
+        );
+        data.insert("limit".to_string(), Value::Number(100.0));
+        assert_eq!(
+            evaluate(&loaded, &data).unwrap(),
+            evaluate(&program, &data).unwrap()
+        );
+
+        // Another format version is refused rather than read
+        let mut stored: serde_json::Value = serde_json::from_slice(&bytes).unwrap();
+        stored["version"] = serde_json::json!(ARTIFACT_VERSION + 1);
+        let stale = serde_json::to_vec(&stored).unwrap();
+        assert!(matches!(
+            CompiledProgram::from_bytes(&stale, &FunctionRegistry::new()),
+            Err(ArtifactError::Version { found, expected })
+                if found == ARTIFACT_VERSION + 1 && expected == ARTIFACT_VERSION
+        ));
+
+        assert!(matches!(
+            CompiledProgram::from_bytes(b"not a program", &FunctionRegistry::new()),
+            Err(ArtifactError::Malformed(_))
+        ));
+
+        // Nesting deep enough to overflow the stack is refused, not followed
+        let deep = format!(
+            r#"{{"version":{},"ast":{}}}"#,
+            ARTIFACT_VERSION,
+            "[".repeat(100_000)
+        );
+        assert!(matches!(
+            CompiledProgram::from_bytes(deep.as_bytes(), &FunctionRegistry::new()),
+            Err(ArtifactError::Malformed(_))
+        ));
+
+        // Host functions are resolved again on load
+        let mut functions = FunctionRegistry::new();
+        functions.register("credit_score", Signature::exact(1), |_| {
+            Ok(Value::Number(700.0))
+        });
+        let program = compile_with_functions("credit_score(applicant)", &[], &functions).unwrap();
+        let bytes = program.to_bytes();
+        assert!(matches!(
+            CompiledProgram::from_bytes(&bytes, &FunctionRegistry::new()),
+            Err(ArtifactError::Invalid(
+                CompileError::UndefinedFunction { .. }
+            ))
+        ));
+        assert!(CompiledProgram::from_bytes(&bytes, &functions).is_ok());
+    }
+
+    #[test]
+    fn test_eliminate_dead_branches() {
+        let source = "let debug = false in \
+                      if debug then :review else \
+                        if 10 > 5 then (if score > 700 then :approve else :decline end) \
+                        else :review end \
+                      end";
+        let program = compile(source, &["review", "approve", "decline"]).unwrap();
+        let (pruned, warnings) = program.eliminate_dead_branches();
+
+        assert_eq!(
+            pruned.ast(),
+            compile(
+                "if score > 700 then :approve else :decline end",
+                &["approve", "decline"]
+            )
+            .unwrap()
+            .ast()
+        );
+        assert_eq!(
+            warnings.iter().map(Warning::to_string).collect::<Vec<_>>(),
+            vec![
+                "if condition is always false, the then branch never runs",
+                "if condition is always true, the else branch never runs",
+            ]
+        );
+        assert!(matches!(
+            &warnings[0],
+            Warning::DeadBranch { condition: Expr::Variable(path), taken: false } if path == &["debug"]
+        ));
+
+        for score in [650.0, 750.0] {
+            let mut data = HashMap::new();
+            data.insert("score".to_string(), Value::Number(score));
+            assert_eq!(
+                evaluate(&pruned, &data).unwrap(),
+                evaluate(&program, &data).unwrap()
+            );
+        }
+
+        // Conditions that depend on data are left alone
+        let program = compile("if score > 700 then 1 else 2 end", &[]).unwrap();
+        let (pruned, warnings) = program.eliminate_dead_branches();
+        assert_eq!(pruned.ast(), program.ast());
+        assert!(warnings.is_empty());
+
+        // Strict truthiness keeps a non-boolean condition for evaluation to reject
+        let program = compile("if \"yes\" then 1 else 2 end", &[])
+            .unwrap()
+            .with_truthiness(Truthiness::Strict);
+        let (pruned, warnings) = program.eliminate_dead_branches();
+        assert_eq!(pruned.ast(), program.ast());
+        assert!(warnings.is_empty());
+    }
+
+    #[test]
+    fn test_stdlib_signatures_are_dispatched() {
+        let data = HashMap::new();
+        let context = Context::borrowed(&data);
+        for function in signatures::all() {
+            // `try` never reaches dispatch: its first argument may not be evaluated
+            if function.name == "try" {
+                continue;
+            }
+            let args = vec![Value::Nil; function.arity.min_args()];
+            let result = dispatch_function(function.name, &args, &context);
+            assert!(
+                !matches!(&result, Err(EvalError::TypeError { expected, .. }) if expected == "known function"),
+                "{} is registered but not dispatched",
+                function.name
+            );
+            assert!(builtin_signature(function.name).is_some_and(|s| s.accepts(args.len())));
+        }
+    }
+
+    #[test]
+    fn test_check_symbols() {
+        let source = "let fallback = :refer in \
+                      if score > 700 then :approve \
+                      else if score > 600 then fallback \
+                      else coalesce(override, :decline) end end";
+        let program = compile(source, &["approve", "refer", "decline", "escalate"]).unwrap();
+        let warnings = program.check_symbols();
+        assert_eq!(
+            warnings,
+            vec![
+                Warning::UnreachableSymbol {
+                    symbol: "escalate".to_string()
+                },
+                Warning::UndeclaredResult {
+                    result: Expr::Variable(vec!["override".to_string()])
+                },
+            ]
+        );
+        assert_eq!(
+            warnings.iter().map(Warning::to_string).collect::<Vec<_>>(),
+            vec![
+                "symbol :escalate is declared but never returned",
+                "result override is not a declared symbol",
+            ]
+        );
+
+        // A let-bound name resolves in the scope it was bound in
+        let program = compile("let x = :a in let x = x in x", &["a"]).unwrap();
+        assert!(program.check_symbols().is_empty());
+
+        let program = compile(
+            "if flagged then :review else premium * 1.1 end",
+            &["review"],
+        )
+        .unwrap();
+        assert!(matches!(
+            program.check_symbols().as_slice(),
+            [Warning::UndeclaredResult {
+                result: Expr::Binary { .. }
+            }]
+        ));
+    }
+
+    #[test]
+    fn test_referenced_variables() {
+        let source = "let age = driver.age in \
+                      (if age < 25 and vehicle.value > 50000 then base * 1.5 \
+                       else base end) + sum(map(claims, \"amount\")) | round(2)";
+        let program = compile(source, &[]).unwrap();
+        assert_eq!(
+            program.referenced_variables(),
+            vec!["base", "claims", "driver.age", "vehicle.value"]
+        );
+
+        // A let-bound name shadows data of the same name only inside its body
+        let program = compile("let driver = driver.primary in driver.age", &[]).unwrap();
+        assert_eq!(program.referenced_variables(), vec!["driver.primary"]);
+
+        assert!(compile("1 + 2", &[])
+            .unwrap()
+            .referenced_variables()
+            .is_empty());
+    }
+
+    #[test]
+    fn test_used_functions_and_symbols() {
+        let mut functions = FunctionRegistry::new();
+        functions.register("credit_score", Signature::exact(1), |_| {
+            Ok(Value::Number(700.0))
+        });
+        let source = "if credit_score(applicant) > 700 and date_before(date_now(), cutoff) \
+                      then :approve \
+                      else if name | upcase == \"TEST\" then :approve else :refer end end";
+        let program =
+            compile_with_functions(source, &["approve", "refer", "decline"], &functions).unwrap();
+
+        assert_eq!(
+            program.used_functions(),
+            vec!["credit_score", "date_before", "date_now", "upcase"]
+        );
+        assert_eq!(program.used_symbols(), vec!["approve", "refer"]);
+
+        let program = compile("1 + 2", &["unused"]).unwrap();
+        assert!(program.used_functions().is_empty());
+        assert!(program.used_symbols().is_empty());
+    }
+
+    #[test]
+    fn test_fingerprint() {
+        let program = compile(
+            "if age >= 18 then :adult else :minor end",
+            &["adult", "minor"],
+        )
+        .unwrap();
+        let fingerprint = program.fingerprint();
+        assert_eq!(fingerprint.len(), 64);
+        assert!(fingerprint.chars().all(|c| c.is_ascii_hexdigit()));
+
+        // Layout and comments do not matter
+        let reformatted = compile(
+            "# Adults get the standard rate\nif age >= 18\n  then :adult\n  else :minor\nend\n",
+            &["minor", "adult", "unused"],
+        )
+        .unwrap();
+        assert_eq!(reformatted.fingerprint(), fingerprint);
+
+        // The rule and how it evaluates do
+        let changed = compile(
+            "if age >= 21 then :adult else :minor end",
+            &["adult", "minor"],
+        )
+        .unwrap();
+        assert_ne!(changed.fingerprint(), fingerprint);
+        let strict = compile(
+            "if age >= 18 then :adult else :minor end",
+            &["adult", "minor"],
+        )
+        .unwrap()
+        .with_truthiness(Truthiness::Strict);
+        assert_ne!(strict.fingerprint(), fingerprint);
+
+        // Stored and reloaded programs keep their fingerprint
+        let loaded =
+            CompiledProgram::from_bytes(&program.to_bytes(), &FunctionRegistry::new()).unwrap();
+        assert_eq!(loaded.fingerprint(), fingerprint);
+    }
+
+    #[test]
+    fn test_function_policy() {
+        let source = "if date_before(date_now(), cutoff) then pow(base, 2) else base | round end";
+
+        let (_, warnings) = compile_with_warnings(source, &[], &CompileOptions::new()).unwrap();
+        assert!(warnings.is_empty());
+
+        let options = CompileOptions::new().with_deprecated_function("round", "trunc");
+        let (_, warnings) = compile_with_warnings(source, &[], &options).unwrap();
+        assert_eq!(
+            warnings,
+            [Warning::DeprecatedFunction {
+                name: "round".to_string(),
+                replacement: "trunc".to_string()
+            }]
+        );
+
+        // Denied functions are errors, with any replacement suggested
+        let pure = CompileOptions::new().with_denied_functions(["date_now", "random"]);
+        let err = compile_with_options(source, &[], &pure).err().unwrap();
+        assert_eq!(
+            err.to_string(),
+            "Function 'date_now' is not allowed in this program"
+        );
+        let strict = options.with_denied_functions(["round"]);
+        assert_eq!(
+            compile_with_options(source, &[], &strict)
+                .err()
+                .unwrap()
+                .to_string(),
+            "Function 'round' is not allowed in this program, use 'trunc' instead"
+        );
+
+        // An allow-list rejects everything else, pipe targets included
+        let allowed =
+            CompileOptions::new().with_allowed_functions(["date_before", "date_now", "pow"]);
+        assert!(matches!(
+            compile_with_options(source, &[], &allowed),
+            Err(CompileError::DisallowedFunction { function, replacement: None }) if function == "round"
+        ));
+        let allowed = allowed.with_allowed_functions(["date_before", "date_now", "pow", "round"]);
+        assert!(compile_with_options(source, &[], &allowed).is_ok());
+    }
+
+    #[test]
+    fn test_multiple_compile_errors() {
+        let source =
+            "if tier == :platinum then upcase(name, 2) else no_such(:gold) + :platinum end";
+        let err = compile(source, &["silver"]).err().unwrap();
+        let messages: Vec<_> = err.errors().iter().map(CompileError::to_string).collect();
+        assert_eq!(
+            messages,
+            vec![
+                "Symbol 'platinum' is not defined in the execution contract",
+                "Function 'upcase' expects 1 arguments, but 2 were provided",
+                "Function 'no_such' is not defined",
+                "Symbol 'gold' is not defined in the execution contract",
+            ]
+        );
+        assert_eq!(
+            err.to_string(),
+            format!("4 errors: {}", messages.join("; "))
+        );
+
+        // Variable and function policy violations are collected in the same pass
+        let options = CompileOptions::new()
+            .with_variables(["tier"])
+            .with_denied_functions(["date_now"]);
+        let err = compile_with_options(
+            "if tier == drver.tier then date_now() else :silver end",
+            &["gold"],
+            &options,
+        )
+        .err()
+        .unwrap();
+        assert!(matches!(
+            err.errors(),
+            [
+                CompileError::UndefinedSymbol { .. },
+                CompileError::UndefinedVariable { .. },
+                CompileError::DisallowedFunction { .. },
+            ]
+        ));
+
+        // A single error is returned as itself
+        let err = compile(":gold", &[]).err().unwrap();
+        assert!(matches!(err, CompileError::UndefinedSymbol { .. }));
+        assert_eq!(err.errors().len(), 1);
     }
 }
@@ -0,0 +1,370 @@
+//! Resource limits and other options for a single evaluation
+//!
+//! Services that run rules they did not write can bound how much work an
+//! evaluation may do and how much memory its values may take. Exceeding a
+//! limit stops the evaluation with `EvalError::LimitExceeded`.
+
+use crate::{Clock, DataProvider, EvalError};
+use amoskeag_stdlib_operators::Value;
+use std::cell::Cell;
+use std::fmt;
+use std::rc::Rc;
+use std::time::{Duration, Instant};
+
+/// Limits on a single evaluation, and how it runs; the default is
+/// unlimited, on the system clock, in `DEFAULT_LOCALE`, without
+/// memoization, a data provider, warnings, or an explanation
+#[derive(Clone, Default)]
+pub struct EvalOptions {
+    max_steps: Option<u64>,
+    timeout: Option<Duration>,
+    max_array_length: Option<usize>,
+    max_string_length: Option<usize>,
+    max_allocation: Option<usize>,
+    memoize: bool,
+    provider: Option<Rc<dyn DataProvider>>,
+    warnings: bool,
+    explain: bool,
+    clock: Option<Clock>,
+    seed: Option<u64>,
+    uuid_seed: Option<u64>,
+    locale: Option<String>,
+}
+
+impl EvalOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Stop after evaluating `max_steps` expressions
+    ///
+    /// Every node of the program counts once each time it is evaluated, so
+    /// the count is deterministic for a given program and data.
+    pub fn with_max_steps(mut self, max_steps: u64) -> Self {
+        self.max_steps = Some(max_steps);
+        self
+    }
+
+    /// Stop once the evaluation has run for longer than `timeout`
+    ///
+    /// The deadline is checked between expressions, so a single slow
+    /// function call can overrun it.
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Reject arrays longer than `max_array_length` built during evaluation,
+    /// including those nested in the values built
+    pub fn with_max_array_length(mut self, max_array_length: usize) -> Self {
+        self.max_array_length = Some(max_array_length);
+        self
+    }
+
+    /// Reject strings longer than `max_string_length` bytes built during
+    /// evaluation, including those nested in the values built
+    ///
+    /// `repeat`, `pad_left`, and `pad_right` are refused before they build an
+    /// overly long string.
+    pub fn with_max_string_length(mut self, max_string_length: usize) -> Self {
+        self.max_string_length = Some(max_string_length);
+        self
+    }
+
+    /// Stop once the values built during evaluation add up to more than
+    /// `max_allocation` bytes
+    ///
+    /// Each string, array, and dictionary is counted when an expression
+    /// produces it, together with everything nested in it, so a value kept
+    /// inside later results counts again each time; values read from the
+    /// data are not counted. The total is an estimate, not an exact measure
+    /// of the heap.
+    pub fn with_max_allocation(mut self, max_allocation: usize) -> Self {
+        self.max_allocation = Some(max_allocation);
+        self
+    }
+
+    /// Evaluate sub-expressions that repeat in the program once, and reuse
+    /// the value at later occurrences
+    ///
+    /// Scanning the program for repeats costs a walk over it before
+    /// evaluation starts, which pays off for large rules that recompute the
+    /// same aggregates. A cached occurrence counts as a single step and is
+    /// not charged against the size limits again. Nothing is cached while
+    /// warnings or an explanation are collected, or an observer is set, so
+    /// they see every occurrence.
+    pub fn with_memoization(mut self, memoize: bool) -> Self {
+        self.memoize = memoize;
+        self
+    }
+
+    /// Fetch variables missing from the data from `provider` as the program
+    /// reads them
+    ///
+    /// Each path is fetched at most once per evaluation.
+    pub fn with_provider(mut self, provider: Rc<dyn DataProvider>) -> Self {
+        self.provider = Some(provider);
+        self
+    }
+
+    /// Collect warnings about questionable but legal behavior, returned in
+    /// `Evaluation::warnings`
+    pub fn with_warnings(mut self, warnings: bool) -> Self {
+        self.warnings = warnings;
+        self
+    }
+
+    /// Record how the evaluation reached its result, returned in
+    /// `Evaluation::explanation`
+    pub fn with_explanation(mut self, explain: bool) -> Self {
+        self.explain = explain;
+        self
+    }
+
+    /// Read the current date for `date_now`, and for functions that default
+    /// to today, from `clock`
+    ///
+    /// A fixed clock makes date-dependent rules reproducible, and lets them
+    /// be backtested against past dates.
+    pub fn with_clock(mut self, clock: Clock) -> Self {
+        self.clock = Some(clock);
+        self
+    }
+
+    /// Seed the generator behind `random` and `random_between`
+    ///
+    /// Unseeded evaluations start from 0, so a program draws the same
+    /// numbers on every run. Pass a different seed per run, such as one
+    /// derived from a customer id for A/B assignment, to vary them.
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.seed = Some(seed);
+        self
+    }
+
+    /// Seed the generator behind `uuid`
+    ///
+    /// Unseeded evaluations draw UUIDs from system entropy, so identifiers
+    /// differ between runs; seed it in tests to get a reproducible sequence.
+    pub fn with_uuid_seed(mut self, seed: u64) -> Self {
+        self.uuid_seed = Some(seed);
+        self
+    }
+
+    /// Set the default locale for formatting functions such as `currency`
+    pub fn with_locale(mut self, locale: impl Into<String>) -> Self {
+        self.locale = Some(locale.into());
+        self
+    }
+
+    pub fn max_steps(&self) -> Option<u64> {
+        self.max_steps
+    }
+
+    pub fn timeout(&self) -> Option<Duration> {
+        self.timeout
+    }
+
+    pub fn max_array_length(&self) -> Option<usize> {
+        self.max_array_length
+    }
+
+    pub fn max_string_length(&self) -> Option<usize> {
+        self.max_string_length
+    }
+
+    pub fn max_allocation(&self) -> Option<usize> {
+        self.max_allocation
+    }
+
+    pub fn memoization(&self) -> bool {
+        self.memoize
+    }
+
+    pub fn provider(&self) -> Option<&Rc<dyn DataProvider>> {
+        self.provider.as_ref()
+    }
+
+    pub fn warnings(&self) -> bool {
+        self.warnings
+    }
+
+    pub fn explanation(&self) -> bool {
+        self.explain
+    }
+
+    /// The clock set with `with_clock`, if any
+    pub fn clock(&self) -> Option<&Clock> {
+        self.clock.as_ref()
+    }
+
+    pub fn seed(&self) -> Option<u64> {
+        self.seed
+    }
+
+    pub fn uuid_seed(&self) -> Option<u64> {
+        self.uuid_seed
+    }
+
+    pub fn locale(&self) -> Option<&str> {
+        self.locale.as_deref()
+    }
+
+    fn limits_size(&self) -> bool {
+        self.max_array_length.is_some()
+            || self.max_string_length.is_some()
+            || self.max_allocation.is_some()
+    }
+}
+
+impl fmt::Debug for EvalOptions {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("EvalOptions")
+            .field("max_steps", &self.max_steps)
+            .field("timeout", &self.timeout)
+            .field("max_array_length", &self.max_array_length)
+            .field("max_string_length", &self.max_string_length)
+            .field("max_allocation", &self.max_allocation)
+            .field("memoize", &self.memoize)
+            .field("provider", &self.provider.is_some())
+            .field("warnings", &self.warnings)
+            .field("explain", &self.explain)
+            .field("clock", &self.clock)
+            .field("seed", &self.seed)
+            .field("uuid_seed", &self.uuid_seed)
+            .field("locale", &self.locale)
+            .finish()
+    }
+}
+
+/// The limit an evaluation ran into
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Limit {
+    /// More than this many expressions were evaluated
+    Steps(u64),
+    /// The evaluation ran longer than this
+    Timeout(Duration),
+    /// An array longer than this was built
+    ArrayLength(usize),
+    /// A string longer than this many bytes was built
+    StringLength(usize),
+    /// The values built took more than this many bytes
+    Allocation(usize),
+}
+
+impl std::fmt::Display for Limit {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Limit::Steps(max) => write!(f, "more than {} steps", max),
+            Limit::Timeout(timeout) => write!(f, "ran longer than {:?}", timeout),
+            Limit::ArrayLength(max) => write!(f, "array longer than {} elements", max),
+            Limit::StringLength(max) => write!(f, "string longer than {} bytes", max),
+            Limit::Allocation(max) => write!(f, "values larger than {} bytes", max),
+        }
+    }
+}
+
+/// What an evaluation has used so far, shared by a context and its children
+#[derive(Debug, Default)]
+pub(crate) struct Budget {
+    options: EvalOptions,
+    steps: Cell<u64>,
+    deadline: Option<Instant>,
+    allocated: Cell<usize>,
+}
+
+impl Budget {
+    /// A fresh budget; any timeout starts counting now
+    ///
+    /// A timeout too long to represent as an instant never expires.
+    pub(crate) fn new(options: EvalOptions) -> Self {
+        Budget {
+            steps: Cell::new(0),
+            deadline: options
+                .timeout
+                .and_then(|timeout| Instant::now().checked_add(timeout)),
+            options,
+            allocated: Cell::new(0),
+        }
+    }
+
+    /// Charge one evaluation step
+    pub(crate) fn step(&self) -> Result<(), EvalError> {
+        let steps = self.steps.get() + 1;
+        self.steps.set(steps);
+        if let Some(max) = self.options.max_steps {
+            if steps > max {
+                return Err(EvalError::LimitExceeded(Limit::Steps(max)));
+            }
+        }
+        if let (Some(deadline), Some(timeout)) = (self.deadline, self.options.timeout) {
+            if Instant::now() >= deadline {
+                return Err(EvalError::LimitExceeded(Limit::Timeout(timeout)));
+            }
+        }
+        Ok(())
+    }
+
+    /// Whether `charge` has anything to check
+    pub(crate) fn limits_size(&self) -> bool {
+        self.options.limits_size()
+    }
+
+    /// Check that a string of `len` bytes could be built without exceeding
+    /// the size limits, before building it
+    pub(crate) fn check_string(&self, len: usize) -> Result<(), EvalError> {
+        if let Some(max) = self.options.max_string_length.filter(|max| len > *max) {
+            return Err(EvalError::LimitExceeded(Limit::StringLength(max)));
+        }
+        match self.options.max_allocation {
+            Some(max) if self.allocated.get().saturating_add(len) > max => {
+                Err(EvalError::LimitExceeded(Limit::Allocation(max)))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Charge a value built during evaluation, and the values nested in it,
+    /// against the size limits
+    pub(crate) fn charge(&self, value: &Value) -> Result<(), EvalError> {
+        let exceeded = |limit| Err(EvalError::LimitExceeded(limit));
+        // Walked with an explicit stack, since values from the data or
+        // parse_json can nest deeply
+        let mut pending = vec![value];
+        let mut size: usize = 0;
+        while let Some(value) = pending.pop() {
+            let held = match value {
+                Value::String(s) => {
+                    if let Some(max) = self.options.max_string_length.filter(|max| s.len() > *max) {
+                        return exceeded(Limit::StringLength(max));
+                    }
+                    s.len()
+                }
+                Value::Array(items) => {
+                    if let Some(max) = self
+                        .options
+                        .max_array_length
+                        .filter(|max| items.len() > *max)
+                    {
+                        return exceeded(Limit::ArrayLength(max));
+                    }
+                    pending.extend(items.iter());
+                    items.len() * std::mem::size_of::<Value>()
+                }
+                Value::Dictionary(map) => {
+                    pending.extend(map.values());
+                    map.keys()
+                        .map(|key| key.len() + std::mem::size_of::<(String, Value)>())
+                        .sum()
+                }
+                _ => 0,
+            };
+            size = size.saturating_add(held);
+        }
+        let allocated = self.allocated.get().saturating_add(size);
+        self.allocated.set(allocated);
+        match self.options.max_allocation {
+            Some(max) if allocated > max => exceeded(Limit::Allocation(max)),
+            _ => Ok(()),
+        }
+    }
+}
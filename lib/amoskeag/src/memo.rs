@@ -0,0 +1,202 @@
+//! Common sub-expression caching
+//!
+//! Rules often repeat a sub-expression, such as `sum(map(claims, "amount"))`
+//! in several conditions. With `EvalOptions::with_memoization`, the program
+//! is scanned before evaluation for sub-expressions that occur more than
+//! once, and each is evaluated the first time it is reached and reused after.
+//!
+//! Two occurrences share a value only if they are written the same way and
+//! their variables resolve to the same bindings: `x` under two different
+//! `let x` is two different expressions. Within one evaluation each node of
+//! the program is evaluated at most once and each `let` binds once, so that
+//! is enough for them to agree. Expressions that call an impure standard
+//! library function, such as `random` or `uuid`, or a host function are
+//! never cached.
+
+use amoskeag_parser::{BinaryOp, Expr, UnaryOp};
+use amoskeag_stdlib_functions::signatures::{self, Purity};
+use amoskeag_stdlib_operators::Value;
+use std::cell::{Cell, OnceCell, RefCell};
+use std::collections::HashMap;
+
+/// Values of repeated sub-expressions, for the evaluations of one program
+#[derive(Debug, Default)]
+pub(crate) struct Memo {
+    /// Cached nodes by address, mapped to the class of nodes equal to them;
+    /// filled in when the program is first evaluated
+    classes: OnceCell<HashMap<*const Expr, usize>>,
+    values: RefCell<HashMap<usize, Value>>,
+}
+
+impl Memo {
+    /// The class `expr` is cached under, if it is cached at all
+    ///
+    /// The first node asked about is taken to be the whole program, and is
+    /// scanned for repeated pure sub-expressions then. It must stay where it
+    /// is for as long as the memo is used, since nodes are looked up by
+    /// address.
+    pub(crate) fn class(&self, expr: &Expr) -> Option<usize> {
+        self.classes
+            .get_or_init(|| {
+                let mut scan = Scan::default();
+                scan.visit(expr, &mut Vec::new());
+                scan.candidates
+                    .into_iter()
+                    .filter(|(_, class)| scan.occurrences[*class] > 1)
+                    .collect()
+            })
+            .get(&(expr as *const Expr))
+            .copied()
+    }
+
+    pub(crate) fn get(&self, class: usize) -> Option<Value> {
+        self.values.borrow().get(&class).cloned()
+    }
+
+    pub(crate) fn insert(&self, class: usize, value: Value) {
+        self.values.borrow_mut().insert(class, value);
+    }
+}
+
+/// Whether calls to `name` give the same value throughout an evaluation:
+/// standard library functions that are not impure
+fn cacheable(name: &str) -> bool {
+    signatures::lookup(name).is_some_and(|function| function.purity != Purity::Impure)
+}
+
+/// A node described by its own contents and the classes of its children,
+/// so equal nodes have equal shapes
+#[derive(PartialEq, Eq, Hash)]
+enum Shape {
+    Number(u64),
+    Integer(i64),
+    String(String),
+    Boolean(bool),
+    Nil,
+    Symbol(String),
+    Array(Vec<usize>),
+    Dictionary(Vec<(String, usize)>),
+    /// The path, and the `let` its root is bound by, or `None` for the data
+    Variable(Vec<String>, Option<usize>),
+    Call(String, Vec<usize>),
+    Let(String, usize, usize),
+    If(usize, usize, usize),
+    Binary(BinaryOp, usize, usize),
+    Unary(UnaryOp, usize),
+    Pipe(usize, usize),
+}
+
+/// Numbers every node of a program by class, equal nodes alike
+#[derive(Default)]
+struct Scan {
+    shapes: HashMap<Shape, usize>,
+    /// How many nodes fall in each class
+    occurrences: Vec<usize>,
+    /// Nodes worth caching, with their classes
+    candidates: Vec<(*const Expr, usize)>,
+    /// `let`s seen so far, to tell their bindings apart
+    lets: usize,
+}
+
+impl Scan {
+    /// Class `expr`, given the `let` bindings in scope (innermost last);
+    /// returns the class and whether `expr` is pure
+    fn visit<'a>(&mut self, expr: &'a Expr, scope: &mut Vec<(&'a str, usize)>) -> (usize, bool) {
+        let pure = Cell::new(true);
+        let child = |scan: &mut Self, expr: &'a Expr, scope: &mut Vec<(&'a str, usize)>| {
+            let (class, child_pure) = scan.visit(expr, scope);
+            pure.set(pure.get() && child_pure);
+            class
+        };
+
+        let shape = match expr {
+            Expr::Number(n) => Shape::Number(n.to_bits()),
+            Expr::Integer(n) => Shape::Integer(*n),
+            Expr::String(s) => Shape::String(s.clone()),
+            Expr::Boolean(b) => Shape::Boolean(*b),
+            Expr::Nil => Shape::Nil,
+            Expr::Symbol(s) => Shape::Symbol(s.clone()),
+            Expr::Array(items) => {
+                Shape::Array(items.iter().map(|item| child(self, item, scope)).collect())
+            }
+            Expr::Dictionary(pairs) => Shape::Dictionary(
+                pairs
+                    .iter()
+                    .map(|(key, value)| (key.clone(), child(self, value, scope)))
+                    .collect(),
+            ),
+            Expr::Variable(path) => {
+                let binding = path.first().and_then(|root| {
+                    scope
+                        .iter()
+                        .rev()
+                        .find(|(name, _)| name == root)
+                        .map(|(_, binding)| *binding)
+                });
+                Shape::Variable(path.clone(), binding)
+            }
+            Expr::FunctionCall { name, args } => {
+                if !cacheable(name) {
+                    pure.set(false);
+                }
+                Shape::Call(
+                    name.clone(),
+                    args.iter().map(|arg| child(self, arg, scope)).collect(),
+                )
+            }
+            Expr::Let { name, value, body } => {
+                let value = child(self, value, scope);
+                self.lets += 1;
+                scope.push((name, self.lets));
+                let body = child(self, body, scope);
+                scope.pop();
+                Shape::Let(name.clone(), value, body)
+            }
+            Expr::If {
+                condition,
+                then_branch,
+                else_branch,
+            } => Shape::If(
+                child(self, condition, scope),
+                child(self, then_branch, scope),
+                child(self, else_branch, scope),
+            ),
+            Expr::Binary { op, left, right } => {
+                Shape::Binary(*op, child(self, left, scope), child(self, right, scope))
+            }
+            Expr::Unary { op, operand } => Shape::Unary(*op, child(self, operand, scope)),
+            Expr::Pipe { left, right } => {
+                // The target names a function, so it is as impure as a call
+                if let Expr::Variable(path) = right.as_ref() {
+                    if !cacheable(&path.join(".")) {
+                        pure.set(false);
+                    }
+                }
+                Shape::Pipe(child(self, left, scope), child(self, right, scope))
+            }
+        };
+
+        let next = self.occurrences.len();
+        let class = *self.shapes.entry(shape).or_insert(next);
+        if class == next {
+            self.occurrences.push(0);
+        }
+        self.occurrences[class] += 1;
+
+        // Literals and variables are as quick to evaluate as to look up
+        let trivial = matches!(
+            expr,
+            Expr::Number(_)
+                | Expr::Integer(_)
+                | Expr::String(_)
+                | Expr::Boolean(_)
+                | Expr::Nil
+                | Expr::Symbol(_)
+                | Expr::Variable(_)
+        );
+        if pure.get() && !trivial {
+            self.candidates.push((expr, class));
+        }
+        (class, pure.get())
+    }
+}
@@ -0,0 +1,22 @@
+//! Hooks into evaluation
+//!
+//! An `EvalObserver` attached to a context sees every expression as it is
+//! evaluated, which is enough to build step debuggers, profilers, and audit
+//! trails without touching the evaluator.
+
+use crate::EvalError;
+use amoskeag_parser::Expr;
+use amoskeag_stdlib_operators::Value;
+
+/// Receives a callback before and after each expression is evaluated
+///
+/// Calls nest: every `enter` is matched by an `exit` for the same
+/// expression, after the `enter`s and `exit`s of its sub-expressions.
+/// Observers take `&self`, so those that record use interior mutability.
+pub trait EvalObserver {
+    /// `expr` is about to be evaluated
+    fn enter(&self, _expr: &Expr) {}
+
+    /// `expr` has been evaluated to `result`
+    fn exit(&self, _expr: &Expr, _result: &Result<Value, EvalError>) {}
+}
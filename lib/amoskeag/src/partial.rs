@@ -0,0 +1,335 @@
+//! Partial evaluation
+//!
+//! Rating rules often read some data that rarely changes, such as a state's
+//! base rates, alongside data that changes with every quote. Specializing a
+//! program on the slow-changing part computes everything that depends only
+//! on it once, leaving a smaller residual program to run per quote.
+
+use crate::{
+    call_function, eval_binary_op, eval_expr, eval_unary_op, program_context, CompiledProgram,
+    Context, EvalOptions, Truthiness, Warning,
+};
+use amoskeag_parser::Expr;
+use amoskeag_stdlib_functions::signatures::{self, Purity};
+use amoskeag_stdlib_operators::Value;
+use std::collections::HashMap;
+
+impl CompiledProgram {
+    /// Specialize this program on the part of its data known ahead of time
+    ///
+    /// Variables rooted at a key of `data` are replaced by their values, and
+    /// whatever then depends only on constants is computed: operators,
+    /// function calls, and `let`s fold, and `if`s with a known condition
+    /// reduce to the branch taken. Anything that would fail is kept, so
+    /// evaluation still reports the error.
+    ///
+    /// The result evaluates like this program for any data that agrees
+    /// with `data` on the keys `data` holds. Values with no literal form,
+    /// such as decimals and durations, stay as variable reads, so that data
+    /// must still provide them.
+    pub fn specialize(&self, data: &HashMap<String, Value>) -> CompiledProgram {
+        self.specialize_with_pruned(data).0
+    }
+
+    /// Drop the branches of `if`s whose condition is the same for any data
+    ///
+    /// This is `specialize` with no data: constant sub-expressions fold, and
+    /// an `if` whose condition folds reduces to the branch it always takes.
+    /// Each such `if` is reported as a `Warning::DeadBranch`, since a rule
+    /// with a branch that can never run is usually a mistake.
+    ///
+    /// Folding follows this program's numeric mode and truthiness, so set
+    /// those first. The result evaluates, and transpiles, like this program
+    /// for all data.
+    pub fn eliminate_dead_branches(&self) -> (CompiledProgram, Vec<Warning>) {
+        self.specialize_with_pruned(&HashMap::new())
+    }
+
+    /// `specialize`, along with a warning for each `if` it reduced
+    fn specialize_with_pruned(
+        &self,
+        data: &HashMap<String, Value>,
+    ) -> (CompiledProgram, Vec<Warning>) {
+        let context = program_context(self, data, EvalOptions::new());
+        let mut specializer = Specializer {
+            scope: Vec::new(),
+            pruned: Vec::new(),
+        };
+        let ast = specializer.visit(&self.ast, &context).expr;
+        let program = CompiledProgram {
+            ast,
+            symbols: self.symbols.clone(),
+            numeric_mode: self.numeric_mode,
+            functions: self.functions.clone(),
+            strict_navigation: self.strict_navigation,
+            truthiness: self.truthiness,
+        };
+        (program, specializer.pruned)
+    }
+}
+
+/// An expression after specialization, with its value if that is known
+struct Partial {
+    /// Evaluates to `value` when it is known; a literal when one exists
+    expr: Expr,
+    value: Option<Value>,
+}
+
+impl Partial {
+    fn residual(expr: Expr) -> Self {
+        Partial { expr, value: None }
+    }
+
+    /// `value`, written as a literal if it has one and as `fallback` if not
+    fn known(value: Value, fallback: impl FnOnce() -> Expr) -> Self {
+        Partial {
+            expr: literal(&value).unwrap_or_else(fallback),
+            value: Some(value),
+        }
+    }
+}
+
+struct Specializer {
+    /// `let` names in scope, innermost last, and whether their values are known
+    scope: Vec<(String, bool)>,
+    /// `if`s reduced to one branch, in the order they were reached
+    pruned: Vec<Warning>,
+}
+
+impl Specializer {
+    /// Specialize `expr`; `context` holds the known data and `let` values
+    fn visit(&mut self, expr: &Expr, context: &Context) -> Partial {
+        match expr {
+            Expr::Number(_)
+            | Expr::Integer(_)
+            | Expr::String(_)
+            | Expr::Boolean(_)
+            | Expr::Nil
+            | Expr::Symbol(_) => match eval_expr(expr, context) {
+                Ok(value) => Partial {
+                    expr: expr.clone(),
+                    value: Some(value),
+                },
+                Err(_) => Partial::residual(expr.clone()),
+            },
+
+            Expr::Array(items) => {
+                let items: Vec<_> = items.iter().map(|item| self.visit(item, context)).collect();
+                let values: Option<Vec<_>> = items.iter().map(|item| item.value.clone()).collect();
+                let exprs = items.into_iter().map(|item| item.expr).collect();
+                match values {
+                    Some(values) => Partial::known(values.into(), || Expr::Array(exprs)),
+                    None => Partial::residual(Expr::Array(exprs)),
+                }
+            }
+
+            Expr::Dictionary(pairs) => {
+                let pairs: Vec<_> = pairs
+                    .iter()
+                    .map(|(key, value)| (key.clone(), self.visit(value, context)))
+                    .collect();
+                let values: Option<HashMap<_, _>> = pairs
+                    .iter()
+                    .map(|(key, value)| Some((key.clone(), value.value.clone()?)))
+                    .collect();
+                let exprs = pairs
+                    .into_iter()
+                    .map(|(key, value)| (key, value.expr))
+                    .collect();
+                match values {
+                    Some(values) => Partial::known(values.into(), || Expr::Dictionary(exprs)),
+                    None => Partial::residual(Expr::Dictionary(exprs)),
+                }
+            }
+
+            Expr::Variable(path) => {
+                let Some(root) = path.first() else {
+                    return Partial::residual(expr.clone());
+                };
+                let known = match self.scope.iter().rev().find(|(name, _)| name == root) {
+                    Some((_, known)) => *known,
+                    None => context.contains(root),
+                };
+                match eval_expr(expr, context) {
+                    Ok(value) if known => Partial::known(value, || expr.clone()),
+                    _ => Partial::residual(expr.clone()),
+                }
+            }
+
+            // `try` is known once its first argument is; a failing first
+            // argument is left for evaluation to catch
+            Expr::FunctionCall { name, args } if name == "try" => {
+                let args: Vec<_> = args.iter().map(|arg| self.visit(arg, context)).collect();
+                match &args[0].value {
+                    Some(_) => args.into_iter().next().unwrap_or_else(|| unreachable!()),
+                    None => Partial::residual(Expr::FunctionCall {
+                        name: name.clone(),
+                        args: args.into_iter().map(|arg| arg.expr).collect(),
+                    }),
+                }
+            }
+
+            Expr::FunctionCall { name, args } => {
+                let args: Vec<_> = args.iter().map(|arg| self.visit(arg, context)).collect();
+                let values: Option<Vec<_>> = args.iter().map(|arg| arg.value.clone()).collect();
+                let call = Expr::FunctionCall {
+                    name: name.clone(),
+                    args: args.into_iter().map(|arg| arg.expr).collect(),
+                };
+                // Host functions, and results that depend on when or where
+                // they are computed, are left to evaluation
+                let foldable = signatures::lookup(name)
+                    .is_some_and(|function| function.purity == Purity::Pure);
+                match values.filter(|_| foldable) {
+                    Some(values) => match call_function(name, &values, context) {
+                        Ok(value) => Partial::known(value, || call),
+                        Err(_) => Partial::residual(call),
+                    },
+                    None => Partial::residual(call),
+                }
+            }
+
+            Expr::Let { name, value, body } => {
+                let value = self.visit(value, context);
+                let Some(known) = value.value.clone() else {
+                    self.scope.push((name.clone(), false));
+                    let body = self.visit(body, context);
+                    self.scope.pop();
+                    return Partial::residual(Expr::Let {
+                        name: name.clone(),
+                        value: Box::new(value.expr),
+                        body: Box::new(body.expr),
+                    });
+                };
+
+                // With a literal value every use is replaced, so the `let`
+                // can go; otherwise it stays unless the body is a literal
+                let inlined = literal(&known).is_some();
+                let inner = context.with_local(name.clone(), known);
+                self.scope.push((name.clone(), true));
+                let body = self.visit(body, &inner);
+                self.scope.pop();
+                if inlined || body.value.as_ref().and_then(literal).is_some() {
+                    body
+                } else {
+                    Partial {
+                        expr: Expr::Let {
+                            name: name.clone(),
+                            value: Box::new(value.expr),
+                            body: Box::new(body.expr),
+                        },
+                        value: body.value,
+                    }
+                }
+            }
+
+            Expr::If {
+                condition,
+                then_branch,
+                else_branch,
+            } => {
+                let folded = self.visit(condition, context);
+                let strict = context.truthiness == Truthiness::Strict;
+                let taken = match &folded.value {
+                    Some(Value::Boolean(b)) => Some(*b),
+                    Some(Value::Nil) if !strict => Some(false),
+                    Some(_) if !strict => Some(true),
+                    // A condition strict truthiness rejects fails at evaluation
+                    _ => None,
+                };
+                match taken {
+                    Some(taken) => {
+                        self.pruned.push(Warning::DeadBranch {
+                            condition: condition.as_ref().clone(),
+                            taken,
+                        });
+                        self.visit(if taken { then_branch } else { else_branch }, context)
+                    }
+                    None => Partial::residual(Expr::If {
+                        condition: Box::new(folded.expr),
+                        then_branch: Box::new(self.visit(then_branch, context).expr),
+                        else_branch: Box::new(self.visit(else_branch, context).expr),
+                    }),
+                }
+            }
+
+            Expr::Binary { op, left, right } => {
+                let left = self.visit(left, context);
+                let right = self.visit(right, context);
+                let folded = match (&left.value, &right.value) {
+                    (Some(l), Some(r)) => eval_binary_op(*op, l, r).ok(),
+                    _ => None,
+                };
+                let residual = || Expr::Binary {
+                    op: *op,
+                    left: Box::new(left.expr),
+                    right: Box::new(right.expr),
+                };
+                match folded {
+                    Some(value) => Partial::known(value, residual),
+                    None => Partial::residual(residual()),
+                }
+            }
+
+            Expr::Unary { op, operand } => {
+                let operand = self.visit(operand, context);
+                let folded = operand
+                    .value
+                    .as_ref()
+                    .and_then(|value| eval_unary_op(*op, value).ok());
+                let residual = || Expr::Unary {
+                    op: *op,
+                    operand: Box::new(operand.expr),
+                };
+                match folded {
+                    Some(value) => Partial::known(value, residual),
+                    None => Partial::residual(residual()),
+                }
+            }
+
+            // Pipes are desugared by the parser; specialize their parts only
+            Expr::Pipe { left, right } => {
+                let left = self.visit(left, context).expr;
+                let right = match right.as_ref() {
+                    Expr::FunctionCall { name, args } => Expr::FunctionCall {
+                        name: name.clone(),
+                        args: args
+                            .iter()
+                            .map(|arg| self.visit(arg, context).expr)
+                            .collect(),
+                    },
+                    other => other.clone(),
+                };
+                Partial::residual(Expr::Pipe {
+                    left: Box::new(left),
+                    right: Box::new(right),
+                })
+            }
+        }
+    }
+}
+
+/// `value` as a literal expression, if it can be written as one
+fn literal(value: &Value) -> Option<Expr> {
+    let expr = match value {
+        Value::Number(n) if n.is_finite() => Expr::Number(*n),
+        Value::Int(n) => Expr::Integer(*n),
+        Value::String(s) => Expr::String(s.to_string()),
+        Value::Boolean(b) => Expr::Boolean(*b),
+        Value::Nil => Expr::Nil,
+        Value::Symbol(s) => Expr::Symbol(s.clone()),
+        Value::Array(items) => Expr::Array(items.iter().map(literal).collect::<Option<_>>()?),
+        Value::Dictionary(map) => {
+            let mut pairs = map
+                .iter()
+                .map(|(key, value)| Some((key.clone(), literal(value)?)))
+                .collect::<Option<Vec<_>>>()?;
+            pairs.sort_by(|(a, _), (b, _)| a.cmp(b));
+            Expr::Dictionary(pairs)
+        }
+        Value::Number(_) | Value::Decimal(_) | Value::Duration(_) | Value::Function(_) => {
+            return None
+        }
+    };
+    Some(expr)
+}
@@ -0,0 +1,53 @@
+//! On-demand data
+//!
+//! A `DataProvider` stands in for data that is expensive to gather up front,
+//! such as a bureau report or a database column. The evaluator asks it for a
+//! variable only when a rule reads one the data dictionary does not hold, so
+//! fields a rule never touches are never fetched.
+
+use amoskeag_stdlib_operators::Value;
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::rc::Rc;
+
+/// Resolves variables missing from the data dictionary
+pub trait DataProvider {
+    /// The value at `path`, e.g. `["driver", "age"]` for `driver.age`
+    ///
+    /// Return `Value::Nil` for paths the provider does not know, as
+    /// navigating the data dictionary would.
+    fn resolve(&self, path: &[String]) -> Value;
+}
+
+impl<F: Fn(&[String]) -> Value> DataProvider for F {
+    fn resolve(&self, path: &[String]) -> Value {
+        self(path)
+    }
+}
+
+/// A provider whose answers are remembered for the rest of the evaluation,
+/// so each path is fetched at most once and reads of it agree
+pub(crate) struct Resolver {
+    provider: Rc<dyn DataProvider>,
+    resolved: RefCell<HashMap<Vec<String>, Value>>,
+}
+
+impl Resolver {
+    pub(crate) fn new(provider: Rc<dyn DataProvider>) -> Self {
+        Resolver {
+            provider,
+            resolved: RefCell::default(),
+        }
+    }
+
+    pub(crate) fn resolve(&self, path: &[String]) -> Value {
+        if let Some(value) = self.resolved.borrow().get(path) {
+            return value.clone();
+        }
+        let value = self.provider.resolve(path);
+        self.resolved
+            .borrow_mut()
+            .insert(path.to_vec(), value.clone());
+        value
+    }
+}
@@ -0,0 +1,109 @@
+//! Symbol reachability
+//!
+//! A decision rule is compiled against the symbols it may return, and those
+//! two drift apart as the rule is edited: a branch that returned `:refer` is
+//! rewritten, or a branch starts returning a data value instead of a symbol.
+//! `CompiledProgram::check_symbols` finds both without evaluating.
+
+use crate::{CompiledProgram, Warning};
+use amoskeag_parser::Expr;
+use std::collections::BTreeSet;
+
+impl CompiledProgram {
+    /// Compare the results this program can produce with its declared symbols
+    ///
+    /// Results are followed through `if` branches, `let` bodies and the
+    /// variables they bind, and the functions that return one of their
+    /// arguments (`if_then_else`, `coalesce`, `default`, `try`). A declared
+    /// symbol that no result can be is reported as
+    /// `Warning::UnreachableSymbol`; a result that is anything other than a
+    /// symbol literal is reported as `Warning::UndeclaredResult`.
+    ///
+    /// Meant for rules whose result is one of their symbols; a symbol used
+    /// only to compare against data is reported as unreachable.
+    pub fn check_symbols(&self) -> Vec<Warning> {
+        let mut results = Vec::new();
+        collect_results(&self.ast, &mut Vec::new(), &mut results);
+
+        let returned: BTreeSet<&str> = results
+            .iter()
+            .filter_map(|result| match result {
+                Expr::Symbol(symbol) => Some(symbol.as_str()),
+                _ => None,
+            })
+            .collect();
+        let declared: BTreeSet<&str> = self.symbols.iter().map(String::as_str).collect();
+
+        let mut warnings: Vec<Warning> = declared
+            .difference(&returned)
+            .map(|symbol| Warning::UnreachableSymbol {
+                symbol: symbol.to_string(),
+            })
+            .collect();
+        for result in results {
+            if !matches!(result, Expr::Symbol(_)) {
+                let warning = Warning::UndeclaredResult {
+                    result: result.clone(),
+                };
+                if !warnings.contains(&warning) {
+                    warnings.push(warning);
+                }
+            }
+        }
+        warnings
+    }
+}
+
+/// Push the expressions whose value `expr` can evaluate to, in source order
+///
+/// `scope` holds the `let` bindings in scope, innermost last.
+fn collect_results<'a>(
+    expr: &'a Expr,
+    scope: &mut Vec<(&'a str, &'a Expr)>,
+    results: &mut Vec<&'a Expr>,
+) {
+    match expr {
+        Expr::If {
+            then_branch,
+            else_branch,
+            ..
+        } => {
+            collect_results(then_branch, scope, results);
+            collect_results(else_branch, scope, results);
+        }
+
+        Expr::Let { name, value, body } => {
+            scope.push((name, value));
+            collect_results(body, scope, results);
+            scope.pop();
+        }
+
+        Expr::Variable(path) if path.len() == 1 => {
+            match scope.iter().rposition(|(name, _)| *name == path[0]) {
+                // The value is resolved in the scope it was bound in
+                Some(index) => {
+                    let value = scope[index].1;
+                    let mut outer = scope[..index].to_vec();
+                    collect_results(value, &mut outer, results);
+                }
+                None => results.push(expr),
+            }
+        }
+
+        Expr::FunctionCall { name, args } => match name.as_str() {
+            "if_then_else" => {
+                for arg in &args[1..] {
+                    collect_results(arg, scope, results);
+                }
+            }
+            "coalesce" | "default" | "try" => {
+                for arg in args {
+                    collect_results(arg, scope, results);
+                }
+            }
+            _ => results.push(expr),
+        },
+
+        _ => results.push(expr),
+    }
+}
@@ -0,0 +1,132 @@
+//! Host function registration
+//!
+//! Embedders expose their own functions (a credit bureau lookup, a rate table
+//! service) by registering them here and passing the registry to
+//! `compile_with_functions`. Registered functions are validated like the
+//! standard library: calls to unknown names or with the wrong number of
+//! arguments fail at compile time.
+
+use amoskeag_stdlib_functions::signatures::Arity;
+use amoskeag_stdlib_operators::{HostFunction, Value};
+use std::collections::HashMap;
+use std::sync::Arc;
+
+/// How many arguments a function accepts
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Signature {
+    min_args: usize,
+    max_args: usize,
+}
+
+impl Signature {
+    /// Exactly `count` arguments
+    pub fn exact(count: usize) -> Self {
+        Signature {
+            min_args: count,
+            max_args: count,
+        }
+    }
+
+    /// Between `min_args` and `max_args` arguments, inclusive
+    pub fn range(min_args: usize, max_args: usize) -> Self {
+        Signature { min_args, max_args }
+    }
+
+    /// `min_args` or more arguments
+    pub fn at_least(min_args: usize) -> Self {
+        Signature {
+            min_args,
+            max_args: usize::MAX,
+        }
+    }
+
+    pub fn min_args(&self) -> usize {
+        self.min_args
+    }
+
+    /// The most arguments accepted, or `None` when there is no limit
+    pub fn max_args(&self) -> Option<usize> {
+        (self.max_args != usize::MAX).then_some(self.max_args)
+    }
+
+    /// Whether a call with `count` arguments is allowed
+    pub fn accepts(&self, count: usize) -> bool {
+        (self.min_args..=self.max_args).contains(&count)
+    }
+
+    /// The expected argument count as shown in arity errors, e.g. `2` or `1-3`
+    pub(crate) fn describe(&self) -> String {
+        if self.min_args == self.max_args {
+            format!("{}", self.min_args)
+        } else if self.max_args == usize::MAX {
+            format!("at least {}", self.min_args)
+        } else {
+            format!("{}-{}", self.min_args, self.max_args)
+        }
+    }
+}
+
+impl From<Arity> for Signature {
+    fn from(arity: Arity) -> Self {
+        match arity {
+            Arity::Exact(count) => Signature::exact(count),
+            Arity::Range(min_args, max_args) => Signature::range(min_args, max_args),
+            Arity::AtLeast(min_args) => Signature::at_least(min_args),
+        }
+    }
+}
+
+/// A set of host functions callable from Amoskeag programs
+///
+/// Cloning is cheap: the functions are shared, and registering on a clone
+/// leaves the original untouched.
+#[derive(Clone, Default)]
+pub struct FunctionRegistry {
+    functions: Arc<HashMap<String, (Signature, HostFunction)>>,
+}
+
+impl FunctionRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Register `func` under `name`, replacing any function already registered there
+    ///
+    /// Arguments arrive as evaluated, with integers and decimals left as
+    /// they are. An `Err` message surfaces as `EvalError::HostFunctionError`.
+    pub fn register(
+        &mut self,
+        name: impl Into<String>,
+        signature: Signature,
+        func: impl Fn(&[Value]) -> Result<Value, String> + Send + Sync + 'static,
+    ) -> &mut Self {
+        let name = name.into();
+        let arity = (signature.min_args == signature.max_args).then_some(signature.min_args);
+        let function = HostFunction::new(name.as_str(), arity, func);
+        Arc::make_mut(&mut self.functions).insert(name, (signature, function));
+        self
+    }
+
+    /// The function registered under `name`
+    pub fn get(&self, name: &str) -> Option<&HostFunction> {
+        self.functions.get(name).map(|(_, function)| function)
+    }
+
+    /// The signature of the function registered under `name`
+    pub fn signature(&self, name: &str) -> Option<Signature> {
+        self.functions.get(name).map(|(signature, _)| *signature)
+    }
+
+    /// The names of all registered functions, in no particular order
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.functions.keys().map(String::as_str)
+    }
+
+    pub fn len(&self) -> usize {
+        self.functions.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.functions.is_empty()
+    }
+}
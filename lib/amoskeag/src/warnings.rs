@@ -0,0 +1,94 @@
+//! Non-fatal warnings
+//!
+//! Some programs run without error but lean on behavior that is easy to get
+//! wrong: a missing value slipping into arithmetic, or a condition that is
+//! a string rather than a boolean. `evaluate_with_warnings` reports these
+//! alongside the result so rule authors can clean them up without breaking
+//! anything in production. Calls to deprecated functions, whether the host
+//! or the standard library deprecated them, are found without evaluating, by `compile_with_warnings`, as are branches
+//! that can never run, by `CompiledProgram::eliminate_dead_branches`, and
+//! symbols a rule can never return, by `CompiledProgram::check_symbols`.
+
+use amoskeag_parser::Expr;
+use std::cell::RefCell;
+
+/// Something questionable in a program or an evaluation
+#[derive(Debug, Clone, PartialEq)]
+pub enum Warning {
+    /// `nil` reached an operator other than `==` or `!=`, usually because a
+    /// variable was missing; inside `try` the resulting error is swallowed
+    NilOperand { op: String, expr: Expr },
+
+    /// An `if` condition was not a boolean and was tested for truthiness
+    NonBooleanCondition { condition: Expr, got: String },
+
+    /// A function deprecated with `CompileOptions::with_deprecated_function`,
+    /// or in its standard library signature, is called
+    DeprecatedFunction { name: String, replacement: String },
+
+    /// An `if` condition is the same for any data, so only the branch it
+    /// always takes can run
+    DeadBranch { condition: Expr, taken: bool },
+
+    /// A declared symbol is never a result of the program
+    UnreachableSymbol { symbol: String },
+
+    /// A result of the program is not a symbol literal, so it can be a
+    /// value outside the declared symbols
+    UndeclaredResult { result: Expr },
+}
+
+impl std::fmt::Display for Warning {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Warning::NilOperand { op, .. } => write!(f, "nil used as an operand of '{}'", op),
+            Warning::NonBooleanCondition { got, .. } => {
+                write!(f, "if condition is {}, not Boolean", got)
+            }
+            Warning::DeprecatedFunction { name, replacement } => {
+                write!(f, "{} is deprecated, use {} instead", name, replacement)
+            }
+            Warning::DeadBranch { taken, .. } => {
+                let (always, dead) = if *taken {
+                    ("true", "else")
+                } else {
+                    ("false", "then")
+                };
+                write!(
+                    f,
+                    "if condition is always {}, the {} branch never runs",
+                    always, dead
+                )
+            }
+            Warning::UnreachableSymbol { symbol } => {
+                write!(f, "symbol :{} is declared but never returned", symbol)
+            }
+            Warning::UndeclaredResult { result } => match result {
+                Expr::Variable(path) => {
+                    write!(f, "result {} is not a declared symbol", path.join("."))
+                }
+                Expr::FunctionCall { name, .. } => {
+                    write!(f, "result of {} is not a declared symbol", name)
+                }
+                _ => write!(f, "a result is not a declared symbol"),
+            },
+        }
+    }
+}
+
+/// Warnings raised so far in an evaluation, each recorded once
+#[derive(Debug, Default)]
+pub(crate) struct Warnings(RefCell<Vec<Warning>>);
+
+impl Warnings {
+    pub(crate) fn push(&self, warning: Warning) {
+        let mut warnings = self.0.borrow_mut();
+        if !warnings.contains(&warning) {
+            warnings.push(warning);
+        }
+    }
+
+    pub(crate) fn into_vec(self) -> Vec<Warning> {
+        self.0.into_inner()
+    }
+}
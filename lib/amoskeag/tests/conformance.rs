@@ -0,0 +1,58 @@
+//! Runs the shared conformance cases on the interpreter
+//!
+//! The same cases are run on the Ruby, Java, and C# runtimes by the tests of
+//! their transpilers, so these results are the ones every backend must give.
+
+#[path = "../../../conformance/support.rs"]
+mod conformance;
+
+use amoskeag::JsonOptions;
+use amoskeag_stdlib_operators::Value;
+use serde_json::json;
+
+/// A value in the fixture's form
+fn tagged(value: &Value) -> serde_json::Value {
+    match value {
+        Value::Int(n) => json!(n),
+        Value::Number(n) => json!(n),
+        Value::Decimal(d) => json!({"$decimal": d.to_string()}),
+        Value::String(s) => json!(s.as_ref()),
+        Value::Boolean(b) => json!(b),
+        Value::Nil => serde_json::Value::Null,
+        Value::Array(items) => items.iter().map(tagged).collect(),
+        Value::Dictionary(map) => map
+            .iter()
+            .map(|(key, value)| (key.clone(), tagged(value)))
+            .collect(),
+        Value::Symbol(s) => json!({"$symbol": s}),
+        Value::Duration(d) => json!({"$duration": d.to_string()}),
+        Value::Function(_) => panic!("a function is not a result"),
+    }
+}
+
+#[test]
+fn test_interpreter_conformance() {
+    let fixture = conformance::load();
+    let symbols: Vec<&str> = fixture.symbols.iter().map(String::as_str).collect();
+    let data = JsonOptions::default()
+        .data_from_json(&fixture.data)
+        .unwrap();
+
+    let cases: Vec<&conformance::Case> = fixture
+        .cases
+        .iter()
+        .filter(|case| case.runs_on("interpreter"))
+        .collect();
+    let results: Vec<serde_json::Value> = cases
+        .iter()
+        .map(|case| {
+            let program = amoskeag::compile(&case.expression, &symbols)
+                .unwrap_or_else(|e| panic!("{} does not compile: {}", case.expression, e));
+            match amoskeag::evaluate(&program, &data) {
+                Ok(value) => tagged(&value),
+                Err(e) => json!({"$error": e.to_string()}),
+            }
+        })
+        .collect();
+    conformance::check("the interpreter", &cases, &results);
+}
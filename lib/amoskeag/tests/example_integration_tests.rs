@@ -27,7 +27,7 @@ where
     I: IntoIterator<Item = (&'static str, Value)>,
 {
     let map: HashMap<String, Value> = items.into_iter().map(|(k, v)| (k.to_string(), v)).collect();
-    Value::Dictionary(map)
+    Value::Dictionary(map.into())
 }
 
 #[test]
@@ -37,7 +37,7 @@ fn test_01_hello_world() {
     let data = HashMap::new();
     let result = evaluate(&program, &data).expect("Evaluation failed");
 
-    assert_eq!(result, Value::String("Hello, World!".to_string()));
+    assert_eq!(result, Value::String("Hello, World!".into()));
 }
 
 #[test]
@@ -57,7 +57,7 @@ fn test_03_variables() {
     let data = HashMap::new();
     let result = evaluate(&program, &data).expect("Evaluation failed");
 
-    assert_eq!(result, Value::String("Hello, Alice!".to_string()));
+    assert_eq!(result, Value::String("Hello, Alice!".into()));
 }
 
 #[test]
@@ -67,7 +67,7 @@ fn test_04_conditionals() {
     let data = HashMap::new();
     let result = evaluate(&program, &data).expect("Evaluation failed");
 
-    assert_eq!(result, Value::String("Student".to_string()));
+    assert_eq!(result, Value::String("Student".into()));
 }
 
 #[test]
@@ -77,10 +77,7 @@ fn test_05_string_operations() {
     let data = HashMap::new();
     let result = evaluate(&program, &data).expect("Evaluation failed");
 
-    assert_eq!(
-        result,
-        Value::String("Hello, amoskeag language!".to_string())
-    );
+    assert_eq!(result, Value::String("Hello, amoskeag language!".into()));
 }
 
 #[test]
@@ -110,7 +107,7 @@ fn test_07_dictionary_access() {
 
     assert_eq!(
         result,
-        Value::String("Bob Smith (bob@example.com) lives in Boston".to_string())
+        Value::String("Bob Smith (bob@example.com) lives in Boston".into())
     );
 }
 
@@ -180,12 +177,12 @@ fn test_10_business_rule_complex() {
     // Build the data context
     let applicant_vehicle = dict([
         ("value", Value::Number(75000.0)),
-        ("type", Value::String("SPORT".to_string())),
+        ("type", Value::String("SPORT".into())),
     ]);
 
     let applicant = dict([
         ("age", Value::Number(30.0)),
-        ("state", Value::String("CA".to_string())),
+        ("state", Value::String("CA".into())),
         ("claims_last_3_years", Value::Number(1.0)),
         ("vehicle", applicant_vehicle),
     ]);
@@ -194,10 +191,7 @@ fn test_10_business_rule_complex() {
         ("max_vehicle_value", Value::Number(100000.0)),
         (
             "restricted_states",
-            Value::Array(vec![
-                Value::String("FL".to_string()),
-                Value::String("LA".to_string()),
-            ]),
+            Value::Array(vec![Value::String("FL".into()), Value::String("LA".into())].into()),
         ),
     ]);
 
@@ -219,9 +213,9 @@ fn test_11_template_greeting() {
 
     // Build the data context
     let user = dict([
-        ("name", Value::String("Alice Johnson".to_string())),
+        ("name", Value::String("Alice Johnson".into())),
         ("is_admin", Value::Boolean(false)),
-        ("last_login", Value::String("2025-01-15".to_string())),
+        ("last_login", Value::String("2025-01-15".into())),
     ]);
 
     let mut data = HashMap::new();
@@ -229,10 +223,7 @@ fn test_11_template_greeting() {
 
     let result = evaluate(&program, &data).expect("Evaluation failed");
 
-    assert_eq!(
-        result,
-        Value::String("Welcome back, Alice Johnson!".to_string())
-    );
+    assert_eq!(result, Value::String("Welcome back, Alice Johnson!".into()));
 }
 
 #[test]
@@ -242,16 +233,16 @@ fn test_12_template_blog_post() {
 
     // Build the data context
     let author = dict([
-        ("name", Value::String("Jane Developer".to_string())),
+        ("name", Value::String("Jane Developer".into())),
         ("is_verified", Value::Boolean(true)),
     ]);
 
     let post = dict([
         (
             "title",
-            Value::String("Introduction to Amoskeag Programming".to_string()),
+            Value::String("Introduction to Amoskeag Programming".into()),
         ),
-        ("status", Value::String("published".to_string())),
+        ("status", Value::String("published".into())),
         ("author", author),
     ]);
 
@@ -263,7 +254,9 @@ fn test_12_template_blog_post() {
     assert_eq!(
         result,
         Value::String(
-            "[LIVE] INTRODUCTION TO AMOSKEAG PROGRAMMING by Jane Developer ✓".to_string()
+            "[LIVE] INTRODUCTION TO AMOSKEAG PROGRAMMING by Jane Developer ✓"
+                .to_string()
+                .into()
         )
     );
 }
@@ -278,11 +271,14 @@ fn test_13_spreadsheet_formulas() {
     data.insert("B1".to_string(), Value::Number(2.0));
     data.insert(
         "B2".to_string(),
-        Value::Array(vec![
-            Value::Number(0.10),
-            Value::Number(0.15),
-            Value::Number(0.20),
-        ]),
+        Value::Array(
+            vec![
+                Value::Number(0.10),
+                Value::Number(0.15),
+                Value::Number(0.20),
+            ]
+            .into(),
+        ),
     );
     data.insert("B3".to_string(), Value::Number(1000.0));
 
@@ -319,7 +315,7 @@ fn test_15_pipe_chaining() {
 
     assert_eq!(
         result,
-        Value::String("Hello, world this is Amoskeag.".to_string())
+        Value::String("Hello, world this is Amoskeag.".into())
     );
 }
 
@@ -371,7 +367,9 @@ fn test_18_nested_data() {
     assert_eq!(
         result,
         Value::String(
-            "TechCorp - Boston, MA (42.36, -71.06) - Contact: alice@techcorp.com".to_string()
+            "TechCorp - Boston, MA (42.36, -71.06) - Contact: alice@techcorp.com"
+                .to_string()
+                .into()
         )
     );
 }
@@ -402,19 +400,19 @@ fn test_20_string_formatting() {
     let result = evaluate(&program, &data).expect("Evaluation failed");
 
     let expected = dict([
-        ("display_name", Value::String("John Doe".to_string())),
-        ("email", Value::String("john.doe@company.com".to_string())),
+        ("display_name", Value::String("John Doe".into())),
+        ("email", Value::String("john.doe@company.com".into())),
         (
             "bio_clean",
-            Value::String("Software engineer with 10 years experience.".to_string()),
+            Value::String("Software engineer with 10 years experience.".into()),
         ),
         (
             "bio_short",
-            Value::String("Software engineer with 10 year".to_string()),
+            Value::String("Software engineer with 10 year".into()),
         ), // truncate(30) cuts at 30 chars, no "..."
         (
             "tags_joined",
-            Value::String("rust, typescript, python".to_string()),
+            Value::String("rust, typescript, python".into()),
         ),
         ("tag_count", Value::Number(3.0)),
     ]);
@@ -447,18 +445,18 @@ fn test_21_number_rounding() {
 fn test_22_date_operations() {
     let source = load_example("22_date_operations");
     let program = compile(&source, &[]).expect("Compilation failed");
-    let data = HashMap::new();
-    let result = evaluate(&program, &data).expect("Evaluation failed");
+    let context = Context::new(HashMap::new()).with_clock(Clock::Fixed("2025-01-18".to_string()));
+    let result = eval_expr(program.ast(), &context).expect("Evaluation failed");
 
     let expected = dict([
-        ("created_at", Value::String("2025-01-18".to_string())),
+        ("created_at", Value::String("2025-01-18".into())),
         (
             "created_at_full",
-            Value::String("2025-01-18 14:30:00".to_string()),
+            Value::String("2025-01-18 14:30:00".into()),
         ),
         (
             "display",
-            Value::String("Account created on 2025-01-18".to_string()),
+            Value::String("Account created on 2025-01-18".into()),
         ),
     ]);
 
@@ -622,7 +620,7 @@ fn test_edge_case_empty_dictionary() {
     let program = compile(&source, &[]).expect("Compilation failed");
     let data = HashMap::new();
     let result = evaluate(&program, &data).expect("Evaluation failed");
-    assert_eq!(result, Value::Dictionary(HashMap::new()));
+    assert_eq!(result, Value::Dictionary(HashMap::new().into()));
 }
 
 #[test]
@@ -631,7 +629,7 @@ fn test_string_concatenation_with_numbers() {
     let program = compile(&source, &[]).expect("Compilation failed");
     let data = HashMap::new();
     let result = evaluate(&program, &data).expect("Evaluation failed");
-    assert_eq!(result, Value::String("Price: 42.5".to_string()));
+    assert_eq!(result, Value::String("Price: 42.5".into()));
 }
 
 #[test]
@@ -640,7 +638,7 @@ fn test_capitalize_function() {
     let program = compile(&source, &[]).expect("Compilation failed");
     let data = HashMap::new();
     let result = evaluate(&program, &data).expect("Evaluation failed");
-    assert_eq!(result, Value::String("Hello world".to_string()));
+    assert_eq!(result, Value::String("Hello world".into()));
 }
 
 // Regression test: variable containing dictionary should return the dictionary, not Boolean(true)
@@ -653,12 +651,15 @@ fn test_variable_returns_dictionary_value() {
     let mut inner_dict = HashMap::new();
     inner_dict.insert("a".to_string(), Value::Number(1.0));
     inner_dict.insert("b".to_string(), Value::Number(2.0));
-    data.insert("x".to_string(), Value::Dictionary(inner_dict.clone()));
+    data.insert(
+        "x".to_string(),
+        Value::Dictionary(inner_dict.clone().into()),
+    );
 
     let result = evaluate(&program, &data).expect("Evaluation failed");
 
     // This should return the dictionary, not Boolean(true)
-    assert_eq!(result, Value::Dictionary(inner_dict));
+    assert_eq!(result, Value::Dictionary(inner_dict.into()));
 }
 
 // Additional test: variable containing array should return the array
@@ -669,11 +670,11 @@ fn test_variable_returns_array_value() {
 
     let mut data = HashMap::new();
     let array = vec![Value::Number(1.0), Value::Number(2.0), Value::Number(3.0)];
-    data.insert("arr".to_string(), Value::Array(array.clone()));
+    data.insert("arr".to_string(), Value::Array(array.clone().into()));
 
     let result = evaluate(&program, &data).expect("Evaluation failed");
 
-    assert_eq!(result, Value::Array(array));
+    assert_eq!(result, Value::Array(array.into()));
 }
 
 // Test: nested dictionary access should work correctly
@@ -683,22 +684,22 @@ fn test_nested_dictionary_access() {
     let program = compile(&source, &[]).expect("Compilation failed");
 
     let mut address = HashMap::new();
-    address.insert(
-        "street".to_string(),
-        Value::String("123 Main St".to_string()),
-    );
-    address.insert("city".to_string(), Value::String("Boston".to_string()));
+    address.insert("street".to_string(), Value::String("123 Main St".into()));
+    address.insert("city".to_string(), Value::String("Boston".into()));
 
     let mut user = HashMap::new();
-    user.insert("address".to_string(), Value::Dictionary(address.clone()));
-    user.insert("name".to_string(), Value::String("Alice".to_string()));
+    user.insert(
+        "address".to_string(),
+        Value::Dictionary(address.clone().into()),
+    );
+    user.insert("name".to_string(), Value::String("Alice".into()));
 
     let mut data = HashMap::new();
-    data.insert("user".to_string(), Value::Dictionary(user));
+    data.insert("user".to_string(), Value::Dictionary(user.into()));
 
     let result = evaluate(&program, &data).expect("Evaluation failed");
 
-    assert_eq!(result, Value::Dictionary(address));
+    assert_eq!(result, Value::Dictionary(address.into()));
 }
 
 // Test: variable containing empty dictionary should return empty dictionary
@@ -709,11 +710,14 @@ fn test_variable_returns_empty_dictionary() {
 
     let mut data = HashMap::new();
     let empty_dict = HashMap::new();
-    data.insert("empty".to_string(), Value::Dictionary(empty_dict.clone()));
+    data.insert(
+        "empty".to_string(),
+        Value::Dictionary(empty_dict.clone().into()),
+    );
 
     let result = evaluate(&program, &data).expect("Evaluation failed");
 
-    assert_eq!(result, Value::Dictionary(empty_dict));
+    assert_eq!(result, Value::Dictionary(empty_dict.into()));
 }
 
 // Test: variable containing dictionary with mixed types
@@ -725,21 +729,21 @@ fn test_variable_returns_complex_dictionary() {
     let mut config = HashMap::new();
     config.insert("enabled".to_string(), Value::Boolean(true));
     config.insert("count".to_string(), Value::Number(42.0));
-    config.insert("name".to_string(), Value::String("test".to_string()));
+    config.insert("name".to_string(), Value::String("test".into()));
     config.insert(
         "tags".to_string(),
-        Value::Array(vec![
-            Value::String("a".to_string()),
-            Value::String("b".to_string()),
-        ]),
+        Value::Array(vec![Value::String("a".into()), Value::String("b".into())].into()),
     );
 
     let mut data = HashMap::new();
-    data.insert("config".to_string(), Value::Dictionary(config.clone()));
+    data.insert(
+        "config".to_string(),
+        Value::Dictionary(config.clone().into()),
+    );
 
     let result = evaluate(&program, &data).expect("Evaluation failed");
 
-    assert_eq!(result, Value::Dictionary(config));
+    assert_eq!(result, Value::Dictionary(config.into()));
 }
 
 // Test: variable containing symbol should return the symbol
@@ -763,11 +767,11 @@ fn test_variable_returns_string_value() {
     let program = compile(&source, &[]).expect("Compilation failed");
 
     let mut data = HashMap::new();
-    data.insert("message".to_string(), Value::String("Hello".to_string()));
+    data.insert("message".to_string(), Value::String("Hello".into()));
 
     let result = evaluate(&program, &data).expect("Evaluation failed");
 
-    assert_eq!(result, Value::String("Hello".to_string()));
+    assert_eq!(result, Value::String("Hello".into()));
 }
 
 // Test: variable containing number should return the number
@@ -834,7 +838,7 @@ fn test_date_trunc_iso8601() {
     let data = HashMap::new();
     let result = evaluate(&program, &data).expect("Evaluation failed");
 
-    assert_eq!(result, Value::String("2025-01-18".to_string()));
+    assert_eq!(result, Value::String("2025-01-18".into()));
 }
 
 // Test: date_trunc function with space-separated datetime
@@ -845,7 +849,7 @@ fn test_date_trunc_space_separated() {
     let data = HashMap::new();
     let result = evaluate(&program, &data).expect("Evaluation failed");
 
-    assert_eq!(result, Value::String("2025-01-18".to_string()));
+    assert_eq!(result, Value::String("2025-01-18".into()));
 }
 
 // Test: date_trunc function with date only (already truncated)
@@ -856,7 +860,7 @@ fn test_date_trunc_already_truncated() {
     let data = HashMap::new();
     let result = evaluate(&program, &data).expect("Evaluation failed");
 
-    assert_eq!(result, Value::String("2025-01-18".to_string()));
+    assert_eq!(result, Value::String("2025-01-18".into()));
 }
 
 // Test: date_trunc with invalid format
@@ -878,7 +882,7 @@ fn test_date_parse_valid() {
     let data = HashMap::new();
     let result = evaluate(&program, &data).expect("Evaluation failed");
 
-    assert_eq!(result, Value::String("2025-01-18".to_string()));
+    assert_eq!(result, Value::String("2025-01-18".into()));
 }
 
 // Test: date_parse with invalid format
@@ -922,5 +926,5 @@ fn test_date_trunc_then_parse() {
     let data = HashMap::new();
     let result = evaluate(&program, &data).expect("Evaluation failed");
 
-    assert_eq!(result, Value::String("2025-01-18".to_string()));
+    assert_eq!(result, Value::String("2025-01-18".into()));
 }